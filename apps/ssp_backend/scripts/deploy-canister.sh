@@ -0,0 +1,92 @@
+#!/bin/bash
+
+set -e
+
+# parse arguments
+while [[ $# -gt 0 ]]; do
+  case $1 in
+    --issuer)
+      ID_TOKEN_ISSUER_BASE_URL="$2"
+      shift # past argument
+      shift # past value
+      ;;
+    --audience)
+      ID_TOKEN_AUDIENCE="$2"
+      shift # past argument
+      shift # past value
+      ;;
+    --env-file)
+      ENV_FILE_PATH="$2"
+      shift # past argument
+      shift # past value
+      ;;
+    --ignore-env-file)
+      ENV_FILE_PATH=""
+      shift # past argument
+      ;;
+  esac
+done
+
+# load environment variables from .env
+if [[ ! -z "$ENV_FILE_PATH" ]]; then
+  echo -e "\nLoading environment variables from $ENV_FILE_PATH file...\n"
+  source $ENV_FILE_PATH
+fi
+
+if [[ -z "$ID_TOKEN_ISSUER_BASE_URL" ]]; then
+  echo -e "\nError: ID_TOKEN_ISSUER_BASE_URL is not set\n"
+  exit 1
+fi
+
+if [[ -z "$ID_TOKEN_AUDIENCE" ]]; then
+  echo -e "\nError: ID_TOKEN_AUDIENCE is not set\n"
+  exit 1
+fi
+
+ID_TOKEN_JWKS_URL="${ID_TOKEN_JWKS_URL:-${ID_TOKEN_ISSUER_BASE_URL}.well-known/jwks.json}"
+ID_TOKEN_CLAIMS_NAMESPACE="${ID_TOKEN_CLAIMS_NAMESPACE:-https://hasura.io/jwt/claims}"
+
+# the OIDC providers are passed as init argument, both on install and on upgrade,
+# and replace the providers configured at runtime
+echo -e "\nDeploying canister..."
+echo -e "JWT Issuer: $ID_TOKEN_ISSUER_BASE_URL\nJWT Audience: $ID_TOKEN_AUDIENCE\nJWKS URL: $ID_TOKEN_JWKS_URL\nClaims namespace: $ID_TOKEN_CLAIMS_NAMESPACE\n"
+
+OIDC_PROVIDERS="record {
+    issuer = \"$ID_TOKEN_ISSUER_BASE_URL\";
+    audiences = vec { \"$ID_TOKEN_AUDIENCE\" };
+    jwks_url = \"$ID_TOKEN_JWKS_URL\";
+    claims_namespace = opt \"$ID_TOKEN_CLAIMS_NAMESPACE\";
+  };"
+
+if [[ ! -z "$GOOGLE_CLIENT_ID" ]]; then
+  echo -e "Google client ID: $GOOGLE_CLIENT_ID"
+  OIDC_PROVIDERS="$OIDC_PROVIDERS
+  record {
+    issuer = \"https://accounts.google.com\";
+    audiences = vec { \"$GOOGLE_CLIENT_ID\" };
+    jwks_url = \"https://www.googleapis.com/oauth2/v3/certs\";
+    claims_namespace = null;
+  };"
+fi
+
+if [[ ! -z "$APPLE_CLIENT_ID" ]]; then
+  echo -e "Apple client ID: $APPLE_CLIENT_ID"
+  OIDC_PROVIDERS="$OIDC_PROVIDERS
+  record {
+    issuer = \"https://appleid.apple.com\";
+    audiences = vec { \"$APPLE_CLIENT_ID\" };
+    jwks_url = \"https://appleid.apple.com/auth/keys\";
+    claims_namespace = null;
+  };"
+fi
+
+INIT_ARGS="(opt record {
+  oidc_providers = opt vec {
+  $OIDC_PROVIDERS
+  };
+  legacy_oidc_issuer = opt \"$ID_TOKEN_ISSUER_BASE_URL\";
+})"
+
+dfx deploy --network="${DFX_NETWORK:-local}" --yes ssp_backend --argument "$INIT_ARGS"
+
+echo -e "\nDone!\n"
@@ -0,0 +1,64 @@
+use candid::Principal;
+use ic_cdk::{caller, query, update};
+use ssp_backend_types::{
+    Achievement, ApiError, ApiResult, CreateAchievementRequest, CreateAchievementResponse,
+};
+
+use crate::{
+    repositories::{AchievementId, AuditLogOperation},
+    services::{AccessControlService, AchievementService, AuditLogService},
+};
+
+#[update]
+async fn create_achievement(req: CreateAchievementRequest) -> ApiResult<CreateAchievementResponse> {
+    let calling_principal = caller();
+
+    AchievementController::default()
+        .create_achievement(calling_principal, req)
+        .await
+        .into()
+}
+
+#[query]
+fn get_achievement(id: String) -> ApiResult<Achievement> {
+    AchievementController::default().get_achievement(id).into()
+}
+
+#[derive(Default)]
+pub struct AchievementController {
+    access_control_service: AccessControlService,
+    achievement_service: AchievementService,
+    audit_log_service: AuditLogService,
+}
+
+impl AchievementController {
+    async fn create_achievement(
+        &self,
+        calling_principal: Principal,
+        req: CreateAchievementRequest,
+    ) -> Result<CreateAchievementResponse, ApiError> {
+        // users can only create achievements for the issuers they are admins of
+        let only_issuer_admin = self
+            .access_control_service
+            .assert_principal_is_user_or_backend(&calling_principal)?;
+
+        let res = self
+            .achievement_service
+            .create_achievement(req.clone(), only_issuer_admin.cloned())
+            .await?;
+
+        self.audit_log_service.record(
+            calling_principal,
+            AuditLogOperation::CreateAchievement {
+                id: AchievementId::try_from(res.id.as_str())?,
+            },
+            &req,
+        );
+
+        Ok(res)
+    }
+
+    fn get_achievement(&self, id: String) -> Result<Achievement, ApiError> {
+        self.achievement_service.get_achievement(id)
+    }
+}
@@ -0,0 +1,33 @@
+use candid::Principal;
+use ic_cdk::{caller, query};
+use ssp_backend_types::{ApiError, ApiResult, GetAuditLogRequest, GetAuditLogResponse};
+
+use crate::services::{AccessControlService, AuditLogService};
+
+#[query]
+fn get_audit_log(req: GetAuditLogRequest) -> ApiResult<GetAuditLogResponse> {
+    let calling_principal = caller();
+
+    AuditLogController::default()
+        .get_audit_log(calling_principal, req)
+        .into()
+}
+
+#[derive(Default)]
+struct AuditLogController {
+    access_control_service: AccessControlService,
+    audit_log_service: AuditLogService,
+}
+
+impl AuditLogController {
+    fn get_audit_log(
+        &self,
+        calling_principal: Principal,
+        req: GetAuditLogRequest,
+    ) -> Result<GetAuditLogResponse, ApiError> {
+        self.access_control_service
+            .assert_principal_is_controller(&calling_principal)?;
+
+        self.audit_log_service.get_audit_log(req)
+    }
+}
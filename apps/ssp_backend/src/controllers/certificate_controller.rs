@@ -1,38 +1,137 @@
 use candid::Principal;
 use ic_cdk::{caller, query, update};
 use ssp_backend_types::{
-    CreateCertificateRequest, CreateCertificateResponse, GetCertificateResponse,
-    GetUserCertificatesRequest, GetUserCertificatesResponse,
+    ApiError, ApiResult, CreateCertificateRequest, CreateCertificateResponse,
+    CreateCertificateShareTokenResponse, GetCertificateAsVcResponse, GetCertificateResponse,
+    GetManagedUserCertificatesRequest, GetMyCertificatesRequest, GetUserCertificatesRequest,
+    GetUserCertificatesResponse, RevokeCertificateRequest, SetCertificatePublicRequest,
+    UpdateCertificateRequest, UpdateCertificateResponse, VerifyCertificateRequest,
 };
 
-use crate::services::{AccessControlService, CertificateService};
+use crate::{
+    repositories::{AuditLogOperation, CertificateId},
+    services::{AccessControlService, AuditLogService, CertificateService, Permission},
+};
 
 #[update]
-async fn create_certificate(req: CreateCertificateRequest) -> CreateCertificateResponse {
+async fn create_certificate(req: CreateCertificateRequest) -> ApiResult<CreateCertificateResponse> {
     let calling_principal = caller();
 
     CertificateController::default()
         .create_certificate(calling_principal, req)
         .await
+        .into()
+}
+
+#[query]
+fn get_user_certificates(
+    req: GetUserCertificatesRequest,
+) -> ApiResult<GetUserCertificatesResponse> {
+    let calling_principal = caller();
+
+    CertificateController::default()
+        .get_user_certificates(calling_principal, req)
+        .into()
+}
+
+#[query]
+fn get_my_certificates(req: GetMyCertificatesRequest) -> ApiResult<GetUserCertificatesResponse> {
+    let calling_principal = caller();
+
+    CertificateController::default()
+        .get_my_certificates(calling_principal, req)
+        .into()
+}
+
+#[query]
+fn get_managed_user_certificates(
+    req: GetManagedUserCertificatesRequest,
+) -> ApiResult<GetUserCertificatesResponse> {
+    let calling_principal = caller();
+
+    CertificateController::default()
+        .get_managed_user_certificates(calling_principal, req)
+        .into()
 }
 
 #[query]
-fn get_user_certificates(req: GetUserCertificatesRequest) -> GetUserCertificatesResponse {
+fn get_certificate(id: String, version: Option<u32>) -> ApiResult<GetCertificateResponse> {
     let calling_principal = caller();
 
-    CertificateController::default().get_user_certificates(calling_principal, req)
+    CertificateController::default()
+        .get_certificate(calling_principal, id, version)
+        .into()
 }
 
 #[query]
-fn get_certificate(id: String) -> GetCertificateResponse {
+fn get_certificate_as_vc(id: String) -> ApiResult<GetCertificateAsVcResponse> {
     let calling_principal = caller();
 
-    CertificateController::default().get_certificate(calling_principal, id)
+    CertificateController::default()
+        .get_certificate_as_vc(calling_principal, id)
+        .into()
+}
+
+#[update]
+fn update_certificate(req: UpdateCertificateRequest) -> ApiResult<UpdateCertificateResponse> {
+    let calling_principal = caller();
+
+    CertificateController::default()
+        .update_certificate(calling_principal, req)
+        .into()
+}
+
+#[update]
+fn revoke_certificate(req: RevokeCertificateRequest) -> ApiResult<()> {
+    let calling_principal = caller();
+
+    CertificateController::default()
+        .revoke_certificate(calling_principal, req)
+        .into()
+}
+
+#[update]
+fn set_certificate_public(req: SetCertificatePublicRequest) -> ApiResult<()> {
+    let calling_principal = caller();
+
+    CertificateController::default()
+        .set_certificate_public(calling_principal, req)
+        .into()
+}
+
+#[update]
+async fn create_certificate_share_token(
+    id: String,
+) -> ApiResult<CreateCertificateShareTokenResponse> {
+    let calling_principal = caller();
+
+    CertificateController::default()
+        .create_certificate_share_token(calling_principal, id)
+        .await
+        .into()
+}
+
+#[update]
+fn revoke_certificate_share_token(id: String) -> ApiResult<()> {
+    let calling_principal = caller();
+
+    CertificateController::default()
+        .revoke_certificate_share_token(calling_principal, id)
+        .into()
+}
+
+/// Can be called by anyone, including the anonymous principal.
+#[query]
+fn verify_certificate(req: VerifyCertificateRequest) -> ApiResult<GetCertificateResponse> {
+    CertificateController::default()
+        .verify_certificate(req)
+        .into()
 }
 
 #[derive(Default)]
 struct CertificateController {
     access_control_service: AccessControlService,
+    audit_log_service: AuditLogService,
     certificate_service: CertificateService,
 }
 
@@ -41,42 +140,243 @@ impl CertificateController {
         &self,
         calling_principal: Principal,
         req: CreateCertificateRequest,
-    ) -> CreateCertificateResponse {
+    ) -> Result<CreateCertificateResponse, ApiError> {
+        // coaches and club admins issue the certificate to the user in the request
         let calling_user_principal = self
             .access_control_service
-            .assert_principal_is_user_or_backend(&calling_principal)
-            .unwrap();
+            .assert_principal_is_user_or_backend_with_permission(
+                &calling_principal,
+                Permission::IssueCertificatesToOthers,
+            )?;
+        // the certificates issued directly by users record who issued them
+        let issuer_principal = match calling_user_principal {
+            Some(_) => None,
+            None => self
+                .access_control_service
+                .principal_has_permission(&calling_principal, Permission::IssueCertificatesToOthers)
+                .then_some(calling_principal),
+        };
 
-        self.certificate_service
-            .create_certificate(req, calling_user_principal.cloned())
-            .await
-            .unwrap()
+        let res = self
+            .certificate_service
+            .create_certificate(
+                req.clone(),
+                calling_user_principal.cloned(),
+                issuer_principal,
+            )
+            .await?;
+
+        let id = CertificateId::try_from(res.id.as_str())?;
+        self.audit_log_service.record(
+            calling_principal,
+            AuditLogOperation::CreateCertificate { id },
+            &req,
+        );
+
+        Ok(res)
     }
 
     fn get_user_certificates(
         &self,
         calling_principal: Principal,
         req: GetUserCertificatesRequest,
-    ) -> GetUserCertificatesResponse {
-        // if the calling principal is the backend, all certificates are visible
+    ) -> Result<GetUserCertificatesResponse, ApiError> {
+        // if the calling principal is the backend or an admin, all certificates are visible
         let only_user_principal = self
             .access_control_service
-            .assert_principal_is_user_or_backend(&calling_principal)
-            .unwrap();
+            .assert_principal_is_user_or_backend_with_permission(
+                &calling_principal,
+                Permission::ReadAllCertificates,
+            )?;
 
         self.certificate_service
             .get_user_certificates(req, only_user_principal.cloned())
-            .unwrap()
     }
 
-    fn get_certificate(&self, calling_principal: Principal, id: String) -> GetCertificateResponse {
+    fn get_my_certificates(
+        &self,
+        calling_principal: Principal,
+        req: GetMyCertificatesRequest,
+    ) -> Result<GetUserCertificatesResponse, ApiError> {
+        self.access_control_service
+            .assert_principal_is_user(&calling_principal)?;
+
+        self.certificate_service
+            .get_my_certificates(req, calling_principal)
+    }
+
+    fn get_managed_user_certificates(
+        &self,
+        calling_principal: Principal,
+        req: GetManagedUserCertificatesRequest,
+    ) -> Result<GetUserCertificatesResponse, ApiError> {
+        // users can only access the certificates of the managed users they are guardians of
+        let only_guardian = self
+            .access_control_service
+            .assert_principal_is_user_or_backend_with_permission(
+                &calling_principal,
+                Permission::ReadAllCertificates,
+            )?;
+
+        self.certificate_service
+            .get_managed_user_certificates(req, only_guardian.cloned())
+    }
+
+    fn get_certificate(
+        &self,
+        calling_principal: Principal,
+        id: String,
+        version: Option<u32>,
+    ) -> Result<GetCertificateResponse, ApiError> {
+        let only_user_principal = self
+            .access_control_service
+            .assert_principal_is_user_or_backend_with_permission(
+                &calling_principal,
+                Permission::ReadAllCertificates,
+            )?;
+
+        self.certificate_service
+            .get_certificate(id, version, only_user_principal.cloned())
+    }
+
+    fn get_certificate_as_vc(
+        &self,
+        calling_principal: Principal,
+        id: String,
+    ) -> Result<GetCertificateAsVcResponse, ApiError> {
+        let only_user_principal = self
+            .access_control_service
+            .assert_principal_is_user_or_backend_with_permission(
+                &calling_principal,
+                Permission::ReadAllCertificates,
+            )?;
+
+        self.certificate_service
+            .get_certificate_as_vc(id, only_user_principal.cloned())
+    }
+
+    fn update_certificate(
+        &self,
+        calling_principal: Principal,
+        req: UpdateCertificateRequest,
+    ) -> Result<UpdateCertificateResponse, ApiError> {
+        let only_user_principal = self
+            .access_control_service
+            .assert_principal_is_user_or_backend(&calling_principal)?;
+
+        let res = self
+            .certificate_service
+            .update_certificate(req.clone(), only_user_principal.cloned())?;
+
+        self.audit_log_service.record(
+            calling_principal,
+            AuditLogOperation::UpdateCertificate {
+                id: CertificateId::try_from(req.id.as_str())?,
+            },
+            &req,
+        );
+
+        Ok(res)
+    }
+
+    fn revoke_certificate(
+        &self,
+        calling_principal: Principal,
+        req: RevokeCertificateRequest,
+    ) -> Result<(), ApiError> {
+        let only_user_principal = self
+            .access_control_service
+            .assert_principal_is_user_or_backend(&calling_principal)?;
+
+        self.certificate_service
+            .revoke_certificate(req.clone(), only_user_principal.cloned())?;
+
+        self.audit_log_service.record(
+            calling_principal,
+            AuditLogOperation::RevokeCertificate {
+                id: CertificateId::try_from(req.id.as_str())?,
+            },
+            &req,
+        );
+
+        Ok(())
+    }
+
+    fn set_certificate_public(
+        &self,
+        calling_principal: Principal,
+        req: SetCertificatePublicRequest,
+    ) -> Result<(), ApiError> {
         let only_user_principal = self
             .access_control_service
-            .assert_principal_is_user_or_backend(&calling_principal)
-            .unwrap();
+            .assert_principal_is_user_or_backend(&calling_principal)?;
 
         self.certificate_service
-            .get_certificate(id, only_user_principal.cloned())
-            .unwrap()
+            .set_certificate_public(req.clone(), only_user_principal.cloned())?;
+
+        self.audit_log_service.record(
+            calling_principal,
+            AuditLogOperation::SetCertificatePublic {
+                id: CertificateId::try_from(req.id.as_str())?,
+            },
+            &req,
+        );
+
+        Ok(())
+    }
+
+    async fn create_certificate_share_token(
+        &self,
+        calling_principal: Principal,
+        id: String,
+    ) -> Result<CreateCertificateShareTokenResponse, ApiError> {
+        let only_user_principal = self
+            .access_control_service
+            .assert_principal_is_user_or_backend(&calling_principal)?;
+
+        let res = self
+            .certificate_service
+            .create_certificate_share_token(id.clone(), only_user_principal.cloned())
+            .await?;
+
+        self.audit_log_service.record(
+            calling_principal,
+            AuditLogOperation::CreateCertificateShareToken {
+                id: CertificateId::try_from(id.as_str())?,
+            },
+            &id,
+        );
+
+        Ok(res)
+    }
+
+    fn revoke_certificate_share_token(
+        &self,
+        calling_principal: Principal,
+        id: String,
+    ) -> Result<(), ApiError> {
+        let only_user_principal = self
+            .access_control_service
+            .assert_principal_is_user_or_backend(&calling_principal)?;
+
+        self.certificate_service
+            .revoke_certificate_share_token(id.clone(), only_user_principal.cloned())?;
+
+        self.audit_log_service.record(
+            calling_principal,
+            AuditLogOperation::RevokeCertificateShareToken {
+                id: CertificateId::try_from(id.as_str())?,
+            },
+            &id,
+        );
+
+        Ok(())
+    }
+
+    fn verify_certificate(
+        &self,
+        req: VerifyCertificateRequest,
+    ) -> Result<GetCertificateResponse, ApiError> {
+        self.certificate_service.verify_certificate(req)
     }
 }
@@ -1,44 +1,183 @@
 use candid::Principal;
 use ic_cdk::{caller, query, update};
+use ssp_backend_types::{ApiError, ApiResult, DelegationConfig, OidcConfig};
 
-use crate::services::{AccessControlService, ConfigService};
+use crate::{
+    repositories::AuditLogOperation,
+    services::{AccessControlService, AuditLogService, ConfigService},
+};
+
+use super::init_controller::start_jwks_sync;
+
+#[update]
+fn set_backend_principal(principal: Principal) -> ApiResult<()> {
+    let calling_principal = caller();
+
+    ConfigController::default()
+        .set_backend_principal(calling_principal, principal)
+        .into()
+}
 
 #[update]
-fn set_backend_principal(principal: Principal) {
+fn set_delegation_config(delegation_config: DelegationConfig) -> ApiResult<()> {
     let calling_principal = caller();
 
-    ConfigController::default().set_backend_principal(calling_principal, principal);
+    ConfigController::default()
+        .set_delegation_config(calling_principal, delegation_config)
+        .into()
+}
+
+#[update]
+fn set_oidc_provider(oidc_config: OidcConfig) -> ApiResult<()> {
+    let calling_principal = caller();
+
+    ConfigController::default()
+        .set_oidc_provider(calling_principal, oidc_config)
+        .into()
+}
+
+#[update]
+fn remove_oidc_provider(issuer: String) -> ApiResult<()> {
+    let calling_principal = caller();
+
+    ConfigController::default()
+        .remove_oidc_provider(calling_principal, issuer)
+        .into()
 }
 
 #[query]
-fn get_config() -> ssp_backend_types::Config {
+fn get_oidc_providers() -> ApiResult<Vec<OidcConfig>> {
     let calling_principal = caller();
 
-    ConfigController::default().get_config(calling_principal)
+    ConfigController::default()
+        .get_oidc_providers(calling_principal)
+        .into()
+}
+
+#[query]
+fn get_config() -> ApiResult<ssp_backend_types::Config> {
+    let calling_principal = caller();
+
+    ConfigController::default()
+        .get_config(calling_principal)
+        .into()
 }
 
 #[derive(Default)]
 struct ConfigController {
     access_control_service: AccessControlService,
+    audit_log_service: AuditLogService,
     config_service: ConfigService,
 }
 
 impl ConfigController {
-    fn set_backend_principal(&self, calling_principal: Principal, principal: Principal) {
+    fn set_backend_principal(
+        &self,
+        calling_principal: Principal,
+        principal: Principal,
+    ) -> Result<(), ApiError> {
+        self.access_control_service
+            .assert_principal_is_controller(&calling_principal)?;
+
+        self.config_service.set_backend_principal(principal)?;
+
+        self.audit_log_service.record(
+            calling_principal,
+            AuditLogOperation::SetBackendPrincipal,
+            &principal,
+        );
+
+        Ok(())
+    }
+
+    fn set_delegation_config(
+        &self,
+        calling_principal: Principal,
+        delegation_config: DelegationConfig,
+    ) -> Result<(), ApiError> {
         self.access_control_service
-            .assert_principal_is_controller(&calling_principal)
-            .unwrap();
+            .assert_principal_is_controller(&calling_principal)?;
 
         self.config_service
-            .set_backend_principal(principal)
-            .unwrap()
+            .set_delegation_config(delegation_config.clone())?;
+
+        self.audit_log_service.record(
+            calling_principal,
+            AuditLogOperation::SetDelegationConfig,
+            &delegation_config,
+        );
+
+        Ok(())
+    }
+
+    fn set_oidc_provider(
+        &self,
+        calling_principal: Principal,
+        oidc_config: OidcConfig,
+    ) -> Result<(), ApiError> {
+        self.access_control_service
+            .assert_principal_is_controller(&calling_principal)?;
+
+        let issuer = oidc_config.issuer.clone();
+        self.config_service.set_oidc_provider(oidc_config.clone())?;
+
+        self.audit_log_service.record(
+            calling_principal,
+            AuditLogOperation::SetOidcProvider {
+                issuer: issuer.clone(),
+            },
+            &oidc_config,
+        );
+
+        // no-op if the JWKS of the provider is already being synced
+        start_jwks_sync(issuer);
+
+        Ok(())
+    }
+
+    fn remove_oidc_provider(
+        &self,
+        calling_principal: Principal,
+        issuer: String,
+    ) -> Result<(), ApiError> {
+        self.access_control_service
+            .assert_principal_is_controller(&calling_principal)?;
+
+        self.config_service.remove_oidc_provider(&issuer)?;
+
+        self.audit_log_service.record(
+            calling_principal,
+            AuditLogOperation::RemoveOidcProvider {
+                issuer: issuer.clone(),
+            },
+            &issuer,
+        );
+
+        Ok(())
+    }
+
+    fn get_oidc_providers(
+        &self,
+        calling_principal: Principal,
+    ) -> Result<Vec<OidcConfig>, ApiError> {
+        self.access_control_service
+            .assert_principal_is_controller(&calling_principal)?;
+
+        Ok(self
+            .config_service
+            .get_oidc_providers()
+            .into_iter()
+            .map(Into::into)
+            .collect())
     }
 
-    fn get_config(&self, calling_principal: Principal) -> ssp_backend_types::Config {
+    fn get_config(
+        &self,
+        calling_principal: Principal,
+    ) -> Result<ssp_backend_types::Config, ApiError> {
         self.access_control_service
-            .assert_principal_is_controller(&calling_principal)
-            .unwrap();
+            .assert_principal_is_controller(&calling_principal)?;
 
-        self.config_service.get_config().into()
+        Ok(self.config_service.get_config().into())
     }
 }
@@ -3,33 +3,72 @@ use ic_cdk::{
     api::management_canister::http_request::{HttpResponse, TransformArgs},
     caller, query, update,
 };
-use ssp_backend_types::{Auth0JWKSet, GetDelegationResponse, PrepareDelegationResponse, Timestamp};
+use ssp_backend_types::{
+    ApiError, ApiResult, Auth0JWKSet, DelegationStats, GetDelegationResponse,
+    PrepareDelegationResponse, SessionKey, Timestamp,
+};
+
+use crate::{
+    repositories::AuditLogOperation,
+    services::{AccessControlService, AuditLogService, DelegationService, ManagedUserService},
+};
 
-use crate::services::{AccessControlService, DelegationService};
+use super::init_controller::start_claimed_certificates_transfer;
 
 #[update]
-async fn prepare_delegation(jwt: String) -> PrepareDelegationResponse {
+async fn prepare_delegation(jwt: String) -> ApiResult<PrepareDelegationResponse> {
     let calling_principal = caller();
 
     DelegationController::default()
         .prepare_delegation(calling_principal, jwt)
         .await
+        .into()
 }
 
 #[query]
-fn get_delegation(jwt: String, expiration: Timestamp) -> GetDelegationResponse {
+fn get_delegation(jwt: String, expiration: Timestamp) -> ApiResult<GetDelegationResponse> {
+    let calling_principal = caller();
+
+    DelegationController::default()
+        .get_delegation(calling_principal, jwt, expiration)
+        .into()
+}
+
+#[update]
+fn revoke_session(session_key: SessionKey) -> ApiResult<()> {
     let calling_principal = caller();
 
-    DelegationController::default().get_delegation(calling_principal, jwt, expiration)
+    DelegationController::default()
+        .revoke_session(calling_principal, session_key)
+        .into()
 }
 
 #[update]
-async fn sync_jwks() {
+fn revoke_all_sessions() -> ApiResult<()> {
+    let calling_principal = caller();
+
+    DelegationController::default()
+        .revoke_all_sessions(calling_principal)
+        .into()
+}
+
+#[query]
+fn get_delegation_stats() -> ApiResult<DelegationStats> {
+    let calling_principal = caller();
+
+    DelegationController::default()
+        .get_delegation_stats(calling_principal)
+        .into()
+}
+
+#[update]
+async fn sync_jwks() -> ApiResult<()> {
     let calling_principal = caller();
 
     DelegationController::default()
         .sync_jwks(calling_principal)
         .await
+        .into()
 }
 
 #[query(hidden = true)]
@@ -39,24 +78,30 @@ fn transform_jwks_response(args: TransformArgs) -> HttpResponse {
 
 #[update]
 // used in tests
-fn set_jwks(jwks: Auth0JWKSet) {
+fn set_jwks(issuer: String, jwks: Auth0JWKSet) -> ApiResult<()> {
     let calling_principal = caller();
 
-    DelegationController::default().set_jwks(calling_principal, jwks);
+    DelegationController::default()
+        .set_jwks(calling_principal, issuer, jwks)
+        .into()
 }
 
 #[query]
 // used in tests
-fn get_jwks() -> Option<Auth0JWKSet> {
+fn get_jwks(issuer: String) -> ApiResult<Option<Auth0JWKSet>> {
     let calling_principal = caller();
 
-    DelegationController::default().get_jwks(calling_principal)
+    DelegationController::default()
+        .get_jwks(calling_principal, issuer)
+        .into()
 }
 
 #[derive(Default)]
 struct DelegationController {
     access_control_service: AccessControlService,
+    audit_log_service: AuditLogService,
     delegation_service: DelegationService,
+    managed_user_service: ManagedUserService,
 }
 
 impl DelegationController {
@@ -64,11 +109,18 @@ impl DelegationController {
         &self,
         calling_principal: Principal,
         jwt: String,
-    ) -> PrepareDelegationResponse {
-        self.delegation_service
+    ) -> Result<PrepareDelegationResponse, ApiError> {
+        let res = self
+            .delegation_service
             .prepare_delegation(calling_principal, jwt)
-            .await
-            .unwrap()
+            .await?;
+
+        // the certificates of a claimed managed user that were not transferred yet
+        if self.managed_user_service.has_managed_user_claims() {
+            start_claimed_certificates_transfer();
+        }
+
+        Ok(res)
     }
 
     fn get_delegation(
@@ -76,36 +128,91 @@ impl DelegationController {
         calling_principal: Principal,
         jwt: String,
         expiration: Timestamp,
-    ) -> GetDelegationResponse {
+    ) -> Result<GetDelegationResponse, ApiError> {
         self.delegation_service
             .get_delegation(calling_principal, jwt, expiration)
     }
 
-    async fn sync_jwks(&self, calling_principal: Principal) {
-        self.access_control_service
-            .assert_principal_is_controller(&calling_principal)
-            .unwrap();
+    fn revoke_session(
+        &self,
+        calling_principal: Principal,
+        session_key: SessionKey,
+    ) -> Result<(), ApiError> {
+        self.delegation_service
+            .revoke_session(calling_principal, session_key.clone())?;
+
+        self.audit_log_service.record(
+            calling_principal,
+            AuditLogOperation::RevokeSession,
+            &session_key,
+        );
+
+        Ok(())
+    }
 
+    fn revoke_all_sessions(&self, calling_principal: Principal) -> Result<(), ApiError> {
         self.delegation_service
-            .fetch_and_store_jwks()
-            .await
-            .unwrap()
+            .revoke_all_sessions(calling_principal)?;
+
+        self.audit_log_service
+            .record(calling_principal, AuditLogOperation::RevokeAllSessions, &());
+
+        Ok(())
+    }
+
+    fn get_delegation_stats(
+        &self,
+        calling_principal: Principal,
+    ) -> Result<DelegationStats, ApiError> {
+        self.access_control_service
+            .assert_principal_is_controller(&calling_principal)?;
+
+        Ok(self.delegation_service.get_delegation_stats())
     }
 
-    fn set_jwks(&self, calling_principal: Principal, jwks: Auth0JWKSet) {
+    async fn sync_jwks(&self, calling_principal: Principal) -> Result<(), ApiError> {
         self.access_control_service
-            .assert_principal_is_controller(&calling_principal)
-            .unwrap();
+            .assert_principal_is_controller(&calling_principal)?;
 
-        self.delegation_service.set_jwks(jwks)
+        // recorded even if some fetches failed, as the other providers' JWKS are stored anyway
+        let res = self.delegation_service.fetch_and_store_all_jwks().await;
+        self.audit_log_service
+            .record(calling_principal, AuditLogOperation::SyncJwks, &());
+
+        Ok(res?)
     }
 
-    fn get_jwks(&self, calling_principal: Principal) -> Option<Auth0JWKSet> {
+    fn set_jwks(
+        &self,
+        calling_principal: Principal,
+        issuer: String,
+        jwks: Auth0JWKSet,
+    ) -> Result<(), ApiError> {
+        self.access_control_service
+            .assert_principal_is_controller(&calling_principal)?;
+
+        self.delegation_service.set_jwks(&issuer, jwks.clone())?;
+
+        self.audit_log_service.record(
+            calling_principal,
+            AuditLogOperation::SetJwks {
+                issuer: issuer.clone(),
+            },
+            &(issuer, jwks),
+        );
+
+        Ok(())
+    }
+
+    fn get_jwks(
+        &self,
+        calling_principal: Principal,
+        issuer: String,
+    ) -> Result<Option<Auth0JWKSet>, ApiError> {
         self.access_control_service
-            .assert_principal_is_controller(&calling_principal)
-            .unwrap();
+            .assert_principal_is_controller(&calling_principal)?;
 
-        self.delegation_service.get_jwks()
+        self.delegation_service.get_jwks(&issuer)
     }
 
     fn transform_jwks_response(&self, args: TransformArgs) -> HttpResponse {
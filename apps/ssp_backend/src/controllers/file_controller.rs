@@ -0,0 +1,189 @@
+use candid::Principal;
+use ic_cdk::{caller, query, update};
+use ssp_backend_types::{
+    ApiError, ApiResult, CommitUploadResponse, CreateUploadRequest, CreateUploadResponse,
+    GetFileChunkRequest, GetFileChunkResponse, GetFileRequest, GetFileResponse, UploadChunkRequest,
+};
+
+use crate::{
+    repositories::{AuditLogOperation, FileHash, UploadId},
+    services::{
+        AccessControlService, AuditLogService, CertificateService, FileService, Permission,
+    },
+};
+
+#[update]
+async fn create_upload(req: CreateUploadRequest) -> ApiResult<CreateUploadResponse> {
+    let calling_principal = caller();
+
+    FileController::default()
+        .create_upload(calling_principal, req)
+        .await
+        .into()
+}
+
+#[update]
+fn upload_chunk(req: UploadChunkRequest) -> ApiResult<()> {
+    let calling_principal = caller();
+
+    FileController::default()
+        .upload_chunk(calling_principal, req)
+        .into()
+}
+
+#[update]
+fn commit_upload(upload_id: String) -> ApiResult<CommitUploadResponse> {
+    let calling_principal = caller();
+
+    FileController::default()
+        .commit_upload(calling_principal, upload_id)
+        .into()
+}
+
+#[query]
+fn get_file(req: GetFileRequest) -> ApiResult<GetFileResponse> {
+    let calling_principal = caller();
+
+    FileController::default()
+        .get_file(calling_principal, req)
+        .into()
+}
+
+#[query]
+fn get_file_chunk(req: GetFileChunkRequest) -> ApiResult<GetFileChunkResponse> {
+    let calling_principal = caller();
+
+    FileController::default()
+        .get_file_chunk(calling_principal, req)
+        .into()
+}
+
+#[derive(Default)]
+struct FileController {
+    access_control_service: AccessControlService,
+    audit_log_service: AuditLogService,
+    certificate_service: CertificateService,
+    file_service: FileService,
+}
+
+impl FileController {
+    async fn create_upload(
+        &self,
+        calling_principal: Principal,
+        req: CreateUploadRequest,
+    ) -> Result<CreateUploadResponse, ApiError> {
+        self.access_control_service
+            .assert_principal_is_user_or_backend(&calling_principal)?;
+
+        let res = self
+            .file_service
+            .create_upload(req.clone(), calling_principal)
+            .await?;
+
+        self.audit_log_service.record(
+            calling_principal,
+            AuditLogOperation::CreateUpload {
+                upload_id: UploadId::try_from(res.upload_id.as_str())?,
+            },
+            &req,
+        );
+
+        Ok(res)
+    }
+
+    fn upload_chunk(
+        &self,
+        calling_principal: Principal,
+        req: UploadChunkRequest,
+    ) -> Result<(), ApiError> {
+        self.access_control_service
+            .assert_principal_is_user_or_backend(&calling_principal)?;
+
+        self.file_service
+            .upload_chunk(req.clone(), calling_principal)?;
+
+        self.audit_log_service.record(
+            calling_principal,
+            AuditLogOperation::UploadChunk {
+                upload_id: UploadId::try_from(req.upload_id.as_str())?,
+            },
+            &req,
+        );
+
+        Ok(())
+    }
+
+    fn commit_upload(
+        &self,
+        calling_principal: Principal,
+        upload_id: String,
+    ) -> Result<CommitUploadResponse, ApiError> {
+        self.access_control_service
+            .assert_principal_is_user_or_backend(&calling_principal)?;
+
+        let res = self
+            .file_service
+            .commit_upload(upload_id.clone(), calling_principal)?;
+
+        self.audit_log_service.record(
+            calling_principal,
+            AuditLogOperation::CommitUpload {
+                upload_id: UploadId::try_from(upload_id.as_str())?,
+                file_hash: FileHash::try_from(res.file_hash.as_str())?,
+            },
+            &upload_id,
+        );
+
+        Ok(res)
+    }
+
+    /// Anyone can read the files of the public and shared certificates,
+    /// e.g. to show them in the page of a public certificate.
+    fn get_file(
+        &self,
+        calling_principal: Principal,
+        req: GetFileRequest,
+    ) -> Result<GetFileResponse, ApiError> {
+        self.assert_can_read_file(
+            calling_principal,
+            &req.file_hash,
+            req.share_token.as_deref(),
+        )?;
+
+        self.file_service.get_file(req.file_hash)
+    }
+
+    fn get_file_chunk(
+        &self,
+        calling_principal: Principal,
+        req: GetFileChunkRequest,
+    ) -> Result<GetFileChunkResponse, ApiError> {
+        self.assert_can_read_file(
+            calling_principal,
+            &req.file_hash,
+            req.share_token.as_deref(),
+        )?;
+
+        self.file_service.get_file_chunk(req)
+    }
+
+    fn assert_can_read_file(
+        &self,
+        calling_principal: Principal,
+        file_hash: &str,
+        share_token: Option<&str>,
+    ) -> Result<(), ApiError> {
+        // the callers that are not users can only read the files of the shared certificates
+        let only_user_principal = self
+            .access_control_service
+            .assert_principal_is_user_or_backend_with_permission(
+                &calling_principal,
+                Permission::ReadAllCertificates,
+            )
+            .unwrap_or(Some(&calling_principal))
+            .copied();
+
+        self.certificate_service
+            .assert_can_read_file(file_hash, share_token, only_user_principal)
+    }
+}
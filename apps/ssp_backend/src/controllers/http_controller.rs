@@ -0,0 +1,39 @@
+use ic_cdk::{println, query};
+use serde_bytes::ByteBuf;
+use ssp_backend_types::{HttpRequest, HttpResponse};
+
+use crate::services::HttpService;
+
+/// Called by the HTTP gateway, usually with the anonymous principal.
+#[query]
+fn http_request(req: HttpRequest) -> HttpResponse {
+    HttpController::default().http_request(req)
+}
+
+#[derive(Default)]
+struct HttpController {
+    http_service: HttpService,
+}
+
+impl HttpController {
+    fn http_request(&self, req: HttpRequest) -> HttpResponse {
+        self.http_service.http_request(req).unwrap_or_else(|e| {
+            println!("Failed to serve HTTP request: {}", e);
+            internal_server_error()
+        })
+    }
+}
+
+/// The (uncertified) response served when the certified response cannot be built,
+/// e.g. if the data certificate is not available.
+fn internal_server_error() -> HttpResponse {
+    HttpResponse {
+        status_code: 500,
+        headers: vec![(
+            "Content-Type".to_string(),
+            "text/plain; charset=utf-8".to_string(),
+        )],
+        body: ByteBuf::from(b"Internal server error".to_vec()),
+        upgrade: None,
+    }
+}
@@ -0,0 +1,39 @@
+use ic_cdk::query;
+use ssp_backend_types::{GetBlocksArgs, GetBlocksResult, Icrc3DataCertificate, SupportedBlockType};
+
+use crate::services::CertificateBlockService;
+
+#[query]
+fn icrc3_get_blocks(args: GetBlocksArgs) -> GetBlocksResult {
+    Icrc3Controller::default().get_blocks(args)
+}
+
+#[query]
+fn icrc3_get_tip_certificate() -> Option<Icrc3DataCertificate> {
+    Icrc3Controller::default().get_tip_certificate()
+}
+
+#[query]
+fn icrc3_supported_block_types() -> Vec<SupportedBlockType> {
+    Icrc3Controller::default().supported_block_types()
+}
+
+/// The ICRC-3 endpoints are public, as required by the standard.
+#[derive(Default)]
+struct Icrc3Controller {
+    certificate_block_service: CertificateBlockService,
+}
+
+impl Icrc3Controller {
+    fn get_blocks(&self, args: GetBlocksArgs) -> GetBlocksResult {
+        self.certificate_block_service.get_blocks(args)
+    }
+
+    fn get_tip_certificate(&self) -> Option<Icrc3DataCertificate> {
+        self.certificate_block_service.get_tip_certificate()
+    }
+
+    fn supported_block_types(&self) -> Vec<SupportedBlockType> {
+        self.certificate_block_service.supported_block_types()
+    }
+}
@@ -0,0 +1,132 @@
+use candid::{Nat, Principal};
+use ic_cdk::{caller, query, trap, update};
+use ssp_backend_types::{
+    Account, CollectionMetadata, SupportedStandard, TokenMetadata, TransferArg, TransferResult,
+};
+
+use crate::services::Icrc7Service;
+
+#[query]
+fn icrc10_supported_standards() -> Vec<SupportedStandard> {
+    Icrc7Controller::default().supported_standards()
+}
+
+#[query]
+fn icrc7_collection_metadata() -> CollectionMetadata {
+    Icrc7Controller::default().collection_metadata()
+}
+
+#[query]
+fn icrc7_name() -> String {
+    Icrc7Controller::default().name()
+}
+
+#[query]
+fn icrc7_symbol() -> String {
+    Icrc7Controller::default().symbol()
+}
+
+#[query]
+fn icrc7_total_supply() -> Nat {
+    Icrc7Controller::default().total_supply()
+}
+
+#[query]
+fn icrc7_balance_of(accounts: Vec<Account>) -> Vec<Nat> {
+    let calling_principal = caller();
+
+    Icrc7Controller::default().balance_of(accounts, calling_principal)
+}
+
+#[query]
+fn icrc7_tokens_of(account: Account, prev: Option<Nat>, take: Option<Nat>) -> Vec<Nat> {
+    let calling_principal = caller();
+
+    Icrc7Controller::default().tokens_of(account, prev, take, calling_principal)
+}
+
+#[query]
+fn icrc7_owner_of(token_ids: Vec<Nat>) -> Vec<Option<Account>> {
+    let calling_principal = caller();
+
+    Icrc7Controller::default().owner_of(token_ids, calling_principal)
+}
+
+#[query]
+fn icrc7_token_metadata(token_ids: Vec<Nat>) -> Vec<Option<TokenMetadata>> {
+    let calling_principal = caller();
+
+    Icrc7Controller::default().token_metadata(token_ids, calling_principal)
+}
+
+#[update]
+fn icrc7_transfer(args: Vec<TransferArg>) -> Vec<Option<TransferResult>> {
+    Icrc7Controller::default().transfer(args)
+}
+
+/// The ICRC-7 endpoints are public, as required by the standard,
+/// and can't return an [ssp_backend_types::ApiError], so they trap on errors.
+#[derive(Default)]
+struct Icrc7Controller {
+    icrc7_service: Icrc7Service,
+}
+
+impl Icrc7Controller {
+    fn supported_standards(&self) -> Vec<SupportedStandard> {
+        self.icrc7_service.supported_standards()
+    }
+
+    fn collection_metadata(&self) -> CollectionMetadata {
+        self.icrc7_service.collection_metadata()
+    }
+
+    fn name(&self) -> String {
+        self.icrc7_service.name()
+    }
+
+    fn symbol(&self) -> String {
+        self.icrc7_service.symbol()
+    }
+
+    fn total_supply(&self) -> Nat {
+        self.icrc7_service.total_supply()
+    }
+
+    fn balance_of(&self, accounts: Vec<Account>, calling_principal: Principal) -> Vec<Nat> {
+        self.icrc7_service
+            .balance_of(accounts, calling_principal)
+            .unwrap_or_else(|e| trap(&e))
+    }
+
+    fn tokens_of(
+        &self,
+        account: Account,
+        prev: Option<Nat>,
+        take: Option<Nat>,
+        calling_principal: Principal,
+    ) -> Vec<Nat> {
+        self.icrc7_service
+            .tokens_of(account, prev, take, calling_principal)
+            .unwrap_or_else(|e| trap(&e))
+    }
+
+    fn owner_of(&self, token_ids: Vec<Nat>, calling_principal: Principal) -> Vec<Option<Account>> {
+        self.icrc7_service
+            .owner_of(token_ids, calling_principal)
+            .unwrap_or_else(|e| trap(&e))
+    }
+
+    fn token_metadata(
+        &self,
+        token_ids: Vec<Nat>,
+        calling_principal: Principal,
+    ) -> Vec<Option<TokenMetadata>> {
+        self.icrc7_service
+            .token_metadata(token_ids, calling_principal)
+            .unwrap_or_else(|e| trap(&e))
+    }
+
+    fn transfer(&self, args: Vec<TransferArg>) -> Vec<Option<TransferResult>> {
+        self.icrc7_service.transfer(args)
+    }
+}
@@ -1,81 +1,531 @@
 use std::time::Duration;
 
-use ic_cdk::{init, post_upgrade, spawn, trap};
-use ic_cdk_timers::set_timer;
+use ic_cdk::{init, post_upgrade, println, trap};
+use ic_certification::Hash;
 
-use crate::services::{CertificateService, DelegationService};
+use ssp_backend_types::InitArgs;
+
+use crate::{
+    repositories::{CertificateId, DelegationSignatureKey, SchemaVersion, UserSub},
+    services::{
+        AuditLogService, CertificateBlockService, CertificateCredentialService, CertificateService,
+        ConfigService, DelegationService, FileService, HttpService, ManagedUserService,
+        SchemaVersionService, UserService,
+    },
+};
+
+pub(super) use jobs::{start_claimed_certificates_transfer, start_jwks_sync};
 
 #[init]
-fn init() {
-    set_timer(Duration::ZERO, move || spawn(init_task()));
+fn init(args: Option<InitArgs>) {
+    let init_controller = InitController::default();
+    init_controller.apply_init_args(args);
+    // a new canister has no data to migrate
+    init_controller.set_schema_version(migrations().len() as SchemaVersion);
 
-    jobs::start_jobs();
+    jobs::start_init();
 }
 
 #[post_upgrade]
-fn post_upgrade() {
-    set_timer(Duration::ZERO, move || spawn(init_task()));
+fn post_upgrade(args: Option<InitArgs>) {
+    InitController::default().apply_init_args(args);
 
-    jobs::start_jobs();
+    jobs::start_init();
 }
 
+/// Runs after all the [InitStep]s are done.
 async fn init_task() {
     let init_controller = InitController::default();
-    // We first certify all certificates, then we run the https outcalls.
+    // The certificates are indexed and certified by the init steps, before we run the https outcalls.
     // If we invert the order, this fails in the tests with PocketIC.
-    init_controller.certify_all_certificates();
+    init_controller.certify_audit_log_tip();
+    init_controller.certify_last_certificate_block();
     init_controller.init_delegation().await;
+
+    jobs::start_jobs();
+}
+
+/// The work done after the canister is installed or upgraded, in batches of at most
+/// `INIT_BATCH_SIZE` items, one batch per message, each step starting after the cursor it holds if set.
+/// The batches that load certificates stop early if they use too many instructions.
+///
+/// Until the restore steps are done, the certified data kept on the heap may be incomplete.
+enum InitStep {
+    /// Adds the certificates to the sort and facet indexes.
+    IndexCertificates(Option<CertificateId>),
+    /// Creates the managed users of the certificates stored before managed users were introduced.
+    IndexManagedUsers(Option<CertificateId>),
+    /// Indexes the users created before multiple OIDC providers were supported by issuer and sub.
+    IndexUserSubs(Option<UserSub>),
+    /// Indexes the stored signatures by seed, to revoke the delegations of a user.
+    IndexDelegationSignatures(Option<DelegationSignatureKey>),
+    /// Moves the signatures that never expire, stored with the delegation ones before,
+    /// to their own store.
+    MovePermanentSignatures(Option<DelegationSignatureKey>),
+    /// Signs the credentials of the certificates that don't have a signature yet.
+    SignCertificateCredentials(Option<CertificateId>),
+    /// Indexes the certificates by the files they reference, to check who can read the files.
+    IndexCertificateFiles(Option<CertificateId>),
+    /// Rebuilds the certified tree of the certificates.
+    CertifyCertificates(Option<CertificateId>),
+    /// Rebuilds the certified HTTP responses of the public certificates.
+    CertifyHttpResponses(Option<CertificateId>),
+    /// Rebuilds the certified signatures from the stored delegation signatures.
+    RestoreDelegationSignatures(Option<DelegationSignatureKey>),
+    /// Rebuilds the certified signatures from the stored signatures that never expire.
+    RestorePermanentSignatures(Option<(Hash, Hash)>),
+}
+
+/// The migrations of the data in stable memory, applied in order to the canisters
+/// whose [SchemaVersion] is lower than the number of migrations.
+///
+/// Migrations must be appended, and they must be idempotent, as they run again from the start
+/// if the canister is upgraded before they are done.
+fn migrations() -> Vec<InitStep> {
+    vec![
+        InitStep::IndexCertificates(None),
+        InitStep::IndexManagedUsers(None),
+        InitStep::IndexUserSubs(None),
+        InitStep::IndexDelegationSignatures(None),
+        InitStep::MovePermanentSignatures(None),
+        InitStep::SignCertificateCredentials(None),
+        InitStep::IndexCertificateFiles(None),
+    ]
+}
+
+/// The steps that run after every install and upgrade, after the migrations,
+/// to restore the certified data kept on the heap.
+fn restore_steps() -> Vec<InitStep> {
+    vec![
+        InitStep::CertifyCertificates(None),
+        InitStep::CertifyHttpResponses(None),
+        InitStep::RestoreDelegationSignatures(None),
+        InitStep::RestorePermanentSignatures(None),
+    ]
 }
 
 #[derive(Default)]
 struct InitController {
+    config_service: ConfigService,
     delegation_service: DelegationService,
     certificate_service: CertificateService,
+    file_service: FileService,
+    http_service: HttpService,
+    managed_user_service: ManagedUserService,
+    audit_log_service: AuditLogService,
+    certificate_block_service: CertificateBlockService,
+    certificate_credential_service: CertificateCredentialService,
+    schema_version_service: SchemaVersionService,
+    user_service: UserService,
 }
 
 impl InitController {
+    fn apply_init_args(&self, args: Option<InitArgs>) {
+        let Some(args) = args else {
+            return;
+        };
+
+        if let Some(oidc_configs) = args.oidc_providers {
+            if let Err(e) = self.config_service.set_oidc_providers(oidc_configs) {
+                trap(&format!("failed to set oidc providers: {:?}", e));
+            }
+        }
+        if let Some(legacy_oidc_issuer) = args.legacy_oidc_issuer {
+            if let Err(e) = self
+                .config_service
+                .set_legacy_oidc_issuer(legacy_oidc_issuer)
+            {
+                trap(&format!("failed to set legacy oidc issuer: {:?}", e));
+            }
+        }
+    }
+
     async fn init_delegation(&self) {
         self.delegation_service.ensure_salt_initialized().await;
+    }
 
-        self.fetch_jwks().await;
+    fn oidc_issuers(&self) -> Vec<String> {
+        self.delegation_service.get_oidc_issuers()
     }
 
-    async fn fetch_jwks(&self) {
-        if let Err(e) = self.delegation_service.fetch_and_store_jwks().await {
-            trap(&format!("failed to fetch and store jwks: {e}"));
+    fn has_oidc_provider(&self, issuer: &str) -> bool {
+        self.delegation_service.has_oidc_provider(issuer)
+    }
+
+    async fn fetch_jwks(&self, issuer: &str) -> Result<(), String> {
+        self.delegation_service.fetch_and_store_jwks(issuer).await
+    }
+
+    fn certify_audit_log_tip(&self) {
+        self.audit_log_service.certify_audit_log_tip();
+    }
+
+    fn certify_last_certificate_block(&self) {
+        self.certificate_block_service.certify_last_block();
+    }
+
+    fn prune_expired_delegation_signatures(&self, limit: usize) -> bool {
+        self.delegation_service
+            .prune_expired_delegation_signatures(limit)
+    }
+
+    fn transfer_claimed_certificates(&self, limit: usize) -> bool {
+        self.managed_user_service
+            .transfer_claimed_certificates(limit)
+            .unwrap_or_else(|e| {
+                // retried once the transfer is started again
+                println!("Failed to transfer the claimed certificates: {:?}", e);
+                true
+            })
+    }
+
+    fn remove_expired_uploads(&self) {
+        if let Err(e) = self.file_service.remove_expired_uploads() {
+            println!("Failed to remove expired uploads: {:?}", e);
         }
     }
 
-    fn certify_all_certificates(&self) {
-        self.certificate_service.certify_all_certificates();
+    fn jwks_fetch_delay(&self, issuer: &str, max_age: Duration) -> Duration {
+        self.delegation_service
+            .jwks_fetch_delay(issuer, max_age)
+            .unwrap_or_else(|e| {
+                println!("Failed to calculate JWKS fetch delay of {issuer}, fetching now: {e}");
+                Duration::ZERO
+            })
+    }
+
+    fn schema_version(&self) -> SchemaVersion {
+        self.schema_version_service.get_schema_version()
+    }
+
+    fn set_schema_version(&self, schema_version: SchemaVersion) {
+        if let Err(e) = self
+            .schema_version_service
+            .set_schema_version(schema_version)
+        {
+            trap(&format!("failed to set schema version: {e}"));
+        }
+    }
+
+    /// Runs a batch of the step. Returns the rest of the step, or `None` if the step is done.
+    fn run_init_step(&self, step: InitStep, limit: usize) -> Result<Option<InitStep>, String> {
+        Ok(match step {
+            InitStep::IndexCertificates(after) => self
+                .certificate_service
+                .index_certificates(after, limit)?
+                .map(|after| InitStep::IndexCertificates(Some(after))),
+            InitStep::IndexManagedUsers(after) => self
+                .managed_user_service
+                .index_managed_users(after, limit)?
+                .map(|after| InitStep::IndexManagedUsers(Some(after))),
+            InitStep::IndexUserSubs(after) => self
+                .user_service
+                .index_user_subs(after, limit)
+                .map(|after| InitStep::IndexUserSubs(Some(after))),
+            InitStep::IndexDelegationSignatures(after) => self
+                .delegation_service
+                .index_delegation_signatures(after, limit)
+                .map(|after| InitStep::IndexDelegationSignatures(Some(after))),
+            InitStep::MovePermanentSignatures(after) => self
+                .delegation_service
+                .move_permanent_signatures(after, limit)?
+                .map(|after| InitStep::MovePermanentSignatures(Some(after))),
+            InitStep::SignCertificateCredentials(after) => self
+                .certificate_credential_service
+                .sign_certificate_credentials(after, limit)?
+                .map(|after| InitStep::SignCertificateCredentials(Some(after))),
+            InitStep::IndexCertificateFiles(after) => self
+                .certificate_service
+                .index_certificate_files(after, limit)?
+                .map(|after| InitStep::IndexCertificateFiles(Some(after))),
+            InitStep::CertifyCertificates(after) => self
+                .certificate_service
+                .certify_certificates(after, limit)?
+                .map(|after| InitStep::CertifyCertificates(Some(after))),
+            InitStep::CertifyHttpResponses(after) => self
+                .http_service
+                .certify_responses(after, limit)?
+                .map(|after| InitStep::CertifyHttpResponses(Some(after))),
+            InitStep::RestoreDelegationSignatures(after) => self
+                .delegation_service
+                .restore_delegation_signatures(after, limit)
+                .map(|after| InitStep::RestoreDelegationSignatures(Some(after))),
+            InitStep::RestorePermanentSignatures(after) => self
+                .delegation_service
+                .restore_permanent_signatures(after, limit)
+                .map(|after| InitStep::RestorePermanentSignatures(Some(after))),
+        })
     }
 }
 
 mod jobs {
-    use ic_cdk::spawn;
-    use ic_cdk_timers::set_timer_interval;
+    use ic_cdk::{println, spawn, trap};
+    use ic_cdk_timers::{set_timer, set_timer_interval};
     use std::time::Duration;
 
+    pub use delegation::start_jwks_sync;
+    pub use init::start as start_init;
+    pub use managed_users::start_claimed_certificates_transfer;
+
     pub fn start_jobs() {
         delegation::start();
+        files::start();
+        managed_users::start_claimed_certificates_transfer();
+    }
+
+    mod init {
+        use std::{cell::RefCell, collections::VecDeque};
+
+        use super::*;
+
+        use crate::{
+            controllers::init_controller::{
+                init_task, migrations, restore_steps, InitController, InitStep,
+            },
+            repositories::SchemaVersion,
+        };
+
+        /// The maximum number of items (e.g. certificates) that an init step processes
+        /// in a single message, so that the init work stays within the instruction limit.
+        const INIT_BATCH_SIZE: usize = 500;
+
+        struct PendingInitStep {
+            step: InitStep,
+            /// The schema version reached once the step is done, if the step is a migration.
+            migrates_to: Option<SchemaVersion>,
+        }
+
+        thread_local! {
+            /// The init steps left to run, the first one possibly started already.
+            static INIT_STEPS: RefCell<VecDeque<PendingInitStep>> = RefCell::new(VecDeque::new());
+        }
+
+        /// Runs the pending migrations and the restore steps in batches, one batch per message,
+        /// then runs [init_task].
+        pub fn start() {
+            let schema_version = InitController::default().schema_version();
+            let migrations = migrations()
+                .into_iter()
+                .zip(1..)
+                .skip(schema_version as usize)
+                .map(|(step, migrates_to)| PendingInitStep {
+                    step,
+                    migrates_to: Some(migrates_to),
+                });
+            let restore_steps = restore_steps().into_iter().map(|step| PendingInitStep {
+                step,
+                migrates_to: None,
+            });
+
+            INIT_STEPS.with_borrow_mut(|steps| {
+                *steps = migrations.chain(restore_steps).collect();
+            });
+            schedule_next_batch();
+        }
+
+        fn schedule_next_batch() {
+            set_timer(Duration::ZERO, run_next_batch);
+        }
+
+        /// Runs a single batch of the first pending step, so that each message
+        /// stays within the instruction limit, and schedules the next batch.
+        fn run_next_batch() {
+            let Some(pending) = INIT_STEPS.with_borrow_mut(|steps| steps.pop_front()) else {
+                spawn(init_task());
+                return;
+            };
+
+            let init_controller = InitController::default();
+            let rest = match init_controller.run_init_step(pending.step, INIT_BATCH_SIZE) {
+                Ok(rest) => rest,
+                Err(e) => trap(&format!("failed to run init step: {e}")),
+            };
+
+            match rest {
+                Some(step) => INIT_STEPS.with_borrow_mut(|steps| {
+                    steps.push_front(PendingInitStep {
+                        step,
+                        migrates_to: pending.migrates_to,
+                    })
+                }),
+                None => {
+                    if let Some(schema_version) = pending.migrates_to {
+                        init_controller.set_schema_version(schema_version);
+                        println!("Migrated to schema version {}", schema_version);
+                    }
+                }
+            }
+
+            schedule_next_batch();
+        }
+    }
+
+    mod files {
+        use super::*;
+
+        use crate::controllers::init_controller::InitController;
+
+        // remove the uploads that have not been committed in time every 15 minutes
+        const EXPIRED_UPLOADS_REMOVAL_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+        /// Starts removing the expired uploads, which are otherwise only removed
+        /// when a new upload is created.
+        pub fn start() {
+            set_timer_interval(EXPIRED_UPLOADS_REMOVAL_INTERVAL, || {
+                InitController::default().remove_expired_uploads();
+            });
+        }
+    }
+
+    mod managed_users {
+        use std::cell::Cell;
+
+        use super::*;
+
+        use crate::controllers::init_controller::InitController;
+
+        /// The maximum number of claimed certificates that a transfer run transfers in a single message.
+        const CLAIM_TRANSFER_BATCH_SIZE: usize = 100;
+
+        thread_local! {
+            /// Whether the claimed certificates are being transferred.
+            static IS_TRANSFERRING: Cell<bool> = const { Cell::new(false) };
+        }
+
+        /// Transfers the certificates of the claimed managed users that were not transferred
+        /// while the users signed in, in batches, one batch per message.
+        /// Does nothing if they are already being transferred.
+        pub fn start_claimed_certificates_transfer() {
+            if IS_TRANSFERRING.replace(true) {
+                return;
+            }
+
+            set_timer(Duration::ZERO, transfer_claimed_certificates);
+        }
+
+        fn transfer_claimed_certificates() {
+            let is_done =
+                InitController::default().transfer_claimed_certificates(CLAIM_TRANSFER_BATCH_SIZE);
+            if is_done {
+                IS_TRANSFERRING.set(false);
+            } else {
+                set_timer(Duration::ZERO, transfer_claimed_certificates);
+            }
+        }
     }
 
     mod delegation {
+        use std::{
+            cell::RefCell,
+            collections::{hash_map::Entry, HashMap},
+        };
+
         use super::*;
 
         use crate::controllers::init_controller::InitController;
 
         // fetch JWKS every 1 hour
         const JWKS_FETCH_INTERVAL: Duration = Duration::from_secs(60 * 60);
+        // failed fetches are retried after 1 minute, doubling the delay at every failure
+        const JWKS_FETCH_MIN_RETRY_DELAY: Duration = Duration::from_secs(60);
+        // prune expired delegation signatures every 1 hour
+        const SIGNATURES_PRUNE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+        /// The maximum number of entries of each kind that a prune run removes in a single message.
+        const SIGNATURES_PRUNE_BATCH_SIZE: usize = 1_000;
 
+        thread_local! {
+            /// The issuers of the providers whose JWKS is being synced,
+            /// with the number of consecutive failed fetches.
+            static JWKS_SYNCS: RefCell<HashMap<String, u32>> = RefCell::new(HashMap::new());
+        }
+
+        /// Starts syncing the JWKS of all the OIDC providers
+        /// and pruning the expired delegation signatures.
         pub fn start() {
-            set_timer_interval(JWKS_FETCH_INTERVAL, || {
-                spawn(fetch_jwks());
+            for issuer in InitController::default().oidc_issuers() {
+                start_jwks_sync(issuer);
+            }
+
+            set_timer_interval(
+                SIGNATURES_PRUNE_INTERVAL,
+                prune_expired_delegation_signatures,
+            );
+        }
+
+        /// Prunes the expired delegation signatures in batches, one batch per message.
+        fn prune_expired_delegation_signatures() {
+            let is_done = InitController::default()
+                .prune_expired_delegation_signatures(SIGNATURES_PRUNE_BATCH_SIZE);
+            if !is_done {
+                set_timer(Duration::ZERO, prune_expired_delegation_signatures);
+            }
+        }
+
+        /// Fetches the JWKS of the provider right away, unless the stored JWKS
+        /// has been fetched less than [JWKS_FETCH_INTERVAL] ago (e.g. before an upgrade).
+        /// Does nothing if the JWKS of the provider is already being synced.
+        pub fn start_jwks_sync(issuer: String) {
+            let is_new = JWKS_SYNCS.with_borrow_mut(|syncs| match syncs.entry(issuer.clone()) {
+                Entry::Occupied(_) => false,
+                Entry::Vacant(entry) => {
+                    entry.insert(0);
+                    true
+                }
             });
+            if !is_new {
+                return;
+            }
+
+            let delay = InitController::default().jwks_fetch_delay(&issuer, JWKS_FETCH_INTERVAL);
+            schedule_jwks_fetch(issuer, delay);
+        }
+
+        fn schedule_jwks_fetch(issuer: String, delay: Duration) {
+            set_timer(delay, || spawn(fetch_jwks(issuer)));
+        }
+
+        /// While the provider is unreachable, the last known JWKS
+        /// keeps being used to verify the JWTs and the fetch is retried with backoff.
+        /// The sync stops once the provider is removed.
+        async fn fetch_jwks(issuer: String) {
+            let init_controller = InitController::default();
+            if !init_controller.has_oidc_provider(&issuer) {
+                JWKS_SYNCS.with_borrow_mut(|syncs| syncs.remove(&issuer));
+                return;
+            }
+
+            let delay = match init_controller.fetch_jwks(&issuer).await {
+                Ok(()) => {
+                    JWKS_SYNCS.with_borrow_mut(|syncs| syncs.insert(issuer.clone(), 0));
+                    JWKS_FETCH_INTERVAL
+                }
+                Err(e) => {
+                    let failed_fetches = JWKS_SYNCS.with_borrow_mut(|syncs| {
+                        let failed_fetches = syncs.entry(issuer.clone()).or_default();
+                        *failed_fetches = failed_fetches.saturating_add(1);
+                        *failed_fetches
+                    });
+
+                    let delay = jwks_fetch_retry_delay(failed_fetches);
+                    println!(
+                        "Failed to fetch JWKS of {} ({} consecutive failures), retrying in {}s: {}",
+                        issuer,
+                        failed_fetches,
+                        delay.as_secs(),
+                        e
+                    );
+                    delay
+                }
+            };
+
+            schedule_jwks_fetch(issuer, delay);
         }
 
-        async fn fetch_jwks() {
-            InitController::default().fetch_jwks().await
+        fn jwks_fetch_retry_delay(failed_fetches: u32) -> Duration {
+            let exponent = failed_fetches.saturating_sub(1).min(u32::BITS - 1);
+            JWKS_FETCH_MIN_RETRY_DELAY
+                .saturating_mul(2u32.saturating_pow(exponent))
+                .min(JWKS_FETCH_INTERVAL)
         }
     }
 }
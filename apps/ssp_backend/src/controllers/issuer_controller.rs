@@ -0,0 +1,161 @@
+use candid::Principal;
+use ic_cdk::{caller, query, update};
+use ssp_backend_types::{
+    ApiError, ApiResult, CreateIssuerRequest, CreateIssuerResponse, Issuer, IssuerMemberRequest,
+    SetIssuerVerifiedRequest,
+};
+
+use crate::{
+    repositories::{AuditLogOperation, IssuerId},
+    services::{AccessControlService, AuditLogService, IssuerService, Permission},
+};
+
+#[update]
+async fn create_issuer(req: CreateIssuerRequest) -> ApiResult<CreateIssuerResponse> {
+    let calling_principal = caller();
+
+    IssuerController::default()
+        .create_issuer(calling_principal, req)
+        .await
+        .into()
+}
+
+#[query]
+fn get_issuer(id: String) -> ApiResult<Issuer> {
+    IssuerController::default().get_issuer(id).into()
+}
+
+#[update]
+fn set_issuer_verified(req: SetIssuerVerifiedRequest) -> ApiResult<()> {
+    let calling_principal = caller();
+
+    IssuerController::default()
+        .set_issuer_verified(calling_principal, req)
+        .into()
+}
+
+#[update]
+fn add_issuer_member(req: IssuerMemberRequest) -> ApiResult<()> {
+    let calling_principal = caller();
+
+    IssuerController::default()
+        .add_issuer_member(calling_principal, req)
+        .into()
+}
+
+#[update]
+fn remove_issuer_member(req: IssuerMemberRequest) -> ApiResult<()> {
+    let calling_principal = caller();
+
+    IssuerController::default()
+        .remove_issuer_member(calling_principal, req)
+        .into()
+}
+
+#[derive(Default)]
+pub struct IssuerController {
+    access_control_service: AccessControlService,
+    audit_log_service: AuditLogService,
+    issuer_service: IssuerService,
+}
+
+impl IssuerController {
+    async fn create_issuer(
+        &self,
+        calling_principal: Principal,
+        req: CreateIssuerRequest,
+    ) -> Result<CreateIssuerResponse, ApiError> {
+        self.access_control_service
+            .assert_principal_is_user(&calling_principal)?;
+        self.access_control_service
+            .assert_principal_has_permission(&calling_principal, Permission::RegisterIssuers)?;
+
+        let res = self
+            .issuer_service
+            .create_issuer(req.clone(), calling_principal)
+            .await?;
+
+        self.audit_log_service.record(
+            calling_principal,
+            AuditLogOperation::CreateIssuer {
+                id: IssuerId::try_from(res.id.as_str())?,
+            },
+            &req,
+        );
+
+        Ok(res)
+    }
+
+    fn get_issuer(&self, id: String) -> Result<Issuer, ApiError> {
+        self.issuer_service.get_issuer(id)
+    }
+
+    fn set_issuer_verified(
+        &self,
+        calling_principal: Principal,
+        req: SetIssuerVerifiedRequest,
+    ) -> Result<(), ApiError> {
+        // only controllers can vouch for an issuer
+        self.access_control_service
+            .assert_principal_is_controller(&calling_principal)?;
+
+        self.issuer_service.set_issuer_verified(req.clone())?;
+
+        self.audit_log_service.record(
+            calling_principal,
+            AuditLogOperation::SetIssuerVerified {
+                id: IssuerId::try_from(req.id.as_str())?,
+            },
+            &req,
+        );
+
+        Ok(())
+    }
+
+    fn add_issuer_member(
+        &self,
+        calling_principal: Principal,
+        req: IssuerMemberRequest,
+    ) -> Result<(), ApiError> {
+        // users can only manage the members of the issuers they are admins of
+        let only_admin = self
+            .access_control_service
+            .assert_principal_is_user_or_backend(&calling_principal)?;
+
+        self.issuer_service
+            .add_issuer_member(req.clone(), only_admin.cloned())?;
+
+        self.audit_log_service.record(
+            calling_principal,
+            AuditLogOperation::AddIssuerMember {
+                issuer_id: IssuerId::try_from(req.issuer_id.as_str())?,
+            },
+            &req,
+        );
+
+        Ok(())
+    }
+
+    fn remove_issuer_member(
+        &self,
+        calling_principal: Principal,
+        req: IssuerMemberRequest,
+    ) -> Result<(), ApiError> {
+        let only_admin = self
+            .access_control_service
+            .assert_principal_is_user_or_backend(&calling_principal)?;
+
+        self.issuer_service
+            .remove_issuer_member(req.clone(), only_admin.cloned())?;
+
+        self.audit_log_service.record(
+            calling_principal,
+            AuditLogOperation::RemoveIssuerMember {
+                issuer_id: IssuerId::try_from(req.issuer_id.as_str())?,
+            },
+            &req,
+        );
+
+        Ok(())
+    }
+}
@@ -1,5 +1,12 @@
+mod achievement_controller;
+mod audit_log_controller;
 mod certificate_controller;
 mod config_controller;
 mod delegation_controller;
+mod file_controller;
+mod http_controller;
+mod icrc3_controller;
+mod icrc7_controller;
 mod init_controller;
+mod issuer_controller;
 mod user_controller;
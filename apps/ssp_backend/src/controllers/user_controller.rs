@@ -1,25 +1,122 @@
 use candid::Principal;
-use ic_cdk::{caller, query, trap};
+use ic_cdk::{caller, query, update};
+use ssp_backend_types::{ApiError, ApiResult, ManagedUser, ManagedUserGuardianRequest};
 
-use crate::services::UserService;
+use crate::{
+    repositories::{AuditLogOperation, ManagedUserId},
+    services::{AccessControlService, AuditLogService, ManagedUserService, UserService},
+};
 
 #[query]
-fn get_my_user() -> ssp_backend_types::User {
+fn get_my_user() -> ApiResult<ssp_backend_types::User> {
     let calling_principal = caller();
 
-    UserController::default().get_my_user(calling_principal)
+    UserController::default()
+        .get_my_user(calling_principal)
+        .into()
+}
+
+#[query]
+fn get_my_managed_users() -> ApiResult<Vec<ManagedUser>> {
+    let calling_principal = caller();
+
+    UserController::default()
+        .get_my_managed_users(calling_principal)
+        .into()
+}
+
+#[update]
+fn add_managed_user_guardian(req: ManagedUserGuardianRequest) -> ApiResult<()> {
+    let calling_principal = caller();
+
+    UserController::default()
+        .add_managed_user_guardian(calling_principal, req)
+        .into()
+}
+
+#[update]
+fn remove_managed_user_guardian(req: ManagedUserGuardianRequest) -> ApiResult<()> {
+    let calling_principal = caller();
+
+    UserController::default()
+        .remove_managed_user_guardian(calling_principal, req)
+        .into()
 }
 
 #[derive(Default)]
 pub struct UserController {
+    access_control_service: AccessControlService,
+    audit_log_service: AuditLogService,
     user_service: UserService,
+    managed_user_service: ManagedUserService,
 }
 
 impl UserController {
-    fn get_my_user(&self, calling_principal: Principal) -> ssp_backend_types::User {
-        match self.user_service.get_user(&calling_principal) {
-            Some(user) => user.into(),
-            None => trap("No user found"),
-        }
+    fn get_my_user(
+        &self,
+        calling_principal: Principal,
+    ) -> Result<ssp_backend_types::User, ApiError> {
+        self.user_service
+            .get_user(&calling_principal)
+            .map(Into::into)
+            .ok_or_else(|| ApiError::not_found("No user found"))
+    }
+
+    fn get_my_managed_users(
+        &self,
+        calling_principal: Principal,
+    ) -> Result<Vec<ManagedUser>, ApiError> {
+        self.access_control_service
+            .assert_principal_is_user(&calling_principal)?;
+
+        self.managed_user_service
+            .get_managed_users_by_guardian(calling_principal)
+    }
+
+    fn add_managed_user_guardian(
+        &self,
+        calling_principal: Principal,
+        req: ManagedUserGuardianRequest,
+    ) -> Result<(), ApiError> {
+        // users can only add guardians to the managed users they are guardians of
+        let only_guardian = self
+            .access_control_service
+            .assert_principal_is_user_or_backend(&calling_principal)?;
+
+        self.managed_user_service
+            .add_guardian(req.clone(), only_guardian.cloned())?;
+
+        self.audit_log_service.record(
+            calling_principal,
+            AuditLogOperation::AddManagedUserGuardian {
+                managed_user_id: ManagedUserId::try_from(req.managed_user_db_id.as_str())?,
+            },
+            &req,
+        );
+
+        Ok(())
+    }
+
+    fn remove_managed_user_guardian(
+        &self,
+        calling_principal: Principal,
+        req: ManagedUserGuardianRequest,
+    ) -> Result<(), ApiError> {
+        let only_guardian = self
+            .access_control_service
+            .assert_principal_is_user_or_backend(&calling_principal)?;
+
+        self.managed_user_service
+            .remove_guardian(req.clone(), only_guardian.cloned())?;
+
+        self.audit_log_service.record(
+            calling_principal,
+            AuditLogOperation::RemoveManagedUserGuardian {
+                managed_user_id: ManagedUserId::try_from(req.managed_user_db_id.as_str())?,
+            },
+            &req,
+        );
+
+        Ok(())
     }
 }
@@ -0,0 +1,22 @@
+use ssp_backend_types::CreateAchievementResponse;
+
+use crate::repositories::{Achievement, AchievementId};
+
+pub fn map_achievement(
+    id: AchievementId,
+    achievement: Achievement,
+) -> ssp_backend_types::Achievement {
+    ssp_backend_types::Achievement {
+        id: id.to_string(),
+        name: achievement.name,
+        description: achievement.description,
+        criteria: achievement.criteria,
+        sport_category: achievement.sport_category,
+        issuer_id: achievement.issuer_id.map(|id| id.to_string()),
+        created_at: achievement.created_at.to_string(),
+    }
+}
+
+pub fn map_create_achievement_response(id: AchievementId) -> CreateAchievementResponse {
+    CreateAchievementResponse { id: id.to_string() }
+}
@@ -0,0 +1,22 @@
+use ssp_backend_types::{AuditLogEntryWithIndex, GetAuditLogResponse};
+
+use crate::repositories::{AuditLogEntry, AuditLogIndex, AuditLogTipWithCertification};
+
+pub fn map_get_audit_log_response(
+    entries: Vec<(AuditLogIndex, AuditLogEntry)>,
+    tip: AuditLogTipWithCertification,
+) -> GetAuditLogResponse {
+    GetAuditLogResponse {
+        entries: entries
+            .into_iter()
+            .map(|(index, entry)| AuditLogEntryWithIndex {
+                index,
+                entry_cbor_hex: hex::encode(entry.entry_cbor()),
+            })
+            .collect(),
+        length: tip.length,
+        tip_hash: hex::encode(tip.tip_hash),
+        ic_certificate: tip.ic_certificate,
+        ic_certificate_witness: tip.ic_certificate_witness,
+    }
+}
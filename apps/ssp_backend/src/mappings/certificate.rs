@@ -1,6 +1,13 @@
-use ssp_backend_types::CreateCertificateResponse;
+use ssp_backend_types::{
+    ApiError, CreateCertificateResponse, CreateCertificateShareTokenResponse,
+    GetUserCertificatesResponse, UpdateCertificateResponse,
+};
 
-use crate::repositories::{Certificate, CertificateContent, CertificateId, DateTime};
+use crate::repositories::{
+    Certificate, CertificateContent, CertificateFilters, CertificateId, CertificateRevocation,
+    CertificateSort, CertificateSortField, CertificateVersion, CertificatesPage, DateTime,
+    FileHash, SortOrder,
+};
 
 impl From<CertificateContent> for ssp_backend_types::CertificateContent {
     fn from(value: CertificateContent) -> Self {
@@ -10,6 +17,7 @@ impl From<CertificateContent> for ssp_backend_types::CertificateContent {
             sport_category: value.sport_category,
             notes: value.notes,
             file_uri: value.file_uri,
+            file_hash: value.file_hash.map(|file_hash| file_hash.to_string()),
             external_id: value.external_id,
             issuer_full_name: value.issuer_full_name,
             issuer_club_name: value.issuer_club_name,
@@ -18,16 +26,26 @@ impl From<CertificateContent> for ssp_backend_types::CertificateContent {
 }
 
 impl TryFrom<ssp_backend_types::CreateCertificateContentRequest> for CertificateContent {
-    type Error = String;
+    type Error = ApiError;
 
-    fn try_from(value: ssp_backend_types::CreateCertificateContentRequest) -> Result<Self, String> {
-        let issued_at = DateTime::from_timestamp_micros(value.issued_at)?;
+    fn try_from(
+        value: ssp_backend_types::CreateCertificateContentRequest,
+    ) -> Result<Self, ApiError> {
+        let issued_at = DateTime::from_timestamp_micros(value.issued_at)
+            .map_err(|e| ApiError::validation("issued_at", e))?;
         Ok(CertificateContent {
             name: value.name,
             issued_at,
             sport_category: value.sport_category,
             notes: value.notes,
             file_uri: value.file_uri,
+            file_hash: match value.file_hash {
+                Some(file_hash) => Some(
+                    FileHash::try_from(file_hash.as_str())
+                        .map_err(|e| ApiError::validation("file_hash", e))?,
+                ),
+                None => None,
+            },
             external_id: value.external_id,
             issuer_full_name: value.issuer_full_name,
             issuer_club_name: value.issuer_club_name,
@@ -35,13 +53,57 @@ impl TryFrom<ssp_backend_types::CreateCertificateContentRequest> for Certificate
     }
 }
 
+impl From<CertificateRevocation> for ssp_backend_types::CertificateRevocation {
+    fn from(value: CertificateRevocation) -> Self {
+        ssp_backend_types::CertificateRevocation {
+            reason: value.reason,
+            revoked_at: value.revoked_at.to_string(),
+        }
+    }
+}
+
 impl From<Certificate> for ssp_backend_types::Certificate {
     fn from(value: Certificate) -> Self {
         ssp_backend_types::Certificate {
             user_principal: value.user_principal,
             created_at: value.created_at.to_string(),
             content: value.content.into(),
+            issuer: value.issuer.map(Into::into),
+            issuer_principal: value.issuer_principal,
             managed_user_id: value.managed_user_id.map(|id| id.to_string()),
+            revocation: value.revocation.map(|revocation| revocation.into()),
+            updated_at: value.updated_at.map(|updated_at| updated_at.to_string()),
+            achievement_id: value.achievement_id.map(|id| id.to_string()),
+        }
+    }
+}
+
+impl From<ssp_backend_types::CertificatesSort> for CertificateSort {
+    fn from(value: ssp_backend_types::CertificatesSort) -> Self {
+        CertificateSort {
+            field: match value.field {
+                ssp_backend_types::CertificatesSortField::CreatedAt => {
+                    CertificateSortField::CreatedAt
+                }
+                ssp_backend_types::CertificatesSortField::IssuedAt => {
+                    CertificateSortField::IssuedAt
+                }
+            },
+            order: match value.order {
+                ssp_backend_types::SortOrder::Asc => SortOrder::Ascending,
+                ssp_backend_types::SortOrder::Desc => SortOrder::Descending,
+            },
+        }
+    }
+}
+
+impl From<ssp_backend_types::CertificatesFilters> for CertificateFilters {
+    fn from(value: ssp_backend_types::CertificatesFilters) -> Self {
+        CertificateFilters {
+            sport_category: value.sport_category,
+            issued_at_from: value.issued_at_from,
+            issued_at_to: value.issued_at_to,
+            issuer_club_name: value.issuer_club_name,
         }
     }
 }
@@ -52,22 +114,51 @@ pub fn map_create_certificate_response(certificate_id: CertificateId) -> CreateC
     }
 }
 
+pub fn map_update_certificate_response(version: CertificateVersion) -> UpdateCertificateResponse {
+    UpdateCertificateResponse { version }
+}
+
+pub fn map_create_certificate_share_token_response(
+    share_token: String,
+) -> CreateCertificateShareTokenResponse {
+    CreateCertificateShareTokenResponse { share_token }
+}
+
+pub fn map_get_user_certificates_response(page: CertificatesPage) -> GetUserCertificatesResponse {
+    GetUserCertificatesResponse {
+        certificates: page
+            .certificates
+            .into_iter()
+            .map(|(id, certificate)| map_certificate_preview_with_id(id, certificate))
+            .collect(),
+        next_cursor: page.next_cursor.map(|cursor| cursor.to_string()),
+    }
+}
+
 pub fn map_certificate_preview_with_id(
     id: CertificateId,
     certificate: Certificate,
 ) -> ssp_backend_types::CertificatePreviewWithId {
+    let is_revoked = certificate.is_revoked();
+
     ssp_backend_types::CertificatePreviewWithId {
         id: id.to_string(),
         name: certificate.content.name,
+        sport_category: certificate.content.sport_category,
+        issued_at: certificate.content.issued_at.to_string(),
+        issuer_club_name: certificate.content.issuer_club_name,
+        is_revoked,
     }
 }
 
 pub fn map_certificate_with_id(
     id: CertificateId,
+    version: CertificateVersion,
     certificate: Certificate,
 ) -> ssp_backend_types::CertificateWithId {
     ssp_backend_types::CertificateWithId {
         id: id.to_string(),
+        version,
         certificate_cbor_hex: certificate.certificate_cbor_hex(),
     }
 }
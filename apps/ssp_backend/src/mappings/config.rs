@@ -1,9 +1,66 @@
-use crate::repositories::Config;
+use crate::repositories::{Config, DelegationConfig, OidcConfig};
 
 impl From<Config> for ssp_backend_types::Config {
     fn from(value: Config) -> Self {
         Self {
             backend_principal: value.backend_principal,
+            delegation: value.delegation.unwrap_or_default().into(),
+            legacy_oidc_issuer: value.legacy_oidc_issuer,
+        }
+    }
+}
+
+impl From<DelegationConfig> for ssp_backend_types::DelegationConfig {
+    fn from(value: DelegationConfig) -> Self {
+        Self {
+            max_session_duration_ns: value.max_session_duration_ns,
+            role_session_durations: value
+                .role_session_durations
+                .into_iter()
+                .map(
+                    |(role, max_session_duration_ns)| ssp_backend_types::RoleSessionDuration {
+                        role,
+                        max_session_duration_ns,
+                    },
+                )
+                .collect(),
+            targets: value.targets,
+        }
+    }
+}
+
+impl From<ssp_backend_types::DelegationConfig> for DelegationConfig {
+    fn from(value: ssp_backend_types::DelegationConfig) -> Self {
+        Self {
+            max_session_duration_ns: value.max_session_duration_ns,
+            role_session_durations: value
+                .role_session_durations
+                .into_iter()
+                .map(|r| (r.role, r.max_session_duration_ns))
+                .collect(),
+            targets: value.targets,
+        }
+    }
+}
+
+impl From<OidcConfig> for ssp_backend_types::OidcConfig {
+    fn from(value: OidcConfig) -> Self {
+        Self {
+            issuer: value.issuer,
+            audiences: value.audiences,
+            jwks_url: value.jwks_url,
+            claims_namespace: value.claims_namespace,
+        }
+    }
+}
+
+impl From<ssp_backend_types::OidcConfig> for OidcConfig {
+    fn from(value: ssp_backend_types::OidcConfig) -> Self {
+        Self {
+            issuer: value.issuer,
+            audiences: value.audiences,
+            jwks_url: value.jwks_url,
+            claims_namespace: value.claims_namespace,
         }
     }
 }
@@ -0,0 +1,91 @@
+use candid::Principal;
+use serde::Serialize;
+
+use crate::repositories::{Certificate, CertificateId, CertificateVersion};
+
+pub(super) const VC_CONTEXT_V2: &str = "https://www.w3.org/ns/credentials/v2";
+pub(super) const VC_TYPE: &str = "VerifiableCredential";
+const CERTIFICATE_VC_TYPE: &str = "SportCertificateCredential";
+
+/// A certificate as a W3C Verifiable Credential, see https://www.w3.org/TR/vc-data-model-2.0/.
+///
+/// The fields are serialized in the order in which they are declared,
+/// so that the same certificate version always results in the same credential.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CertificateCredential {
+    #[serde(rename = "@context")]
+    context: Vec<&'static str>,
+    id: String,
+    #[serde(rename = "type")]
+    credential_type: Vec<&'static str>,
+    issuer: String,
+    valid_from: String,
+    credential_subject: CertificateCredentialSubject,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CertificateCredentialSubject {
+    id: String,
+    certificate: CertificateClaims,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CertificateClaims {
+    version: CertificateVersion,
+    name: String,
+    issued_at: String,
+    sport_category: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    notes: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    file_uri: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    file_hash: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    external_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    issuer_full_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    issuer_club_name: Option<String>,
+}
+
+pub(super) fn did_icp(principal: &Principal) -> String {
+    format!("did:icp:{}", principal.to_text())
+}
+
+/// Maps the certificate version to its JSON credential, issued by the canister.
+pub fn map_certificate_credential(
+    issuer: &Principal,
+    id: CertificateId,
+    version: CertificateVersion,
+    certificate: &Certificate,
+) -> String {
+    let content = certificate.content.clone();
+    let credential = CertificateCredential {
+        context: vec![VC_CONTEXT_V2],
+        id: format!("urn:uuid:{}", id.to_string()),
+        credential_type: vec![VC_TYPE, CERTIFICATE_VC_TYPE],
+        issuer: did_icp(issuer),
+        valid_from: content.issued_at.to_string(),
+        credential_subject: CertificateCredentialSubject {
+            id: did_icp(&certificate.user_principal),
+            certificate: CertificateClaims {
+                version,
+                name: content.name,
+                issued_at: content.issued_at.to_string(),
+                sport_category: content.sport_category,
+                notes: content.notes,
+                file_uri: content.file_uri,
+                file_hash: content.file_hash.map(|file_hash| file_hash.to_string()),
+                external_id: content.external_id,
+                issuer_full_name: content.issuer_full_name,
+                issuer_club_name: content.issuer_club_name,
+            },
+        },
+    };
+
+    serde_json::to_string(&credential).unwrap()
+}
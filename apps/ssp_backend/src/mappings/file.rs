@@ -0,0 +1,31 @@
+use ssp_backend_types::{
+    CommitUploadResponse, CreateUploadResponse, GetFileChunkResponse, GetFileResponse,
+};
+
+use crate::repositories::{File, FileHash, UploadId};
+
+pub fn map_create_upload_response(upload_id: UploadId) -> CreateUploadResponse {
+    CreateUploadResponse {
+        upload_id: upload_id.to_string(),
+    }
+}
+
+pub fn map_commit_upload_response(file_hash: FileHash) -> CommitUploadResponse {
+    CommitUploadResponse {
+        file_hash: file_hash.to_string(),
+    }
+}
+
+pub fn map_get_file_response(file: File) -> GetFileResponse {
+    GetFileResponse {
+        content_type: file.content_type,
+        size: file.size,
+        chunks_count: file.chunks_count,
+    }
+}
+
+pub fn map_get_file_chunk_response(content: Vec<u8>) -> GetFileChunkResponse {
+    GetFileChunkResponse {
+        content: content.into(),
+    }
+}
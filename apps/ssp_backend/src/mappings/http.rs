@@ -0,0 +1,125 @@
+use serde::Serialize;
+
+use crate::repositories::{Certificate, CertificateId, CertificateVersion};
+
+#[derive(Serialize)]
+struct CertificateJson<'a> {
+    id: String,
+    version: CertificateVersion,
+    certificate: &'a Certificate,
+}
+
+pub fn map_certificate_json(
+    id: CertificateId,
+    version: CertificateVersion,
+    certificate: &Certificate,
+) -> Result<Vec<u8>, String> {
+    serde_json::to_vec(&CertificateJson {
+        id: id.to_string(),
+        version,
+        certificate,
+    })
+    .map_err(|e| e.to_string())
+}
+
+pub fn map_certificate_html(
+    id: CertificateId,
+    version: CertificateVersion,
+    certificate: &Certificate,
+) -> String {
+    let id = id.to_string();
+    let content = &certificate.content;
+
+    let mut details = vec![
+        ("Sport category", Some(content.sport_category.clone())),
+        ("Issued at", Some(content.issued_at.to_string())),
+        ("Issuer", content.issuer_full_name.clone()),
+        ("Issuer club", content.issuer_club_name.clone()),
+        (
+            "Registered issuer",
+            certificate.issuer.as_ref().map(|issuer| {
+                if issuer.verified {
+                    format!("{} (verified)", issuer.name)
+                } else {
+                    format!("{} (not verified)", issuer.name)
+                }
+            }),
+        ),
+        ("External id", content.external_id.clone()),
+        ("Notes", content.notes.clone()),
+        (
+            "File hash",
+            content.file_hash.map(|file_hash| file_hash.to_string()),
+        ),
+        ("Owner", Some(certificate.user_principal.to_text())),
+        ("Certificate id", Some(id.clone())),
+        ("Version", Some(version.to_string())),
+    ];
+    if let Some(revocation) = &certificate.revocation {
+        details.push(("Revoked at", Some(revocation.revoked_at.to_string())));
+        details.push(("Revocation reason", Some(revocation.reason.clone())));
+    }
+    let details: String = details
+        .into_iter()
+        .filter_map(|(term, description)| {
+            description.map(|description| {
+                format!("<dt>{}</dt><dd>{}</dd>", term, escape_html(&description))
+            })
+        })
+        .collect();
+
+    let status = if certificate.is_revoked() {
+        "<p><strong>This certificate has been revoked.</strong></p>"
+    } else {
+        ""
+    };
+    let image = match &content.file_uri {
+        Some(file_uri) if file_uri.starts_with("data:image/") => format!(
+            "<img src=\"{}\" alt=\"{}\">",
+            escape_html(file_uri),
+            escape_html(&content.name)
+        ),
+        _ => String::new(),
+    };
+
+    format!(
+        "<!DOCTYPE html>\
+<html lang=\"en\">\
+<head>\
+<meta charset=\"utf-8\">\
+<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\
+<title>{name}</title>\
+</head>\
+<body>\
+<main>\
+<h1>{name}</h1>\
+{status}\
+{image}\
+<dl>{details}</dl>\
+<p><a href=\"/certificates/{id}.json\">JSON</a></p>\
+</main>\
+</body>\
+</html>",
+        name = escape_html(&content.name),
+        status = status,
+        image = image,
+        details = details,
+        id = id,
+    )
+}
+
+fn escape_html(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+
+    escaped
+}
@@ -0,0 +1,53 @@
+use candid::Nat;
+use serde_bytes::ByteBuf;
+use ssp_backend_types::{BlockWithId, GetBlocksResult, Icrc3DataCertificate};
+
+use crate::repositories::{BlockIndex, CertificateBlock, Icrc3Value, LastBlockWithCertification};
+
+impl From<Icrc3Value> for ssp_backend_types::Icrc3Value {
+    fn from(value: Icrc3Value) -> Self {
+        match value {
+            Icrc3Value::Blob(bytes) => Self::Blob(ByteBuf::from(bytes)),
+            Icrc3Value::Text(text) => Self::Text(text),
+            Icrc3Value::Nat(nat) => Self::Nat(Nat::from(nat)),
+            Icrc3Value::Array(values) => Self::Array(values.into_iter().map(Into::into).collect()),
+            Icrc3Value::Map(entries) => Self::Map(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (key, value.into()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// Saturates at [u64::MAX], as there can't be that many blocks or tokens anyway.
+pub fn nat_to_u64(nat: &Nat) -> u64 {
+    u64::try_from(&nat.0).unwrap_or(u64::MAX)
+}
+
+pub fn map_get_blocks_result(
+    log_length: u64,
+    blocks: Vec<(BlockIndex, CertificateBlock)>,
+) -> GetBlocksResult {
+    GetBlocksResult {
+        log_length: Nat::from(log_length),
+        blocks: blocks
+            .into_iter()
+            .map(|(index, block)| BlockWithId {
+                id: Nat::from(index),
+                block: block.to_value().into(),
+            })
+            .collect(),
+        archived_blocks: vec![],
+    }
+}
+
+pub fn map_icrc3_data_certificate(
+    certification: LastBlockWithCertification,
+) -> Icrc3DataCertificate {
+    Icrc3DataCertificate {
+        certificate: ByteBuf::from(certification.ic_certificate),
+        hash_tree: ByteBuf::from(certification.ic_certificate_witness),
+    }
+}
@@ -0,0 +1,133 @@
+use candid::{Nat, Principal};
+use ssp_backend_types::{
+    Account, CollectionMetadata, Icrc3Value, TokenMetadata, DEFAULT_ICRC7_TAKE_VALUE,
+    ICRC7_COLLECTION_NAME, ICRC7_COLLECTION_SYMBOL, MAX_ICRC7_QUERY_BATCH_SIZE,
+    MAX_ICRC7_TAKE_VALUE,
+};
+
+use crate::repositories::{Certificate, CertificateId};
+
+const ICRC7_COLLECTION_DESCRIPTION: &str =
+    "Sport certificates, held as soulbound tokens by the athletes they were issued to.";
+
+/// The token id of a certificate is its UUID, as a 128-bit number.
+pub fn map_certificate_token_id(id: CertificateId) -> Nat {
+    Nat::from(u128::from(id))
+}
+
+/// Returns `None` if the token id is too large to be a certificate id.
+pub fn map_token_id_to_certificate_id(token_id: &Nat) -> Option<CertificateId> {
+    u128::try_from(&token_id.0).ok().map(CertificateId::from)
+}
+
+pub fn map_certificate_token_owner(certificate: &Certificate) -> Account {
+    Account {
+        owner: certificate.user_principal,
+        subaccount: None,
+    }
+}
+
+pub fn map_collection_metadata(total_supply: u64) -> CollectionMetadata {
+    vec![
+        (
+            "icrc7:name".to_string(),
+            Icrc3Value::Text(ICRC7_COLLECTION_NAME.to_string()),
+        ),
+        (
+            "icrc7:symbol".to_string(),
+            Icrc3Value::Text(ICRC7_COLLECTION_SYMBOL.to_string()),
+        ),
+        (
+            "icrc7:description".to_string(),
+            Icrc3Value::Text(ICRC7_COLLECTION_DESCRIPTION.to_string()),
+        ),
+        (
+            "icrc7:total_supply".to_string(),
+            Icrc3Value::Nat(Nat::from(total_supply)),
+        ),
+        (
+            "icrc7:max_query_batch_size".to_string(),
+            Icrc3Value::Nat(Nat::from(MAX_ICRC7_QUERY_BATCH_SIZE)),
+        ),
+        (
+            "icrc7:default_take_value".to_string(),
+            Icrc3Value::Nat(Nat::from(DEFAULT_ICRC7_TAKE_VALUE)),
+        ),
+        (
+            "icrc7:max_take_value".to_string(),
+            Icrc3Value::Nat(Nat::from(MAX_ICRC7_TAKE_VALUE)),
+        ),
+    ]
+}
+
+/// The `icrc7:url` is the page of the certificate served by the canister,
+/// which can be read once the certificate is public. The `icrc7:image` is set
+/// if the file of the certificate is an inline image.
+pub fn map_certificate_token_metadata(
+    canister_id: &Principal,
+    id: CertificateId,
+    certificate: Certificate,
+) -> TokenMetadata {
+    let content = certificate.content;
+    let description = match &content.issuer_club_name {
+        Some(issuer_club_name) => format!(
+            "{} certificate issued by {}",
+            content.sport_category, issuer_club_name
+        ),
+        None => format!("{} certificate", content.sport_category),
+    };
+    let image = content
+        .file_uri
+        .clone()
+        .filter(|file_uri| file_uri.starts_with("data:image/"));
+    let mut metadata = vec![
+        ("icrc7:name", Icrc3Value::Text(content.name.clone())),
+        ("icrc7:description", Icrc3Value::Text(description)),
+        (
+            "icrc7:url",
+            Icrc3Value::Text(format!(
+                "https://{}.icp0.io/certificates/{}",
+                canister_id.to_text(),
+                id.to_string()
+            )),
+        ),
+        ("ssp:certificate_id", Icrc3Value::Text(id.to_string())),
+        ("ssp:name", Icrc3Value::Text(content.name)),
+        (
+            "ssp:issued_at",
+            Icrc3Value::Text(content.issued_at.to_string()),
+        ),
+        (
+            "ssp:sport_category",
+            Icrc3Value::Text(content.sport_category),
+        ),
+    ];
+    let optional_metadata = [
+        ("icrc7:image", image),
+        ("ssp:notes", content.notes),
+        ("ssp:file_uri", content.file_uri),
+        (
+            "ssp:file_hash",
+            content.file_hash.map(|file_hash| file_hash.to_string()),
+        ),
+        ("ssp:external_id", content.external_id),
+        ("ssp:issuer_full_name", content.issuer_full_name),
+        ("ssp:issuer_club_name", content.issuer_club_name),
+        (
+            "ssp:revoked_at",
+            certificate
+                .revocation
+                .map(|revocation| revocation.revoked_at.to_string()),
+        ),
+    ];
+    metadata.extend(
+        optional_metadata
+            .into_iter()
+            .filter_map(|(key, value)| value.map(|value| (key, Icrc3Value::Text(value)))),
+    );
+
+    metadata
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), value))
+        .collect()
+}
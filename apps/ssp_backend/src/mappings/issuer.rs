@@ -0,0 +1,30 @@
+use ssp_backend_types::CreateIssuerResponse;
+
+use crate::repositories::{CertificateIssuer, Issuer, IssuerId};
+
+pub fn map_issuer(id: IssuerId, issuer: Issuer) -> ssp_backend_types::Issuer {
+    ssp_backend_types::Issuer {
+        id: id.to_string(),
+        name: issuer.name,
+        admins: issuer.admins,
+        coaches: issuer.coaches,
+        verified_at: issuer
+            .verified_at
+            .map(|verified_at| verified_at.to_string()),
+        created_at: issuer.created_at.to_string(),
+    }
+}
+
+pub fn map_create_issuer_response(id: IssuerId) -> CreateIssuerResponse {
+    CreateIssuerResponse { id: id.to_string() }
+}
+
+impl From<CertificateIssuer> for ssp_backend_types::CertificateIssuer {
+    fn from(value: CertificateIssuer) -> Self {
+        ssp_backend_types::CertificateIssuer {
+            id: value.id.to_string(),
+            name: value.name,
+            verified: value.verified,
+        }
+    }
+}
@@ -1,5 +1,24 @@
+mod achievement;
+mod audit_log;
 mod certificate;
 mod config;
+mod credential;
+mod file;
+mod http;
+mod icrc3;
+mod icrc7;
+mod issuer;
+mod open_badge;
 mod user;
 
+pub use achievement::*;
+pub use audit_log::*;
 pub use certificate::*;
+pub use credential::*;
+pub use file::*;
+pub use http::*;
+pub use icrc3::*;
+pub use icrc7::*;
+pub use issuer::*;
+pub use open_badge::*;
+pub use user::*;
@@ -0,0 +1,118 @@
+use candid::Principal;
+use serde::Serialize;
+
+use super::credential::{did_icp, VC_CONTEXT_V2, VC_TYPE};
+use crate::repositories::{Achievement, AchievementId, Certificate, CertificateId};
+
+const OPEN_BADGES_CONTEXT_V3: &str = "https://purl.imsglobal.org/spec/ob/v3p0/context-3.0.3.json";
+const OPEN_BADGE_CREDENTIAL_TYPE: &str = "OpenBadgeCredential";
+const PROFILE_TYPE: &str = "Profile";
+const ACHIEVEMENT_SUBJECT_TYPE: &str = "AchievementSubject";
+const ACHIEVEMENT_TYPE: &str = "Achievement";
+
+/// A certificate as an Open Badges 3.0 credential, see https://www.imsglobal.org/spec/ob/v3p0/.
+///
+/// The fields are serialized in the order in which they are declared,
+/// so that the same certificate version always results in the same credential.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct OpenBadgeCredential {
+    #[serde(rename = "@context")]
+    context: Vec<&'static str>,
+    id: String,
+    #[serde(rename = "type")]
+    credential_type: Vec<&'static str>,
+    name: String,
+    issuer: Profile,
+    valid_from: String,
+    credential_subject: AchievementSubject,
+}
+
+#[derive(Serialize)]
+struct Profile {
+    id: String,
+    #[serde(rename = "type")]
+    profile_type: Vec<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+}
+
+#[derive(Serialize)]
+struct AchievementSubject {
+    id: String,
+    #[serde(rename = "type")]
+    subject_type: Vec<&'static str>,
+    achievement: OpenBadgeAchievement,
+}
+
+#[derive(Serialize)]
+struct OpenBadgeAchievement {
+    id: String,
+    #[serde(rename = "type")]
+    achievement_type: Vec<&'static str>,
+    name: String,
+    description: String,
+    criteria: Criteria,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    creator: Option<Profile>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tag: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct Criteria {
+    narrative: String,
+}
+
+fn urn_uuid(id: &impl ToString) -> String {
+    format!("urn:uuid:{}", id.to_string())
+}
+
+/// Maps the certificate to the Open Badges credential of the achievement it awards,
+/// issued by the canister.
+///
+/// The achievement is created by the registered issuer of the certificate, if it has one.
+pub fn map_certificate_open_badge(
+    issuer: &Principal,
+    id: CertificateId,
+    certificate: &Certificate,
+    achievement_id: AchievementId,
+    achievement: &Achievement,
+) -> String {
+    let credential = OpenBadgeCredential {
+        context: vec![VC_CONTEXT_V2, OPEN_BADGES_CONTEXT_V3],
+        id: urn_uuid(&id),
+        credential_type: vec![VC_TYPE, OPEN_BADGE_CREDENTIAL_TYPE],
+        name: certificate.content.name.clone(),
+        issuer: Profile {
+            id: did_icp(issuer),
+            profile_type: vec![PROFILE_TYPE],
+            name: None,
+        },
+        valid_from: certificate.content.issued_at.to_string(),
+        credential_subject: AchievementSubject {
+            id: did_icp(&certificate.user_principal),
+            subject_type: vec![ACHIEVEMENT_SUBJECT_TYPE],
+            achievement: OpenBadgeAchievement {
+                id: urn_uuid(&achievement_id),
+                achievement_type: vec![ACHIEVEMENT_TYPE],
+                name: achievement.name.clone(),
+                description: achievement.description.clone(),
+                criteria: Criteria {
+                    narrative: achievement.criteria.clone(),
+                },
+                creator: achievement
+                    .issuer_id
+                    .and(certificate.issuer.as_ref())
+                    .map(|issuer| Profile {
+                        id: urn_uuid(&issuer.id),
+                        profile_type: vec![PROFILE_TYPE],
+                        name: Some(issuer.name.clone()),
+                    }),
+                tag: achievement.sport_category.iter().cloned().collect(),
+            },
+        },
+    };
+
+    serde_json::to_string(&credential).unwrap()
+}
@@ -1,4 +1,4 @@
-use crate::repositories::User;
+use crate::repositories::{ManagedUser, ManagedUserId, User, UserRole};
 
 impl From<User> for ssp_backend_types::User {
     fn from(user: User) -> Self {
@@ -6,6 +6,33 @@ impl From<User> for ssp_backend_types::User {
             sub: user.jwt_sub,
             db_id: user.db_id.to_string(),
             created_at: user.created_at.to_string(),
+            roles: user.roles().iter().map(|role| (*role).into()).collect(),
         }
     }
 }
+
+impl From<UserRole> for ssp_backend_types::UserRole {
+    fn from(role: UserRole) -> Self {
+        match role {
+            UserRole::Athlete => Self::Athlete,
+            UserRole::Coach => Self::Coach,
+            UserRole::ClubAdmin => Self::ClubAdmin,
+            UserRole::Admin => Self::Admin,
+        }
+    }
+}
+
+pub fn map_managed_user(
+    id: ManagedUserId,
+    managed_user: ManagedUser,
+) -> ssp_backend_types::ManagedUser {
+    ssp_backend_types::ManagedUser {
+        db_id: id.to_string(),
+        guardians: managed_user.guardians,
+        created_at: managed_user.created_at.to_string(),
+        claimed_by: managed_user.claimed_by,
+        claimed_at: managed_user
+            .claimed_at
+            .map(|claimed_at| claimed_at.to_string()),
+    }
+}
@@ -0,0 +1,39 @@
+use std::cell::RefCell;
+
+use super::{init_achievements, Achievement, AchievementId, AchievementMemory};
+
+struct AchievementState {
+    achievements: AchievementMemory,
+}
+
+impl Default for AchievementState {
+    fn default() -> Self {
+        Self {
+            achievements: init_achievements(),
+        }
+    }
+}
+
+thread_local! {
+    static STATE: RefCell<AchievementState> = RefCell::new(AchievementState::default());
+}
+
+#[derive(Default)]
+pub struct AchievementRepository {}
+
+impl AchievementRepository {
+    pub fn get_achievement(&self, id: &AchievementId) -> Option<Achievement> {
+        STATE.with_borrow(|s| s.achievements.get(id))
+    }
+
+    pub async fn create_achievement(
+        &self,
+        achievement: Achievement,
+    ) -> Result<AchievementId, String> {
+        let id = AchievementId::new().await?;
+
+        STATE.with_borrow_mut(|s| s.achievements.insert(id, achievement));
+
+        Ok(id)
+    }
+}
@@ -0,0 +1,137 @@
+use std::cell::RefCell;
+
+use ic_certification::{AsHashTree, Hash, RbTree};
+
+use crate::utils::cbor_serialize;
+
+use super::{
+    init_audit_log, AuditLogEntry, AuditLogIndex, AuditLogMemory, CertifiedDataRepository,
+    AUDIT_LOG_GENESIS_HASH,
+};
+
+const AUDIT_LOG_TREE_LABEL: &[u8] = b"audit_log";
+const LENGTH_LABEL: &[u8] = b"length";
+const TIP_LABEL: &[u8] = b"tip";
+
+/// Audit log tree structure:
+/// audit_log
+/// ├── length
+/// │   └── number of entries (big endian u64)
+/// └── tip
+///     └── hash of the last entry
+type AuditLogTree = RbTree<&'static [u8], Vec<u8>>;
+
+struct AuditLogState {
+    audit_log: AuditLogMemory,
+    tree: AuditLogTree,
+}
+
+impl Default for AuditLogState {
+    fn default() -> Self {
+        Self {
+            audit_log: init_audit_log(),
+            tree: RbTree::new(),
+        }
+    }
+}
+
+impl AuditLogState {
+    fn tip_hash(&self) -> Hash {
+        match self.audit_log.len().checked_sub(1) {
+            Some(last_index) => self.audit_log.get(last_index).unwrap().hash(),
+            None => AUDIT_LOG_GENESIS_HASH,
+        }
+    }
+
+    fn update_tree(&mut self) -> Hash {
+        let length = self.audit_log.len();
+        let tip_hash = self.tip_hash();
+        self.tree
+            .insert(LENGTH_LABEL, length.to_be_bytes().to_vec());
+        self.tree.insert(TIP_LABEL, tip_hash.to_vec());
+
+        self.tree.root_hash()
+    }
+}
+
+thread_local! {
+    static STATE: RefCell<AuditLogState> = RefCell::new(AuditLogState::default());
+}
+
+pub struct AuditLogTipWithCertification {
+    pub length: u64,
+    pub tip_hash: Hash,
+    pub ic_certificate: Vec<u8>,
+    pub ic_certificate_witness: Vec<u8>,
+}
+
+#[derive(Default)]
+pub struct AuditLogRepository {
+    certified_data_repository: CertifiedDataRepository,
+}
+
+impl AuditLogRepository {
+    /// Returns the hash of the last entry, or [AUDIT_LOG_GENESIS_HASH] if the log is empty.
+    pub fn get_tip_hash(&self) -> Hash {
+        STATE.with_borrow(|s| s.tip_hash())
+    }
+
+    /// Returns up to `limit` entries, starting from the entry at `start`.
+    pub fn get_entries(
+        &self,
+        start: AuditLogIndex,
+        limit: u64,
+    ) -> Vec<(AuditLogIndex, AuditLogEntry)> {
+        STATE.with_borrow(|s| {
+            let end = start.saturating_add(limit).min(s.audit_log.len());
+            (start..end)
+                .filter_map(|index| s.audit_log.get(index).map(|entry| (index, entry)))
+                .collect()
+        })
+    }
+
+    /// Appends the entry, which must be chained to the current tip,
+    /// and certifies the new tip.
+    pub fn append_entry(&self, entry: AuditLogEntry) -> Result<AuditLogIndex, String> {
+        let (index, tree_hash) = STATE.with_borrow_mut(|s| {
+            if entry.previous_hash != s.tip_hash() {
+                return Err("Audit log entry is not chained to the tip".to_string());
+            }
+
+            let index = s
+                .audit_log
+                .append(&entry)
+                .map_err(|e| format!("Failed to append audit log entry: {:?}", e))?;
+
+            Ok((index, s.update_tree()))
+        })?;
+
+        self.certified_data_repository
+            .set_subtree_root_hash(AUDIT_LOG_TREE_LABEL, tree_hash);
+
+        Ok(index)
+    }
+
+    /// Certifies the current tip, e.g. after an upgrade.
+    pub fn certify_tip(&self) {
+        let tree_hash = STATE.with_borrow_mut(|s| s.update_tree());
+
+        self.certified_data_repository
+            .set_subtree_root_hash(AUDIT_LOG_TREE_LABEL, tree_hash);
+    }
+
+    pub fn get_tip_with_certification(&self) -> AuditLogTipWithCertification {
+        let (length, tip_hash, witness) =
+            STATE.with_borrow(|s| (s.audit_log.len(), s.tip_hash(), s.tree.as_hash_tree()));
+        let tree = self
+            .certified_data_repository
+            .witness(AUDIT_LOG_TREE_LABEL, witness);
+
+        AuditLogTipWithCertification {
+            length,
+            tip_hash,
+            ic_certificate: ic_cdk::api::data_certificate().expect("No data certificate available"),
+            ic_certificate_witness: cbor_serialize(&tree).unwrap(),
+        }
+    }
+}
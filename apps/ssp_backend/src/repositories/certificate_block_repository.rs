@@ -0,0 +1,116 @@
+use std::{borrow::Cow, cell::RefCell};
+
+use ic_certification::{leaf, leaf_hash, merge_hash_trees, Hash};
+
+use crate::utils::cbor_serialize;
+
+use super::{
+    init_certificate_blocks, leb128_encode, BlockIndex, CertificateBlock, CertificateBlockMemory,
+    CertifiedDataRepository,
+};
+
+/// The ICRC-3 labels are leaves at the root of the certified data tree, as required by the standard.
+const LAST_BLOCK_INDEX_LABEL: &[u8] = b"last_block_index";
+const LAST_BLOCK_HASH_LABEL: &[u8] = b"last_block_hash";
+
+struct CertificateBlockState {
+    blocks: CertificateBlockMemory,
+}
+
+impl Default for CertificateBlockState {
+    fn default() -> Self {
+        Self {
+            blocks: init_certificate_blocks(),
+        }
+    }
+}
+
+impl CertificateBlockState {
+    fn last_block(&self) -> Option<(BlockIndex, Hash)> {
+        let last_index = self.blocks.len().checked_sub(1)?;
+
+        self.blocks
+            .get(last_index)
+            .map(|block| (last_index, block.hash()))
+    }
+}
+
+thread_local! {
+    static STATE: RefCell<CertificateBlockState> = RefCell::new(CertificateBlockState::default());
+}
+
+pub struct LastBlockWithCertification {
+    pub ic_certificate: Vec<u8>,
+    pub ic_certificate_witness: Vec<u8>,
+}
+
+#[derive(Default)]
+pub struct CertificateBlockRepository {
+    certified_data_repository: CertifiedDataRepository,
+}
+
+impl CertificateBlockRepository {
+    pub fn get_blocks_count(&self) -> u64 {
+        STATE.with_borrow(|s| s.blocks.len())
+    }
+
+    pub fn get_last_block_hash(&self) -> Option<Hash> {
+        STATE.with_borrow(|s| s.last_block().map(|(_, hash)| hash))
+    }
+
+    /// Returns up to `limit` blocks, starting from the block at `start`.
+    pub fn get_blocks(&self, start: BlockIndex, limit: u64) -> Vec<(BlockIndex, CertificateBlock)> {
+        STATE.with_borrow(|s| {
+            let end = start.saturating_add(limit).min(s.blocks.len());
+            (start..end)
+                .filter_map(|index| s.blocks.get(index).map(|block| (index, block)))
+                .collect()
+        })
+    }
+
+    /// Appends the block, whose parent must be the last block, and certifies it.
+    pub fn append_block(&self, block: CertificateBlock) -> Result<BlockIndex, String> {
+        let index = STATE.with_borrow_mut(|s| {
+            if block.parent_hash != s.last_block().map(|(_, hash)| hash) {
+                return Err("Block parent hash does not match the last block".to_string());
+            }
+
+            s.blocks
+                .append(&block)
+                .map_err(|e| format!("Failed to append block: {:?}", e))
+        })?;
+
+        self.certify_last_block();
+
+        Ok(index)
+    }
+
+    /// Certifies the last block, e.g. after an upgrade. Does nothing if there are no blocks.
+    pub fn certify_last_block(&self) {
+        if let Some((index, hash)) = STATE.with_borrow(|s| s.last_block()) {
+            self.certified_data_repository
+                .set_subtree_root_hash(LAST_BLOCK_INDEX_LABEL, leaf_hash(&leb128_encode(index)));
+            self.certified_data_repository
+                .set_subtree_root_hash(LAST_BLOCK_HASH_LABEL, leaf_hash(&hash));
+        }
+    }
+
+    /// Returns the certification of the last block, or `None` if there are no blocks.
+    pub fn get_last_block_certification(&self) -> Option<LastBlockWithCertification> {
+        let (index, hash) = STATE.with_borrow(|s| s.last_block())?;
+
+        let tree = merge_hash_trees(
+            self.certified_data_repository.witness(
+                LAST_BLOCK_INDEX_LABEL,
+                leaf(Cow::from(leb128_encode(index))),
+            ),
+            self.certified_data_repository
+                .witness(LAST_BLOCK_HASH_LABEL, leaf(Cow::from(hash.to_vec()))),
+        );
+
+        Some(LastBlockWithCertification {
+            ic_certificate: ic_cdk::api::data_certificate().expect("No data certificate available"),
+            ic_certificate_witness: cbor_serialize(&tree).unwrap(),
+        })
+    }
+}
@@ -1,33 +1,73 @@
-use std::cell::RefCell;
+use std::{
+    cell::RefCell,
+    ops::{Bound, RangeBounds},
+};
 
 use candid::Principal;
-use ic_cdk::println;
-use ic_certification::{labeled, labeled_hash, AsHashTree, Hash, RbTree};
-use ic_stable_structures::Storable;
-use serde::Serialize;
-use serde_cbor::Serializer;
+use ic_certification::{AsHashTree, Hash, RbTree};
+use ic_stable_structures::{BTreeMap, Memory, Storable};
+
+use crate::utils::{batch_cursor, cbor_serialize, is_batch_instructions_limit_reached, take_batch};
 
 const SSP_CERTIFICATES_TREE_LABEL: &[u8] = b"ssp_certificates";
 
+/// The maximum number of certificates read from an index to fill a page of certificates.
+const MAX_CERTIFICATES_PAGE_SCAN: usize = 1_000;
+
 use super::{
-    init_certificate_managed_user_id_index, init_certificate_user_principal_index,
-    init_certificates, Certificate, CertificateId, CertificateManagedUserIdIndexMemory,
-    CertificateManagedUserIdKey, CertificateManagedUserIdRange, CertificateMemory,
-    CertificateUserPrincipalIndexMemory, CertificateUserPrincipalKey,
-    CertificateUserPrincipalRange, Uuid,
+    init_certificate_facet_index, init_certificate_file_hash_index,
+    init_certificate_issuer_principal_created_at_index,
+    init_certificate_issuer_principal_issued_at_index,
+    init_certificate_managed_user_id_created_at_index, init_certificate_managed_user_id_index,
+    init_certificate_managed_user_id_issued_at_index, init_certificate_sharing,
+    init_certificate_user_principal_created_at_index, init_certificate_user_principal_index,
+    init_certificate_user_principal_issued_at_index, init_certificate_versions, init_certificates,
+    Certificate, CertificateCursor, CertificateFacet, CertificateFacetIndexMemory,
+    CertificateFacetKey, CertificateFacetRange, CertificateFileHashIndexMemory,
+    CertificateFileHashKey, CertificateFileHashRange, CertificateFilters, CertificateId,
+    CertificateManagedUserIdIndexMemory, CertificateManagedUserIdKey,
+    CertificateManagedUserIdRange, CertificateManagedUserIdSortIndexMemory,
+    CertificateManagedUserIdSortKey, CertificateManagedUserIdSortRange, CertificateMemory,
+    CertificateOwner, CertificateSharing, CertificateSharingMemory, CertificateSort,
+    CertificateSortBounds, CertificateSortField, CertificateUserPrincipalIndexMemory,
+    CertificateUserPrincipalKey, CertificateUserPrincipalRange,
+    CertificateUserPrincipalSortIndexMemory, CertificateUserPrincipalSortKey,
+    CertificateUserPrincipalSortRange, CertificateVersion, CertificateVersionKey,
+    CertificateVersionRange, CertificateVersionsMemory, CertifiedDataRepository, DateTime,
+    FileHash, ManagedUserId, SortOrder, UserPrincipal, FIRST_CERTIFICATE_VERSION,
 };
 
+/// The label of a certificate version in the [IcCertificateTree],
+/// i.e. the big endian bytes of the version number.
+type CertificateVersionLabel = [u8; 4];
+
 /// SSP certificates tree structure:
 /// ssp_certificates
 /// └── <user_principal>
 ///     └── <certificate_id>
-///         └── certificate cbor data hash
-type IcCertificateTree = RbTree<Principal, RbTree<CertificateId, Hash>>;
+///         └── <version>
+///             └── certificate version cbor data hash
+type IcCertificateTree =
+    RbTree<Principal, RbTree<CertificateId, RbTree<CertificateVersionLabel, Hash>>>;
 
 struct CertificateState {
+    /// The latest version of each certificate.
     certificates: CertificateMemory,
+    /// The previous versions of each certificate.
+    certificate_versions: CertificateVersionsMemory,
     certificate_user_principal_index: CertificateUserPrincipalIndexMemory,
     certificate_managed_user_id_index: CertificateManagedUserIdIndexMemory,
+    certificate_user_principal_created_at_index: CertificateUserPrincipalSortIndexMemory,
+    certificate_user_principal_issued_at_index: CertificateUserPrincipalSortIndexMemory,
+    certificate_managed_user_id_created_at_index: CertificateManagedUserIdSortIndexMemory,
+    certificate_managed_user_id_issued_at_index: CertificateManagedUserIdSortIndexMemory,
+    certificate_issuer_principal_created_at_index: CertificateUserPrincipalSortIndexMemory,
+    certificate_issuer_principal_issued_at_index: CertificateUserPrincipalSortIndexMemory,
+    certificate_facet_index: CertificateFacetIndexMemory,
+    /// The sharing settings of the certificates that have been shared.
+    certificate_sharing: CertificateSharingMemory,
+    /// The certificates that reference each file, in any of their versions.
+    certificate_file_hash_index: CertificateFileHashIndexMemory,
     ic_certificate_tree: IcCertificateTree,
 }
 
@@ -35,88 +75,555 @@ impl Default for CertificateState {
     fn default() -> Self {
         Self {
             certificates: init_certificates(),
+            certificate_versions: init_certificate_versions(),
             certificate_user_principal_index: init_certificate_user_principal_index(),
             certificate_managed_user_id_index: init_certificate_managed_user_id_index(),
+            certificate_user_principal_created_at_index:
+                init_certificate_user_principal_created_at_index(),
+            certificate_user_principal_issued_at_index:
+                init_certificate_user_principal_issued_at_index(),
+            certificate_managed_user_id_created_at_index:
+                init_certificate_managed_user_id_created_at_index(),
+            certificate_managed_user_id_issued_at_index:
+                init_certificate_managed_user_id_issued_at_index(),
+            certificate_issuer_principal_created_at_index:
+                init_certificate_issuer_principal_created_at_index(),
+            certificate_issuer_principal_issued_at_index:
+                init_certificate_issuer_principal_issued_at_index(),
+            certificate_facet_index: init_certificate_facet_index(),
+            certificate_sharing: init_certificate_sharing(),
+            certificate_file_hash_index: init_certificate_file_hash_index(),
             ic_certificate_tree: RbTree::new(),
         }
     }
 }
 
+impl CertificateState {
+    fn latest_version(&self, id: &CertificateId) -> Result<CertificateVersion, String> {
+        let range = CertificateVersionRange::new(*id)?;
+        let previous_versions_count = self.certificate_versions.range(range).count();
+
+        Ok(FIRST_CERTIFICATE_VERSION + previous_versions_count as CertificateVersion)
+    }
+
+    fn user_principal_sort_index(
+        &self,
+        field: CertificateSortField,
+    ) -> &CertificateUserPrincipalSortIndexMemory {
+        match field {
+            CertificateSortField::CreatedAt => &self.certificate_user_principal_created_at_index,
+            CertificateSortField::IssuedAt => &self.certificate_user_principal_issued_at_index,
+        }
+    }
+
+    fn user_principal_sort_index_mut(
+        &mut self,
+        field: CertificateSortField,
+    ) -> &mut CertificateUserPrincipalSortIndexMemory {
+        match field {
+            CertificateSortField::CreatedAt => {
+                &mut self.certificate_user_principal_created_at_index
+            }
+            CertificateSortField::IssuedAt => &mut self.certificate_user_principal_issued_at_index,
+        }
+    }
+
+    fn managed_user_id_sort_index(
+        &self,
+        field: CertificateSortField,
+    ) -> &CertificateManagedUserIdSortIndexMemory {
+        match field {
+            CertificateSortField::CreatedAt => &self.certificate_managed_user_id_created_at_index,
+            CertificateSortField::IssuedAt => &self.certificate_managed_user_id_issued_at_index,
+        }
+    }
+
+    fn managed_user_id_sort_index_mut(
+        &mut self,
+        field: CertificateSortField,
+    ) -> &mut CertificateManagedUserIdSortIndexMemory {
+        match field {
+            CertificateSortField::CreatedAt => {
+                &mut self.certificate_managed_user_id_created_at_index
+            }
+            CertificateSortField::IssuedAt => &mut self.certificate_managed_user_id_issued_at_index,
+        }
+    }
+
+    fn issuer_principal_sort_index(
+        &self,
+        field: CertificateSortField,
+    ) -> &CertificateUserPrincipalSortIndexMemory {
+        match field {
+            CertificateSortField::CreatedAt => &self.certificate_issuer_principal_created_at_index,
+            CertificateSortField::IssuedAt => &self.certificate_issuer_principal_issued_at_index,
+        }
+    }
+
+    fn issuer_principal_sort_index_mut(
+        &mut self,
+        field: CertificateSortField,
+    ) -> &mut CertificateUserPrincipalSortIndexMemory {
+        match field {
+            CertificateSortField::CreatedAt => {
+                &mut self.certificate_issuer_principal_created_at_index
+            }
+            CertificateSortField::IssuedAt => {
+                &mut self.certificate_issuer_principal_issued_at_index
+            }
+        }
+    }
+
+    /// Adds the certificate to the index of the file it references, if any.
+    /// The certificate stays in the index of the files referenced by its previous versions.
+    fn add_to_file_hash_index(
+        &mut self,
+        id: CertificateId,
+        certificate: &Certificate,
+    ) -> Result<(), String> {
+        if let Some(file_hash) = certificate.content.file_hash {
+            self.certificate_file_hash_index
+                .insert(CertificateFileHashKey::new(file_hash, id)?, id);
+        }
+
+        Ok(())
+    }
+
+    /// Adds the certificate to all the sort and facet indexes.
+    fn add_to_sort_indexes(
+        &mut self,
+        id: CertificateId,
+        certificate: &Certificate,
+    ) -> Result<(), String> {
+        for field in CertificateSortField::ALL {
+            let timestamp = field.timestamp(certificate);
+
+            let key =
+                CertificateUserPrincipalSortKey::new(certificate.user_principal, timestamp, id)?;
+            self.user_principal_sort_index_mut(field).insert(key, id);
+
+            if let Some(managed_user_id) = certificate.managed_user_id {
+                let key = CertificateManagedUserIdSortKey::new(managed_user_id, timestamp, id)?;
+                self.managed_user_id_sort_index_mut(field).insert(key, id);
+            }
+
+            if let Some(issuer_principal) = certificate.issuer_principal {
+                let key = CertificateUserPrincipalSortKey::new(issuer_principal, timestamp, id)?;
+                self.issuer_principal_sort_index_mut(field).insert(key, id);
+            }
+        }
+
+        for key in facet_keys(id, certificate)? {
+            self.certificate_facet_index.insert(key, id);
+        }
+
+        Ok(())
+    }
+
+    /// Removes the certificate from all the sort and facet indexes.
+    fn remove_from_sort_indexes(
+        &mut self,
+        id: CertificateId,
+        certificate: &Certificate,
+    ) -> Result<(), String> {
+        for field in CertificateSortField::ALL {
+            let timestamp = field.timestamp(certificate);
+
+            let key =
+                CertificateUserPrincipalSortKey::new(certificate.user_principal, timestamp, id)?;
+            self.user_principal_sort_index_mut(field).remove(&key);
+
+            if let Some(managed_user_id) = certificate.managed_user_id {
+                let key = CertificateManagedUserIdSortKey::new(managed_user_id, timestamp, id)?;
+                self.managed_user_id_sort_index_mut(field).remove(&key);
+            }
+
+            if let Some(issuer_principal) = certificate.issuer_principal {
+                let key = CertificateUserPrincipalSortKey::new(issuer_principal, timestamp, id)?;
+                self.issuer_principal_sort_index_mut(field).remove(&key);
+            }
+        }
+
+        for key in facet_keys(id, certificate)? {
+            self.certificate_facet_index.remove(&key);
+        }
+
+        Ok(())
+    }
+
+    /// Collects at most `limit` certificates from the sorted `ids` that match the `filters`,
+    /// optionally keeping only the certificates owned by `only_user_principal`.
+    ///
+    /// At most [MAX_CERTIFICATES_PAGE_SCAN] certificates are read, so the page can have fewer
+    /// than `limit` certificates (even none) along with a cursor to continue after them,
+    /// e.g. when filtering by issue date while sorting by creation date.
+    fn certificates_page(
+        &self,
+        ids: impl Iterator<Item = CertificateId>,
+        sort_field: CertificateSortField,
+        limit: usize,
+        filters: &CertificateFilters,
+        only_user_principal: Option<Principal>,
+    ) -> Result<CertificatesPage, String> {
+        let mut certificates = Vec::with_capacity(limit);
+        let mut next_cursor = None;
+
+        for (scanned, id) in ids.enumerate() {
+            let certificate = self
+                .certificates
+                .get(&id)
+                .ok_or_else(|| format!("Indexed certificate {} does not exist", id.to_string()))?;
+            let cursor = || CertificateCursor {
+                timestamp: sort_field.timestamp(&certificate),
+                id,
+            };
+
+            // the next page starts with the first certificate that was not read
+            if scanned == MAX_CERTIFICATES_PAGE_SCAN {
+                next_cursor = Some(cursor());
+                break;
+            }
+
+            let is_owned = only_user_principal.map_or(true, |p| certificate.user_principal == p);
+            if !is_owned || !filters.matches(&certificate) {
+                continue;
+            }
+
+            // the certificate after the last one of the page is the first one of the next page
+            if certificates.len() == limit {
+                next_cursor = Some(cursor());
+                break;
+            }
+
+            certificates.push((id, certificate));
+        }
+
+        Ok(CertificatesPage {
+            certificates,
+            next_cursor,
+        })
+    }
+}
+
+/// Returns the keys of the certificate in the facet index,
+/// for each owner, facet value and sort field of the certificate.
+fn facet_keys(
+    id: CertificateId,
+    certificate: &Certificate,
+) -> Result<Vec<CertificateFacetKey>, String> {
+    let owners = std::iter::once(CertificateOwner::UserPrincipal(certificate.user_principal))
+        .chain(
+            certificate
+                .managed_user_id
+                .map(CertificateOwner::ManagedUserId),
+        )
+        .chain(
+            certificate
+                .issuer_principal
+                .map(CertificateOwner::IssuerPrincipal),
+        );
+
+    let mut keys = vec![];
+    for owner in owners {
+        for facet in CertificateFacet::ALL {
+            let Some(value) = facet.value(certificate) else {
+                continue;
+            };
+            let value_hash = CertificateFacet::value_hash(value);
+
+            for field in CertificateSortField::ALL {
+                keys.push(CertificateFacetKey::new(
+                    owner,
+                    facet,
+                    value_hash,
+                    field,
+                    field.timestamp(certificate),
+                    id,
+                )?);
+            }
+        }
+    }
+
+    Ok(keys)
+}
+
+/// Iterates over the certificate ids of the index range in the given order.
+fn sorted_ids<'a, K, M>(
+    index: &'a BTreeMap<K, CertificateId, M>,
+    range: impl RangeBounds<K>,
+    order: SortOrder,
+) -> Box<dyn Iterator<Item = CertificateId> + 'a>
+where
+    K: Storable + Ord + Clone + 'a,
+    M: Memory + 'a,
+{
+    match order {
+        SortOrder::Ascending => Box::new(index.range(range).map(|(_, id)| id)),
+        SortOrder::Descending => Box::new(index.range(range).rev().map(|(_, id)| id)),
+    }
+}
+
+/// The range of the ids that come after the `after` id, or of all the ids if not set.
+fn ids_after(after: Option<CertificateId>) -> (Bound<CertificateId>, Bound<CertificateId>) {
+    (
+        after.map_or(Bound::Unbounded, Bound::Excluded),
+        Bound::Unbounded,
+    )
+}
+
 thread_local! {
     static STATE: RefCell<CertificateState> = RefCell::new(CertificateState::default());
 }
 
 pub struct UserCertificateWithCertification {
-    pub certificate: Option<Certificate>,
+    pub certificate: Certificate,
+    pub version: CertificateVersion,
+    pub latest_version: CertificateVersion,
     pub ic_certificate: Vec<u8>,
     pub ic_certificate_witness: Vec<u8>,
 }
 
+#[derive(Default)]
+pub struct CertificatesPage {
+    pub certificates: Vec<(CertificateId, Certificate)>,
+    pub next_cursor: Option<CertificateCursor>,
+}
+
 fn ic_certificate() -> Vec<u8> {
     ic_cdk::api::data_certificate().expect("No data certificate available")
 }
 
 #[derive(Default)]
-pub struct CertificateRepository {}
+pub struct CertificateRepository {
+    certified_data_repository: CertifiedDataRepository,
+}
 
 impl CertificateRepository {
-    pub fn get_certificate(&self, id: &CertificateId) -> UserCertificateWithCertification {
-        match STATE.with_borrow(|s| s.certificates.get(id)) {
-            Some(certificate) => {
+    /// Returns the latest version of the certificate.
+    pub fn get_certificate(&self, id: &CertificateId) -> Option<Certificate> {
+        STATE.with_borrow(|s| s.certificates.get(id))
+    }
+
+    /// Returns the latest version of the certificate, along with its version number.
+    pub fn get_latest_certificate_version(
+        &self,
+        id: &CertificateId,
+    ) -> Result<Option<(Certificate, CertificateVersion)>, String> {
+        STATE.with_borrow(|s| match s.certificates.get(id) {
+            Some(certificate) => Ok(Some((certificate, s.latest_version(id)?))),
+            None => Ok(None),
+        })
+    }
+
+    /// Returns the requested version of the certificate, or the latest one if `version` is `None`.
+    pub fn get_certificate_with_certification(
+        &self,
+        id: &CertificateId,
+        version: Option<CertificateVersion>,
+    ) -> Result<Option<UserCertificateWithCertification>, String> {
+        let certificate_version = STATE.with_borrow(|s| {
+            let latest_certificate = match s.certificates.get(id) {
+                Some(certificate) => certificate,
+                None => return Ok(None),
+            };
+            let latest_version = s.latest_version(id)?;
+            let version = version.unwrap_or(latest_version);
+
+            let certificate = if version == latest_version {
+                Some(latest_certificate)
+            } else {
+                s.certificate_versions
+                    .get(&CertificateVersionKey::new(*id, version)?)
+            };
+
+            Ok::<_, String>(certificate.map(|certificate| (certificate, version, latest_version)))
+        })?;
+
+        match certificate_version {
+            Some((certificate, version, latest_version)) => {
                 let ic_certificate_witness =
-                    self.certificate_witness(&certificate.user_principal, Some(id));
+                    self.certificate_witness(&certificate.user_principal, id, version);
 
-                UserCertificateWithCertification {
-                    certificate: Some(certificate),
+                Ok(Some(UserCertificateWithCertification {
+                    certificate,
+                    version,
+                    latest_version,
                     ic_certificate: ic_certificate(),
                     ic_certificate_witness,
-                }
+                }))
             }
-            None => UserCertificateWithCertification {
-                certificate: None,
-                ic_certificate: vec![],
-                ic_certificate_witness: vec![],
-            },
+            None => Ok(None),
         }
     }
 
-    pub fn get_certificates_by_user_principal(
+    /// Returns the latest version of up to `limit` certificates, along with their version numbers,
+    /// starting after the `after` id if set.
+    pub fn get_latest_certificate_versions(
+        &self,
+        after: Option<CertificateId>,
+        limit: usize,
+    ) -> Result<Vec<(CertificateId, Certificate, CertificateVersion)>, String> {
+        STATE.with_borrow(|s| {
+            take_batch(s.certificates.range(ids_after(after)), limit)
+                .into_iter()
+                .map(|(id, certificate)| Ok((id, certificate, s.latest_version(&id)?)))
+                .collect()
+        })
+    }
+
+    pub fn count_certificates(&self) -> u64 {
+        STATE.with_borrow(|s| s.certificates.len())
+    }
+
+    pub fn has_certificates_by_user_principal(
         &self,
         user_principal: &Principal,
-    ) -> Result<Vec<(CertificateId, Certificate)>, String> {
+    ) -> Result<bool, String> {
         let range = CertificateUserPrincipalRange::new(*user_principal)?;
 
-        let certificates = STATE.with_borrow(|s| {
+        Ok(STATE.with_borrow(|s| {
             s.certificate_user_principal_index
                 .range(range)
-                .map(|(_, id)| (id, s.certificates.get(&id).unwrap()))
-                .collect()
-        });
+                .next()
+                .is_some()
+        }))
+    }
+
+    /// Returns up to `limit` ids of the certificates held by the user principal,
+    /// in ascending order and starting after the `after` id if set.
+    pub fn get_certificate_ids_by_user_principal(
+        &self,
+        user_principal: &Principal,
+        after: Option<CertificateId>,
+        limit: usize,
+    ) -> Result<Vec<CertificateId>, String> {
+        let range = match after {
+            Some(id) => CertificateUserPrincipalRange::after(*user_principal, id)?,
+            None => CertificateUserPrincipalRange::new(*user_principal)?,
+        };
 
-        Ok(certificates)
+        Ok(STATE.with_borrow(|s| {
+            s.certificate_user_principal_index
+                .range(range)
+                .take(limit)
+                .map(|(_, id)| id)
+                .collect()
+        }))
     }
 
-    pub fn get_certificates_by_managed_user_id(
+    /// Returns up to `limit` ids of the certificates of the managed user,
+    /// in ascending order and starting after the `after` id if set.
+    pub fn get_certificate_ids_by_managed_user_id(
         &self,
-        managed_user_id: &Uuid,
-    ) -> Result<Vec<(CertificateId, Certificate)>, String> {
-        let range = CertificateManagedUserIdRange::new(*managed_user_id)?;
+        managed_user_id: &ManagedUserId,
+        after: Option<CertificateId>,
+        limit: usize,
+    ) -> Result<Vec<CertificateId>, String> {
+        let range = match after {
+            Some(id) => CertificateManagedUserIdRange::after(*managed_user_id, id)?,
+            None => CertificateManagedUserIdRange::new(*managed_user_id)?,
+        };
 
-        let certificates: Vec<_> = STATE.with_borrow(|s| {
+        Ok(STATE.with_borrow(|s| {
             s.certificate_managed_user_id_index
                 .range(range)
-                .map(|(_, id)| (id, s.certificates.get(&id).unwrap()))
+                .take(limit)
+                .map(|(_, id)| id)
                 .collect()
-        });
+        }))
+    }
+
+    /// Returns the ids of the certificates that reference the file in any of their versions.
+    pub fn get_certificate_ids_by_file_hash(
+        &self,
+        file_hash: FileHash,
+    ) -> Result<Vec<CertificateId>, String> {
+        let range = CertificateFileHashRange::new(file_hash)?;
+
+        Ok(STATE.with_borrow(|s| {
+            s.certificate_file_hash_index
+                .range(range)
+                .map(|(_, id)| id)
+                .collect()
+        }))
+    }
+
+    /// Returns a page of the certificates listed under the owner that match the `filters`.
+    ///
+    /// The facet index is used if filtering by sport category or issuer club name,
+    /// otherwise the sort index of the owner is used.
+    pub fn get_certificates_page(
+        &self,
+        owner: CertificateOwner,
+        sort: CertificateSort,
+        cursor: Option<CertificateCursor>,
+        limit: usize,
+        filters: &CertificateFilters,
+        only_user_principal: Option<Principal>,
+    ) -> Result<CertificatesPage, String> {
+        // the issue date filter narrows down the index range
+        // only if the certificates are sorted by issue date
+        let timestamps = match sort.field {
+            CertificateSortField::IssuedAt => filters.issued_at_range(),
+            CertificateSortField::CreatedAt => u64::MIN..=u64::MAX,
+        };
+        let bounds = CertificateSortBounds::new(timestamps, cursor.as_ref(), sort.order);
+        if bounds.is_empty() {
+            return Ok(CertificatesPage::default());
+        }
+
+        let page = match (filters.facet_filter(), owner) {
+            (Some((facet, value)), _) => {
+                let value_hash = CertificateFacet::value_hash(value);
+                let range =
+                    CertificateFacetRange::new(owner, facet, value_hash, sort.field, &bounds)?;
+
+                STATE.with_borrow(|s| {
+                    let ids = sorted_ids(&s.certificate_facet_index, range, sort.order);
+                    s.certificates_page(ids, sort.field, limit, filters, only_user_principal)
+                })?
+            }
+            (None, CertificateOwner::UserPrincipal(user_principal)) => {
+                let range = CertificateUserPrincipalSortRange::new(user_principal, &bounds)?;
+
+                STATE.with_borrow(|s| {
+                    let ids =
+                        sorted_ids(s.user_principal_sort_index(sort.field), range, sort.order);
+                    s.certificates_page(ids, sort.field, limit, filters, only_user_principal)
+                })?
+            }
+            (None, CertificateOwner::ManagedUserId(managed_user_id)) => {
+                let range = CertificateManagedUserIdSortRange::new(managed_user_id, &bounds)?;
+
+                STATE.with_borrow(|s| {
+                    let ids =
+                        sorted_ids(s.managed_user_id_sort_index(sort.field), range, sort.order);
+                    s.certificates_page(ids, sort.field, limit, filters, only_user_principal)
+                })?
+            }
+            (None, CertificateOwner::IssuerPrincipal(issuer_principal)) => {
+                let range = CertificateUserPrincipalSortRange::new(issuer_principal, &bounds)?;
 
-        Ok(certificates)
+                STATE.with_borrow(|s| {
+                    let ids =
+                        sorted_ids(s.issuer_principal_sort_index(sort.field), range, sort.order);
+                    s.certificates_page(ids, sort.field, limit, filters, only_user_principal)
+                })?
+            }
+        };
+
+        Ok(page)
     }
 
-    pub async fn create_certificate(
+    pub fn create_certificate(
         &self,
+        id: CertificateId,
         certificate: Certificate,
-    ) -> Result<CertificateId, String> {
-        let id = CertificateId::new().await?;
+    ) -> Result<(), String> {
+        if STATE.with_borrow(|s| s.certificates.contains_key(&id)) {
+            return Err(format!("Certificate {} already exists", id.to_string()));
+        }
+
         let user_principal = certificate.user_principal;
         let user_principal_key = CertificateUserPrincipalKey::new(user_principal, id)?;
 
@@ -131,79 +638,297 @@ impl CertificateRepository {
                 s.certificate_managed_user_id_index
                     .insert(managed_user_id_key, id);
             }
-            self.certify_certificate_data(&mut s.ic_certificate_tree, id, certificate);
+            s.add_to_sort_indexes(id, &certificate)?;
+            s.add_to_file_hash_index(id, &certificate)?;
+            self.certify_certificate_data(
+                &mut s.ic_certificate_tree,
+                id,
+                FIRST_CERTIFICATE_VERSION,
+                &certificate,
+            );
 
             Ok::<(), String>(())
         })?;
 
         self.set_certified_data();
 
-        Ok(id)
+        Ok(())
     }
 
-    pub fn certify_all_certificates(&self) {
-        let count = STATE.with_borrow_mut(|s| {
-            s.certificates.iter().for_each(|(id, certificate)| {
-                self.certify_certificate_data(&mut s.ic_certificate_tree, id, certificate);
-            });
-            s.certificates.len()
-        });
+    /// Stores a new version of an existing certificate and certifies it.
+    /// The previous version is kept in the certificate history and stays certified.
+    /// The certificate owner cannot be changed.
+    pub fn add_certificate_version(
+        &self,
+        id: CertificateId,
+        certificate: Certificate,
+    ) -> Result<CertificateVersion, String> {
+        let version = STATE.with_borrow_mut(|s| {
+            let previous_certificate = s
+                .certificates
+                .get(&id)
+                .ok_or_else(|| format!("Certificate {} does not exist", id.to_string()))?;
+            if previous_certificate.user_principal != certificate.user_principal {
+                return Err("Certificate owner cannot be changed".to_string());
+            }
+
+            let previous_version = s.latest_version(&id)?;
+            let version = previous_version + 1;
+
+            s.remove_from_sort_indexes(id, &previous_certificate)?;
+            s.add_to_sort_indexes(id, &certificate)?;
+            s.add_to_file_hash_index(id, &certificate)?;
+            s.certificate_versions.insert(
+                CertificateVersionKey::new(id, previous_version)?,
+                previous_certificate,
+            );
+            s.certificates.insert(id, certificate.clone());
+            self.certify_certificate_data(&mut s.ic_certificate_tree, id, version, &certificate);
+
+            Ok(version)
+        })?;
+
+        self.set_certified_data();
+
+        Ok(version)
+    }
+
+    /// Transfers an existing certificate to another user principal, e.g. when a managed user claims it.
+    /// The transfer is stored as a new version of the certificate, certified under the new
+    /// user principal, while the previous versions stay certified under the previous one.
+    pub fn transfer_certificate(
+        &self,
+        id: CertificateId,
+        user_principal: UserPrincipal,
+        updated_at: DateTime,
+    ) -> Result<CertificateVersion, String> {
+        let version = STATE.with_borrow_mut(|s| {
+            let previous_certificate = s
+                .certificates
+                .get(&id)
+                .ok_or_else(|| format!("Certificate {} does not exist", id.to_string()))?;
+            let certificate = Certificate {
+                user_principal,
+                updated_at: Some(updated_at),
+                ..previous_certificate.clone()
+            };
+
+            let previous_version = s.latest_version(&id)?;
+            let version = previous_version + 1;
+
+            s.certificate_user_principal_index
+                .remove(&CertificateUserPrincipalKey::new(
+                    previous_certificate.user_principal,
+                    id,
+                )?);
+            s.certificate_user_principal_index
+                .insert(CertificateUserPrincipalKey::new(user_principal, id)?, id);
+            s.remove_from_sort_indexes(id, &previous_certificate)?;
+            s.add_to_sort_indexes(id, &certificate)?;
+            s.certificate_versions.insert(
+                CertificateVersionKey::new(id, previous_version)?,
+                previous_certificate,
+            );
+            s.certificates.insert(id, certificate.clone());
+            self.certify_certificate_data(&mut s.ic_certificate_tree, id, version, &certificate);
+
+            Ok::<_, String>(version)
+        })?;
+
+        self.set_certified_data();
+
+        Ok(version)
+    }
+
+    /// Returns the sharing settings of the certificate, which are empty if it has never been shared.
+    pub fn get_certificate_sharing(&self, id: &CertificateId) -> CertificateSharing {
+        STATE.with_borrow(|s| s.certificate_sharing.get(id).unwrap_or_default())
+    }
+
+    /// Returns up to `limit` sharing settings of the certificates that have been shared,
+    /// starting after the `after` id if set.
+    pub fn get_certificate_sharings(
+        &self,
+        after: Option<CertificateId>,
+        limit: usize,
+    ) -> Vec<(CertificateId, CertificateSharing)> {
+        STATE.with_borrow(|s| {
+            s.certificate_sharing
+                .range(ids_after(after))
+                .take(limit)
+                .collect()
+        })
+    }
+
+    /// Sets the sharing settings of an existing certificate.
+    /// Empty settings are removed, making the certificate private again.
+    pub fn set_certificate_sharing(
+        &self,
+        id: CertificateId,
+        sharing: CertificateSharing,
+    ) -> Result<(), String> {
+        STATE.with_borrow_mut(|s| {
+            if !s.certificates.contains_key(&id) {
+                return Err(format!("Certificate {} does not exist", id.to_string()));
+            }
+
+            if sharing.is_empty() {
+                s.certificate_sharing.remove(&id);
+            } else {
+                s.certificate_sharing.insert(id, sharing);
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Adds up to `limit` certificates, starting after the `after` id if set,
+    /// to the sort and facet indexes, e.g. right after the indexes have been introduced.
+    /// Returns the id of the last indexed certificate if there may be more certificates to index.
+    pub fn index_certificates(
+        &self,
+        after: Option<CertificateId>,
+        limit: usize,
+    ) -> Result<Option<CertificateId>, String> {
+        STATE.with_borrow_mut(|s| {
+            let certificates = take_batch(s.certificates.range(ids_after(after)), limit);
+            for (id, certificate) in certificates.iter() {
+                s.add_to_sort_indexes(*id, certificate)?;
+            }
+
+            Ok(batch_cursor(&certificates, limit, |(id, _)| *id))
+        })
+    }
+
+    /// Adds up to `limit` certificates, starting after the `after` id if set, to the index
+    /// of the files referenced by any of their versions, e.g. right after the index has been introduced.
+    /// Returns the id of the last indexed certificate if there may be more certificates to index.
+    pub fn index_certificate_files(
+        &self,
+        after: Option<CertificateId>,
+        limit: usize,
+    ) -> Result<Option<CertificateId>, String> {
+        STATE.with_borrow_mut(|s| {
+            let certificates = take_batch(s.certificates.range(ids_after(after)), limit);
+            for (id, certificate) in certificates.iter() {
+                let range = CertificateVersionRange::new(*id)?;
+                let previous_certificates: Vec<_> = s
+                    .certificate_versions
+                    .range(range)
+                    .map(|(_, previous_certificate)| previous_certificate)
+                    .collect();
+                for certificate in previous_certificates.iter().chain([certificate]) {
+                    s.add_to_file_hash_index(*id, certificate)?;
+                }
+            }
+
+            Ok(batch_cursor(&certificates, limit, |(id, _)| *id))
+        })
+    }
+
+    /// Certifies all the versions of up to `limit` certificates, starting after the `after` id if set.
+    /// Returns the id of the last certified certificate if there may be more certificates to certify.
+    pub fn certify_certificates(
+        &self,
+        after: Option<CertificateId>,
+        limit: usize,
+    ) -> Result<Option<CertificateId>, String> {
+        let cursor = STATE.with_borrow_mut(|s| {
+            // the versions are loaded while certifying, so the batch is checked after each certificate
+            let mut certified = Vec::new();
+            for (id, certificate) in s.certificates.range(ids_after(after)).take(limit) {
+                let range = CertificateVersionRange::new(id)?;
+                let mut version = FIRST_CERTIFICATE_VERSION;
+
+                for (_, previous_certificate) in s.certificate_versions.range(range) {
+                    self.certify_certificate_data(
+                        &mut s.ic_certificate_tree,
+                        id,
+                        version,
+                        &previous_certificate,
+                    );
+                    version += 1;
+                }
+
+                self.certify_certificate_data(
+                    &mut s.ic_certificate_tree,
+                    id,
+                    version,
+                    &certificate,
+                );
+                certified.push(id);
+
+                if is_batch_instructions_limit_reached() {
+                    break;
+                }
+            }
+
+            Ok::<_, String>(batch_cursor(&certified, limit, |id| *id))
+        })?;
         self.set_certified_data();
-        println!("Certified {} certificates", count);
+
+        Ok(cursor)
     }
 
     fn certify_certificate_data(
         &self,
         ic_certificate_tree: &mut IcCertificateTree,
         id: CertificateId,
-        certificate: Certificate,
+        version: CertificateVersion,
+        certificate: &Certificate,
     ) {
         let user_principal = certificate.user_principal;
-        let user_principal_bytes = user_principal.to_bytes();
-        match ic_certificate_tree.get(&user_principal_bytes) {
+        let version_label: CertificateVersionLabel = version.to_be_bytes();
+        let certificate_hash = certificate.root_hash();
+        let versions_tree = || {
+            let mut tree = RbTree::new();
+            tree.insert(version_label, certificate_hash);
+            tree
+        };
+
+        match ic_certificate_tree.get(user_principal.as_ref()) {
             Some(_) => {
-                ic_certificate_tree.modify(&user_principal_bytes, |inner| {
-                    inner.insert(id, certificate.root_hash())
+                ic_certificate_tree.modify(user_principal.as_ref(), |certificates| {
+                    match certificates.get(id.as_ref()) {
+                        Some(_) => certificates.modify(id.as_ref(), |versions| {
+                            versions.insert(version_label, certificate_hash)
+                        }),
+                        None => certificates.insert(id, versions_tree()),
+                    }
                 });
             }
             None => {
                 let mut tree = RbTree::new();
-                tree.insert(id, certificate.root_hash());
+                tree.insert(id, versions_tree());
                 ic_certificate_tree.insert(user_principal, tree);
             }
         }
     }
 
     fn set_certified_data(&self) {
-        STATE.with_borrow(|s| {
-            let tree_hash = s.ic_certificate_tree.root_hash();
-            let root_hash = labeled_hash(SSP_CERTIFICATES_TREE_LABEL, &tree_hash);
-            ic_cdk::api::set_certified_data(&root_hash);
-        });
+        let tree_hash = STATE.with_borrow(|s| s.ic_certificate_tree.root_hash());
+        self.certified_data_repository
+            .set_subtree_root_hash(SSP_CERTIFICATES_TREE_LABEL, tree_hash);
     }
 
     fn certificate_witness(
         &self,
         user_principal: &Principal,
-        certificate_id: Option<&CertificateId>,
+        id: &CertificateId,
+        version: CertificateVersion,
     ) -> Vec<u8> {
-        STATE.with_borrow(|s| {
-            let witness = match certificate_id {
-                Some(id) => s
-                    .ic_certificate_tree
-                    .nested_witness(user_principal.as_ref(), |inner| inner.witness(id.as_ref())),
-                None => s
-                    .ic_certificate_tree
-                    .nested_witness(user_principal.as_ref(), |inner| inner.keys()),
-            };
-            let tree = labeled(SSP_CERTIFICATES_TREE_LABEL, witness);
-
-            let mut data = vec![];
-            let mut serializer = Serializer::new(&mut data);
-            serializer.self_describe().unwrap();
-            tree.serialize(&mut serializer).unwrap();
+        let version_label: CertificateVersionLabel = version.to_be_bytes();
+        let witness = STATE.with_borrow(|s| {
+            s.ic_certificate_tree
+                .nested_witness(user_principal.as_ref(), |certificates| {
+                    certificates
+                        .nested_witness(id.as_ref(), |versions| versions.witness(&version_label))
+                })
+        });
+        let tree = self
+            .certified_data_repository
+            .witness(SSP_CERTIFICATES_TREE_LABEL, witness);
 
-            data
-        })
+        cbor_serialize(&tree).unwrap()
     }
 }
@@ -0,0 +1,100 @@
+use std::{cell::RefCell, collections::BTreeMap};
+
+use ic_cdk::api::set_certified_data;
+use ic_certification::{empty, fork, labeled_hash, pruned, Hash, HashTree};
+
+/// Certified data tree structure:
+/// root
+/// ├── audit_log
+/// │   └── audit log length and tip hash (see [super::AuditLogRepository])
+/// ├── http_expr
+/// │   └── certified HTTP responses (see [super::HttpCertificationRepository])
+/// ├── last_block_hash
+/// │   └── hash of the last ICRC-3 block (see [super::CertificateBlockRepository])
+/// ├── last_block_index
+/// │   └── index of the last ICRC-3 block (see [super::CertificateBlockRepository])
+/// ├── sig
+/// │   └── delegation signatures (see [super::DelegationRepository])
+/// └── ssp_certificates
+///     └── certificates (see [super::CertificateRepository])
+///
+/// Each repository owns its subtree and is responsible for updating
+/// the subtree root hash whenever the subtree changes.
+///
+/// The subtrees are stored by label with the hash of their labeled node,
+/// so that the trees that include their own label (e.g. the HTTP certification tree) can be used as is.
+type CertifiedDataTree = BTreeMap<&'static [u8], Hash>;
+
+#[derive(Default)]
+struct CertifiedDataState {
+    tree: CertifiedDataTree,
+}
+
+impl CertifiedDataState {
+    /// The hash tree of the subtrees sorted by label, in which all the subtrees are pruned
+    /// except for the labeled `witness`, if any.
+    fn hash_tree(&self, mut witness: Option<(&[u8], HashTree)>) -> HashTree {
+        let mut tree: Option<HashTree> = None;
+        for (subtree_label, labeled_hash) in self.tree.iter().rev() {
+            let node = match witness.take() {
+                Some((witness_label, witness_tree)) if witness_label == *subtree_label => {
+                    witness_tree
+                }
+                other => {
+                    witness = other;
+                    pruned(*labeled_hash)
+                }
+            };
+
+            tree = Some(match tree {
+                Some(right) => fork(node, right),
+                None => node,
+            });
+        }
+
+        tree.unwrap_or_else(empty)
+    }
+}
+
+thread_local! {
+    static STATE: RefCell<CertifiedDataState> = RefCell::new(CertifiedDataState::default());
+}
+
+#[derive(Default)]
+pub struct CertifiedDataRepository {}
+
+impl CertifiedDataRepository {
+    /// Updates the root hash of the subtree at `label`
+    /// and sets the canister certified data to the new root hash of the whole tree.
+    pub fn set_subtree_root_hash(&self, label: &'static [u8], root_hash: Hash) {
+        self.set_labeled_subtree_hash(label, labeled_hash(label, &root_hash));
+    }
+
+    /// Same as [CertifiedDataRepository::set_subtree_root_hash], for a subtree
+    /// whose root is the node labeled with `label` already.
+    pub fn set_labeled_subtree_hash(&self, label: &'static [u8], hash: Hash) {
+        let certified_data = STATE.with_borrow_mut(|s| {
+            s.tree.insert(label, hash);
+            s.hash_tree(None).digest()
+        });
+
+        set_certified_data(&certified_data);
+    }
+
+    /// Wraps the witness of the subtree at `label` into a witness
+    /// of the whole certified data tree, in which all the other subtrees are pruned.
+    ///
+    /// The digest of the returned tree matches the canister certified data.
+    pub fn witness(&self, label: &[u8], subtree_witness: HashTree) -> HashTree {
+        self.labeled_witness(
+            label,
+            ic_certification::label(label.to_vec(), subtree_witness),
+        )
+    }
+
+    /// Same as [CertifiedDataRepository::witness], for a witness
+    /// whose root is the node labeled with `label` already.
+    pub fn labeled_witness(&self, label: &[u8], witness: HashTree) -> HashTree {
+        STATE.with_borrow(|s| s.hash_tree(Some((label, witness))))
+    }
+}
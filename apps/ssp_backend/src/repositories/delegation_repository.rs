@@ -1,33 +1,95 @@
-use std::cell::RefCell;
+use std::{cell::RefCell, ops::Bound};
 
-use canister_sig_util::signature_map::SignatureMap;
-use ic_certification::Hash;
-use ssp_backend_types::Auth0JWKSet;
+use candid::Principal;
+use canister_sig_util::{
+    hash_bytes,
+    signature_map::{SignatureMap, LABEL_SIG},
+};
+use ic_cdk::api::data_certificate;
+use ic_certification::{Hash, HashTree};
+use serde::Serialize;
+use serde_bytes::ByteBuf;
+use ssp_backend_types::Timestamp;
 
-use super::{init_salt, Salt, SaltMemory};
+use crate::utils::{batch_cursor, cbor_serialize};
+
+use super::{
+    init_delegation_seeds, init_delegation_signature_seed_index, init_delegation_signatures,
+    init_permanent_signatures, init_revoked_delegations, init_revoked_sessions, init_salt,
+    CertifiedDataRepository, DelegationSeedsMemory, DelegationSignature, DelegationSignatureKey,
+    DelegationSignatureSeedIndexMemory, DelegationSignaturesMemory, PermanentSignaturesMemory,
+    RevokedDelegationsMemory, RevokedSessionsMemory, Salt, SaltMemory,
+};
 
 pub struct DelegationState {
+    /// The certified signatures, restored from [DelegationState::signatures]
+    /// and [DelegationState::permanent_signatures] after an upgrade.
     sigs: SignatureMap,
-    jwks: Option<Auth0JWKSet>,
+    /// The signatures of the delegations that have not expired yet.
+    signatures: DelegationSignaturesMemory,
+    /// The signatures that never expire (e.g. of the certificate credentials),
+    /// by seed and message hash.
+    permanent_signatures: PermanentSignaturesMemory,
+    /// The signatures by seed and message hash,
+    /// to find the signatures of a seed without scanning all of them.
+    signature_seed_index: DelegationSignatureSeedIndexMemory,
+    /// The delegations revoked before the session keys were recorded, refused until they expire.
+    revoked_delegations: RevokedDelegationsMemory,
+    /// The time until which the revoked sessions are refused, by seed and session key hash.
+    revoked_sessions: RevokedSessionsMemory,
+    /// The seed of each user principal, to find the delegations of the caller.
+    seeds: DelegationSeedsMemory,
     salt: SaltMemory,
+    /// The last revoked session checked by the previous prune run,
+    /// as the revoked sessions are not sorted by expiration.
+    revoked_sessions_prune_cursor: Option<(Hash, Hash)>,
 }
 
 impl Default for DelegationState {
     fn default() -> Self {
         Self {
             sigs: SignatureMap::default(),
-            jwks: None,
+            signatures: init_delegation_signatures(),
+            permanent_signatures: init_permanent_signatures(),
+            signature_seed_index: init_delegation_signature_seed_index(),
+            revoked_delegations: init_revoked_delegations(),
+            revoked_sessions: init_revoked_sessions(),
+            seeds: init_delegation_seeds(),
             salt: init_salt(),
+            revoked_sessions_prune_cursor: None,
         }
     }
 }
 
+/// The expiration of the certified signatures that are never pruned,
+/// e.g. of the certificate credentials. These signatures used to be stored
+/// with this expiration among the delegation ones (see [DelegationState::permanent_signatures]).
+const PERMANENT_SIGNATURE_EXPIRATION: Timestamp = Timestamp::MAX;
+
+/// The outcome of a prune run, see [DelegationRepository::prune_expired_delegation_signatures].
+pub struct PrunedDelegationSignatures {
+    /// The number of removed signatures.
+    pub count: usize,
+    /// Whether all the expired entries have been removed, otherwise another run is needed.
+    pub is_done: bool,
+}
+
 thread_local! {
   static STATE: RefCell<DelegationState> = RefCell::new(DelegationState::default());
 }
 
+/// The canister signature, as defined in
+/// https://internetcomputer.org/docs/current/references/ic-interface-spec#canister-signatures.
+#[derive(Serialize)]
+struct CanisterSig {
+    certificate: ByteBuf,
+    tree: HashTree,
+}
+
 #[derive(Default)]
-pub struct DelegationRepository {}
+pub struct DelegationRepository {
+    certified_data_repository: CertifiedDataRepository,
+}
 
 impl DelegationRepository {
     pub fn get_salt(&self) -> Salt {
@@ -38,23 +100,404 @@ impl DelegationRepository {
         STATE.with_borrow_mut(|s| s.salt.set(salt).unwrap());
     }
 
-    pub fn set_jwks(&self, jwks: Auth0JWKSet) {
-        STATE.with_borrow_mut(|s| s.jwks = Some(jwks));
+    pub fn set_delegation_seed(&self, user_principal: Principal, seed: Hash) {
+        STATE.with_borrow_mut(|s| s.seeds.insert(user_principal, seed));
+    }
+
+    pub fn get_delegation_seed(&self, user_principal: &Principal) -> Option<Hash> {
+        STATE.with_borrow(|s| s.seeds.get(user_principal))
+    }
+
+    /// Adds the signature of a delegation, which is kept until the delegation expires.
+    pub fn add_delegation_signature(
+        &self,
+        seed: Hash,
+        session_key_hash: Hash,
+        message_hash: Hash,
+        expiration: Timestamp,
+    ) -> Result<(), String> {
+        let key = DelegationSignatureKey::new(expiration, seed, message_hash)?;
+        let sigs_root_hash = STATE.with_borrow_mut(|s| {
+            s.signatures.insert(key, ());
+            s.signature_seed_index.insert(
+                (seed, message_hash),
+                DelegationSignature {
+                    expiration,
+                    session_key_hash: Some(session_key_hash),
+                },
+            );
+            s.sigs.put(hash_bytes(seed), message_hash, expiration);
+            s.sigs.root_hash()
+        });
+
+        self.certified_data_repository
+            .set_subtree_root_hash(LABEL_SIG, sigs_root_hash);
+
+        Ok(())
+    }
+
+    /// Adds signatures that never expire, until they are removed.
+    /// Signatures that already exist are left untouched.
+    pub fn add_permanent_signatures(&self, seed: Hash, message_hashes: &[Hash]) {
+        let sigs_root_hash = STATE.with_borrow_mut(|s| {
+            let mut added = false;
+            for message_hash in message_hashes {
+                if s.permanent_signatures
+                    .insert((seed, *message_hash), ())
+                    .is_none()
+                {
+                    s.sigs.put(
+                        hash_bytes(seed),
+                        *message_hash,
+                        PERMANENT_SIGNATURE_EXPIRATION,
+                    );
+                    added = true;
+                }
+            }
+            added.then(|| s.sigs.root_hash())
+        });
+
+        if let Some(sigs_root_hash) = sigs_root_hash {
+            self.certified_data_repository
+                .set_subtree_root_hash(LABEL_SIG, sigs_root_hash);
+        }
     }
 
-    pub fn get_jwks(&self) -> Option<Auth0JWKSet> {
-        STATE.with_borrow(|s| s.jwks.clone())
+    /// Removes a signature added with [DelegationRepository::add_permanent_signatures].
+    pub fn remove_permanent_signature(&self, seed: Hash, message_hash: Hash) {
+        let sigs_root_hash = STATE.with_borrow_mut(|s| {
+            s.permanent_signatures.remove(&(seed, message_hash))?;
+            s.sigs.delete(hash_bytes(seed), message_hash);
+            Some(s.sigs.root_hash())
+        });
+
+        if let Some(sigs_root_hash) = sigs_root_hash {
+            self.certified_data_repository
+                .set_subtree_root_hash(LABEL_SIG, sigs_root_hash);
+        }
     }
 
-    pub fn add_delegation_signature(&self, seed: &[u8], message_hash: Hash) {
-        STATE.with_borrow_mut(|s| s.sigs.add_signature(seed, message_hash));
+    /// Restores up to `limit` permanent signatures to the certified signatures,
+    /// starting after the `after` seed and message hash if set, e.g. after an upgrade.
+    /// Returns the seed and message hash of the last restored signature
+    /// if there may be more signatures to restore.
+    pub fn restore_permanent_signatures(
+        &self,
+        after: Option<(Hash, Hash)>,
+        limit: usize,
+    ) -> Option<(Hash, Hash)> {
+        let (cursor, sigs_root_hash) = STATE.with_borrow_mut(|s| {
+            let range = (
+                after.map_or(Bound::Unbounded, Bound::Excluded),
+                Bound::Unbounded,
+            );
+            let keys: Vec<(Hash, Hash)> = s
+                .permanent_signatures
+                .range(range)
+                .take(limit)
+                .map(|(key, _)| key)
+                .collect();
+            for (seed, message_hash) in &keys {
+                s.sigs.put(
+                    hash_bytes(seed),
+                    *message_hash,
+                    PERMANENT_SIGNATURE_EXPIRATION,
+                );
+            }
+
+            (batch_cursor(&keys, limit, Clone::clone), s.sigs.root_hash())
+        });
+
+        self.certified_data_repository
+            .set_subtree_root_hash(LABEL_SIG, sigs_root_hash);
+
+        cursor
     }
 
+    /// Moves up to `limit` permanent signatures stored among the delegation ones,
+    /// i.e. with the [PERMANENT_SIGNATURE_EXPIRATION], to the permanent signatures,
+    /// starting after the `after` key if set.
+    /// Returns the key of the last moved signature if there may be more signatures to move.
+    pub fn move_permanent_signatures(
+        &self,
+        after: Option<DelegationSignatureKey>,
+        limit: usize,
+    ) -> Result<Option<DelegationSignatureKey>, String> {
+        let first_key =
+            DelegationSignatureKey::new(PERMANENT_SIGNATURE_EXPIRATION, [0; 32], [0; 32])?;
+
+        Ok(STATE.with_borrow_mut(|s| {
+            let range = (
+                after.map_or(Bound::Included(first_key), Bound::Excluded),
+                Bound::Unbounded,
+            );
+            let keys: Vec<DelegationSignatureKey> = s
+                .signatures
+                .range(range)
+                .take(limit)
+                .map(|(key, _)| key)
+                .collect();
+            for key in &keys {
+                s.signatures.remove(key);
+                s.signature_seed_index
+                    .remove(&(key.seed(), key.message_hash()));
+                s.permanent_signatures
+                    .insert((key.seed(), key.message_hash()), ());
+            }
+
+            batch_cursor(&keys, limit, Clone::clone)
+        }))
+    }
+
+    /// Restores up to `limit` stored signatures to the certified signatures,
+    /// starting after the `after` key if set, e.g. after an upgrade.
+    /// Returns the key of the last restored signature if there may be more signatures to restore.
+    pub fn restore_delegation_signatures(
+        &self,
+        after: Option<DelegationSignatureKey>,
+        limit: usize,
+    ) -> Option<DelegationSignatureKey> {
+        let (cursor, sigs_root_hash) = STATE.with_borrow_mut(|s| {
+            let range = (
+                after.map_or(Bound::Unbounded, Bound::Excluded),
+                Bound::Unbounded,
+            );
+            let keys: Vec<DelegationSignatureKey> = s
+                .signatures
+                .range(range)
+                .take(limit)
+                .map(|(key, _)| key)
+                .collect();
+            for key in &keys {
+                s.sigs
+                    .put(hash_bytes(key.seed()), key.message_hash(), key.expiration());
+            }
+
+            (batch_cursor(&keys, limit, Clone::clone), s.sigs.root_hash())
+        });
+
+        self.certified_data_repository
+            .set_subtree_root_hash(LABEL_SIG, sigs_root_hash);
+
+        cursor
+    }
+
+    /// Adds up to `limit` stored signatures to the seed index, starting after the `after` key if set,
+    /// e.g. right after the index has been introduced.
+    /// Returns the key of the last indexed signature if there may be more signatures to index.
+    pub fn index_delegation_signatures(
+        &self,
+        after: Option<DelegationSignatureKey>,
+        limit: usize,
+    ) -> Option<DelegationSignatureKey> {
+        STATE.with_borrow_mut(|s| {
+            let range = (
+                after.map_or(Bound::Unbounded, Bound::Excluded),
+                Bound::Unbounded,
+            );
+            let keys: Vec<DelegationSignatureKey> = s
+                .signatures
+                .range(range)
+                .take(limit)
+                .map(|(key, _)| key)
+                .collect();
+            for key in &keys {
+                s.signature_seed_index.insert(
+                    (key.seed(), key.message_hash()),
+                    DelegationSignature {
+                        expiration: key.expiration(),
+                        session_key_hash: None,
+                    },
+                );
+            }
+
+            batch_cursor(&keys, limit, Clone::clone)
+        })
+    }
+
+    /// Revokes the delegations of the seed that match the filter: their signatures are removed,
+    /// and their sessions are refused until `revoked_until` or until they expire, whichever is later.
+    /// Returns the number of revoked delegations.
+    pub fn revoke_delegations(
+        &self,
+        seed: Hash,
+        filter: impl Fn(&DelegationSignatureKey, &DelegationSignature) -> bool,
+        revoked_until: Timestamp,
+    ) -> Result<usize, String> {
+        let revoked = STATE.with_borrow_mut(|s| {
+            let revoked_keys = s
+                .signature_seed_index
+                .range((seed, [0; 32])..=(seed, [u8::MAX; 32]))
+                .map(|((seed, message_hash), signature)| {
+                    DelegationSignatureKey::new(signature.expiration, seed, message_hash)
+                        .map(|key| (key, signature))
+                })
+                .collect::<Result<Vec<_>, _>>()?
+                .into_iter()
+                .filter(|(key, signature)| filter(key, signature))
+                .collect::<Vec<_>>();
+            for (key, signature) in &revoked_keys {
+                s.signatures.remove(key);
+                s.signature_seed_index.remove(&(seed, key.message_hash()));
+                match signature.session_key_hash {
+                    Some(session_key_hash) => {
+                        let session = (seed, session_key_hash);
+                        let until = s
+                            .revoked_sessions
+                            .get(&session)
+                            .unwrap_or_default()
+                            .max(revoked_until)
+                            .max(signature.expiration);
+                        s.revoked_sessions.insert(session, until);
+                    }
+                    None => {
+                        s.revoked_delegations.insert(key.clone(), ());
+                    }
+                }
+                s.sigs.delete(hash_bytes(seed), key.message_hash());
+            }
+
+            Ok::<_, String>(if revoked_keys.is_empty() {
+                None
+            } else {
+                Some((revoked_keys.len(), s.sigs.root_hash()))
+            })
+        })?;
+
+        Ok(match revoked {
+            Some((count, sigs_root_hash)) => {
+                self.certified_data_repository
+                    .set_subtree_root_hash(LABEL_SIG, sigs_root_hash);
+                count
+            }
+            None => 0,
+        })
+    }
+
+    pub fn is_session_revoked(&self, seed: Hash, session_key_hash: Hash, now: Timestamp) -> bool {
+        STATE.with_borrow(|s| {
+            s.revoked_sessions
+                .get(&(seed, session_key_hash))
+                .is_some_and(|until| until >= now)
+        })
+    }
+
+    /// Whether the delegation was revoked before the session keys were recorded.
+    pub fn is_delegation_revoked(
+        &self,
+        seed: Hash,
+        message_hash: Hash,
+        expiration: Timestamp,
+    ) -> Result<bool, String> {
+        let key = DelegationSignatureKey::new(expiration, seed, message_hash)?;
+
+        Ok(STATE.with_borrow(|s| s.revoked_delegations.contains_key(&key)))
+    }
+
+    /// Removes the signatures and the revocations of the delegations that expired before `now`,
+    /// and the revocations of the sessions that are no longer refused.
+    /// Removes up to `limit` entries of each kind, and checks up to `limit` revoked sessions
+    /// starting after the ones checked by the previous run.
+    pub fn prune_expired_delegation_signatures(
+        &self,
+        now: Timestamp,
+        limit: usize,
+    ) -> PrunedDelegationSignatures {
+        let (pruned, is_done) = STATE.with_borrow_mut(|s| {
+            // expired delegations are refused anyway
+            let expired_delegations = remove_expired_keys(&mut s.revoked_delegations, now, limit);
+
+            let range = (
+                s.revoked_sessions_prune_cursor
+                    .map_or(Bound::Unbounded, Bound::Excluded),
+                Bound::Unbounded,
+            );
+            let sessions: Vec<((Hash, Hash), Timestamp)> =
+                s.revoked_sessions.range(range).take(limit).collect();
+            for (session, until) in &sessions {
+                if *until < now {
+                    s.revoked_sessions.remove(session);
+                }
+            }
+            // starts again from the first session once all the sessions have been checked
+            s.revoked_sessions_prune_cursor =
+                batch_cursor(&sessions, limit, |(session, _)| *session);
+
+            let expired_keys = remove_expired_keys(&mut s.signatures, now, limit);
+            let is_done = expired_delegations.len() < limit
+                && s.revoked_sessions_prune_cursor.is_none()
+                && expired_keys.len() < limit;
+            if expired_keys.is_empty() {
+                (None, is_done)
+            } else {
+                for key in &expired_keys {
+                    s.signature_seed_index
+                        .remove(&(key.seed(), key.message_hash()));
+                }
+                // the certified signatures were put with the same expiration
+                s.sigs.prune_expired(now, limit);
+                (Some((expired_keys.len(), s.sigs.root_hash())), is_done)
+            }
+        });
+
+        let count = match pruned {
+            Some((count, sigs_root_hash)) => {
+                self.certified_data_repository
+                    .set_subtree_root_hash(LABEL_SIG, sigs_root_hash);
+                count
+            }
+            None => 0,
+        };
+
+        PrunedDelegationSignatures { count, is_done }
+    }
+
+    pub fn count_delegation_signatures(&self) -> u64 {
+        STATE.with_borrow(|s| s.signatures.len())
+    }
+
+    pub fn count_revoked_sessions(&self) -> u64 {
+        STATE.with_borrow(|s| s.revoked_sessions.len())
+    }
+
+    pub fn count_revoked_delegations(&self) -> u64 {
+        STATE.with_borrow(|s| s.revoked_delegations.len())
+    }
+
+    /// Returns the CBOR-encoded canister signature, whose tree is a witness
+    /// of the whole certified data tree (see [CertifiedDataRepository]).
     pub fn get_signature(&self, seed: &[u8], message_hash: Hash) -> Result<Vec<u8>, String> {
-        STATE.with_borrow(|s| s.sigs.get_signature_as_cbor(seed, message_hash, None))
+        let certificate =
+            data_certificate().ok_or("Data certificate is only available in query calls")?;
+        let witness = STATE
+            .with_borrow(|s| s.sigs.witness(hash_bytes(seed), message_hash))
+            .ok_or("Signature not found")?;
+
+        let sig = CanisterSig {
+            certificate: ByteBuf::from(certificate),
+            tree: self.certified_data_repository.witness(LABEL_SIG, witness),
+        };
+
+        cbor_serialize(&sig)
     }
+}
 
-    pub fn get_sigs_root_hash(&self) -> Hash {
-        STATE.with_borrow(|s| s.sigs.root_hash())
+/// Keys are sorted by expiration, so the expired ones come first.
+/// Removes up to `limit` keys and returns them.
+fn remove_expired_keys(
+    keys: &mut DelegationSignaturesMemory,
+    now: Timestamp,
+    limit: usize,
+) -> Vec<DelegationSignatureKey> {
+    let expired_keys: Vec<DelegationSignatureKey> = keys
+        .iter()
+        .map(|(key, _)| key)
+        .take_while(|key| key.expiration() < now)
+        .take(limit)
+        .collect();
+    for key in &expired_keys {
+        keys.remove(key);
     }
+
+    expired_keys
 }
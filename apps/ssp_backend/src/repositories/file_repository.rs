@@ -0,0 +1,123 @@
+use std::{cell::RefCell, collections::HashMap};
+
+use candid::Principal;
+
+use super::{
+    init_file_chunks, init_files, DateTime, File, FileChunkIndex, FileChunkKey, FileChunkMemory,
+    FileHash, FileMemory, Upload, UploadId,
+};
+
+struct FileState {
+    files: FileMemory,
+    file_chunks: FileChunkMemory,
+    /// The uploads in progress. They are lost on canister upgrade.
+    uploads: HashMap<UploadId, Upload>,
+}
+
+impl Default for FileState {
+    fn default() -> Self {
+        Self {
+            files: init_files(),
+            file_chunks: init_file_chunks(),
+            uploads: HashMap::new(),
+        }
+    }
+}
+
+thread_local! {
+    static STATE: RefCell<FileState> = RefCell::new(FileState::default());
+}
+
+#[derive(Default)]
+pub struct FileRepository {}
+
+impl FileRepository {
+    pub fn get_file(&self, file_hash: &FileHash) -> Option<File> {
+        STATE.with_borrow(|s| s.files.get(file_hash))
+    }
+
+    pub fn has_file(&self, file_hash: &FileHash) -> bool {
+        STATE.with_borrow(|s| s.files.contains_key(file_hash))
+    }
+
+    pub fn get_file_chunk(
+        &self,
+        file_hash: FileHash,
+        index: FileChunkIndex,
+    ) -> Result<Option<Vec<u8>>, String> {
+        let key = FileChunkKey::new(file_hash, index)?;
+
+        Ok(STATE.with_borrow(|s| s.file_chunks.get(&key)))
+    }
+
+    /// Stores the file and its chunks, unless a file with the same hash is already stored.
+    pub fn create_file(
+        &self,
+        file_hash: FileHash,
+        file: File,
+        chunks: Vec<&Vec<u8>>,
+    ) -> Result<(), String> {
+        STATE.with_borrow_mut(|s| {
+            if s.files.contains_key(&file_hash) {
+                return Ok(());
+            }
+
+            for (index, chunk) in chunks.into_iter().enumerate() {
+                let key = FileChunkKey::new(file_hash, index as FileChunkIndex)?;
+                s.file_chunks.insert(key, chunk.clone());
+            }
+            s.files.insert(file_hash, file);
+
+            Ok(())
+        })
+    }
+
+    pub fn get_upload(&self, upload_id: &UploadId) -> Option<Upload> {
+        STATE.with_borrow(|s| s.uploads.get(upload_id).cloned())
+    }
+
+    pub fn count_uploads_by_owner(&self, owner: &Principal) -> usize {
+        STATE.with_borrow(|s| {
+            s.uploads
+                .values()
+                .filter(|upload| upload.owner == *owner)
+                .count()
+        })
+    }
+
+    pub fn create_upload(&self, upload_id: UploadId, upload: Upload) {
+        STATE.with_borrow_mut(|s| s.uploads.insert(upload_id, upload));
+    }
+
+    pub fn set_upload_chunk(
+        &self,
+        upload_id: &UploadId,
+        index: FileChunkIndex,
+        content: Vec<u8>,
+    ) -> Result<(), String> {
+        STATE.with_borrow_mut(|s| {
+            let upload = s
+                .uploads
+                .get_mut(upload_id)
+                .ok_or_else(|| format!("Upload {} does not exist", upload_id.to_string()))?;
+            upload.chunks.insert(index, content);
+
+            Ok(())
+        })
+    }
+
+    pub fn remove_upload(&self, upload_id: &UploadId) -> Option<Upload> {
+        STATE.with_borrow_mut(|s| s.uploads.remove(upload_id))
+    }
+
+    /// Removes the uploads created before `created_before`, returning how many were removed.
+    pub fn remove_expired_uploads(&self, created_before: DateTime) -> usize {
+        STATE.with_borrow_mut(|s| {
+            let count = s.uploads.len();
+            s.uploads
+                .retain(|_, upload| upload.created_at >= created_before);
+
+            count - s.uploads.len()
+        })
+    }
+}
@@ -0,0 +1,93 @@
+use std::{cell::RefCell, collections::HashMap};
+
+use ic_certification::HashTree;
+use ic_http_certification::{
+    HttpCertification, HttpCertificationPath, HttpCertificationTree, HttpCertificationTreeEntry,
+};
+
+use super::{CertifiedDataRepository, HTTP_EXPR_TREE_LABEL};
+
+/// HTTP certification tree structure (response verification v2), see [HttpCertificationTree]:
+/// http_expr
+/// └── <url path segments>
+///     └── <$> (exact path) or <*> (wildcard path)
+///         └── <certification expression hash>
+///             └── "" (the request is not certified)
+///                 └── <response hash>
+///                     └── ""
+#[derive(Default)]
+struct HttpCertificationState {
+    tree: HttpCertificationTree,
+    /// The certification of the response certified at each path (see [HttpCertificationPath::to_expr_path]),
+    /// to replace or remove it from the tree.
+    certifications: HashMap<Vec<String>, HttpCertification>,
+}
+
+thread_local! {
+    static STATE: RefCell<HttpCertificationState> = RefCell::new(HttpCertificationState::default());
+}
+
+#[derive(Default)]
+pub struct HttpCertificationRepository {
+    certified_data_repository: CertifiedDataRepository,
+}
+
+impl HttpCertificationRepository {
+    /// Certifies the response, replacing the response previously certified at the same path.
+    pub fn certify_response(&self, path: &HttpCertificationPath, certification: HttpCertification) {
+        STATE.with_borrow_mut(|s| {
+            if let Some(previous) = s.certifications.remove(&path.to_expr_path()) {
+                s.tree
+                    .delete(&HttpCertificationTreeEntry::new(path, &previous));
+            }
+
+            s.tree
+                .insert(&HttpCertificationTreeEntry::new(path, &certification));
+            s.certifications.insert(path.to_expr_path(), certification);
+        });
+
+        self.set_certified_data();
+    }
+
+    /// Removes the response certified at the path, if any.
+    pub fn remove_response(&self, path: &HttpCertificationPath) {
+        STATE.with_borrow_mut(|s| {
+            if let Some(previous) = s.certifications.remove(&path.to_expr_path()) {
+                s.tree
+                    .delete(&HttpCertificationTreeEntry::new(path, &previous));
+            }
+        });
+
+        self.set_certified_data();
+    }
+
+    /// Returns the witness of the response certified at the path for the url path,
+    /// which also proves that no more specific path is certified for the url path.
+    pub fn witness(
+        &self,
+        path: &HttpCertificationPath,
+        certification: &HttpCertification,
+        url_path: &str,
+    ) -> Result<HashTree, String> {
+        let witness = STATE.with_borrow(|s| {
+            s.tree
+                .witness(
+                    &HttpCertificationTreeEntry::new(path, certification),
+                    url_path,
+                )
+                .map_err(|e| e.to_string())
+        })?;
+
+        Ok(self
+            .certified_data_repository
+            .labeled_witness(HTTP_EXPR_TREE_LABEL.as_bytes(), witness))
+    }
+
+    fn set_certified_data(&self) {
+        let root_hash = STATE.with_borrow(|s| s.tree.root_hash());
+
+        // the root of the tree is labeled with `http_expr` already
+        self.certified_data_repository
+            .set_labeled_subtree_hash(HTTP_EXPR_TREE_LABEL.as_bytes(), root_hash);
+    }
+}
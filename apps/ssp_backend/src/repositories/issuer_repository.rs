@@ -0,0 +1,48 @@
+use std::cell::RefCell;
+
+use super::{init_issuers, Issuer, IssuerId, IssuerMemory};
+
+struct IssuerState {
+    issuers: IssuerMemory,
+}
+
+impl Default for IssuerState {
+    fn default() -> Self {
+        Self {
+            issuers: init_issuers(),
+        }
+    }
+}
+
+thread_local! {
+    static STATE: RefCell<IssuerState> = RefCell::new(IssuerState::default());
+}
+
+#[derive(Default)]
+pub struct IssuerRepository {}
+
+impl IssuerRepository {
+    pub fn get_issuer(&self, id: &IssuerId) -> Option<Issuer> {
+        STATE.with_borrow(|s| s.issuers.get(id))
+    }
+
+    pub async fn create_issuer(&self, issuer: Issuer) -> Result<IssuerId, String> {
+        let id = IssuerId::new().await?;
+
+        STATE.with_borrow_mut(|s| s.issuers.insert(id, issuer));
+
+        Ok(id)
+    }
+
+    pub fn update_issuer(&self, id: IssuerId, issuer: Issuer) -> Result<(), String> {
+        STATE.with_borrow_mut(|s| {
+            if !s.issuers.contains_key(&id) {
+                return Err(format!("Issuer {} does not exist", id.to_string()));
+            }
+
+            s.issuers.insert(id, issuer);
+
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,119 @@
+use std::cell::RefCell;
+
+use super::{
+    init_managed_user_claims, init_managed_user_guardian_index, init_managed_users, ManagedUser,
+    ManagedUserClaim, ManagedUserClaimMemory, ManagedUserGuardianIndexMemory,
+    ManagedUserGuardianKey, ManagedUserGuardianRange, ManagedUserId, ManagedUserMemory,
+    UserPrincipal,
+};
+
+struct ManagedUserState {
+    managed_users: ManagedUserMemory,
+    managed_user_guardian_index: ManagedUserGuardianIndexMemory,
+    /// The claims whose certificates are still being transferred.
+    managed_user_claims: ManagedUserClaimMemory,
+}
+
+impl Default for ManagedUserState {
+    fn default() -> Self {
+        Self {
+            managed_users: init_managed_users(),
+            managed_user_guardian_index: init_managed_user_guardian_index(),
+            managed_user_claims: init_managed_user_claims(),
+        }
+    }
+}
+
+thread_local! {
+    static STATE: RefCell<ManagedUserState> = RefCell::new(ManagedUserState::default());
+}
+
+#[derive(Default)]
+pub struct ManagedUserRepository {}
+
+impl ManagedUserRepository {
+    pub fn get_managed_user(&self, id: &ManagedUserId) -> Option<ManagedUser> {
+        STATE.with_borrow(|s| s.managed_users.get(id))
+    }
+
+    /// Returns the managed users of which the principal is a guardian.
+    pub fn get_managed_users_by_guardian(
+        &self,
+        guardian: UserPrincipal,
+    ) -> Result<Vec<(ManagedUserId, ManagedUser)>, String> {
+        let range = ManagedUserGuardianRange::new(guardian)?;
+
+        Ok(STATE.with_borrow(|s| {
+            s.managed_user_guardian_index
+                .range(range)
+                .filter_map(|(_, id)| s.managed_users.get(&id).map(|user| (id, user)))
+                .collect()
+        }))
+    }
+
+    pub fn create_managed_user(
+        &self,
+        id: ManagedUserId,
+        managed_user: ManagedUser,
+    ) -> Result<(), String> {
+        STATE.with_borrow_mut(|s| {
+            if s.managed_users.contains_key(&id) {
+                return Err(format!(
+                    "Managed user with id {} already exists",
+                    id.to_string()
+                ));
+            }
+
+            for guardian in managed_user.guardians.iter() {
+                s.managed_user_guardian_index
+                    .insert(ManagedUserGuardianKey::new(*guardian, id)?, id);
+            }
+            s.managed_users.insert(id, managed_user);
+
+            Ok(())
+        })
+    }
+
+    /// Updates the managed user and the guardian index to match its guardians.
+    pub fn update_managed_user(
+        &self,
+        id: ManagedUserId,
+        managed_user: ManagedUser,
+    ) -> Result<(), String> {
+        STATE.with_borrow_mut(|s| {
+            let existing_managed_user = s
+                .managed_users
+                .get(&id)
+                .ok_or_else(|| format!("Managed user with id {} does not exist", id.to_string()))?;
+
+            for guardian in existing_managed_user.guardians.iter() {
+                s.managed_user_guardian_index
+                    .remove(&ManagedUserGuardianKey::new(*guardian, id)?);
+            }
+            for guardian in managed_user.guardians.iter() {
+                s.managed_user_guardian_index
+                    .insert(ManagedUserGuardianKey::new(*guardian, id)?, id);
+            }
+            s.managed_users.insert(id, managed_user);
+
+            Ok(())
+        })
+    }
+
+    /// Returns the first claim whose certificates are still being transferred, if any.
+    pub fn get_first_managed_user_claim(&self) -> Option<(ManagedUserId, ManagedUserClaim)> {
+        STATE.with_borrow(|s| s.managed_user_claims.first_key_value())
+    }
+
+    pub fn has_managed_user_claims(&self) -> bool {
+        STATE.with_borrow(|s| !s.managed_user_claims.is_empty())
+    }
+
+    pub fn set_managed_user_claim(&self, id: ManagedUserId, claim: ManagedUserClaim) {
+        STATE.with_borrow_mut(|s| s.managed_user_claims.insert(id, claim));
+    }
+
+    pub fn remove_managed_user_claim(&self, id: &ManagedUserId) {
+        STATE.with_borrow_mut(|s| s.managed_user_claims.remove(id));
+    }
+}
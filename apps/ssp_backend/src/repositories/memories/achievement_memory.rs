@@ -0,0 +1,15 @@
+use ic_stable_structures::BTreeMap;
+
+use crate::repositories::{Achievement, AchievementId};
+
+use super::{Memory, ACHIEVEMENTS_MEMORY_ID, MEMORY_MANAGER};
+
+pub type AchievementMemory = BTreeMap<AchievementId, Achievement, Memory>;
+
+pub fn init_achievements() -> AchievementMemory {
+    AchievementMemory::init(get_achievements_memory())
+}
+
+fn get_achievements_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| m.borrow().get(ACHIEVEMENTS_MEMORY_ID))
+}
@@ -0,0 +1,19 @@
+use ic_stable_structures::Log;
+
+use crate::repositories::AuditLogEntry;
+
+use super::{Memory, AUDIT_LOG_DATA_MEMORY_ID, AUDIT_LOG_INDEX_MEMORY_ID, MEMORY_MANAGER};
+
+pub type AuditLogMemory = Log<AuditLogEntry, Memory, Memory>;
+
+pub fn init_audit_log() -> AuditLogMemory {
+    AuditLogMemory::init(get_audit_log_index_memory(), get_audit_log_data_memory()).unwrap()
+}
+
+fn get_audit_log_index_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| m.borrow().get(AUDIT_LOG_INDEX_MEMORY_ID))
+}
+
+fn get_audit_log_data_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| m.borrow().get(AUDIT_LOG_DATA_MEMORY_ID))
+}
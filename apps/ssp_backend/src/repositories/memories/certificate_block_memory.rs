@@ -0,0 +1,25 @@
+use ic_stable_structures::Log;
+
+use crate::repositories::CertificateBlock;
+
+use super::{
+    Memory, CERTIFICATE_BLOCKS_DATA_MEMORY_ID, CERTIFICATE_BLOCKS_INDEX_MEMORY_ID, MEMORY_MANAGER,
+};
+
+pub type CertificateBlockMemory = Log<CertificateBlock, Memory, Memory>;
+
+pub fn init_certificate_blocks() -> CertificateBlockMemory {
+    CertificateBlockMemory::init(
+        get_certificate_blocks_index_memory(),
+        get_certificate_blocks_data_memory(),
+    )
+    .unwrap()
+}
+
+fn get_certificate_blocks_index_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| m.borrow().get(CERTIFICATE_BLOCKS_INDEX_MEMORY_ID))
+}
+
+fn get_certificate_blocks_data_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| m.borrow().get(CERTIFICATE_BLOCKS_DATA_MEMORY_ID))
+}
@@ -1,12 +1,22 @@
 use ic_stable_structures::BTreeMap;
 
 use crate::repositories::{
-    Certificate, CertificateId, CertificateManagedUserIdKey, CertificateUserPrincipalKey,
+    Certificate, CertificateFacetKey, CertificateFileHashKey, CertificateId,
+    CertificateManagedUserIdKey, CertificateManagedUserIdSortKey, CertificateSharing,
+    CertificateUserPrincipalKey, CertificateUserPrincipalSortKey, CertificateVersionKey,
 };
 
 use super::{
-    Memory, CERTIFICATE_MANAGED_USER_ID_INDEX_MEMORY_ID, CERTIFICATE_MEMORY_ID,
-    CERTIFICATE_USER_PRINCIPAL_INDEX_MEMORY_ID, MEMORY_MANAGER,
+    Memory, CERTIFICATE_FACET_INDEX_MEMORY_ID, CERTIFICATE_FILE_HASH_INDEX_MEMORY_ID,
+    CERTIFICATE_ISSUER_PRINCIPAL_CREATED_AT_INDEX_MEMORY_ID,
+    CERTIFICATE_ISSUER_PRINCIPAL_ISSUED_AT_INDEX_MEMORY_ID,
+    CERTIFICATE_MANAGED_USER_ID_CREATED_AT_INDEX_MEMORY_ID,
+    CERTIFICATE_MANAGED_USER_ID_INDEX_MEMORY_ID,
+    CERTIFICATE_MANAGED_USER_ID_ISSUED_AT_INDEX_MEMORY_ID, CERTIFICATE_MEMORY_ID,
+    CERTIFICATE_SHARING_MEMORY_ID, CERTIFICATE_USER_PRINCIPAL_CREATED_AT_INDEX_MEMORY_ID,
+    CERTIFICATE_USER_PRINCIPAL_INDEX_MEMORY_ID,
+    CERTIFICATE_USER_PRINCIPAL_ISSUED_AT_INDEX_MEMORY_ID, CERTIFICATE_VERSIONS_MEMORY_ID,
+    MEMORY_MANAGER,
 };
 
 pub type CertificateMemory = BTreeMap<CertificateId, Certificate, Memory>;
@@ -14,6 +24,14 @@ pub type CertificateUserPrincipalIndexMemory =
     BTreeMap<CertificateUserPrincipalKey, CertificateId, Memory>;
 pub type CertificateManagedUserIdIndexMemory =
     BTreeMap<CertificateManagedUserIdKey, CertificateId, Memory>;
+pub type CertificateVersionsMemory = BTreeMap<CertificateVersionKey, Certificate, Memory>;
+pub type CertificateUserPrincipalSortIndexMemory =
+    BTreeMap<CertificateUserPrincipalSortKey, CertificateId, Memory>;
+pub type CertificateManagedUserIdSortIndexMemory =
+    BTreeMap<CertificateManagedUserIdSortKey, CertificateId, Memory>;
+pub type CertificateFacetIndexMemory = BTreeMap<CertificateFacetKey, CertificateId, Memory>;
+pub type CertificateSharingMemory = BTreeMap<CertificateId, CertificateSharing, Memory>;
+pub type CertificateFileHashIndexMemory = BTreeMap<CertificateFileHashKey, CertificateId, Memory>;
 
 pub fn init_certificates() -> CertificateMemory {
     BTreeMap::init(get_certificates_memory())
@@ -27,6 +45,53 @@ pub fn init_certificate_managed_user_id_index() -> CertificateManagedUserIdIndex
     BTreeMap::init(get_certificate_managed_user_id_index_memory())
 }
 
+pub fn init_certificate_versions() -> CertificateVersionsMemory {
+    BTreeMap::init(get_certificate_versions_memory())
+}
+
+pub fn init_certificate_user_principal_created_at_index() -> CertificateUserPrincipalSortIndexMemory
+{
+    BTreeMap::init(get_certificate_user_principal_created_at_index_memory())
+}
+
+pub fn init_certificate_user_principal_issued_at_index() -> CertificateUserPrincipalSortIndexMemory
+{
+    BTreeMap::init(get_certificate_user_principal_issued_at_index_memory())
+}
+
+pub fn init_certificate_managed_user_id_created_at_index() -> CertificateManagedUserIdSortIndexMemory
+{
+    BTreeMap::init(get_certificate_managed_user_id_created_at_index_memory())
+}
+
+pub fn init_certificate_managed_user_id_issued_at_index() -> CertificateManagedUserIdSortIndexMemory
+{
+    BTreeMap::init(get_certificate_managed_user_id_issued_at_index_memory())
+}
+
+/// The issuer principal sort indexes use the same keys as the user principal ones.
+pub fn init_certificate_issuer_principal_created_at_index(
+) -> CertificateUserPrincipalSortIndexMemory {
+    BTreeMap::init(get_certificate_issuer_principal_created_at_index_memory())
+}
+
+pub fn init_certificate_issuer_principal_issued_at_index() -> CertificateUserPrincipalSortIndexMemory
+{
+    BTreeMap::init(get_certificate_issuer_principal_issued_at_index_memory())
+}
+
+pub fn init_certificate_facet_index() -> CertificateFacetIndexMemory {
+    BTreeMap::init(get_certificate_facet_index_memory())
+}
+
+pub fn init_certificate_sharing() -> CertificateSharingMemory {
+    BTreeMap::init(get_certificate_sharing_memory())
+}
+
+pub fn init_certificate_file_hash_index() -> CertificateFileHashIndexMemory {
+    BTreeMap::init(get_certificate_file_hash_index_memory())
+}
+
 fn get_certificates_memory() -> Memory {
     MEMORY_MANAGER.with(|m| m.borrow().get(CERTIFICATE_MEMORY_ID))
 }
@@ -38,3 +103,61 @@ fn get_certificate_user_principal_index_memory() -> Memory {
 fn get_certificate_managed_user_id_index_memory() -> Memory {
     MEMORY_MANAGER.with(|m| m.borrow().get(CERTIFICATE_MANAGED_USER_ID_INDEX_MEMORY_ID))
 }
+
+fn get_certificate_versions_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| m.borrow().get(CERTIFICATE_VERSIONS_MEMORY_ID))
+}
+
+fn get_certificate_user_principal_created_at_index_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| {
+        m.borrow()
+            .get(CERTIFICATE_USER_PRINCIPAL_CREATED_AT_INDEX_MEMORY_ID)
+    })
+}
+
+fn get_certificate_user_principal_issued_at_index_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| {
+        m.borrow()
+            .get(CERTIFICATE_USER_PRINCIPAL_ISSUED_AT_INDEX_MEMORY_ID)
+    })
+}
+
+fn get_certificate_managed_user_id_created_at_index_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| {
+        m.borrow()
+            .get(CERTIFICATE_MANAGED_USER_ID_CREATED_AT_INDEX_MEMORY_ID)
+    })
+}
+
+fn get_certificate_managed_user_id_issued_at_index_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| {
+        m.borrow()
+            .get(CERTIFICATE_MANAGED_USER_ID_ISSUED_AT_INDEX_MEMORY_ID)
+    })
+}
+
+fn get_certificate_issuer_principal_created_at_index_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| {
+        m.borrow()
+            .get(CERTIFICATE_ISSUER_PRINCIPAL_CREATED_AT_INDEX_MEMORY_ID)
+    })
+}
+
+fn get_certificate_issuer_principal_issued_at_index_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| {
+        m.borrow()
+            .get(CERTIFICATE_ISSUER_PRINCIPAL_ISSUED_AT_INDEX_MEMORY_ID)
+    })
+}
+
+fn get_certificate_facet_index_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| m.borrow().get(CERTIFICATE_FACET_INDEX_MEMORY_ID))
+}
+
+fn get_certificate_sharing_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| m.borrow().get(CERTIFICATE_SHARING_MEMORY_ID))
+}
+
+fn get_certificate_file_hash_index_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| m.borrow().get(CERTIFICATE_FILE_HASH_INDEX_MEMORY_ID))
+}
@@ -1,15 +1,76 @@
-use ic_stable_structures::Cell;
+use candid::Principal;
+use ic_certification::Hash;
+use ic_stable_structures::{BTreeMap, Cell};
+use ssp_backend_types::Timestamp;
 
-use crate::repositories::{Salt, EMPTY_SALT};
+use crate::repositories::{DelegationSignature, DelegationSignatureKey, Salt, EMPTY_SALT};
 
-use super::{Memory, MEMORY_MANAGER, SALT_MEMORY_ID};
+use super::{
+    Memory, DELEGATION_SEEDS_MEMORY_ID, DELEGATION_SIGNATURES_MEMORY_ID,
+    DELEGATION_SIGNATURE_SEED_INDEX_MEMORY_ID, MEMORY_MANAGER, PERMANENT_SIGNATURES_MEMORY_ID,
+    REVOKED_DELEGATIONS_MEMORY_ID, REVOKED_SESSIONS_MEMORY_ID, SALT_MEMORY_ID,
+};
 
 pub type SaltMemory = Cell<Salt, Memory>;
+pub type DelegationSignaturesMemory = BTreeMap<DelegationSignatureKey, (), Memory>;
+pub type DelegationSignatureSeedIndexMemory = BTreeMap<(Hash, Hash), DelegationSignature, Memory>;
+pub type PermanentSignaturesMemory = BTreeMap<(Hash, Hash), (), Memory>;
+pub type RevokedDelegationsMemory = BTreeMap<DelegationSignatureKey, (), Memory>;
+pub type RevokedSessionsMemory = BTreeMap<(Hash, Hash), Timestamp, Memory>;
+pub type DelegationSeedsMemory = BTreeMap<Principal, Hash, Memory>;
 
 pub fn init_salt() -> SaltMemory {
     SaltMemory::init(get_salt_memory(), EMPTY_SALT).unwrap()
 }
 
+pub fn init_delegation_signatures() -> DelegationSignaturesMemory {
+    BTreeMap::init(get_delegation_signatures_memory())
+}
+
+pub fn init_delegation_signature_seed_index() -> DelegationSignatureSeedIndexMemory {
+    BTreeMap::init(get_delegation_signature_seed_index_memory())
+}
+
+pub fn init_permanent_signatures() -> PermanentSignaturesMemory {
+    BTreeMap::init(get_permanent_signatures_memory())
+}
+
+pub fn init_revoked_delegations() -> RevokedDelegationsMemory {
+    BTreeMap::init(get_revoked_delegations_memory())
+}
+
+pub fn init_revoked_sessions() -> RevokedSessionsMemory {
+    BTreeMap::init(get_revoked_sessions_memory())
+}
+
+pub fn init_delegation_seeds() -> DelegationSeedsMemory {
+    BTreeMap::init(get_delegation_seeds_memory())
+}
+
 fn get_salt_memory() -> Memory {
     MEMORY_MANAGER.with(|m| m.borrow().get(SALT_MEMORY_ID))
 }
+
+fn get_delegation_signatures_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| m.borrow().get(DELEGATION_SIGNATURES_MEMORY_ID))
+}
+
+fn get_delegation_signature_seed_index_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| m.borrow().get(DELEGATION_SIGNATURE_SEED_INDEX_MEMORY_ID))
+}
+
+fn get_permanent_signatures_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| m.borrow().get(PERMANENT_SIGNATURES_MEMORY_ID))
+}
+
+fn get_revoked_delegations_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| m.borrow().get(REVOKED_DELEGATIONS_MEMORY_ID))
+}
+
+fn get_revoked_sessions_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| m.borrow().get(REVOKED_SESSIONS_MEMORY_ID))
+}
+
+fn get_delegation_seeds_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| m.borrow().get(DELEGATION_SEEDS_MEMORY_ID))
+}
@@ -0,0 +1,24 @@
+use ic_stable_structures::BTreeMap;
+
+use crate::repositories::{File, FileChunkKey, FileHash};
+
+use super::{Memory, FILE_CHUNK_MEMORY_ID, FILE_MEMORY_ID, MEMORY_MANAGER};
+
+pub type FileMemory = BTreeMap<FileHash, File, Memory>;
+pub type FileChunkMemory = BTreeMap<FileChunkKey, Vec<u8>, Memory>;
+
+pub fn init_files() -> FileMemory {
+    BTreeMap::init(get_files_memory())
+}
+
+pub fn init_file_chunks() -> FileChunkMemory {
+    BTreeMap::init(get_file_chunks_memory())
+}
+
+fn get_files_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| m.borrow().get(FILE_MEMORY_ID))
+}
+
+fn get_file_chunks_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| m.borrow().get(FILE_CHUNK_MEMORY_ID))
+}
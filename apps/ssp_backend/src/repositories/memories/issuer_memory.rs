@@ -0,0 +1,15 @@
+use ic_stable_structures::BTreeMap;
+
+use crate::repositories::{Issuer, IssuerId};
+
+use super::{Memory, ISSUERS_MEMORY_ID, MEMORY_MANAGER};
+
+pub type IssuerMemory = BTreeMap<IssuerId, Issuer, Memory>;
+
+pub fn init_issuers() -> IssuerMemory {
+    IssuerMemory::init(get_issuers_memory())
+}
+
+fn get_issuers_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| m.borrow().get(ISSUERS_MEMORY_ID))
+}
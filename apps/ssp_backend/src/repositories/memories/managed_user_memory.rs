@@ -0,0 +1,36 @@
+use ic_stable_structures::BTreeMap;
+
+use crate::repositories::{ManagedUser, ManagedUserClaim, ManagedUserGuardianKey, ManagedUserId};
+
+use super::{
+    Memory, MANAGED_USERS_MEMORY_ID, MANAGED_USER_CLAIMS_MEMORY_ID,
+    MANAGED_USER_GUARDIAN_INDEX_MEMORY_ID, MEMORY_MANAGER,
+};
+
+pub type ManagedUserMemory = BTreeMap<ManagedUserId, ManagedUser, Memory>;
+pub type ManagedUserGuardianIndexMemory = BTreeMap<ManagedUserGuardianKey, ManagedUserId, Memory>;
+pub type ManagedUserClaimMemory = BTreeMap<ManagedUserId, ManagedUserClaim, Memory>;
+
+pub fn init_managed_users() -> ManagedUserMemory {
+    ManagedUserMemory::init(get_managed_users_memory())
+}
+
+pub fn init_managed_user_guardian_index() -> ManagedUserGuardianIndexMemory {
+    ManagedUserGuardianIndexMemory::init(get_managed_user_guardian_index_memory())
+}
+
+pub fn init_managed_user_claims() -> ManagedUserClaimMemory {
+    ManagedUserClaimMemory::init(get_managed_user_claims_memory())
+}
+
+fn get_managed_users_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| m.borrow().get(MANAGED_USERS_MEMORY_ID))
+}
+
+fn get_managed_user_guardian_index_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| m.borrow().get(MANAGED_USER_GUARDIAN_INDEX_MEMORY_ID))
+}
+
+fn get_managed_user_claims_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| m.borrow().get(MANAGED_USER_CLAIMS_MEMORY_ID))
+}
@@ -11,9 +11,45 @@ thread_local! {
 
 pub(super) const SALT_MEMORY_ID: MemoryId = MemoryId::new(0);
 pub(super) const USERS_MEMORY_ID: MemoryId = MemoryId::new(1);
+// the users created before multiple OIDC providers were supported are indexed by sub only
 pub(super) const USER_SUB_INDEX_MEMORY_ID: MemoryId = MemoryId::new(2);
 pub(super) const USER_DB_ID_INDEX_MEMORY_ID: MemoryId = MemoryId::new(3);
 pub(super) const CONFIG_MEMORY_ID: MemoryId = MemoryId::new(4);
 pub(super) const CERTIFICATE_MEMORY_ID: MemoryId = MemoryId::new(5);
 pub(super) const CERTIFICATE_USER_PRINCIPAL_INDEX_MEMORY_ID: MemoryId = MemoryId::new(6);
 pub(super) const CERTIFICATE_MANAGED_USER_ID_INDEX_MEMORY_ID: MemoryId = MemoryId::new(7);
+pub(super) const CERTIFICATE_VERSIONS_MEMORY_ID: MemoryId = MemoryId::new(8);
+pub(super) const CERTIFICATE_USER_PRINCIPAL_CREATED_AT_INDEX_MEMORY_ID: MemoryId = MemoryId::new(9);
+pub(super) const CERTIFICATE_USER_PRINCIPAL_ISSUED_AT_INDEX_MEMORY_ID: MemoryId = MemoryId::new(10);
+pub(super) const CERTIFICATE_MANAGED_USER_ID_CREATED_AT_INDEX_MEMORY_ID: MemoryId =
+    MemoryId::new(11);
+pub(super) const CERTIFICATE_MANAGED_USER_ID_ISSUED_AT_INDEX_MEMORY_ID: MemoryId =
+    MemoryId::new(12);
+pub(super) const CERTIFICATE_FACET_INDEX_MEMORY_ID: MemoryId = MemoryId::new(13);
+pub(super) const CERTIFICATE_SHARING_MEMORY_ID: MemoryId = MemoryId::new(14);
+pub(super) const FILE_MEMORY_ID: MemoryId = MemoryId::new(15);
+pub(super) const FILE_CHUNK_MEMORY_ID: MemoryId = MemoryId::new(16);
+// memory 17 was used by the single JWKS, now stored in the OIDC providers
+pub(super) const DELEGATION_SIGNATURES_MEMORY_ID: MemoryId = MemoryId::new(18);
+pub(super) const OIDC_PROVIDERS_MEMORY_ID: MemoryId = MemoryId::new(19);
+pub(super) const REVOKED_DELEGATIONS_MEMORY_ID: MemoryId = MemoryId::new(20);
+pub(super) const DELEGATION_SEEDS_MEMORY_ID: MemoryId = MemoryId::new(21);
+pub(super) const CERTIFICATE_ISSUER_PRINCIPAL_CREATED_AT_INDEX_MEMORY_ID: MemoryId =
+    MemoryId::new(22);
+pub(super) const CERTIFICATE_ISSUER_PRINCIPAL_ISSUED_AT_INDEX_MEMORY_ID: MemoryId =
+    MemoryId::new(23);
+pub(super) const MANAGED_USERS_MEMORY_ID: MemoryId = MemoryId::new(24);
+pub(super) const MANAGED_USER_GUARDIAN_INDEX_MEMORY_ID: MemoryId = MemoryId::new(25);
+pub(super) const ISSUERS_MEMORY_ID: MemoryId = MemoryId::new(26);
+pub(super) const AUDIT_LOG_INDEX_MEMORY_ID: MemoryId = MemoryId::new(27);
+pub(super) const AUDIT_LOG_DATA_MEMORY_ID: MemoryId = MemoryId::new(28);
+pub(super) const CERTIFICATE_BLOCKS_INDEX_MEMORY_ID: MemoryId = MemoryId::new(29);
+pub(super) const CERTIFICATE_BLOCKS_DATA_MEMORY_ID: MemoryId = MemoryId::new(30);
+pub(super) const ACHIEVEMENTS_MEMORY_ID: MemoryId = MemoryId::new(31);
+pub(super) const SCHEMA_VERSION_MEMORY_ID: MemoryId = MemoryId::new(32);
+pub(super) const USER_ISSUER_SUB_INDEX_MEMORY_ID: MemoryId = MemoryId::new(33);
+pub(super) const DELEGATION_SIGNATURE_SEED_INDEX_MEMORY_ID: MemoryId = MemoryId::new(34);
+pub(super) const REVOKED_SESSIONS_MEMORY_ID: MemoryId = MemoryId::new(35);
+pub(super) const PERMANENT_SIGNATURES_MEMORY_ID: MemoryId = MemoryId::new(36);
+pub(super) const CERTIFICATE_FILE_HASH_INDEX_MEMORY_ID: MemoryId = MemoryId::new(37);
+pub(super) const MANAGED_USER_CLAIMS_MEMORY_ID: MemoryId = MemoryId::new(38);
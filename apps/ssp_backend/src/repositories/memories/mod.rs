@@ -1,12 +1,28 @@
+mod achievement_memory;
+mod audit_log_memory;
+mod certificate_block_memory;
 mod certificate_memory;
 mod config_memory;
 mod delegation_memory;
+mod file_memory;
+mod issuer_memory;
+mod managed_user_memory;
 mod memory_manager;
+mod oidc_provider_memory;
+mod schema_version_memory;
 mod user_memory;
 
 use memory_manager::*;
 
+pub(super) use achievement_memory::*;
+pub(super) use audit_log_memory::*;
+pub(super) use certificate_block_memory::*;
 pub(super) use certificate_memory::*;
 pub(super) use config_memory::*;
 pub(super) use delegation_memory::*;
+pub(super) use file_memory::*;
+pub(super) use issuer_memory::*;
+pub(super) use managed_user_memory::*;
+pub(super) use oidc_provider_memory::*;
+pub(super) use schema_version_memory::*;
 pub(super) use user_memory::*;
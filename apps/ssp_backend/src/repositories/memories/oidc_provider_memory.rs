@@ -0,0 +1,15 @@
+use ic_stable_structures::BTreeMap;
+
+use crate::repositories::{OidcIssuer, OidcProvider};
+
+use super::{Memory, MEMORY_MANAGER, OIDC_PROVIDERS_MEMORY_ID};
+
+pub type OidcProviderMemory = BTreeMap<OidcIssuer, OidcProvider, Memory>;
+
+pub fn init_oidc_providers() -> OidcProviderMemory {
+    OidcProviderMemory::init(get_oidc_providers_memory())
+}
+
+fn get_oidc_providers_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| m.borrow().get(OIDC_PROVIDERS_MEMORY_ID))
+}
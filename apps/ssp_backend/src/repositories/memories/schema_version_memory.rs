@@ -0,0 +1,15 @@
+use ic_stable_structures::Cell;
+
+use crate::repositories::{SchemaVersion, INITIAL_SCHEMA_VERSION};
+
+use super::{Memory, MEMORY_MANAGER, SCHEMA_VERSION_MEMORY_ID};
+
+pub type SchemaVersionMemory = Cell<SchemaVersion, Memory>;
+
+pub fn init_schema_version() -> SchemaVersionMemory {
+    SchemaVersionMemory::init(get_schema_version_memory(), INITIAL_SCHEMA_VERSION).unwrap()
+}
+
+fn get_schema_version_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| m.borrow().get(SCHEMA_VERSION_MEMORY_ID))
+}
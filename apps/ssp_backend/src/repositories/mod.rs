@@ -1,13 +1,33 @@
+mod achievement_repository;
+mod audit_log_repository;
+mod certificate_block_repository;
 mod certificate_repository;
+mod certified_data_repository;
 mod config_repository;
 mod delegation_repository;
+mod file_repository;
+mod http_certification_repository;
+mod issuer_repository;
+mod managed_user_repository;
 mod memories;
+mod oidc_provider_repository;
+mod schema_version_repository;
 mod types;
 mod user_repository;
 
+pub use achievement_repository::*;
+pub use audit_log_repository::*;
+pub use certificate_block_repository::*;
 pub use certificate_repository::*;
+pub use certified_data_repository::*;
 pub use config_repository::*;
 pub use delegation_repository::*;
+pub use file_repository::*;
+pub use http_certification_repository::*;
+pub use issuer_repository::*;
+pub use managed_user_repository::*;
 use memories::*;
+pub use oidc_provider_repository::*;
+pub use schema_version_repository::*;
 pub use types::*;
 pub use user_repository::*;
@@ -0,0 +1,93 @@
+use std::{cell::RefCell, collections::HashMap};
+
+use ssp_backend_types::{Auth0JWKSet, Timestamp};
+
+use super::{
+    init_oidc_providers, DateTime, OidcConfig, OidcIssuer, OidcProvider, OidcProviderMemory,
+};
+
+struct OidcProviderState {
+    providers: OidcProviderMemory,
+    /// When the JWKS of each provider was last refreshed on demand.
+    /// It's not persisted across upgrades.
+    jwks_refreshed_at: HashMap<OidcIssuer, Timestamp>,
+}
+
+impl Default for OidcProviderState {
+    fn default() -> Self {
+        Self {
+            providers: init_oidc_providers(),
+            jwks_refreshed_at: HashMap::new(),
+        }
+    }
+}
+
+thread_local! {
+    static STATE: RefCell<OidcProviderState> = RefCell::new(OidcProviderState::default());
+}
+
+#[derive(Default)]
+pub struct OidcProviderRepository {}
+
+impl OidcProviderRepository {
+    pub fn get_provider(&self, issuer: &str) -> Option<OidcProvider> {
+        STATE.with_borrow(|s| s.providers.get(&issuer.to_string()))
+    }
+
+    pub fn get_providers(&self) -> Vec<OidcProvider> {
+        STATE.with_borrow(|s| s.providers.iter().map(|(_, provider)| provider).collect())
+    }
+
+    /// Adds the provider, or updates its config if a provider with the same issuer exists.
+    /// The JWKS of an existing provider is kept, unless its URL has changed.
+    pub fn set_provider_config(&self, config: OidcConfig) {
+        STATE.with_borrow_mut(|s| {
+            let provider = match s.providers.get(&config.issuer) {
+                Some(provider) if provider.config.jwks_url == config.jwks_url => OidcProvider {
+                    config: config.clone(),
+                    ..provider
+                },
+                _ => OidcProvider::new(config.clone()),
+            };
+            s.providers.insert(config.issuer, provider);
+        });
+    }
+
+    /// Returns `false` if the provider doesn't exist.
+    pub fn remove_provider(&self, issuer: &str) -> bool {
+        STATE.with_borrow_mut(|s| s.providers.remove(&issuer.to_string()).is_some())
+    }
+
+    /// Stores the JWKS of the provider, together with the time it was fetched.
+    pub fn set_jwks(
+        &self,
+        issuer: &str,
+        jwks: Auth0JWKSet,
+        fetched_at: Option<DateTime>,
+    ) -> Result<(), String> {
+        STATE.with_borrow_mut(|s| {
+            let provider = s
+                .providers
+                .get(&issuer.to_string())
+                .ok_or_else(|| format!("OIDC provider {} not found", issuer))?;
+            s.providers.insert(
+                issuer.to_string(),
+                OidcProvider {
+                    jwks: Some(jwks),
+                    jwks_fetched_at: fetched_at,
+                    ..provider
+                },
+            );
+
+            Ok(())
+        })
+    }
+
+    pub fn get_jwks_refreshed_at(&self, issuer: &str) -> Option<Timestamp> {
+        STATE.with_borrow(|s| s.jwks_refreshed_at.get(issuer).copied())
+    }
+
+    pub fn set_jwks_refreshed_at(&self, issuer: &str, refreshed_at: Timestamp) {
+        STATE.with_borrow_mut(|s| s.jwks_refreshed_at.insert(issuer.to_string(), refreshed_at));
+    }
+}
@@ -0,0 +1,38 @@
+use std::cell::RefCell;
+
+use super::{init_schema_version, SchemaVersion, SchemaVersionMemory};
+
+struct SchemaVersionState {
+    schema_version: SchemaVersionMemory,
+}
+
+impl Default for SchemaVersionState {
+    fn default() -> Self {
+        Self {
+            schema_version: init_schema_version(),
+        }
+    }
+}
+
+thread_local! {
+    static STATE: RefCell<SchemaVersionState> = RefCell::new(SchemaVersionState::default());
+}
+
+#[derive(Default)]
+pub struct SchemaVersionRepository {}
+
+impl SchemaVersionRepository {
+    pub fn get_schema_version(&self) -> SchemaVersion {
+        STATE.with_borrow(|s| *s.schema_version.get())
+    }
+
+    pub fn set_schema_version(&self, schema_version: SchemaVersion) -> Result<(), String> {
+        STATE.with_borrow_mut(|s| {
+            s.schema_version
+                .set(schema_version)
+                .map_err(|err| format!("Cannot set schema version: {:?}", err))
+        })?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,151 @@
+use std::borrow::Cow;
+
+use candid::{CandidType, Decode, Deserialize, Encode};
+use ic_stable_structures::{storable::Bound, Storable};
+
+use super::{Certificate, DateTime, IssuerId, Uuid};
+
+pub type AchievementId = Uuid;
+
+/// A badge class that certificates can reference, exported as an Open Badges achievement.
+///
+/// Achievements cannot be changed once created, as they are part of
+/// the certified Open Badges responses of the certificates referencing them.
+#[derive(Debug, CandidType, Deserialize, Clone, PartialEq, Eq)]
+pub struct Achievement {
+    pub name: String,
+    pub description: String,
+    /// The narrative of what is required to earn the achievement.
+    pub criteria: String,
+    /// Only certificates of this sport category can reference the achievement, if set.
+    pub sport_category: Option<String>,
+    /// Only certificates issued on behalf of this issuer can reference the achievement, if set.
+    pub issuer_id: Option<IssuerId>,
+    pub created_at: DateTime,
+}
+
+impl Achievement {
+    /// Whether the certificate can reference the achievement.
+    pub fn matches(&self, certificate: &Certificate) -> bool {
+        let sport_category_matches = self.sport_category.as_ref().map_or(true, |sport_category| {
+            *sport_category == certificate.content.sport_category
+        });
+        let issuer_matches = self.issuer_id.map_or(true, |issuer_id| {
+            certificate
+                .issuer
+                .as_ref()
+                .is_some_and(|issuer| issuer.id == issuer_id)
+        });
+
+        sport_category_matches && issuer_matches
+    }
+}
+
+impl Storable for Achievement {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use candid::Principal;
+    use rstest::*;
+
+    use crate::{
+        repositories::{CertificateContent, CertificateIssuer},
+        system_api::get_date_time,
+    };
+
+    #[rstest]
+    fn storable_impl() {
+        let achievement = achievement();
+        let serialized_achievement = achievement.to_bytes();
+        let deserialized_achievement = Achievement::from_bytes(serialized_achievement);
+
+        assert_eq!(achievement, deserialized_achievement);
+    }
+
+    #[rstest]
+    fn matches() {
+        let issuer_id = Uuid::from(1u128);
+        let issued_certificate = certificate(Some(issuer_id));
+
+        assert!(achievement().matches(&issued_certificate));
+        assert!(Achievement {
+            issuer_id: Some(issuer_id),
+            ..achievement()
+        }
+        .matches(&issued_certificate));
+        assert!(Achievement {
+            sport_category: None,
+            ..achievement()
+        }
+        .matches(&issued_certificate));
+        assert!(!Achievement {
+            sport_category: Some("Football".to_string()),
+            ..achievement()
+        }
+        .matches(&issued_certificate));
+        assert!(!Achievement {
+            issuer_id: Some(Uuid::from(2u128)),
+            ..achievement()
+        }
+        .matches(&issued_certificate));
+        assert!(!Achievement {
+            issuer_id: Some(issuer_id),
+            ..achievement()
+        }
+        .matches(&certificate(None)));
+    }
+
+    fn achievement() -> Achievement {
+        Achievement {
+            name: "Swimming level 1".to_string(),
+            description: "Swims 25 meters without stopping.".to_string(),
+            criteria: "Swim 25 meters freestyle in front of a coach.".to_string(),
+            sport_category: Some("Swimming".to_string()),
+            issuer_id: None,
+            created_at: date_time(),
+        }
+    }
+
+    fn certificate(issuer_id: Option<IssuerId>) -> Certificate {
+        Certificate {
+            content: CertificateContent {
+                external_id: None,
+                file_hash: None,
+                file_uri: None,
+                issued_at: date_time(),
+                issuer_club_name: None,
+                issuer_full_name: None,
+                name: "Swimming level 1".to_string(),
+                notes: None,
+                sport_category: "Swimming".to_string(),
+            },
+            achievement_id: None,
+            created_at: date_time(),
+            issuer: issuer_id.map(|id| CertificateIssuer {
+                id,
+                name: "FC Example".to_string(),
+                verified: true,
+            }),
+            issuer_principal: None,
+            managed_user_id: None,
+            revocation: None,
+            updated_at: None,
+            user_principal: Principal::from_slice(&[1]),
+        }
+    }
+
+    fn date_time() -> DateTime {
+        DateTime::new(get_date_time().unwrap()).unwrap()
+    }
+}
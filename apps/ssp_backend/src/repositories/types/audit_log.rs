@@ -0,0 +1,159 @@
+use std::borrow::Cow;
+
+use candid::{CandidType, Decode, Deserialize, Encode, Principal};
+use canister_sig_util::hash_bytes;
+use ic_certification::Hash;
+use ic_stable_structures::{storable::Bound, Storable};
+use serde::Serialize;
+use ssp_backend_types::Timestamp;
+
+use crate::utils::cbor_serialize;
+
+use super::{
+    AchievementId, CertificateId, FileHash, IssuerId, ManagedUserId, UploadId, UserPrincipal,
+};
+
+/// The position of an entry in the audit log, starting from 0.
+pub type AuditLogIndex = u64;
+
+/// The previous hash of the first entry, which is also the tip hash of an empty audit log.
+pub const AUDIT_LOG_GENESIS_HASH: Hash = [0; 32];
+
+/// The mutating call recorded by an [AuditLogEntry].
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub enum AuditLogOperation {
+    #[serde(rename = "create_user")]
+    CreateUser { user_principal: UserPrincipal },
+    #[serde(rename = "create_certificate")]
+    CreateCertificate { id: CertificateId },
+    #[serde(rename = "set_backend_principal")]
+    SetBackendPrincipal,
+    #[serde(rename = "set_jwks")]
+    SetJwks { issuer: String },
+    #[serde(rename = "sync_jwks")]
+    SyncJwks,
+    #[serde(rename = "set_delegation_config")]
+    SetDelegationConfig,
+    #[serde(rename = "set_oidc_provider")]
+    SetOidcProvider { issuer: String },
+    #[serde(rename = "remove_oidc_provider")]
+    RemoveOidcProvider { issuer: String },
+    #[serde(rename = "revoke_session")]
+    RevokeSession,
+    #[serde(rename = "revoke_all_sessions")]
+    RevokeAllSessions,
+    #[serde(rename = "add_managed_user_guardian")]
+    AddManagedUserGuardian { managed_user_id: ManagedUserId },
+    #[serde(rename = "remove_managed_user_guardian")]
+    RemoveManagedUserGuardian { managed_user_id: ManagedUserId },
+    #[serde(rename = "claim_managed_user")]
+    ClaimManagedUser {
+        managed_user_id: ManagedUserId,
+        user_principal: UserPrincipal,
+    },
+    #[serde(rename = "create_issuer")]
+    CreateIssuer { id: IssuerId },
+    #[serde(rename = "set_issuer_verified")]
+    SetIssuerVerified { id: IssuerId },
+    #[serde(rename = "add_issuer_member")]
+    AddIssuerMember { issuer_id: IssuerId },
+    #[serde(rename = "remove_issuer_member")]
+    RemoveIssuerMember { issuer_id: IssuerId },
+    #[serde(rename = "create_achievement")]
+    CreateAchievement { id: AchievementId },
+    #[serde(rename = "update_certificate")]
+    UpdateCertificate { id: CertificateId },
+    #[serde(rename = "revoke_certificate")]
+    RevokeCertificate { id: CertificateId },
+    #[serde(rename = "set_certificate_public")]
+    SetCertificatePublic { id: CertificateId },
+    #[serde(rename = "create_certificate_share_token")]
+    CreateCertificateShareToken { id: CertificateId },
+    #[serde(rename = "revoke_certificate_share_token")]
+    RevokeCertificateShareToken { id: CertificateId },
+    #[serde(rename = "create_upload")]
+    CreateUpload { upload_id: UploadId },
+    #[serde(rename = "upload_chunk")]
+    UploadChunk { upload_id: UploadId },
+    #[serde(rename = "commit_upload")]
+    CommitUpload {
+        upload_id: UploadId,
+        file_hash: FileHash,
+    },
+}
+
+/// An entry of the append-only audit log.
+///
+/// Entries are hash-chained: each entry contains the hash of the previous one,
+/// so that the hash of the last entry (the tip) commits to the whole log.
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct AuditLogEntry {
+    pub caller: Principal,
+    pub timestamp: Timestamp,
+    pub operation: AuditLogOperation,
+    /// The hash of the Candid encoded arguments of the call.
+    pub payload_hash: Hash,
+    pub previous_hash: Hash,
+}
+
+impl AuditLogEntry {
+    pub fn entry_cbor(&self) -> Vec<u8> {
+        cbor_serialize(&self).unwrap()
+    }
+
+    /// The SHA-256 hash of the entry CBOR.
+    pub fn hash(&self) -> Hash {
+        hash_bytes(self.entry_cbor())
+    }
+}
+
+impl Storable for AuditLogEntry {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    #[rstest]
+    fn storable_impl() {
+        let entry = audit_log_entry();
+        let serialized_entry = entry.to_bytes();
+        let deserialized_entry = AuditLogEntry::from_bytes(serialized_entry);
+
+        assert_eq!(entry, deserialized_entry);
+    }
+
+    #[rstest]
+    fn hash_commits_to_previous_hash() {
+        let entry = audit_log_entry();
+        let chained_entry = AuditLogEntry {
+            previous_hash: entry.hash(),
+            ..entry.clone()
+        };
+
+        assert_ne!(entry.hash(), chained_entry.hash());
+        assert_eq!(entry.hash(), audit_log_entry().hash());
+    }
+
+    fn audit_log_entry() -> AuditLogEntry {
+        AuditLogEntry {
+            caller: Principal::from_slice(&[1, 2, 3]),
+            timestamp: 1704063600000000000,
+            operation: AuditLogOperation::SetJwks {
+                issuer: "https://example.com/".to_string(),
+            },
+            payload_hash: [1; 32],
+            previous_hash: AUDIT_LOG_GENESIS_HASH,
+        }
+    }
+}
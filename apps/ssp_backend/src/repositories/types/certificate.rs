@@ -1,6 +1,10 @@
-use std::{borrow::Cow, ops::RangeBounds};
+use std::{
+    borrow::Cow,
+    ops::{RangeBounds, RangeInclusive},
+};
 
 use candid::{CandidType, Decode, Deserialize, Encode, Principal};
+use canister_sig_util::hash_bytes;
 use ic_certification::{leaf, leaf_hash, AsHashTree, Hash, HashTree};
 use ic_stable_structures::{
     storable::{Blob, Bound},
@@ -10,13 +14,21 @@ use serde::Serialize;
 
 use crate::utils::cbor_serialize;
 
-use super::{DateTime, Uuid};
+use super::{AchievementId, DateTime, FileHash, IssuerId, Uuid};
 
 pub type CertificateId = Uuid;
+pub type CertificateVersion = u32;
+
+pub const FIRST_CERTIFICATE_VERSION: CertificateVersion = 1;
 
+/// See [Certificate] for the fields skipped when serializing.
 #[derive(Debug, CandidType, Serialize, Deserialize, Clone, PartialEq, Eq)]
 pub struct CertificateContent {
     pub external_id: Option<String>,
+    /// The hash of the file in the file store.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_hash: Option<FileHash>,
+    /// Deprecated, files should be uploaded to the file store and referenced by `file_hash`.
     pub file_uri: Option<String>,
     pub issued_at: DateTime,
     pub issuer_club_name: Option<String>,
@@ -26,15 +38,53 @@ pub struct CertificateContent {
     pub sport_category: String,
 }
 
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct CertificateRevocation {
+    pub reason: String,
+    pub revoked_at: DateTime,
+}
+
+/// The registered issuer of a certificate, as it was when the certificate was issued.
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct CertificateIssuer {
+    pub id: IssuerId,
+    pub name: String,
+    pub verified: bool,
+}
+
+/// A version of a certificate, certified as its CBOR.
+///
+/// The optional fields added after the first release are skipped when serializing
+/// if not set, so that the CBOR (and therefore the certified hash) of the versions
+/// that were certified before those fields existed doesn't change.
 #[derive(Debug, CandidType, Serialize, Deserialize, Clone, PartialEq, Eq)]
 pub struct Certificate {
+    /// The achievement that the certificate awards, exported as an Open Badges credential.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub achievement_id: Option<AchievementId>,
     pub content: CertificateContent,
     pub created_at: DateTime,
+    /// The registered issuer on behalf of which the certificate was issued.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub issuer: Option<CertificateIssuer>,
+    /// The principal that issued the certificate to `user_principal`, the recipient.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub issuer_principal: Option<Principal>,
     pub managed_user_id: Option<Uuid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revocation: Option<CertificateRevocation>,
+    /// The date time at which this version of the certificate was created.
+    /// Only set for versions other than the first one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<DateTime>,
     pub user_principal: Principal,
 }
 
 impl Certificate {
+    pub fn is_revoked(&self) -> bool {
+        self.revocation.is_some()
+    }
+
     pub fn certificate_cbor(&self) -> Vec<u8> {
         cbor_serialize(&self).unwrap()
     }
@@ -66,6 +116,52 @@ impl Storable for Certificate {
     const BOUND: Bound = Bound::Unbounded;
 }
 
+/// The sharing settings of a certificate, which allow anyone
+/// to verify it without being authenticated.
+///
+/// They are not part of the certified certificate data.
+#[derive(Debug, Default, CandidType, Deserialize, Clone, PartialEq, Eq)]
+pub struct CertificateSharing {
+    /// Anyone knowing the certificate id can verify the certificate.
+    pub is_public: bool,
+    /// Only the hash of the share token is stored.
+    pub share_token_hash: Option<Hash>,
+}
+
+impl CertificateSharing {
+    pub fn hash_share_token(share_token: &str) -> Hash {
+        hash_bytes(share_token.as_bytes())
+    }
+
+    /// Whether the certificate can be verified by anyone, given the (optional) share token.
+    pub fn allows(&self, share_token: Option<&str>) -> bool {
+        if self.is_public {
+            return true;
+        }
+
+        match (&self.share_token_hash, share_token) {
+            (Some(hash), Some(share_token)) => *hash == Self::hash_share_token(share_token),
+            _ => false,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        !self.is_public && self.share_token_hash.is_none()
+    }
+}
+
+impl Storable for CertificateSharing {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct CertificateUserPrincipalKey(Blob<{ Self::MAX_SIZE as usize }>);
 
@@ -100,14 +196,28 @@ impl Storable for CertificateUserPrincipalKey {
 }
 
 pub struct CertificateUserPrincipalRange {
-    start_bound: CertificateUserPrincipalKey,
+    start_bound: std::ops::Bound<CertificateUserPrincipalKey>,
     end_bound: CertificateUserPrincipalKey,
 }
 
 impl CertificateUserPrincipalRange {
     pub fn new(user_principal: Principal) -> Result<Self, String> {
         Ok(Self {
-            start_bound: CertificateUserPrincipalKey::new(user_principal, CertificateId::min())?,
+            start_bound: std::ops::Bound::Included(CertificateUserPrincipalKey::new(
+                user_principal,
+                CertificateId::min(),
+            )?),
+            end_bound: CertificateUserPrincipalKey::new(user_principal, CertificateId::max())?,
+        })
+    }
+
+    /// The certificates of the user principal with an id greater than `certificate_id`.
+    pub fn after(user_principal: Principal, certificate_id: CertificateId) -> Result<Self, String> {
+        Ok(Self {
+            start_bound: std::ops::Bound::Excluded(CertificateUserPrincipalKey::new(
+                user_principal,
+                certificate_id,
+            )?),
             end_bound: CertificateUserPrincipalKey::new(user_principal, CertificateId::max())?,
         })
     }
@@ -115,7 +225,7 @@ impl CertificateUserPrincipalRange {
 
 impl RangeBounds<CertificateUserPrincipalKey> for CertificateUserPrincipalRange {
     fn start_bound(&self) -> std::ops::Bound<&CertificateUserPrincipalKey> {
-        std::ops::Bound::Included(&self.start_bound)
+        self.start_bound.as_ref()
     }
 
     fn end_bound(&self) -> std::ops::Bound<&CertificateUserPrincipalKey> {
@@ -159,14 +269,28 @@ impl Storable for CertificateManagedUserIdKey {
 }
 
 pub struct CertificateManagedUserIdRange {
-    start_bound: CertificateManagedUserIdKey,
+    start_bound: std::ops::Bound<CertificateManagedUserIdKey>,
     end_bound: CertificateManagedUserIdKey,
 }
 
 impl CertificateManagedUserIdRange {
     pub fn new(managed_user_id: Uuid) -> Result<Self, String> {
         Ok(Self {
-            start_bound: CertificateManagedUserIdKey::new(managed_user_id, CertificateId::min())?,
+            start_bound: std::ops::Bound::Included(CertificateManagedUserIdKey::new(
+                managed_user_id,
+                CertificateId::min(),
+            )?),
+            end_bound: CertificateManagedUserIdKey::new(managed_user_id, CertificateId::max())?,
+        })
+    }
+
+    /// The certificates of the managed user with an id greater than `certificate_id`.
+    pub fn after(managed_user_id: Uuid, certificate_id: CertificateId) -> Result<Self, String> {
+        Ok(Self {
+            start_bound: std::ops::Bound::Excluded(CertificateManagedUserIdKey::new(
+                managed_user_id,
+                certificate_id,
+            )?),
             end_bound: CertificateManagedUserIdKey::new(managed_user_id, CertificateId::max())?,
         })
     }
@@ -174,7 +298,7 @@ impl CertificateManagedUserIdRange {
 
 impl RangeBounds<CertificateManagedUserIdKey> for CertificateManagedUserIdRange {
     fn start_bound(&self) -> std::ops::Bound<&CertificateManagedUserIdKey> {
-        std::ops::Bound::Included(&self.start_bound)
+        self.start_bound.as_ref()
     }
 
     fn end_bound(&self) -> std::ops::Bound<&CertificateManagedUserIdKey> {
@@ -182,6 +306,567 @@ impl RangeBounds<CertificateManagedUserIdKey> for CertificateManagedUserIdRange
     }
 }
 
+/// Key of the index of the certificates by the files they reference, in any of their versions.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct CertificateFileHashKey(Blob<{ Self::MAX_SIZE as usize }>);
+
+impl CertificateFileHashKey {
+    const MAX_SIZE: u32 = <(FileHash, CertificateId)>::BOUND.max_size();
+
+    pub fn new(file_hash: FileHash, certificate_id: CertificateId) -> Result<Self, String> {
+        Ok(Self(
+            Blob::try_from((file_hash, certificate_id).to_bytes().as_ref()).map_err(|_| {
+                format!(
+                    "Failed to convert file hash {:?} and certificate id {:?} to bytes.",
+                    file_hash, certificate_id
+                )
+            })?,
+        ))
+    }
+}
+
+impl Storable for CertificateFileHashKey {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        self.0.to_bytes()
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Self(Blob::from_bytes(bytes))
+    }
+
+    const BOUND: Bound = Bound::Bounded {
+        max_size: Self::MAX_SIZE,
+        is_fixed_size: true,
+    };
+}
+
+pub struct CertificateFileHashRange {
+    start_bound: CertificateFileHashKey,
+    end_bound: CertificateFileHashKey,
+}
+
+impl CertificateFileHashRange {
+    pub fn new(file_hash: FileHash) -> Result<Self, String> {
+        Ok(Self {
+            start_bound: CertificateFileHashKey::new(file_hash, CertificateId::min())?,
+            end_bound: CertificateFileHashKey::new(file_hash, CertificateId::max())?,
+        })
+    }
+}
+
+impl RangeBounds<CertificateFileHashKey> for CertificateFileHashRange {
+    fn start_bound(&self) -> std::ops::Bound<&CertificateFileHashKey> {
+        std::ops::Bound::Included(&self.start_bound)
+    }
+
+    fn end_bound(&self) -> std::ops::Bound<&CertificateFileHashKey> {
+        std::ops::Bound::Included(&self.end_bound)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CertificateSortField {
+    CreatedAt,
+    IssuedAt,
+}
+
+impl CertificateSortField {
+    pub const ALL: [Self; 2] = [Self::CreatedAt, Self::IssuedAt];
+
+    /// Returns the timestamp (in microseconds) by which the certificate is sorted.
+    pub fn timestamp(&self, certificate: &Certificate) -> u64 {
+        match self {
+            Self::CreatedAt => certificate.created_at.timestamp_micros(),
+            Self::IssuedAt => certificate.content.issued_at.timestamp_micros(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortOrder {
+    Ascending,
+    Descending,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CertificateSort {
+    pub field: CertificateSortField,
+    pub order: SortOrder,
+}
+
+impl Default for CertificateSort {
+    /// Newest certificates first.
+    fn default() -> Self {
+        Self {
+            field: CertificateSortField::CreatedAt,
+            order: SortOrder::Descending,
+        }
+    }
+}
+
+/// Points to the first certificate of a page in a sort index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CertificateCursor {
+    pub timestamp: u64,
+    pub id: CertificateId,
+}
+
+const CERTIFICATE_CURSOR_SEPARATOR: char = '_';
+
+impl TryFrom<&str> for CertificateCursor {
+    type Error = String;
+
+    fn try_from(cursor: &str) -> Result<Self, Self::Error> {
+        let (timestamp, id) = cursor
+            .split_once(CERTIFICATE_CURSOR_SEPARATOR)
+            .ok_or_else(|| format!("Invalid cursor: {}", cursor))?;
+
+        Ok(Self {
+            timestamp: timestamp
+                .parse()
+                .map_err(|_| format!("Invalid cursor: {}", cursor))?,
+            id: CertificateId::try_from(id)?,
+        })
+    }
+}
+
+impl ToString for CertificateCursor {
+    fn to_string(&self) -> String {
+        format!(
+            "{}{}{}",
+            self.timestamp,
+            CERTIFICATE_CURSOR_SEPARATOR,
+            self.id.to_string()
+        )
+    }
+}
+
+/// Inclusive bounds of a range in a sort index, as `(timestamp, certificate id)` pairs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CertificateSortBounds {
+    pub start: (u64, CertificateId),
+    pub end: (u64, CertificateId),
+}
+
+impl CertificateSortBounds {
+    /// Covers the certificates sorted by a timestamp in the `timestamps` range,
+    /// starting from the cursor (included) in the given sort order.
+    pub fn new(
+        timestamps: RangeInclusive<u64>,
+        cursor: Option<&CertificateCursor>,
+        order: SortOrder,
+    ) -> Self {
+        let mut start = (*timestamps.start(), CertificateId::min());
+        let mut end = (*timestamps.end(), CertificateId::max());
+
+        if let Some(cursor) = cursor {
+            let cursor = (cursor.timestamp, cursor.id);
+            match order {
+                SortOrder::Ascending => start = start.max(cursor),
+                SortOrder::Descending => end = end.min(cursor),
+            }
+        }
+
+        Self { start, end }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start > self.end
+    }
+}
+
+/// The owner under which a certificate is listed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CertificateOwner {
+    UserPrincipal(Principal),
+    ManagedUserId(Uuid),
+    /// Certificates are also listed under the principal that issued them.
+    IssuerPrincipal(Principal),
+}
+
+impl CertificateOwner {
+    const KEY_SIZE: usize = 1 + 1 + Principal::MAX_LENGTH_IN_BYTES;
+
+    /// Fixed size bytes representation of the owner, used as a prefix in index keys:
+    /// `<owner type><owner bytes length><owner bytes padded with zeros>`.
+    fn to_key_bytes(self) -> [u8; Self::KEY_SIZE] {
+        let (owner_type, owner_bytes): (u8, &[u8]) = match &self {
+            Self::UserPrincipal(user_principal) => (0, user_principal.as_slice()),
+            Self::ManagedUserId(managed_user_id) => (1, managed_user_id.as_ref()),
+            Self::IssuerPrincipal(issuer_principal) => (2, issuer_principal.as_slice()),
+        };
+
+        let mut bytes = [0; Self::KEY_SIZE];
+        bytes[0] = owner_type;
+        bytes[1] = owner_bytes.len() as u8;
+        bytes[2..2 + owner_bytes.len()].copy_from_slice(owner_bytes);
+        bytes
+    }
+}
+
+/// A certificate attribute that can be used to filter certificates by exact value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CertificateFacet {
+    SportCategory,
+    IssuerClubName,
+}
+
+impl CertificateFacet {
+    pub const ALL: [Self; 2] = [Self::SportCategory, Self::IssuerClubName];
+
+    pub fn value<'a>(&self, certificate: &'a Certificate) -> Option<&'a str> {
+        match self {
+            Self::SportCategory => Some(certificate.content.sport_category.as_str()),
+            Self::IssuerClubName => certificate.content.issuer_club_name.as_deref(),
+        }
+    }
+
+    /// Facet values are compared case insensitively, ignoring leading and trailing whitespaces.
+    pub fn value_hash(value: &str) -> Hash {
+        hash_bytes(value.trim().to_lowercase())
+    }
+
+    fn key_byte(&self) -> u8 {
+        match self {
+            Self::SportCategory => 0,
+            Self::IssuerClubName => 1,
+        }
+    }
+}
+
+impl CertificateSortField {
+    fn key_byte(&self) -> u8 {
+        match self {
+            Self::CreatedAt => 0,
+            Self::IssuedAt => 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CertificateFilters {
+    pub sport_category: Option<String>,
+    /// Timestamp in microseconds (included).
+    pub issued_at_from: Option<u64>,
+    /// Timestamp in microseconds (included).
+    pub issued_at_to: Option<u64>,
+    pub issuer_club_name: Option<String>,
+}
+
+impl CertificateFilters {
+    /// The facet filter used to select the facet index, if any.
+    pub fn facet_filter(&self) -> Option<(CertificateFacet, &str)> {
+        if let Some(sport_category) = &self.sport_category {
+            return Some((CertificateFacet::SportCategory, sport_category));
+        }
+
+        self.issuer_club_name
+            .as_deref()
+            .map(|issuer_club_name| (CertificateFacet::IssuerClubName, issuer_club_name))
+    }
+
+    pub fn issued_at_range(&self) -> RangeInclusive<u64> {
+        self.issued_at_from.unwrap_or(u64::MIN)..=self.issued_at_to.unwrap_or(u64::MAX)
+    }
+
+    pub fn matches(&self, certificate: &Certificate) -> bool {
+        let facet_matches = |facet: CertificateFacet, filter: &Option<String>| match filter {
+            Some(value) => facet.value(certificate).is_some_and(|v| {
+                CertificateFacet::value_hash(v) == CertificateFacet::value_hash(value)
+            }),
+            None => true,
+        };
+
+        facet_matches(CertificateFacet::SportCategory, &self.sport_category)
+            && facet_matches(CertificateFacet::IssuerClubName, &self.issuer_club_name)
+            && self
+                .issued_at_range()
+                .contains(&certificate.content.issued_at.timestamp_micros())
+    }
+}
+
+/// Key of the index of the certificates by owner and facet value, sorted by timestamp:
+/// `<owner><facet><facet value hash><sort field><timestamp><certificate id>`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct CertificateFacetKey(Blob<{ Self::MAX_SIZE as usize }>);
+
+impl CertificateFacetKey {
+    const MAX_SIZE: u32 =
+        (CertificateOwner::KEY_SIZE + 1 + 32 + 1 + 8) as u32 + CertificateId::BOUND.max_size();
+
+    pub fn new(
+        owner: CertificateOwner,
+        facet: CertificateFacet,
+        facet_value_hash: Hash,
+        sort_field: CertificateSortField,
+        timestamp: u64,
+        certificate_id: CertificateId,
+    ) -> Result<Self, String> {
+        let mut bytes = Vec::with_capacity(Self::MAX_SIZE as usize);
+        bytes.extend_from_slice(&owner.to_key_bytes());
+        bytes.push(facet.key_byte());
+        bytes.extend_from_slice(&facet_value_hash);
+        bytes.push(sort_field.key_byte());
+        bytes.extend_from_slice(&timestamp.to_be_bytes());
+        bytes.extend_from_slice(&certificate_id.to_bytes());
+
+        Ok(Self(Blob::try_from(bytes.as_slice()).map_err(|_| {
+            format!(
+                "Failed to convert owner {:?}, facet {:?}, sort field {:?}, timestamp {:?} and certificate id {:?} to bytes.",
+                owner, facet, sort_field, timestamp, certificate_id
+            )
+        })?))
+    }
+}
+
+impl Storable for CertificateFacetKey {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        self.0.to_bytes()
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Self(Blob::from_bytes(bytes))
+    }
+
+    const BOUND: Bound = Bound::Bounded {
+        max_size: Self::MAX_SIZE,
+        is_fixed_size: true,
+    };
+}
+
+pub struct CertificateFacetRange {
+    start_bound: CertificateFacetKey,
+    end_bound: CertificateFacetKey,
+}
+
+impl CertificateFacetRange {
+    pub fn new(
+        owner: CertificateOwner,
+        facet: CertificateFacet,
+        facet_value_hash: Hash,
+        sort_field: CertificateSortField,
+        bounds: &CertificateSortBounds,
+    ) -> Result<Self, String> {
+        let key = |(timestamp, id)| {
+            CertificateFacetKey::new(owner, facet, facet_value_hash, sort_field, timestamp, id)
+        };
+
+        Ok(Self {
+            start_bound: key(bounds.start)?,
+            end_bound: key(bounds.end)?,
+        })
+    }
+}
+
+impl RangeBounds<CertificateFacetKey> for CertificateFacetRange {
+    fn start_bound(&self) -> std::ops::Bound<&CertificateFacetKey> {
+        std::ops::Bound::Included(&self.start_bound)
+    }
+
+    fn end_bound(&self) -> std::ops::Bound<&CertificateFacetKey> {
+        std::ops::Bound::Included(&self.end_bound)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct CertificateUserPrincipalSortKey(Blob<{ Self::MAX_SIZE as usize }>);
+
+impl CertificateUserPrincipalSortKey {
+    const MAX_SIZE: u32 = <(Principal, (u64, CertificateId))>::BOUND.max_size();
+
+    pub fn new(
+        user_principal: Principal,
+        timestamp: u64,
+        certificate_id: CertificateId,
+    ) -> Result<Self, String> {
+        Ok(Self(
+            Blob::try_from((user_principal, (timestamp, certificate_id)).to_bytes().as_ref())
+                .map_err(|_| {
+                    format!(
+                        "Failed to convert user principal {:?}, timestamp {:?} and certificate id {:?} to bytes.",
+                        user_principal, timestamp, certificate_id
+                    )
+                })?,
+        ))
+    }
+}
+
+impl Storable for CertificateUserPrincipalSortKey {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        self.0.to_bytes()
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Self(Blob::from_bytes(bytes))
+    }
+
+    const BOUND: Bound = Bound::Bounded {
+        max_size: Self::MAX_SIZE,
+        is_fixed_size: true,
+    };
+}
+
+pub struct CertificateUserPrincipalSortRange {
+    start_bound: CertificateUserPrincipalSortKey,
+    end_bound: CertificateUserPrincipalSortKey,
+}
+
+impl CertificateUserPrincipalSortRange {
+    pub fn new(user_principal: Principal, bounds: &CertificateSortBounds) -> Result<Self, String> {
+        let (start_timestamp, start_id) = bounds.start;
+        let (end_timestamp, end_id) = bounds.end;
+
+        Ok(Self {
+            start_bound: CertificateUserPrincipalSortKey::new(
+                user_principal,
+                start_timestamp,
+                start_id,
+            )?,
+            end_bound: CertificateUserPrincipalSortKey::new(user_principal, end_timestamp, end_id)?,
+        })
+    }
+}
+
+impl RangeBounds<CertificateUserPrincipalSortKey> for CertificateUserPrincipalSortRange {
+    fn start_bound(&self) -> std::ops::Bound<&CertificateUserPrincipalSortKey> {
+        std::ops::Bound::Included(&self.start_bound)
+    }
+
+    fn end_bound(&self) -> std::ops::Bound<&CertificateUserPrincipalSortKey> {
+        std::ops::Bound::Included(&self.end_bound)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct CertificateManagedUserIdSortKey(Blob<{ Self::MAX_SIZE as usize }>);
+
+impl CertificateManagedUserIdSortKey {
+    const MAX_SIZE: u32 = <(Uuid, (u64, CertificateId))>::BOUND.max_size();
+
+    pub fn new(
+        managed_user_id: Uuid,
+        timestamp: u64,
+        certificate_id: CertificateId,
+    ) -> Result<Self, String> {
+        Ok(Self(
+            Blob::try_from((managed_user_id, (timestamp, certificate_id)).to_bytes().as_ref())
+                .map_err(|_| {
+                    format!(
+                        "Failed to convert managed user id {:?}, timestamp {:?} and certificate id {:?} to bytes.",
+                        managed_user_id, timestamp, certificate_id
+                    )
+                })?,
+        ))
+    }
+}
+
+impl Storable for CertificateManagedUserIdSortKey {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        self.0.to_bytes()
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Self(Blob::from_bytes(bytes))
+    }
+
+    const BOUND: Bound = Bound::Bounded {
+        max_size: Self::MAX_SIZE,
+        is_fixed_size: true,
+    };
+}
+
+pub struct CertificateManagedUserIdSortRange {
+    start_bound: CertificateManagedUserIdSortKey,
+    end_bound: CertificateManagedUserIdSortKey,
+}
+
+impl CertificateManagedUserIdSortRange {
+    pub fn new(managed_user_id: Uuid, bounds: &CertificateSortBounds) -> Result<Self, String> {
+        let (start_timestamp, start_id) = bounds.start;
+        let (end_timestamp, end_id) = bounds.end;
+
+        Ok(Self {
+            start_bound: CertificateManagedUserIdSortKey::new(
+                managed_user_id,
+                start_timestamp,
+                start_id,
+            )?,
+            end_bound: CertificateManagedUserIdSortKey::new(
+                managed_user_id,
+                end_timestamp,
+                end_id,
+            )?,
+        })
+    }
+}
+
+impl RangeBounds<CertificateManagedUserIdSortKey> for CertificateManagedUserIdSortRange {
+    fn start_bound(&self) -> std::ops::Bound<&CertificateManagedUserIdSortKey> {
+        std::ops::Bound::Included(&self.start_bound)
+    }
+
+    fn end_bound(&self) -> std::ops::Bound<&CertificateManagedUserIdSortKey> {
+        std::ops::Bound::Included(&self.end_bound)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct CertificateVersionKey(Blob<{ Self::MAX_SIZE as usize }>);
+
+impl CertificateVersionKey {
+    const MAX_SIZE: u32 = <(CertificateId, CertificateVersion)>::BOUND.max_size();
+
+    pub fn new(certificate_id: CertificateId, version: CertificateVersion) -> Result<Self, String> {
+        Ok(Self(
+            Blob::try_from((certificate_id, version).to_bytes().as_ref()).map_err(|_| {
+                format!(
+                    "Failed to convert certificate id {:?} and version {:?} to bytes.",
+                    certificate_id, version
+                )
+            })?,
+        ))
+    }
+}
+
+impl Storable for CertificateVersionKey {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        self.0.to_bytes()
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Self(Blob::from_bytes(bytes))
+    }
+
+    const BOUND: Bound = Bound::Bounded {
+        max_size: Self::MAX_SIZE,
+        is_fixed_size: true,
+    };
+}
+
+pub struct CertificateVersionRange {
+    start_bound: CertificateVersionKey,
+    end_bound: CertificateVersionKey,
+}
+
+impl CertificateVersionRange {
+    pub fn new(certificate_id: CertificateId) -> Result<Self, String> {
+        Ok(Self {
+            start_bound: CertificateVersionKey::new(certificate_id, CertificateVersion::MIN)?,
+            end_bound: CertificateVersionKey::new(certificate_id, CertificateVersion::MAX)?,
+        })
+    }
+}
+
+impl RangeBounds<CertificateVersionKey> for CertificateVersionRange {
+    fn start_bound(&self) -> std::ops::Bound<&CertificateVersionKey> {
+        std::ops::Bound::Included(&self.start_bound)
+    }
+
+    fn end_bound(&self) -> std::ops::Bound<&CertificateVersionKey> {
+        std::ops::Bound::Included(&self.end_bound)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use crate::system_api::get_date_time;
@@ -196,11 +881,318 @@ mod test {
         let deserialized_certificate = Certificate::from_bytes(serialized_certificate);
 
         assert_eq!(certificate, deserialized_certificate);
+
+        let revoked_certificate = revoked_certificate();
+        let serialized_revoked_certificate = revoked_certificate.to_bytes();
+        let deserialized_revoked_certificate =
+            Certificate::from_bytes(serialized_revoked_certificate);
+
+        assert_eq!(revoked_certificate, deserialized_revoked_certificate);
+    }
+
+    #[rstest]
+    fn certificate_version_key_ordering() {
+        let first_id = CertificateId::try_from("36a1174f-b789-46e4-a5d6-ef8d38cd52b9").unwrap();
+        let second_id = CertificateId::try_from("e645cfd2-b365-4bda-bb64-535ffa050328").unwrap();
+
+        let key = |id, version| CertificateVersionKey::new(id, version).unwrap();
+
+        assert!(key(first_id, 1) < key(first_id, 2));
+        assert!(key(first_id, 2) < key(first_id, 256));
+        assert!(key(first_id, CertificateVersion::MAX) < key(second_id, 1));
+
+        let range = CertificateVersionRange::new(first_id).unwrap();
+        assert!(range.contains(&key(first_id, 1)));
+        assert!(range.contains(&key(first_id, CertificateVersion::MAX)));
+        assert!(!range.contains(&key(second_id, 1)));
+    }
+
+    #[rstest]
+    fn certificate_user_principal_sort_key_ordering() {
+        let user_principal = certificate().user_principal;
+        let first_id = CertificateId::try_from("e645cfd2-b365-4bda-bb64-535ffa050328").unwrap();
+        let second_id = CertificateId::try_from("36a1174f-b789-46e4-a5d6-ef8d38cd52b9").unwrap();
+
+        let key = |timestamp, id| {
+            CertificateUserPrincipalSortKey::new(user_principal, timestamp, id).unwrap()
+        };
+
+        // sorted by timestamp first, then by id
+        assert!(key(1, first_id) < key(2, second_id));
+        assert!(key(255, first_id) < key(256, second_id));
+        assert!(key(2, second_id) < key(2, first_id));
+
+        let cursor = CertificateCursor {
+            timestamp: 2,
+            id: second_id,
+        };
+        let range = |bounds: CertificateSortBounds| {
+            CertificateUserPrincipalSortRange::new(user_principal, &bounds).unwrap()
+        };
+
+        let ascending = range(CertificateSortBounds::new(
+            u64::MIN..=u64::MAX,
+            Some(&cursor),
+            SortOrder::Ascending,
+        ));
+        assert!(!ascending.contains(&key(1, first_id)));
+        assert!(ascending.contains(&key(2, second_id)));
+        assert!(ascending.contains(&key(u64::MAX, first_id)));
+
+        let descending = range(CertificateSortBounds::new(
+            u64::MIN..=u64::MAX,
+            Some(&cursor),
+            SortOrder::Descending,
+        ));
+        assert!(descending.contains(&key(1, first_id)));
+        assert!(descending.contains(&key(2, second_id)));
+        assert!(!descending.contains(&key(2, first_id)));
+
+        let bounded = range(CertificateSortBounds::new(
+            2..=3,
+            None,
+            SortOrder::Ascending,
+        ));
+        assert!(!bounded.contains(&key(1, first_id)));
+        assert!(bounded.contains(&key(2, second_id)));
+        assert!(bounded.contains(&key(3, first_id)));
+        assert!(!bounded.contains(&key(4, second_id)));
+    }
+
+    #[rstest]
+    fn certificate_sort_bounds() {
+        let id = CertificateId::try_from("36a1174f-b789-46e4-a5d6-ef8d38cd52b9").unwrap();
+        let cursor = CertificateCursor { timestamp: 5, id };
+
+        let bounds = CertificateSortBounds::new(2..=10, Some(&cursor), SortOrder::Ascending);
+        assert_eq!(bounds.start, (5, id));
+        assert_eq!(bounds.end, (10, CertificateId::max()));
+
+        let bounds = CertificateSortBounds::new(2..=10, Some(&cursor), SortOrder::Descending);
+        assert_eq!(bounds.start, (2, CertificateId::min()));
+        assert_eq!(bounds.end, (5, id));
+
+        // the cursor is outside of the timestamps range
+        let bounds = CertificateSortBounds::new(6..=10, Some(&cursor), SortOrder::Descending);
+        assert!(bounds.is_empty());
+    }
+
+    #[rstest]
+    fn certificate_facet_key_ordering() {
+        let certificate = certificate();
+        let owner = CertificateOwner::UserPrincipal(certificate.user_principal);
+        let other_owner = CertificateOwner::ManagedUserId(
+            Uuid::try_from("ccb31f93-1a16-4089-bc84-1822ae591da2").unwrap(),
+        );
+        let id = CertificateId::try_from("36a1174f-b789-46e4-a5d6-ef8d38cd52b9").unwrap();
+        let swimming = CertificateFacet::value_hash(" Swimming");
+        let key = |owner, value_hash, timestamp| {
+            CertificateFacetKey::new(
+                owner,
+                CertificateFacet::SportCategory,
+                value_hash,
+                CertificateSortField::IssuedAt,
+                timestamp,
+                id,
+            )
+            .unwrap()
+        };
+
+        assert_eq!(swimming, CertificateFacet::value_hash("swimming "));
+        assert!(key(owner, swimming, 1) < key(owner, swimming, 256));
+
+        let range = CertificateFacetRange::new(
+            owner,
+            CertificateFacet::SportCategory,
+            swimming,
+            CertificateSortField::IssuedAt,
+            &CertificateSortBounds::new(u64::MIN..=u64::MAX, None, SortOrder::Ascending),
+        )
+        .unwrap();
+        assert!(range.contains(&key(owner, swimming, 1)));
+        assert!(!range.contains(&key(other_owner, swimming, 1)));
+        // the same principal as issuer is a different owner
+        assert!(!range.contains(&key(
+            CertificateOwner::IssuerPrincipal(certificate.user_principal),
+            swimming,
+            1
+        )));
+        assert!(!range.contains(&key(owner, CertificateFacet::value_hash("Running"), 1)));
+    }
+
+    #[rstest]
+    fn certificate_filters_matches() {
+        let certificate = certificate();
+        let issued_at = certificate.content.issued_at.timestamp_micros();
+
+        assert!(CertificateFilters::default().matches(&certificate));
+        assert!(CertificateFilters {
+            sport_category: Some(" SPORT_category".to_string()),
+            issued_at_from: Some(issued_at),
+            issued_at_to: Some(issued_at),
+            issuer_club_name: None,
+        }
+        .matches(&certificate));
+        assert!(!CertificateFilters {
+            sport_category: Some("other".to_string()),
+            ..Default::default()
+        }
+        .matches(&certificate));
+        assert!(!CertificateFilters {
+            issued_at_from: Some(issued_at + 1),
+            ..Default::default()
+        }
+        .matches(&certificate));
+        // the certificate has no issuer club name
+        assert!(!CertificateFilters {
+            issuer_club_name: Some("club".to_string()),
+            ..Default::default()
+        }
+        .matches(&certificate));
+    }
+
+    #[rstest]
+    fn certificate_cursor_string() {
+        let cursor = CertificateCursor {
+            timestamp: 1704063600000000,
+            id: CertificateId::try_from("36a1174f-b789-46e4-a5d6-ef8d38cd52b9").unwrap(),
+        };
+        let cursor_string = cursor.to_string();
+
+        assert_eq!(
+            cursor_string,
+            "1704063600000000_36a1174f-b789-46e4-a5d6-ef8d38cd52b9"
+        );
+        assert_eq!(
+            CertificateCursor::try_from(cursor_string.as_str()).unwrap(),
+            cursor
+        );
+        assert!(CertificateCursor::try_from("invalid").is_err());
+        assert!(CertificateCursor::try_from("abc_36a1174f-b789-46e4-a5d6-ef8d38cd52b9").is_err());
+    }
+
+    #[rstest]
+    fn certificate_cbor_without_revocation() {
+        let certificate = certificate();
+        let revoked_certificate = revoked_certificate();
+
+        let cbor: serde_cbor::Value =
+            serde_cbor::from_slice(&certificate.certificate_cbor()).unwrap();
+        let revoked_cbor: serde_cbor::Value =
+            serde_cbor::from_slice(&revoked_certificate.certificate_cbor()).unwrap();
+
+        let has_revocation = |value: &serde_cbor::Value| match value {
+            serde_cbor::Value::Map(map) => {
+                map.contains_key(&serde_cbor::Value::Text("revocation".to_string()))
+            }
+            _ => panic!("expected serde_cbor::Value::Map"),
+        };
+        assert!(!has_revocation(&cbor));
+        assert!(has_revocation(&revoked_cbor));
+    }
+
+    #[rstest]
+    fn certificate_cbor_with_issuer() {
+        let certificate = Certificate {
+            issuer: Some(CertificateIssuer {
+                id: Uuid::try_from("ccb31f93-1a16-4089-bc84-1822ae591da2").unwrap(),
+                name: "FC Example".to_string(),
+                verified: true,
+            }),
+            ..certificate()
+        };
+
+        let cbor: serde_cbor::Value =
+            serde_cbor::from_slice(&certificate.certificate_cbor()).unwrap();
+        let issuer = match cbor {
+            serde_cbor::Value::Map(mut map) => map
+                .remove(&serde_cbor::Value::Text("issuer".to_string()))
+                .unwrap(),
+            _ => panic!("expected serde_cbor::Value::Map"),
+        };
+        let issuer_field = |field: &str| match &issuer {
+            serde_cbor::Value::Map(map) => map
+                .get(&serde_cbor::Value::Text(field.to_string()))
+                .unwrap()
+                .clone(),
+            _ => panic!("expected serde_cbor::Value::Map"),
+        };
+        assert_eq!(
+            issuer_field("id"),
+            serde_cbor::Value::Text("ccb31f93-1a16-4089-bc84-1822ae591da2".to_string())
+        );
+        assert_eq!(
+            issuer_field("name"),
+            serde_cbor::Value::Text("FC Example".to_string())
+        );
+        assert_eq!(issuer_field("verified"), serde_cbor::Value::Bool(true));
+    }
+
+    #[rstest]
+    fn certificate_cbor_with_achievement() {
+        let achievement_id = Uuid::try_from("0b4a4f6e-8f5e-4a8e-9a3c-5f7d2f3a1c2b").unwrap();
+        let certificate_with_achievement = Certificate {
+            achievement_id: Some(achievement_id),
+            ..certificate()
+        };
+
+        let achievement_id_value = |certificate: &Certificate| match serde_cbor::from_slice(
+            &certificate.certificate_cbor(),
+        )
+        .unwrap()
+        {
+            serde_cbor::Value::Map(mut map) => {
+                map.remove(&serde_cbor::Value::Text("achievement_id".to_string()))
+            }
+            _ => panic!("expected serde_cbor::Value::Map"),
+        };
+        assert_eq!(achievement_id_value(&certificate()), None);
+        assert_eq!(
+            achievement_id_value(&certificate_with_achievement),
+            Some(serde_cbor::Value::Text(achievement_id.to_string()))
+        );
+    }
+
+    #[rstest]
+    fn certificate_sharing_storable_impl() {
+        let sharing = CertificateSharing {
+            is_public: true,
+            share_token_hash: Some(CertificateSharing::hash_share_token("share_token")),
+        };
+        let serialized_sharing = sharing.to_bytes();
+        let deserialized_sharing = CertificateSharing::from_bytes(serialized_sharing);
+
+        assert_eq!(sharing, deserialized_sharing);
+    }
+
+    #[rstest]
+    fn certificate_sharing_allows() {
+        let private = CertificateSharing::default();
+        assert!(private.is_empty());
+        assert!(!private.allows(None));
+        assert!(!private.allows(Some("share_token")));
+
+        let public = CertificateSharing {
+            is_public: true,
+            share_token_hash: None,
+        };
+        assert!(public.allows(None));
+        assert!(public.allows(Some("another_share_token")));
+
+        let shared = CertificateSharing {
+            is_public: false,
+            share_token_hash: Some(CertificateSharing::hash_share_token("share_token")),
+        };
+        assert!(!shared.is_empty());
+        assert!(!shared.allows(None));
+        assert!(shared.allows(Some("share_token")));
+        assert!(!shared.allows(Some("another_share_token")));
     }
 
     fn certificate() -> Certificate {
         let date_time = DateTime::new(get_date_time().unwrap()).unwrap();
         Certificate {
+            achievement_id: None,
             user_principal: Principal::from_text(
                 "63ubj-icu27-xedai-mj7py-uj2uw-pygtr-ckarq-owt2g-fhbcc-c4urf-tqe",
             )
@@ -211,12 +1203,28 @@ mod test {
                 issued_at: date_time,
                 sport_category: "sport_category".to_string(),
                 notes: None,
+                file_hash: None,
                 file_uri: Some("data:image/png;base64,iVBORw0KGgoAAAANSUhEUgAAAAUA".to_string()),
                 external_id: None,
                 issuer_full_name: None,
                 issuer_club_name: None,
             },
+            issuer: None,
+            issuer_principal: None,
             managed_user_id: None,
+            revocation: None,
+            updated_at: None,
+        }
+    }
+
+    fn revoked_certificate() -> Certificate {
+        let date_time = DateTime::new(get_date_time().unwrap()).unwrap();
+        Certificate {
+            revocation: Some(CertificateRevocation {
+                reason: "reason".to_string(),
+                revoked_at: date_time,
+            }),
+            ..certificate()
         }
     }
 }
@@ -0,0 +1,189 @@
+use std::borrow::Cow;
+
+use candid::{CandidType, Decode, Deserialize, Encode};
+use ic_certification::Hash;
+use ic_stable_structures::{storable::Bound, Storable};
+use ssp_backend_types::Timestamp;
+
+use super::{CertificateId, CertificateVersion, Icrc3Value, UserPrincipal};
+
+/// The position of a block in the ICRC-3 log, starting from 0.
+pub type BlockIndex = u64;
+
+pub const CERTIFICATE_ISSUE_BLOCK_TYPE: &str = "ssp_certificate_issue";
+pub const CERTIFICATE_UPDATE_BLOCK_TYPE: &str = "ssp_certificate_update";
+pub const CERTIFICATE_REVOKE_BLOCK_TYPE: &str = "ssp_certificate_revoke";
+pub const CERTIFICATE_TRANSFER_BLOCK_TYPE: &str = "ssp_certificate_transfer";
+
+#[derive(Debug, CandidType, Deserialize, Clone, PartialEq, Eq)]
+pub enum CertificateBlockOperation {
+    Issue,
+    Update,
+    Revoke { reason: String },
+    Transfer { from: UserPrincipal },
+}
+
+/// A certificate event, recorded as an ICRC-3 block.
+#[derive(Debug, CandidType, Deserialize, Clone, PartialEq, Eq)]
+pub struct CertificateBlock {
+    pub timestamp: Timestamp,
+    /// The hash of the previous block, if any.
+    pub parent_hash: Option<Hash>,
+    pub operation: CertificateBlockOperation,
+    pub certificate_id: CertificateId,
+    pub certificate_version: CertificateVersion,
+    /// The hash of the certificate version, as certified in the `ssp_certificates` tree.
+    pub certificate_hash: Hash,
+    /// The owner of the certificate after the operation.
+    pub to: UserPrincipal,
+}
+
+impl CertificateBlock {
+    /// Block schema:
+    /// ```text
+    /// btype: text
+    /// ts: nat
+    /// phash: blob (omitted for the first block)
+    /// tx:
+    ///   certificate_id: text
+    ///   version: nat
+    ///   certificate_hash: blob
+    ///   to: account
+    ///   from: account (transfer only)
+    ///   reason: text (revoke only)
+    /// ```
+    pub fn to_value(&self) -> Icrc3Value {
+        let mut tx = vec![
+            (
+                "certificate_id".to_string(),
+                Icrc3Value::Text(self.certificate_id.to_string()),
+            ),
+            (
+                "version".to_string(),
+                Icrc3Value::Nat(self.certificate_version.into()),
+            ),
+            (
+                "certificate_hash".to_string(),
+                Icrc3Value::Blob(self.certificate_hash.to_vec()),
+            ),
+            ("to".to_string(), account_value(&self.to)),
+        ];
+        let btype = match &self.operation {
+            CertificateBlockOperation::Issue => CERTIFICATE_ISSUE_BLOCK_TYPE,
+            CertificateBlockOperation::Update => CERTIFICATE_UPDATE_BLOCK_TYPE,
+            CertificateBlockOperation::Revoke { reason } => {
+                tx.push(("reason".to_string(), Icrc3Value::Text(reason.clone())));
+                CERTIFICATE_REVOKE_BLOCK_TYPE
+            }
+            CertificateBlockOperation::Transfer { from } => {
+                tx.push(("from".to_string(), account_value(from)));
+                CERTIFICATE_TRANSFER_BLOCK_TYPE
+            }
+        };
+
+        let mut block = vec![
+            ("btype".to_string(), Icrc3Value::Text(btype.to_string())),
+            ("ts".to_string(), Icrc3Value::Nat(self.timestamp)),
+            ("tx".to_string(), Icrc3Value::Map(tx)),
+        ];
+        if let Some(parent_hash) = self.parent_hash {
+            block.push(("phash".to_string(), Icrc3Value::Blob(parent_hash.to_vec())));
+        }
+
+        Icrc3Value::Map(block)
+    }
+
+    pub fn hash(&self) -> Hash {
+        self.to_value().hash()
+    }
+}
+
+/// An ICRC account without subaccount.
+fn account_value(owner: &UserPrincipal) -> Icrc3Value {
+    Icrc3Value::Array(vec![Icrc3Value::Blob(owner.as_slice().to_vec())])
+}
+
+impl Storable for CertificateBlock {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use candid::Principal;
+    use rstest::*;
+
+    #[rstest]
+    fn storable_impl() {
+        let block = certificate_block();
+        let serialized_block = block.to_bytes();
+        let deserialized_block = CertificateBlock::from_bytes(serialized_block);
+
+        assert_eq!(block, deserialized_block);
+    }
+
+    #[rstest]
+    fn to_value() {
+        let block = CertificateBlock {
+            parent_hash: Some([2; 32]),
+            operation: CertificateBlockOperation::Transfer { from: owner() },
+            ..certificate_block()
+        };
+
+        let value = block.to_value();
+
+        let Icrc3Value::Map(entries) = value else {
+            panic!("expected Icrc3Value::Map");
+        };
+        assert!(entries.contains(&(
+            "btype".to_string(),
+            Icrc3Value::Text(CERTIFICATE_TRANSFER_BLOCK_TYPE.to_string())
+        )));
+        assert!(entries.contains(&("phash".to_string(), Icrc3Value::Blob(vec![2; 32]))));
+        let tx = entries
+            .iter()
+            .find_map(|(key, value)| match (key.as_str(), value) {
+                ("tx", Icrc3Value::Map(tx)) => Some(tx),
+                _ => None,
+            })
+            .unwrap();
+        assert!(tx.contains(&("from".to_string(), account_value(&owner()))));
+        assert!(tx.contains(&("version".to_string(), Icrc3Value::Nat(1))));
+    }
+
+    #[rstest]
+    fn hash_commits_to_parent_hash() {
+        let block = certificate_block();
+        let child_block = CertificateBlock {
+            parent_hash: Some(block.hash()),
+            ..block.clone()
+        };
+
+        assert_ne!(block.hash(), child_block.hash());
+    }
+
+    fn owner() -> Principal {
+        Principal::from_slice(&[1, 2, 3])
+    }
+
+    fn certificate_block() -> CertificateBlock {
+        CertificateBlock {
+            timestamp: 1704063600000000000,
+            parent_hash: None,
+            operation: CertificateBlockOperation::Issue,
+            certificate_id: CertificateId::try_from("ccb31f93-1a16-4089-bc84-1822ae591da2")
+                .unwrap(),
+            certificate_version: 1,
+            certificate_hash: [1; 32],
+            to: owner(),
+        }
+    }
+}
@@ -3,10 +3,39 @@ use std::borrow::Cow;
 use candid::{CandidType, Decode, Deserialize, Encode, Principal};
 use ic_stable_structures::{storable::Bound, Storable};
 
+use super::OidcIssuer;
+
 #[derive(Debug, Default, CandidType, Deserialize, Clone, PartialEq, Eq)]
 pub struct Config {
     /// The off-chain backend principal.
     pub backend_principal: Option<Principal>,
+    /// Not set until configured, also for the configs stored before it existed.
+    pub delegation: Option<DelegationConfig>,
+    /// The issuer of the JWTs accepted before multiple OIDC providers were supported.
+    /// Its users keep the principals derived from their `sub` only, so it cannot change once set.
+    pub legacy_oidc_issuer: Option<OidcIssuer>,
+}
+
+#[derive(Debug, Default, CandidType, Deserialize, Clone, PartialEq, Eq)]
+pub struct DelegationConfig {
+    pub max_session_duration_ns: Option<u64>,
+    /// The maximum session duration of each Hasura role.
+    pub role_session_durations: Vec<(String, u64)>,
+    pub targets: Option<Vec<Principal>>,
+}
+
+impl DelegationConfig {
+    /// Returns the maximum session duration of the given role,
+    /// falling back to the default one.
+    pub fn max_session_duration_ns(&self, role: Option<&str>) -> Option<u64> {
+        role.and_then(|role| {
+            self.role_session_durations
+                .iter()
+                .find(|(r, _)| r == role)
+                .map(|(_, duration)| *duration)
+        })
+        .or(self.max_session_duration_ns)
+    }
 }
 
 impl Storable for Config {
@@ -30,6 +59,12 @@ mod tests {
         let config = Config {
             // a random principal
             backend_principal: Some(backend_principal()),
+            delegation: Some(DelegationConfig {
+                max_session_duration_ns: Some(3_600_000_000_000),
+                role_session_durations: vec![("admin".to_string(), 600_000_000_000)],
+                targets: Some(vec![backend_principal()]),
+            }),
+            legacy_oidc_issuer: Some("https://example.auth0.com/".to_string()),
         };
         let serialized_config = config.to_bytes();
         let deserialized_config = Config::from_bytes(serialized_config);
@@ -37,6 +72,23 @@ mod tests {
         assert_eq!(config, deserialized_config);
     }
 
+    #[test]
+    fn max_session_duration() {
+        let config = DelegationConfig {
+            max_session_duration_ns: Some(3_600),
+            role_session_durations: vec![("admin".to_string(), 600)],
+            targets: None,
+        };
+
+        assert_eq!(config.max_session_duration_ns(None), Some(3_600));
+        assert_eq!(config.max_session_duration_ns(Some("user")), Some(3_600));
+        assert_eq!(config.max_session_duration_ns(Some("admin")), Some(600));
+        assert_eq!(
+            DelegationConfig::default().max_session_duration_ns(Some("user")),
+            None
+        );
+    }
+
     fn backend_principal() -> Principal {
         Principal::from_text("63ubj-icu27-xedai-mj7py-uj2uw-pygtr-ckarq-owt2g-fhbcc-c4urf-tqe")
             .unwrap()
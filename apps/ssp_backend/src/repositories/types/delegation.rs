@@ -0,0 +1,124 @@
+use std::borrow::Cow;
+
+use candid::{CandidType, Decode, Deserialize, Encode};
+use ic_certification::Hash;
+use ic_stable_structures::{
+    storable::{Blob, Bound},
+    Storable,
+};
+use ssp_backend_types::Timestamp;
+
+/// The key of a delegation signature, made of the delegation expiration,
+/// the seed and the hash of the signed delegation message.
+///
+/// Keys are sorted by expiration first, so that expired signatures can be pruned in order.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct DelegationSignatureKey(Blob<{ Self::MAX_SIZE as usize }>);
+
+impl DelegationSignatureKey {
+    const MAX_SIZE: u32 = <(Timestamp, (Hash, Hash))>::BOUND.max_size();
+
+    pub fn new(expiration: Timestamp, seed: Hash, message_hash: Hash) -> Result<Self, String> {
+        Ok(Self(
+            Blob::try_from((expiration, (seed, message_hash)).to_bytes().as_ref()).map_err(
+                |_| {
+                    format!(
+                        "Failed to convert expiration {:?}, seed {:?} and message hash {:?} to bytes.",
+                        expiration, seed, message_hash
+                    )
+                },
+            )?,
+        ))
+    }
+
+    pub fn expiration(&self) -> Timestamp {
+        self.decode().0
+    }
+
+    pub fn seed(&self) -> Hash {
+        self.decode().1 .0
+    }
+
+    pub fn message_hash(&self) -> Hash {
+        self.decode().1 .1
+    }
+
+    fn decode(&self) -> (Timestamp, (Hash, Hash)) {
+        <(Timestamp, (Hash, Hash))>::from_bytes(self.0.to_bytes())
+    }
+}
+
+impl Storable for DelegationSignatureKey {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        self.0.to_bytes()
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Self(Blob::from_bytes(bytes))
+    }
+
+    const BOUND: Bound = Bound::Bounded {
+        max_size: Self::MAX_SIZE,
+        is_fixed_size: true,
+    };
+}
+
+/// A stored delegation signature, indexed by seed and message hash.
+#[derive(Debug, CandidType, Deserialize, Clone, PartialEq, Eq)]
+pub struct DelegationSignature {
+    pub expiration: Timestamp,
+    /// The hash of the session key of the delegation, to revoke the session.
+    /// Not set for the signatures stored before the session keys were recorded.
+    pub session_key_hash: Option<Hash>,
+}
+
+impl Storable for DelegationSignature {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn storable_impl() {
+        let key = DelegationSignatureKey::new(1706899350000000000, [1; 32], [2; 32]).unwrap();
+        let serialized_key = key.to_bytes();
+        let deserialized_key = DelegationSignatureKey::from_bytes(serialized_key);
+
+        assert_eq!(key, deserialized_key);
+        assert_eq!(deserialized_key.expiration(), 1706899350000000000);
+        assert_eq!(deserialized_key.seed(), [1; 32]);
+        assert_eq!(deserialized_key.message_hash(), [2; 32]);
+    }
+
+    #[test]
+    fn delegation_signature_storable_impl() {
+        let signature = DelegationSignature {
+            expiration: 1706899350000000000,
+            session_key_hash: Some([3; 32]),
+        };
+        let serialized_signature = signature.to_bytes();
+        let deserialized_signature = DelegationSignature::from_bytes(serialized_signature);
+
+        assert_eq!(signature, deserialized_signature);
+    }
+
+    #[test]
+    fn sorted_by_expiration() {
+        let first = DelegationSignatureKey::new(1, [2; 32], [2; 32]).unwrap();
+        let second = DelegationSignatureKey::new(256, [1; 32], [1; 32]).unwrap();
+        let third = DelegationSignatureKey::new(u64::MAX, [0; 32], [0; 32]).unwrap();
+
+        assert!(first < second);
+        assert!(second < third);
+    }
+}
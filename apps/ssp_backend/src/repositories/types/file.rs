@@ -0,0 +1,242 @@
+use std::{borrow::Cow, collections::BTreeMap};
+
+use candid::{
+    types::{Serializer, Type, TypeInner},
+    CandidType, Decode, Deserialize, Encode, Principal,
+};
+use canister_sig_util::hash_bytes;
+use ic_certification::Hash;
+use ic_stable_structures::{
+    storable::{Blob, Bound},
+    Storable,
+};
+use serde::Serialize;
+
+use super::{DateTime, Uuid};
+
+pub type UploadId = Uuid;
+pub type FileChunkIndex = u32;
+
+const FILE_HASH_SIZE: usize = 32;
+
+/// The SHA-256 hash of the file content, which identifies the file in the file store.
+///
+/// Serialized as a lowercase hex string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct FileHash(Hash);
+
+impl FileHash {
+    pub fn from_content(content: &[u8]) -> Self {
+        Self(hash_bytes(content))
+    }
+}
+
+impl TryFrom<&str> for FileHash {
+    type Error = String;
+
+    fn try_from(file_hash: &str) -> Result<Self, Self::Error> {
+        let bytes = hex::decode(file_hash)
+            .ok()
+            .and_then(|bytes| Hash::try_from(bytes).ok())
+            .ok_or_else(|| format!("Failed to parse file hash from string: {}", file_hash))?;
+
+        Ok(Self(bytes))
+    }
+}
+
+impl ToString for FileHash {
+    fn to_string(&self) -> String {
+        hex::encode(self.0)
+    }
+}
+
+impl CandidType for FileHash {
+    fn _ty() -> Type {
+        TypeInner::Text.into()
+    }
+
+    fn idl_serialize<S>(&self, serializer: S) -> Result<(), S::Error>
+    where
+        S: Serializer,
+    {
+        self.to_string().idl_serialize(serializer)
+    }
+}
+
+impl Serialize for FileHash {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.to_string().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for FileHash {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        String::deserialize(deserializer).and_then(|file_hash| {
+            FileHash::try_from(file_hash.as_str())
+                .map_err(|_| serde::de::Error::custom("Invalid file hash."))
+        })
+    }
+}
+
+impl Storable for FileHash {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Borrowed(&self.0)
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Self(bytes.into_owned().try_into().unwrap())
+    }
+
+    const BOUND: Bound = Bound::Bounded {
+        max_size: FILE_HASH_SIZE as u32,
+        is_fixed_size: true,
+    };
+}
+
+/// The metadata of a file in the file store. The content is stored in chunks.
+#[derive(Debug, CandidType, Deserialize, Clone, PartialEq, Eq)]
+pub struct File {
+    pub content_type: String,
+    pub size: u64,
+    pub chunks_count: u32,
+    pub created_at: DateTime,
+}
+
+impl Storable for File {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct FileChunkKey(Blob<{ Self::MAX_SIZE as usize }>);
+
+impl FileChunkKey {
+    const MAX_SIZE: u32 = <(FileHash, FileChunkIndex)>::BOUND.max_size();
+
+    pub fn new(file_hash: FileHash, index: FileChunkIndex) -> Result<Self, String> {
+        Ok(Self(
+            Blob::try_from((file_hash, index).to_bytes().as_ref()).map_err(|_| {
+                format!(
+                    "Failed to convert file hash {:?} and chunk index {:?} to bytes.",
+                    file_hash, index
+                )
+            })?,
+        ))
+    }
+}
+
+impl Storable for FileChunkKey {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        self.0.to_bytes()
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Self(Blob::from_bytes(bytes))
+    }
+
+    const BOUND: Bound = Bound::Bounded {
+        max_size: Self::MAX_SIZE,
+        is_fixed_size: true,
+    };
+}
+
+/// A file being uploaded, kept in the heap until it is committed to the file store.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Upload {
+    pub owner: Principal,
+    pub content_type: String,
+    pub size: u64,
+    pub chunks: BTreeMap<FileChunkIndex, Vec<u8>>,
+    pub created_at: DateTime,
+}
+
+impl Upload {
+    pub fn uploaded_size(&self) -> u64 {
+        self.chunks.values().map(|chunk| chunk.len() as u64).sum()
+    }
+
+    /// Returns the chunks in order, if all the chunks from `0` have been uploaded.
+    pub fn contiguous_chunks(&self) -> Option<Vec<&Vec<u8>>> {
+        self.chunks
+            .iter()
+            .enumerate()
+            .map(|(expected_index, (index, chunk))| {
+                (*index as usize == expected_index).then_some(chunk)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    #[rstest]
+    fn file_hash_string_conversion() {
+        let file_hash = FileHash::from_content(b"file content");
+        let file_hash_string = file_hash.to_string();
+
+        assert_eq!(file_hash_string.len(), FILE_HASH_SIZE * 2);
+        assert_eq!(
+            FileHash::try_from(file_hash_string.as_str()).unwrap(),
+            file_hash
+        );
+        assert!(FileHash::try_from("invalid").is_err());
+        assert!(FileHash::try_from("abcd").is_err());
+    }
+
+    #[rstest]
+    fn file_hash_storable_impl() {
+        let file_hash = FileHash::from_content(b"file content");
+
+        assert_eq!(FileHash::from_bytes(file_hash.to_bytes()), file_hash);
+    }
+
+    #[rstest]
+    fn file_chunk_key_ordering() {
+        let file_hash = FileHash::from_content(b"file content");
+
+        let key = |index| FileChunkKey::new(file_hash, index).unwrap();
+
+        assert!(key(0) < key(1));
+        assert!(key(1) < key(256));
+    }
+
+    #[rstest]
+    fn upload_contiguous_chunks() {
+        let mut upload = Upload {
+            owner: Principal::anonymous(),
+            content_type: "image/png".to_string(),
+            size: 6,
+            chunks: BTreeMap::new(),
+            created_at: DateTime::min(),
+        };
+        upload.chunks.insert(0, vec![1, 2]);
+        upload.chunks.insert(2, vec![5, 6]);
+
+        assert_eq!(upload.uploaded_size(), 4);
+        assert!(upload.contiguous_chunks().is_none());
+
+        upload.chunks.insert(1, vec![3, 4]);
+
+        assert_eq!(upload.uploaded_size(), 6);
+        assert_eq!(
+            upload.contiguous_chunks().unwrap(),
+            vec![&vec![1, 2], &vec![3, 4], &vec![5, 6]]
+        );
+    }
+}
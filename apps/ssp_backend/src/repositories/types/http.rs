@@ -0,0 +1,60 @@
+use ic_http_certification::{
+    DefaultCelBuilder, DefaultResponseCertification, DefaultResponseOnlyCelExpression,
+};
+
+/// The label of the tree of the certified HTTP responses,
+/// as defined by the [response verification v2](https://internetcomputer.org/docs/current/references/http-gateway-protocol-spec#response-verification) spec.
+pub const HTTP_EXPR_TREE_LABEL: &str = "http_expr";
+
+/// The url path, without the query string and the fragment.
+pub fn url_path(url: &str) -> &str {
+    url.split(['?', '#']).next().unwrap_or_default()
+}
+
+/// The segments of the url path, e.g. `/certificates/1234` -> `["certificates", "1234"]`.
+/// The query string and the fragment are ignored.
+pub fn url_path_segments(url: &str) -> Vec<String> {
+    let path = url_path(url);
+    let path = path.strip_prefix('/').unwrap_or(path);
+
+    path.split('/').map(|segment| segment.to_string()).collect()
+}
+
+/// Certifies the status code, all the headers (except the `IC-Certificate` header) and the body
+/// of the response, without certifying the request.
+pub fn response_only_cel_expression() -> DefaultResponseOnlyCelExpression<'static> {
+    DefaultCelBuilder::response_only_certification()
+        .with_response_certification(DefaultResponseCertification::response_header_exclusions(
+            vec![],
+        ))
+        .build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    fn segments(segments: &[&str]) -> Vec<String> {
+        segments.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[rstest]
+    #[case("/", &[""])]
+    #[case("/certificates", &["certificates"])]
+    #[case("/certificates/1234", &["certificates", "1234"])]
+    #[case("/certificates/1234.json?version=1", &["certificates", "1234.json"])]
+    #[case("/certificates/1234#details", &["certificates", "1234"])]
+    #[case("/certificates/", &["certificates", ""])]
+    fn url_path_segments_parsing(#[case] url: &str, #[case] expected: &[&str]) {
+        assert_eq!(url_path_segments(url), segments(expected));
+    }
+
+    #[rstest]
+    #[case("/certificates/1234", "/certificates/1234")]
+    #[case("/certificates/1234.json?version=1", "/certificates/1234.json")]
+    #[case("/certificates/1234#details", "/certificates/1234")]
+    fn url_path_parsing(#[case] url: &str, #[case] expected: &str) {
+        assert_eq!(url_path(url), expected);
+    }
+}
@@ -0,0 +1,106 @@
+use canister_sig_util::hash_bytes;
+use ic_certification::Hash;
+
+/// The generic value of an ICRC-3 block. Only the variants used by the canister are supported.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Icrc3Value {
+    Blob(Vec<u8>),
+    Text(String),
+    Nat(u64),
+    Array(Vec<Icrc3Value>),
+    Map(Vec<(String, Icrc3Value)>),
+}
+
+impl Icrc3Value {
+    /// The representation independent hash of the value, as defined by ICRC-3.
+    pub fn hash(&self) -> Hash {
+        match self {
+            Self::Blob(bytes) => hash_bytes(bytes),
+            Self::Text(text) => hash_bytes(text.as_bytes()),
+            Self::Nat(nat) => hash_bytes(leb128_encode(*nat)),
+            Self::Array(values) => hash_bytes(
+                values
+                    .iter()
+                    .flat_map(|value| value.hash())
+                    .collect::<Vec<u8>>(),
+            ),
+            Self::Map(entries) => {
+                let mut entry_hashes: Vec<Vec<u8>> = entries
+                    .iter()
+                    .map(|(key, value)| [hash_bytes(key.as_bytes()), value.hash()].concat())
+                    .collect();
+                entry_hashes.sort();
+
+                hash_bytes(entry_hashes.concat())
+            }
+        }
+    }
+}
+
+pub fn leb128_encode(mut value: u64) -> Vec<u8> {
+    let mut bytes = vec![];
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            bytes.push(byte);
+            return bytes;
+        }
+        bytes.push(byte | 0x80);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    // test vectors from the ICRC-3 standard
+    #[rstest]
+    #[case::nat(
+        Icrc3Value::Nat(42),
+        "684888c0ebb17f374298b65ee2807526c066094c701bcc7ebbe1c1095f494fc1"
+    )]
+    #[case::text(
+        Icrc3Value::Text("Hello, World!".to_string()),
+        "dffd6021bb2bd5b0af676290809ec3a53191dd81c7f70a4b28688a362182986f"
+    )]
+    #[case::blob(
+        Icrc3Value::Blob(vec![1, 2, 3, 4]),
+        "9f64a747e1b97f131fabb6b447296c9b6f0201e79fb3c5356e6c77e89b6a806a"
+    )]
+    #[case::array(
+        Icrc3Value::Array(vec![
+            Icrc3Value::Nat(3),
+            Icrc3Value::Text("foo".to_string()),
+            Icrc3Value::Blob(vec![5, 6]),
+        ]),
+        "514a04011caa503990d446b7dec5d79e19c221ae607fb08b2848c67734d468d6"
+    )]
+    fn hash(#[case] value: Icrc3Value, #[case] expected_hash: &str) {
+        assert_eq!(hex::encode(value.hash()), expected_hash);
+    }
+
+    #[rstest]
+    fn map_hash_is_independent_of_order() {
+        let entries = vec![
+            ("a".to_string(), Icrc3Value::Nat(1)),
+            ("b".to_string(), Icrc3Value::Text("b".to_string())),
+        ];
+        let reversed_entries = entries.iter().rev().cloned().collect();
+
+        assert_eq!(
+            Icrc3Value::Map(entries).hash(),
+            Icrc3Value::Map(reversed_entries).hash()
+        );
+    }
+
+    #[rstest]
+    #[case(0, vec![0x00])]
+    #[case(127, vec![0x7f])]
+    #[case(128, vec![0x80, 0x01])]
+    #[case(624485, vec![0xe5, 0x8e, 0x26])]
+    fn leb128(#[case] value: u64, #[case] expected_bytes: Vec<u8>) {
+        assert_eq!(leb128_encode(value), expected_bytes);
+    }
+}
@@ -1,23 +1,36 @@
 use base64::{engine::general_purpose, Engine};
-use jsonwebtoken_rustcrypto::{
-    crypto::verify, decode_header, errors::ErrorKind, Algorithm, DecodingKey, TokenData,
-};
-use serde::{Deserialize, Serialize};
-use ssp_backend_types::{Auth0JWKSet, HasuraJWTClaims};
+use jsonwebtoken_rustcrypto::{crypto::verify, Algorithm, DecodingKey};
+use p256::ecdsa::signature::Verifier;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{Map, Value};
+use ssp_backend_types::{Auth0JWK, Auth0JWKSet, HasuraJWTClaims};
 
 use crate::system_api::{unix_timestamp, NANOS_IN_SECONDS};
 
+use super::OidcConfig;
+
 /// The maximum age of an ID token (checked against the `iat` claim).
 /// This value is arbitrary and should be reasonably small.
-const MAX_IAT_AGE_SECONDS: u64 = 10 * 60; // 10 minutes
+pub const MAX_IAT_AGE_SECONDS: u64 = 10 * 60; // 10 minutes
+
+const RS256: &str = "RS256";
+const ES256: &str = "ES256";
+const EDDSA: &str = "EdDSA";
+
+pub type IdTokenResult<T> = std::result::Result<T, JwtError>;
 
-// ignore rust-analyzer errors on these environment variables
-// compilation succeeds if you've correctly set the .env file
-pub const AUTH0_ISSUER: &str = env!("ID_TOKEN_ISSUER_BASE_URL"); // expected to have a trailing slash
-const AUTH0_AUDIENCE: &str = env!("ID_TOKEN_AUDIENCE");
+#[derive(Debug, PartialEq, Clone)]
+pub struct IdToken {
+    pub header: JwtHeader,
+    pub claims: JWTClaims,
+}
 
-pub type IdToken = TokenData<JWTClaims>;
-pub type IdTokenResult<T> = std::result::Result<T, ErrorKind>;
+#[derive(Debug, PartialEq, Clone, Deserialize)]
+pub struct JwtHeader {
+    pub alg: String,
+    /// The id of the key in the JWKS of the provider.
+    pub kid: Option<String>,
+}
 
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct JWTClaims {
@@ -29,11 +42,9 @@ pub struct JWTClaims {
     pub exp: u64,
     pub sub: String,
     pub nonce: String,
-    #[serde(
-        rename = "https://hasura.io/jwt/claims",
-        skip_serializing_if = "Option::is_none"
-    )]
-    pub hasura_claims: Option<HasuraJWTClaims>,
+    /// The custom claims, whose namespace is set in the [OidcConfig].
+    #[serde(flatten)]
+    pub custom_claims: Map<String, Value>,
 }
 
 impl JWTClaims {
@@ -41,7 +52,7 @@ impl JWTClaims {
         self.exp * NANOS_IN_SECONDS
     }
 
-    pub fn validate(&self) -> Result<(), ValidationError> {
+    pub fn validate(&self, oidc_config: &OidcConfig) -> Result<(), ValidationError> {
         let time = unix_timestamp();
 
         if self.exp < time {
@@ -52,19 +63,27 @@ impl JWTClaims {
             return Err(ValidationError::IatTooOld);
         }
 
-        if self.iss != AUTH0_ISSUER {
+        if self.iss != oidc_config.issuer {
             return Err(ValidationError::IssuerMismatch);
         }
 
-        if self.aud != AUTH0_AUDIENCE {
+        if !oidc_config.audiences.contains(&self.aud) {
             return Err(ValidationError::AudienceMismatch);
         }
 
         Ok(())
     }
 
-    pub fn user_id(&self) -> Option<String> {
-        self.hasura_claims.clone().map(|c| c.x_hasura_user_id)
+    /// Returns the Hasura claims set under the `claims_namespace` claim, if valid.
+    pub fn hasura_claims(&self, claims_namespace: &str) -> Option<HasuraJWTClaims> {
+        self.custom_claims
+            .get(claims_namespace)
+            .and_then(|claims| serde_json::from_value(claims.clone()).ok())
+    }
+
+    pub fn user_id(&self, claims_namespace: &str) -> Option<String> {
+        self.hasura_claims(claims_namespace)
+            .map(|c| c.x_hasura_user_id)
     }
 }
 
@@ -76,6 +95,26 @@ pub enum ValidationError {
     AudienceMismatch,
 }
 
+/// The error returned by [decode_jwt], in its `Debug` form, to the caller.
+#[derive(Debug, PartialEq, Eq)]
+pub enum JwtError {
+    /// The token is not made of three parts.
+    InvalidToken,
+    InvalidHeader,
+    InvalidClaims,
+    /// The header doesn't set the `kid` of the signing key.
+    MissingKeyId,
+    /// The JWKS of the provider is not available yet.
+    NoWorkingKey,
+    /// The JWKS of the provider doesn't contain the key with the `kid` of the header.
+    KeyNotFound,
+    /// The algorithm of the header is not supported, or doesn't match the key.
+    InvalidAlgorithm,
+    /// The key in the JWKS is malformed.
+    InvalidKey,
+    InvalidSignature,
+}
+
 /// Takes the result of a rsplit and ensure we only get 2 parts
 /// Errors if we don't
 macro_rules! expect_two {
@@ -83,54 +122,150 @@ macro_rules! expect_two {
         let mut i = $iter;
         match (i.next(), i.next(), i.next()) {
             (Some(first), Some(second), None) => (first, second),
-            _ => return Err(ErrorKind::InvalidToken),
+            _ => return Err(JwtError::InvalidToken),
         }
     }};
 }
 
-pub fn decode_jwt(
-    token: &str,
-    expected_alg: Algorithm,
-    jwks: Option<&Auth0JWKSet>,
-) -> IdTokenResult<IdToken> {
+/// Verifies the signature of the token with the key of the JWKS identified by the `kid` header.
+/// The `RS256`, `ES256` and `EdDSA` (Ed25519) algorithms are supported.
+pub fn decode_jwt(token: &str, jwks: Option<&Auth0JWKSet>) -> IdTokenResult<IdToken> {
     let (signature, message) = expect_two!(token.rsplitn(2, '.'));
+    let (claims, header) = expect_two!(message.rsplitn(2, '.'));
+
+    let header: JwtHeader = decode_json_part(header).ok_or(JwtError::InvalidHeader)?;
+    let key_id = header.kid.as_ref().ok_or(JwtError::MissingKeyId)?;
+    let jwks = jwks.ok_or(JwtError::NoWorkingKey)?;
+    let jwk = jwks.find_key(key_id).ok_or(JwtError::KeyNotFound)?;
+
+    verify_signature(&header.alg, jwk, message, signature)?;
+
+    let claims: JWTClaims = decode_json_part(claims).ok_or(JwtError::InvalidClaims)?;
+
+    Ok(IdToken { header, claims })
+}
+
+/// Reads the header of the token **without verifying it**,
+/// e.g. to check whether the key that signed it is in the JWKS.
+pub fn decode_jwt_header(token: &str) -> IdTokenResult<JwtHeader> {
+    let (_, message) = expect_two!(token.rsplitn(2, '.'));
+    let (_, header) = expect_two!(message.rsplitn(2, '.'));
+
+    decode_json_part(header).ok_or(JwtError::InvalidHeader)
+}
+
+/// Reads the `iss` claim of the token **without verifying it**,
+/// to pick the provider whose JWKS the token is then verified with.
+pub fn decode_jwt_issuer(token: &str) -> IdTokenResult<String> {
+    #[derive(Deserialize)]
+    struct IssuerClaim {
+        iss: String,
+    }
+
+    let (_, message) = expect_two!(token.rsplitn(2, '.'));
     let (claims, _) = expect_two!(message.rsplitn(2, '.'));
 
-    let jwks = jwks.ok_or(ErrorKind::NoWorkingKey)?;
+    let claims: IssuerClaim = decode_json_part(claims).ok_or(JwtError::InvalidClaims)?;
+
+    Ok(claims.iss)
+}
 
-    let header = decode_header(token).map_err(|e| e.into_kind())?;
-    let key_id = header.jwk_set_headers.kid.as_ref().unwrap();
-    let jwk = jwks.find_key(key_id).unwrap();
-    let key = DecodingKey::from_rsa_components(&jwk.n, &jwk.e).map_err(|e| e.into_kind())?;
-    let header_alg = header
-        .general_headers
-        .alg
-        .ok_or(ErrorKind::InvalidAlgorithm)?;
+fn verify_signature(
+    alg: &str,
+    jwk: &Auth0JWK,
+    message: &str,
+    signature: &str,
+) -> IdTokenResult<()> {
+    // the algorithm comes from the untrusted header,
+    // so it must match the one the key is meant for
+    if !jwk.alg.is_empty() && jwk.alg != alg {
+        return Err(JwtError::InvalidAlgorithm);
+    }
 
-    if expected_alg != header_alg {
-        return Err(ErrorKind::InvalidAlgorithm);
+    match (alg, jwk.kty.as_str()) {
+        (RS256, "RSA") => verify_rs256(jwk, message, signature),
+        (ES256, "EC") => verify_es256(jwk, message, signature),
+        (EDDSA, "OKP") => verify_eddsa(jwk, message, signature),
+        _ => Err(JwtError::InvalidAlgorithm),
     }
+}
 
-    if !verify(signature, message, &key, header_alg).map_err(|e| e.into_kind())? {
-        return Err(ErrorKind::InvalidSignature);
+fn verify_rs256(jwk: &Auth0JWK, message: &str, signature: &str) -> IdTokenResult<()> {
+    let key = DecodingKey::from_rsa_components(&jwk.n, &jwk.e).map_err(|_| JwtError::InvalidKey)?;
+
+    match verify(signature, message, &key, Algorithm::RS256) {
+        Ok(true) => Ok(()),
+        _ => Err(JwtError::InvalidSignature),
     }
+}
 
-    let decoded_claims = String::from_utf8(base64_decode(claims)?).map_err(ErrorKind::Utf8)?;
-    let claims: JWTClaims = serde_json::from_str(&decoded_claims).map_err(ErrorKind::Json)?;
+fn verify_es256(jwk: &Auth0JWK, message: &str, signature: &str) -> IdTokenResult<()> {
+    if jwk.crv.as_deref() != Some("P-256") {
+        return Err(JwtError::InvalidKey);
+    }
+    let x = decode_key_component(jwk.x.as_deref())?;
+    let y = decode_key_component(jwk.y.as_deref())?;
+    let point = p256::EncodedPoint::from_affine_coordinates(
+        p256::FieldBytes::from_slice(&x),
+        p256::FieldBytes::from_slice(&y),
+        false,
+    );
+    let key =
+        p256::ecdsa::VerifyingKey::from_encoded_point(&point).map_err(|_| JwtError::InvalidKey)?;
+
+    let signature = base64_decode(signature)
+        .ok()
+        .and_then(|signature| p256::ecdsa::Signature::from_slice(&signature).ok())
+        .ok_or(JwtError::InvalidSignature)?;
+
+    key.verify(message.as_bytes(), &signature)
+        .map_err(|_| JwtError::InvalidSignature)
+}
 
-    Ok(IdToken { header, claims })
+fn verify_eddsa(jwk: &Auth0JWK, message: &str, signature: &str) -> IdTokenResult<()> {
+    if jwk.crv.as_deref() != Some("Ed25519") {
+        return Err(JwtError::InvalidKey);
+    }
+    let key = ed25519_compact::PublicKey::new(decode_key_component(jwk.x.as_deref())?);
+
+    let signature = base64_decode(signature)
+        .ok()
+        .and_then(|signature| ed25519_compact::Signature::from_slice(&signature).ok())
+        .ok_or(JwtError::InvalidSignature)?;
+
+    key.verify(message, &signature)
+        .map_err(|_| JwtError::InvalidSignature)
 }
 
-fn base64_decode(input: &str) -> IdTokenResult<Vec<u8>> {
+/// Decodes a 32 bytes component of an `EC` or `OKP` key.
+fn decode_key_component(component: Option<&str>) -> IdTokenResult<[u8; 32]> {
+    component
+        .and_then(|component| base64_decode(component).ok())
+        .and_then(|component| component.try_into().ok())
+        .ok_or(JwtError::InvalidKey)
+}
+
+fn decode_json_part<T: DeserializeOwned>(part: &str) -> Option<T> {
+    let decoded_part = base64_decode(part).ok()?;
+    serde_json::from_slice(&decoded_part).ok()
+}
+
+fn base64_decode(input: &str) -> Result<Vec<u8>, base64::DecodeError> {
     let engine = general_purpose::URL_SAFE_NO_PAD;
-    engine.decode(input).map_err(ErrorKind::Base64)
+    engine.decode(input)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use jwt_simple::prelude::{
+        Claims, Duration, ECDSAP256KeyPairLike, ECDSAP256PublicKeyLike, ES256KeyPair,
+        Ed25519KeyPair, EdDSAKeyPairLike, NoCustomClaims, RS256KeyPair, RSAKeyPairLike,
+    };
     use serde_json::json;
 
+    const CLAIMS_NAMESPACE: &str = "https://hasura.io/jwt/claims";
+
     #[test]
     fn test_jwt_claims_serialization_with_hasura_claims() {
         let claims = JWTClaims {
@@ -140,11 +275,14 @@ mod tests {
             exp: 1234567890,
             sub: "6eae002a-f832-414e-835c-18e82a2b11c3".to_string(),
             nonce: "nonce123".to_string(),
-            hasura_claims: Some(HasuraJWTClaims {
-                x_hasura_default_role: "user".to_string(),
-                x_hasura_allowed_roles: vec!["user".to_string()],
-                x_hasura_user_id: "6eae002a-f832-414e-835c-18e82a2b11c3".to_string(),
-            }),
+            custom_claims: Map::from_iter([(
+                CLAIMS_NAMESPACE.to_string(),
+                json!({
+                    "x-hasura-default-role": "user",
+                    "x-hasura-allowed-roles": ["user"],
+                    "x-hasura-user-id": "6eae002a-f832-414e-835c-18e82a2b11c3"
+                }),
+            )]),
         };
 
         let expected_json = json!({
@@ -166,6 +304,19 @@ mod tests {
 
         let deserialized: JWTClaims = serde_json::from_str(&serialized).unwrap();
         assert_eq!(claims, deserialized);
+        assert_eq!(
+            deserialized.hasura_claims(CLAIMS_NAMESPACE),
+            Some(HasuraJWTClaims {
+                x_hasura_default_role: "user".to_string(),
+                x_hasura_allowed_roles: vec!["user".to_string()],
+                x_hasura_user_id: "6eae002a-f832-414e-835c-18e82a2b11c3".to_string(),
+            })
+        );
+        assert_eq!(
+            deserialized.user_id(CLAIMS_NAMESPACE),
+            Some("6eae002a-f832-414e-835c-18e82a2b11c3".to_string())
+        );
+        assert_eq!(deserialized.user_id("https://other.namespace/claims"), None);
     }
 
     #[test]
@@ -177,7 +328,7 @@ mod tests {
             exp: 1234567890,
             sub: "6eae002a-f832-414e-835c-18e82a2b11c3".to_string(),
             nonce: "nonce123".to_string(),
-            hasura_claims: None,
+            custom_claims: Map::new(),
         };
 
         let expected_json = json!({
@@ -194,5 +345,204 @@ mod tests {
 
         let deserialized: JWTClaims = serde_json::from_str(&serialized).unwrap();
         assert_eq!(claims, deserialized);
+        assert_eq!(deserialized.user_id(CLAIMS_NAMESPACE), None);
+    }
+
+    #[test]
+    fn test_decode_jwt_issuer() {
+        let claims = general_purpose::URL_SAFE_NO_PAD
+            .encode(json!({ "iss": "https://example.com/", "sub": "sub" }).to_string());
+        let token = format!("header.{}.signature", claims);
+
+        assert_eq!(
+            decode_jwt_issuer(&token).unwrap(),
+            "https://example.com/".to_string()
+        );
+        assert_eq!(
+            decode_jwt_issuer("header.signature"),
+            Err(JwtError::InvalidToken)
+        );
+        assert_eq!(
+            decode_jwt_issuer("header.e30.signature"),
+            Err(JwtError::InvalidClaims)
+        );
+    }
+
+    #[test]
+    fn test_decode_jwt_rs256() {
+        let key_pair = RS256KeyPair::generate(2048).unwrap().with_key_id("rs256");
+        let jwks = Auth0JWKSet {
+            keys: vec![rs256_jwk(&key_pair)],
+        };
+        let token = key_pair.sign(claims()).unwrap();
+
+        let id_token = decode_jwt(&token, Some(&jwks)).unwrap();
+
+        assert_eq!(
+            id_token.header,
+            JwtHeader {
+                alg: "RS256".to_string(),
+                kid: Some("rs256".to_string()),
+            }
+        );
+        assert_eq!(id_token.claims.sub, "sub");
+    }
+
+    #[test]
+    fn test_decode_jwt_es256() {
+        let key_pair = ES256KeyPair::generate().with_key_id("es256");
+        let jwks = Auth0JWKSet {
+            keys: vec![es256_jwk(&key_pair)],
+        };
+        let token = key_pair.sign(claims()).unwrap();
+
+        let id_token = decode_jwt(&token, Some(&jwks)).unwrap();
+
+        assert_eq!(id_token.header.alg, "ES256");
+        assert_eq!(id_token.claims.sub, "sub");
+    }
+
+    #[test]
+    fn test_decode_jwt_eddsa() {
+        let key_pair = Ed25519KeyPair::generate().with_key_id("eddsa");
+        let jwks = Auth0JWKSet {
+            keys: vec![eddsa_jwk(&key_pair)],
+        };
+        let token = key_pair.sign(claims()).unwrap();
+
+        let id_token = decode_jwt(&token, Some(&jwks)).unwrap();
+
+        assert_eq!(id_token.header.alg, "EdDSA");
+        assert_eq!(id_token.claims.sub, "sub");
+    }
+
+    #[test]
+    fn test_decode_jwt_errors() {
+        let key_pair = RS256KeyPair::generate(2048).unwrap().with_key_id("rs256");
+        let jwks = Auth0JWKSet {
+            keys: vec![rs256_jwk(&key_pair)],
+        };
+        let token = key_pair.sign(claims()).unwrap();
+
+        assert_eq!(
+            decode_jwt("header.claims", Some(&jwks)),
+            Err(JwtError::InvalidToken)
+        );
+        assert_eq!(decode_jwt(&token, None), Err(JwtError::NoWorkingKey));
+
+        let no_kid_key_pair = RS256KeyPair::generate(2048).unwrap();
+        let no_kid_token = no_kid_key_pair.sign(claims()).unwrap();
+        assert_eq!(
+            decode_jwt(&no_kid_token, Some(&jwks)),
+            Err(JwtError::MissingKeyId)
+        );
+        assert_eq!(
+            decode_jwt_header(&no_kid_token).unwrap(),
+            JwtHeader {
+                alg: "RS256".to_string(),
+                kid: None,
+            }
+        );
+
+        let unknown_kid_token = no_kid_key_pair
+            .with_key_id("unknown")
+            .sign(claims())
+            .unwrap();
+        assert_eq!(
+            decode_jwt(&unknown_kid_token, Some(&jwks)),
+            Err(JwtError::KeyNotFound)
+        );
+
+        // same kid, different key
+        let other_key_pair = RS256KeyPair::generate(2048).unwrap().with_key_id("rs256");
+        let other_token = other_key_pair.sign(claims()).unwrap();
+        assert_eq!(
+            decode_jwt(&other_token, Some(&jwks)),
+            Err(JwtError::InvalidSignature)
+        );
+
+        // the header algorithm doesn't match the key
+        let es256_key_pair = ES256KeyPair::generate().with_key_id("rs256");
+        let es256_token = es256_key_pair.sign(claims()).unwrap();
+        assert_eq!(
+            decode_jwt(&es256_token, Some(&jwks)),
+            Err(JwtError::InvalidAlgorithm)
+        );
+        // the key type must match even if the key doesn't set its algorithm
+        let no_alg_jwks = Auth0JWKSet {
+            keys: vec![Auth0JWK {
+                alg: "".to_string(),
+                ..rs256_jwk(&key_pair)
+            }],
+        };
+        assert_eq!(
+            decode_jwt(&es256_token, Some(&no_alg_jwks)),
+            Err(JwtError::InvalidAlgorithm)
+        );
+    }
+
+    fn claims() -> jwt_simple::prelude::JWTClaims<NoCustomClaims> {
+        Claims::create(Duration::from_hours(1))
+            .with_issuer("https://example.com/")
+            .with_audience("audience")
+            .with_subject("sub")
+            .with_nonce("nonce")
+    }
+
+    fn rs256_jwk(key_pair: &RS256KeyPair) -> Auth0JWK {
+        let components = key_pair.public_key().to_components();
+        Auth0JWK {
+            kid: key_pair.key_id().clone().unwrap(),
+            kty: "RSA".to_string(),
+            alg: "RS256".to_string(),
+            n: base64_encode(&components.n),
+            e: base64_encode(&components.e),
+            ..empty_jwk()
+        }
+    }
+
+    fn es256_jwk(key_pair: &ES256KeyPair) -> Auth0JWK {
+        // 0x04 || x || y
+        let public_key = key_pair.public_key().public_key().to_bytes_uncompressed();
+        Auth0JWK {
+            kid: key_pair.key_id().clone().unwrap(),
+            kty: "EC".to_string(),
+            alg: "ES256".to_string(),
+            crv: Some("P-256".to_string()),
+            x: Some(base64_encode(&public_key[1..33])),
+            y: Some(base64_encode(&public_key[33..65])),
+            ..empty_jwk()
+        }
+    }
+
+    fn eddsa_jwk(key_pair: &Ed25519KeyPair) -> Auth0JWK {
+        Auth0JWK {
+            kid: key_pair.key_id().clone().unwrap(),
+            kty: "OKP".to_string(),
+            alg: "EdDSA".to_string(),
+            crv: Some("Ed25519".to_string()),
+            x: Some(base64_encode(&key_pair.public_key().to_bytes())),
+            ..empty_jwk()
+        }
+    }
+
+    fn empty_jwk() -> Auth0JWK {
+        Auth0JWK {
+            kty: "".to_string(),
+            r#use: "sig".to_string(),
+            n: "".to_string(),
+            e: "".to_string(),
+            kid: "".to_string(),
+            x5t: "".to_string(),
+            x5c: vec![],
+            alg: "".to_string(),
+            crv: None,
+            x: None,
+            y: None,
+        }
+    }
+
+    fn base64_encode(input: &[u8]) -> String {
+        general_purpose::URL_SAFE_NO_PAD.encode(input)
     }
 }
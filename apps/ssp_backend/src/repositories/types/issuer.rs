@@ -0,0 +1,99 @@
+use std::borrow::Cow;
+
+use candid::{CandidType, Decode, Deserialize, Encode};
+use ic_stable_structures::{storable::Bound, Storable};
+
+use super::{DateTime, UserPrincipal, Uuid};
+
+pub type IssuerId = Uuid;
+
+/// A club or organization that issues certificates through its admins and coaches.
+#[derive(Debug, CandidType, Deserialize, Clone, PartialEq, Eq)]
+pub struct Issuer {
+    pub name: String,
+    pub admins: Vec<UserPrincipal>,
+    pub coaches: Vec<UserPrincipal>,
+    /// Set by the controllers once the issuer has been verified.
+    pub verified_at: Option<DateTime>,
+    pub created_at: DateTime,
+}
+
+impl Issuer {
+    pub fn new(name: String, admin: UserPrincipal, created_at: DateTime) -> Self {
+        Self {
+            name,
+            admins: vec![admin],
+            coaches: vec![],
+            verified_at: None,
+            created_at,
+        }
+    }
+
+    pub fn is_verified(&self) -> bool {
+        self.verified_at.is_some()
+    }
+
+    pub fn is_admin(&self, principal: &UserPrincipal) -> bool {
+        self.admins.contains(principal)
+    }
+
+    /// Both admins and coaches can issue certificates on behalf of the issuer.
+    pub fn is_member(&self, principal: &UserPrincipal) -> bool {
+        self.is_admin(principal) || self.coaches.contains(principal)
+    }
+}
+
+impl Storable for Issuer {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use candid::Principal;
+    use rstest::*;
+
+    use crate::system_api::get_date_time;
+
+    #[rstest]
+    fn storable_impl() {
+        let issuer = issuer();
+        let serialized_issuer = issuer.to_bytes();
+        let deserialized_issuer = Issuer::from_bytes(serialized_issuer);
+
+        assert_eq!(issuer, deserialized_issuer);
+    }
+
+    #[rstest]
+    fn is_member() {
+        let admin = Principal::from_slice(&[1]);
+        let coach = Principal::from_slice(&[2]);
+        let issuer = Issuer {
+            coaches: vec![coach],
+            ..issuer()
+        };
+
+        assert!(issuer.is_admin(&admin));
+        assert!(issuer.is_member(&admin));
+        assert!(!issuer.is_admin(&coach));
+        assert!(issuer.is_member(&coach));
+        assert!(!issuer.is_member(&Principal::from_slice(&[3])));
+    }
+
+    fn issuer() -> Issuer {
+        let date_time = DateTime::new(get_date_time().unwrap()).unwrap();
+        Issuer::new(
+            "FC Example".to_string(),
+            Principal::from_slice(&[1]),
+            date_time,
+        )
+    }
+}
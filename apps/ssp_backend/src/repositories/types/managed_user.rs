@@ -0,0 +1,183 @@
+use std::{borrow::Cow, ops::RangeBounds};
+
+use candid::{CandidType, Decode, Deserialize, Encode, Principal};
+use ic_stable_structures::{
+    storable::{Blob, Bound},
+    Storable,
+};
+
+use super::{CertificateId, DateTime, UserDbId, UserPrincipal, Uuid};
+
+/// The managed user id is the database id that the user will have once they sign up.
+pub type ManagedUserId = UserDbId;
+
+/// A user (e.g. a minor) whose certificates are held by their guardians
+/// until they sign up and claim them.
+#[derive(Debug, CandidType, Deserialize, Clone, PartialEq, Eq)]
+pub struct ManagedUser {
+    pub guardians: Vec<UserPrincipal>,
+    pub created_at: DateTime,
+    /// The principal of the user who claimed the certificates, once they signed up.
+    /// The guardians are removed when the certificates are claimed.
+    pub claimed_by: Option<UserPrincipal>,
+    pub claimed_at: Option<DateTime>,
+}
+
+impl ManagedUser {
+    pub fn new(guardian: UserPrincipal, created_at: DateTime) -> Self {
+        Self {
+            guardians: vec![guardian],
+            created_at,
+            claimed_by: None,
+            claimed_at: None,
+        }
+    }
+
+    pub fn is_claimed(&self) -> bool {
+        self.claimed_by.is_some()
+    }
+
+    pub fn is_guardian(&self, principal: &UserPrincipal) -> bool {
+        self.guardians.contains(principal)
+    }
+}
+
+impl Storable for ManagedUser {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+/// A claim whose certificates are still being transferred to the user who claimed them,
+/// in batches, starting after the `after` certificate id if set.
+#[derive(Debug, CandidType, Deserialize, Clone, PartialEq, Eq)]
+pub struct ManagedUserClaim {
+    pub after: Option<CertificateId>,
+}
+
+impl Storable for ManagedUserClaim {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ManagedUserGuardianKey(Blob<{ Self::MAX_SIZE as usize }>);
+
+impl ManagedUserGuardianKey {
+    const MAX_SIZE: u32 = <(Principal, ManagedUserId)>::BOUND.max_size();
+
+    pub fn new(guardian: UserPrincipal, managed_user_id: ManagedUserId) -> Result<Self, String> {
+        Ok(Self(
+            Blob::try_from((guardian, managed_user_id).to_bytes().as_ref()).map_err(|_| {
+                format!(
+                    "Failed to convert guardian {:?} and managed user id {:?} to bytes.",
+                    guardian, managed_user_id
+                )
+            })?,
+        ))
+    }
+}
+
+impl Storable for ManagedUserGuardianKey {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        self.0.to_bytes()
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Self(Blob::from_bytes(bytes))
+    }
+
+    const BOUND: Bound = Bound::Bounded {
+        max_size: Self::MAX_SIZE,
+        is_fixed_size: true,
+    };
+}
+
+pub struct ManagedUserGuardianRange {
+    start_bound: ManagedUserGuardianKey,
+    end_bound: ManagedUserGuardianKey,
+}
+
+impl ManagedUserGuardianRange {
+    pub fn new(guardian: UserPrincipal) -> Result<Self, String> {
+        Ok(Self {
+            start_bound: ManagedUserGuardianKey::new(guardian, Uuid::min())?,
+            end_bound: ManagedUserGuardianKey::new(guardian, Uuid::max())?,
+        })
+    }
+}
+
+impl RangeBounds<ManagedUserGuardianKey> for ManagedUserGuardianRange {
+    fn start_bound(&self) -> std::ops::Bound<&ManagedUserGuardianKey> {
+        std::ops::Bound::Included(&self.start_bound)
+    }
+
+    fn end_bound(&self) -> std::ops::Bound<&ManagedUserGuardianKey> {
+        std::ops::Bound::Included(&self.end_bound)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    use crate::system_api::get_date_time;
+
+    #[rstest]
+    fn storable_impl() {
+        let managed_user = managed_user();
+        let serialized_managed_user = managed_user.to_bytes();
+        let deserialized_managed_user = ManagedUser::from_bytes(serialized_managed_user);
+
+        assert_eq!(managed_user, deserialized_managed_user);
+    }
+
+    #[rstest]
+    fn claim_storable_impl() {
+        let claim = ManagedUserClaim {
+            after: Some(Uuid::try_from("ccb31f93-1a16-4089-bc84-1822ae591da2").unwrap()),
+        };
+        let serialized_claim = claim.to_bytes();
+        let deserialized_claim = ManagedUserClaim::from_bytes(serialized_claim);
+
+        assert_eq!(claim, deserialized_claim);
+    }
+
+    #[rstest]
+    fn guardian_range() {
+        let guardian = guardian();
+        let other_guardian =
+            Principal::from_text("63ubj-icu27-xedai-mj7py-uj2uw-pygtr-ckarq-owt2g-fhbcc-c4urf-tqe")
+                .unwrap();
+        let managed_user_id = Uuid::try_from("ccb31f93-1a16-4089-bc84-1822ae591da2").unwrap();
+        let range = ManagedUserGuardianRange::new(guardian).unwrap();
+
+        assert!(range.contains(&ManagedUserGuardianKey::new(guardian, managed_user_id).unwrap()));
+        assert!(
+            !range.contains(&ManagedUserGuardianKey::new(other_guardian, managed_user_id).unwrap())
+        );
+    }
+
+    fn guardian() -> Principal {
+        Principal::from_slice(&[1, 2, 3])
+    }
+
+    fn managed_user() -> ManagedUser {
+        let date_time = DateTime::new(get_date_time().unwrap()).unwrap();
+        ManagedUser::new(guardian(), date_time)
+    }
+}
@@ -1,15 +1,37 @@
+mod achievement;
+mod audit_log;
 mod certificate;
+mod certificate_block;
 mod config;
 mod date_time;
+mod delegation;
+mod file;
+mod http;
+mod icrc3;
 mod id_token;
+mod issuer;
+mod managed_user;
+mod oidc_provider;
 mod salt;
+mod schema_version;
 mod user;
 mod uuid;
 
+pub use achievement::*;
+pub use audit_log::*;
 pub use certificate::*;
+pub use certificate_block::*;
 pub use config::*;
 pub use date_time::*;
+pub use delegation::*;
+pub use file::*;
+pub use http::*;
+pub use icrc3::*;
 pub use id_token::*;
+pub use issuer::*;
+pub use managed_user::*;
+pub use oidc_provider::*;
 pub use salt::*;
+pub use schema_version::*;
 pub use user::*;
 pub use uuid::*;
@@ -0,0 +1,91 @@
+use std::borrow::Cow;
+
+use candid::{CandidType, Decode, Deserialize, Encode};
+use ic_stable_structures::{storable::Bound, Storable};
+use ssp_backend_types::Auth0JWKSet;
+
+use super::DateTime;
+
+/// The `iss` claim of the JWTs issued by an OpenID Connect provider.
+pub type OidcIssuer = String;
+
+#[derive(Debug, CandidType, Deserialize, Clone, PartialEq, Eq)]
+pub struct OidcConfig {
+    /// The expected `iss` claim, with trailing slash.
+    pub issuer: OidcIssuer,
+    /// The accepted values of the `aud` claim.
+    pub audiences: Vec<String>,
+    pub jwks_url: String,
+    /// The claim under which the Hasura claims are set, if the provider sets them.
+    pub claims_namespace: Option<String>,
+}
+
+/// An OpenID Connect provider, together with its last known JWKS.
+#[derive(Debug, CandidType, Deserialize, Clone, PartialEq)]
+pub struct OidcProvider {
+    pub config: OidcConfig,
+    pub jwks: Option<Auth0JWKSet>,
+    /// When the JWKS was last fetched from the provider.
+    /// It's `None` if the JWKS has been set manually by a controller.
+    pub jwks_fetched_at: Option<DateTime>,
+}
+
+impl OidcProvider {
+    pub fn new(config: OidcConfig) -> Self {
+        Self {
+            config,
+            jwks: None,
+            jwks_fetched_at: None,
+        }
+    }
+}
+
+impl Storable for OidcProvider {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ssp_backend_types::Auth0JWK;
+
+    #[test]
+    fn storable_impl() {
+        let provider = OidcProvider {
+            config: OidcConfig {
+                issuer: "https://example.com/".to_string(),
+                audiences: vec!["audience".to_string()],
+                jwks_url: "https://example.com/.well-known/jwks.json".to_string(),
+                claims_namespace: Some("https://hasura.io/jwt/claims".to_string()),
+            },
+            jwks: Some(Auth0JWKSet {
+                keys: vec![Auth0JWK {
+                    kty: "RSA".to_string(),
+                    r#use: "sig".to_string(),
+                    n: "n".to_string(),
+                    e: "AQAB".to_string(),
+                    kid: "kid".to_string(),
+                    x5t: "x5t".to_string(),
+                    x5c: vec!["x5c".to_string()],
+                    alg: "RS256".to_string(),
+                    crv: None,
+                    x: None,
+                    y: None,
+                }],
+            }),
+            jwks_fetched_at: Some(DateTime::from_timestamp_micros(1706899350000000).unwrap()),
+        };
+        let serialized_provider = provider.to_bytes();
+        let deserialized_provider = OidcProvider::from_bytes(serialized_provider);
+
+        assert_eq!(provider, deserialized_provider);
+    }
+}
@@ -0,0 +1,5 @@
+/// The version of the data stored in stable memory, i.e. the number of migrations applied to it.
+pub type SchemaVersion = u32;
+
+/// The version of the data stored before the schema version was introduced.
+pub const INITIAL_SCHEMA_VERSION: SchemaVersion = 0;
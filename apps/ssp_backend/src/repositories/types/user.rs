@@ -5,7 +5,7 @@ use ic_stable_structures::{storable::Bound, Storable};
 
 use crate::system_api::get_date_time;
 
-use super::{DateTime, Uuid};
+use super::{DateTime, OidcIssuer, Uuid};
 
 pub type UserPrincipal = Principal;
 pub type UserSub = String;
@@ -14,20 +14,94 @@ pub type UserDbId = Uuid;
 #[derive(Debug, CandidType, Deserialize, Clone, PartialEq, Eq)]
 pub struct User {
     pub jwt_sub: UserSub,
+    /// The issuer of the JWT, not set for the users of the legacy OIDC issuer
+    /// (see [super::Config::legacy_oidc_issuer]).
+    pub jwt_iss: Option<OidcIssuer>,
     pub db_id: UserDbId,
     pub created_at: DateTime,
+    /// The roles from the Hasura claims, refreshed on each delegation.
+    /// Not set for the users created before roles were introduced.
+    pub roles: Option<Vec<UserRole>>,
 }
 
 impl User {
-    pub fn new(jwt_sub: UserSub, db_id: &str) -> Result<Self, String> {
+    pub fn new(
+        jwt_iss: Option<OidcIssuer>,
+        jwt_sub: UserSub,
+        db_id: &str,
+        roles: Vec<UserRole>,
+    ) -> Result<Self, String> {
         let datetime = get_date_time()?;
 
         Ok(Self {
             jwt_sub,
+            jwt_iss,
             db_id: UserDbId::try_from(db_id)?,
             created_at: DateTime::new(datetime)?,
+            roles: Some(roles),
         })
     }
+
+    pub fn sub_key(&self) -> UserSubKey {
+        UserSubKey {
+            issuer: self.jwt_iss.clone(),
+            sub: self.jwt_sub.clone(),
+        }
+    }
+
+    pub fn roles(&self) -> &[UserRole] {
+        self.roles.as_deref().unwrap_or_default()
+    }
+
+    pub fn has_any_role(&self, roles: &[UserRole]) -> bool {
+        self.roles().iter().any(|role| roles.contains(role))
+    }
+}
+
+/// Identifies a user by the `iss` and `sub` claims of their JWTs,
+/// the issuer not being set for the users of the legacy OIDC issuer.
+#[derive(Debug, CandidType, Deserialize, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct UserSubKey {
+    pub issuer: Option<OidcIssuer>,
+    pub sub: UserSub,
+}
+
+#[derive(Debug, CandidType, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum UserRole {
+    Athlete,
+    Coach,
+    ClubAdmin,
+    Admin,
+}
+
+impl UserRole {
+    /// Maps the Hasura roles to the user roles, ignoring the unknown ones (e.g. `user`).
+    pub fn from_hasura_roles(hasura_roles: &[String]) -> Vec<Self> {
+        let mut roles: Vec<Self> = vec![];
+        for role in hasura_roles
+            .iter()
+            .filter_map(|r| Self::try_from(r.as_str()).ok())
+        {
+            if !roles.contains(&role) {
+                roles.push(role);
+            }
+        }
+        roles
+    }
+}
+
+impl TryFrom<&str> for UserRole {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "athlete" => Ok(Self::Athlete),
+            "coach" => Ok(Self::Coach),
+            "club_admin" => Ok(Self::ClubAdmin),
+            "admin" => Ok(Self::Admin),
+            _ => Err(format!("Unknown user role: {}", value)),
+        }
+    }
 }
 
 impl Storable for User {
@@ -42,6 +116,18 @@ impl Storable for User {
     const BOUND: Bound = Bound::Unbounded;
 }
 
+impl Storable for UserSubKey {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -54,12 +140,51 @@ mod test {
         let deserialized_user = User::from_bytes(serialized_user);
 
         assert_eq!(user, deserialized_user);
+
+        let user_sub_key = user.sub_key();
+        let serialized_user_sub_key = user_sub_key.to_bytes();
+        let deserialized_user_sub_key = UserSubKey::from_bytes(serialized_user_sub_key);
+
+        assert_eq!(user_sub_key, deserialized_user_sub_key);
+    }
+
+    #[rstest]
+    fn from_hasura_roles() {
+        let hasura_roles = ["user", "coach", "admin", "coach", "club_admin", "athlete"]
+            .map(String::from)
+            .to_vec();
+
+        assert_eq!(
+            UserRole::from_hasura_roles(&hasura_roles),
+            vec![
+                UserRole::Coach,
+                UserRole::Admin,
+                UserRole::ClubAdmin,
+                UserRole::Athlete
+            ]
+        );
+        assert_eq!(UserRole::from_hasura_roles(&["user".to_string()]), vec![]);
+    }
+
+    #[rstest]
+    fn has_any_role() {
+        let user = user();
+
+        assert!(user.has_any_role(&[UserRole::Admin, UserRole::Coach]));
+        assert!(!user.has_any_role(&[UserRole::Admin]));
+        assert!(!User {
+            roles: None,
+            ..user
+        }
+        .has_any_role(&[UserRole::Coach]));
     }
 
     fn user() -> User {
         User::new(
+            Some("https://accounts.google.com".to_string()),
             "test_sub".to_string(),
             "8c8471a5-b91a-4b8b-9e24-219136ea2b76",
+            vec![UserRole::Coach],
         )
         .unwrap()
     }
@@ -38,6 +38,18 @@ impl Uuid {
     }
 }
 
+impl From<u128> for Uuid {
+    fn from(value: u128) -> Self {
+        Self(UuidImpl::from_u128(value))
+    }
+}
+
+impl From<Uuid> for u128 {
+    fn from(uuid: Uuid) -> Self {
+        uuid.0.as_u128()
+    }
+}
+
 impl TryFrom<&str> for Uuid {
     type Error = String;
 
@@ -139,6 +151,16 @@ mod tests {
         );
     }
 
+    #[rstest]
+    fn u128_conversion() {
+        let uuid = uuid();
+
+        let value = u128::from(uuid);
+
+        assert_eq!(value, 0x36a1174f_b789_46e4_a5d6_ef8d38cd52b9);
+        assert_eq!(Uuid::from(value), uuid);
+    }
+
     fn uuid() -> Uuid {
         Uuid::try_from("36a1174f-b789-46e4-a5d6-ef8d38cd52b9").unwrap()
     }
@@ -1,12 +1,17 @@
-use std::cell::RefCell;
+use std::{cell::RefCell, ops::Bound};
+
+use crate::utils::batch_cursor;
 
 use super::{
-    init_user_db_id_index, init_user_sub_index, init_users, User, UserDbId, UserDbIdIndexMemory,
-    UserMemory, UserPrincipal, UserSub, UserSubIndexMemory,
+    init_legacy_user_sub_index, init_user_db_id_index, init_user_sub_index, init_users,
+    LegacyUserSubIndexMemory, User, UserDbId, UserDbIdIndexMemory, UserMemory, UserPrincipal,
+    UserSub, UserSubIndexMemory, UserSubKey,
 };
 
 pub struct UserState {
     users: UserMemory,
+    /// Only read to migrate it to `user_sub_index`.
+    legacy_user_sub_index: LegacyUserSubIndexMemory,
     user_sub_index: UserSubIndexMemory,
     user_db_id_index: UserDbIdIndexMemory,
 }
@@ -15,6 +20,7 @@ impl Default for UserState {
     fn default() -> Self {
         Self {
             users: init_users(),
+            legacy_user_sub_index: init_legacy_user_sub_index(),
             user_sub_index: init_user_sub_index(),
             user_db_id_index: init_user_db_id_index(),
         }
@@ -33,13 +39,15 @@ impl UserRepository {
         STATE.with_borrow(|s| s.users.get(user_principal))
     }
 
-    pub fn get_user_by_sub(&self, user_sub: &UserSub) -> Option<(UserPrincipal, User)> {
+    pub fn get_user_by_sub(&self, user_sub_key: &UserSubKey) -> Option<(UserPrincipal, User)> {
         STATE.with_borrow(|s| {
-            s.user_sub_index.get(user_sub).and_then(|user_principal| {
-                s.users
-                    .get(&user_principal)
-                    .map(|user| (user_principal, user))
-            })
+            s.user_sub_index
+                .get(user_sub_key)
+                .and_then(|user_principal| {
+                    s.users
+                        .get(&user_principal)
+                        .map(|user| (user_principal, user))
+                })
         })
     }
 
@@ -54,11 +62,11 @@ impl UserRepository {
     }
 
     pub fn create_user(&self, user_principal: UserPrincipal, user: User) -> Result<(), String> {
-        let user_sub = user.jwt_sub.clone();
+        let user_sub_key = user.sub_key();
         let db_id = user.db_id;
 
-        if self.get_user_by_sub(&user_sub).is_some() {
-            return Err(format!("User with sub {} already exists", user_sub));
+        if self.get_user_by_sub(&user_sub_key).is_some() {
+            return Err(format!("User with sub {} already exists", user_sub_key.sub));
         }
 
         if self.get_user_by_db_id(&db_id).is_some() {
@@ -70,10 +78,54 @@ impl UserRepository {
 
         STATE.with_borrow_mut(|s| {
             s.users.insert(user_principal, user);
-            s.user_sub_index.insert(user_sub, user_principal);
+            s.user_sub_index.insert(user_sub_key, user_principal);
             s.user_db_id_index.insert(db_id, user_principal);
         });
 
         Ok(())
     }
+
+    /// Updates the user, which must keep the same issuer, sub and database id.
+    pub fn update_user(&self, user_principal: UserPrincipal, user: User) -> Result<(), String> {
+        let existing_user = self
+            .get_user_by_principal(&user_principal)
+            .ok_or_else(|| format!("User {} does not exist", user_principal.to_text()))?;
+        if existing_user.sub_key() != user.sub_key() || existing_user.db_id != user.db_id {
+            return Err(format!(
+                "User {} cannot change issuer, sub or database id",
+                user_principal.to_text()
+            ));
+        }
+
+        STATE.with_borrow_mut(|s| s.users.insert(user_principal, user));
+
+        Ok(())
+    }
+
+    /// Indexes up to `limit` users of the legacy sub index by issuer and sub,
+    /// starting after the `after` sub if set.
+    /// Returns the last indexed sub if there may be more users to index.
+    pub fn index_user_subs(&self, after: Option<UserSub>, limit: usize) -> Option<UserSub> {
+        STATE.with_borrow_mut(|s| {
+            let users: Vec<_> = s
+                .legacy_user_sub_index
+                .range((
+                    after.map_or(Bound::Unbounded, Bound::Excluded),
+                    Bound::Unbounded,
+                ))
+                .take(limit)
+                .collect();
+            for (sub, user_principal) in users.iter() {
+                s.user_sub_index.insert(
+                    UserSubKey {
+                        issuer: None,
+                        sub: sub.clone(),
+                    },
+                    *user_principal,
+                );
+            }
+
+            batch_cursor(&users, limit, |(sub, _)| sub.clone())
+        })
+    }
 }
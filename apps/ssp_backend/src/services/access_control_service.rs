@@ -1,7 +1,29 @@
 use candid::Principal;
 use ic_cdk::api::is_controller;
+use ssp_backend_types::ApiError;
 
-use crate::repositories::{ConfigRepository, UserRepository};
+use crate::repositories::{ConfigRepository, UserRepository, UserRole};
+
+/// The actions that only the users with some roles can perform.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Permission {
+    /// Issue certificates to other users, like the backend.
+    IssueCertificatesToOthers,
+    /// Read the certificates of all users, like the backend.
+    ReadAllCertificates,
+    /// Register clubs and organizations as certificate issuers.
+    RegisterIssuers,
+}
+
+impl Permission {
+    fn granted_roles(&self) -> &'static [UserRole] {
+        match self {
+            Self::IssueCertificatesToOthers => &[UserRole::Coach, UserRole::ClubAdmin],
+            Self::ReadAllCertificates => &[UserRole::Admin],
+            Self::RegisterIssuers => &[UserRole::ClubAdmin],
+        }
+    }
+}
 
 #[derive(Default)]
 pub struct AccessControlService {
@@ -13,37 +35,40 @@ impl AccessControlService {
     pub fn assert_principal_is_controller(
         &self,
         calling_principal: &Principal,
-    ) -> Result<(), String> {
+    ) -> Result<(), ApiError> {
         if !is_controller(calling_principal) {
-            return Err("Caller is not a controller".to_string());
+            return Err(ApiError::unauthorized("Caller is not a controller"));
         }
 
         Ok(())
     }
 
-    pub fn assert_principal_is_user(&self, calling_principal: &Principal) -> Result<(), String> {
+    pub fn assert_principal_is_user(&self, calling_principal: &Principal) -> Result<(), ApiError> {
         if self
             .user_repository
             .get_user_by_principal(calling_principal)
             .is_none()
         {
-            return Err(format!(
+            return Err(ApiError::unauthorized(format!(
                 "Caller {} is not a user",
                 calling_principal.to_text()
-            ));
+            )));
         }
 
         Ok(())
     }
 
-    pub fn assert_principal_is_backend(&self, calling_principal: &Principal) -> Result<(), String> {
+    pub fn assert_principal_is_backend(
+        &self,
+        calling_principal: &Principal,
+    ) -> Result<(), ApiError> {
         self.config_repository
             .get_config()
             .backend_principal
-            .ok_or_else(|| "Backend principal not set".to_string())
+            .ok_or_else(|| ApiError::unauthorized("Backend principal not set"))
             .and_then(|backend_principal| {
                 if calling_principal.ne(&backend_principal) {
-                    return Err("Caller is not the backend".to_string());
+                    return Err(ApiError::unauthorized("Caller is not the backend"));
                 }
 
                 Ok(())
@@ -53,14 +78,53 @@ impl AccessControlService {
     pub fn assert_principal_is_user_or_backend<'a>(
         &self,
         calling_principal: &'a Principal,
-    ) -> Result<Option<&'a Principal>, String> {
+    ) -> Result<Option<&'a Principal>, ApiError> {
         match self.assert_principal_is_backend(calling_principal) {
             Ok(_) => Ok(None),
             Err(_) => {
                 self.assert_principal_is_user(calling_principal)
-                    .map_err(|_| "Caller is not the backend or a registered user".to_string())?;
+                    .map_err(|_| {
+                        ApiError::unauthorized("Caller is not the backend or a registered user")
+                    })?;
                 Ok(Some(calling_principal))
             }
         }
     }
+
+    pub fn principal_has_permission(
+        &self,
+        calling_principal: &Principal,
+        permission: Permission,
+    ) -> bool {
+        self.user_repository
+            .get_user_by_principal(calling_principal)
+            .is_some_and(|user| user.has_any_role(permission.granted_roles()))
+    }
+
+    pub fn assert_principal_has_permission(
+        &self,
+        calling_principal: &Principal,
+        permission: Permission,
+    ) -> Result<(), ApiError> {
+        if !self.principal_has_permission(calling_principal, permission) {
+            return Err(ApiError::unauthorized(format!(
+                "Caller {} does not have the required role",
+                calling_principal.to_text()
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Like [AccessControlService::assert_principal_is_user_or_backend],
+    /// but the users with the permission are not restricted to their own principal.
+    pub fn assert_principal_is_user_or_backend_with_permission<'a>(
+        &self,
+        calling_principal: &'a Principal,
+        permission: Permission,
+    ) -> Result<Option<&'a Principal>, ApiError> {
+        let only_user_principal = self.assert_principal_is_user_or_backend(calling_principal)?;
+
+        Ok(only_user_principal.filter(|p| !self.principal_has_permission(p, permission)))
+    }
 }
@@ -0,0 +1,92 @@
+use candid::Principal;
+use ic_cdk::println;
+use ssp_backend_types::{
+    ApiError, CreateAchievementRequest, CreateAchievementResponse, ValidateRequest,
+};
+
+use crate::{
+    mappings::{map_achievement, map_create_achievement_response},
+    repositories::{Achievement, AchievementId, AchievementRepository, Certificate, DateTime},
+    services::IssuerService,
+    system_api::get_date_time,
+};
+
+#[derive(Default)]
+pub struct AchievementService {
+    achievement_repository: AchievementRepository,
+    issuer_service: IssuerService,
+}
+
+impl AchievementService {
+    pub fn get_achievement(&self, id: String) -> Result<ssp_backend_types::Achievement, ApiError> {
+        let id = AchievementId::try_from(id.as_str()).map_err(|e| ApiError::validation("id", e))?;
+
+        self.get_existing_achievement(&id)
+            .map(|achievement| map_achievement(id, achievement))
+    }
+
+    /// Registers a new achievement, which cannot be changed afterwards.
+    /// If `only_issuer_admin` is set, the achievement must be for an issuer
+    /// of which it is an admin.
+    pub async fn create_achievement(
+        &self,
+        request: CreateAchievementRequest,
+        only_issuer_admin: Option<Principal>,
+    ) -> Result<CreateAchievementResponse, ApiError> {
+        request.validate()?;
+
+        let issuer_id = match request.issuer_id {
+            Some(ref issuer_id) => Some(
+                self.issuer_service
+                    .achievement_issuer(issuer_id, only_issuer_admin)?,
+            ),
+            None if only_issuer_admin.is_some() => {
+                return Err(ApiError::unauthorized(
+                    "Users can only create achievements for their issuers",
+                ));
+            }
+            None => None,
+        };
+
+        let achievement = Achievement {
+            name: request.name.trim().to_string(),
+            description: request.description.trim().to_string(),
+            criteria: request.criteria.trim().to_string(),
+            sport_category: request.sport_category,
+            issuer_id,
+            created_at: DateTime::new(get_date_time()?)?,
+        };
+        let id = self
+            .achievement_repository
+            .create_achievement(achievement)
+            .await?;
+
+        println!("Created achievement with id: {}", id.to_string());
+
+        Ok(map_create_achievement_response(id))
+    }
+
+    /// Returns the achievement awarded by the certificate,
+    /// which must be for the sport category and the issuer of the certificate.
+    pub fn certificate_achievement(
+        &self,
+        id: &AchievementId,
+        certificate: &Certificate,
+    ) -> Result<Achievement, ApiError> {
+        let achievement = self.get_existing_achievement(id)?;
+
+        if !achievement.matches(certificate) {
+            return Err(ApiError::conflict(
+                "Achievement cannot be awarded for the sport category or the issuer of the certificate",
+            ));
+        }
+
+        Ok(achievement)
+    }
+
+    fn get_existing_achievement(&self, id: &AchievementId) -> Result<Achievement, ApiError> {
+        self.achievement_repository
+            .get_achievement(id)
+            .ok_or_else(|| ApiError::not_found("Achievement not found"))
+    }
+}
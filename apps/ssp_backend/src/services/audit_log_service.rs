@@ -0,0 +1,77 @@
+use candid::{CandidType, Encode, Principal};
+use canister_sig_util::hash_bytes;
+use ic_cdk::trap;
+use ssp_backend_types::{
+    ApiError, GetAuditLogRequest, GetAuditLogResponse, ValidateRequest,
+    DEFAULT_AUDIT_LOG_PAGE_LIMIT,
+};
+
+use crate::{
+    mappings::map_get_audit_log_response,
+    repositories::{AuditLogEntry, AuditLogOperation, AuditLogRepository},
+    system_api::unix_timestamp_ns,
+};
+
+#[derive(Default)]
+pub struct AuditLogService {
+    audit_log_repository: AuditLogRepository,
+}
+
+impl AuditLogService {
+    /// Appends an entry for the mutating call to the audit log, chained to the current tip.
+    /// The `payload` is the Candid encodable arguments of the call, of which only the hash is stored.
+    ///
+    /// Traps if the entry cannot be appended, so that the writes of the call are rolled back
+    /// together with it: the entry must be recorded after the last `await` of the call.
+    pub fn record<T: CandidType>(
+        &self,
+        caller: Principal,
+        operation: AuditLogOperation,
+        payload: &T,
+    ) {
+        if let Err(e) = self.append_entry(caller, operation, payload) {
+            trap(&format!("Failed to record the audit log entry: {}", e));
+        }
+    }
+
+    fn append_entry<T: CandidType>(
+        &self,
+        caller: Principal,
+        operation: AuditLogOperation,
+        payload: &T,
+    ) -> Result<(), String> {
+        let payload = Encode!(payload).map_err(|e| e.to_string())?;
+
+        let entry = AuditLogEntry {
+            caller,
+            timestamp: unix_timestamp_ns(),
+            operation,
+            payload_hash: hash_bytes(payload),
+            previous_hash: self.audit_log_repository.get_tip_hash(),
+        };
+        self.audit_log_repository.append_entry(entry)?;
+
+        Ok(())
+    }
+
+    pub fn get_audit_log(
+        &self,
+        request: GetAuditLogRequest,
+    ) -> Result<GetAuditLogResponse, ApiError> {
+        request.validate()?;
+
+        let entries = self.audit_log_repository.get_entries(
+            request.start.unwrap_or_default(),
+            request.limit.unwrap_or(DEFAULT_AUDIT_LOG_PAGE_LIMIT).into(),
+        );
+
+        Ok(map_get_audit_log_response(
+            entries,
+            self.audit_log_repository.get_tip_with_certification(),
+        ))
+    }
+
+    pub fn certify_audit_log_tip(&self) {
+        self.audit_log_repository.certify_tip();
+    }
+}
@@ -0,0 +1,88 @@
+use ic_certification::AsHashTree;
+use ssp_backend_types::{
+    GetBlocksArgs, GetBlocksResult, Icrc3DataCertificate, SupportedBlockType,
+    MAX_ICRC3_BLOCKS_PER_RESPONSE,
+};
+
+use crate::{
+    mappings::{map_get_blocks_result, map_icrc3_data_certificate, nat_to_u64},
+    repositories::{
+        Certificate, CertificateBlock, CertificateBlockOperation, CertificateBlockRepository,
+        CertificateId, CertificateVersion, CERTIFICATE_ISSUE_BLOCK_TYPE,
+        CERTIFICATE_REVOKE_BLOCK_TYPE, CERTIFICATE_TRANSFER_BLOCK_TYPE,
+        CERTIFICATE_UPDATE_BLOCK_TYPE,
+    },
+    system_api::unix_timestamp_ns,
+};
+
+pub const ICRC3_STANDARD_URL: &str = "https://github.com/dfinity/ICRC-1/tree/main/standards/ICRC-3";
+
+#[derive(Default)]
+pub struct CertificateBlockService {
+    certificate_block_repository: CertificateBlockRepository,
+}
+
+impl CertificateBlockService {
+    /// Records the operation on the certificate version as a new ICRC-3 block.
+    pub fn append_block(
+        &self,
+        operation: CertificateBlockOperation,
+        id: CertificateId,
+        version: CertificateVersion,
+        certificate: &Certificate,
+    ) -> Result<(), String> {
+        let block = CertificateBlock {
+            timestamp: unix_timestamp_ns(),
+            parent_hash: self.certificate_block_repository.get_last_block_hash(),
+            operation,
+            certificate_id: id,
+            certificate_version: version,
+            certificate_hash: certificate.root_hash(),
+            to: certificate.user_principal,
+        };
+        self.certificate_block_repository.append_block(block)?;
+
+        Ok(())
+    }
+
+    /// Returns the requested blocks, up to [MAX_ICRC3_BLOCKS_PER_RESPONSE] in total.
+    pub fn get_blocks(&self, args: GetBlocksArgs) -> GetBlocksResult {
+        let mut remaining = MAX_ICRC3_BLOCKS_PER_RESPONSE;
+        let mut blocks = vec![];
+        for request in args {
+            let start = nat_to_u64(&request.start);
+            let length = nat_to_u64(&request.length).min(remaining);
+
+            let range_blocks = self.certificate_block_repository.get_blocks(start, length);
+            remaining -= range_blocks.len() as u64;
+            blocks.extend(range_blocks);
+        }
+
+        map_get_blocks_result(self.certificate_block_repository.get_blocks_count(), blocks)
+    }
+
+    pub fn get_tip_certificate(&self) -> Option<Icrc3DataCertificate> {
+        self.certificate_block_repository
+            .get_last_block_certification()
+            .map(map_icrc3_data_certificate)
+    }
+
+    pub fn supported_block_types(&self) -> Vec<SupportedBlockType> {
+        [
+            CERTIFICATE_ISSUE_BLOCK_TYPE,
+            CERTIFICATE_UPDATE_BLOCK_TYPE,
+            CERTIFICATE_REVOKE_BLOCK_TYPE,
+            CERTIFICATE_TRANSFER_BLOCK_TYPE,
+        ]
+        .into_iter()
+        .map(|block_type| SupportedBlockType {
+            block_type: block_type.to_string(),
+            url: ICRC3_STANDARD_URL.to_string(),
+        })
+        .collect()
+    }
+
+    pub fn certify_last_block(&self) {
+        self.certificate_block_repository.certify_last_block();
+    }
+}
@@ -0,0 +1,131 @@
+use canister_sig_util::{hash_bytes, CanisterSigPublicKey};
+use ic_cdk::id as canister_id;
+use ic_certification::Hash;
+use ssp_backend_types::GetCertificateAsVcResponse;
+
+use crate::{
+    mappings::map_certificate_credential,
+    repositories::{
+        Certificate, CertificateId, CertificateRepository, CertificateVersion, DelegationRepository,
+    },
+    utils::batch_cursor,
+};
+
+const CREDENTIAL_DOMAIN_SEPARATOR: &[u8] = b"ssp-certificate-credential";
+/// The canister signatures of all the credentials share the same seed,
+/// so that they can be verified with the same public key.
+const CREDENTIAL_SEED_DOMAIN: &[u8] = b"ssp_certificate_credentials";
+
+/// Signs the latest version of each certificate that is not revoked as a verifiable credential,
+/// using canister signatures. The signatures are certified along with the delegation ones,
+/// but they never expire and are stored apart from them.
+#[derive(Default)]
+pub struct CertificateCredentialService {
+    certificate_repository: CertificateRepository,
+    delegation_repository: DelegationRepository,
+}
+
+impl CertificateCredentialService {
+    /// Returns the signed credential. Must be called in a query, after
+    /// [CertificateCredentialService::sign_certificate_credential] was called for the version.
+    pub fn get_certificate_credential(
+        &self,
+        id: CertificateId,
+        version: CertificateVersion,
+        certificate: &Certificate,
+    ) -> Result<GetCertificateAsVcResponse, String> {
+        let credential = map_certificate_credential(&canister_id(), id, version, certificate);
+        let signature = self
+            .delegation_repository
+            .get_signature(&credential_seed(), credential_msg_hash(&credential))?;
+
+        Ok(GetCertificateAsVcResponse {
+            credential,
+            public_key: CanisterSigPublicKey::new(canister_id(), credential_seed().to_vec())
+                .to_der(),
+            signature,
+        })
+    }
+
+    /// Signs the credential of the certificate version, unless the certificate is revoked.
+    pub fn sign_certificate_credential(
+        &self,
+        id: CertificateId,
+        version: CertificateVersion,
+        certificate: &Certificate,
+    ) {
+        if certificate.is_revoked() {
+            return;
+        }
+
+        self.delegation_repository.add_permanent_signatures(
+            credential_seed(),
+            &[self.certificate_credential_msg_hash(id, version, certificate)],
+        );
+    }
+
+    /// Removes the signature of the credential of the certificate version, if any,
+    /// e.g. when a new version is added or the certificate is revoked.
+    pub fn remove_certificate_credential_signature(
+        &self,
+        id: CertificateId,
+        version: CertificateVersion,
+        certificate: &Certificate,
+    ) {
+        self.delegation_repository.remove_permanent_signature(
+            credential_seed(),
+            self.certificate_credential_msg_hash(id, version, certificate),
+        );
+    }
+
+    /// Signs the credentials of up to `limit` certificates, starting after the `after` id if set,
+    /// e.g. of the certificates that were created before the credentials were introduced.
+    /// Only the missing signatures are added, the existing ones are left untouched.
+    /// Returns the id of the last processed certificate if there may be more certificates to process.
+    pub fn sign_certificate_credentials(
+        &self,
+        after: Option<CertificateId>,
+        limit: usize,
+    ) -> Result<Option<CertificateId>, String> {
+        let certificates = self
+            .certificate_repository
+            .get_latest_certificate_versions(after, limit)?;
+        let message_hashes: Vec<Hash> = certificates
+            .iter()
+            .filter(|(_, certificate, _)| !certificate.is_revoked())
+            .map(|(id, certificate, version)| {
+                self.certificate_credential_msg_hash(*id, *version, certificate)
+            })
+            .collect();
+
+        self.delegation_repository
+            .add_permanent_signatures(credential_seed(), &message_hashes);
+
+        Ok(batch_cursor(&certificates, limit, |(id, ..)| *id))
+    }
+
+    fn certificate_credential_msg_hash(
+        &self,
+        id: CertificateId,
+        version: CertificateVersion,
+        certificate: &Certificate,
+    ) -> Hash {
+        credential_msg_hash(&map_certificate_credential(
+            &canister_id(),
+            id,
+            version,
+            certificate,
+        ))
+    }
+}
+
+fn credential_seed() -> Hash {
+    hash_bytes(CREDENTIAL_SEED_DOMAIN)
+}
+
+fn credential_msg_hash(credential: &str) -> Hash {
+    let mut msg: Vec<u8> = Vec::from([(CREDENTIAL_DOMAIN_SEPARATOR.len() as u8)]);
+    msg.extend_from_slice(CREDENTIAL_DOMAIN_SEPARATOR);
+    msg.extend_from_slice(credential.as_bytes());
+    hash_bytes(msg)
+}
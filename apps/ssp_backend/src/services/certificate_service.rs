@@ -1,70 +1,142 @@
 use candid::Principal;
 use ic_cdk::println;
 use ssp_backend_types::{
-    CreateCertificateRequest, CreateCertificateResponse, GetCertificateResponse,
-    GetUserCertificatesRequest, GetUserCertificatesResponse, ValidateRequest,
+    ApiError, CreateCertificateRequest, CreateCertificateResponse,
+    CreateCertificateShareTokenResponse, GetCertificateAsVcResponse, GetCertificateResponse,
+    GetManagedUserCertificatesRequest, GetMyCertificatesRequest, GetUserCertificatesRequest,
+    GetUserCertificatesResponse, MyCertificatesList, RevokeCertificateRequest,
+    SetCertificatePublicRequest, UpdateCertificateRequest, UpdateCertificateResponse,
+    ValidateRequest, VerifyCertificateRequest, DEFAULT_CERTIFICATES_PAGE_LIMIT,
 };
 
 use crate::{
     mappings::{
-        map_certificate_preview_with_id, map_certificate_with_id, map_create_certificate_response,
+        map_certificate_with_id, map_create_certificate_response,
+        map_create_certificate_share_token_response, map_get_user_certificates_response,
+        map_update_certificate_response,
     },
     repositories::{
-        Certificate, CertificateId, CertificateRepository, DateTime,
-        UserCertificateWithCertification, UserDbId, UserRepository,
+        AchievementId, Certificate, CertificateBlockOperation, CertificateContent,
+        CertificateCursor, CertificateFilters, CertificateId, CertificateOwner,
+        CertificateRepository, CertificateRevocation, CertificateSharing, CertificateSort,
+        CertificateVersion, DateTime, FileHash, ManagedUserId, UserCertificateWithCertification,
+        UserDbId, UserRepository, FIRST_CERTIFICATE_VERSION,
     },
-    system_api::get_date_time,
+    services::{
+        AchievementService, CertificateBlockService, CertificateCredentialService, FileService,
+        HttpService, IssuerService, ManagedUserService,
+    },
+    system_api::{get_date_time, with_random_bytes},
 };
 
+const SHARE_TOKEN_BYTES: usize = 32;
+
 #[derive(Default)]
 pub struct CertificateService {
     certificate_repository: CertificateRepository,
     user_repository: UserRepository,
+    http_service: HttpService,
+    file_service: FileService,
+    managed_user_service: ManagedUserService,
+    issuer_service: IssuerService,
+    certificate_block_service: CertificateBlockService,
+    certificate_credential_service: CertificateCredentialService,
+    achievement_service: AchievementService,
 }
 
 impl CertificateService {
     pub fn get_certificate(
         &self,
         id: String,
+        version: Option<CertificateVersion>,
         only_user_principal: Option<Principal>,
-    ) -> Result<GetCertificateResponse, String> {
-        let id = CertificateId::try_from(id.as_str())?;
-
-        let UserCertificateWithCertification {
-            certificate,
-            ic_certificate,
-            ic_certificate_witness,
-        } = self.certificate_repository.get_certificate(&id);
-
-        match certificate {
-            Some(cert) => {
-                if let Some(p) = only_user_principal {
-                    if p != cert.user_principal {
-                        return Err("User can only access their own certificates".to_string());
-                    }
-                }
+    ) -> Result<GetCertificateResponse, ApiError> {
+        let id = CertificateId::try_from(id.as_str()).map_err(|e| ApiError::validation("id", e))?;
 
-                Ok(GetCertificateResponse {
-                    certificate: map_certificate_with_id(id, cert),
-                    ic_certificate,
-                    ic_certificate_witness,
-                })
+        if let Some(p) = only_user_principal {
+            // the access is checked on the latest version, as the previous versions
+            // of a claimed certificate are still held by the guardian
+            let latest_certificate = self
+                .certificate_repository
+                .get_certificate(&id)
+                .ok_or_else(|| ApiError::not_found("Certificate not found"))?;
+            if !self.can_read_certificate(&latest_certificate, &p) {
+                return Err(ApiError::unauthorized(
+                    "User can only access their own certificates",
+                ));
             }
-            None => Err("Certificate not found".to_string()),
+        }
+
+        match self
+            .certificate_repository
+            .get_certificate_with_certification(&id, version)?
+        {
+            Some(UserCertificateWithCertification {
+                certificate,
+                version,
+                latest_version,
+                ic_certificate,
+                ic_certificate_witness,
+            }) => Ok(GetCertificateResponse {
+                certificate: map_certificate_with_id(id, version, certificate),
+                latest_version,
+                ic_certificate,
+                ic_certificate_witness,
+            }),
+            None => Err(ApiError::not_found("Certificate not found")),
         }
     }
 
+    /// Returns the latest version of the certificate as a signed verifiable credential.
+    pub fn get_certificate_as_vc(
+        &self,
+        id: String,
+        only_user_principal: Option<Principal>,
+    ) -> Result<GetCertificateAsVcResponse, ApiError> {
+        let id = CertificateId::try_from(id.as_str()).map_err(|e| ApiError::validation("id", e))?;
+        let (certificate, version) = self
+            .certificate_repository
+            .get_latest_certificate_version(&id)?
+            .ok_or_else(|| ApiError::not_found("Certificate not found"))?;
+
+        if let Some(p) = only_user_principal {
+            if !self.can_read_certificate(&certificate, &p) {
+                return Err(ApiError::unauthorized(
+                    "User can only access their own certificates",
+                ));
+            }
+        }
+
+        if certificate.is_revoked() {
+            return Err(ApiError::conflict(
+                "Revoked certificates cannot be exported as verifiable credentials",
+            ));
+        }
+
+        Ok(self
+            .certificate_credential_service
+            .get_certificate_credential(id, version, &certificate)?)
+    }
+
     pub fn get_user_certificates(
         &self,
         request: GetUserCertificatesRequest,
         only_user_principal: Option<Principal>,
-    ) -> Result<GetUserCertificatesResponse, String> {
+    ) -> Result<GetUserCertificatesResponse, ApiError> {
         request.validate()?;
 
         let maybe_user_db_id = match request.user_db_id {
-            Some(id) => Some(UserDbId::try_from(id.as_str())?),
+            Some(id) => Some(
+                UserDbId::try_from(id.as_str())
+                    .map_err(|e| ApiError::validation("user_db_id", e))?,
+            ),
             None => None,
         };
+        let sort: CertificateSort = request.sort.map(Into::into).unwrap_or_default();
+        let cursor = parse_cursor(request.cursor)?;
+        let limit = request.limit.unwrap_or(DEFAULT_CERTIFICATES_PAGE_LIMIT) as usize;
+        let filters: CertificateFilters = request.filters.map(Into::into).unwrap_or_default();
+
         let maybe_user_principal = request.user_principal.or_else(|| {
             // at this point, the user_db_id has a value because of the validation,
             // which requires either user_db_id or user_principal to be provided
@@ -75,78 +147,213 @@ impl CertificateService {
                 .map(|(p, _)| p)
         });
 
-        let mut certificates = vec![];
+        let mut page = None;
         if let Some(user_principal) = maybe_user_principal {
             if let Some(p) = only_user_principal {
                 if p != user_principal {
-                    return Err("User can only access their own certificates".to_string());
+                    return Err(ApiError::unauthorized(
+                        "User can only access their own certificates",
+                    ));
                 }
             }
 
-            certificates = self
+            if self
                 .certificate_repository
-                .get_certificates_by_user_principal(&user_principal)?;
+                .has_certificates_by_user_principal(&user_principal)?
+            {
+                page = Some(self.certificate_repository.get_certificates_page(
+                    CertificateOwner::UserPrincipal(user_principal),
+                    sort,
+                    cursor,
+                    limit,
+                    &filters,
+                    None,
+                )?);
+            }
         }
 
         // try getting the certificates by managed user id
-        if certificates.is_empty() {
+        if page.is_none() {
             if let Some(user_db_id) = maybe_user_db_id {
-                certificates = self
-                    .certificate_repository
-                    .get_certificates_by_managed_user_id(&user_db_id)?;
-                if let Some(p) = only_user_principal {
-                    certificates.retain(|(_, cert)| cert.user_principal == p);
-                }
+                page = Some(self.certificate_repository.get_certificates_page(
+                    CertificateOwner::ManagedUserId(user_db_id),
+                    sort,
+                    cursor,
+                    limit,
+                    &filters,
+                    only_user_principal,
+                )?);
             }
         }
 
-        Ok(GetUserCertificatesResponse {
-            certificates: certificates
-                .into_iter()
-                .map(|(id, certificate)| map_certificate_preview_with_id(id, certificate))
-                .collect(),
-        })
+        Ok(map_get_user_certificates_response(page.unwrap_or_default()))
     }
 
+    /// Lists the certificates received by the user or the ones they issued to other users.
+    pub fn get_my_certificates(
+        &self,
+        request: GetMyCertificatesRequest,
+        calling_principal: Principal,
+    ) -> Result<GetUserCertificatesResponse, ApiError> {
+        request.validate()?;
+
+        let owner = match request.list {
+            MyCertificatesList::Received => CertificateOwner::UserPrincipal(calling_principal),
+            MyCertificatesList::Issued => CertificateOwner::IssuerPrincipal(calling_principal),
+        };
+        let sort: CertificateSort = request.sort.map(Into::into).unwrap_or_default();
+        let cursor = parse_cursor(request.cursor)?;
+        let limit = request.limit.unwrap_or(DEFAULT_CERTIFICATES_PAGE_LIMIT) as usize;
+        let filters: CertificateFilters = request.filters.map(Into::into).unwrap_or_default();
+
+        let page = self
+            .certificate_repository
+            .get_certificates_page(owner, sort, cursor, limit, &filters, None)?;
+
+        Ok(map_get_user_certificates_response(page))
+    }
+
+    /// Lists the certificates of the managed user, held by any of their guardians.
+    /// If `only_guardian` is set, it must be a guardian of the managed user.
+    pub fn get_managed_user_certificates(
+        &self,
+        request: GetManagedUserCertificatesRequest,
+        only_guardian: Option<Principal>,
+    ) -> Result<GetUserCertificatesResponse, ApiError> {
+        request.validate()?;
+
+        let managed_user_id = ManagedUserId::try_from(request.managed_user_db_id.as_str())
+            .map_err(|e| ApiError::validation("managed_user_db_id", e))?;
+        if let Some(p) = only_guardian {
+            if !self.managed_user_service.is_guardian(&managed_user_id, &p) {
+                return Err(ApiError::unauthorized(
+                    "User is not a guardian of the managed user",
+                ));
+            }
+        }
+        let sort: CertificateSort = request.sort.map(Into::into).unwrap_or_default();
+        let cursor = parse_cursor(request.cursor)?;
+        let limit = request.limit.unwrap_or(DEFAULT_CERTIFICATES_PAGE_LIMIT) as usize;
+        let filters: CertificateFilters = request.filters.map(Into::into).unwrap_or_default();
+
+        let page = self.certificate_repository.get_certificates_page(
+            CertificateOwner::ManagedUserId(managed_user_id),
+            sort,
+            cursor,
+            limit,
+            &filters,
+            None,
+        )?;
+
+        Ok(map_get_user_certificates_response(page))
+    }
+
+    /// The `issuer_principal` is recorded on the certificate
+    /// if it is issued to a user other than the issuer.
+    /// If the request has an issuer id, the issuer's current verified status is recorded
+    /// and the issuing user must be an admin or a coach of the issuer.
+    /// If the request has an achievement id, the achievement must be
+    /// for the sport category and the issuer of the certificate.
+    /// If the request has a managed user id, see [ManagedUserService::assert_can_link_certificate_holder].
+    ///
+    /// All the checks are done before anything is stored, and there is no `await` in between,
+    /// so that a failed request leaves no partial writes behind.
     pub async fn create_certificate(
         &self,
         request: CreateCertificateRequest,
         calling_user_principal: Option<Principal>,
-    ) -> Result<CreateCertificateResponse, String> {
+        issuer_principal: Option<Principal>,
+    ) -> Result<CreateCertificateResponse, ApiError> {
         request.validate()?;
 
+        let id = CertificateId::new().await?;
+
         let user_principal = match calling_user_principal {
             Some(principal) => principal,
             None => {
-                let user_db_id = UserDbId::try_from(request.user_db_id.as_str())?;
+                let user_db_id = UserDbId::try_from(request.user_db_id.as_str())
+                    .map_err(|e| ApiError::validation("user_db_id", e))?;
                 self.user_repository
                     .get_user_by_db_id(&user_db_id)
                     .ok_or_else(|| {
-                        format!(
+                        ApiError::not_found(format!(
                             "User with database id {} does not exist",
                             user_db_id.to_string()
-                        )
+                        ))
                     })?
                     .0
             }
         };
 
+        let issuer = match request.issuer_id {
+            Some(ref issuer_id) => Some(
+                self.issuer_service
+                    .certificate_issuer(issuer_id, calling_user_principal.or(issuer_principal))?,
+            ),
+            None => None,
+        };
+
+        let achievement_id = match request.achievement_id {
+            Some(ref id) => Some(
+                AchievementId::try_from(id.as_str())
+                    .map_err(|e| ApiError::validation("achievement_id", e))?,
+            ),
+            None => None,
+        };
+
         let date_time = get_date_time()?;
 
         let certificate = Certificate {
+            achievement_id,
             user_principal,
             created_at: DateTime::new(date_time)?,
-            content: request.content.try_into()?,
+            issuer,
+            content: CertificateContent::try_from(request.content)
+                .map_err(|e| e.in_field("content"))?,
+            issuer_principal: issuer_principal.filter(|p| *p != user_principal),
             managed_user_id: match request.managed_user_db_id {
-                Some(id) => Some(UserDbId::try_from(id.as_str())?),
+                Some(id) => Some(
+                    UserDbId::try_from(id.as_str())
+                        .map_err(|e| ApiError::validation("managed_user_db_id", e))?,
+                ),
                 None => None,
             },
+            revocation: None,
+            updated_at: None,
         };
+        self.assert_certificate_file_exists(&certificate)?;
+        self.assert_certificate_can_award_achievement(&certificate)?;
+        if let Some(managed_user_id) = certificate.managed_user_id {
+            // only the users who can issue certificates to others (i.e. with an `issuer_principal`)
+            // can issue the certificates of a managed user, to one of their existing guardians
+            if calling_user_principal.is_some() {
+                return Err(ApiError::unauthorized(
+                    "User cannot issue certificates to managed users",
+                ));
+            }
+            let is_backend = issuer_principal.is_none();
+            self.managed_user_service
+                .assert_can_link_certificate_holder(
+                    &managed_user_id,
+                    &user_principal,
+                    is_backend,
+                )?;
+        }
 
-        let id = self
-            .certificate_repository
-            .create_certificate(certificate)
-            .await?;
+        self.certificate_repository
+            .create_certificate(id, certificate.clone())?;
+        if let Some(managed_user_id) = certificate.managed_user_id {
+            self.managed_user_service
+                .link_certificate_holder(managed_user_id, user_principal)?;
+        }
+        self.certificate_block_service.append_block(
+            CertificateBlockOperation::Issue,
+            id,
+            FIRST_CERTIFICATE_VERSION,
+            &certificate,
+        )?;
+        self.certificate_credential_service
+            .sign_certificate_credential(id, FIRST_CERTIFICATE_VERSION, &certificate);
 
         println!(
             "Created certificate for user {} with id: {}",
@@ -157,7 +364,372 @@ impl CertificateService {
         Ok(map_create_certificate_response(id))
     }
 
-    pub fn certify_all_certificates(&self) {
-        self.certificate_repository.certify_all_certificates();
+    /// Adds a new version of the certificate with the updated content.
+    /// If the certificate was issued on behalf of an issuer, the issuer's current
+    /// verified status is recorded again and the updating user must still be
+    /// an admin or a coach of the issuer.
+    pub fn update_certificate(
+        &self,
+        request: UpdateCertificateRequest,
+        only_user_principal: Option<Principal>,
+    ) -> Result<UpdateCertificateResponse, ApiError> {
+        request.validate()?;
+
+        let id = CertificateId::try_from(request.id.as_str())
+            .map_err(|e| ApiError::validation("id", e))?;
+        let previous_certificate = self
+            .certificate_repository
+            .get_certificate(&id)
+            .ok_or_else(|| ApiError::not_found("Certificate not found"))?;
+
+        assert_can_modify_certificate(&previous_certificate, only_user_principal, "update")?;
+
+        if previous_certificate.is_revoked() {
+            return Err(ApiError::conflict("Revoked certificates cannot be updated"));
+        }
+
+        let issuer = match previous_certificate.issuer {
+            Some(ref issuer) => Some(
+                self.issuer_service
+                    .certificate_issuer(&issuer.id.to_string(), only_user_principal)?,
+            ),
+            None => None,
+        };
+
+        let date_time = get_date_time()?;
+        let certificate = Certificate {
+            content: CertificateContent::try_from(request.content)
+                .map_err(|e| e.in_field("content"))?,
+            updated_at: Some(DateTime::new(date_time)?),
+            issuer,
+            ..previous_certificate.clone()
+        };
+        self.assert_certificate_file_exists(&certificate)?;
+        // the sport category may have changed
+        self.assert_certificate_can_award_achievement(&certificate)?;
+
+        let version = self
+            .certificate_repository
+            .add_certificate_version(id, certificate.clone())?;
+        self.certificate_block_service.append_block(
+            CertificateBlockOperation::Update,
+            id,
+            version,
+            &certificate,
+        )?;
+        self.certificate_credential_service
+            .remove_certificate_credential_signature(id, version - 1, &previous_certificate);
+        self.certificate_credential_service
+            .sign_certificate_credential(id, version, &certificate);
+        self.http_service.certify_certificate_responses(&id)?;
+
+        println!(
+            "Updated certificate with id: {} to version {}",
+            id.to_string(),
+            version
+        );
+
+        Ok(map_update_certificate_response(version))
     }
+
+    /// Adds a new version of the certificate that carries the revocation,
+    /// so that the previous versions stay certified as they were issued.
+    pub fn revoke_certificate(
+        &self,
+        request: RevokeCertificateRequest,
+        only_user_principal: Option<Principal>,
+    ) -> Result<(), ApiError> {
+        request.validate()?;
+
+        let id = CertificateId::try_from(request.id.as_str())
+            .map_err(|e| ApiError::validation("id", e))?;
+        let mut certificate = self
+            .certificate_repository
+            .get_certificate(&id)
+            .ok_or_else(|| ApiError::not_found("Certificate not found"))?;
+
+        assert_can_modify_certificate(&certificate, only_user_principal, "revoke")?;
+
+        if certificate.is_revoked() {
+            return Err(ApiError::conflict("Certificate is already revoked"));
+        }
+
+        let previous_certificate = certificate.clone();
+        let date_time = get_date_time()?;
+        certificate.revocation = Some(CertificateRevocation {
+            reason: request.reason.clone(),
+            revoked_at: DateTime::new(date_time)?,
+        });
+
+        let version = self
+            .certificate_repository
+            .add_certificate_version(id, certificate.clone())?;
+        self.certificate_block_service.append_block(
+            CertificateBlockOperation::Revoke {
+                reason: request.reason,
+            },
+            id,
+            version,
+            &certificate,
+        )?;
+        self.certificate_credential_service
+            .remove_certificate_credential_signature(id, version - 1, &previous_certificate);
+        self.http_service.certify_certificate_responses(&id)?;
+
+        println!(
+            "Revoked certificate with id: {} in version {}",
+            id.to_string(),
+            version
+        );
+
+        Ok(())
+    }
+
+    pub fn set_certificate_public(
+        &self,
+        request: SetCertificatePublicRequest,
+        only_user_principal: Option<Principal>,
+    ) -> Result<(), ApiError> {
+        let id = CertificateId::try_from(request.id.as_str())
+            .map_err(|e| ApiError::validation("id", e))?;
+        self.assert_can_share_certificate(&id, only_user_principal)?;
+
+        let sharing = CertificateSharing {
+            is_public: request.is_public,
+            ..self.certificate_repository.get_certificate_sharing(&id)
+        };
+        self.certificate_repository
+            .set_certificate_sharing(id, sharing)?;
+        self.http_service.certify_certificate_responses(&id)?;
+
+        println!(
+            "Set certificate with id: {} public: {}",
+            id.to_string(),
+            request.is_public
+        );
+
+        Ok(())
+    }
+
+    /// Generates a new share token for the certificate, replacing the previous one if any.
+    pub async fn create_certificate_share_token(
+        &self,
+        id: String,
+        only_user_principal: Option<Principal>,
+    ) -> Result<CreateCertificateShareTokenResponse, ApiError> {
+        let id = CertificateId::try_from(id.as_str()).map_err(|e| ApiError::validation("id", e))?;
+        self.assert_can_share_certificate(&id, only_user_principal)?;
+
+        let share_token =
+            with_random_bytes(|bytes: [u8; SHARE_TOKEN_BYTES]| hex::encode(bytes)).await?;
+
+        // read the sharing settings after the await, as they may have changed in the meantime
+        let sharing = CertificateSharing {
+            share_token_hash: Some(CertificateSharing::hash_share_token(&share_token)),
+            ..self.certificate_repository.get_certificate_sharing(&id)
+        };
+        self.certificate_repository
+            .set_certificate_sharing(id, sharing)?;
+
+        println!(
+            "Created share token for certificate with id: {}",
+            id.to_string()
+        );
+
+        Ok(map_create_certificate_share_token_response(share_token))
+    }
+
+    pub fn revoke_certificate_share_token(
+        &self,
+        id: String,
+        only_user_principal: Option<Principal>,
+    ) -> Result<(), ApiError> {
+        let id = CertificateId::try_from(id.as_str()).map_err(|e| ApiError::validation("id", e))?;
+        self.assert_can_share_certificate(&id, only_user_principal)?;
+
+        let sharing = CertificateSharing {
+            share_token_hash: None,
+            ..self.certificate_repository.get_certificate_sharing(&id)
+        };
+        self.certificate_repository
+            .set_certificate_sharing(id, sharing)?;
+
+        println!(
+            "Revoked share token for certificate with id: {}",
+            id.to_string()
+        );
+
+        Ok(())
+    }
+
+    /// Returns the certificate with its certification to anyone,
+    /// if the certificate is public or the share token is valid.
+    ///
+    /// Certificates that are not shared are reported as not found,
+    /// so that their existence is not disclosed.
+    pub fn verify_certificate(
+        &self,
+        request: VerifyCertificateRequest,
+    ) -> Result<GetCertificateResponse, ApiError> {
+        request.validate()?;
+
+        let id = CertificateId::try_from(request.id.as_str())
+            .map_err(|e| ApiError::validation("id", e))?;
+        if !self
+            .certificate_repository
+            .get_certificate_sharing(&id)
+            .allows(request.share_token.as_deref())
+        {
+            return Err(ApiError::not_found("Certificate not found"));
+        }
+
+        self.get_certificate(request.id, request.version, None)
+    }
+
+    /// Users can read the certificates they hold or issued,
+    /// and the ones of the managed users of which they are a guardian.
+    pub fn can_read_certificate(&self, certificate: &Certificate, principal: &Principal) -> bool {
+        certificate.user_principal == *principal
+            || certificate.issuer_principal == Some(*principal)
+            || certificate
+                .managed_user_id
+                .is_some_and(|id| self.managed_user_service.is_guardian(&id, principal))
+    }
+
+    /// Anyone can read the files of the public certificates and of the certificates shared with
+    /// the `share_token`. The users can also read the files of the certificates they can read,
+    /// see [CertificateService::can_read_certificate], and `None` can read all the files.
+    ///
+    /// Files that cannot be read are reported as not found, so that their existence is not disclosed.
+    pub fn assert_can_read_file(
+        &self,
+        file_hash: &str,
+        share_token: Option<&str>,
+        only_user_principal: Option<Principal>,
+    ) -> Result<(), ApiError> {
+        let file_hash =
+            FileHash::try_from(file_hash).map_err(|e| ApiError::validation("file_hash", e))?;
+        let Some(principal) = only_user_principal else {
+            return Ok(());
+        };
+
+        let can_read = self
+            .certificate_repository
+            .get_certificate_ids_by_file_hash(file_hash)?
+            .into_iter()
+            .any(|id| {
+                self.certificate_repository
+                    .get_certificate_sharing(&id)
+                    .allows(share_token)
+                    || self
+                        .certificate_repository
+                        .get_certificate(&id)
+                        .is_some_and(|certificate| {
+                            self.can_read_certificate(&certificate, &principal)
+                        })
+            });
+        if !can_read {
+            return Err(ApiError::not_found("File not found"));
+        }
+
+        Ok(())
+    }
+
+    fn assert_certificate_file_exists(&self, certificate: &Certificate) -> Result<(), ApiError> {
+        match &certificate.content.file_hash {
+            Some(file_hash) => self.file_service.assert_file_exists(file_hash),
+            None => Ok(()),
+        }
+    }
+
+    fn assert_certificate_can_award_achievement(
+        &self,
+        certificate: &Certificate,
+    ) -> Result<(), ApiError> {
+        match &certificate.achievement_id {
+            Some(achievement_id) => self
+                .achievement_service
+                .certificate_achievement(achievement_id, certificate)
+                .map(|_| ()),
+            None => Ok(()),
+        }
+    }
+
+    fn assert_can_share_certificate(
+        &self,
+        id: &CertificateId,
+        only_user_principal: Option<Principal>,
+    ) -> Result<(), ApiError> {
+        let certificate = self
+            .certificate_repository
+            .get_certificate(id)
+            .ok_or_else(|| ApiError::not_found("Certificate not found"))?;
+
+        if let Some(p) = only_user_principal {
+            if p != certificate.user_principal {
+                return Err(ApiError::unauthorized(
+                    "User can only share their own certificates",
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn index_certificates(
+        &self,
+        after: Option<CertificateId>,
+        limit: usize,
+    ) -> Result<Option<CertificateId>, String> {
+        self.certificate_repository.index_certificates(after, limit)
+    }
+
+    pub fn index_certificate_files(
+        &self,
+        after: Option<CertificateId>,
+        limit: usize,
+    ) -> Result<Option<CertificateId>, String> {
+        self.certificate_repository
+            .index_certificate_files(after, limit)
+    }
+
+    pub fn certify_certificates(
+        &self,
+        after: Option<CertificateId>,
+        limit: usize,
+    ) -> Result<Option<CertificateId>, String> {
+        self.certificate_repository
+            .certify_certificates(after, limit)
+    }
+}
+
+fn parse_cursor(cursor: Option<String>) -> Result<Option<CertificateCursor>, ApiError> {
+    match cursor {
+        Some(cursor) => Ok(Some(
+            CertificateCursor::try_from(cursor.as_str())
+                .map_err(|e| ApiError::validation("cursor", e))?,
+        )),
+        None => Ok(None),
+    }
+}
+
+/// Users can only modify the certificates they issued to others,
+/// and the certificates they hold that were not issued by another user.
+fn assert_can_modify_certificate(
+    certificate: &Certificate,
+    only_user_principal: Option<Principal>,
+    action: &str,
+) -> Result<(), ApiError> {
+    if let Some(p) = only_user_principal {
+        let is_issuer = certificate.issuer_principal == Some(p);
+        let is_holder = certificate.issuer_principal.is_none() && certificate.user_principal == p;
+        if !is_issuer && !is_holder {
+            return Err(ApiError::unauthorized(format!(
+                "User can only {} their own certificates",
+                action
+            )));
+        }
+    }
+
+    Ok(())
 }
@@ -1,23 +1,151 @@
 use candid::Principal;
+use ssp_backend_types::{ApiError, ValidateRequest};
 
-use crate::repositories::{Config, ConfigRepository};
+use crate::repositories::{
+    Config, ConfigRepository, DelegationConfig, OidcConfig, OidcIssuer, OidcProviderRepository,
+};
 
 #[derive(Default)]
 pub struct ConfigService {
     config_repository: ConfigRepository,
+    oidc_provider_repository: OidcProviderRepository,
 }
 
 impl ConfigService {
-    pub fn set_backend_principal(&self, backend_principal: Principal) -> Result<(), String> {
+    pub fn set_backend_principal(&self, backend_principal: Principal) -> Result<(), ApiError> {
         if backend_principal == Principal::anonymous() {
-            return Err("Backend principal cannot be anonymous".to_string());
+            return Err(ApiError::validation(
+                "backend_principal",
+                "Backend principal cannot be anonymous",
+            ));
         }
 
         let mut config = self.config_repository.get_config();
 
         config.backend_principal = Some(backend_principal);
 
-        self.config_repository.set_config(config)
+        self.config_repository.set_config(config)?;
+
+        Ok(())
+    }
+
+    /// Applies to the delegations prepared from now on.
+    pub fn set_delegation_config(
+        &self,
+        delegation_config: ssp_backend_types::DelegationConfig,
+    ) -> Result<(), ApiError> {
+        delegation_config.validate()?;
+
+        let mut config = self.config_repository.get_config();
+
+        config.delegation = Some(delegation_config.into());
+
+        self.config_repository.set_config(config)?;
+
+        Ok(())
+    }
+
+    pub fn get_delegation_config(&self) -> DelegationConfig {
+        self.config_repository
+            .get_config()
+            .delegation
+            .unwrap_or_default()
+    }
+
+    /// Sets the legacy OIDC issuer, which cannot change once set,
+    /// as it determines the principals of its users.
+    pub fn set_legacy_oidc_issuer(&self, issuer: OidcIssuer) -> Result<(), ApiError> {
+        let mut config = self.config_repository.get_config();
+
+        match config.legacy_oidc_issuer {
+            Some(legacy_oidc_issuer) if legacy_oidc_issuer == issuer => return Ok(()),
+            Some(_) => {
+                return Err(ApiError::conflict(
+                    "Legacy OIDC issuer cannot be changed once set",
+                ))
+            }
+            None => {}
+        }
+
+        config.legacy_oidc_issuer = Some(issuer);
+
+        self.config_repository.set_config(config)?;
+
+        Ok(())
+    }
+
+    pub fn is_legacy_oidc_issuer(&self, issuer: &str) -> bool {
+        self.config_repository
+            .get_config()
+            .legacy_oidc_issuer
+            .is_some_and(|legacy_oidc_issuer| legacy_oidc_issuer == issuer)
+    }
+
+    /// Adds the OIDC provider, or updates the provider with the same issuer.
+    pub fn set_oidc_provider(
+        &self,
+        oidc_config: ssp_backend_types::OidcConfig,
+    ) -> Result<(), ApiError> {
+        oidc_config.validate()?;
+
+        self.oidc_provider_repository
+            .set_provider_config(oidc_config.into());
+
+        Ok(())
+    }
+
+    /// Replaces all the OIDC providers. The JWKS of the providers that are kept is preserved.
+    pub fn set_oidc_providers(
+        &self,
+        oidc_configs: Vec<ssp_backend_types::OidcConfig>,
+    ) -> Result<(), ApiError> {
+        for (i, oidc_config) in oidc_configs.iter().enumerate() {
+            oidc_config
+                .validate()
+                .map_err(|e| e.in_field(&format!("oidc_providers[{}]", i)))?;
+
+            if oidc_configs[..i]
+                .iter()
+                .any(|other| other.issuer == oidc_config.issuer)
+            {
+                return Err(ApiError::validation(
+                    format!("oidc_providers[{}].issuer", i),
+                    "Issuers must be unique.",
+                ));
+            }
+        }
+
+        for provider in self.oidc_provider_repository.get_providers() {
+            if !oidc_configs
+                .iter()
+                .any(|oidc_config| oidc_config.issuer == provider.config.issuer)
+            {
+                self.oidc_provider_repository
+                    .remove_provider(&provider.config.issuer);
+            }
+        }
+        for oidc_config in oidc_configs {
+            self.oidc_provider_repository
+                .set_provider_config(oidc_config.into());
+        }
+
+        Ok(())
+    }
+
+    pub fn remove_oidc_provider(&self, issuer: &str) -> Result<(), ApiError> {
+        if !self.oidc_provider_repository.remove_provider(issuer) {
+            return Err(ApiError::not_found("OIDC provider not found"));
+        }
+
+        Ok(())
+    }
+
+    pub fn get_oidc_providers(&self) -> Vec<OidcConfig> {
+        self.oidc_provider_repository
+            .get_providers()
+            .into_iter()
+            .map(|provider| provider.config)
+            .collect()
     }
 
     pub fn get_config(&self) -> Config {
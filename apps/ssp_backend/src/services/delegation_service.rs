@@ -1,43 +1,56 @@
 mod utils;
 
+use std::time::Duration;
+
 use candid::Principal;
-use canister_sig_util::{hash_bytes, signature_map::LABEL_SIG};
+use canister_sig_util::hash_bytes;
 use ic_cdk::{
-    api::{
-        management_canister::http_request::{
-            http_request, CanisterHttpRequestArgument, HttpMethod, HttpResponse, TransformArgs,
-            TransformContext,
-        },
-        set_certified_data,
+    api::management_canister::http_request::{
+        http_request, CanisterHttpRequestArgument, HttpMethod, HttpResponse, TransformArgs,
+        TransformContext,
     },
-    print, trap,
+    print,
 };
-use ic_certification::labeled_hash;
 use ic_certification::Hash;
-use jsonwebtoken_rustcrypto::Algorithm;
 use serde_bytes::ByteBuf;
 use ssp_backend_types::{
-    Auth0JWKSet, Delegation, GetDelegationResponse, PrepareDelegationResponse, SessionKey,
-    SignedDelegation, Timestamp,
+    ApiError, Auth0JWKSet, Delegation, DelegationStats, GetDelegationResponse,
+    PrepareDelegationResponse, SessionKey, SignedDelegation, Timestamp,
 };
 
-use crate::repositories::{
-    decode_jwt, DelegationRepository, IdToken, User, UserDbId, UserRepository, UserSub,
-    AUTH0_ISSUER, EMPTY_SALT,
+use crate::{
+    repositories::{
+        decode_jwt, decode_jwt_header, decode_jwt_issuer, AuditLogOperation, DateTime,
+        DelegationRepository, DelegationSignatureKey, IdToken, JwtHeader, OidcConfig, OidcIssuer,
+        OidcProvider, OidcProviderRepository, User, UserDbId, UserRepository, UserRole, UserSub,
+        ValidationError, EMPTY_SALT,
+    },
+    services::{AuditLogService, ConfigService, ManagedUserService},
+    system_api::{get_date_time, unix_timestamp_ns, NANOS_IN_SECONDS},
 };
 
-use self::utils::{delegation_signature_msg_hash, der_encode_canister_sig_key, random_salt};
+use self::utils::{
+    delegation_signature_msg_hash, der_encode_canister_sig_key, random_salt, revoked_until,
+};
 
 const SUBNET_SIZE: u128 = 13;
 // the response should be around 3KB, so we set a limit of 10KB
 const MAX_RESPONSE_BYTES: u128 = 10_000;
 // there's no body in the request, so we can set a low value
 const REQUEST_BYTES: u128 = 100;
+// an unknown key id triggers at most one JWKS refresh per provider every 5 minutes
+const JWKS_REFRESH_MIN_INTERVAL_NS: u64 = 5 * 60 * NANOS_IN_SECONDS;
+// the certificates of a claimed managed user transferred while signing in, the rest by a job
+const MANAGED_USER_CLAIM_BATCH_SIZE: usize = 20;
 
 #[derive(Default)]
 pub struct DelegationService {
     delegation_repository: DelegationRepository,
     user_repository: UserRepository,
+    oidc_provider_repository: OidcProviderRepository,
+    config_service: ConfigService,
+    managed_user_service: ManagedUserService,
+    audit_log_service: AuditLogService,
 }
 
 impl DelegationService {
@@ -49,7 +62,40 @@ impl DelegationService {
         }
     }
 
-    pub async fn fetch_and_store_jwks(&self) -> Result<(), String> {
+    pub fn get_oidc_issuers(&self) -> Vec<OidcIssuer> {
+        self.oidc_provider_repository
+            .get_providers()
+            .into_iter()
+            .map(|provider| provider.config.issuer)
+            .collect()
+    }
+
+    pub fn has_oidc_provider(&self, issuer: &str) -> bool {
+        self.oidc_provider_repository.get_provider(issuer).is_some()
+    }
+
+    /// Fetches the JWKS of all the OIDC providers.
+    /// The fetch is attempted for every provider, even if some of them fail.
+    pub async fn fetch_and_store_all_jwks(&self) -> Result<(), String> {
+        let mut errors = vec![];
+        for issuer in self.get_oidc_issuers() {
+            if let Err(e) = self.fetch_and_store_jwks(&issuer).await {
+                errors.push(format!("{}: {}", issuer, e));
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(errors.join("; "));
+        }
+
+        Ok(())
+    }
+
+    /// Fetches the JWKS from the OIDC provider and stores it.
+    /// If the fetch fails, the last known JWKS is kept.
+    pub async fn fetch_and_store_jwks(&self, issuer: &str) -> Result<(), String> {
+        let oidc_config = self.get_oidc_provider(issuer)?.config;
+
         // Formula from https://internetcomputer.org/docs/current/developer-docs/gas-cost#special-features.
         // Parameters calculated with https://github.com/domwoe/HTTPS-Outcalls-Calculator.
         let cycles: u128 = (3_000_000 + (60_000 * SUBNET_SIZE)) * SUBNET_SIZE
@@ -58,7 +104,7 @@ impl DelegationService {
 
         let (res,) = http_request(
             CanisterHttpRequestArgument {
-                url: format!("{AUTH0_ISSUER}.well-known/jwks.json"),
+                url: oidc_config.jwks_url,
                 method: HttpMethod::GET,
                 headers: vec![],
                 body: None,
@@ -75,16 +121,110 @@ impl DelegationService {
 
         let jwks: Auth0JWKSet = serde_json::from_slice(&res.body)
             .map_err(|e| format!("Error parsing JWKS: {:?}", e))?;
-        self.delegation_repository.set_jwks(jwks.clone());
+        if jwks.keys.is_empty() {
+            return Err("Fetched JWKS has no keys".to_string());
+        }
+
+        let fetched_at = DateTime::new(get_date_time()?)?;
+        let keys_count = jwks.keys.len();
+        self.oidc_provider_repository
+            .set_jwks(issuer, jwks, Some(fetched_at))?;
 
         print(format!(
-            "Fetched JWKS. JSON Web Keys available: {}",
-            jwks.keys.len()
+            "Fetched JWKS of {}. JSON Web Keys available: {}",
+            issuer, keys_count
         ));
 
         Ok(())
     }
 
+    /// Returns how long to wait before fetching the JWKS of the provider again.
+    /// The delay is zero if the JWKS has never been fetched or is older than `max_age`.
+    pub fn jwks_fetch_delay(&self, issuer: &str, max_age: Duration) -> Result<Duration, String> {
+        let fetched_at = match self.get_oidc_provider(issuer)?.jwks_fetched_at {
+            Some(fetched_at) => fetched_at,
+            None => return Ok(Duration::ZERO),
+        };
+
+        let now = DateTime::new(get_date_time()?)?;
+        let age = Duration::from_micros(
+            now.timestamp_micros()
+                .saturating_sub(fetched_at.timestamp_micros()),
+        );
+
+        Ok(max_age.saturating_sub(age))
+    }
+
+    /// Restores up to `limit` signatures of the delegations that have not expired yet,
+    /// starting after the `after` key if set, e.g. after an upgrade.
+    pub fn restore_delegation_signatures(
+        &self,
+        after: Option<DelegationSignatureKey>,
+        limit: usize,
+    ) -> Option<DelegationSignatureKey> {
+        self.delegation_repository
+            .restore_delegation_signatures(after, limit)
+    }
+
+    /// Restores up to `limit` signatures that never expire, e.g. of the certificate credentials,
+    /// starting after the `after` seed and message hash if set, e.g. after an upgrade.
+    pub fn restore_permanent_signatures(
+        &self,
+        after: Option<(Hash, Hash)>,
+        limit: usize,
+    ) -> Option<(Hash, Hash)> {
+        self.delegation_repository
+            .restore_permanent_signatures(after, limit)
+    }
+
+    /// Moves up to `limit` signatures that never expire out of the delegation signatures,
+    /// starting after the `after` key if set, e.g. of the credentials signed before
+    /// they were stored apart.
+    pub fn move_permanent_signatures(
+        &self,
+        after: Option<DelegationSignatureKey>,
+        limit: usize,
+    ) -> Result<Option<DelegationSignatureKey>, String> {
+        self.delegation_repository
+            .move_permanent_signatures(after, limit)
+    }
+
+    /// Indexes up to `limit` stored signatures by seed,
+    /// starting after the `after` key if set.
+    pub fn index_delegation_signatures(
+        &self,
+        after: Option<DelegationSignatureKey>,
+        limit: usize,
+    ) -> Option<DelegationSignatureKey> {
+        self.delegation_repository
+            .index_delegation_signatures(after, limit)
+    }
+
+    /// Removes up to `limit` signatures of the expired delegations,
+    /// see [DelegationRepository::prune_expired_delegation_signatures].
+    /// Returns whether all the expired entries have been removed.
+    pub fn prune_expired_delegation_signatures(&self, limit: usize) -> bool {
+        let pruned = self
+            .delegation_repository
+            .prune_expired_delegation_signatures(unix_timestamp_ns(), limit);
+        if pruned.count > 0 {
+            print(format!(
+                "Pruned {} expired delegation signatures",
+                pruned.count
+            ));
+        }
+
+        pruned.is_done
+    }
+
+    pub fn get_delegation_stats(&self) -> DelegationStats {
+        DelegationStats {
+            signatures_count: self.delegation_repository.count_delegation_signatures(),
+            revoked_sessions_count: self.delegation_repository.count_revoked_sessions(),
+            revoked_delegations_count: self.delegation_repository.count_revoked_delegations(),
+        }
+    }
+
     pub fn transform_jwks_response(&self, args: TransformArgs) -> HttpResponse {
         let raw_response = args.response;
         // We just need to agree on the body content and content type.
@@ -103,35 +243,95 @@ impl DelegationService {
         &self,
         session_principal: Principal,
         jwt: String,
-    ) -> Result<PrepareDelegationResponse, String> {
-        let (token, session_key) = match self.check_authorization(session_principal, jwt) {
-            Ok(res) => res,
-            Err(e) => {
-                trap(&e);
-            }
+    ) -> Result<PrepareDelegationResponse, ApiError> {
+        self.refresh_jwks_if_key_unknown(&jwt).await;
+        let (token, session_key, oidc_config) = self.check_authorization(session_principal, jwt)?;
+
+        let issuer = token.claims.iss.clone();
+        let sub = token.claims.sub.clone();
+        // only the users of the providers that set the Hasura claims are linked to a database user
+        let db_id = match &oidc_config.claims_namespace {
+            Some(claims_namespace) => match token.claims.user_id(claims_namespace) {
+                Some(id) => Some(UserDbId::try_from(id.as_str()).map_err(ApiError::unauthorized)?),
+                None => return Err(ApiError::unauthorized("User ID not found in hasura claims")),
+            },
+            None => None,
         };
-
-        let sub = token.claims.clone().sub;
-        let db_id = match token.claims.clone().user_id() {
-            Some(id) => UserDbId::try_from(id.as_str()).unwrap(),
-            None => return Err("User ID not found in hasura claims".to_string()),
+        let hasura_claims = oidc_config
+            .claims_namespace
+            .as_ref()
+            .and_then(|claims_namespace| token.claims.hasura_claims(claims_namespace));
+
+        // the delegation never outlives the ID token
+        let max_session_duration_ns = self
+            .config_service
+            .get_delegation_config()
+            .max_session_duration_ns(
+                hasura_claims
+                    .as_ref()
+                    .map(|hasura_claims| hasura_claims.x_hasura_default_role.as_str()),
+            );
+        let expiration = match max_session_duration_ns {
+            Some(max_session_duration_ns) => token
+                .claims
+                .expiration_timestamp_ns()
+                .min(unix_timestamp_ns().saturating_add(max_session_duration_ns)),
+            None => token.claims.expiration_timestamp_ns(),
         };
-        let expiration = token.claims.expiration_timestamp_ns();
 
         self.ensure_salt_initialized().await;
 
-        let user_key = self.create_delegation(&sub, session_key, expiration);
-        let user_principal = self.principal_from_sub(&sub);
+        let user_key = self.create_delegation(&issuer, &sub, session_key, expiration)?;
+
+        if let Some(db_id) = db_id {
+            let user_principal = self.principal_from_sub(&issuer, &sub);
+            // the roles are refreshed, as they may have changed since the last sign in
+            let roles = hasura_claims
+                .map(|hasura_claims| {
+                    UserRole::from_hasura_roles(&hasura_claims.x_hasura_allowed_roles)
+                })
+                .unwrap_or_default();
+            match self.user_repository.get_user_by_principal(&user_principal) {
+                Some(user) => self.user_repository.update_user(
+                    user_principal,
+                    User {
+                        roles: Some(roles),
+                        ..user
+                    },
+                )?,
+                None => {
+                    let user = User::new(
+                        self.principal_issuer(&issuer).map(String::from),
+                        sub,
+                        db_id.to_string().as_str(),
+                        roles,
+                    )?;
+                    self.user_repository
+                        .create_user(user_principal, user.clone())?;
+                    self.audit_log_service.record(
+                        session_principal,
+                        AuditLogOperation::CreateUser { user_principal },
+                        &user,
+                    );
+                }
+            }
 
-        let user = User::new(sub, db_id.to_string().as_str()).unwrap();
-        if self
-            .user_repository
-            .get_user_by_principal(&user_principal)
-            .is_none()
-        {
-            self.user_repository
-                .create_user(user_principal, user)
-                .unwrap();
+            // the certificates held by guardians while the user was managed are now theirs
+            let is_claimed = self.managed_user_service.claim_managed_user(
+                db_id,
+                user_principal,
+                MANAGED_USER_CLAIM_BATCH_SIZE,
+            )?;
+            if is_claimed {
+                self.audit_log_service.record(
+                    session_principal,
+                    AuditLogOperation::ClaimManagedUser {
+                        managed_user_id: db_id,
+                        user_principal,
+                    },
+                    &user_principal,
+                );
+            }
         }
 
         Ok(PrepareDelegationResponse {
@@ -145,117 +345,287 @@ impl DelegationService {
         session_principal: Principal,
         jwt: String,
         expiration: Timestamp,
-    ) -> GetDelegationResponse {
-        let (token, session_key) = match self.check_authorization(session_principal, jwt) {
-            Ok(res) => res,
-            Err(e) => {
-                trap(&e);
-            }
-        };
+    ) -> Result<GetDelegationResponse, ApiError> {
+        let (token, session_key, _) = self.check_authorization(session_principal, jwt)?;
 
-        let sub = &token.claims.sub;
-        self.load_delegation(sub, session_key, expiration)
+        self.load_delegation(
+            &token.claims.iss,
+            &token.claims.sub,
+            session_key,
+            expiration,
+        )
     }
 
-    pub fn get_jwks(&self) -> Option<Auth0JWKSet> {
-        self.delegation_repository.get_jwks()
+    /// Revokes the delegation issued to the session key, so that the session can no longer be used
+    /// to sign in, even with a valid ID token.
+    pub fn revoke_session(
+        &self,
+        user_principal: Principal,
+        session_key: SessionKey,
+    ) -> Result<(), ApiError> {
+        let seed = self.get_delegation_seed(&user_principal)?;
+        // the delegations signed before the targets changed can only be revoked all together
+        let targets = self.config_service.get_delegation_config().targets;
+
+        let session_key_hash = hash_bytes(&session_key);
+
+        let revoked_count = self.delegation_repository.revoke_delegations(
+            seed,
+            |key, signature| match signature.session_key_hash {
+                Some(hash) => hash == session_key_hash,
+                None => {
+                    key.message_hash()
+                        == delegation_signature_msg_hash(
+                            &session_key,
+                            key.expiration(),
+                            targets.as_ref(),
+                        )
+                }
+            },
+            revoked_until(),
+        )?;
+        if revoked_count == 0 {
+            return Err(ApiError::not_found("Session not found"));
+        }
+
+        Ok(())
     }
 
-    pub fn set_jwks(&self, jwks: Auth0JWKSet) {
+    /// Revokes all the delegations of the user, logging them out everywhere.
+    pub fn revoke_all_sessions(&self, user_principal: Principal) -> Result<(), ApiError> {
+        let seed = self.get_delegation_seed(&user_principal)?;
+
+        let revoked_count =
+            self.delegation_repository
+                .revoke_delegations(seed, |_, _| true, revoked_until())?;
+        print(format!(
+            "Revoked {} sessions of {}",
+            revoked_count, user_principal
+        ));
+
+        Ok(())
+    }
+
+    pub fn get_jwks(&self, issuer: &str) -> Result<Option<Auth0JWKSet>, ApiError> {
+        let provider = self
+            .oidc_provider_repository
+            .get_provider(issuer)
+            .ok_or_else(|| ApiError::not_found("OIDC provider not found"))?;
+
+        Ok(provider.jwks)
+    }
+
+    pub fn set_jwks(&self, issuer: &str, jwks: Auth0JWKSet) -> Result<(), ApiError> {
         // add an extra layer of security:
         // we can only set the jwks once
-        if self.get_jwks().is_some() {
-            trap("JWKS already set. Call sync_jwks to fetch the JWKS from the auth provider");
+        if self.get_jwks(issuer)?.is_some() {
+            return Err(ApiError::conflict(
+                "JWKS already set. Call sync_jwks to fetch the JWKS from the auth provider",
+            ));
         }
 
-        self.delegation_repository.set_jwks(jwks)
+        self.oidc_provider_repository.set_jwks(issuer, jwks, None)?;
+
+        Ok(())
     }
 
     fn check_authorization(
         &self,
         caller: Principal,
         jwt: String,
-    ) -> Result<(IdToken, SessionKey), String> {
-        let jwks = self.delegation_repository.get_jwks();
-        let token =
-            decode_jwt(&jwt, Algorithm::RS256, jwks.as_ref()).map_err(|e| format!("{:?}", e))?;
-
-        token.claims.validate().map_err(|e| format!("{:?}", e))?;
+    ) -> Result<(IdToken, SessionKey, OidcConfig), ApiError> {
+        let issuer =
+            decode_jwt_issuer(&jwt).map_err(|e| ApiError::unauthorized(format!("{:?}", e)))?;
+        // tokens of unknown providers are rejected before verifying their signature
+        let provider = self
+            .oidc_provider_repository
+            .get_provider(&issuer)
+            .ok_or_else(|| {
+                ApiError::unauthorized(format!("{:?}", ValidationError::IssuerMismatch))
+            })?;
+        let token = decode_jwt(&jwt, provider.jwks.as_ref())
+            .map_err(|e| ApiError::unauthorized(format!("{:?}", e)))?;
+
+        token
+            .claims
+            .validate(&provider.config)
+            .map_err(|e| ApiError::unauthorized(format!("{:?}", e)))?;
 
         let nonce = {
-            let nonce = hex::decode(&token.claims.nonce).map_err(|e| format!("{:?}", e))?;
+            let nonce = hex::decode(&token.claims.nonce)
+                .map_err(|e| ApiError::unauthorized(format!("{:?}", e)))?;
             ByteBuf::from(nonce)
         };
         let token_principal = Principal::self_authenticating(&nonce);
         if caller != token_principal {
-            return Err("caller and token principal mismatch".to_string());
+            return Err(ApiError::unauthorized(
+                "caller and token principal mismatch",
+            ));
+        }
+
+        Ok((token, nonce, provider.config))
+    }
+
+    /// Refreshes the JWKS of the token issuer if it doesn't contain the key that signed the token,
+    /// e.g. because the provider has rotated its keys since the last sync.
+    /// The refresh is rate limited, as anyone can send a token with an unknown key id.
+    async fn refresh_jwks_if_key_unknown(&self, jwt: &str) {
+        let (issuer, key_id) = match (decode_jwt_issuer(jwt), decode_jwt_header(jwt)) {
+            (
+                Ok(issuer),
+                Ok(JwtHeader {
+                    kid: Some(key_id), ..
+                }),
+            ) => (issuer, key_id),
+            _ => return,
+        };
+        let provider = match self.oidc_provider_repository.get_provider(&issuer) {
+            Some(provider) => provider,
+            None => return,
+        };
+        if provider
+            .jwks
+            .is_some_and(|jwks| jwks.find_key(&key_id).is_some())
+        {
+            return;
+        }
+
+        let now = unix_timestamp_ns();
+        if let Some(refreshed_at) = self.oidc_provider_repository.get_jwks_refreshed_at(&issuer) {
+            if now.saturating_sub(refreshed_at) < JWKS_REFRESH_MIN_INTERVAL_NS {
+                return;
+            }
         }
+        self.oidc_provider_repository
+            .set_jwks_refreshed_at(&issuer, now);
+
+        if let Err(e) = self.fetch_and_store_jwks(&issuer).await {
+            print(format!("Failed to refresh JWKS of {}: {}", issuer, e));
+        }
+    }
 
-        Ok((token, nonce))
+    fn get_delegation_seed(&self, user_principal: &Principal) -> Result<Hash, ApiError> {
+        self.delegation_repository
+            .get_delegation_seed(user_principal)
+            .ok_or_else(|| ApiError::not_found("No session found"))
+    }
+
+    fn get_oidc_provider(&self, issuer: &str) -> Result<OidcProvider, String> {
+        self.oidc_provider_repository
+            .get_provider(issuer)
+            .ok_or_else(|| format!("OIDC provider {} not found", issuer))
     }
 
     fn create_delegation(
         &self,
+        issuer: &str,
         user_sub: &UserSub,
         session_key: SessionKey,
         expiration: Timestamp,
-    ) -> ByteBuf {
-        let seed = self.calculate_seed(user_sub);
+    ) -> Result<ByteBuf, ApiError> {
+        let seed = self.calculate_seed(issuer, user_sub);
+        let targets = self.config_service.get_delegation_config().targets;
+
+        let msg_hash = delegation_signature_msg_hash(&session_key, expiration, targets.as_ref());
+        // the ID token is still valid after the session is revoked,
+        // so it must not be possible to sign a delegation for the same session key again
+        self.assert_session_not_revoked(seed, &session_key, msg_hash, expiration)?;
+        self.delegation_repository.add_delegation_signature(
+            seed,
+            hash_bytes(&session_key),
+            msg_hash,
+            expiration,
+        )?;
 
-        let msg_hash = delegation_signature_msg_hash(&session_key, expiration);
+        let public_key = der_encode_canister_sig_key(seed.to_vec());
         self.delegation_repository
-            .add_delegation_signature(&seed, msg_hash);
-
-        self.update_root_hash();
+            .set_delegation_seed(Principal::self_authenticating(&public_key), seed);
 
-        ByteBuf::from(der_encode_canister_sig_key(seed.to_vec()))
-    }
-
-    fn update_root_hash(&self) {
-        let root_hash = self.delegation_repository.get_sigs_root_hash();
-        let prefixed_root_hash = labeled_hash(LABEL_SIG, &root_hash);
-        set_certified_data(&prefixed_root_hash[..]);
+        Ok(ByteBuf::from(public_key))
     }
 
     fn load_delegation(
         &self,
+        issuer: &str,
         user_sub: &UserSub,
         session_key: SessionKey,
         expiration: Timestamp,
-    ) -> GetDelegationResponse {
-        let message_hash = delegation_signature_msg_hash(&session_key, expiration);
-
-        let seed = self.calculate_seed(user_sub);
+    ) -> Result<GetDelegationResponse, ApiError> {
+        let targets = self.config_service.get_delegation_config().targets;
+        let message_hash =
+            delegation_signature_msg_hash(&session_key, expiration, targets.as_ref());
+
+        let seed = self.calculate_seed(issuer, user_sub);
+
+        self.assert_session_not_revoked(seed, &session_key, message_hash, expiration)?;
+
+        Ok(
+            match self
+                .delegation_repository
+                .get_signature(&seed, message_hash)
+            {
+                Ok(signature) => GetDelegationResponse::SignedDelegation(SignedDelegation {
+                    delegation: Delegation {
+                        pubkey: session_key,
+                        expiration,
+                        targets,
+                    },
+                    signature: ByteBuf::from(signature),
+                }),
+                Err(_) => GetDelegationResponse::NoSuchDelegation,
+            },
+        )
+    }
 
-        match self
+    fn assert_session_not_revoked(
+        &self,
+        seed: Hash,
+        session_key: &SessionKey,
+        message_hash: Hash,
+        expiration: Timestamp,
+    ) -> Result<(), ApiError> {
+        if self.delegation_repository.is_session_revoked(
+            seed,
+            hash_bytes(session_key),
+            unix_timestamp_ns(),
+        ) || self
             .delegation_repository
-            .get_signature(&seed, message_hash)
+            .is_delegation_revoked(seed, message_hash, expiration)?
         {
-            Ok(signature) => GetDelegationResponse::SignedDelegation(SignedDelegation {
-                delegation: Delegation {
-                    pubkey: session_key,
-                    expiration,
-                    targets: None,
-                },
-                signature: ByteBuf::from(signature),
-            }),
-            Err(_) => GetDelegationResponse::NoSuchDelegation,
+            return Err(ApiError::unauthorized("Session revoked"));
         }
+
+        Ok(())
     }
 
-    fn principal_from_sub(&self, user_sub: &UserSub) -> Principal {
-        let seed = self.calculate_seed(user_sub);
+    fn principal_from_sub(&self, issuer: &str, user_sub: &UserSub) -> Principal {
+        let seed = self.calculate_seed(issuer, user_sub);
         let public_key = der_encode_canister_sig_key(seed.to_vec());
         Principal::self_authenticating(public_key)
     }
 
-    fn calculate_seed(&self, user_sub: &UserSub) -> Hash {
+    /// The issuer that the principals of its users are derived from,
+    /// i.e. `None` for the legacy OIDC issuer, whose users keep the principals
+    /// they had before multiple OIDC providers were supported.
+    fn principal_issuer<'a>(&self, issuer: &'a str) -> Option<&'a str> {
+        (!self.config_service.is_legacy_oidc_issuer(issuer)).then_some(issuer)
+    }
+
+    /// The issuer is part of the seed, except for the legacy OIDC issuer,
+    /// so that the same `sub` at two different providers results in two different principals.
+    fn calculate_seed(&self, issuer: &str, user_sub: &UserSub) -> Hash {
         let salt = self.delegation_repository.get_salt();
 
         let mut blob: Vec<u8> = vec![];
         blob.push(salt.len() as u8);
         blob.extend_from_slice(&salt);
 
+        if let Some(issuer) = self.principal_issuer(issuer) {
+            let issuer_blob = issuer.bytes();
+            blob.push(issuer_blob.len() as u8);
+            blob.extend(issuer_blob);
+        }
+
         let user_sub_blob = user_sub.bytes();
         blob.push(user_sub_blob.len() as u8);
         blob.extend(user_sub_blob);
@@ -1,15 +1,31 @@
+use candid::Principal;
 use canister_sig_util::{delegation_signature_msg, hash_bytes, CanisterSigPublicKey};
 use ic_cdk::{api::management_canister::main::raw_rand, id, trap};
 use ic_certification::Hash;
 use ssp_backend_types::{PublicKey, Timestamp};
 
-use crate::repositories::Salt;
+use crate::{
+    repositories::{Salt, MAX_IAT_AGE_SECONDS},
+    system_api::{unix_timestamp_ns, NANOS_IN_SECONDS},
+};
 
-pub(super) fn delegation_signature_msg_hash(pubkey: &PublicKey, expiration: Timestamp) -> Hash {
-    let msg = delegation_signature_msg(pubkey, expiration, None);
+pub(super) fn delegation_signature_msg_hash(
+    pubkey: &PublicKey,
+    expiration: Timestamp,
+    targets: Option<&Vec<Principal>>,
+) -> Hash {
+    let targets: Option<Vec<Vec<u8>>> =
+        targets.map(|targets| targets.iter().map(|t| t.as_slice().to_vec()).collect());
+    let msg = delegation_signature_msg(pubkey, expiration, targets.as_ref());
     hash_bytes(msg)
 }
 
+/// The time until which a session revoked now is refused,
+/// i.e. until the ID tokens issued before the revocation are too old to sign it again.
+pub(super) fn revoked_until() -> Timestamp {
+    unix_timestamp_ns().saturating_add(MAX_IAT_AGE_SECONDS * NANOS_IN_SECONDS)
+}
+
 pub(super) fn der_encode_canister_sig_key(seed: Vec<u8>) -> Vec<u8> {
     let my_canister_id = id();
     CanisterSigPublicKey::new(my_canister_id, seed).to_der()
@@ -0,0 +1,201 @@
+use candid::Principal;
+use ic_cdk::println;
+use ssp_backend_types::{
+    ApiError, CommitUploadResponse, CreateUploadRequest, CreateUploadResponse, GetFileChunkRequest,
+    GetFileChunkResponse, GetFileResponse, UploadChunkRequest, ValidateRequest,
+};
+
+use crate::{
+    mappings::{
+        map_commit_upload_response, map_create_upload_response, map_get_file_chunk_response,
+        map_get_file_response,
+    },
+    repositories::{DateTime, File, FileHash, FileRepository, Upload, UploadId},
+    system_api::get_date_time,
+};
+
+/// Uploads that are not committed within this time are discarded.
+const UPLOAD_EXPIRATION: chrono::Duration = chrono::Duration::hours(1);
+/// The uploads in progress are kept in the heap, so each user can only have a few of them.
+const MAX_UPLOADS_PER_OWNER: usize = 3;
+
+#[derive(Default)]
+pub struct FileService {
+    file_repository: FileRepository,
+}
+
+impl FileService {
+    pub async fn create_upload(
+        &self,
+        request: CreateUploadRequest,
+        owner: Principal,
+    ) -> Result<CreateUploadResponse, ApiError> {
+        request.validate()?;
+
+        let upload_id = UploadId::new().await?;
+        self.remove_expired_uploads()?;
+        if self.file_repository.count_uploads_by_owner(&owner) >= MAX_UPLOADS_PER_OWNER {
+            return Err(ApiError::conflict(format!(
+                "User cannot have more than {} uploads in progress.",
+                MAX_UPLOADS_PER_OWNER
+            )));
+        }
+
+        let created_at = DateTime::new(get_date_time()?)?;
+        self.file_repository.create_upload(
+            upload_id,
+            Upload {
+                owner,
+                content_type: request.content_type,
+                size: request.size,
+                chunks: Default::default(),
+                created_at,
+            },
+        );
+
+        Ok(map_create_upload_response(upload_id))
+    }
+
+    pub fn upload_chunk(
+        &self,
+        request: UploadChunkRequest,
+        owner: Principal,
+    ) -> Result<(), ApiError> {
+        request.validate()?;
+
+        let upload_id = UploadId::try_from(request.upload_id.as_str())
+            .map_err(|e| ApiError::validation("upload_id", e))?;
+        let upload = self.get_own_upload(&upload_id, owner)?;
+
+        let previous_chunk_size = upload
+            .chunks
+            .get(&request.index)
+            .map(|chunk| chunk.len() as u64)
+            .unwrap_or_default();
+        let uploaded_size =
+            upload.uploaded_size() - previous_chunk_size + request.content.len() as u64;
+        if uploaded_size > upload.size {
+            return Err(ApiError::validation(
+                "content",
+                format!(
+                    "Uploaded bytes cannot be more than the file size of {} bytes.",
+                    upload.size
+                ),
+            ));
+        }
+
+        self.file_repository.set_upload_chunk(
+            &upload_id,
+            request.index,
+            request.content.into_vec(),
+        )?;
+
+        Ok(())
+    }
+
+    /// Moves the uploaded chunks to the file store.
+    /// If the same file has already been stored, the existing file is reused.
+    pub fn commit_upload(
+        &self,
+        upload_id: String,
+        owner: Principal,
+    ) -> Result<CommitUploadResponse, ApiError> {
+        let upload_id = UploadId::try_from(upload_id.as_str())
+            .map_err(|e| ApiError::validation("upload_id", e))?;
+        let upload = self.get_own_upload(&upload_id, owner)?;
+
+        if upload.uploaded_size() != upload.size {
+            return Err(ApiError::conflict(format!(
+                "Uploaded bytes ({}) do not match the file size of {} bytes.",
+                upload.uploaded_size(),
+                upload.size
+            )));
+        }
+        let chunks = upload.contiguous_chunks().ok_or_else(|| {
+            ApiError::conflict("Uploaded chunks must be contiguous, starting from 0.")
+        })?;
+
+        let file_hash = FileHash::from_content(
+            &chunks
+                .iter()
+                .flat_map(|chunk| chunk.iter().copied())
+                .collect::<Vec<u8>>(),
+        );
+        let file = File {
+            content_type: upload.content_type.clone(),
+            size: upload.size,
+            chunks_count: chunks.len() as u32,
+            created_at: DateTime::new(get_date_time()?)?,
+        };
+
+        self.file_repository.create_file(file_hash, file, chunks)?;
+        self.file_repository.remove_upload(&upload_id);
+
+        println!("Stored file with hash: {}", file_hash.to_string());
+
+        Ok(map_commit_upload_response(file_hash))
+    }
+
+    pub fn get_file(&self, file_hash: String) -> Result<GetFileResponse, ApiError> {
+        let file_hash = FileHash::try_from(file_hash.as_str())
+            .map_err(|e| ApiError::validation("file_hash", e))?;
+        let file = self
+            .file_repository
+            .get_file(&file_hash)
+            .ok_or_else(|| ApiError::not_found("File not found"))?;
+
+        Ok(map_get_file_response(file))
+    }
+
+    pub fn get_file_chunk(
+        &self,
+        request: GetFileChunkRequest,
+    ) -> Result<GetFileChunkResponse, ApiError> {
+        let file_hash = FileHash::try_from(request.file_hash.as_str())
+            .map_err(|e| ApiError::validation("file_hash", e))?;
+        let chunk = self
+            .file_repository
+            .get_file_chunk(file_hash, request.index)?
+            .ok_or_else(|| ApiError::not_found("File chunk not found"))?;
+
+        Ok(map_get_file_chunk_response(chunk))
+    }
+
+    /// Removes the uploads that have not been committed in time.
+    pub fn remove_expired_uploads(&self) -> Result<(), ApiError> {
+        let now = DateTime::new(get_date_time()?)?;
+        let expired_count = self
+            .file_repository
+            .remove_expired_uploads(now.sub(UPLOAD_EXPIRATION));
+        if expired_count > 0 {
+            println!("Removed {} expired uploads", expired_count);
+        }
+
+        Ok(())
+    }
+
+    pub fn assert_file_exists(&self, file_hash: &FileHash) -> Result<(), ApiError> {
+        if !self.file_repository.has_file(file_hash) {
+            return Err(ApiError::not_found(format!(
+                "File {} does not exist",
+                file_hash.to_string()
+            )));
+        }
+
+        Ok(())
+    }
+
+    fn get_own_upload(&self, upload_id: &UploadId, owner: Principal) -> Result<Upload, ApiError> {
+        let upload = self
+            .file_repository
+            .get_upload(upload_id)
+            .ok_or_else(|| ApiError::not_found("Upload not found"))?;
+        if upload.owner != owner {
+            return Err(ApiError::unauthorized(
+                "User can only access their own uploads",
+            ));
+        }
+
+        Ok(upload)
+    }
+}
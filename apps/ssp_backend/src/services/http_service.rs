@@ -0,0 +1,289 @@
+use ic_cdk::id as canister_id;
+use ic_http_certification::{
+    utils::add_v2_certificate_header, HttpCertification, HttpCertificationPath,
+    CERTIFICATE_EXPRESSION_HEADER_NAME,
+};
+use ssp_backend_types::{HttpRequest, HttpResponse};
+
+use crate::{
+    mappings::{map_certificate_html, map_certificate_json, map_certificate_open_badge},
+    repositories::{
+        response_only_cel_expression, url_path, url_path_segments, AchievementRepository,
+        CertificateId, CertificateRepository, HttpCertificationRepository,
+    },
+    utils::{batch_cursor, is_batch_instructions_limit_reached},
+};
+
+const CERTIFICATES_PATH_SEGMENT: &str = "certificates";
+const JSON_EXTENSION: &str = ".json";
+const OPEN_BADGE_EXTENSION: &str = ".open-badge.json";
+
+#[derive(Debug, Clone, Copy)]
+enum CertificateFormat {
+    Html,
+    Json,
+    /// The Open Badges 3.0 credential of the achievement awarded by the certificate.
+    OpenBadge,
+}
+
+impl CertificateFormat {
+    const ALL: [Self; 3] = [Self::Html, Self::Json, Self::OpenBadge];
+
+    /// The url path segments of the certificate in this format,
+    /// i.e. `/certificates/<id>` for HTML, `/certificates/<id>.json` for JSON
+    /// and `/certificates/<id>.open-badge.json` for Open Badges.
+    fn url_path_segments(&self, id: &CertificateId) -> Vec<String> {
+        let file_name = match self {
+            Self::Html => id.to_string(),
+            Self::Json => format!("{}{}", id.to_string(), JSON_EXTENSION),
+            Self::OpenBadge => format!("{}{}", id.to_string(), OPEN_BADGE_EXTENSION),
+        };
+
+        vec![CERTIFICATES_PATH_SEGMENT.to_string(), file_name]
+    }
+
+    /// The path at which the response of the certificate in this format is certified.
+    fn certification_path(&self, id: &CertificateId) -> HttpCertificationPath<'static> {
+        HttpCertificationPath::exact(format!("/{}", self.url_path_segments(id).join("/")))
+    }
+
+    fn content_type(&self) -> &'static str {
+        match self {
+            Self::Html => "text/html; charset=utf-8",
+            Self::Json | Self::OpenBadge => "application/json",
+        }
+    }
+}
+
+/// A response that has been (or will be) certified in the HTTP certification tree.
+struct CertifiedResponse {
+    response: ic_http_certification::HttpResponse,
+    path: HttpCertificationPath<'static>,
+    certification: HttpCertification,
+}
+
+impl CertifiedResponse {
+    fn new(
+        path: HttpCertificationPath<'static>,
+        status_code: u16,
+        content_type: &str,
+        body: Vec<u8>,
+    ) -> Result<Self, String> {
+        let cel_expr = response_only_cel_expression();
+        let response = ic_http_certification::HttpResponse {
+            status_code,
+            headers: vec![
+                ("Content-Type".to_string(), content_type.to_string()),
+                (
+                    CERTIFICATE_EXPRESSION_HEADER_NAME.to_string(),
+                    cel_expr.to_string(),
+                ),
+            ],
+            body,
+            upgrade: None,
+        };
+        let certification = HttpCertification::response_only(&cel_expr, &response, None)
+            .map_err(|e| e.to_string())?;
+
+        Ok(Self {
+            response,
+            path,
+            certification,
+        })
+    }
+
+    /// The response served for all the paths that don't match a public certificate.
+    fn not_found() -> Result<Self, String> {
+        Self::new(
+            HttpCertificationPath::wildcard(""),
+            404,
+            "text/plain; charset=utf-8",
+            b"Certificate not found".to_vec(),
+        )
+    }
+}
+
+#[derive(Default)]
+pub struct HttpService {
+    certificate_repository: CertificateRepository,
+    http_certification_repository: HttpCertificationRepository,
+    achievement_repository: AchievementRepository,
+}
+
+impl HttpService {
+    /// Serves the public certificates as certified HTML, JSON or Open Badges,
+    /// and a certified `404` response for all the other paths.
+    pub fn http_request(&self, request: HttpRequest) -> Result<HttpResponse, String> {
+        let response = match self.parse_certificate_path(&url_path_segments(&request.url)) {
+            Some((id, format)) => self.public_certificate_response(&id, format)?,
+            None => None,
+        };
+        let CertifiedResponse {
+            mut response,
+            path,
+            certification,
+        } = match response {
+            Some(response) => response,
+            None => CertifiedResponse::not_found()?,
+        };
+
+        let ic_certificate = ic_cdk::api::data_certificate()
+            .ok_or_else(|| "No data certificate available".to_string())?;
+        let witness = self.http_certification_repository.witness(
+            &path,
+            &certification,
+            url_path(&request.url),
+        )?;
+        add_v2_certificate_header(
+            &ic_certificate,
+            &mut response,
+            &witness,
+            &path.to_expr_path(),
+        );
+
+        Ok(HttpResponse {
+            status_code: response.status_code,
+            headers: response.headers,
+            body: response.body.into(),
+            upgrade: None,
+        })
+    }
+
+    /// Certifies the HTML, JSON and Open Badges responses of the certificate if it is public,
+    /// otherwise removes them, so that the certificate cannot be served anymore.
+    ///
+    /// Must be called every time the certificate or its sharing settings change.
+    pub fn certify_certificate_responses(&self, id: &CertificateId) -> Result<(), String> {
+        for format in CertificateFormat::ALL {
+            match self.public_certificate_response(id, format)? {
+                Some(response) => self
+                    .http_certification_repository
+                    .certify_response(&response.path, response.certification),
+                None => self
+                    .http_certification_repository
+                    .remove_response(&format.certification_path(id)),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Certifies the responses of up to `limit` shared certificates, starting after the `after` id
+    /// if set, and the `404` response along with the first batch. Returns the id of the last
+    /// processed certificate if there may be more certificates to process.
+    pub fn certify_responses(
+        &self,
+        after: Option<CertificateId>,
+        limit: usize,
+    ) -> Result<Option<CertificateId>, String> {
+        if after.is_none() {
+            let not_found = CertifiedResponse::not_found()?;
+            self.http_certification_repository
+                .certify_response(&not_found.path, not_found.certification);
+        }
+
+        let sharings = self
+            .certificate_repository
+            .get_certificate_sharings(after, limit);
+        let mut processed = Vec::with_capacity(sharings.len());
+        for (id, sharing) in sharings {
+            if sharing.is_public {
+                self.certify_certificate_responses(&id)?;
+            }
+            processed.push(id);
+
+            if is_batch_instructions_limit_reached() {
+                break;
+            }
+        }
+
+        Ok(batch_cursor(&processed, limit, |id| *id))
+    }
+
+    fn parse_certificate_path(
+        &self,
+        url_path_segments: &[String],
+    ) -> Option<(CertificateId, CertificateFormat)> {
+        match url_path_segments {
+            [prefix, file_name] if prefix == CERTIFICATES_PATH_SEGMENT => {
+                let (id, format) = if let Some(id) = file_name.strip_suffix(OPEN_BADGE_EXTENSION) {
+                    (id, CertificateFormat::OpenBadge)
+                } else if let Some(id) = file_name.strip_suffix(JSON_EXTENSION) {
+                    (id, CertificateFormat::Json)
+                } else {
+                    (file_name.as_str(), CertificateFormat::Html)
+                };
+
+                CertificateId::try_from(id)
+                    .ok()
+                    .map(|id| (id, format))
+                    // only the canonical path of the certificate is certified
+                    .filter(|(id, format)| format.url_path_segments(id) == url_path_segments)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the response for the latest version of the certificate,
+    /// or `None` if the certificate does not exist or is not public.
+    ///
+    /// Only the certificates that award an achievement and are not revoked
+    /// have an Open Badges response.
+    fn public_certificate_response(
+        &self,
+        id: &CertificateId,
+        format: CertificateFormat,
+    ) -> Result<Option<CertifiedResponse>, String> {
+        if !self
+            .certificate_repository
+            .get_certificate_sharing(id)
+            .is_public
+        {
+            return Ok(None);
+        }
+
+        let (certificate, version) = match self
+            .certificate_repository
+            .get_latest_certificate_version(id)?
+        {
+            Some(certificate_version) => certificate_version,
+            None => return Ok(None),
+        };
+
+        let body = match format {
+            CertificateFormat::Html => {
+                map_certificate_html(*id, version, &certificate).into_bytes()
+            }
+            CertificateFormat::Json => map_certificate_json(*id, version, &certificate)?,
+            CertificateFormat::OpenBadge => {
+                let (achievement_id, achievement) = match certificate
+                    .achievement_id
+                    .filter(|_| !certificate.is_revoked())
+                    .and_then(|achievement_id| {
+                        self.achievement_repository
+                            .get_achievement(&achievement_id)
+                            .map(|achievement| (achievement_id, achievement))
+                    }) {
+                    Some(achievement) => achievement,
+                    None => return Ok(None),
+                };
+
+                map_certificate_open_badge(
+                    &canister_id(),
+                    *id,
+                    &certificate,
+                    achievement_id,
+                    &achievement,
+                )
+                .into_bytes()
+            }
+        };
+
+        Ok(Some(CertifiedResponse::new(
+            format.certification_path(id),
+            200,
+            format.content_type(),
+            body,
+        )?))
+    }
+}
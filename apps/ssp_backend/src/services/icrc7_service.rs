@@ -0,0 +1,242 @@
+use candid::{Nat, Principal};
+use ic_cdk::id as canister_id;
+use ssp_backend_types::{
+    Account, CollectionMetadata, SupportedStandard, TokenMetadata, TransferArg, TransferError,
+    TransferResult, DEFAULT_ICRC7_TAKE_VALUE, ICRC7_COLLECTION_NAME, ICRC7_COLLECTION_SYMBOL,
+    MAX_ICRC7_QUERY_BATCH_SIZE, MAX_ICRC7_TAKE_VALUE,
+};
+
+use crate::{
+    mappings::{
+        map_certificate_token_id, map_certificate_token_metadata, map_certificate_token_owner,
+        map_collection_metadata, map_token_id_to_certificate_id, nat_to_u64,
+    },
+    repositories::{Certificate, CertificateId, CertificateRepository},
+    services::{CertificateService, ICRC3_STANDARD_URL},
+};
+
+const ICRC7_STANDARD_URL: &str = "https://github.com/dfinity/ICRC/blob/main/ICRCs/ICRC-7/ICRC-7.md";
+const ICRC10_STANDARD_URL: &str =
+    "https://github.com/dfinity/ICRC/blob/main/ICRCs/ICRC-10/ICRC-10.md";
+/// The number of certificate ids of a holder read at once while looking for
+/// the ones that the caller can read.
+const CERTIFICATE_IDS_PAGE_SIZE: usize = 100;
+
+/// Exposes the certificates as soulbound ICRC-7 tokens, owned by their holders.
+///
+/// The tokens are only listed for the callers that can read the certificates
+/// or if the certificates are public, as for the other certificate endpoints.
+#[derive(Default)]
+pub struct Icrc7Service {
+    certificate_repository: CertificateRepository,
+    certificate_service: CertificateService,
+}
+
+impl Icrc7Service {
+    pub fn supported_standards(&self) -> Vec<SupportedStandard> {
+        [
+            ("ICRC-3", ICRC3_STANDARD_URL),
+            ("ICRC-7", ICRC7_STANDARD_URL),
+            ("ICRC-10", ICRC10_STANDARD_URL),
+        ]
+        .into_iter()
+        .map(|(name, url)| SupportedStandard {
+            name: name.to_string(),
+            url: url.to_string(),
+        })
+        .collect()
+    }
+
+    pub fn collection_metadata(&self) -> CollectionMetadata {
+        map_collection_metadata(self.certificate_repository.count_certificates())
+    }
+
+    pub fn name(&self) -> String {
+        ICRC7_COLLECTION_NAME.to_string()
+    }
+
+    pub fn symbol(&self) -> String {
+        ICRC7_COLLECTION_SYMBOL.to_string()
+    }
+
+    /// All the certificates, including the private and the revoked ones.
+    pub fn total_supply(&self) -> Nat {
+        Nat::from(self.certificate_repository.count_certificates())
+    }
+
+    /// Returns the number of tokens held by each account that the caller can read.
+    pub fn balance_of(
+        &self,
+        accounts: Vec<Account>,
+        caller: Principal,
+    ) -> Result<Vec<Nat>, String> {
+        assert_query_batch_size(&accounts)?;
+
+        accounts
+            .iter()
+            .map(|account| {
+                if !account.is_default() {
+                    return Ok(Nat::from(0u64));
+                }
+
+                self.readable_certificate_ids(&account.owner, None, usize::MAX, &caller)
+                    .map(|ids| Nat::from(ids.len()))
+            })
+            .collect()
+    }
+
+    /// Returns the token ids held by the account that the caller can read, in ascending order.
+    /// Certificates are only held by the default account of their holder.
+    pub fn tokens_of(
+        &self,
+        account: Account,
+        prev: Option<Nat>,
+        take: Option<Nat>,
+        caller: Principal,
+    ) -> Result<Vec<Nat>, String> {
+        if !account.is_default() {
+            return Ok(vec![]);
+        }
+
+        let after = match prev {
+            Some(prev) => match map_token_id_to_certificate_id(&prev) {
+                Some(id) => Some(id),
+                // no certificate id is greater than this token id
+                None => return Ok(vec![]),
+            },
+            None => None,
+        };
+        let take = take
+            .map(|take| nat_to_u64(&take))
+            .unwrap_or(DEFAULT_ICRC7_TAKE_VALUE)
+            .min(MAX_ICRC7_TAKE_VALUE);
+
+        let ids = self.readable_certificate_ids(&account.owner, after, take as usize, &caller)?;
+
+        Ok(ids.into_iter().map(map_certificate_token_id).collect())
+    }
+
+    /// The owner is only returned for the tokens that the caller can read,
+    /// the other ones are reported as non-existing.
+    pub fn owner_of(
+        &self,
+        token_ids: Vec<Nat>,
+        caller: Principal,
+    ) -> Result<Vec<Option<Account>>, String> {
+        assert_query_batch_size(&token_ids)?;
+
+        Ok(token_ids
+            .iter()
+            .map(|token_id| {
+                self.get_readable_certificate(token_id, &caller)
+                    .map(|(_, certificate)| map_certificate_token_owner(&certificate))
+            })
+            .collect())
+    }
+
+    /// The metadata is only returned for the tokens that the caller can read.
+    pub fn token_metadata(
+        &self,
+        token_ids: Vec<Nat>,
+        caller: Principal,
+    ) -> Result<Vec<Option<TokenMetadata>>, String> {
+        assert_query_batch_size(&token_ids)?;
+
+        let canister_id = canister_id();
+        Ok(token_ids
+            .iter()
+            .map(|token_id| {
+                self.get_readable_certificate(token_id, &caller)
+                    .map(|(id, certificate)| {
+                        map_certificate_token_metadata(&canister_id, id, certificate)
+                    })
+            })
+            .collect())
+    }
+
+    /// Certificates are soulbound, so every transfer is rejected.
+    pub fn transfer(&self, args: Vec<TransferArg>) -> Vec<Option<TransferResult>> {
+        args.iter()
+            .map(|_| {
+                Some(Err(TransferError::GenericError {
+                    error_code: Nat::from(0u64),
+                    message: "Certificates are soulbound and cannot be transferred".to_string(),
+                }))
+            })
+            .collect()
+    }
+
+    fn get_readable_certificate(
+        &self,
+        token_id: &Nat,
+        caller: &Principal,
+    ) -> Option<(CertificateId, Certificate)> {
+        let id = map_token_id_to_certificate_id(token_id)?;
+
+        self.certificate_repository
+            .get_certificate(&id)
+            .filter(|certificate| self.can_read_certificate(&id, certificate, caller))
+            .map(|certificate| (id, certificate))
+    }
+
+    /// Returns up to `limit` ids of the certificates held by the owner that the caller can read,
+    /// in ascending order and starting after the `after` id if set.
+    fn readable_certificate_ids(
+        &self,
+        owner: &Principal,
+        mut after: Option<CertificateId>,
+        limit: usize,
+        caller: &Principal,
+    ) -> Result<Vec<CertificateId>, String> {
+        let mut readable_ids = vec![];
+        while readable_ids.len() < limit {
+            let page_size = (limit - readable_ids.len()).min(CERTIFICATE_IDS_PAGE_SIZE);
+            let ids = self
+                .certificate_repository
+                .get_certificate_ids_by_user_principal(owner, after, page_size)?;
+            let is_last_page = ids.len() < page_size;
+            after = ids.last().copied();
+
+            // the holders can read all their certificates
+            readable_ids.extend(ids.into_iter().filter(|id| {
+                owner == caller
+                    || self
+                        .certificate_repository
+                        .get_certificate(id)
+                        .is_some_and(|certificate| {
+                            self.can_read_certificate(id, &certificate, caller)
+                        })
+            }));
+            if is_last_page {
+                break;
+            }
+        }
+
+        Ok(readable_ids)
+    }
+
+    fn can_read_certificate(
+        &self,
+        id: &CertificateId,
+        certificate: &Certificate,
+        caller: &Principal,
+    ) -> bool {
+        self.certificate_service
+            .can_read_certificate(certificate, caller)
+            || self
+                .certificate_repository
+                .get_certificate_sharing(id)
+                .is_public
+    }
+}
+
+fn assert_query_batch_size<T>(items: &[T]) -> Result<(), String> {
+    if items.len() > MAX_ICRC7_QUERY_BATCH_SIZE {
+        return Err(format!(
+            "Cannot query more than {} items at once",
+            MAX_ICRC7_QUERY_BATCH_SIZE
+        ));
+    }
+
+    Ok(())
+}
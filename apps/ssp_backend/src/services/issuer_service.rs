@@ -0,0 +1,203 @@
+use candid::Principal;
+use ic_cdk::println;
+use ssp_backend_types::{
+    ApiError, CreateIssuerRequest, CreateIssuerResponse, IssuerMemberRequest, IssuerMemberRole,
+    SetIssuerVerifiedRequest, ValidateRequest,
+};
+
+use crate::{
+    mappings::{map_create_issuer_response, map_issuer},
+    repositories::{
+        CertificateIssuer, DateTime, Issuer, IssuerId, IssuerRepository, UserDbId, UserRepository,
+    },
+    system_api::get_date_time,
+};
+
+#[derive(Default)]
+pub struct IssuerService {
+    issuer_repository: IssuerRepository,
+    user_repository: UserRepository,
+}
+
+impl IssuerService {
+    pub fn get_issuer(&self, id: String) -> Result<ssp_backend_types::Issuer, ApiError> {
+        let id = IssuerId::try_from(id.as_str()).map_err(|e| ApiError::validation("id", e))?;
+
+        self.issuer_repository
+            .get_issuer(&id)
+            .map(|issuer| map_issuer(id, issuer))
+            .ok_or_else(|| ApiError::not_found("Issuer not found"))
+    }
+
+    /// Registers a new issuer, not verified yet, with the creator as its first admin.
+    pub async fn create_issuer(
+        &self,
+        request: CreateIssuerRequest,
+        admin: Principal,
+    ) -> Result<CreateIssuerResponse, ApiError> {
+        request.validate()?;
+
+        let issuer = Issuer::new(
+            request.name.trim().to_string(),
+            admin,
+            DateTime::new(get_date_time()?)?,
+        );
+        let id = self.issuer_repository.create_issuer(issuer).await?;
+
+        println!(
+            "Created issuer with id: {} by admin {}",
+            id.to_string(),
+            admin.to_text()
+        );
+
+        Ok(map_create_issuer_response(id))
+    }
+
+    pub fn set_issuer_verified(&self, request: SetIssuerVerifiedRequest) -> Result<(), ApiError> {
+        let id =
+            IssuerId::try_from(request.id.as_str()).map_err(|e| ApiError::validation("id", e))?;
+        let mut issuer = self.get_existing_issuer(&id)?;
+
+        issuer.verified_at = match (request.verified, issuer.verified_at) {
+            (true, Some(verified_at)) => Some(verified_at),
+            (true, None) => Some(DateTime::new(get_date_time()?)?),
+            (false, _) => None,
+        };
+        self.issuer_repository.update_issuer(id, issuer)?;
+
+        println!(
+            "Set issuer with id: {} verified: {}",
+            id.to_string(),
+            request.verified
+        );
+
+        Ok(())
+    }
+
+    /// Adds the user to the issuer with the role, replacing their previous role if any.
+    /// If `only_admin` is set, it must be an admin of the issuer.
+    pub fn add_issuer_member(
+        &self,
+        request: IssuerMemberRequest,
+        only_admin: Option<Principal>,
+    ) -> Result<(), ApiError> {
+        let (id, mut issuer, member) = self.member_request(&request, only_admin)?;
+
+        issuer.admins.retain(|p| *p != member);
+        issuer.coaches.retain(|p| *p != member);
+        match request.role {
+            IssuerMemberRole::Admin => issuer.admins.push(member),
+            IssuerMemberRole::Coach => issuer.coaches.push(member),
+        }
+        self.issuer_repository.update_issuer(id, issuer)?;
+
+        Ok(())
+    }
+
+    /// Removes the user with the role from the issuer, which must keep at least one admin.
+    /// If `only_admin` is set, it must be an admin of the issuer.
+    ///
+    /// The certificates already issued on behalf of the issuer by the removed member
+    /// keep the issuer recorded at issuance time and stay valid.
+    /// The removed member can still revoke them, but can no longer update them.
+    pub fn remove_issuer_member(
+        &self,
+        request: IssuerMemberRequest,
+        only_admin: Option<Principal>,
+    ) -> Result<(), ApiError> {
+        let (id, mut issuer, member) = self.member_request(&request, only_admin)?;
+
+        let members = match request.role {
+            IssuerMemberRole::Admin => &mut issuer.admins,
+            IssuerMemberRole::Coach => &mut issuer.coaches,
+        };
+        if !members.contains(&member) {
+            return Err(ApiError::not_found("Issuer member not found"));
+        }
+        members.retain(|p| *p != member);
+        if issuer.admins.is_empty() {
+            return Err(ApiError::conflict("Issuer must have at least one admin"));
+        }
+        self.issuer_repository.update_issuer(id, issuer)?;
+
+        Ok(())
+    }
+
+    /// Returns the issuer of a new certificate, with its current verified status.
+    /// If `only_member` is set, it must be an admin or a coach of the issuer.
+    pub fn certificate_issuer(
+        &self,
+        id: &str,
+        only_member: Option<Principal>,
+    ) -> Result<CertificateIssuer, ApiError> {
+        let id = IssuerId::try_from(id).map_err(|e| ApiError::validation("issuer_id", e))?;
+        let issuer = self.get_existing_issuer(&id)?;
+
+        if let Some(p) = only_member {
+            if !issuer.is_member(&p) {
+                return Err(ApiError::unauthorized("User is not a member of the issuer"));
+            }
+        }
+
+        Ok(CertificateIssuer {
+            id,
+            verified: issuer.is_verified(),
+            name: issuer.name,
+        })
+    }
+
+    /// Returns the id of the issuer of a new achievement.
+    /// If `only_admin` is set, it must be an admin of the issuer.
+    pub fn achievement_issuer(
+        &self,
+        id: &str,
+        only_admin: Option<Principal>,
+    ) -> Result<IssuerId, ApiError> {
+        let id = IssuerId::try_from(id).map_err(|e| ApiError::validation("issuer_id", e))?;
+        let issuer = self.get_existing_issuer(&id)?;
+
+        if let Some(p) = only_admin {
+            if !issuer.is_admin(&p) {
+                return Err(ApiError::unauthorized("User is not an admin of the issuer"));
+            }
+        }
+
+        Ok(id)
+    }
+
+    fn get_existing_issuer(&self, id: &IssuerId) -> Result<Issuer, ApiError> {
+        self.issuer_repository
+            .get_issuer(id)
+            .ok_or_else(|| ApiError::not_found("Issuer not found"))
+    }
+
+    fn member_request(
+        &self,
+        request: &IssuerMemberRequest,
+        only_admin: Option<Principal>,
+    ) -> Result<(IssuerId, Issuer, Principal), ApiError> {
+        let id = IssuerId::try_from(request.issuer_id.as_str())
+            .map_err(|e| ApiError::validation("issuer_id", e))?;
+        let user_db_id = UserDbId::try_from(request.user_db_id.as_str())
+            .map_err(|e| ApiError::validation("user_db_id", e))?;
+
+        let issuer = self.get_existing_issuer(&id)?;
+        if let Some(p) = only_admin {
+            if !issuer.is_admin(&p) {
+                return Err(ApiError::unauthorized("User is not an admin of the issuer"));
+            }
+        }
+
+        let (member, _) = self
+            .user_repository
+            .get_user_by_db_id(&user_db_id)
+            .ok_or_else(|| {
+                ApiError::not_found(format!(
+                    "User with database id {} does not exist",
+                    user_db_id.to_string()
+                ))
+            })?;
+
+        Ok((id, issuer, member))
+    }
+}
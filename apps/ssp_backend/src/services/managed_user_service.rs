@@ -0,0 +1,382 @@
+use candid::Principal;
+use ic_cdk::println;
+use ssp_backend_types::{ApiError, ManagedUserGuardianRequest};
+
+use crate::{
+    mappings::map_managed_user,
+    repositories::{
+        CertificateBlockOperation, CertificateId, CertificateRepository, DateTime, ManagedUser,
+        ManagedUserClaim, ManagedUserId, ManagedUserRepository, UserDbId, UserPrincipal,
+        UserRepository,
+    },
+    services::{CertificateBlockService, CertificateCredentialService, HttpService},
+    system_api::get_date_time,
+    utils::{batch_cursor, is_batch_instructions_limit_reached},
+};
+
+#[derive(Default)]
+pub struct ManagedUserService {
+    managed_user_repository: ManagedUserRepository,
+    user_repository: UserRepository,
+    certificate_repository: CertificateRepository,
+    http_service: HttpService,
+    certificate_block_service: CertificateBlockService,
+    certificate_credential_service: CertificateCredentialService,
+}
+
+impl ManagedUserService {
+    pub fn get_managed_users_by_guardian(
+        &self,
+        guardian: UserPrincipal,
+    ) -> Result<Vec<ssp_backend_types::ManagedUser>, ApiError> {
+        Ok(self
+            .managed_user_repository
+            .get_managed_users_by_guardian(guardian)?
+            .into_iter()
+            .map(|(id, managed_user)| map_managed_user(id, managed_user))
+            .collect())
+    }
+
+    pub fn is_guardian(&self, id: &ManagedUserId, principal: &UserPrincipal) -> bool {
+        self.managed_user_repository
+            .get_managed_user(id)
+            .is_some_and(|managed_user| managed_user.is_guardian(principal))
+    }
+
+    /// Checks that the holder of a new certificate of the managed user can be linked as their guardian.
+    /// Only the backend (`is_backend`), which knows who the guardians of a managed user are,
+    /// can create the managed user on their first certificate and link a new guardian,
+    /// so that users cannot claim the database id of someone else.
+    pub fn assert_can_link_certificate_holder(
+        &self,
+        id: &ManagedUserId,
+        holder: &UserPrincipal,
+        is_backend: bool,
+    ) -> Result<(), ApiError> {
+        let managed_user = match self.managed_user_repository.get_managed_user(id) {
+            Some(managed_user) => managed_user,
+            None if !is_backend => return Err(ApiError::not_found("Managed user not found")),
+            None => return Ok(()),
+        };
+
+        if managed_user.is_claimed() {
+            return Err(ApiError::conflict(
+                "Managed user has already claimed their certificates",
+            ));
+        }
+        if !is_backend && !managed_user.is_guardian(holder) {
+            return Err(ApiError::unauthorized(
+                "User is not a guardian of the managed user",
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Links the holder of a new certificate of the managed user as their guardian,
+    /// creating the managed user on their first certificate. Must be called once the certificate
+    /// is stored, in the same message as [ManagedUserService::assert_can_link_certificate_holder].
+    pub fn link_certificate_holder(
+        &self,
+        id: ManagedUserId,
+        holder: UserPrincipal,
+    ) -> Result<(), ApiError> {
+        match self.managed_user_repository.get_managed_user(&id) {
+            Some(mut managed_user) => {
+                if !managed_user.is_guardian(&holder) {
+                    managed_user.guardians.push(holder);
+                    self.managed_user_repository
+                        .update_managed_user(id, managed_user)?;
+                }
+            }
+            None => {
+                let managed_user = ManagedUser::new(holder, DateTime::new(get_date_time()?)?);
+                self.managed_user_repository
+                    .create_managed_user(id, managed_user)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Adds a guardian to the managed user.
+    /// If `only_guardian` is set, it must be a guardian of the managed user.
+    pub fn add_guardian(
+        &self,
+        request: ManagedUserGuardianRequest,
+        only_guardian: Option<Principal>,
+    ) -> Result<(), ApiError> {
+        let (id, mut managed_user, guardian) = self.guardian_request(&request, only_guardian)?;
+
+        if !managed_user.is_guardian(&guardian) {
+            managed_user.guardians.push(guardian);
+            self.managed_user_repository
+                .update_managed_user(id, managed_user)?;
+
+            println!(
+                "Added guardian {} to managed user {}",
+                guardian.to_text(),
+                id.to_string()
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Removes a guardian from the managed user, who must keep at least one guardian.
+    /// If `only_guardian` is set, it must be a guardian of the managed user.
+    pub fn remove_guardian(
+        &self,
+        request: ManagedUserGuardianRequest,
+        only_guardian: Option<Principal>,
+    ) -> Result<(), ApiError> {
+        let (id, mut managed_user, guardian) = self.guardian_request(&request, only_guardian)?;
+
+        if !managed_user.is_guardian(&guardian) {
+            return Err(ApiError::not_found(
+                "User is not a guardian of the managed user",
+            ));
+        }
+        if managed_user.guardians.len() == 1 {
+            return Err(ApiError::conflict(
+                "Managed user must have at least one guardian",
+            ));
+        }
+
+        managed_user.guardians.retain(|p| *p != guardian);
+        self.managed_user_repository
+            .update_managed_user(id, managed_user)?;
+
+        println!(
+            "Removed guardian {} from managed user {}",
+            guardian.to_text(),
+            id.to_string()
+        );
+
+        Ok(())
+    }
+
+    /// Transfers the custody of the managed user's certificates to the user who signed up
+    /// with their database id. The guardians are removed right away, and the certificates are
+    /// transferred to the user's principal in batches of up to `limit` certificates,
+    /// the first one in this message and the rest by [Self::transfer_claimed_certificates].
+    /// Until then, the certificates not transferred yet stay with the guardians who held them.
+    ///
+    /// Returns whether the managed user was claimed, i.e. `false` if there's no managed user to claim.
+    pub fn claim_managed_user(
+        &self,
+        id: ManagedUserId,
+        user_principal: UserPrincipal,
+        limit: usize,
+    ) -> Result<bool, ApiError> {
+        let managed_user = match self.managed_user_repository.get_managed_user(&id) {
+            Some(managed_user) if !managed_user.is_claimed() => managed_user,
+            _ => return Ok(false),
+        };
+
+        self.managed_user_repository.update_managed_user(
+            id,
+            ManagedUser {
+                guardians: vec![],
+                claimed_by: Some(user_principal),
+                claimed_at: Some(DateTime::new(get_date_time()?)?),
+                ..managed_user
+            },
+        )?;
+        let claim = ManagedUserClaim { after: None };
+        self.managed_user_repository
+            .set_managed_user_claim(id, claim.clone());
+
+        println!(
+            "User {} claimed managed user {}",
+            user_principal.to_text(),
+            id.to_string()
+        );
+
+        self.transfer_claim_batch(id, claim, limit)?;
+
+        Ok(true)
+    }
+
+    /// Whether some claimed certificates are still to be transferred.
+    pub fn has_managed_user_claims(&self) -> bool {
+        self.managed_user_repository.has_managed_user_claims()
+    }
+
+    /// Transfers up to `limit` certificates of the claims that are still being transferred.
+    /// Returns whether all the claimed certificates have been transferred.
+    pub fn transfer_claimed_certificates(&self, limit: usize) -> Result<bool, ApiError> {
+        let Some((id, claim)) = self.managed_user_repository.get_first_managed_user_claim() else {
+            return Ok(true);
+        };
+
+        self.transfer_claim_batch(id, claim, limit)?;
+
+        Ok(!self.managed_user_repository.has_managed_user_claims())
+    }
+
+    /// Transfers up to `limit` certificates of the claim, stopping early if the message
+    /// reaches the instructions of a batch, and stores where the next batch starts.
+    fn transfer_claim_batch(
+        &self,
+        id: ManagedUserId,
+        claim: ManagedUserClaim,
+        limit: usize,
+    ) -> Result<(), ApiError> {
+        let managed_user = self
+            .managed_user_repository
+            .get_managed_user(&id)
+            .ok_or_else(|| ApiError::not_found("Managed user not found"))?;
+        let (Some(user_principal), Some(claimed_at)) =
+            (managed_user.claimed_by, managed_user.claimed_at)
+        else {
+            return Err(ApiError::conflict("Managed user has not been claimed"));
+        };
+
+        let certificate_ids = self
+            .certificate_repository
+            .get_certificate_ids_by_managed_user_id(&id, claim.after, limit)?;
+        let mut transferred_ids = Vec::with_capacity(certificate_ids.len());
+        for certificate_id in certificate_ids {
+            self.transfer_certificate(certificate_id, user_principal, claimed_at)?;
+            transferred_ids.push(certificate_id);
+            if is_batch_instructions_limit_reached() {
+                break;
+            }
+        }
+
+        match batch_cursor(&transferred_ids, limit, |certificate_id| *certificate_id) {
+            Some(after) => self
+                .managed_user_repository
+                .set_managed_user_claim(id, ManagedUserClaim { after: Some(after) }),
+            None => {
+                self.managed_user_repository.remove_managed_user_claim(&id);
+                println!(
+                    "Transferred the certificates of managed user {} to user {}",
+                    id.to_string(),
+                    user_principal.to_text()
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    fn transfer_certificate(
+        &self,
+        certificate_id: CertificateId,
+        user_principal: UserPrincipal,
+        claimed_at: DateTime,
+    ) -> Result<(), ApiError> {
+        let previous_certificate = self
+            .certificate_repository
+            .get_certificate(&certificate_id)
+            .ok_or_else(|| ApiError::not_found("Certificate not found"))?;
+        let version = self.certificate_repository.transfer_certificate(
+            certificate_id,
+            user_principal,
+            claimed_at,
+        )?;
+        let certificate = self
+            .certificate_repository
+            .get_certificate(&certificate_id)
+            .ok_or_else(|| ApiError::not_found("Certificate not found"))?;
+        self.certificate_block_service.append_block(
+            CertificateBlockOperation::Transfer {
+                from: previous_certificate.user_principal,
+            },
+            certificate_id,
+            version,
+            &certificate,
+        )?;
+        self.certificate_credential_service
+            .remove_certificate_credential_signature(
+                certificate_id,
+                version - 1,
+                &previous_certificate,
+            );
+        self.certificate_credential_service
+            .sign_certificate_credential(certificate_id, version, &certificate);
+        self.http_service
+            .certify_certificate_responses(&certificate_id)?;
+
+        Ok(())
+    }
+
+    /// Creates the managed users of up to `limit` certificates stored before managed users
+    /// were introduced, starting after the `after` id if set, linking the holders of
+    /// the certificates as guardians. Returns the id of the last processed certificate
+    /// if there may be more certificates to process.
+    pub fn index_managed_users(
+        &self,
+        after: Option<CertificateId>,
+        limit: usize,
+    ) -> Result<Option<CertificateId>, String> {
+        let certificates = self
+            .certificate_repository
+            .get_latest_certificate_versions(after, limit)?;
+
+        let created_at = DateTime::new(get_date_time()?)?;
+        for (_, certificate, _) in certificates.iter() {
+            let (id, holder) = match certificate.managed_user_id {
+                Some(id) => (id, certificate.user_principal),
+                None => continue,
+            };
+
+            match self.managed_user_repository.get_managed_user(&id) {
+                Some(mut managed_user) => {
+                    if !managed_user.is_guardian(&holder) {
+                        managed_user.guardians.push(holder);
+                        self.managed_user_repository
+                            .update_managed_user(id, managed_user)?;
+                    }
+                }
+                None => self
+                    .managed_user_repository
+                    .create_managed_user(id, ManagedUser::new(holder, created_at))?,
+            }
+        }
+
+        Ok(batch_cursor(&certificates, limit, |(id, ..)| *id))
+    }
+
+    fn guardian_request(
+        &self,
+        request: &ManagedUserGuardianRequest,
+        only_guardian: Option<Principal>,
+    ) -> Result<(ManagedUserId, ManagedUser, UserPrincipal), ApiError> {
+        let id = ManagedUserId::try_from(request.managed_user_db_id.as_str())
+            .map_err(|e| ApiError::validation("managed_user_db_id", e))?;
+        let guardian_db_id = UserDbId::try_from(request.guardian_db_id.as_str())
+            .map_err(|e| ApiError::validation("guardian_db_id", e))?;
+
+        let managed_user = self
+            .managed_user_repository
+            .get_managed_user(&id)
+            .ok_or_else(|| ApiError::not_found("Managed user not found"))?;
+        if let Some(p) = only_guardian {
+            if !managed_user.is_guardian(&p) {
+                return Err(ApiError::unauthorized(
+                    "User is not a guardian of the managed user",
+                ));
+            }
+        }
+        if managed_user.is_claimed() {
+            return Err(ApiError::conflict(
+                "Managed user has already claimed their certificates",
+            ));
+        }
+
+        let (guardian, _) = self
+            .user_repository
+            .get_user_by_db_id(&guardian_db_id)
+            .ok_or_else(|| {
+                ApiError::not_found(format!(
+                    "User with database id {} does not exist",
+                    guardian_db_id.to_string()
+                ))
+            })?;
+
+        Ok((id, managed_user, guardian))
+    }
+}
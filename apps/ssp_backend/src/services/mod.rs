@@ -1,11 +1,31 @@
 mod access_control_service;
+mod achievement_service;
+mod audit_log_service;
+mod certificate_block_service;
+mod certificate_credential_service;
 mod certificate_service;
 mod config_service;
 mod delegation_service;
+mod file_service;
+mod http_service;
+mod icrc7_service;
+mod issuer_service;
+mod managed_user_service;
+mod schema_version_service;
 mod user_service;
 
 pub use access_control_service::*;
+pub use achievement_service::*;
+pub use audit_log_service::*;
+pub use certificate_block_service::*;
+pub use certificate_credential_service::*;
 pub use certificate_service::*;
 pub use config_service::*;
 pub use delegation_service::*;
+pub use file_service::*;
+pub use http_service::*;
+pub use icrc7_service::*;
+pub use issuer_service::*;
+pub use managed_user_service::*;
+pub use schema_version_service::*;
 pub use user_service::*;
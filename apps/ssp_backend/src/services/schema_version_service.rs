@@ -0,0 +1,17 @@
+use crate::repositories::{SchemaVersion, SchemaVersionRepository};
+
+#[derive(Default)]
+pub struct SchemaVersionService {
+    schema_version_repository: SchemaVersionRepository,
+}
+
+impl SchemaVersionService {
+    pub fn get_schema_version(&self) -> SchemaVersion {
+        self.schema_version_repository.get_schema_version()
+    }
+
+    pub fn set_schema_version(&self, schema_version: SchemaVersion) -> Result<(), String> {
+        self.schema_version_repository
+            .set_schema_version(schema_version)
+    }
+}
@@ -1,4 +1,4 @@
-use crate::repositories::{User, UserPrincipal, UserRepository};
+use crate::repositories::{User, UserPrincipal, UserRepository, UserSub};
 
 #[derive(Default)]
 pub struct UserService {
@@ -9,4 +9,8 @@ impl UserService {
     pub fn get_user(&self, user_principal: &UserPrincipal) -> Option<User> {
         self.user_repository.get_user_by_principal(user_principal)
     }
+
+    pub fn index_user_subs(&self, after: Option<UserSub>, limit: usize) -> Option<UserSub> {
+        self.user_repository.index_user_subs(after, limit)
+    }
 }
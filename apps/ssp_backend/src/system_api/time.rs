@@ -1,6 +1,7 @@
 pub const NANOS_IN_SECONDS: u64 = 1_000_000_000;
 
-fn unix_timestamp_ns() -> u64 {
+/// Returns the current unix timestamp in nanoseconds
+pub fn unix_timestamp_ns() -> u64 {
     #[cfg(target_family = "wasm")]
     {
         ic_cdk::api::time()
@@ -10,3 +10,37 @@ pub fn cbor_serialize<T: Serialize>(value: &T) -> Result<Vec<u8>, String> {
         .map_err(|e| e.to_string())?;
     Ok(data)
 }
+
+/// The instructions that a batch can use in a single message, well below the limit of a message,
+/// as the items of a batch (e.g. certificates with an inline file of up to 1.5 MB) vary a lot in size.
+const BATCH_INSTRUCTIONS_LIMIT: u64 = 10_000_000_000;
+
+/// Whether the current message has used the instructions of a batch,
+/// in which case the batch stops early and continues in the next message.
+pub fn is_batch_instructions_limit_reached() -> bool {
+    ic_cdk::api::instruction_counter() >= BATCH_INSTRUCTIONS_LIMIT
+}
+
+/// Takes up to `limit` items, stopping early if the message reaches the instructions of a batch
+/// while loading them, e.g. while decoding large certificates. Always takes at least one item.
+pub fn take_batch<T>(items: impl Iterator<Item = T>, limit: usize) -> Vec<T> {
+    let mut batch = Vec::new();
+    for item in items.take(limit) {
+        batch.push(item);
+        if is_batch_instructions_limit_reached() {
+            break;
+        }
+    }
+
+    batch
+}
+
+/// Returns the key of the last item of the batch if the batch is full or was stopped early,
+/// i.e. if there may be more items to process after it.
+pub fn batch_cursor<T, K>(batch: &[T], limit: usize, key: impl Fn(&T) -> K) -> Option<K> {
+    if batch.len() < limit && !is_batch_instructions_limit_reached() {
+        return None;
+    }
+
+    batch.last().map(key)
+}
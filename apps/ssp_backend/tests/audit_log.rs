@@ -0,0 +1,644 @@
+pub mod common;
+
+use candid::{Encode, Principal};
+use common::{
+    auth_provider::{create_jwt, create_jwt_with_roles, initialize_auth_provider, oidc_config},
+    canister::{
+        add_issuer_member, add_managed_user_guardian, await_sync_jwks, commit_upload,
+        create_achievement, create_certificate, create_certificate_share_token, create_issuer,
+        create_upload, get_audit_log, initialize_canister, prepare_delegation,
+        remove_issuer_member, remove_managed_user_guardian, remove_oidc_provider,
+        revoke_all_sessions, revoke_certificate, revoke_certificate_share_token, revoke_session,
+        set_backend_principal, set_certificate_public, set_delegation_config, set_issuer_verified,
+        set_oidc_provider, submit_sync_jwks, update_certificate, upload_chunk,
+    },
+    certification::{assert_ic_certification_is_valid, sha256},
+    fixtures::certificate_request,
+    http_outcalls::reply_http_outcalls,
+    identity::{generate_random_identity, pk_to_hex},
+    test_env::{self, upgrade_canister, TestEnv},
+};
+use ic_agent::Identity;
+use ic_certification::{HashTree, LookupResult};
+use jwt_simple::prelude::*;
+use serde_bytes::ByteBuf;
+use ssp_backend_types::{
+    ApiError, AuditLogEntry, AuditLogOperation, CreateAchievementRequest,
+    CreateCertificateContentRequest, CreateIssuerRequest, CreateUploadRequest, DelegationConfig,
+    GetAuditLogRequest, GetAuditLogResponse, IssuerMemberRequest, IssuerMemberRole,
+    ManagedUserGuardianRequest, OidcConfig, PrepareDelegationResponse, RevokeCertificateRequest,
+    SetCertificatePublicRequest, SetIssuerVerifiedRequest, UpdateCertificateRequest,
+    UploadChunkRequest, MAX_AUDIT_LOG_PAGE_LIMIT,
+};
+
+const TEST_USER_SUB: &str = "test_sub";
+const TEST_USER_DB_ID: &str = "96b51c08-9846-40f2-8f37-a1e4421e2ba8";
+const CLUB_ADMIN_SUB: &str = "club_admin_sub";
+const CLUB_ADMIN_DB_ID: &str = "ccb31f93-1a16-4089-bc84-1822ae591da2";
+const MANAGED_USER_DB_ID: &str = "3f1c7b2e-5d7a-4b39-8c1e-6a2d9f0b4e71";
+
+fn decode_entry(entry_cbor_hex: &str) -> AuditLogEntry {
+    serde_cbor::from_slice(&hex::decode(entry_cbor_hex).unwrap()).unwrap()
+}
+
+/// Verifies that the entries are hash-chained, starting from the genesis hash,
+/// and that the tip and the length of the audit log are certified.
+fn assert_audit_log_is_valid(env: &TestEnv, res: GetAuditLogResponse) -> Vec<AuditLogEntry> {
+    let mut previous_hash = vec![0; 32];
+    let mut entries = vec![];
+    for (index, entry_with_index) in res.entries.iter().enumerate() {
+        assert_eq!(entry_with_index.index, index as u64);
+
+        let entry = decode_entry(&entry_with_index.entry_cbor_hex);
+        assert_eq!(entry.previous_hash, previous_hash);
+        previous_hash = sha256(&hex::decode(&entry_with_index.entry_cbor_hex).unwrap());
+        entries.push(entry);
+    }
+    assert_eq!(res.length, entries.len() as u64);
+    assert_eq!(res.tip_hash, hex::encode(previous_hash.as_slice()));
+
+    assert_ic_certification_is_valid(env, res.ic_certificate, res.ic_certificate_witness.clone());
+    // Audit log tree structure:
+    // audit_log
+    // ├── length
+    // │   └── number of entries (big endian u64)
+    // └── tip
+    //     └── hash of the last entry
+    let tree: HashTree = serde_cbor::from_slice(&res.ic_certificate_witness).unwrap();
+    match tree.lookup_path(vec![b"audit_log".as_slice(), b"tip".as_slice()]) {
+        LookupResult::Found(tip_hash) => assert_eq!(tip_hash, previous_hash.as_slice()),
+        _ => panic!("expected LookupResult::Found"),
+    }
+    match tree.lookup_path(vec![b"audit_log".as_slice(), b"length".as_slice()]) {
+        LookupResult::Found(length) => assert_eq!(length, res.length.to_be_bytes().as_slice()),
+        _ => panic!("expected LookupResult::Found"),
+    }
+
+    entries
+}
+
+fn audit_log_length(env: &TestEnv) -> u64 {
+    get_audit_log(env, env.controller(), GetAuditLogRequest::default())
+        .unwrap()
+        .length
+}
+
+/// Returns the caller and the operation of the last entry of the audit log.
+fn last_operation(env: &TestEnv) -> (Principal, AuditLogOperation) {
+    let res = get_audit_log(
+        env,
+        env.controller(),
+        GetAuditLogRequest {
+            start: Some(audit_log_length(env) - 1),
+            limit: Some(1),
+        },
+    )
+    .unwrap();
+    let entry = decode_entry(&res.entries[0].entry_cbor_hex);
+
+    (entry.caller, entry.operation)
+}
+
+#[test]
+fn test_get_audit_log_controller_only() {
+    let env = test_env::create_test_env();
+
+    let sender = generate_random_identity().sender().unwrap();
+
+    let res = get_audit_log(&env, sender, GetAuditLogRequest::default()).unwrap_err();
+
+    assert_eq!(res, ApiError::unauthorized("Caller is not a controller"));
+}
+
+#[test]
+fn test_get_audit_log_invalid_limit() {
+    let env = test_env::create_test_env();
+
+    let res = get_audit_log(
+        &env,
+        env.controller(),
+        GetAuditLogRequest {
+            start: None,
+            limit: Some(MAX_AUDIT_LOG_PAGE_LIMIT + 1),
+        },
+    )
+    .unwrap_err();
+
+    assert_eq!(
+        res,
+        ApiError::validation(
+            "limit",
+            format!("Limit must be between 1 and {}.", MAX_AUDIT_LOG_PAGE_LIMIT)
+        )
+    );
+}
+
+#[test]
+fn test_audit_log() {
+    let env = test_env::create_test_env();
+
+    // the tip of an empty audit log is the genesis hash
+    let res = get_audit_log(&env, env.controller(), GetAuditLogRequest::default()).unwrap();
+    assert_eq!(res.length, 0);
+    assert_eq!(res.tip_hash, hex::encode([0; 32]));
+
+    let backend_principal = generate_random_identity().sender().unwrap();
+    set_backend_principal(&env, env.controller(), backend_principal).unwrap();
+    let (auth_provider_key_pair, jwks) = initialize_auth_provider();
+    initialize_canister(&env, jwks);
+
+    let session_identity = generate_random_identity();
+    let session_principal = session_identity.sender().unwrap();
+    let (jwt, _) = create_jwt(
+        &auth_provider_key_pair,
+        TEST_USER_SUB,
+        &pk_to_hex(&session_identity.public_key().unwrap()),
+        Some(TEST_USER_DB_ID),
+        Duration::from_hours(10),
+    );
+    let PrepareDelegationResponse { user_key, .. } =
+        prepare_delegation(&env, session_principal, jwt.clone()).unwrap();
+    let user_principal = Principal::self_authenticating(&user_key);
+    // signing in again doesn't create the user again
+    prepare_delegation(&env, session_principal, jwt).unwrap();
+
+    let request = certificate_request(TEST_USER_DB_ID, None);
+    let certificate_id = create_certificate(&env, backend_principal, request.clone())
+        .unwrap()
+        .id;
+
+    let res = get_audit_log(&env, env.controller(), GetAuditLogRequest::default()).unwrap();
+    let entries = assert_audit_log_is_valid(&env, res);
+    let operations: Vec<(Principal, AuditLogOperation)> = entries
+        .iter()
+        .map(|entry| (entry.caller, entry.operation.clone()))
+        .collect();
+    assert_eq!(
+        operations,
+        vec![
+            (env.controller(), AuditLogOperation::SetBackendPrincipal),
+            (
+                env.controller(),
+                AuditLogOperation::SetJwks {
+                    issuer: common::auth_provider::AUTH0_ISSUER.to_string(),
+                }
+            ),
+            (
+                session_principal,
+                AuditLogOperation::CreateUser { user_principal }
+            ),
+            (
+                backend_principal,
+                AuditLogOperation::CreateCertificate { id: certificate_id }
+            ),
+        ]
+    );
+    assert_eq!(
+        entries[0].payload_hash,
+        sha256(&Encode!(&backend_principal).unwrap())
+    );
+    assert_eq!(entries[3].payload_hash, sha256(&Encode!(&request).unwrap()));
+
+    // the log can be paged through
+    let res = get_audit_log(
+        &env,
+        env.controller(),
+        GetAuditLogRequest {
+            start: Some(1),
+            limit: Some(2),
+        },
+    )
+    .unwrap();
+    assert_eq!(res.length, 4);
+    assert_eq!(
+        res.entries
+            .iter()
+            .map(|entry| entry.index)
+            .collect::<Vec<_>>(),
+        vec![1, 2]
+    );
+    assert_eq!(decode_entry(&res.entries[0].entry_cbor_hex), entries[1]);
+
+    // sessions
+    revoke_session(&env, user_principal, ByteBuf::from(session_public_key)).unwrap();
+    assert_recorded(user_principal, AuditLogOperation::RevokeSession);
+
+    revoke_all_sessions(&env, user_principal).unwrap();
+    assert_recorded(user_principal, AuditLogOperation::RevokeAllSessions);
+
+    // failed calls are not recorded
+    set_backend_principal(&env, env.controller(), Principal::anonymous()).unwrap_err();
+
+    // the log and its certified tip survive upgrades
+    upgrade_canister(&env);
+    let res = get_audit_log(&env, env.controller(), GetAuditLogRequest::default()).unwrap();
+    assert_eq!(assert_audit_log_is_valid(&env, res), entries);
+}
+
+#[test]
+fn test_audit_log_records_every_mutating_call() {
+    let env = test_env::create_test_env();
+    let controller = env.controller();
+    let backend_principal = generate_random_identity().sender().unwrap();
+    set_backend_principal(&env, controller, backend_principal).unwrap();
+    let (auth_provider_key_pair, jwks) = initialize_auth_provider();
+    initialize_canister(&env, jwks.clone());
+
+    let mut length = audit_log_length(&env);
+    let mut assert_recorded = |caller: Principal, operation: AuditLogOperation| {
+        let new_length = audit_log_length(&env);
+        assert_eq!(
+            new_length,
+            length + 1,
+            "{:?} is not recorded once",
+            operation
+        );
+        assert_eq!(last_operation(&env), (caller, operation));
+        length = new_length;
+    };
+
+    // config
+    let message_id = submit_sync_jwks(&env, controller);
+    reply_http_outcalls(&env, serde_json::to_vec(&jwks).unwrap());
+    await_sync_jwks(&env, message_id).unwrap();
+    assert_recorded(controller, AuditLogOperation::SyncJwks);
+
+    set_delegation_config(&env, controller, DelegationConfig::default()).unwrap();
+    assert_recorded(controller, AuditLogOperation::SetDelegationConfig);
+
+    let other_issuer = "https://other-tenant.local/";
+    set_oidc_provider(
+        &env,
+        controller,
+        OidcConfig {
+            issuer: other_issuer.to_string(),
+            jwks_url: format!("{other_issuer}.well-known/jwks.json"),
+            ..oidc_config()
+        },
+    )
+    .unwrap();
+    assert_recorded(
+        controller,
+        AuditLogOperation::SetOidcProvider {
+            issuer: other_issuer.to_string(),
+        },
+    );
+
+    remove_oidc_provider(&env, controller, other_issuer).unwrap();
+    assert_recorded(
+        controller,
+        AuditLogOperation::RemoveOidcProvider {
+            issuer: other_issuer.to_string(),
+        },
+    );
+
+    // users
+    let session_identity = generate_random_identity();
+    let session_public_key = session_identity.public_key().unwrap();
+    let (jwt, _) = create_jwt(
+        &auth_provider_key_pair,
+        TEST_USER_SUB,
+        &pk_to_hex(&session_public_key),
+        Some(TEST_USER_DB_ID),
+        Duration::from_hours(10),
+    );
+    let PrepareDelegationResponse { user_key, .. } =
+        prepare_delegation(&env, session_identity.sender().unwrap(), jwt).unwrap();
+    let user_principal = Principal::self_authenticating(&user_key);
+    assert_recorded(
+        session_identity.sender().unwrap(),
+        AuditLogOperation::CreateUser { user_principal },
+    );
+
+    let club_admin_session_identity = generate_random_identity();
+    let (jwt, _) = create_jwt_with_roles(
+        &auth_provider_key_pair,
+        CLUB_ADMIN_SUB,
+        &pk_to_hex(&club_admin_session_identity.public_key().unwrap()),
+        CLUB_ADMIN_DB_ID,
+        &["club_admin"],
+        Duration::from_hours(10),
+    );
+    let PrepareDelegationResponse { user_key, .. } =
+        prepare_delegation(&env, club_admin_session_identity.sender().unwrap(), jwt).unwrap();
+    let club_admin_principal = Principal::self_authenticating(&user_key);
+    assert_recorded(
+        club_admin_session_identity.sender().unwrap(),
+        AuditLogOperation::CreateUser {
+            user_principal: club_admin_principal,
+        },
+    );
+
+    // certificates
+    let certificate_id = create_certificate(
+        &env,
+        backend_principal,
+        certificate_request(TEST_USER_DB_ID, None),
+    )
+    .unwrap()
+    .id;
+    assert_recorded(
+        backend_principal,
+        AuditLogOperation::CreateCertificate {
+            id: certificate_id.clone(),
+        },
+    );
+
+    update_certificate(
+        &env,
+        backend_principal,
+        UpdateCertificateRequest {
+            id: certificate_id.clone(),
+            content: CreateCertificateContentRequest {
+                notes: Some("Updated".to_string()),
+                ..certificate_request(TEST_USER_DB_ID, None).content
+            },
+        },
+    )
+    .unwrap();
+    assert_recorded(
+        backend_principal,
+        AuditLogOperation::UpdateCertificate {
+            id: certificate_id.clone(),
+        },
+    );
+
+    set_certificate_public(
+        &env,
+        user_principal,
+        SetCertificatePublicRequest {
+            id: certificate_id.clone(),
+            is_public: true,
+        },
+    )
+    .unwrap();
+    assert_recorded(
+        user_principal,
+        AuditLogOperation::SetCertificatePublic {
+            id: certificate_id.clone(),
+        },
+    );
+
+    create_certificate_share_token(&env, user_principal, certificate_id.clone()).unwrap();
+    assert_recorded(
+        user_principal,
+        AuditLogOperation::CreateCertificateShareToken {
+            id: certificate_id.clone(),
+        },
+    );
+
+    revoke_certificate_share_token(&env, user_principal, certificate_id.clone()).unwrap();
+    assert_recorded(
+        user_principal,
+        AuditLogOperation::RevokeCertificateShareToken {
+            id: certificate_id.clone(),
+        },
+    );
+
+    revoke_certificate(
+        &env,
+        backend_principal,
+        RevokeCertificateRequest {
+            id: certificate_id.clone(),
+            reason: "Issued by mistake".to_string(),
+        },
+    )
+    .unwrap();
+    assert_recorded(
+        backend_principal,
+        AuditLogOperation::RevokeCertificate { id: certificate_id },
+    );
+
+    // managed users
+    let certificate_id = create_certificate(
+        &env,
+        backend_principal,
+        certificate_request(TEST_USER_DB_ID, Some(MANAGED_USER_DB_ID)),
+    )
+    .unwrap()
+    .id;
+    assert_recorded(
+        backend_principal,
+        AuditLogOperation::CreateCertificate { id: certificate_id },
+    );
+
+    let guardian_request = ManagedUserGuardianRequest {
+        managed_user_db_id: MANAGED_USER_DB_ID.to_string(),
+        guardian_db_id: CLUB_ADMIN_DB_ID.to_string(),
+    };
+    add_managed_user_guardian(&env, user_principal, guardian_request.clone()).unwrap();
+    assert_recorded(
+        user_principal,
+        AuditLogOperation::AddManagedUserGuardian {
+            managed_user_id: MANAGED_USER_DB_ID.to_string(),
+        },
+    );
+
+    remove_managed_user_guardian(&env, backend_principal, guardian_request).unwrap();
+    assert_recorded(
+        backend_principal,
+        AuditLogOperation::RemoveManagedUserGuardian {
+            managed_user_id: MANAGED_USER_DB_ID.to_string(),
+        },
+    );
+
+    // issuers and achievements
+    let issuer_id = create_issuer(
+        &env,
+        club_admin_principal,
+        CreateIssuerRequest {
+            name: "Swimming club".to_string(),
+        },
+    )
+    .unwrap()
+    .id;
+    assert_recorded(
+        club_admin_principal,
+        AuditLogOperation::CreateIssuer {
+            id: issuer_id.clone(),
+        },
+    );
+
+    set_issuer_verified(
+        &env,
+        controller,
+        SetIssuerVerifiedRequest {
+            id: issuer_id.clone(),
+            verified: true,
+        },
+    )
+    .unwrap();
+    assert_recorded(
+        controller,
+        AuditLogOperation::SetIssuerVerified {
+            id: issuer_id.clone(),
+        },
+    );
+
+    let member_request = IssuerMemberRequest {
+        issuer_id: issuer_id.clone(),
+        user_db_id: TEST_USER_DB_ID.to_string(),
+        role: IssuerMemberRole::Coach,
+    };
+    add_issuer_member(&env, club_admin_principal, member_request.clone()).unwrap();
+    assert_recorded(
+        club_admin_principal,
+        AuditLogOperation::AddIssuerMember {
+            issuer_id: issuer_id.clone(),
+        },
+    );
+
+    remove_issuer_member(&env, backend_principal, member_request).unwrap();
+    assert_recorded(
+        backend_principal,
+        AuditLogOperation::RemoveIssuerMember {
+            issuer_id: issuer_id.clone(),
+        },
+    );
+
+    let achievement_id = create_achievement(
+        &env,
+        club_admin_principal,
+        CreateAchievementRequest {
+            name: "Swimming level 1".to_string(),
+            description: "Swims 25 meters without stopping.".to_string(),
+            criteria: "Swim 25 meters freestyle in front of a coach.".to_string(),
+            sport_category: None,
+            issuer_id: Some(issuer_id),
+        },
+    )
+    .unwrap()
+    .id;
+    assert_recorded(
+        club_admin_principal,
+        AuditLogOperation::CreateAchievement { id: achievement_id },
+    );
+
+    // files
+    let upload_id = create_upload(
+        &env,
+        user_principal,
+        CreateUploadRequest {
+            content_type: "image/png".to_string(),
+            size: 5,
+        },
+    )
+    .unwrap()
+    .upload_id;
+    assert_recorded(
+        user_principal,
+        AuditLogOperation::CreateUpload {
+            upload_id: upload_id.clone(),
+        },
+    );
+
+    upload_chunk(
+        &env,
+        user_principal,
+        UploadChunkRequest {
+            upload_id: upload_id.clone(),
+            index: 0,
+            content: ByteBuf::from(b"abcde".to_vec()),
+        },
+    )
+    .unwrap();
+    assert_recorded(
+        user_principal,
+        AuditLogOperation::UploadChunk {
+            upload_id: upload_id.clone(),
+        },
+    );
+
+    let file_hash = commit_upload(&env, user_principal, upload_id.clone())
+        .unwrap()
+        .file_hash;
+    assert_recorded(
+        user_principal,
+        AuditLogOperation::CommitUpload {
+            upload_id,
+            file_hash,
+        },
+    );
+
+    // failed calls are not recorded
+    revoke_all_sessions(&env, Principal::anonymous()).unwrap_err();
+    remove_oidc_provider(&env, controller, "https://unknown-tenant.local/").unwrap_err();
+    assert_eq!(audit_log_length(&env), length);
+
+    let res = get_audit_log(&env, controller, GetAuditLogRequest::default()).unwrap();
+    assert_audit_log_is_valid(&env, res);
+}
+
+#[test]
+fn test_audit_log_records_managed_user_claim() {
+    let env = test_env::create_test_env();
+    let backend_principal = generate_random_identity().sender().unwrap();
+    set_backend_principal(&env, env.controller(), backend_principal).unwrap();
+    let (auth_provider_key_pair, jwks) = initialize_auth_provider();
+    initialize_canister(&env, jwks);
+
+    let sign_in = |sub: &str, db_id: &str| {
+        let session_identity = generate_random_identity();
+        let session_principal = session_identity.sender().unwrap();
+        let (jwt, _) = create_jwt(
+            &auth_provider_key_pair,
+            sub,
+            &pk_to_hex(&session_identity.public_key().unwrap()),
+            Some(db_id),
+            Duration::from_hours(10),
+        );
+        let PrepareDelegationResponse { user_key, .. } =
+            prepare_delegation(&env, session_principal, jwt).unwrap();
+
+        (session_principal, Principal::self_authenticating(&user_key))
+    };
+
+    sign_in(TEST_USER_SUB, TEST_USER_DB_ID);
+    create_certificate(
+        &env,
+        backend_principal,
+        certificate_request(TEST_USER_DB_ID, Some(MANAGED_USER_DB_ID)),
+    )
+    .unwrap();
+
+    // the managed user signs up and claims their certificates
+    let (session_principal, user_principal) = sign_in("managed_user_sub", MANAGED_USER_DB_ID);
+    let length = audit_log_length(&env);
+    let res = get_audit_log(
+        &env,
+        env.controller(),
+        GetAuditLogRequest {
+            start: Some(length - 2),
+            limit: Some(2),
+        },
+    )
+    .unwrap();
+    let operations: Vec<(Principal, AuditLogOperation)> = res
+        .entries
+        .iter()
+        .map(|entry| {
+            let entry = decode_entry(&entry.entry_cbor_hex);
+            (entry.caller, entry.operation)
+        })
+        .collect();
+    assert_eq!(
+        operations,
+        vec![
+            (
+                session_principal,
+                AuditLogOperation::CreateUser { user_principal }
+            ),
+            (
+                session_principal,
+                AuditLogOperation::ClaimManagedUser {
+                    managed_user_id: MANAGED_USER_DB_ID.to_string(),
+                    user_principal,
+                }
+            ),
+        ]
+    );
+
+    // the certificates are not claimed again on the next sign in
+    sign_in("managed_user_sub", MANAGED_USER_DB_ID);
+    assert_eq!(audit_log_length(&env), length);
+}
@@ -3,13 +3,13 @@ pub mod common;
 use candid::Principal;
 use ic_agent::Identity;
 use jwt_simple::prelude::*;
-use ssp_backend_types::{GetDelegationResponse, PrepareDelegationResponse, User};
+use ssp_backend_types::{
+    ApiError, GetDelegationResponse, PrepareDelegationResponse, User, UserRole,
+};
 
 use common::{
-    auth_provider::{create_jwt, initialize_auth_provider},
-    canister::{
-        extract_trap_message, get_delegation, get_my_user, initialize_canister, prepare_delegation,
-    },
+    auth_provider::{create_jwt, create_jwt_with_roles, initialize_auth_provider},
+    canister::{get_delegation, get_my_user, initialize_canister, prepare_delegation},
     date_time::date_time_str_from_canister_time,
     identity::{delegated_identity_from_delegation, generate_random_identity, pk_to_hex},
     test_env::{create_test_env, upgrade_canister},
@@ -30,7 +30,7 @@ fn test_get_my_user_no_user() {
     let identity = generate_random_identity();
     let res = get_my_user(&env, identity.sender().unwrap()).unwrap_err();
 
-    assert!(extract_trap_message(res).contains("No user found"));
+    assert_eq!(res, ApiError::not_found("No user found"));
 }
 
 #[test]
@@ -72,6 +72,8 @@ fn test_get_my_user() {
             sub: TEST_USER_SUB.to_string(),
             created_at: date_time_str_from_canister_time(env.get_canister_time()),
             db_id: TEST_USER_DB_ID.to_string(),
+            // the "user" role is not a known role
+            roles: vec![],
         }
     );
 }
@@ -98,12 +100,12 @@ fn test_get_my_user_wrong_identity() {
 
     // use the session identity to call the method
     let res = get_my_user(&env, session_principal).unwrap_err();
-    assert!(extract_trap_message(res).contains("No user found"));
+    assert_eq!(res, ApiError::not_found("No user found"));
 
     // use another identity to call the method
     let wrong_identity = generate_random_identity();
     let res = get_my_user(&env, wrong_identity.sender().unwrap()).unwrap_err();
-    assert!(extract_trap_message(res).contains("No user found"));
+    assert_eq!(res, ApiError::not_found("No user found"));
 }
 
 #[test]
@@ -127,14 +129,14 @@ fn test_get_my_user_anonymous() {
     get_delegation(&env, session_principal, jwt, expiration).unwrap();
 
     let res = get_my_user(&env, Principal::anonymous()).unwrap_err();
-    assert!(extract_trap_message(res).contains("No user found"));
+    assert_eq!(res, ApiError::not_found("No user found"));
 }
 
 #[test]
 fn test_get_my_user_across_upgrades() {
     let env = create_test_env();
     let (auth_provider_key_pair, jwks) = initialize_auth_provider();
-    initialize_canister(&env, jwks.clone());
+    initialize_canister(&env, jwks);
 
     let session_identity = generate_random_identity();
     let session_principal = session_identity.sender().unwrap();
@@ -164,9 +166,45 @@ fn test_get_my_user_across_upgrades() {
 
     // upgrade the canister
     upgrade_canister(&env);
-    initialize_canister(&env, jwks);
 
     let res_after_upgrade = get_my_user(&env, user_principal).unwrap();
 
     assert_eq!(res_before_upgrade, res_after_upgrade);
 }
+
+#[test]
+fn test_get_my_user_roles_refreshed() {
+    let env = create_test_env();
+    let (auth_provider_key_pair, jwks) = initialize_auth_provider();
+    initialize_canister(&env, jwks);
+
+    let sign_in = |roles: &[&str]| {
+        let session_identity = generate_random_identity();
+        let session_principal = session_identity.sender().unwrap();
+        let (jwt, _) = create_jwt_with_roles(
+            &auth_provider_key_pair,
+            TEST_USER_SUB,
+            &pk_to_hex(&session_identity.public_key().unwrap()),
+            TEST_USER_DB_ID,
+            roles,
+            Duration::from_hours(JWT_VALID_FOR_HOURS),
+        );
+
+        let PrepareDelegationResponse { user_key, .. } =
+            prepare_delegation(&env, session_principal, jwt).unwrap();
+        Principal::self_authenticating(&user_key)
+    };
+
+    let user_principal = sign_in(&["user", "coach", "club_admin"]);
+    let res = get_my_user(&env, user_principal).unwrap();
+    assert_eq!(res.roles, vec![UserRole::Coach, UserRole::ClubAdmin]);
+
+    // the roles are replaced with the ones of the latest ID token
+    let user_principal = sign_in(&["admin"]);
+    let res = get_my_user(&env, user_principal).unwrap();
+    assert_eq!(res.roles, vec![UserRole::Admin]);
+
+    upgrade_canister(&env);
+    let res = get_my_user(&env, user_principal).unwrap();
+    assert_eq!(res.roles, vec![UserRole::Admin]);
+}
@@ -1,29 +1,46 @@
 pub mod common;
 
 use core::str;
-use std::time::{SystemTime, UNIX_EPOCH};
 
 use candid::Principal;
 use common::{
-    auth_provider::{create_jwt, initialize_auth_provider},
+    auth_provider::{create_jwt, create_jwt_with_roles, initialize_auth_provider},
     canister::{
-        create_certificate, extract_trap_message, get_certificate, get_delegation,
-        get_user_certificates, initialize_canister, prepare_delegation, set_backend_principal,
+        add_issuer_member, add_managed_user_guardian, commit_upload, create_achievement,
+        create_certificate, create_certificate_share_token, create_issuer, create_upload,
+        get_achievement, get_certificate, get_certificate_as_vc, get_certificate_version,
+        get_delegation, get_file, get_file_chunk, get_issuer, get_managed_user_certificates,
+        get_my_certificates, get_my_managed_users, get_user_certificates, initialize_canister,
+        prepare_delegation, remove_issuer_member, remove_managed_user_guardian, revoke_certificate,
+        revoke_certificate_share_token, set_backend_principal, set_certificate_public,
+        set_issuer_verified, update_certificate, upload_chunk, verify_certificate,
+    },
+    certification::{
+        assert_http_response_is_valid, assert_ic_certification_is_valid,
+        verify_certificate_credential, verify_delegation,
     },
     identity::{delegated_identity_from_delegation, generate_random_identity, pk_to_hex},
     test_env::{self, upgrade_canister, TestEnv},
 };
 use ic_agent::{hash_tree::SubtreeLookupResult, identity::DelegatedIdentity, Identity};
-use ic_certificate_verification::VerifyCertificate;
-use ic_certification::{
-    leaf_hash, Certificate as IcCertificate, HashTree, HashTreeNode, LookupResult,
-};
+use ic_certification::{leaf_hash, HashTree, HashTreeNode};
 use jwt_simple::prelude::*;
+use ring::digest::{digest, SHA256};
+use serde_bytes::ByteBuf;
 use ssp_backend_types::{
-    Certificate, CertificateWithId, CreateCertificateContentRequest, CreateCertificateRequest,
-    GetDelegationResponse, GetUserCertificatesRequest, PrepareDelegationResponse,
-    MAX_EXTERNAL_ID_CHARS_COUNT, MAX_FILE_BYTES_SIZE, MAX_ISSUER_CLUB_NAME_CHARS_COUNT,
-    MAX_ISSUER_FULL_NAME_CHARS_COUNT, MAX_NAME_CHARS_COUNT, MAX_NOTES_CHARS_COUNT,
+    ApiError, Certificate, CertificateIssuer, CertificateWithId, CertificatesFilters,
+    CertificatesSort, CertificatesSortField, CreateAchievementRequest,
+    CreateCertificateContentRequest, CreateCertificateRequest, CreateIssuerRequest,
+    CreateUploadRequest, CreateUploadResponse, GetCertificateAsVcResponse, GetDelegationResponse,
+    GetFileChunkRequest, GetFileRequest, GetFileResponse, GetManagedUserCertificatesRequest,
+    GetMyCertificatesRequest, GetUserCertificatesRequest, HttpRequest, HttpResponse,
+    IssuerMemberRequest, IssuerMemberRole, ManagedUserGuardianRequest, MyCertificatesList,
+    PrepareDelegationResponse, RevokeCertificateRequest, SetCertificatePublicRequest,
+    SetIssuerVerifiedRequest, SortOrder, UpdateCertificateRequest, UploadChunkRequest,
+    VerifyCertificateRequest, ALLOWED_FILE_CONTENT_TYPES, MAX_CERTIFICATES_PAGE_LIMIT,
+    MAX_EXTERNAL_ID_CHARS_COUNT, MAX_FILE_BYTES_SIZE, MAX_FILE_CHUNK_BYTES_SIZE,
+    MAX_FILE_URI_BYTES_SIZE, MAX_ISSUER_CLUB_NAME_CHARS_COUNT, MAX_ISSUER_FULL_NAME_CHARS_COUNT,
+    MAX_NAME_CHARS_COUNT, MAX_NOTES_CHARS_COUNT, MAX_REVOCATION_REASON_CHARS_COUNT,
     MAX_SPORT_CATEGORY_CHARS_COUNT,
 };
 use uuid::Uuid;
@@ -33,8 +50,6 @@ const JWT_VALID_FOR_HOURS: u64 = 10;
 const TEST_USER_SUB: &str = "test_sub";
 const TEST_USER_DB_ID: &str = "96b51c08-9846-40f2-8f37-a1e4421e2ba8";
 
-const MAX_IC_CERT_TIME_OFFSET_NS: u128 = 300_000_000_000; // 5 min
-
 fn certificate_content_request() -> CreateCertificateContentRequest {
     CreateCertificateContentRequest {
         name: "Test certificate".to_string(),
@@ -45,6 +60,7 @@ fn certificate_content_request() -> CreateCertificateContentRequest {
             "data:image/gif;base64,R0lGODlhAQABAIAAAAAAAP///yH5BAEAAAAALAAAAAABAAEAAAIBRAA7"
                 .to_string(),
         ),
+        file_hash: None,
         external_id: Some("123456".to_string()),
         issuer_full_name: Some("John Doe".to_string()),
         issuer_club_name: Some("Swimming club".to_string()),
@@ -94,6 +110,71 @@ fn create_user(
     delegated_identity_from_delegation(user_key, session_identity, signed_delegation)
 }
 
+/// Prepares a delegation for a new session of the user, without fetching it.
+/// Returns the session principal, the JWT used and the prepare delegation response.
+fn prepare_user_session(
+    env: &TestEnv,
+    auth_provider_key_pair: &RS256KeyPair,
+    user_sub: &str,
+    db_id: &str,
+) -> (Principal, String, PrepareDelegationResponse) {
+    let session_identity = generate_random_identity();
+    let session_principal = session_identity.sender().unwrap();
+    let (jwt, _) = create_jwt(
+        auth_provider_key_pair,
+        user_sub,
+        &pk_to_hex(&session_identity.public_key().unwrap()),
+        Some(db_id),
+        Duration::from_hours(JWT_VALID_FOR_HOURS),
+    );
+
+    let res = prepare_delegation(env, session_principal, jwt.clone()).unwrap();
+
+    (session_principal, jwt, res)
+}
+
+fn assert_user_session_is_valid(
+    env: &TestEnv,
+    session_principal: Principal,
+    jwt: String,
+    prepare_delegation_response: PrepareDelegationResponse,
+) {
+    let PrepareDelegationResponse {
+        expiration,
+        user_key,
+    } = prepare_delegation_response;
+    let signed_delegation = match get_delegation(env, session_principal, jwt, expiration).unwrap() {
+        GetDelegationResponse::SignedDelegation(delegation) => delegation,
+        _ => panic!("expected GetDelegationResponse::SignedDelegation"),
+    };
+
+    verify_delegation(env, user_key, &signed_delegation, env.root_ic_key());
+}
+
+/// Signs in a user with the given Hasura roles. Returns the user principal.
+fn create_user_with_roles(
+    env: &TestEnv,
+    auth_provider_key_pair: &RS256KeyPair,
+    user_sub: &str,
+    db_id: &str,
+    roles: &[&str],
+) -> Principal {
+    let session_identity = generate_random_identity();
+    let (jwt, _) = create_jwt_with_roles(
+        auth_provider_key_pair,
+        user_sub,
+        &pk_to_hex(&session_identity.public_key().unwrap()),
+        db_id,
+        roles,
+        Duration::from_hours(JWT_VALID_FOR_HOURS),
+    );
+
+    let PrepareDelegationResponse { user_key, .. } =
+        prepare_delegation(env, session_identity.sender().unwrap(), jwt).unwrap();
+
+    Principal::self_authenticating(&user_key)
+}
+
 fn setup_user(env: &TestEnv, user_sub: &str, db_id: &str) -> DelegatedIdentity {
     let (auth_provider_key_pair, jwks) = initialize_auth_provider();
     initialize_canister(env, jwks);
@@ -113,11 +194,16 @@ fn test_create_certificate_anonymous() {
         user_db_id: TEST_USER_DB_ID.to_string(),
         content: certificate_content_request(),
         managed_user_db_id: None,
+        issuer_id: None,
+        achievement_id: None,
     };
 
     let res = create_certificate(&env, sender, request).unwrap_err();
 
-    assert!(extract_trap_message(res).contains("Caller is not the backend or a registered user"));
+    assert_eq!(
+        res,
+        ApiError::unauthorized("Caller is not the backend or a registered user")
+    );
 }
 
 #[test]
@@ -132,6 +218,8 @@ fn test_create_certificate_user() {
         user_db_id: TEST_USER_DB_ID.to_string(),
         content: certificate_content_request(),
         managed_user_db_id: None,
+        issuer_id: None,
+        achievement_id: None,
     };
 
     let res = create_certificate(&env, user_principal, request).unwrap();
@@ -146,6 +234,8 @@ fn test_create_certificate_user() {
         user_db_id: "83163621-9085-4e12-88bb-0b9aee290420".to_string(),
         content: certificate_content_request(),
         managed_user_db_id: None,
+        issuer_id: None,
+        achievement_id: None,
     };
     let res_another_user = create_certificate(&env, user_principal, request).unwrap();
     let res_certificate_another_user =
@@ -172,6 +262,8 @@ fn test_create_certificate() {
         user_db_id: TEST_USER_DB_ID.to_string(),
         content: certificate_content_request(),
         managed_user_db_id: None,
+        issuer_id: None,
+        achievement_id: None,
     };
 
     let res = create_certificate(&env, backend_principal, request).unwrap();
@@ -191,624 +283,4030 @@ fn test_create_certificate_no_user() {
         user_db_id: TEST_USER_DB_ID.to_string(),
         content: certificate_content_request(),
         managed_user_db_id: None,
+        issuer_id: None,
+        achievement_id: None,
     };
 
     let res = create_certificate(&env, backend_principal, request).unwrap_err();
 
-    assert!(extract_trap_message(res).contains(&format!(
-        "User with database id {TEST_USER_DB_ID} does not exist"
-    )));
+    assert_eq!(
+        res,
+        ApiError::not_found(format!(
+            "User with database id {TEST_USER_DB_ID} does not exist"
+        ))
+    );
 }
 
 #[test]
-fn test_create_certificate_invalid_request() {
+fn test_create_certificate_coach() {
     let env = test_env::create_test_env();
-    let backend_principal = setup_config(&env);
-    setup_user(&env, TEST_USER_SUB, TEST_USER_DB_ID);
+    setup_config(&env);
+    let (auth_provider_key_pair, jwks) = initialize_auth_provider();
+    initialize_canister(&env, jwks);
+    let athlete_principal = create_user_with_roles(
+        &env,
+        &auth_provider_key_pair,
+        TEST_USER_SUB,
+        TEST_USER_DB_ID,
+        &["athlete"],
+    );
+    let coach_db_id = "ccb31f93-1a16-4089-bc84-1822ae591da2";
+    let coach_principal = create_user_with_roles(
+        &env,
+        &auth_provider_key_pair,
+        "test_coach_sub",
+        coach_db_id,
+        &["coach"],
+    );
 
-    // empty name
+    // the coach issues the certificate to the athlete
     let request = CreateCertificateRequest {
         user_db_id: TEST_USER_DB_ID.to_string(),
-        content: CreateCertificateContentRequest {
-            name: "".to_string(),
-            ..certificate_content_request()
-        },
+        content: certificate_content_request(),
         managed_user_db_id: None,
+        issuer_id: None,
+        achievement_id: None,
     };
-    let res = create_certificate(&env, backend_principal, request).unwrap_err();
-    assert!(extract_trap_message(res).contains("Title cannot be empty."));
+    let res = create_certificate(&env, coach_principal, request).unwrap();
+    let res_certificate = get_certificate(&env, athlete_principal, res.id).unwrap();
+    let certificate = decode_certificate(&res_certificate.certificate.certificate_cbor_hex);
+    assert_eq!(certificate.user_principal, athlete_principal);
+    assert_eq!(certificate.issuer_principal, Some(coach_principal));
 
-    // too long name
+    // the athlete can only issue certificates to themselves
     let request = CreateCertificateRequest {
-        user_db_id: TEST_USER_DB_ID.to_string(),
-        content: CreateCertificateContentRequest {
-            name: "a".repeat(MAX_NAME_CHARS_COUNT + 1),
-            ..certificate_content_request()
-        },
+        user_db_id: coach_db_id.to_string(),
+        content: certificate_content_request(),
         managed_user_db_id: None,
+        issuer_id: None,
+        achievement_id: None,
     };
-    let res = create_certificate(&env, backend_principal, request).unwrap_err();
-    assert!(extract_trap_message(res).contains(&format!(
-        "Title cannot be longer than {} characters.",
-        MAX_NAME_CHARS_COUNT
-    )));
+    let res = create_certificate(&env, athlete_principal, request).unwrap();
+    let res_certificate = get_certificate(&env, athlete_principal, res.id).unwrap();
+    let certificate = decode_certificate(&res_certificate.certificate.certificate_cbor_hex);
+    assert_eq!(certificate.user_principal, athlete_principal);
+    assert_eq!(certificate.issuer_principal, None);
 
-    // empty sport category
+    // the recipient must exist
     let request = CreateCertificateRequest {
-        user_db_id: TEST_USER_DB_ID.to_string(),
-        content: CreateCertificateContentRequest {
-            sport_category: "".to_string(),
-            ..certificate_content_request()
-        },
+        user_db_id: "83163621-9085-4e12-88bb-0b9aee290420".to_string(),
+        content: certificate_content_request(),
         managed_user_db_id: None,
+        issuer_id: None,
+        achievement_id: None,
     };
-    let res = create_certificate(&env, backend_principal, request).unwrap_err();
-    assert!(extract_trap_message(res).contains("Sport category cannot be empty."));
+    let res = create_certificate(&env, coach_principal, request).unwrap_err();
+    assert_eq!(
+        res,
+        ApiError::not_found(
+            "User with database id 83163621-9085-4e12-88bb-0b9aee290420 does not exist"
+        )
+    );
+}
 
-    // too long sport category
-    let request = CreateCertificateRequest {
-        user_db_id: TEST_USER_DB_ID.to_string(),
-        content: CreateCertificateContentRequest {
-            sport_category: "a".repeat(MAX_SPORT_CATEGORY_CHARS_COUNT + 1),
-            ..certificate_content_request()
-        },
-        managed_user_db_id: None,
-    };
-    let res = create_certificate(&env, backend_principal, request).unwrap_err();
-    assert!(extract_trap_message(res).contains(&format!(
-        "Sport category cannot be longer than {} characters.",
-        MAX_SPORT_CATEGORY_CHARS_COUNT
-    )));
+#[test]
+fn test_get_my_certificates_issued_and_received() {
+    let env = test_env::create_test_env();
+    let backend_principal = setup_config(&env);
+    let (auth_provider_key_pair, jwks) = initialize_auth_provider();
+    initialize_canister(&env, jwks);
+    let athlete_principal = create_user_with_roles(
+        &env,
+        &auth_provider_key_pair,
+        TEST_USER_SUB,
+        TEST_USER_DB_ID,
+        &["athlete"],
+    );
+    let coach_principal = create_user_with_roles(
+        &env,
+        &auth_provider_key_pair,
+        "test_coach_sub",
+        "ccb31f93-1a16-4089-bc84-1822ae591da2",
+        &["coach"],
+    );
+    let other_athlete_principal = create_user_with_roles(
+        &env,
+        &auth_provider_key_pair,
+        "test_other_athlete_sub",
+        "83163621-9085-4e12-88bb-0b9aee290420",
+        &["athlete"],
+    );
 
-    // too long notes
-    let request = CreateCertificateRequest {
-        user_db_id: TEST_USER_DB_ID.to_string(),
-        content: CreateCertificateContentRequest {
-            notes: Some("a".repeat(MAX_NOTES_CHARS_COUNT + 1)),
-            ..certificate_content_request()
+    let issued_certificate_id = create_certificate(
+        &env,
+        coach_principal,
+        CreateCertificateRequest {
+            user_db_id: TEST_USER_DB_ID.to_string(),
+            content: certificate_content_request(),
+            managed_user_db_id: None,
+            issuer_id: None,
+            achievement_id: None,
         },
-        managed_user_db_id: None,
-    };
-    let res = create_certificate(&env, backend_principal, request).unwrap_err();
-    assert!(extract_trap_message(res).contains(&format!(
-        "Notes cannot be longer than {} characters.",
-        MAX_NOTES_CHARS_COUNT
-    )));
+    )
+    .unwrap()
+    .id;
+    let (backend_certificate_id, _) =
+        create_test_certificate(&env, backend_principal, TEST_USER_DB_ID.to_string());
 
-    // too long file bytes
-    let request = CreateCertificateRequest {
-        user_db_id: TEST_USER_DB_ID.to_string(),
-        content: CreateCertificateContentRequest {
-            file_uri: Some(
-                str::from_utf8(&vec![1; MAX_FILE_BYTES_SIZE + 1])
-                    .unwrap()
-                    .to_string(),
-            ),
-            ..certificate_content_request()
-        },
-        managed_user_db_id: None,
+    let list_ids = |principal: Principal, list: MyCertificatesList| {
+        let request = GetMyCertificatesRequest {
+            list,
+            ..Default::default()
+        };
+        get_my_certificates(&env, principal, request)
+            .unwrap()
+            .certificates
+            .into_iter()
+            .map(|c| c.id)
+            .collect::<Vec<_>>()
     };
-    let res = create_certificate(&env, backend_principal, request).unwrap_err();
-    assert!(extract_trap_message(res).contains(&format!(
-        "File bytes cannot be longer than {} bytes.",
-        MAX_FILE_BYTES_SIZE
-    )));
 
-    // too long external id
-    let request = CreateCertificateRequest {
-        user_db_id: TEST_USER_DB_ID.to_string(),
-        content: CreateCertificateContentRequest {
-            external_id: Some("a".repeat(MAX_EXTERNAL_ID_CHARS_COUNT + 1)),
-            ..certificate_content_request()
-        },
-        managed_user_db_id: None,
-    };
-    let res = create_certificate(&env, backend_principal, request).unwrap_err();
-    assert!(extract_trap_message(res).contains(&format!(
-        "External ID cannot be longer than {} characters.",
-        MAX_EXTERNAL_ID_CHARS_COUNT
-    )));
+    assert_eq!(
+        list_ids(athlete_principal, MyCertificatesList::Received),
+        vec![
+            backend_certificate_id.clone(),
+            issued_certificate_id.clone()
+        ]
+    );
+    assert!(list_ids(athlete_principal, MyCertificatesList::Issued).is_empty());
+    assert_eq!(
+        list_ids(coach_principal, MyCertificatesList::Issued),
+        vec![issued_certificate_id.clone()]
+    );
+    assert!(list_ids(coach_principal, MyCertificatesList::Received).is_empty());
 
-    // too long issuer full name
-    let request = CreateCertificateRequest {
-        user_db_id: TEST_USER_DB_ID.to_string(),
-        content: CreateCertificateContentRequest {
-            issuer_full_name: Some("a".repeat(MAX_ISSUER_FULL_NAME_CHARS_COUNT + 1)),
-            ..certificate_content_request()
+    // the issuer can read the certificates they issued, but not the other ones of the athlete
+    let res = get_certificate(&env, coach_principal, issued_certificate_id.clone()).unwrap();
+    assert_eq!(
+        decode_certificate(&res.certificate.certificate_cbor_hex).issuer_principal,
+        Some(coach_principal)
+    );
+    let res = get_certificate(&env, coach_principal, backend_certificate_id).unwrap_err();
+    assert_eq!(
+        res,
+        ApiError::unauthorized("User can only access their own certificates")
+    );
+    let res = get_certificate(&env, other_athlete_principal, issued_certificate_id).unwrap_err();
+    assert_eq!(
+        res,
+        ApiError::unauthorized("User can only access their own certificates")
+    );
+
+    // the issued list survives upgrades
+    upgrade_canister(&env);
+    assert_eq!(
+        list_ids(coach_principal, MyCertificatesList::Issued).len(),
+        1
+    );
+
+    // only users have their own certificates
+    let res = get_my_certificates(&env, backend_principal, GetMyCertificatesRequest::default())
+        .unwrap_err();
+    assert_eq!(
+        res,
+        ApiError::unauthorized(format!(
+            "Caller {} is not a user",
+            backend_principal.to_text()
+        ))
+    );
+}
+
+#[test]
+fn test_registered_issuer() {
+    let env = test_env::create_test_env();
+    setup_config(&env);
+    let (auth_provider_key_pair, jwks) = initialize_auth_provider();
+    initialize_canister(&env, jwks);
+    create_user_with_roles(
+        &env,
+        &auth_provider_key_pair,
+        TEST_USER_SUB,
+        TEST_USER_DB_ID,
+        &["athlete"],
+    );
+    let club_admin_principal = create_user_with_roles(
+        &env,
+        &auth_provider_key_pair,
+        "test_club_admin_sub",
+        "ccb31f93-1a16-4089-bc84-1822ae591da2",
+        &["club_admin"],
+    );
+    let coach_db_id = "83163621-9085-4e12-88bb-0b9aee290420";
+    let coach_principal = create_user_with_roles(
+        &env,
+        &auth_provider_key_pair,
+        "test_coach_sub",
+        coach_db_id,
+        &["coach"],
+    );
+
+    // only club admins can register issuers
+    let res = create_issuer(
+        &env,
+        coach_principal,
+        CreateIssuerRequest {
+            name: "Swimming club".to_string(),
         },
-        managed_user_db_id: None,
-    };
-    let res = create_certificate(&env, backend_principal, request).unwrap_err();
-    assert!(extract_trap_message(res).contains(&format!(
-        "Issuer full name cannot be longer than {} characters.",
-        MAX_ISSUER_FULL_NAME_CHARS_COUNT
-    )));
+    )
+    .unwrap_err();
+    assert_eq!(
+        res,
+        ApiError::unauthorized(format!(
+            "Caller {} does not have the required role",
+            coach_principal.to_text()
+        ))
+    );
+    let res = create_issuer(
+        &env,
+        club_admin_principal,
+        CreateIssuerRequest {
+            name: "".to_string(),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(res, ApiError::validation("name", "Name cannot be empty."));
 
-    // too long issuer club name
-    let request = CreateCertificateRequest {
-        user_db_id: TEST_USER_DB_ID.to_string(),
-        content: CreateCertificateContentRequest {
-            issuer_club_name: Some("a".repeat(MAX_ISSUER_CLUB_NAME_CHARS_COUNT + 1)),
-            ..certificate_content_request()
+    let issuer_id = create_issuer(
+        &env,
+        club_admin_principal,
+        CreateIssuerRequest {
+            name: "Swimming club".to_string(),
         },
-        managed_user_db_id: None,
-    };
-    let res = create_certificate(&env, backend_principal, request).unwrap_err();
-    assert!(extract_trap_message(res).contains(&format!(
-        "Issuer club name cannot be longer than {} characters.",
-        MAX_ISSUER_CLUB_NAME_CHARS_COUNT
-    )));
-}
+    )
+    .unwrap()
+    .id;
+    let issuer = get_issuer(&env, Principal::anonymous(), issuer_id.clone()).unwrap();
+    assert_eq!(issuer.name, "Swimming club");
+    assert_eq!(issuer.admins, vec![club_admin_principal]);
+    assert!(issuer.coaches.is_empty());
+    assert_eq!(issuer.verified_at, None);
 
-fn create_test_certificate(
-    env: &TestEnv,
-    backend_principal: Principal,
-    user_db_id: String,
-) -> (String, String) {
-    let content = certificate_content_request();
-    let request = CreateCertificateRequest {
-        user_db_id,
-        content: content.clone(),
-        managed_user_db_id: None,
+    let issue = |issuer_id: &str| {
+        create_certificate(
+            &env,
+            coach_principal,
+            CreateCertificateRequest {
+                user_db_id: TEST_USER_DB_ID.to_string(),
+                content: certificate_content_request(),
+                managed_user_db_id: None,
+                issuer_id: Some(issuer_id.to_string()),
+                achievement_id: None,
+            },
+        )
     };
 
-    let res = create_certificate(env, backend_principal, request).unwrap();
-    (res.id, content.name)
-}
+    // only members of the issuer can issue certificates on its behalf
+    let res = issue(&issuer_id).unwrap_err();
+    assert_eq!(
+        res,
+        ApiError::unauthorized("User is not a member of the issuer")
+    );
+    let res = issue(&Uuid::new_v4().to_string()).unwrap_err();
+    assert_eq!(res, ApiError::not_found("Issuer not found"));
 
-fn create_test_certificate_for_managed_user(
-    env: &TestEnv,
-    backend_principal: Principal,
-    user_db_id: String,
-    managed_user_db_id: String,
-) -> (String, String) {
-    let content = certificate_content_request();
-    let request = CreateCertificateRequest {
-        user_db_id,
-        content: content.clone(),
-        managed_user_db_id: Some(managed_user_db_id),
+    let member_request = IssuerMemberRequest {
+        issuer_id: issuer_id.clone(),
+        user_db_id: coach_db_id.to_string(),
+        role: IssuerMemberRole::Coach,
     };
+    let res = add_issuer_member(&env, coach_principal, member_request.clone()).unwrap_err();
+    assert_eq!(
+        res,
+        ApiError::unauthorized("User is not an admin of the issuer")
+    );
+    add_issuer_member(&env, club_admin_principal, member_request.clone()).unwrap();
+    let issuer = get_issuer(&env, Principal::anonymous(), issuer_id.clone()).unwrap();
+    assert_eq!(issuer.coaches, vec![coach_principal]);
 
-    let res = create_certificate(env, backend_principal, request).unwrap();
-    (res.id, content.name)
-}
+    // certificates issued before verification record the issuer as not verified
+    let unverified_certificate_id = issue(&issuer_id).unwrap().id;
 
-#[test]
-fn test_get_user_certificates_invalid_request() {
-    let env = test_env::create_test_env();
-    let user_identity = setup_user(&env, TEST_USER_SUB, TEST_USER_DB_ID);
-    let user_principal = user_identity.sender().unwrap();
+    // only controllers can verify issuers
+    let verify_request = SetIssuerVerifiedRequest {
+        id: issuer_id.clone(),
+        verified: true,
+    };
+    let res = set_issuer_verified(&env, club_admin_principal, verify_request.clone()).unwrap_err();
+    assert_eq!(res, ApiError::unauthorized("Caller is not a controller"));
+    set_issuer_verified(&env, env.controller(), verify_request).unwrap();
+    let issuer = get_issuer(&env, Principal::anonymous(), issuer_id.clone()).unwrap();
+    assert!(issuer.verified_at.is_some());
 
-    // empty request
-    let request = GetUserCertificatesRequest {
-        user_principal: None,
-        user_db_id: None,
+    let verified_certificate_id = issue(&issuer_id).unwrap().id;
+
+    // the verified status at issuance time is part of the certified certificate
+    let res = get_certificate(&env, coach_principal, unverified_certificate_id).unwrap();
+    let certificate = decode_certificate(&res.certificate.certificate_cbor_hex);
+    assert_eq!(
+        certificate.issuer,
+        Some(CertificateIssuer {
+            id: issuer_id.clone(),
+            name: "Swimming club".to_string(),
+            verified: false,
+        })
+    );
+    let res = get_certificate(&env, coach_principal, verified_certificate_id.clone()).unwrap();
+    let certificate = decode_certificate(&res.certificate.certificate_cbor_hex);
+    assert_eq!(
+        certificate.issuer,
+        Some(CertificateIssuer {
+            id: issuer_id.clone(),
+            name: "Swimming club".to_string(),
+            verified: true,
+        })
+    );
+
+    // the issuer keeps the status recorded at issuance time, and survives upgrades
+    upgrade_canister(&env);
+    set_issuer_verified(
+        &env,
+        env.controller(),
+        SetIssuerVerifiedRequest {
+            id: issuer_id.clone(),
+            verified: false,
+        },
+    )
+    .unwrap();
+    let res = get_certificate(&env, coach_principal, verified_certificate_id.clone()).unwrap();
+    assert!(
+        decode_certificate(&res.certificate.certificate_cbor_hex)
+            .issuer
+            .unwrap()
+            .verified
+    );
+
+    // updates record the issuer's current status
+    let update = || {
+        update_certificate(
+            &env,
+            coach_principal,
+            UpdateCertificateRequest {
+                id: verified_certificate_id.clone(),
+                content: updated_certificate_content_request(),
+            },
+        )
     };
-    let res = get_user_certificates(&env, user_principal, request).unwrap_err();
+    update().unwrap();
+    let res = get_certificate(&env, coach_principal, verified_certificate_id.clone()).unwrap();
     assert!(
-        extract_trap_message(res).contains("Either user_principal or user_db_id must be provided.")
+        !decode_certificate(&res.certificate.certificate_cbor_hex)
+            .issuer
+            .unwrap()
+            .verified
     );
 
-    // both user_principal and user_db_id are provided
-    let request = GetUserCertificatesRequest {
-        user_principal: Some(user_principal),
-        user_db_id: Some(TEST_USER_DB_ID.to_string()),
+    // removed coaches can no longer issue or update certificates on behalf of the issuer
+    remove_issuer_member(&env, club_admin_principal, member_request).unwrap();
+    let res = issue(&issuer_id).unwrap_err();
+    assert_eq!(
+        res,
+        ApiError::unauthorized("User is not a member of the issuer")
+    );
+    let res = update().unwrap_err();
+    assert_eq!(
+        res,
+        ApiError::unauthorized("User is not a member of the issuer")
+    );
+
+    // the issuer cannot lose its last admin
+    let res = remove_issuer_member(
+        &env,
+        club_admin_principal,
+        IssuerMemberRequest {
+            issuer_id,
+            user_db_id: "ccb31f93-1a16-4089-bc84-1822ae591da2".to_string(),
+            role: IssuerMemberRole::Admin,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        res,
+        ApiError::conflict("Issuer must have at least one admin")
+    );
+}
+
+fn achievement_request(issuer_id: Option<&str>) -> CreateAchievementRequest {
+    CreateAchievementRequest {
+        name: "Swimming level 1".to_string(),
+        description: "Swims 25 meters without stopping.".to_string(),
+        criteria: "Swim 25 meters freestyle in front of a coach.".to_string(),
+        sport_category: Some("Swimming".to_string()),
+        issuer_id: issuer_id.map(|id| id.to_string()),
+    }
+}
+
+fn open_badge_url(certificate_id: &str) -> String {
+    format!("/certificates/{}.open-badge.json", certificate_id)
+}
+
+fn json_array_contains(values: &serde_json::Value, value: &str) -> bool {
+    values.as_array().unwrap().iter().any(|v| v == value)
+}
+
+/// Fetches the certified Open Badges response of the certificate and checks that it is
+/// an Open Badges 3.0 credential, with all the fields required by the spec.
+fn get_open_badge(env: &TestEnv, certificate_id: &str) -> serde_json::Value {
+    let response = get_http_response(env, &open_badge_url(certificate_id));
+    assert_eq!(response.status_code, 200);
+    assert_eq!(
+        http_response_header(&response, "Content-Type"),
+        Some("application/json")
+    );
+    let json: serde_json::Value = serde_json::from_slice(&response.body).unwrap();
+
+    assert_eq!(json["@context"][0], "https://www.w3.org/ns/credentials/v2");
+    assert!(json_array_contains(
+        &json["@context"],
+        "https://purl.imsglobal.org/spec/ob/v3p0/context-3.0.3.json"
+    ));
+    assert_eq!(json["id"], format!("urn:uuid:{}", certificate_id));
+    assert!(json_array_contains(&json["type"], "VerifiableCredential"));
+    assert!(json_array_contains(&json["type"], "OpenBadgeCredential"));
+    assert!(json["name"].is_string());
+    assert!(json["validFrom"].is_string());
+    assert!(json["issuer"]["id"].is_string());
+    assert!(json_array_contains(&json["issuer"]["type"], "Profile"));
+
+    let subject = &json["credentialSubject"];
+    assert!(json_array_contains(&subject["type"], "AchievementSubject"));
+    let achievement = &subject["achievement"];
+    assert!(achievement["id"].as_str().unwrap().starts_with("urn:uuid:"));
+    assert!(json_array_contains(&achievement["type"], "Achievement"));
+    assert!(achievement["name"].is_string());
+    assert!(achievement["description"].is_string());
+    assert!(achievement["criteria"]["narrative"].is_string());
+
+    json
+}
+
+#[test]
+fn test_achievements() {
+    let env = test_env::create_test_env();
+    let backend_principal = setup_config(&env);
+    let (auth_provider_key_pair, jwks) = initialize_auth_provider();
+    initialize_canister(&env, jwks);
+    let user_principal = create_user_with_roles(
+        &env,
+        &auth_provider_key_pair,
+        TEST_USER_SUB,
+        TEST_USER_DB_ID,
+        &["athlete"],
+    );
+    let club_admin_principal = create_user_with_roles(
+        &env,
+        &auth_provider_key_pair,
+        "test_club_admin_sub",
+        "ccb31f93-1a16-4089-bc84-1822ae591da2",
+        &["club_admin"],
+    );
+    let coach_db_id = "83163621-9085-4e12-88bb-0b9aee290420";
+    let coach_principal = create_user_with_roles(
+        &env,
+        &auth_provider_key_pair,
+        "test_coach_sub",
+        coach_db_id,
+        &["coach"],
+    );
+    let issuer_id = create_issuer(
+        &env,
+        club_admin_principal,
+        CreateIssuerRequest {
+            name: "Swimming club".to_string(),
+        },
+    )
+    .unwrap()
+    .id;
+    add_issuer_member(
+        &env,
+        club_admin_principal,
+        IssuerMemberRequest {
+            issuer_id: issuer_id.clone(),
+            user_db_id: coach_db_id.to_string(),
+            role: IssuerMemberRole::Coach,
+        },
+    )
+    .unwrap();
+
+    // users can only create achievements for the issuers they are admins of
+    let res =
+        create_achievement(&env, club_admin_principal, achievement_request(None)).unwrap_err();
+    assert_eq!(
+        res,
+        ApiError::unauthorized("Users can only create achievements for their issuers")
+    );
+    let res = create_achievement(&env, coach_principal, achievement_request(Some(&issuer_id)))
+        .unwrap_err();
+    assert_eq!(
+        res,
+        ApiError::unauthorized("User is not an admin of the issuer")
+    );
+    let res = create_achievement(
+        &env,
+        backend_principal,
+        CreateAchievementRequest {
+            name: " ".to_string(),
+            ..achievement_request(None)
+        },
+    )
+    .unwrap_err();
+    assert_eq!(res, ApiError::validation("name", "Name cannot be empty."));
+    let res = create_achievement(
+        &env,
+        backend_principal,
+        CreateAchievementRequest {
+            sport_category: None,
+            ..achievement_request(None)
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        res,
+        ApiError::validation(
+            "sport_category",
+            "Sport category is required for achievements without an issuer."
+        )
+    );
+
+    let sport_achievement_id =
+        create_achievement(&env, backend_principal, achievement_request(None))
+            .unwrap()
+            .id;
+    let issuer_achievement_id = create_achievement(
+        &env,
+        club_admin_principal,
+        CreateAchievementRequest {
+            sport_category: None,
+            ..achievement_request(Some(&issuer_id))
+        },
+    )
+    .unwrap()
+    .id;
+
+    let issue = |sender: Principal,
+                 content: CreateCertificateContentRequest,
+                 issuer_id: Option<&str>,
+                 achievement_id: &str| {
+        create_certificate(
+            &env,
+            sender,
+            CreateCertificateRequest {
+                user_db_id: TEST_USER_DB_ID.to_string(),
+                content,
+                managed_user_db_id: None,
+                issuer_id: issuer_id.map(|id| id.to_string()),
+                achievement_id: Some(achievement_id.to_string()),
+            },
+        )
     };
-    let res = get_user_certificates(&env, user_principal, request).unwrap_err();
-    assert!(extract_trap_message(res)
-        .contains("Only one of user_principal or user_db_id can be provided."));
+
+    // the achievement must be for the sport category and the issuer of the certificate
+    let res = issue(
+        backend_principal,
+        certificate_content_request(),
+        None,
+        &Uuid::new_v4().to_string(),
+    )
+    .unwrap_err();
+    assert_eq!(res, ApiError::not_found("Achievement not found"));
+    let res = issue(
+        backend_principal,
+        CreateCertificateContentRequest {
+            sport_category: "Football".to_string(),
+            ..certificate_content_request()
+        },
+        None,
+        &sport_achievement_id,
+    )
+    .unwrap_err();
+    let mismatch_error = ApiError::conflict(
+        "Achievement cannot be awarded for the sport category or the issuer of the certificate",
+    );
+    assert_eq!(res, mismatch_error);
+    let res = issue(
+        backend_principal,
+        certificate_content_request(),
+        None,
+        &issuer_achievement_id,
+    )
+    .unwrap_err();
+    assert_eq!(res, mismatch_error);
+
+    let sport_certificate_id = issue(
+        backend_principal,
+        certificate_content_request(),
+        None,
+        &sport_achievement_id,
+    )
+    .unwrap()
+    .id;
+    let issuer_certificate_id = issue(
+        coach_principal,
+        certificate_content_request(),
+        Some(&issuer_id),
+        &issuer_achievement_id,
+    )
+    .unwrap()
+    .id;
+
+    // the achievement is part of the certified certificate
+    let res = get_certificate(&env, user_principal, sport_certificate_id.clone()).unwrap();
+    assert_eq!(
+        decode_certificate(&res.certificate.certificate_cbor_hex).achievement_id,
+        Some(sport_achievement_id.clone())
+    );
+
+    // the sport category cannot be changed to one that doesn't match the achievement
+    let res = update_certificate(
+        &env,
+        user_principal,
+        UpdateCertificateRequest {
+            id: sport_certificate_id,
+            content: CreateCertificateContentRequest {
+                sport_category: "Football".to_string(),
+                ..updated_certificate_content_request()
+            },
+        },
+    )
+    .unwrap_err();
+    assert_eq!(res, mismatch_error);
+
+    // the achievements survive upgrades
+    upgrade_canister(&env);
+    let achievement =
+        get_achievement(&env, Principal::anonymous(), sport_achievement_id.clone()).unwrap();
+    let request = achievement_request(None);
+    assert_eq!(achievement.id, sport_achievement_id);
+    assert_eq!(achievement.name, request.name);
+    assert_eq!(achievement.description, request.description);
+    assert_eq!(achievement.criteria, request.criteria);
+    assert_eq!(achievement.sport_category, request.sport_category);
+    assert_eq!(achievement.issuer_id, None);
+    let achievement =
+        get_achievement(&env, Principal::anonymous(), issuer_achievement_id.clone()).unwrap();
+    assert_eq!(achievement.sport_category, None);
+    assert_eq!(achievement.issuer_id, Some(issuer_id.clone()));
+    let res =
+        get_achievement(&env, Principal::anonymous(), Uuid::new_v4().to_string()).unwrap_err();
+    assert_eq!(res, ApiError::not_found("Achievement not found"));
+
+    // the issuer is the creator of its achievements in the Open Badges credential
+    set_certificate_public(
+        &env,
+        user_principal,
+        SetCertificatePublicRequest {
+            id: issuer_certificate_id.clone(),
+            is_public: true,
+        },
+    )
+    .unwrap();
+    let open_badge = get_open_badge(&env, &issuer_certificate_id);
+    let achievement = &open_badge["credentialSubject"]["achievement"];
+    assert_eq!(
+        achievement["id"],
+        format!("urn:uuid:{}", issuer_achievement_id)
+    );
+    assert_eq!(
+        achievement["creator"]["id"],
+        format!("urn:uuid:{}", issuer_id)
+    );
+    assert!(json_array_contains(
+        &achievement["creator"]["type"],
+        "Profile"
+    ));
+    assert_eq!(achievement["creator"]["name"], "Swimming club");
+    assert!(achievement.get("tag").is_none());
+}
+
+#[test]
+fn test_create_certificate_invalid_request() {
+    let env = test_env::create_test_env();
+    let backend_principal = setup_config(&env);
+    setup_user(&env, TEST_USER_SUB, TEST_USER_DB_ID);
+
+    // empty name
+    let request = CreateCertificateRequest {
+        user_db_id: TEST_USER_DB_ID.to_string(),
+        content: CreateCertificateContentRequest {
+            name: "".to_string(),
+            ..certificate_content_request()
+        },
+        managed_user_db_id: None,
+        issuer_id: None,
+        achievement_id: None,
+    };
+    let res = create_certificate(&env, backend_principal, request).unwrap_err();
+    assert_eq!(
+        res,
+        ApiError::validation("content.name", "Title cannot be empty.")
+    );
+
+    // too long name
+    let request = CreateCertificateRequest {
+        user_db_id: TEST_USER_DB_ID.to_string(),
+        content: CreateCertificateContentRequest {
+            name: "a".repeat(MAX_NAME_CHARS_COUNT + 1),
+            ..certificate_content_request()
+        },
+        managed_user_db_id: None,
+        issuer_id: None,
+        achievement_id: None,
+    };
+    let res = create_certificate(&env, backend_principal, request).unwrap_err();
+    assert_eq!(
+        res,
+        ApiError::validation(
+            "content.name",
+            format!(
+                "Title cannot be longer than {} characters.",
+                MAX_NAME_CHARS_COUNT
+            )
+        )
+    );
+
+    // empty sport category
+    let request = CreateCertificateRequest {
+        user_db_id: TEST_USER_DB_ID.to_string(),
+        content: CreateCertificateContentRequest {
+            sport_category: "".to_string(),
+            ..certificate_content_request()
+        },
+        managed_user_db_id: None,
+        issuer_id: None,
+        achievement_id: None,
+    };
+    let res = create_certificate(&env, backend_principal, request).unwrap_err();
+    assert_eq!(
+        res,
+        ApiError::validation("content.sport_category", "Sport category cannot be empty.")
+    );
+
+    // too long sport category
+    let request = CreateCertificateRequest {
+        user_db_id: TEST_USER_DB_ID.to_string(),
+        content: CreateCertificateContentRequest {
+            sport_category: "a".repeat(MAX_SPORT_CATEGORY_CHARS_COUNT + 1),
+            ..certificate_content_request()
+        },
+        managed_user_db_id: None,
+        issuer_id: None,
+        achievement_id: None,
+    };
+    let res = create_certificate(&env, backend_principal, request).unwrap_err();
+    assert_eq!(
+        res,
+        ApiError::validation(
+            "content.sport_category",
+            format!(
+                "Sport category cannot be longer than {} characters.",
+                MAX_SPORT_CATEGORY_CHARS_COUNT
+            )
+        )
+    );
+
+    // too long notes
+    let request = CreateCertificateRequest {
+        user_db_id: TEST_USER_DB_ID.to_string(),
+        content: CreateCertificateContentRequest {
+            notes: Some("a".repeat(MAX_NOTES_CHARS_COUNT + 1)),
+            ..certificate_content_request()
+        },
+        managed_user_db_id: None,
+        issuer_id: None,
+        achievement_id: None,
+    };
+    let res = create_certificate(&env, backend_principal, request).unwrap_err();
+    assert_eq!(
+        res,
+        ApiError::validation(
+            "content.notes",
+            format!(
+                "Notes cannot be longer than {} characters.",
+                MAX_NOTES_CHARS_COUNT
+            )
+        )
+    );
+
+    // too long file uri, e.g. an inline file that should be uploaded instead
+    let request = CreateCertificateRequest {
+        user_db_id: TEST_USER_DB_ID.to_string(),
+        content: CreateCertificateContentRequest {
+            file_uri: Some(
+                str::from_utf8(&vec![1; MAX_FILE_URI_BYTES_SIZE + 1])
+                    .unwrap()
+                    .to_string(),
+            ),
+            ..certificate_content_request()
+        },
+        managed_user_db_id: None,
+        issuer_id: None,
+        achievement_id: None,
+    };
+    let res = create_certificate(&env, backend_principal, request).unwrap_err();
+    assert_eq!(
+        res,
+        ApiError::validation(
+            "content.file_uri",
+            format!(
+                "File URI cannot be longer than {} bytes, upload the file instead.",
+                MAX_FILE_URI_BYTES_SIZE
+            )
+        )
+    );
+
+    // too long external id
+    let request = CreateCertificateRequest {
+        user_db_id: TEST_USER_DB_ID.to_string(),
+        content: CreateCertificateContentRequest {
+            external_id: Some("a".repeat(MAX_EXTERNAL_ID_CHARS_COUNT + 1)),
+            ..certificate_content_request()
+        },
+        managed_user_db_id: None,
+        issuer_id: None,
+        achievement_id: None,
+    };
+    let res = create_certificate(&env, backend_principal, request).unwrap_err();
+    assert_eq!(
+        res,
+        ApiError::validation(
+            "content.external_id",
+            format!(
+                "External ID cannot be longer than {} characters.",
+                MAX_EXTERNAL_ID_CHARS_COUNT
+            )
+        )
+    );
+
+    // too long issuer full name
+    let request = CreateCertificateRequest {
+        user_db_id: TEST_USER_DB_ID.to_string(),
+        content: CreateCertificateContentRequest {
+            issuer_full_name: Some("a".repeat(MAX_ISSUER_FULL_NAME_CHARS_COUNT + 1)),
+            ..certificate_content_request()
+        },
+        managed_user_db_id: None,
+        issuer_id: None,
+        achievement_id: None,
+    };
+    let res = create_certificate(&env, backend_principal, request).unwrap_err();
+    assert_eq!(
+        res,
+        ApiError::validation(
+            "content.issuer_full_name",
+            format!(
+                "Issuer full name cannot be longer than {} characters.",
+                MAX_ISSUER_FULL_NAME_CHARS_COUNT
+            )
+        )
+    );
+
+    // too long issuer club name
+    let request = CreateCertificateRequest {
+        user_db_id: TEST_USER_DB_ID.to_string(),
+        content: CreateCertificateContentRequest {
+            issuer_club_name: Some("a".repeat(MAX_ISSUER_CLUB_NAME_CHARS_COUNT + 1)),
+            ..certificate_content_request()
+        },
+        managed_user_db_id: None,
+        issuer_id: None,
+        achievement_id: None,
+    };
+    let res = create_certificate(&env, backend_principal, request).unwrap_err();
+    assert_eq!(
+        res,
+        ApiError::validation(
+            "content.issuer_club_name",
+            format!(
+                "Issuer club name cannot be longer than {} characters.",
+                MAX_ISSUER_CLUB_NAME_CHARS_COUNT
+            )
+        )
+    );
+}
+
+fn create_test_certificate(
+    env: &TestEnv,
+    backend_principal: Principal,
+    user_db_id: String,
+) -> (String, String) {
+    let content = certificate_content_request();
+    let request = CreateCertificateRequest {
+        user_db_id,
+        content: content.clone(),
+        managed_user_db_id: None,
+        issuer_id: None,
+        achievement_id: None,
+    };
+
+    let res = create_certificate(env, backend_principal, request).unwrap();
+    (res.id, content.name)
+}
+
+fn create_test_certificate_for_managed_user(
+    env: &TestEnv,
+    backend_principal: Principal,
+    user_db_id: String,
+    managed_user_db_id: String,
+) -> (String, String) {
+    let content = certificate_content_request();
+    let request = CreateCertificateRequest {
+        user_db_id,
+        content: content.clone(),
+        managed_user_db_id: Some(managed_user_db_id),
+        issuer_id: None,
+        achievement_id: None,
+    };
+
+    let res = create_certificate(env, backend_principal, request).unwrap();
+    (res.id, content.name)
+}
+
+#[test]
+fn test_get_user_certificates_invalid_request() {
+    let env = test_env::create_test_env();
+    let user_identity = setup_user(&env, TEST_USER_SUB, TEST_USER_DB_ID);
+    let user_principal = user_identity.sender().unwrap();
+
+    // empty request
+    let request = GetUserCertificatesRequest {
+        user_principal: None,
+        user_db_id: None,
+        ..Default::default()
+    };
+    let res = get_user_certificates(&env, user_principal, request).unwrap_err();
+    assert_eq!(
+        res,
+        ApiError::validation(
+            "user_principal",
+            "Either user_principal or user_db_id must be provided."
+        )
+    );
+
+    // both user_principal and user_db_id are provided
+    let request = GetUserCertificatesRequest {
+        user_principal: Some(user_principal),
+        user_db_id: Some(TEST_USER_DB_ID.to_string()),
+        ..Default::default()
+    };
+    let res = get_user_certificates(&env, user_principal, request).unwrap_err();
+    assert_eq!(
+        res,
+        ApiError::validation(
+            "user_principal",
+            "Only one of user_principal or user_db_id can be provided."
+        )
+    );
+}
+
+#[test]
+fn test_get_user_certificates_not_authorized() {
+    let env = test_env::create_test_env();
+    let backend_principal = setup_config(&env);
+    let user_identity = setup_user(&env, TEST_USER_SUB, TEST_USER_DB_ID);
+    let user_principal = user_identity.sender().unwrap();
+
+    const TEST_CERTIFICATES_COUNT: usize = 10;
+    for _ in 0..TEST_CERTIFICATES_COUNT {
+        create_test_certificate(&env, backend_principal, TEST_USER_DB_ID.to_string());
+    }
+
+    for sender in [
+        generate_random_identity().sender().unwrap(),
+        Principal::anonymous(),
+    ] {
+        let request = GetUserCertificatesRequest {
+            user_principal: Some(user_principal),
+            user_db_id: None,
+            ..Default::default()
+        };
+        let res = get_user_certificates(&env, sender, request).unwrap_err();
+        assert_eq!(
+            res,
+            ApiError::unauthorized("Caller is not the backend or a registered user")
+        );
+    }
+}
+
+#[test]
+fn test_get_user_certificates() {
+    let env = test_env::create_test_env();
+    let backend_principal = setup_config(&env);
+    let user_identity = setup_user(&env, TEST_USER_SUB, TEST_USER_DB_ID);
+    let user_principal = user_identity.sender().unwrap();
+
+    const TEST_CERTIFICATES_COUNT: usize = 10;
+    let mut created_certificates_previews = vec![];
+    for _ in 0..TEST_CERTIFICATES_COUNT {
+        let res = create_test_certificate(&env, backend_principal, TEST_USER_DB_ID.to_string());
+        created_certificates_previews.push(res);
+    }
+
+    let test = |calling_principal: Principal| {
+        // by user principal
+        let request = GetUserCertificatesRequest {
+            user_principal: Some(user_principal),
+            user_db_id: None,
+            ..Default::default()
+        };
+        let res_by_principal = get_user_certificates(&env, calling_principal, request).unwrap();
+        assert_eq!(res_by_principal.certificates.len(), TEST_CERTIFICATES_COUNT);
+        // check that all certificates are present
+        for (id, name) in created_certificates_previews.iter() {
+            let certificate = res_by_principal
+                .certificates
+                .iter()
+                .find(|c| c.id == *id)
+                .unwrap();
+            assert_eq!(certificate.name, *name);
+        }
+
+        // by user db id
+        let request = GetUserCertificatesRequest {
+            user_principal: None,
+            user_db_id: Some(TEST_USER_DB_ID.to_string()),
+            ..Default::default()
+        };
+        let res_by_id = get_user_certificates(&env, calling_principal, request).unwrap();
+        assert_eq!(res_by_id.certificates.len(), TEST_CERTIFICATES_COUNT);
+        // check that all certificates are present
+        for (id, name) in created_certificates_previews.iter() {
+            let certificate = res_by_id.certificates.iter().find(|c| c.id == *id).unwrap();
+            assert_eq!(certificate.name, *name);
+        }
+    };
+
+    test(user_principal);
+    test(backend_principal);
+    // check that certification is still valid after canister upgrade
+    upgrade_canister(&env);
+    test(user_principal);
+    test(backend_principal);
+}
+
+/// Fetches all the pages of the user certificates, returning the certificate ids in order.
+fn get_all_user_certificates_pages(
+    env: &TestEnv,
+    calling_principal: Principal,
+    request: GetUserCertificatesRequest,
+) -> (Vec<String>, usize) {
+    let mut ids = vec![];
+    let mut pages_count = 0;
+    let mut cursor = None;
+    loop {
+        let res = get_user_certificates(
+            env,
+            calling_principal,
+            GetUserCertificatesRequest {
+                cursor,
+                ..request.clone()
+            },
+        )
+        .unwrap();
+        assert!(res.certificates.len() <= request.limit.unwrap() as usize);
+        ids.extend(res.certificates.into_iter().map(|c| c.id));
+        pages_count += 1;
+
+        match res.next_cursor {
+            Some(next_cursor) => cursor = Some(next_cursor),
+            None => break,
+        }
+    }
+
+    (ids, pages_count)
+}
+
+#[test]
+fn test_get_user_certificates_pagination() {
+    let env = test_env::create_test_env();
+    let backend_principal = setup_config(&env);
+    let user_identity = setup_user(&env, TEST_USER_SUB, TEST_USER_DB_ID);
+    let user_principal = user_identity.sender().unwrap();
+
+    const TEST_CERTIFICATES_COUNT: u64 = 5;
+    const DAY_MICROS: u64 = 24 * 60 * 60 * 1_000_000;
+    // certificates are created in order, but issued in reverse order
+    let mut created_ids = vec![];
+    for i in 0..TEST_CERTIFICATES_COUNT {
+        let content = certificate_content_request();
+        let request = CreateCertificateRequest {
+            user_db_id: TEST_USER_DB_ID.to_string(),
+            content: CreateCertificateContentRequest {
+                issued_at: content.issued_at - i * DAY_MICROS,
+                ..content
+            },
+            managed_user_db_id: None,
+            issuer_id: None,
+            achievement_id: None,
+        };
+        created_ids.push(
+            create_certificate(&env, backend_principal, request)
+                .unwrap()
+                .id,
+        );
+        env.pic().advance_time(std::time::Duration::from_secs(1));
+    }
+    let mut created_ids_reversed = created_ids.clone();
+    created_ids_reversed.reverse();
+
+    let test = |calling_principal: Principal| {
+        for (user_principal, user_db_id) in [
+            (Some(user_principal), None),
+            (None, Some(TEST_USER_DB_ID.to_string())),
+        ] {
+            let request = |sort| GetUserCertificatesRequest {
+                user_principal,
+                user_db_id: user_db_id.clone(),
+                limit: Some(2),
+                cursor: None,
+                sort,
+            };
+
+            // newest first by default
+            let (ids, pages_count) =
+                get_all_user_certificates_pages(&env, calling_principal, request(None));
+            assert_eq!(ids, created_ids_reversed);
+            assert_eq!(pages_count, 3);
+
+            let (ids, _) = get_all_user_certificates_pages(
+                &env,
+                calling_principal,
+                request(Some(CertificatesSort {
+                    field: CertificatesSortField::CreatedAt,
+                    order: SortOrder::Asc,
+                })),
+            );
+            assert_eq!(ids, created_ids);
+
+            let (ids, _) = get_all_user_certificates_pages(
+                &env,
+                calling_principal,
+                request(Some(CertificatesSort {
+                    field: CertificatesSortField::IssuedAt,
+                    order: SortOrder::Asc,
+                })),
+            );
+            assert_eq!(ids, created_ids_reversed);
+
+            let (ids, _) = get_all_user_certificates_pages(
+                &env,
+                calling_principal,
+                request(Some(CertificatesSort {
+                    field: CertificatesSortField::IssuedAt,
+                    order: SortOrder::Desc,
+                })),
+            );
+            assert_eq!(ids, created_ids);
+        }
+    };
+
+    test(user_principal);
+    test(backend_principal);
+
+    // updating the issue date of a certificate moves it in the issued_at sort order
+    let content = certificate_content_request();
+    update_certificate(
+        &env,
+        backend_principal,
+        UpdateCertificateRequest {
+            id: created_ids[0].clone(),
+            content: CreateCertificateContentRequest {
+                issued_at: content.issued_at - TEST_CERTIFICATES_COUNT * DAY_MICROS,
+                ..content
+            },
+        },
+    )
+    .unwrap();
+    let res = get_user_certificates(
+        &env,
+        user_principal,
+        GetUserCertificatesRequest {
+            user_principal: Some(user_principal),
+            sort: Some(CertificatesSort {
+                field: CertificatesSortField::IssuedAt,
+                order: SortOrder::Asc,
+            }),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    assert_eq!(res.certificates.len(), TEST_CERTIFICATES_COUNT as usize);
+    assert_eq!(res.certificates[0].id, created_ids[0]);
+    assert!(res.next_cursor.is_none());
+
+    // check that the indexes are still valid after canister upgrade
+    upgrade_canister(&env);
+    test(backend_principal);
+}
+
+#[test]
+fn test_get_user_certificates_pagination_invalid_request() {
+    let env = test_env::create_test_env();
+    let backend_principal = setup_config(&env);
+    setup_user(&env, TEST_USER_SUB, TEST_USER_DB_ID);
+
+    for limit in [0, MAX_CERTIFICATES_PAGE_LIMIT + 1] {
+        let request = GetUserCertificatesRequest {
+            user_db_id: Some(TEST_USER_DB_ID.to_string()),
+            limit: Some(limit),
+            ..Default::default()
+        };
+        let res = get_user_certificates(&env, backend_principal, request).unwrap_err();
+        assert_eq!(
+            res,
+            ApiError::validation(
+                "limit",
+                format!(
+                    "Limit must be between 1 and {}.",
+                    MAX_CERTIFICATES_PAGE_LIMIT
+                )
+            )
+        );
+    }
+
+    let request = GetUserCertificatesRequest {
+        user_db_id: Some(TEST_USER_DB_ID.to_string()),
+        cursor: Some("invalid".to_string()),
+        ..Default::default()
+    };
+    let res = get_user_certificates(&env, backend_principal, request).unwrap_err();
+    assert_eq!(
+        res,
+        ApiError::validation("cursor", "Invalid cursor: invalid")
+    );
+}
+
+#[test]
+fn test_get_user_certificates_another_user() {
+    let env = test_env::create_test_env();
+    let backend_principal = setup_config(&env);
+    let (auth_provider_key_pair, jwks) = initialize_auth_provider();
+    initialize_canister(&env, jwks);
+    let first_user_db_id = TEST_USER_DB_ID;
+    let first_user_identity = create_user(
+        &env,
+        &auth_provider_key_pair,
+        TEST_USER_SUB,
+        first_user_db_id,
+    );
+    let first_user_principal = first_user_identity.sender().unwrap();
+
+    let second_user_sub = "test_sub_2";
+    let second_user_db_id = "ccb31f93-1a16-4089-bc84-1822ae591da2";
+    let second_user_identity = create_user(
+        &env,
+        &auth_provider_key_pair,
+        second_user_sub,
+        second_user_db_id,
+    );
+
+    const TEST_CERTIFICATES_COUNT: usize = 10;
+
+    for _ in 0..TEST_CERTIFICATES_COUNT {
+        create_test_certificate(&env, backend_principal, first_user_db_id.to_string());
+    }
+
+    let second_user_principal = second_user_identity.sender().unwrap();
+    // by user principal
+    let request = GetUserCertificatesRequest {
+        user_principal: Some(first_user_principal),
+        user_db_id: None,
+        ..Default::default()
+    };
+    let res_principal = get_user_certificates(&env, second_user_principal, request).unwrap_err();
+    assert_eq!(
+        res_principal,
+        ApiError::unauthorized("User can only access their own certificates")
+    );
+
+    // by user db id
+    let request = GetUserCertificatesRequest {
+        user_principal: None,
+        user_db_id: Some(first_user_db_id.to_string()),
+        ..Default::default()
+    };
+    let res_db_id = get_user_certificates(&env, second_user_principal, request).unwrap_err();
+    assert_eq!(
+        res_db_id,
+        ApiError::unauthorized("User can only access their own certificates")
+    );
+
+    // managed user
+    const MANAGED_USER_DB_ID: &str = "ccb31f93-1a16-4089-bc84-1822ae591da2";
+    create_test_certificate_for_managed_user(
+        &env,
+        backend_principal,
+        first_user_db_id.to_string(),
+        MANAGED_USER_DB_ID.to_string(),
+    );
+    let request = GetUserCertificatesRequest {
+        user_principal: None,
+        user_db_id: Some(MANAGED_USER_DB_ID.to_string()),
+        ..Default::default()
+    };
+    let res_managed = get_user_certificates(&env, second_user_principal, request).unwrap();
+    assert_eq!(res_managed.certificates.len(), 0);
+}
+
+#[test]
+fn test_get_user_certificates_no_user() {
+    let env = test_env::create_test_env();
+    let backend_principal = setup_config(&env);
+    setup_user(&env, TEST_USER_SUB, TEST_USER_DB_ID);
+
+    const TEST_CERTIFICATES_COUNT: usize = 10;
+
+    for _ in 0..TEST_CERTIFICATES_COUNT {
+        create_test_certificate(&env, backend_principal, TEST_USER_DB_ID.to_string());
+    }
+
+    // by user principal
+    let non_existing_user_principal = generate_random_identity().sender().unwrap();
+    let request = GetUserCertificatesRequest {
+        user_principal: Some(non_existing_user_principal),
+        user_db_id: None,
+        ..Default::default()
+    };
+    let res_principal = get_user_certificates(&env, backend_principal, request).unwrap();
+    assert_eq!(res_principal.certificates.len(), 0);
+
+    // by user db id
+    let non_existing_user_db_id = "ccb31f93-1a16-4089-bc84-1822ae591da2";
+    let request = GetUserCertificatesRequest {
+        user_principal: None,
+        user_db_id: Some(non_existing_user_db_id.to_string()),
+        ..Default::default()
+    };
+    let res_db_id = get_user_certificates(&env, backend_principal, request).unwrap();
+    assert_eq!(res_db_id.certificates.len(), 0);
+}
+
+#[test]
+fn test_get_user_certificates_managed_user() {
+    let env = test_env::create_test_env();
+    let backend_principal = setup_config(&env);
+    let user_identity = setup_user(&env, TEST_USER_SUB, TEST_USER_DB_ID);
+    let user_principal = user_identity.sender().unwrap();
+
+    const MANAGED_USER_DB_ID: &str = "ccb31f93-1a16-4089-bc84-1822ae591da2";
+
+    const TEST_CERTIFICATES_COUNT: usize = 10;
+    let mut created_certificates_previews = vec![];
+    for _ in 0..TEST_CERTIFICATES_COUNT {
+        let res = create_test_certificate_for_managed_user(
+            &env,
+            backend_principal,
+            TEST_USER_DB_ID.to_string(),
+            MANAGED_USER_DB_ID.to_string(),
+        );
+        created_certificates_previews.push(res);
+    }
+
+    let test = |calling_principal: Principal| {
+        let request = GetUserCertificatesRequest {
+            user_principal: None,
+            user_db_id: Some(MANAGED_USER_DB_ID.to_string()),
+            ..Default::default()
+        };
+        let res = get_user_certificates(&env, calling_principal, request).unwrap();
+        assert_eq!(res.certificates.len(), TEST_CERTIFICATES_COUNT);
+        // check that all certificates are present
+        for (id, name) in created_certificates_previews.iter() {
+            let certificate = res.certificates.iter().find(|c| c.id == *id).unwrap();
+            assert_eq!(certificate.name, *name);
+        }
+    };
+
+    test(user_principal);
+    test(backend_principal);
+}
+
+fn create_test_certificate_with_content(
+    env: &TestEnv,
+    backend_principal: Principal,
+    managed_user_db_id: Option<String>,
+    content: CreateCertificateContentRequest,
+) -> String {
+    let request = CreateCertificateRequest {
+        user_db_id: TEST_USER_DB_ID.to_string(),
+        content,
+        managed_user_db_id,
+        issuer_id: None,
+        achievement_id: None,
+    };
+
+    create_certificate(env, backend_principal, request)
+        .unwrap()
+        .id
+}
+
+#[test]
+fn test_get_user_certificates_filters() {
+    let env = test_env::create_test_env();
+    let backend_principal = setup_config(&env);
+    let user_identity = setup_user(&env, TEST_USER_SUB, TEST_USER_DB_ID);
+    let user_principal = user_identity.sender().unwrap();
+
+    const MANAGED_USER_DB_ID: &str = "ccb31f93-1a16-4089-bc84-1822ae591da2";
+    const DAY_MICROS: u64 = 24 * 60 * 60 * 1_000_000;
+    let content = certificate_content_request();
+    let issued_at = content.issued_at;
+
+    let swimming_club =
+        create_test_certificate_with_content(&env, backend_principal, None, content.clone());
+    let swimming_no_club = create_test_certificate_with_content(
+        &env,
+        backend_principal,
+        None,
+        CreateCertificateContentRequest {
+            issued_at: issued_at + DAY_MICROS,
+            issuer_club_name: None,
+            ..content.clone()
+        },
+    );
+    let running_club = create_test_certificate_with_content(
+        &env,
+        backend_principal,
+        None,
+        CreateCertificateContentRequest {
+            sport_category: "Running".to_string(),
+            issued_at: issued_at + 2 * DAY_MICROS,
+            ..content.clone()
+        },
+    );
+    let managed_swimming_club = create_test_certificate_with_content(
+        &env,
+        backend_principal,
+        Some(MANAGED_USER_DB_ID.to_string()),
+        content.clone(),
+    );
+    revoke_certificate(
+        &env,
+        backend_principal,
+        RevokeCertificateRequest {
+            id: running_club.clone(),
+            reason: "reason".to_string(),
+        },
+    )
+    .unwrap();
+
+    let get_ids = |calling_principal: Principal, user_db_id: &str, filters| {
+        let request = GetUserCertificatesRequest {
+            user_db_id: Some(user_db_id.to_string()),
+            limit: Some(1),
+            sort: Some(CertificatesSort {
+                field: CertificatesSortField::IssuedAt,
+                order: SortOrder::Asc,
+            }),
+            filters: Some(filters),
+            ..Default::default()
+        };
+        let (mut ids, _) = get_all_user_certificates_pages(&env, calling_principal, request);
+        ids.sort();
+        ids
+    };
+    let sorted = |mut ids: Vec<&String>| {
+        ids.sort();
+        ids.into_iter().cloned().collect::<Vec<_>>()
+    };
+
+    let test = |calling_principal: Principal| {
+        // previews
+        let res = get_user_certificates(
+            &env,
+            calling_principal,
+            GetUserCertificatesRequest {
+                user_principal: Some(user_principal),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(res.certificates.len(), 4);
+        let preview = res
+            .certificates
+            .iter()
+            .find(|c| c.id == running_club)
+            .unwrap();
+        assert_eq!(preview.name, content.name);
+        assert_eq!(preview.sport_category, "Running");
+        assert_eq!(preview.issuer_club_name, content.issuer_club_name);
+        assert!(preview.is_revoked);
+        let preview = res
+            .certificates
+            .iter()
+            .find(|c| c.id == swimming_no_club)
+            .unwrap();
+        assert_eq!(preview.sport_category, content.sport_category);
+        assert_eq!(preview.issuer_club_name, None);
+        assert!(!preview.is_revoked);
+
+        // sport category, case insensitive
+        let ids = get_ids(
+            calling_principal,
+            TEST_USER_DB_ID,
+            CertificatesFilters {
+                sport_category: Some(" swimming".to_string()),
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            ids,
+            sorted(vec![
+                &swimming_club,
+                &swimming_no_club,
+                &managed_swimming_club
+            ])
+        );
+
+        // issuer club name
+        let ids = get_ids(
+            calling_principal,
+            TEST_USER_DB_ID,
+            CertificatesFilters {
+                issuer_club_name: content.issuer_club_name.clone(),
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            ids,
+            sorted(vec![&swimming_club, &running_club, &managed_swimming_club])
+        );
+
+        // issued at range
+        let ids = get_ids(
+            calling_principal,
+            TEST_USER_DB_ID,
+            CertificatesFilters {
+                issued_at_from: Some(issued_at + DAY_MICROS),
+                issued_at_to: Some(issued_at + 2 * DAY_MICROS),
+                ..Default::default()
+            },
+        );
+        assert_eq!(ids, sorted(vec![&swimming_no_club, &running_club]));
+
+        // all filters combined
+        let ids = get_ids(
+            calling_principal,
+            TEST_USER_DB_ID,
+            CertificatesFilters {
+                sport_category: Some("Swimming".to_string()),
+                issued_at_from: Some(issued_at),
+                issued_at_to: Some(issued_at),
+                issuer_club_name: content.issuer_club_name.clone(),
+            },
+        );
+        assert_eq!(ids, sorted(vec![&swimming_club, &managed_swimming_club]));
+
+        // no matches
+        let ids = get_ids(
+            calling_principal,
+            TEST_USER_DB_ID,
+            CertificatesFilters {
+                sport_category: Some("Cycling".to_string()),
+                ..Default::default()
+            },
+        );
+        assert!(ids.is_empty());
+
+        // managed user
+        let ids = get_ids(
+            calling_principal,
+            MANAGED_USER_DB_ID,
+            CertificatesFilters {
+                sport_category: Some("Swimming".to_string()),
+                ..Default::default()
+            },
+        );
+        assert_eq!(ids, vec![managed_swimming_club.clone()]);
+    };
+
+    test(user_principal);
+    test(backend_principal);
+    // check that the indexes are still valid after canister upgrade
+    upgrade_canister(&env);
+    test(backend_principal);
+}
+
+#[test]
+fn test_get_user_certificates_filters_invalid_request() {
+    let env = test_env::create_test_env();
+    let backend_principal = setup_config(&env);
+    setup_user(&env, TEST_USER_SUB, TEST_USER_DB_ID);
+
+    for (filters, expected_field, expected_error) in [
+        (
+            CertificatesFilters {
+                sport_category: Some(" ".to_string()),
+                ..Default::default()
+            },
+            "sport_category",
+            "Sport category filter cannot be empty.",
+        ),
+        (
+            CertificatesFilters {
+                issued_at_from: Some(2),
+                issued_at_to: Some(1),
+                ..Default::default()
+            },
+            "issued_at_from",
+            "Issued at from cannot be after issued at to.",
+        ),
+        (
+            CertificatesFilters {
+                issuer_club_name: Some("".to_string()),
+                ..Default::default()
+            },
+            "issuer_club_name",
+            "Issuer club name filter cannot be empty.",
+        ),
+    ] {
+        let request = GetUserCertificatesRequest {
+            user_db_id: Some(TEST_USER_DB_ID.to_string()),
+            filters: Some(filters),
+            ..Default::default()
+        };
+        let res = get_user_certificates(&env, backend_principal, request).unwrap_err();
+        assert_eq!(
+            res,
+            ApiError::validation(format!("filters.{}", expected_field), expected_error)
+        );
+    }
+}
+
+#[test]
+fn test_get_certificate() {
+    let env = test_env::create_test_env();
+    let backend_principal = setup_config(&env);
+    let user_identity = setup_user(&env, TEST_USER_SUB, TEST_USER_DB_ID);
+    let user_principal = user_identity.sender().unwrap();
+
+    const TEST_CERTIFICATES_COUNT: usize = 10;
+    let mut created_certificates_previews = vec![];
+    for _ in 0..TEST_CERTIFICATES_COUNT {
+        let res = create_test_certificate(&env, backend_principal, TEST_USER_DB_ID.to_string());
+        created_certificates_previews.push(res);
+    }
+
+    let test = |calling_principal: Principal| {
+        for (id, name) in created_certificates_previews.iter() {
+            let res_by_id = get_certificate(&env, calling_principal, id.clone()).unwrap();
+            let certificate = decode_certificate(&res_by_id.certificate.certificate_cbor_hex);
+            assert_eq!(certificate.user_principal, user_principal);
+            assert_eq!(certificate.content.name, *name);
+            assert_ic_certification_is_valid(
+                &env,
+                res_by_id.ic_certificate,
+                res_by_id.ic_certificate_witness.clone(),
+            );
+            assert_ic_certificate_tree_is_valid(
+                res_by_id.ic_certificate_witness,
+                &user_principal,
+                vec![res_by_id.certificate],
+            );
+        }
+    };
+
+    test(user_principal);
+    test(backend_principal);
+}
+
+#[test]
+fn test_get_certificate_managed_user() {
+    let env = test_env::create_test_env();
+    let backend_principal = setup_config(&env);
+    let user_identity = setup_user(&env, TEST_USER_SUB, TEST_USER_DB_ID);
+    let user_principal = user_identity.sender().unwrap();
+
+    const MANAGED_USER_DB_ID: &str = "ccb31f93-1a16-4089-bc84-1822ae591da2";
+    let (certificate_id, name) = create_test_certificate_for_managed_user(
+        &env,
+        backend_principal,
+        TEST_USER_DB_ID.to_string(),
+        MANAGED_USER_DB_ID.to_string(),
+    );
+
+    let test = |calling_principal: Principal| {
+        let res = get_certificate(&env, calling_principal, certificate_id.clone()).unwrap();
+        let certificate = decode_certificate(&res.certificate.certificate_cbor_hex);
+        assert_eq!(certificate.user_principal, user_principal);
+        assert_eq!(certificate.content.name, name);
+        assert_eq!(
+            certificate.managed_user_id.clone().unwrap(),
+            MANAGED_USER_DB_ID.to_string()
+        );
+        assert_ic_certification_is_valid(
+            &env,
+            res.ic_certificate,
+            res.ic_certificate_witness.clone(),
+        );
+        assert_ic_certificate_tree_is_valid(
+            res.ic_certificate_witness,
+            &user_principal,
+            vec![res.certificate],
+        );
+    };
+
+    test(user_principal);
+    test(backend_principal);
+}
+
+#[test]
+fn test_managed_user_guardians() {
+    let env = test_env::create_test_env();
+    let backend_principal = setup_config(&env);
+    let (auth_provider_key_pair, jwks) = initialize_auth_provider();
+    initialize_canister(&env, jwks);
+    let first_guardian_principal = create_user_with_roles(
+        &env,
+        &auth_provider_key_pair,
+        TEST_USER_SUB,
+        TEST_USER_DB_ID,
+        &["athlete"],
+    );
+    let second_guardian_db_id = "83163621-9085-4e12-88bb-0b9aee290420";
+    let second_guardian_principal = create_user_with_roles(
+        &env,
+        &auth_provider_key_pair,
+        "test_second_guardian_sub",
+        second_guardian_db_id,
+        &["athlete"],
+    );
+
+    const MANAGED_USER_DB_ID: &str = "ccb31f93-1a16-4089-bc84-1822ae591da2";
+    let (certificate_id, _) = create_test_certificate_for_managed_user(
+        &env,
+        backend_principal,
+        TEST_USER_DB_ID.to_string(),
+        MANAGED_USER_DB_ID.to_string(),
+    );
+
+    let managed_users = get_my_managed_users(&env, first_guardian_principal).unwrap();
+    assert_eq!(managed_users.len(), 1);
+    assert_eq!(managed_users[0].db_id, MANAGED_USER_DB_ID);
+    assert_eq!(managed_users[0].guardians, vec![first_guardian_principal]);
+    assert_eq!(managed_users[0].claimed_by, None);
+    assert!(get_my_managed_users(&env, second_guardian_principal)
+        .unwrap()
+        .is_empty());
+
+    let certificates_request = GetManagedUserCertificatesRequest {
+        managed_user_db_id: MANAGED_USER_DB_ID.to_string(),
+        ..Default::default()
+    };
+    let guardian_request = |guardian_db_id: &str| ManagedUserGuardianRequest {
+        managed_user_db_id: MANAGED_USER_DB_ID.to_string(),
+        guardian_db_id: guardian_db_id.to_string(),
+    };
+
+    // only guardians can access the certificates of the managed user
+    let res = get_managed_user_certificates(
+        &env,
+        second_guardian_principal,
+        certificates_request.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(
+        res,
+        ApiError::unauthorized("User is not a guardian of the managed user")
+    );
+    let res = add_managed_user_guardian(
+        &env,
+        second_guardian_principal,
+        guardian_request(second_guardian_db_id),
+    )
+    .unwrap_err();
+    assert_eq!(
+        res,
+        ApiError::unauthorized("User is not a guardian of the managed user")
+    );
+    let res = create_certificate(
+        &env,
+        second_guardian_principal,
+        CreateCertificateRequest {
+            user_db_id: second_guardian_db_id.to_string(),
+            content: certificate_content_request(),
+            managed_user_db_id: Some(MANAGED_USER_DB_ID.to_string()),
+            issuer_id: None,
+            achievement_id: None,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        res,
+        ApiError::unauthorized("User cannot issue certificates to managed users")
+    );
+
+    add_managed_user_guardian(
+        &env,
+        first_guardian_principal,
+        guardian_request(second_guardian_db_id),
+    )
+    .unwrap();
+
+    // the new guardian sees the certificates held by the other guardian
+    let res = get_managed_user_certificates(&env, second_guardian_principal, certificates_request)
+        .unwrap();
+    assert_eq!(res.certificates.len(), 1);
+    assert_eq!(res.certificates[0].id, certificate_id);
+    get_certificate(&env, second_guardian_principal, certificate_id).unwrap();
+
+    remove_managed_user_guardian(&env, backend_principal, guardian_request(TEST_USER_DB_ID))
+        .unwrap();
+    let res = remove_managed_user_guardian(
+        &env,
+        second_guardian_principal,
+        guardian_request(second_guardian_db_id),
+    )
+    .unwrap_err();
+    assert_eq!(
+        res,
+        ApiError::conflict("Managed user must have at least one guardian")
+    );
+
+    upgrade_canister(&env);
+
+    assert!(get_my_managed_users(&env, first_guardian_principal)
+        .unwrap()
+        .is_empty());
+    let managed_users = get_my_managed_users(&env, second_guardian_principal).unwrap();
+    assert_eq!(managed_users.len(), 1);
+    assert_eq!(managed_users[0].guardians, vec![second_guardian_principal]);
+}
+
+#[test]
+fn test_create_certificate_for_managed_user_coach() {
+    let env = test_env::create_test_env();
+    let backend_principal = setup_config(&env);
+    let (auth_provider_key_pair, jwks) = initialize_auth_provider();
+    initialize_canister(&env, jwks);
+    let guardian_principal = create_user_with_roles(
+        &env,
+        &auth_provider_key_pair,
+        TEST_USER_SUB,
+        TEST_USER_DB_ID,
+        &["athlete"],
+    );
+    let other_user_db_id = "83163621-9085-4e12-88bb-0b9aee290420";
+    create_user_with_roles(
+        &env,
+        &auth_provider_key_pair,
+        "test_other_user_sub",
+        other_user_db_id,
+        &["athlete"],
+    );
+    let coach_principal = create_user_with_roles(
+        &env,
+        &auth_provider_key_pair,
+        "test_coach_sub",
+        "4e0b6d3a-0f57-4b0e-9a53-6d1f1e7c2a90",
+        &["coach"],
+    );
+
+    const MANAGED_USER_DB_ID: &str = "ccb31f93-1a16-4089-bc84-1822ae591da2";
+    let request = |user_db_id: &str| CreateCertificateRequest {
+        user_db_id: user_db_id.to_string(),
+        content: certificate_content_request(),
+        managed_user_db_id: Some(MANAGED_USER_DB_ID.to_string()),
+        issuer_id: None,
+        achievement_id: None,
+    };
+
+    // users cannot create managed users
+    let res = create_certificate(&env, coach_principal, request(TEST_USER_DB_ID)).unwrap_err();
+    assert_eq!(res, ApiError::not_found("Managed user not found"));
+
+    create_test_certificate_for_managed_user(
+        &env,
+        backend_principal,
+        TEST_USER_DB_ID.to_string(),
+        MANAGED_USER_DB_ID.to_string(),
+    );
+
+    // the coach issues the certificate to an existing guardian only
+    let res = create_certificate(&env, coach_principal, request(other_user_db_id)).unwrap_err();
+    assert_eq!(
+        res,
+        ApiError::unauthorized("User is not a guardian of the managed user")
+    );
+    let res = create_certificate(&env, coach_principal, request(TEST_USER_DB_ID)).unwrap();
+    let res_certificate = get_certificate(&env, guardian_principal, res.id).unwrap();
+    let certificate = decode_certificate(&res_certificate.certificate.certificate_cbor_hex);
+    assert_eq!(certificate.user_principal, guardian_principal);
+    assert_eq!(certificate.issuer_principal, Some(coach_principal));
+    assert_eq!(
+        certificate.managed_user_id,
+        Some(MANAGED_USER_DB_ID.to_string())
+    );
+
+    // users who cannot issue certificates to others cannot issue them to managed users,
+    // not even to themselves as guardians
+    let res = create_certificate(&env, guardian_principal, request(TEST_USER_DB_ID)).unwrap_err();
+    assert_eq!(
+        res,
+        ApiError::unauthorized("User cannot issue certificates to managed users")
+    );
+}
+
+#[test]
+fn test_managed_user_claims_certificates() {
+    let env = test_env::create_test_env();
+    let backend_principal = setup_config(&env);
+    let (auth_provider_key_pair, jwks) = initialize_auth_provider();
+    initialize_canister(&env, jwks);
+    let guardian_principal = create_user_with_roles(
+        &env,
+        &auth_provider_key_pair,
+        TEST_USER_SUB,
+        TEST_USER_DB_ID,
+        &["athlete"],
+    );
+
+    const MANAGED_USER_DB_ID: &str = "ccb31f93-1a16-4089-bc84-1822ae591da2";
+    let (certificate_id, name) = create_test_certificate_for_managed_user(
+        &env,
+        backend_principal,
+        TEST_USER_DB_ID.to_string(),
+        MANAGED_USER_DB_ID.to_string(),
+    );
+    let (guardian_certificate_id, _) =
+        create_test_certificate(&env, backend_principal, TEST_USER_DB_ID.to_string());
+
+    // the managed user signs up
+    let managed_user_principal = create_user_with_roles(
+        &env,
+        &auth_provider_key_pair,
+        "test_managed_user_sub",
+        MANAGED_USER_DB_ID,
+        &["athlete"],
+    );
+
+    let test = || {
+        let res = get_my_certificates(
+            &env,
+            managed_user_principal,
+            GetMyCertificatesRequest::default(),
+        )
+        .unwrap();
+        assert_eq!(res.certificates.len(), 1);
+        assert_eq!(res.certificates[0].id, certificate_id);
+
+        // the claim is certified as a new version under the principal of the managed user,
+        // while the previous version stays certified under the principal of the guardian
+        let certificate = assert_certificate_version_is_valid(
+            &env,
+            managed_user_principal,
+            &managed_user_principal,
+            certificate_id.clone(),
+            2,
+            2,
+        );
+        assert_eq!(certificate.user_principal, managed_user_principal);
+        assert_eq!(certificate.content.name, name);
+        assert_eq!(
+            certificate.managed_user_id,
+            Some(MANAGED_USER_DB_ID.to_string())
+        );
+        let previous_certificate = assert_certificate_version_is_valid(
+            &env,
+            managed_user_principal,
+            &guardian_principal,
+            certificate_id.clone(),
+            1,
+            2,
+        );
+        assert_eq!(previous_certificate.user_principal, guardian_principal);
+
+        // the guardian no longer has custody of the certificate
+        let res = get_certificate(&env, guardian_principal, certificate_id.clone()).unwrap_err();
+        assert_eq!(
+            res,
+            ApiError::unauthorized("User can only access their own certificates")
+        );
+        assert!(get_my_managed_users(&env, guardian_principal)
+            .unwrap()
+            .is_empty());
+        let res = get_user_certificates(
+            &env,
+            guardian_principal,
+            GetUserCertificatesRequest {
+                user_principal: Some(guardian_principal),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(res.certificates.len(), 1);
+        assert_eq!(res.certificates[0].id, guardian_certificate_id);
+    };
+
+    test();
+    upgrade_canister(&env);
+    test();
+
+    // the certificates are not claimed again on the next sign in
+    create_user_with_roles(
+        &env,
+        &auth_provider_key_pair,
+        "test_managed_user_sub",
+        MANAGED_USER_DB_ID,
+        &["athlete"],
+    );
+    let res = get_certificate(&env, managed_user_principal, certificate_id).unwrap();
+    assert_eq!(res.latest_version, 2);
+
+    let request = CreateCertificateRequest {
+        user_db_id: TEST_USER_DB_ID.to_string(),
+        content: certificate_content_request(),
+        managed_user_db_id: Some(MANAGED_USER_DB_ID.to_string()),
+        issuer_id: None,
+        achievement_id: None,
+    };
+    let res = create_certificate(&env, backend_principal, request).unwrap_err();
+    assert_eq!(
+        res,
+        ApiError::conflict("Managed user has already claimed their certificates")
+    );
+}
+
+#[test]
+fn test_managed_user_claims_certificates_in_batches() {
+    let env = test_env::create_test_env();
+    let backend_principal = setup_config(&env);
+    let (auth_provider_key_pair, jwks) = initialize_auth_provider();
+    initialize_canister(&env, jwks);
+    let guardian_principal = create_user_with_roles(
+        &env,
+        &auth_provider_key_pair,
+        TEST_USER_SUB,
+        TEST_USER_DB_ID,
+        &["athlete"],
+    );
+
+    const MANAGED_USER_DB_ID: &str = "ccb31f93-1a16-4089-bc84-1822ae591da2";
+    // more certificates than the ones transferred while the managed user signs in
+    let mut certificate_ids: Vec<String> = (0..25)
+        .map(|_| {
+            create_test_certificate_for_managed_user(
+                &env,
+                backend_principal,
+                TEST_USER_DB_ID.to_string(),
+                MANAGED_USER_DB_ID.to_string(),
+            )
+            .0
+        })
+        .collect();
+    certificate_ids.sort();
+
+    let managed_user_principal = create_user_with_roles(
+        &env,
+        &auth_provider_key_pair,
+        "test_managed_user_sub",
+        MANAGED_USER_DB_ID,
+        &["athlete"],
+    );
+    // the guardian is removed right away
+    assert!(get_my_managed_users(&env, guardian_principal)
+        .unwrap()
+        .is_empty());
+
+    // the rest of the certificates are transferred by a job
+    env.ticks(10);
+
+    let res = get_my_certificates(
+        &env,
+        managed_user_principal,
+        GetMyCertificatesRequest {
+            limit: Some(MAX_CERTIFICATES_PAGE_LIMIT),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    let mut claimed_certificate_ids: Vec<String> = res
+        .certificates
+        .into_iter()
+        .map(|certificate| certificate.id)
+        .collect();
+    claimed_certificate_ids.sort();
+    assert_eq!(claimed_certificate_ids, certificate_ids);
+
+    for certificate_id in certificate_ids {
+        let res = get_certificate(&env, managed_user_principal, certificate_id.clone()).unwrap();
+        assert_eq!(res.latest_version, 2);
+        let res = get_certificate(&env, guardian_principal, certificate_id).unwrap_err();
+        assert_eq!(
+            res,
+            ApiError::unauthorized("User can only access their own certificates")
+        );
+    }
+}
+
+#[test]
+fn test_get_certificate_as_vc() {
+    let env = test_env::create_test_env();
+    let backend_principal = setup_config(&env);
+    let user_identity = setup_user(&env, TEST_USER_SUB, TEST_USER_DB_ID);
+    let user_principal = user_identity.sender().unwrap();
+    let (certificate_id, name) =
+        create_test_certificate(&env, backend_principal, TEST_USER_DB_ID.to_string());
+
+    let test = |calling_principal: Principal, version: u32| {
+        let res = get_certificate_as_vc(&env, calling_principal, certificate_id.clone()).unwrap();
+        let credential = verify_certificate_credential(&env, &res, env.root_ic_key()).unwrap();
+
+        assert_eq!(
+            credential["@context"],
+            serde_json::json!(["https://www.w3.org/ns/credentials/v2"])
+        );
+        assert_eq!(
+            credential["type"],
+            serde_json::json!(["VerifiableCredential", "SportCertificateCredential"])
+        );
+        assert_eq!(credential["id"], format!("urn:uuid:{}", certificate_id));
+        assert_eq!(credential["validFrom"], "2023-12-31T23:00:00+00:00");
+        let subject = &credential["credentialSubject"];
+        assert_eq!(
+            subject["id"],
+            format!("did:icp:{}", user_principal.to_text())
+        );
+        assert_eq!(subject["certificate"]["version"], version);
+        assert_eq!(subject["certificate"]["sportCategory"], "Swimming");
+
+        // the signature doesn't verify a tampered credential
+        let tampered_res = GetCertificateAsVcResponse {
+            credential: res
+                .credential
+                .replace(&certificate_id, &Uuid::new_v4().to_string()),
+            ..res.clone()
+        };
+        verify_certificate_credential(&env, &tampered_res, env.root_ic_key()).unwrap_err();
+
+        credential
+    };
+
+    let credential = test(user_principal, 1);
+    assert_eq!(credential["credentialSubject"]["certificate"]["name"], name);
+    assert_eq!(test(backend_principal, 1), credential);
+
+    // the credential of the latest version is signed
+    let content = updated_certificate_content_request();
+    update_certificate(
+        &env,
+        user_principal,
+        UpdateCertificateRequest {
+            id: certificate_id.clone(),
+            content: content.clone(),
+        },
+    )
+    .unwrap();
+    let credential = test(user_principal, 2);
+    assert_eq!(
+        credential["credentialSubject"]["certificate"]["name"],
+        content.name
+    );
+
+    // the signatures are restored after an upgrade
+    upgrade_canister(&env);
+    assert_eq!(test(user_principal, 2), credential);
+
+    revoke_certificate(
+        &env,
+        user_principal,
+        RevokeCertificateRequest {
+            id: certificate_id.clone(),
+            reason: "Issued by mistake".to_string(),
+        },
+    )
+    .unwrap();
+    let res = get_certificate_as_vc(&env, user_principal, certificate_id).unwrap_err();
+    assert_eq!(
+        res,
+        ApiError::conflict("Revoked certificates cannot be exported as verifiable credentials")
+    );
+}
+
+#[test]
+fn test_get_certificate_as_vc_another_user() {
+    let env = test_env::create_test_env();
+    let backend_principal = setup_config(&env);
+    let (auth_provider_key_pair, jwks) = initialize_auth_provider();
+    initialize_canister(&env, jwks);
+    create_user(
+        &env,
+        &auth_provider_key_pair,
+        TEST_USER_SUB,
+        TEST_USER_DB_ID,
+    );
+    let another_user_principal = create_user(
+        &env,
+        &auth_provider_key_pair,
+        "another_test_sub",
+        "ccb31f93-1a16-4089-bc84-1822ae591da2",
+    )
+    .sender()
+    .unwrap();
+    let (certificate_id, _) =
+        create_test_certificate(&env, backend_principal, TEST_USER_DB_ID.to_string());
+
+    let res = get_certificate_as_vc(&env, another_user_principal, certificate_id).unwrap_err();
+
+    assert_eq!(
+        res,
+        ApiError::unauthorized("User can only access their own certificates")
+    );
+}
+
+#[test]
+fn test_get_certificate_not_found() {
+    let env = test_env::create_test_env();
+    let backend_principal = setup_config(&env);
+    setup_user(&env, TEST_USER_SUB, TEST_USER_DB_ID);
+
+    const TEST_CERTIFICATES_COUNT: usize = 10;
+
+    for _ in 0..TEST_CERTIFICATES_COUNT {
+        create_test_certificate(&env, backend_principal, TEST_USER_DB_ID.to_string());
+    }
+
+    let non_existing_certificate_id = "ccb31f93-1a16-4089-bc84-1822ae591da2";
+    let res = get_certificate(
+        &env,
+        backend_principal,
+        non_existing_certificate_id.to_string(),
+    )
+    .unwrap_err();
+    assert_eq!(res, ApiError::not_found("Certificate not found"));
+}
+
+#[test]
+fn test_get_certificate_another_user() {
+    let env = test_env::create_test_env();
+    let backend_principal = setup_config(&env);
+    let (auth_provider_key_pair, jwks) = initialize_auth_provider();
+    initialize_canister(&env, jwks);
+    let first_user_db_id = TEST_USER_DB_ID;
+    create_user(
+        &env,
+        &auth_provider_key_pair,
+        TEST_USER_SUB,
+        first_user_db_id,
+    );
+
+    let second_user_sub = "test_sub_2";
+    let second_user_db_id = "ccb31f93-1a16-4089-bc84-1822ae591da2";
+    let second_user_identity = create_user(
+        &env,
+        &auth_provider_key_pair,
+        second_user_sub,
+        second_user_db_id,
+    );
+
+    let (certificate_id, _) =
+        create_test_certificate(&env, backend_principal, first_user_db_id.to_string());
+
+    let second_user_principal = second_user_identity.sender().unwrap();
+    let res_principal = get_certificate(&env, second_user_principal, certificate_id).unwrap_err();
+    assert_eq!(
+        res_principal,
+        ApiError::unauthorized("User can only access their own certificates")
+    );
+
+    // managed user
+    const MANAGED_USER_DB_ID: &str = "ccb31f93-1a16-4089-bc84-1822ae591da2";
+    let (certificate_id, _) = create_test_certificate_for_managed_user(
+        &env,
+        backend_principal,
+        first_user_db_id.to_string(),
+        MANAGED_USER_DB_ID.to_string(),
+    );
+    let res_managed = get_certificate(&env, second_user_principal, certificate_id).unwrap_err();
+    assert_eq!(
+        res_managed,
+        ApiError::unauthorized("User can only access their own certificates")
+    );
+}
+
+#[test]
+fn test_get_certificate_admin() {
+    let env = test_env::create_test_env();
+    let backend_principal = setup_config(&env);
+    let (auth_provider_key_pair, jwks) = initialize_auth_provider();
+    initialize_canister(&env, jwks);
+    create_user(
+        &env,
+        &auth_provider_key_pair,
+        TEST_USER_SUB,
+        TEST_USER_DB_ID,
+    );
+    let admin_principal = create_user_with_roles(
+        &env,
+        &auth_provider_key_pair,
+        "test_admin_sub",
+        "ccb31f93-1a16-4089-bc84-1822ae591da2",
+        &["admin"],
+    );
+    let coach_principal = create_user_with_roles(
+        &env,
+        &auth_provider_key_pair,
+        "test_coach_sub",
+        "5b5a0a6e-6f4c-4d5f-9d0a-2f2a6b1c9e11",
+        &["coach"],
+    );
+
+    let (certificate_id, certificate_name) =
+        create_test_certificate(&env, backend_principal, TEST_USER_DB_ID.to_string());
+
+    // admins can read the certificates of all users
+    let res = get_certificate(&env, admin_principal, certificate_id.clone()).unwrap();
+    assert_eq!(res.certificate.id, certificate_id);
+    let res = get_user_certificates(
+        &env,
+        admin_principal,
+        GetUserCertificatesRequest {
+            user_db_id: Some(TEST_USER_DB_ID.to_string()),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    assert_eq!(res.certificates.len(), 1);
+    assert_eq!(res.certificates[0].id, certificate_id);
+    assert_eq!(res.certificates[0].name, certificate_name);
+
+    // coaches can't
+    let res = get_certificate(&env, coach_principal, certificate_id).unwrap_err();
+    assert_eq!(
+        res,
+        ApiError::unauthorized("User can only access their own certificates")
+    );
+}
+
+#[test]
+fn test_get_certificate_not_authorized() {
+    let env = test_env::create_test_env();
+    let backend_principal = setup_config(&env);
+    setup_user(&env, TEST_USER_SUB, TEST_USER_DB_ID);
+
+    let (certificate_id, _) =
+        create_test_certificate(&env, backend_principal, TEST_USER_DB_ID.to_string());
+
+    for sender in [
+        generate_random_identity().sender().unwrap(),
+        Principal::anonymous(),
+    ] {
+        let res = get_certificate(&env, sender, certificate_id.clone()).unwrap_err();
+        assert_eq!(
+            res,
+            ApiError::unauthorized("Caller is not the backend or a registered user")
+        );
+    }
+}
+
+#[test]
+fn test_revoke_certificate() {
+    let env = test_env::create_test_env();
+    let backend_principal = setup_config(&env);
+    let user_identity = setup_user(&env, TEST_USER_SUB, TEST_USER_DB_ID);
+    let user_principal = user_identity.sender().unwrap();
+
+    let test = |calling_principal: Principal| {
+        let (certificate_id, name) =
+            create_test_certificate(&env, backend_principal, TEST_USER_DB_ID.to_string());
+
+        let res = get_certificate(&env, calling_principal, certificate_id.clone()).unwrap();
+        assert!(decode_certificate(&res.certificate.certificate_cbor_hex)
+            .revocation
+            .is_none());
+
+        let reason = "Issued by mistake".to_string();
+        revoke_certificate(
+            &env,
+            calling_principal,
+            RevokeCertificateRequest {
+                id: certificate_id.clone(),
+                reason: reason.clone(),
+            },
+        )
+        .unwrap();
+
+        let res = get_certificate(&env, calling_principal, certificate_id.clone()).unwrap();
+        assert_eq!(res.certificate.version, 2);
+        assert_eq!(res.latest_version, 2);
+        let certificate = decode_certificate(&res.certificate.certificate_cbor_hex);
+        assert_eq!(certificate.content.name, name);
+        assert_eq!(certificate.revocation.unwrap().reason, reason);
+        assert_ic_certification_is_valid(
+            &env,
+            res.ic_certificate,
+            res.ic_certificate_witness.clone(),
+        );
+        assert_ic_certificate_tree_is_valid(
+            res.ic_certificate_witness,
+            &user_principal,
+            vec![res.certificate],
+        );
+
+        // the revoked version stays certified as it was issued
+        let res = get_certificate_version(&env, calling_principal, certificate_id.clone(), Some(1))
+            .unwrap();
+        assert_eq!(res.latest_version, 2);
+        assert!(decode_certificate(&res.certificate.certificate_cbor_hex)
+            .revocation
+            .is_none());
+        assert_ic_certification_is_valid(
+            &env,
+            res.ic_certificate,
+            res.ic_certificate_witness.clone(),
+        );
+        assert_ic_certificate_tree_is_valid(
+            res.ic_certificate_witness,
+            &user_principal,
+            vec![res.certificate],
+        );
+
+        // a certificate can only be revoked once
+        let res = revoke_certificate(
+            &env,
+            calling_principal,
+            RevokeCertificateRequest {
+                id: certificate_id.clone(),
+                reason,
+            },
+        )
+        .unwrap_err();
+        assert_eq!(res, ApiError::conflict("Certificate is already revoked"));
+
+        certificate_id
+    };
+
+    let revoked_by_user = test(user_principal);
+    let revoked_by_backend = test(backend_principal);
+
+    // check that the revocation is still certified after canister upgrade
+    upgrade_canister(&env);
+    for certificate_id in [revoked_by_user, revoked_by_backend] {
+        let res = get_certificate(&env, user_principal, certificate_id).unwrap();
+        assert!(decode_certificate(&res.certificate.certificate_cbor_hex)
+            .revocation
+            .is_some());
+        assert_ic_certification_is_valid(
+            &env,
+            res.ic_certificate,
+            res.ic_certificate_witness.clone(),
+        );
+        assert_ic_certificate_tree_is_valid(
+            res.ic_certificate_witness,
+            &user_principal,
+            vec![res.certificate],
+        );
+    }
+}
+
+#[test]
+fn test_revoke_certificate_invalid_request() {
+    let env = test_env::create_test_env();
+    let backend_principal = setup_config(&env);
+    setup_user(&env, TEST_USER_SUB, TEST_USER_DB_ID);
+
+    let (certificate_id, _) =
+        create_test_certificate(&env, backend_principal, TEST_USER_DB_ID.to_string());
+
+    // empty reason
+    let request = RevokeCertificateRequest {
+        id: certificate_id.clone(),
+        reason: "".to_string(),
+    };
+    let res = revoke_certificate(&env, backend_principal, request).unwrap_err();
+    assert_eq!(
+        res,
+        ApiError::validation("reason", "Revocation reason cannot be empty.")
+    );
+
+    // too long reason
+    let request = RevokeCertificateRequest {
+        id: certificate_id,
+        reason: "a".repeat(MAX_REVOCATION_REASON_CHARS_COUNT + 1),
+    };
+    let res = revoke_certificate(&env, backend_principal, request).unwrap_err();
+    assert_eq!(
+        res,
+        ApiError::validation(
+            "reason",
+            format!(
+                "Revocation reason cannot be longer than {} characters.",
+                MAX_REVOCATION_REASON_CHARS_COUNT
+            )
+        )
+    );
+
+    // not found
+    let request = RevokeCertificateRequest {
+        id: "ccb31f93-1a16-4089-bc84-1822ae591da2".to_string(),
+        reason: "reason".to_string(),
+    };
+    let res = revoke_certificate(&env, backend_principal, request).unwrap_err();
+    assert_eq!(res, ApiError::not_found("Certificate not found"));
+}
+
+#[test]
+fn test_revoke_certificate_another_user() {
+    let env = test_env::create_test_env();
+    let backend_principal = setup_config(&env);
+    let (auth_provider_key_pair, jwks) = initialize_auth_provider();
+    initialize_canister(&env, jwks);
+    create_user(
+        &env,
+        &auth_provider_key_pair,
+        TEST_USER_SUB,
+        TEST_USER_DB_ID,
+    );
+    let second_user_identity = create_user(
+        &env,
+        &auth_provider_key_pair,
+        "test_sub_2",
+        "ccb31f93-1a16-4089-bc84-1822ae591da2",
+    );
+
+    let (certificate_id, _) =
+        create_test_certificate(&env, backend_principal, TEST_USER_DB_ID.to_string());
+
+    let request = RevokeCertificateRequest {
+        id: certificate_id.clone(),
+        reason: "reason".to_string(),
+    };
+    let res = revoke_certificate(
+        &env,
+        second_user_identity.sender().unwrap(),
+        request.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(
+        res,
+        ApiError::unauthorized("User can only revoke their own certificates")
+    );
+
+    for sender in [
+        generate_random_identity().sender().unwrap(),
+        Principal::anonymous(),
+    ] {
+        let res = revoke_certificate(&env, sender, request.clone()).unwrap_err();
+        assert_eq!(
+            res,
+            ApiError::unauthorized("Caller is not the backend or a registered user")
+        );
+    }
+
+    let res = get_certificate(&env, backend_principal, certificate_id).unwrap();
+    assert!(decode_certificate(&res.certificate.certificate_cbor_hex)
+        .revocation
+        .is_none());
+}
+
+fn updated_certificate_content_request() -> CreateCertificateContentRequest {
+    CreateCertificateContentRequest {
+        name: "Test certificate (fixed)".to_string(),
+        notes: Some("Fixed test notes".to_string()),
+        issuer_club_name: Some("Swimming club Lugano".to_string()),
+        ..certificate_content_request()
+    }
+}
+
+fn assert_certificate_version_is_valid(
+    env: &TestEnv,
+    calling_principal: Principal,
+    user_principal: &Principal,
+    certificate_id: String,
+    version: u32,
+    latest_version: u32,
+) -> Certificate {
+    let res =
+        get_certificate_version(env, calling_principal, certificate_id, Some(version)).unwrap();
+    assert_eq!(res.certificate.version, version);
+    assert_eq!(res.latest_version, latest_version);
+    assert_ic_certification_is_valid(env, res.ic_certificate, res.ic_certificate_witness.clone());
+    assert_ic_certificate_tree_is_valid(
+        res.ic_certificate_witness,
+        user_principal,
+        vec![res.certificate.clone()],
+    );
+
+    decode_certificate(&res.certificate.certificate_cbor_hex)
+}
+
+#[test]
+fn test_update_certificate() {
+    let env = test_env::create_test_env();
+    let backend_principal = setup_config(&env);
+    let user_identity = setup_user(&env, TEST_USER_SUB, TEST_USER_DB_ID);
+    let user_principal = user_identity.sender().unwrap();
+
+    let test = |calling_principal: Principal| {
+        let (certificate_id, name) =
+            create_test_certificate(&env, backend_principal, TEST_USER_DB_ID.to_string());
+
+        let res = get_certificate(&env, calling_principal, certificate_id.clone()).unwrap();
+        assert_eq!(res.certificate.version, 1);
+        assert_eq!(res.latest_version, 1);
+        assert!(decode_certificate(&res.certificate.certificate_cbor_hex)
+            .updated_at
+            .is_none());
+
+        let content = updated_certificate_content_request();
+        let res = update_certificate(
+            &env,
+            calling_principal,
+            UpdateCertificateRequest {
+                id: certificate_id.clone(),
+                content: content.clone(),
+            },
+        )
+        .unwrap();
+        assert_eq!(res.version, 2);
+
+        // the latest version is returned by default
+        let res = get_certificate(&env, calling_principal, certificate_id.clone()).unwrap();
+        assert_eq!(res.certificate.version, 2);
+        assert_eq!(res.latest_version, 2);
+        let latest_certificate = decode_certificate(&res.certificate.certificate_cbor_hex);
+        assert_eq!(latest_certificate.content.name, content.name);
+        assert_eq!(latest_certificate.content.notes, content.notes);
+        assert_eq!(
+            latest_certificate.content.issuer_club_name,
+            content.issuer_club_name
+        );
+        assert!(latest_certificate.updated_at.is_some());
+
+        // both versions are certified
+        let first_version = assert_certificate_version_is_valid(
+            &env,
+            calling_principal,
+            &user_principal,
+            certificate_id.clone(),
+            1,
+            2,
+        );
+        assert_eq!(first_version.content.name, name);
+        assert!(first_version.updated_at.is_none());
+        let second_version = assert_certificate_version_is_valid(
+            &env,
+            calling_principal,
+            &user_principal,
+            certificate_id.clone(),
+            2,
+            2,
+        );
+        assert_eq!(second_version, latest_certificate);
+        assert_eq!(second_version.created_at, first_version.created_at);
+
+        let res = update_certificate(
+            &env,
+            calling_principal,
+            UpdateCertificateRequest {
+                id: certificate_id.clone(),
+                content: certificate_content_request(),
+            },
+        )
+        .unwrap();
+        assert_eq!(res.version, 3);
+
+        certificate_id
+    };
+
+    let updated_by_user = test(user_principal);
+    let updated_by_backend = test(backend_principal);
+
+    // check that all versions are still certified after canister upgrade
+    upgrade_canister(&env);
+    for certificate_id in [updated_by_user, updated_by_backend] {
+        for version in 1..=3 {
+            assert_certificate_version_is_valid(
+                &env,
+                user_principal,
+                &user_principal,
+                certificate_id.clone(),
+                version,
+                3,
+            );
+        }
+    }
+}
+
+#[test]
+fn test_update_certificate_invalid_request() {
+    let env = test_env::create_test_env();
+    let backend_principal = setup_config(&env);
+    let user_identity = setup_user(&env, TEST_USER_SUB, TEST_USER_DB_ID);
+    let user_principal = user_identity.sender().unwrap();
+
+    let (certificate_id, _) =
+        create_test_certificate(&env, backend_principal, TEST_USER_DB_ID.to_string());
+
+    // invalid content
+    let request = UpdateCertificateRequest {
+        id: certificate_id.clone(),
+        content: CreateCertificateContentRequest {
+            name: "".to_string(),
+            ..certificate_content_request()
+        },
+    };
+    let res = update_certificate(&env, backend_principal, request).unwrap_err();
+    assert_eq!(
+        res,
+        ApiError::validation("content.name", "Title cannot be empty.")
+    );
+
+    // not found
+    let request = UpdateCertificateRequest {
+        id: "ccb31f93-1a16-4089-bc84-1822ae591da2".to_string(),
+        content: updated_certificate_content_request(),
+    };
+    let res = update_certificate(&env, backend_principal, request).unwrap_err();
+    assert_eq!(res, ApiError::not_found("Certificate not found"));
+
+    // version not found
+    let res =
+        get_certificate_version(&env, user_principal, certificate_id.clone(), Some(2)).unwrap_err();
+    assert_eq!(res, ApiError::not_found("Certificate not found"));
+
+    // revoked
+    revoke_certificate(
+        &env,
+        backend_principal,
+        RevokeCertificateRequest {
+            id: certificate_id.clone(),
+            reason: "reason".to_string(),
+        },
+    )
+    .unwrap();
+    let request = UpdateCertificateRequest {
+        id: certificate_id.clone(),
+        content: updated_certificate_content_request(),
+    };
+    let res = update_certificate(&env, user_principal, request).unwrap_err();
+    assert_eq!(
+        res,
+        ApiError::conflict("Revoked certificates cannot be updated")
+    );
+
+    let res = get_certificate(&env, user_principal, certificate_id).unwrap();
+    assert_eq!(res.latest_version, 2);
+}
+
+#[test]
+fn test_update_certificate_another_user() {
+    let env = test_env::create_test_env();
+    let backend_principal = setup_config(&env);
+    let (auth_provider_key_pair, jwks) = initialize_auth_provider();
+    initialize_canister(&env, jwks);
+    create_user(
+        &env,
+        &auth_provider_key_pair,
+        TEST_USER_SUB,
+        TEST_USER_DB_ID,
+    );
+    let second_user_identity = create_user(
+        &env,
+        &auth_provider_key_pair,
+        "test_sub_2",
+        "ccb31f93-1a16-4089-bc84-1822ae591da2",
+    );
+
+    let (certificate_id, _) =
+        create_test_certificate(&env, backend_principal, TEST_USER_DB_ID.to_string());
+
+    let request = UpdateCertificateRequest {
+        id: certificate_id.clone(),
+        content: updated_certificate_content_request(),
+    };
+    let res = update_certificate(
+        &env,
+        second_user_identity.sender().unwrap(),
+        request.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(
+        res,
+        ApiError::unauthorized("User can only update their own certificates")
+    );
+
+    for sender in [
+        generate_random_identity().sender().unwrap(),
+        Principal::anonymous(),
+    ] {
+        let res = update_certificate(&env, sender, request.clone()).unwrap_err();
+        assert_eq!(
+            res,
+            ApiError::unauthorized("Caller is not the backend or a registered user")
+        );
+    }
+
+    let res = get_certificate(&env, backend_principal, certificate_id).unwrap();
+    assert_eq!(res.latest_version, 1);
+}
+
+#[test]
+fn test_update_and_revoke_certificate_issued_by_coach() {
+    let env = test_env::create_test_env();
+    setup_config(&env);
+    let (auth_provider_key_pair, jwks) = initialize_auth_provider();
+    initialize_canister(&env, jwks);
+    let athlete_principal = create_user_with_roles(
+        &env,
+        &auth_provider_key_pair,
+        TEST_USER_SUB,
+        TEST_USER_DB_ID,
+        &["athlete"],
+    );
+    let coach_principal = create_user_with_roles(
+        &env,
+        &auth_provider_key_pair,
+        "test_coach_sub",
+        "ccb31f93-1a16-4089-bc84-1822ae591da2",
+        &["coach"],
+    );
+
+    let request = CreateCertificateRequest {
+        user_db_id: TEST_USER_DB_ID.to_string(),
+        content: certificate_content_request(),
+        managed_user_db_id: None,
+        issuer_id: None,
+        achievement_id: None,
+    };
+    let certificate_id = create_certificate(&env, coach_principal, request)
+        .unwrap()
+        .id;
+
+    // the recipient cannot change a certificate issued by someone else
+    let update_request = UpdateCertificateRequest {
+        id: certificate_id.clone(),
+        content: updated_certificate_content_request(),
+    };
+    let res = update_certificate(&env, athlete_principal, update_request.clone()).unwrap_err();
+    assert_eq!(
+        res,
+        ApiError::unauthorized("User can only update their own certificates")
+    );
+    let revoke_request = RevokeCertificateRequest {
+        id: certificate_id.clone(),
+        reason: "Issued by mistake".to_string(),
+    };
+    let res = revoke_certificate(&env, athlete_principal, revoke_request.clone()).unwrap_err();
+    assert_eq!(
+        res,
+        ApiError::unauthorized("User can only revoke their own certificates")
+    );
+
+    // the issuer can
+    let res = update_certificate(&env, coach_principal, update_request).unwrap();
+    assert_eq!(res.version, 2);
+    revoke_certificate(&env, coach_principal, revoke_request).unwrap();
+
+    let res = get_certificate(&env, athlete_principal, certificate_id).unwrap();
+    let certificate = decode_certificate(&res.certificate.certificate_cbor_hex);
+    assert_eq!(
+        certificate.content.name,
+        updated_certificate_content_request().name
+    );
+    assert!(certificate.revocation.is_some());
+}
+
+fn verify_certificate_request(id: &str, share_token: Option<&str>) -> VerifyCertificateRequest {
+    VerifyCertificateRequest {
+        id: id.to_string(),
+        share_token: share_token.map(|t| t.to_string()),
+        version: None,
+    }
+}
+
+fn assert_verified_certificate_is_valid(
+    env: &TestEnv,
+    request: VerifyCertificateRequest,
+    user_principal: &Principal,
+) {
+    let res = verify_certificate(env, Principal::anonymous(), request).unwrap();
+    let certificate = decode_certificate(&res.certificate.certificate_cbor_hex);
+    assert_eq!(certificate.user_principal, *user_principal);
+    assert_ic_certification_is_valid(env, res.ic_certificate, res.ic_certificate_witness.clone());
+    assert_ic_certificate_tree_is_valid(
+        res.ic_certificate_witness,
+        user_principal,
+        vec![res.certificate],
+    );
+}
+
+fn assert_certificate_is_not_verifiable(env: &TestEnv, request: VerifyCertificateRequest) {
+    let res = verify_certificate(env, Principal::anonymous(), request).unwrap_err();
+    assert_eq!(res, ApiError::not_found("Certificate not found"));
+}
+
+#[test]
+fn test_verify_certificate_public() {
+    let env = test_env::create_test_env();
+    let backend_principal = setup_config(&env);
+    let user_identity = setup_user(&env, TEST_USER_SUB, TEST_USER_DB_ID);
+    let user_principal = user_identity.sender().unwrap();
+
+    let (certificate_id, _) =
+        create_test_certificate(&env, backend_principal, TEST_USER_DB_ID.to_string());
+
+    // certificates are private by default
+    assert_certificate_is_not_verifiable(&env, verify_certificate_request(&certificate_id, None));
+
+    set_certificate_public(
+        &env,
+        user_principal,
+        SetCertificatePublicRequest {
+            id: certificate_id.clone(),
+            is_public: true,
+        },
+    )
+    .unwrap();
+    assert_verified_certificate_is_valid(
+        &env,
+        verify_certificate_request(&certificate_id, None),
+        &user_principal,
+    );
+
+    // previous versions of a public certificate can be verified too
+    update_certificate(
+        &env,
+        user_principal,
+        UpdateCertificateRequest {
+            id: certificate_id.clone(),
+            content: updated_certificate_content_request(),
+        },
+    )
+    .unwrap();
+    let res = verify_certificate(
+        &env,
+        Principal::anonymous(),
+        VerifyCertificateRequest {
+            version: Some(1),
+            ..verify_certificate_request(&certificate_id, None)
+        },
+    )
+    .unwrap();
+    assert_eq!(res.certificate.version, 1);
+    assert_eq!(res.latest_version, 2);
+
+    // check that the sharing settings are still valid after canister upgrade
+    upgrade_canister(&env);
+    assert_verified_certificate_is_valid(
+        &env,
+        verify_certificate_request(&certificate_id, None),
+        &user_principal,
+    );
+
+    set_certificate_public(
+        &env,
+        backend_principal,
+        SetCertificatePublicRequest {
+            id: certificate_id.clone(),
+            is_public: false,
+        },
+    )
+    .unwrap();
+    assert_certificate_is_not_verifiable(&env, verify_certificate_request(&certificate_id, None));
+}
+
+#[test]
+fn test_verify_certificate_share_token() {
+    let env = test_env::create_test_env();
+    let backend_principal = setup_config(&env);
+    let user_identity = setup_user(&env, TEST_USER_SUB, TEST_USER_DB_ID);
+    let user_principal = user_identity.sender().unwrap();
+
+    let (certificate_id, _) =
+        create_test_certificate(&env, backend_principal, TEST_USER_DB_ID.to_string());
+
+    let share_token = create_certificate_share_token(&env, user_principal, certificate_id.clone())
+        .unwrap()
+        .share_token;
+    assert_verified_certificate_is_valid(
+        &env,
+        verify_certificate_request(&certificate_id, Some(&share_token)),
+        &user_principal,
+    );
+    assert_certificate_is_not_verifiable(&env, verify_certificate_request(&certificate_id, None));
+    assert_certificate_is_not_verifiable(
+        &env,
+        verify_certificate_request(&certificate_id, Some("invalid_share_token")),
+    );
+
+    // a new share token replaces the previous one
+    let new_share_token =
+        create_certificate_share_token(&env, backend_principal, certificate_id.clone())
+            .unwrap()
+            .share_token;
+    assert_ne!(new_share_token, share_token);
+    assert_certificate_is_not_verifiable(
+        &env,
+        verify_certificate_request(&certificate_id, Some(&share_token)),
+    );
+
+    // check that the share token is still valid after canister upgrade
+    upgrade_canister(&env);
+    assert_verified_certificate_is_valid(
+        &env,
+        verify_certificate_request(&certificate_id, Some(&new_share_token)),
+        &user_principal,
+    );
+
+    revoke_certificate_share_token(&env, user_principal, certificate_id.clone()).unwrap();
+    assert_certificate_is_not_verifiable(
+        &env,
+        verify_certificate_request(&certificate_id, Some(&new_share_token)),
+    );
+}
+
+#[test]
+fn test_verify_certificate_invalid_request() {
+    let env = test_env::create_test_env();
+    let backend_principal = setup_config(&env);
+    setup_user(&env, TEST_USER_SUB, TEST_USER_DB_ID);
+
+    let (certificate_id, _) =
+        create_test_certificate(&env, backend_principal, TEST_USER_DB_ID.to_string());
+
+    let res = verify_certificate(
+        &env,
+        Principal::anonymous(),
+        verify_certificate_request(&certificate_id, Some("")),
+    )
+    .unwrap_err();
+    assert_eq!(
+        res,
+        ApiError::validation("share_token", "Share token cannot be empty.")
+    );
+
+    let non_existing_certificate_id = "ccb31f93-1a16-4089-bc84-1822ae591da2";
+    assert_certificate_is_not_verifiable(
+        &env,
+        verify_certificate_request(non_existing_certificate_id, None),
+    );
+}
+
+#[test]
+fn test_share_certificate_another_user() {
+    let env = test_env::create_test_env();
+    let backend_principal = setup_config(&env);
+    let (auth_provider_key_pair, jwks) = initialize_auth_provider();
+    initialize_canister(&env, jwks);
+    create_user(
+        &env,
+        &auth_provider_key_pair,
+        TEST_USER_SUB,
+        TEST_USER_DB_ID,
+    );
+    let second_user_identity = create_user(
+        &env,
+        &auth_provider_key_pair,
+        "test_sub_2",
+        "ccb31f93-1a16-4089-bc84-1822ae591da2",
+    );
+    let second_user_principal = second_user_identity.sender().unwrap();
+
+    let (certificate_id, _) =
+        create_test_certificate(&env, backend_principal, TEST_USER_DB_ID.to_string());
+
+    let request = SetCertificatePublicRequest {
+        id: certificate_id.clone(),
+        is_public: true,
+    };
+    let res = set_certificate_public(&env, second_user_principal, request.clone()).unwrap_err();
+    assert_eq!(
+        res,
+        ApiError::unauthorized("User can only share their own certificates")
+    );
+    let res = create_certificate_share_token(&env, second_user_principal, certificate_id.clone())
+        .unwrap_err();
+    assert_eq!(
+        res,
+        ApiError::unauthorized("User can only share their own certificates")
+    );
+    let res = revoke_certificate_share_token(&env, second_user_principal, certificate_id.clone())
+        .unwrap_err();
+    assert_eq!(
+        res,
+        ApiError::unauthorized("User can only share their own certificates")
+    );
+
+    for sender in [
+        generate_random_identity().sender().unwrap(),
+        Principal::anonymous(),
+    ] {
+        let res = set_certificate_public(&env, sender, request.clone()).unwrap_err();
+        assert_eq!(
+            res,
+            ApiError::unauthorized("Caller is not the backend or a registered user")
+        );
+    }
+
+    assert_certificate_is_not_verifiable(&env, verify_certificate_request(&certificate_id, None));
+}
+
+fn upload_test_file(env: &TestEnv, sender: Principal, chunks: &[&[u8]]) -> String {
+    let size = chunks.iter().map(|chunk| chunk.len() as u64).sum();
+    let CreateUploadResponse { upload_id } = create_upload(
+        env,
+        sender,
+        CreateUploadRequest {
+            content_type: "image/png".to_string(),
+            size,
+        },
+    )
+    .unwrap();
+
+    for (index, chunk) in chunks.iter().enumerate() {
+        upload_chunk(
+            env,
+            sender,
+            UploadChunkRequest {
+                upload_id: upload_id.clone(),
+                index: index as u32,
+                content: ByteBuf::from(chunk.to_vec()),
+            },
+        )
+        .unwrap();
+    }
+
+    upload_id
+}
+
+fn get_file_request(file_hash: &str, share_token: Option<&str>) -> GetFileRequest {
+    GetFileRequest {
+        file_hash: file_hash.to_string(),
+        share_token: share_token.map(|t| t.to_string()),
+    }
+}
+
+fn assert_file_is_readable(
+    env: &TestEnv,
+    sender: Principal,
+    file_hash: &str,
+    share_token: Option<&str>,
+    chunks: &[&[u8]],
+) {
+    let file = get_file(env, sender, get_file_request(file_hash, share_token)).unwrap();
+    assert_eq!(
+        file,
+        GetFileResponse {
+            content_type: "image/png".to_string(),
+            size: chunks.concat().len() as u64,
+            chunks_count: chunks.len() as u32,
+        }
+    );
+
+    for (index, chunk) in chunks.iter().enumerate() {
+        let res = get_file_chunk(
+            env,
+            sender,
+            GetFileChunkRequest {
+                file_hash: file_hash.to_string(),
+                index: index as u32,
+                share_token: share_token.map(|t| t.to_string()),
+            },
+        )
+        .unwrap();
+        assert_eq!(res.content.as_slice(), *chunk);
+    }
+}
+
+fn assert_file_is_not_readable(
+    env: &TestEnv,
+    sender: Principal,
+    file_hash: &str,
+    share_token: Option<&str>,
+) {
+    let res = get_file(env, sender, get_file_request(file_hash, share_token)).unwrap_err();
+    assert_eq!(res, ApiError::not_found("File not found"));
+
+    let res = get_file_chunk(
+        env,
+        sender,
+        GetFileChunkRequest {
+            file_hash: file_hash.to_string(),
+            index: 0,
+            share_token: share_token.map(|t| t.to_string()),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(res, ApiError::not_found("File not found"));
+}
+
+#[test]
+fn test_upload_file() {
+    let env = test_env::create_test_env();
+    let backend_principal = setup_config(&env);
+    let user_identity = setup_user(&env, TEST_USER_SUB, TEST_USER_DB_ID);
+    let user_principal = user_identity.sender().unwrap();
+
+    let chunks: [&[u8]; 2] = [b"first chunk ", b"second chunk"];
+    let upload_id = upload_test_file(&env, backend_principal, &chunks);
+    let file_hash = commit_upload(&env, backend_principal, upload_id.clone())
+        .unwrap()
+        .file_hash;
+
+    let expected_file_hash = hex::encode(digest(&SHA256, &chunks.concat()));
+    assert_eq!(file_hash, expected_file_hash);
+
+    // the upload cannot be committed twice
+    let res = commit_upload(&env, backend_principal, upload_id).unwrap_err();
+    assert_eq!(res, ApiError::not_found("Upload not found"));
+
+    assert_file_is_readable(&env, backend_principal, &file_hash, None, &chunks);
+
+    // uploading the same content again returns the same file
+    let upload_id = upload_test_file(&env, user_principal, &chunks);
+    let res = commit_upload(&env, user_principal, upload_id).unwrap();
+    assert_eq!(res.file_hash, file_hash);
+
+    let res = get_file_chunk(
+        &env,
+        backend_principal,
+        GetFileChunkRequest {
+            file_hash: file_hash.clone(),
+            index: chunks.len() as u32,
+            share_token: None,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(res, ApiError::not_found("File chunk not found"));
+
+    let res = get_file(
+        &env,
+        backend_principal,
+        get_file_request(&hex::encode(digest(&SHA256, b"non existing file")), None),
+    )
+    .unwrap_err();
+    assert_eq!(res, ApiError::not_found("File not found"));
+
+    let res = get_file(&env, backend_principal, get_file_request("invalid", None)).unwrap_err();
+    assert!(matches!(res, ApiError::Validation { .. }));
+
+    // the file survives an upgrade
+    upgrade_canister(&env);
+    let file = get_file(&env, backend_principal, get_file_request(&file_hash, None)).unwrap();
+    assert_eq!(file.chunks_count, chunks.len() as u32);
 }
 
 #[test]
-fn test_get_user_certificates_not_authorized() {
+fn test_read_file_access() {
     let env = test_env::create_test_env();
     let backend_principal = setup_config(&env);
-    let user_identity = setup_user(&env, TEST_USER_SUB, TEST_USER_DB_ID);
-    let user_principal = user_identity.sender().unwrap();
+    let (auth_provider_key_pair, jwks) = initialize_auth_provider();
+    initialize_canister(&env, jwks);
+    let user_principal = create_user(
+        &env,
+        &auth_provider_key_pair,
+        TEST_USER_SUB,
+        TEST_USER_DB_ID,
+    )
+    .sender()
+    .unwrap();
+    let second_user_principal = create_user(
+        &env,
+        &auth_provider_key_pair,
+        "test_sub_2",
+        "ccb31f93-1a16-4089-bc84-1822ae591da2",
+    )
+    .sender()
+    .unwrap();
 
-    const TEST_CERTIFICATES_COUNT: usize = 10;
-    for _ in 0..TEST_CERTIFICATES_COUNT {
-        create_test_certificate(&env, backend_principal, TEST_USER_DB_ID.to_string());
-    }
+    let chunks: [&[u8]; 1] = [b"certificate file"];
+    let upload_id = upload_test_file(&env, backend_principal, &chunks);
+    let file_hash = commit_upload(&env, backend_principal, upload_id)
+        .unwrap()
+        .file_hash;
 
+    // a file that no certificate references can only be read by the backend
     for sender in [
-        generate_random_identity().sender().unwrap(),
+        user_principal,
+        second_user_principal,
         Principal::anonymous(),
     ] {
-        let request = GetUserCertificatesRequest {
-            user_principal: Some(user_principal),
-            user_db_id: None,
-        };
-        let res = get_user_certificates(&env, sender, request).unwrap_err();
-        assert!(
-            extract_trap_message(res).contains("Caller is not the backend or a registered user")
-        );
+        assert_file_is_not_readable(&env, sender, &file_hash, None);
+    }
+
+    let request = CreateCertificateRequest {
+        user_db_id: TEST_USER_DB_ID.to_string(),
+        content: CreateCertificateContentRequest {
+            file_uri: None,
+            file_hash: Some(file_hash.clone()),
+            ..certificate_content_request()
+        },
+        managed_user_db_id: None,
+        issuer_id: None,
+        achievement_id: None,
+    };
+    let certificate_id = create_certificate(&env, backend_principal, request)
+        .unwrap()
+        .id;
+
+    // the file can be read by whoever can read a certificate that references it
+    assert_file_is_readable(&env, user_principal, &file_hash, None, &chunks);
+    for sender in [second_user_principal, Principal::anonymous()] {
+        assert_file_is_not_readable(&env, sender, &file_hash, None);
+    }
+
+    let share_token = create_certificate_share_token(&env, user_principal, certificate_id.clone())
+        .unwrap()
+        .share_token;
+    for sender in [second_user_principal, Principal::anonymous()] {
+        assert_file_is_readable(&env, sender, &file_hash, Some(&share_token), &chunks);
+        assert_file_is_not_readable(&env, sender, &file_hash, Some("invalid_share_token"));
+    }
+    revoke_certificate_share_token(&env, user_principal, certificate_id.clone()).unwrap();
+    assert_file_is_not_readable(&env, Principal::anonymous(), &file_hash, Some(&share_token));
+
+    set_certificate_public(
+        &env,
+        user_principal,
+        SetCertificatePublicRequest {
+            id: certificate_id,
+            is_public: true,
+        },
+    )
+    .unwrap();
+    for sender in [second_user_principal, Principal::anonymous()] {
+        assert_file_is_readable(&env, sender, &file_hash, None, &chunks);
     }
 }
 
 #[test]
-fn test_get_user_certificates() {
+fn test_upload_limit_per_owner() {
     let env = test_env::create_test_env();
     let backend_principal = setup_config(&env);
-    let user_identity = setup_user(&env, TEST_USER_SUB, TEST_USER_DB_ID);
-    let user_principal = user_identity.sender().unwrap();
+    let user_principal = setup_user(&env, TEST_USER_SUB, TEST_USER_DB_ID)
+        .sender()
+        .unwrap();
 
-    const TEST_CERTIFICATES_COUNT: usize = 10;
-    let mut created_certificates_previews = vec![];
-    for _ in 0..TEST_CERTIFICATES_COUNT {
-        let res = create_test_certificate(&env, backend_principal, TEST_USER_DB_ID.to_string());
-        created_certificates_previews.push(res);
+    let request = CreateUploadRequest {
+        content_type: "image/png".to_string(),
+        size: 5,
+    };
+    let upload_ids: Vec<_> = (0..3)
+        .map(|_| {
+            create_upload(&env, user_principal, request.clone())
+                .unwrap()
+                .upload_id
+        })
+        .collect();
+
+    let res = create_upload(&env, user_principal, request.clone()).unwrap_err();
+    assert_eq!(
+        res,
+        ApiError::conflict("User cannot have more than 3 uploads in progress.")
+    );
+
+    // the limit is per owner
+    create_upload(&env, backend_principal, request.clone()).unwrap();
+
+    // committing an upload frees a slot
+    upload_chunk(
+        &env,
+        user_principal,
+        UploadChunkRequest {
+            upload_id: upload_ids[0].clone(),
+            index: 0,
+            content: ByteBuf::from(b"abcde".to_vec()),
+        },
+    )
+    .unwrap();
+    commit_upload(&env, user_principal, upload_ids[0].clone()).unwrap();
+    create_upload(&env, user_principal, request.clone()).unwrap();
+
+    // the expired uploads are removed and free their slots
+    env.pic()
+        .advance_time(std::time::Duration::from_secs(2 * 60 * 60));
+    env.pic().tick();
+    let res = upload_chunk(
+        &env,
+        user_principal,
+        UploadChunkRequest {
+            upload_id: upload_ids[1].clone(),
+            index: 0,
+            content: ByteBuf::from(b"abcde".to_vec()),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(res, ApiError::not_found("Upload not found"));
+    for _ in 0..3 {
+        create_upload(&env, user_principal, request.clone()).unwrap();
     }
+}
 
-    let test = |calling_principal: Principal| {
-        // by user principal
-        let request = GetUserCertificatesRequest {
-            user_principal: Some(user_principal),
-            user_db_id: None,
-        };
-        let res_by_principal = get_user_certificates(&env, calling_principal, request).unwrap();
-        assert_eq!(res_by_principal.certificates.len(), TEST_CERTIFICATES_COUNT);
-        // check that all certificates are present
-        for (id, name) in created_certificates_previews.iter() {
-            let certificate = res_by_principal
-                .certificates
-                .iter()
-                .find(|c| c.id == *id)
-                .unwrap();
-            assert_eq!(certificate.name, *name);
-        }
+#[test]
+fn test_upload_file_invalid_request() {
+    let env = test_env::create_test_env();
+    let backend_principal = setup_config(&env);
 
-        // by user db id
-        let request = GetUserCertificatesRequest {
-            user_principal: None,
-            user_db_id: Some(TEST_USER_DB_ID.to_string()),
-        };
-        let res_by_id = get_user_certificates(&env, calling_principal, request).unwrap();
-        assert_eq!(res_by_id.certificates.len(), TEST_CERTIFICATES_COUNT);
-        // check that all certificates are present
-        for (id, name) in created_certificates_previews.iter() {
-            let certificate = res_by_id.certificates.iter().find(|c| c.id == *id).unwrap();
-            assert_eq!(certificate.name, *name);
-        }
+    let request = CreateUploadRequest {
+        content_type: "text/html".to_string(),
+        size: 10,
     };
+    let res = create_upload(&env, backend_principal, request).unwrap_err();
+    assert_eq!(
+        res,
+        ApiError::validation(
+            "content_type",
+            format!(
+                "Content type must be one of: {}.",
+                ALLOWED_FILE_CONTENT_TYPES.join(", ")
+            )
+        )
+    );
 
-    test(user_principal);
-    test(backend_principal);
-    // check that certification is still valid after canister upgrade
-    upgrade_canister(&env);
-    test(user_principal);
-    test(backend_principal);
+    let request = CreateUploadRequest {
+        content_type: "image/png".to_string(),
+        size: 0,
+    };
+    let res = create_upload(&env, backend_principal, request).unwrap_err();
+    assert_eq!(res, ApiError::validation("size", "File cannot be empty."));
+
+    let request = CreateUploadRequest {
+        content_type: "image/png".to_string(),
+        size: MAX_FILE_BYTES_SIZE as u64 + 1,
+    };
+    let res = create_upload(&env, backend_principal, request).unwrap_err();
+    assert_eq!(
+        res,
+        ApiError::validation(
+            "size",
+            format!(
+                "File bytes cannot be longer than {} bytes.",
+                MAX_FILE_BYTES_SIZE
+            )
+        )
+    );
+
+    let upload_id = upload_test_file(&env, backend_principal, &[b"chunk".as_slice()]);
+    let request = UploadChunkRequest {
+        upload_id: upload_id.clone(),
+        index: 0,
+        content: ByteBuf::new(),
+    };
+    let res = upload_chunk(&env, backend_principal, request).unwrap_err();
+    assert_eq!(
+        res,
+        ApiError::validation("content", "Chunk cannot be empty.")
+    );
+
+    let request = UploadChunkRequest {
+        upload_id: upload_id.clone(),
+        index: 0,
+        content: ByteBuf::from(vec![0; MAX_FILE_CHUNK_BYTES_SIZE + 1]),
+    };
+    let res = upload_chunk(&env, backend_principal, request).unwrap_err();
+    assert_eq!(
+        res,
+        ApiError::validation(
+            "content",
+            format!(
+                "Chunk bytes cannot be longer than {} bytes.",
+                MAX_FILE_CHUNK_BYTES_SIZE
+            )
+        )
+    );
+
+    let request = UploadChunkRequest {
+        upload_id: Uuid::new_v4().to_string(),
+        index: 0,
+        content: ByteBuf::from(b"chunk".to_vec()),
+    };
+    let res = upload_chunk(&env, backend_principal, request).unwrap_err();
+    assert_eq!(res, ApiError::not_found("Upload not found"));
 }
 
 #[test]
-fn test_get_user_certificates_another_user() {
+fn test_upload_file_incomplete() {
     let env = test_env::create_test_env();
     let backend_principal = setup_config(&env);
-    let (auth_provider_key_pair, jwks) = initialize_auth_provider();
-    initialize_canister(&env, jwks);
-    let first_user_db_id = TEST_USER_DB_ID;
-    let first_user_identity = create_user(
+
+    let CreateUploadResponse { upload_id } = create_upload(
         &env,
-        &auth_provider_key_pair,
-        TEST_USER_SUB,
-        first_user_db_id,
+        backend_principal,
+        CreateUploadRequest {
+            content_type: "application/pdf".to_string(),
+            size: 6,
+        },
+    )
+    .unwrap();
+    let chunk_request = |index: u32, content: &[u8]| UploadChunkRequest {
+        upload_id: upload_id.clone(),
+        index,
+        content: ByteBuf::from(content.to_vec()),
+    };
+
+    upload_chunk(&env, backend_principal, chunk_request(0, b"ab")).unwrap();
+    let res = commit_upload(&env, backend_principal, upload_id.clone()).unwrap_err();
+    assert_eq!(
+        res,
+        ApiError::conflict("Uploaded bytes (2) do not match the file size of 6 bytes.")
     );
-    let first_user_principal = first_user_identity.sender().unwrap();
 
-    let second_user_sub = "test_sub_2";
-    let second_user_db_id = "ccb31f93-1a16-4089-bc84-1822ae591da2";
-    let second_user_identity = create_user(
-        &env,
-        &auth_provider_key_pair,
-        second_user_sub,
-        second_user_db_id,
+    let res = upload_chunk(&env, backend_principal, chunk_request(1, b"cdefg")).unwrap_err();
+    assert_eq!(
+        res,
+        ApiError::validation(
+            "content",
+            "Uploaded bytes cannot be more than the file size of 6 bytes."
+        )
     );
 
-    const TEST_CERTIFICATES_COUNT: usize = 10;
+    // a missing chunk in the middle
+    upload_chunk(&env, backend_principal, chunk_request(2, b"cdef")).unwrap();
+    let res = commit_upload(&env, backend_principal, upload_id.clone()).unwrap_err();
+    assert_eq!(
+        res,
+        ApiError::conflict("Uploaded chunks must be contiguous, starting from 0.")
+    );
 
-    for _ in 0..TEST_CERTIFICATES_COUNT {
-        create_test_certificate(&env, backend_principal, first_user_db_id.to_string());
+    // replacing a chunk fixes the upload
+    upload_chunk(&env, backend_principal, chunk_request(2, b"ef")).unwrap();
+    upload_chunk(&env, backend_principal, chunk_request(1, b"cd")).unwrap();
+    let res = commit_upload(&env, backend_principal, upload_id).unwrap();
+    assert_eq!(res.file_hash, hex::encode(digest(&SHA256, b"abcdef")));
+}
+
+#[test]
+fn test_upload_file_another_user() {
+    let env = test_env::create_test_env();
+    let backend_principal = setup_config(&env);
+    let user_identity = setup_user(&env, TEST_USER_SUB, TEST_USER_DB_ID);
+    let user_principal = user_identity.sender().unwrap();
+
+    let upload_id = upload_test_file(&env, user_principal, &[b"chunk".as_slice()]);
+
+    let res = upload_chunk(
+        &env,
+        backend_principal,
+        UploadChunkRequest {
+            upload_id: upload_id.clone(),
+            index: 0,
+            content: ByteBuf::from(b"other".to_vec()),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        res,
+        ApiError::unauthorized("User can only access their own uploads")
+    );
+    let res = commit_upload(&env, backend_principal, upload_id.clone()).unwrap_err();
+    assert_eq!(
+        res,
+        ApiError::unauthorized("User can only access their own uploads")
+    );
+
+    for sender in [
+        generate_random_identity().sender().unwrap(),
+        Principal::anonymous(),
+    ] {
+        let res = commit_upload(&env, sender, upload_id.clone()).unwrap_err();
+        assert_eq!(
+            res,
+            ApiError::unauthorized("Caller is not the backend or a registered user")
+        );
+        let res = create_upload(
+            &env,
+            sender,
+            CreateUploadRequest {
+                content_type: "image/png".to_string(),
+                size: 5,
+            },
+        )
+        .unwrap_err();
+        assert_eq!(
+            res,
+            ApiError::unauthorized("Caller is not the backend or a registered user")
+        );
     }
 
-    let second_user_principal = second_user_identity.sender().unwrap();
-    // by user principal
-    let request = GetUserCertificatesRequest {
-        user_principal: Some(first_user_principal),
-        user_db_id: None,
+    commit_upload(&env, user_principal, upload_id).unwrap();
+}
+
+#[test]
+fn test_create_certificate_with_file() {
+    let env = test_env::create_test_env();
+    let backend_principal = setup_config(&env);
+    setup_user(&env, TEST_USER_SUB, TEST_USER_DB_ID);
+
+    let upload_id = upload_test_file(&env, backend_principal, &[b"certificate file".as_slice()]);
+    let file_hash = commit_upload(&env, backend_principal, upload_id)
+        .unwrap()
+        .file_hash;
+
+    let request = CreateCertificateRequest {
+        user_db_id: TEST_USER_DB_ID.to_string(),
+        content: CreateCertificateContentRequest {
+            file_uri: None,
+            file_hash: Some(file_hash.clone()),
+            ..certificate_content_request()
+        },
+        managed_user_db_id: None,
+        issuer_id: None,
+        achievement_id: None,
     };
-    let res_principal = get_user_certificates(&env, second_user_principal, request).unwrap_err();
-    assert!(
-        extract_trap_message(res_principal).contains("User can only access their own certificates")
+    let res = create_certificate(&env, backend_principal, request).unwrap();
+    let res_certificate = get_certificate(&env, backend_principal, res.id.clone()).unwrap();
+    let certificate = decode_certificate(&res_certificate.certificate.certificate_cbor_hex);
+    assert_eq!(certificate.content.file_hash, Some(file_hash));
+    assert_eq!(certificate.content.file_uri, None);
+    assert_ic_certification_is_valid(
+        &env,
+        res_certificate.ic_certificate,
+        res_certificate.ic_certificate_witness,
     );
 
-    // by user db id
-    let request = GetUserCertificatesRequest {
-        user_principal: None,
-        user_db_id: Some(first_user_db_id.to_string()),
+    let non_existing_file_hash = hex::encode(digest(&SHA256, b"non existing file"));
+    let content = CreateCertificateContentRequest {
+        file_uri: None,
+        file_hash: Some(non_existing_file_hash.clone()),
+        ..certificate_content_request()
     };
-    let res_db_id = get_user_certificates(&env, second_user_principal, request).unwrap_err();
-    assert!(extract_trap_message(res_db_id).contains("User can only access their own certificates"));
+    let request = CreateCertificateRequest {
+        user_db_id: TEST_USER_DB_ID.to_string(),
+        content: content.clone(),
+        managed_user_db_id: None,
+        issuer_id: None,
+        achievement_id: None,
+    };
+    let res_err = create_certificate(&env, backend_principal, request).unwrap_err();
+    assert_eq!(
+        res_err,
+        ApiError::not_found(format!("File {} does not exist", non_existing_file_hash))
+    );
 
-    // managed user
-    const MANAGED_USER_DB_ID: &str = "ccb31f93-1a16-4089-bc84-1822ae591da2";
-    create_test_certificate_for_managed_user(
-        &env,
-        backend_principal,
-        first_user_db_id.to_string(),
-        MANAGED_USER_DB_ID.to_string(),
+    let request = UpdateCertificateRequest {
+        id: res.id,
+        content,
+    };
+    let res_err = update_certificate(&env, backend_principal, request).unwrap_err();
+    assert_eq!(
+        res_err,
+        ApiError::not_found(format!("File {} does not exist", non_existing_file_hash))
     );
-    let request = GetUserCertificatesRequest {
-        user_principal: None,
-        user_db_id: Some(MANAGED_USER_DB_ID.to_string()),
+
+    let request = CreateCertificateRequest {
+        user_db_id: TEST_USER_DB_ID.to_string(),
+        content: CreateCertificateContentRequest {
+            file_hash: Some("invalid".to_string()),
+            file_uri: None,
+            ..certificate_content_request()
+        },
+        managed_user_db_id: None,
+        issuer_id: None,
+        achievement_id: None,
+    };
+    let res_err = create_certificate(&env, backend_principal, request).unwrap_err();
+    assert_eq!(
+        res_err,
+        ApiError::validation(
+            "content.file_hash",
+            "Failed to parse file hash from string: invalid"
+        )
+    );
+
+    let request = CreateCertificateRequest {
+        user_db_id: TEST_USER_DB_ID.to_string(),
+        content: CreateCertificateContentRequest {
+            file_hash: Some(non_existing_file_hash),
+            ..certificate_content_request()
+        },
+        managed_user_db_id: None,
+        issuer_id: None,
+        achievement_id: None,
     };
-    let res_managed = get_user_certificates(&env, second_user_principal, request).unwrap();
-    assert_eq!(res_managed.certificates.len(), 0);
+    let res_err = create_certificate(&env, backend_principal, request).unwrap_err();
+    assert_eq!(
+        res_err,
+        ApiError::validation(
+            "content.file_hash",
+            "File URI and file hash cannot be both set."
+        )
+    );
 }
 
-#[test]
-fn test_get_user_certificates_no_user() {
-    let env = test_env::create_test_env();
-    let backend_principal = setup_config(&env);
-    setup_user(&env, TEST_USER_SUB, TEST_USER_DB_ID);
-
-    const TEST_CERTIFICATES_COUNT: usize = 10;
+fn get_http_response(env: &TestEnv, url: &str) -> HttpResponse {
+    let request = HttpRequest {
+        method: "GET".to_string(),
+        url: url.to_string(),
+        headers: vec![],
+        body: vec![].into(),
+        certificate_version: Some(2),
+    };
+    let response = http_request(env, Principal::anonymous(), request.clone()).unwrap();
+    assert_http_response_is_valid(env, &request, &response);
 
-    for _ in 0..TEST_CERTIFICATES_COUNT {
-        create_test_certificate(&env, backend_principal, TEST_USER_DB_ID.to_string());
-    }
+    response
+}
 
-    // by user principal
-    let non_existing_user_principal = generate_random_identity().sender().unwrap();
-    let request = GetUserCertificatesRequest {
-        user_principal: Some(non_existing_user_principal),
-        user_db_id: None,
-    };
-    let res_principal = get_user_certificates(&env, backend_principal, request).unwrap();
-    assert_eq!(res_principal.certificates.len(), 0);
+fn http_response_header<'a>(response: &'a HttpResponse, name: &str) -> Option<&'a str> {
+    response
+        .headers
+        .iter()
+        .find(|(header_name, _)| header_name.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.as_str())
+}
 
-    // by user db id
-    let non_existing_user_db_id = "ccb31f93-1a16-4089-bc84-1822ae591da2";
-    let request = GetUserCertificatesRequest {
-        user_principal: None,
-        user_db_id: Some(non_existing_user_db_id.to_string()),
-    };
-    let res_db_id = get_user_certificates(&env, backend_principal, request).unwrap();
-    assert_eq!(res_db_id.certificates.len(), 0);
+fn assert_http_response_is_not_found(env: &TestEnv, url: &str) {
+    let response = get_http_response(env, url);
+    assert_eq!(response.status_code, 404);
 }
 
 #[test]
-fn test_get_user_certificates_managed_user() {
+fn test_http_request_public_certificate() {
     let env = test_env::create_test_env();
     let backend_principal = setup_config(&env);
     let user_identity = setup_user(&env, TEST_USER_SUB, TEST_USER_DB_ID);
     let user_principal = user_identity.sender().unwrap();
 
-    const MANAGED_USER_DB_ID: &str = "ccb31f93-1a16-4089-bc84-1822ae591da2";
+    let (certificate_id, name) =
+        create_test_certificate(&env, backend_principal, TEST_USER_DB_ID.to_string());
+    let html_url = format!("/certificates/{}", certificate_id);
+    let json_url = format!("/certificates/{}.json", certificate_id);
 
-    const TEST_CERTIFICATES_COUNT: usize = 10;
-    let mut created_certificates_previews = vec![];
-    for _ in 0..TEST_CERTIFICATES_COUNT {
-        let res = create_test_certificate_for_managed_user(
-            &env,
-            backend_principal,
-            TEST_USER_DB_ID.to_string(),
-            MANAGED_USER_DB_ID.to_string(),
+    set_certificate_public(
+        &env,
+        user_principal,
+        SetCertificatePublicRequest {
+            id: certificate_id.clone(),
+            is_public: true,
+        },
+    )
+    .unwrap();
+
+    let test = |expected_version: u64, expected_name: &str| {
+        let html_response = get_http_response(&env, &html_url);
+        assert_eq!(html_response.status_code, 200);
+        assert_eq!(
+            http_response_header(&html_response, "Content-Type"),
+            Some("text/html; charset=utf-8")
         );
-        created_certificates_previews.push(res);
-    }
+        let html = str::from_utf8(&html_response.body).unwrap();
+        assert!(html.contains(&format!("<h1>{}</h1>", expected_name)));
 
-    let test = |calling_principal: Principal| {
-        let request = GetUserCertificatesRequest {
-            user_principal: None,
-            user_db_id: Some(MANAGED_USER_DB_ID.to_string()),
-        };
-        let res = get_user_certificates(&env, calling_principal, request).unwrap();
-        assert_eq!(res.certificates.len(), TEST_CERTIFICATES_COUNT);
-        // check that all certificates are present
-        for (id, name) in created_certificates_previews.iter() {
-            let certificate = res.certificates.iter().find(|c| c.id == *id).unwrap();
-            assert_eq!(certificate.name, *name);
-        }
+        let json_response = get_http_response(&env, &json_url);
+        assert_eq!(json_response.status_code, 200);
+        assert_eq!(
+            http_response_header(&json_response, "Content-Type"),
+            Some("application/json")
+        );
+        let json: serde_json::Value = serde_json::from_slice(&json_response.body).unwrap();
+        assert_eq!(json["id"], certificate_id);
+        assert_eq!(json["version"], expected_version);
+        assert_eq!(json["certificate"]["content"]["name"], expected_name);
+        assert_eq!(
+            json["certificate"]["user_principal"],
+            user_principal.to_text()
+        );
     };
 
-    test(user_principal);
-    test(backend_principal);
-}
+    test(1, &name);
 
-#[test]
-fn test_get_certificate() {
-    let env = test_env::create_test_env();
-    let backend_principal = setup_config(&env);
-    let user_identity = setup_user(&env, TEST_USER_SUB, TEST_USER_DB_ID);
-    let user_principal = user_identity.sender().unwrap();
+    let updated_content = updated_certificate_content_request();
+    update_certificate(
+        &env,
+        user_principal,
+        UpdateCertificateRequest {
+            id: certificate_id.clone(),
+            content: updated_content.clone(),
+        },
+    )
+    .unwrap();
+    test(2, &updated_content.name);
 
-    const TEST_CERTIFICATES_COUNT: usize = 10;
-    let mut created_certificates_previews = vec![];
-    for _ in 0..TEST_CERTIFICATES_COUNT {
-        let res = create_test_certificate(&env, backend_principal, TEST_USER_DB_ID.to_string());
-        created_certificates_previews.push(res);
-    }
+    // query string is ignored
+    let response = get_http_response(&env, &format!("{}?utm_source=qr", html_url));
+    assert_eq!(response.status_code, 200);
 
-    let test = |calling_principal: Principal| {
-        for (id, name) in created_certificates_previews.iter() {
-            let res_by_id = get_certificate(&env, calling_principal, id.clone()).unwrap();
-            let certificate = decode_certificate(&res_by_id.certificate.certificate_cbor_hex);
-            assert_eq!(certificate.user_principal, user_principal);
-            assert_eq!(certificate.content.name, *name);
-            assert_ic_certification_is_valid(
-                &env,
-                res_by_id.ic_certificate,
-                res_by_id.ic_certificate_witness.clone(),
-            );
-            assert_ic_certificate_tree_is_valid(
-                res_by_id.ic_certificate_witness,
-                &user_principal,
-                vec![res_by_id.certificate],
-            );
-        }
-    };
+    // check that the responses are still certified after canister upgrade
+    upgrade_canister(&env);
+    test(2, &updated_content.name);
 
-    test(user_principal);
-    test(backend_principal);
+    revoke_certificate(
+        &env,
+        user_principal,
+        RevokeCertificateRequest {
+            id: certificate_id.clone(),
+            reason: "reason".to_string(),
+        },
+    )
+    .unwrap();
+    let html_response = get_http_response(&env, &html_url);
+    assert_eq!(html_response.status_code, 200);
+    assert!(str::from_utf8(&html_response.body)
+        .unwrap()
+        .contains("This certificate has been revoked."));
+
+    set_certificate_public(
+        &env,
+        user_principal,
+        SetCertificatePublicRequest {
+            id: certificate_id.clone(),
+            is_public: false,
+        },
+    )
+    .unwrap();
+    assert_http_response_is_not_found(&env, &html_url);
+    assert_http_response_is_not_found(&env, &json_url);
 }
 
 #[test]
-fn test_get_certificate_managed_user() {
+fn test_http_request_open_badge() {
     let env = test_env::create_test_env();
     let backend_principal = setup_config(&env);
     let user_identity = setup_user(&env, TEST_USER_SUB, TEST_USER_DB_ID);
     let user_principal = user_identity.sender().unwrap();
 
-    const MANAGED_USER_DB_ID: &str = "ccb31f93-1a16-4089-bc84-1822ae591da2";
-    let (certificate_id, name) = create_test_certificate_for_managed_user(
+    let request = achievement_request(None);
+    let achievement_id = create_achievement(&env, backend_principal, request.clone())
+        .unwrap()
+        .id;
+    let content = certificate_content_request();
+    let certificate_id = create_certificate(
         &env,
         backend_principal,
-        TEST_USER_DB_ID.to_string(),
-        MANAGED_USER_DB_ID.to_string(),
-    );
+        CreateCertificateRequest {
+            user_db_id: TEST_USER_DB_ID.to_string(),
+            content: content.clone(),
+            managed_user_db_id: None,
+            issuer_id: None,
+            achievement_id: Some(achievement_id.clone()),
+        },
+    )
+    .unwrap()
+    .id;
 
-    let test = |calling_principal: Principal| {
-        let res = get_certificate(&env, calling_principal, certificate_id.clone()).unwrap();
-        let certificate = decode_certificate(&res.certificate.certificate_cbor_hex);
-        assert_eq!(certificate.user_principal, user_principal);
-        assert_eq!(certificate.content.name, name);
+    // only public certificates are exported
+    assert_http_response_is_not_found(&env, &open_badge_url(&certificate_id));
+    set_certificate_public(
+        &env,
+        user_principal,
+        SetCertificatePublicRequest {
+            id: certificate_id.clone(),
+            is_public: true,
+        },
+    )
+    .unwrap();
+
+    let test = |expected_name: &str| {
+        let json = get_open_badge(&env, &certificate_id);
+
+        assert_eq!(json["name"], expected_name);
         assert_eq!(
-            certificate.managed_user_id.clone().unwrap(),
-            MANAGED_USER_DB_ID.to_string()
-        );
-        assert_ic_certification_is_valid(
-            &env,
-            res.ic_certificate,
-            res.ic_certificate_witness.clone(),
+            json["issuer"]["id"],
+            format!("did:icp:{}", env.canister_id().to_text())
         );
-        assert_ic_certificate_tree_is_valid(
-            res.ic_certificate_witness,
-            &user_principal,
-            vec![res.certificate],
+        assert_eq!(json["validFrom"], "2023-12-31T23:00:00+00:00");
+
+        let subject = &json["credentialSubject"];
+        assert_eq!(
+            subject["id"],
+            format!("did:icp:{}", user_principal.to_text())
         );
+        let achievement = &subject["achievement"];
+        assert_eq!(achievement["id"], format!("urn:uuid:{}", achievement_id));
+        assert_eq!(achievement["name"], request.name);
+        assert_eq!(achievement["description"], request.description);
+        assert_eq!(achievement["criteria"]["narrative"], request.criteria);
+        assert_eq!(achievement["tag"], serde_json::json!(["Swimming"]));
+        assert!(achievement.get("creator").is_none());
+
+        json
     };
 
-    test(user_principal);
-    test(backend_principal);
+    test(&content.name);
+
+    let updated_content = updated_certificate_content_request();
+    update_certificate(
+        &env,
+        user_principal,
+        UpdateCertificateRequest {
+            id: certificate_id.clone(),
+            content: updated_content.clone(),
+        },
+    )
+    .unwrap();
+    let json = test(&updated_content.name);
+
+    // check that the response is still certified after canister upgrade
+    upgrade_canister(&env);
+    assert_eq!(test(&updated_content.name), json);
+
+    // revoked certificates no longer award the achievement
+    revoke_certificate(
+        &env,
+        user_principal,
+        RevokeCertificateRequest {
+            id: certificate_id.clone(),
+            reason: "reason".to_string(),
+        },
+    )
+    .unwrap();
+    assert_http_response_is_not_found(&env, &open_badge_url(&certificate_id));
 }
 
 #[test]
-fn test_get_certificate_not_found() {
+fn test_http_request_not_found() {
     let env = test_env::create_test_env();
     let backend_principal = setup_config(&env);
-    setup_user(&env, TEST_USER_SUB, TEST_USER_DB_ID);
-
-    const TEST_CERTIFICATES_COUNT: usize = 10;
+    let user_identity = setup_user(&env, TEST_USER_SUB, TEST_USER_DB_ID);
+    let user_principal = user_identity.sender().unwrap();
 
-    for _ in 0..TEST_CERTIFICATES_COUNT {
+    let (private_certificate_id, _) =
         create_test_certificate(&env, backend_principal, TEST_USER_DB_ID.to_string());
-    }
+    // share tokens don't make the certificate available over HTTP
+    create_certificate_share_token(&env, user_principal, private_certificate_id.clone()).unwrap();
 
-    let non_existing_certificate_id = "ccb31f93-1a16-4089-bc84-1822ae591da2";
-    let res = get_certificate(
+    let (public_certificate_id, _) =
+        create_test_certificate(&env, backend_principal, TEST_USER_DB_ID.to_string());
+    set_certificate_public(
         &env,
-        backend_principal,
-        non_existing_certificate_id.to_string(),
+        user_principal,
+        SetCertificatePublicRequest {
+            id: public_certificate_id.clone(),
+            is_public: true,
+        },
     )
-    .unwrap_err();
-    assert!(extract_trap_message(res).contains("Certificate not found"));
+    .unwrap();
+
+    for url in [
+        "/".to_string(),
+        "/certificates".to_string(),
+        "/certificates/".to_string(),
+        "/certificates/invalid_id".to_string(),
+        format!("/certificates/{}", private_certificate_id),
+        format!("/certificates/{}.json", private_certificate_id),
+        format!("/certificates/{}", public_certificate_id.to_uppercase()),
+        format!("/certificates/{}/details", public_certificate_id),
+        format!("/certificates/{}.html", public_certificate_id),
+        // the certificate doesn't award an achievement
+        open_badge_url(&public_certificate_id),
+    ] {
+        assert_http_response_is_not_found(&env, &url);
+    }
 }
 
 #[test]
-fn test_get_certificate_another_user() {
+fn test_get_certificate_interleaved_with_delegations() {
     let env = test_env::create_test_env();
     let backend_principal = setup_config(&env);
     let (auth_provider_key_pair, jwks) = initialize_auth_provider();
     initialize_canister(&env, jwks);
-    let first_user_db_id = TEST_USER_DB_ID;
-    create_user(
+    let user_identity = create_user(
         &env,
         &auth_provider_key_pair,
         TEST_USER_SUB,
-        first_user_db_id,
-    );
-
-    let second_user_sub = "test_sub_2";
-    let second_user_db_id = "ccb31f93-1a16-4089-bc84-1822ae591da2";
-    let second_user_identity = create_user(
-        &env,
-        &auth_provider_key_pair,
-        second_user_sub,
-        second_user_db_id,
+        TEST_USER_DB_ID,
     );
+    let user_principal = user_identity.sender().unwrap();
 
-    let (certificate_id, _) =
-        create_test_certificate(&env, backend_principal, first_user_db_id.to_string());
+    let assert_certificate_is_valid = |certificate_id: &String| {
+        let res = get_certificate(&env, user_principal, certificate_id.clone()).unwrap();
+        assert_ic_certification_is_valid(
+            &env,
+            res.ic_certificate,
+            res.ic_certificate_witness.clone(),
+        );
+        assert_ic_certificate_tree_is_valid(
+            res.ic_certificate_witness,
+            &user_principal,
+            vec![res.certificate],
+        );
+    };
 
-    let second_user_principal = second_user_identity.sender().unwrap();
-    let res_principal = get_certificate(&env, second_user_principal, certificate_id).unwrap_err();
-    assert!(
-        extract_trap_message(res_principal).contains("User can only access their own certificates")
-    );
+    let (first_certificate_id, _) =
+        create_test_certificate(&env, backend_principal, TEST_USER_DB_ID.to_string());
 
-    // managed user
-    const MANAGED_USER_DB_ID: &str = "ccb31f93-1a16-4089-bc84-1822ae591da2";
-    let (certificate_id, _) = create_test_certificate_for_managed_user(
+    // a delegation prepared after a certificate has been created
+    // must not invalidate the certificate certification
+    let (first_session_principal, first_jwt, first_session) = prepare_user_session(
         &env,
-        backend_principal,
-        first_user_db_id.to_string(),
-        MANAGED_USER_DB_ID.to_string(),
+        &auth_provider_key_pair,
+        TEST_USER_SUB,
+        TEST_USER_DB_ID,
     );
-    let res_managed = get_certificate(&env, second_user_principal, certificate_id).unwrap_err();
-    assert!(
-        extract_trap_message(res_managed).contains("User can only access their own certificates")
+    assert_certificate_is_valid(&first_certificate_id);
+    assert_user_session_is_valid(
+        &env,
+        first_session_principal,
+        first_jwt.clone(),
+        first_session.clone(),
     );
-}
-
-#[test]
-fn test_get_certificate_not_authorized() {
-    let env = test_env::create_test_env();
-    let backend_principal = setup_config(&env);
-    setup_user(&env, TEST_USER_SUB, TEST_USER_DB_ID);
 
-    let (certificate_id, _) =
+    // a certificate created after a delegation has been prepared
+    // must not invalidate the delegation signature
+    let (second_certificate_id, _) =
         create_test_certificate(&env, backend_principal, TEST_USER_DB_ID.to_string());
+    assert_user_session_is_valid(&env, first_session_principal, first_jwt, first_session);
+    assert_certificate_is_valid(&first_certificate_id);
+    assert_certificate_is_valid(&second_certificate_id);
 
-    for sender in [
-        generate_random_identity().sender().unwrap(),
-        Principal::anonymous(),
-    ] {
-        let res = get_certificate(&env, sender, certificate_id.clone()).unwrap_err();
-        assert!(
-            extract_trap_message(res).contains("Caller is not the backend or a registered user")
-        );
-    }
-}
-
-fn assert_ic_certification_is_valid(
-    test_env: &TestEnv,
-    ic_certificate: Vec<u8>,
-    ic_certificate_witness: Vec<u8>,
-) {
-    let cert: IcCertificate = serde_cbor::from_slice(&ic_certificate).unwrap();
-    let canister_id = test_env.canister_id();
-    let canister_id_bytes = canister_id.as_slice();
-    let current_time = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .expect("Time went backwards")
-        .as_nanos();
-
-    cert.verify(
-        canister_id_bytes,
-        test_env.root_ic_key(),
-        &current_time,
-        &MAX_IC_CERT_TIME_OFFSET_NS,
-    )
-    .unwrap();
-
-    let tree: HashTree = serde_cbor::from_slice(&ic_certificate_witness).unwrap();
-    match cert
-        .tree
-        .lookup_path(vec![b"canister", canister_id_bytes, b"certified_data"])
-    {
-        LookupResult::Found(witness) => assert_eq!(witness, tree.digest()),
-        _ => panic!("expected LookupResult::Found"),
-    }
+    let (second_session_principal, second_jwt, second_session) = prepare_user_session(
+        &env,
+        &auth_provider_key_pair,
+        TEST_USER_SUB,
+        TEST_USER_DB_ID,
+    );
+    assert_user_session_is_valid(&env, second_session_principal, second_jwt, second_session);
+    assert_certificate_is_valid(&first_certificate_id);
+    assert_certificate_is_valid(&second_certificate_id);
 }
 
 fn assert_ic_certificate_tree_is_valid(
@@ -820,14 +4318,17 @@ fn assert_ic_certificate_tree_is_valid(
     // ssp_certificates
     // └── <user_principal>
     //     └── <certificate_id>
-    //         └── certificate cbor data hash
+    //         └── <version>
+    //             └── certificate version cbor data hash
     let tree: HashTree = serde_cbor::from_slice(&ic_certificate_witness).unwrap();
     for cert in certificates.iter() {
         let id = Uuid::parse_str(cert.id.as_str()).unwrap();
+        let version = cert.version.to_be_bytes();
         match tree.lookup_subtree(vec![
             b"ssp_certificates",
             user_principal.as_ref(),
             id.as_bytes(),
+            version.as_slice(),
         ]) {
             SubtreeLookupResult::Found(witness_cert) => {
                 let certificate_cbor = hex::decode(cert.certificate_cbor_hex.clone()).unwrap();
@@ -1,11 +1,10 @@
 use base64::{engine::general_purpose, Engine as _};
 use jwt_simple::prelude::*;
-use ssp_backend_types::{Auth0JWK, Auth0JWKSet, HasuraJWTClaims};
+use ssp_backend_types::{Auth0JWK, Auth0JWKSet, HasuraJWTClaims, OidcConfig};
 
-// ignore rust-analyzer errors on these environment variables
-// compilation succeeds if you've correctly set the .env file
-const AUTH0_ISSUER: &str = env!("ID_TOKEN_ISSUER_BASE_URL"); // expected to have a trailing slash
-const AUTH0_AUDIENCE: &str = env!("ID_TOKEN_AUDIENCE");
+pub const AUTH0_ISSUER: &str = "http://integration-test.local/";
+pub const AUTH0_AUDIENCE: &str = "integration-test-audience";
+const CLAIMS_NAMESPACE: &str = "https://hasura.io/jwt/claims";
 
 const KEY_ID: &str = "integration_tests_key_id";
 
@@ -13,6 +12,16 @@ fn component_to_base64(component: &[u8]) -> String {
     general_purpose::URL_SAFE_NO_PAD.encode(component)
 }
 
+/// The OIDC config the canister is installed with.
+pub fn oidc_config() -> OidcConfig {
+    OidcConfig {
+        issuer: AUTH0_ISSUER.to_string(),
+        audiences: vec![AUTH0_AUDIENCE.to_string()],
+        jwks_url: format!("{AUTH0_ISSUER}.well-known/jwks.json"),
+        claims_namespace: Some(CLAIMS_NAMESPACE.to_string()),
+    }
+}
+
 pub fn initialize_auth_provider() -> (RS256KeyPair, Auth0JWKSet) {
     let key_pair = create_key_pair();
     let jwks = create_jwks(&key_pair);
@@ -38,10 +47,47 @@ pub fn create_jwks(key_pair: &RS256KeyPair) -> Auth0JWKSet {
             // not needed
             x5c: vec!["".to_string()],
             x5t: "".to_string(),
+            crv: None,
+            x: None,
+            y: None,
         }],
     }
 }
 
+pub fn create_es256_jwk(key_pair: &ES256KeyPair) -> Auth0JWK {
+    // 0x04 || x || y
+    let public_key = key_pair.public_key().public_key().to_bytes_uncompressed();
+    Auth0JWK {
+        kid: key_pair.key_id().as_ref().unwrap().to_string(),
+        kty: "EC".to_string(),
+        alg: ES256KeyPair::jwt_alg_name().to_string(),
+        r#use: "sig".to_string(),
+        n: "".to_string(),
+        e: "".to_string(),
+        x5c: vec![],
+        x5t: "".to_string(),
+        crv: Some("P-256".to_string()),
+        x: Some(component_to_base64(&public_key[1..33])),
+        y: Some(component_to_base64(&public_key[33..65])),
+    }
+}
+
+pub fn create_ed25519_jwk(key_pair: &Ed25519KeyPair) -> Auth0JWK {
+    Auth0JWK {
+        kid: key_pair.key_id().as_ref().unwrap().to_string(),
+        kty: "OKP".to_string(),
+        alg: Ed25519KeyPair::jwt_alg_name().to_string(),
+        r#use: "sig".to_string(),
+        n: "".to_string(),
+        e: "".to_string(),
+        x5c: vec![],
+        x5t: "".to_string(),
+        crv: Some("Ed25519".to_string()),
+        x: Some(component_to_base64(&key_pair.public_key().to_bytes())),
+        y: None,
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct CustomClaims {
     #[serde(rename = "https://hasura.io/jwt/claims")]
@@ -55,17 +101,79 @@ pub fn create_jwt(
     db_id: Option<&str>,
     valid_for: Duration,
 ) -> (String, JWTClaims<CustomClaims>) {
-    let custom_claims = CustomClaims {
-        hasura_claims: db_id.map(|db_id| HasuraJWTClaims {
+    create_jwt_for_issuer(
+        key_pair,
+        AUTH0_ISSUER,
+        AUTH0_AUDIENCE,
+        sub,
+        nonce,
+        db_id,
+        valid_for,
+    )
+}
+
+pub fn create_jwt_for_issuer(
+    key_pair: &RS256KeyPair,
+    issuer: &str,
+    audience: &str,
+    sub: &str,
+    nonce: &str,
+    db_id: Option<&str>,
+    valid_for: Duration,
+) -> (String, JWTClaims<CustomClaims>) {
+    sign_jwt(
+        key_pair,
+        issuer,
+        audience,
+        sub,
+        nonce,
+        db_id.map(|db_id| HasuraJWTClaims {
             x_hasura_default_role: "user".to_string(),
             x_hasura_allowed_roles: vec!["user".to_string()],
             x_hasura_user_id: db_id.to_string(),
         }),
-    };
+        valid_for,
+    )
+}
+
+/// Creates a JWT for a user with the given Hasura roles, the first one being the default role.
+pub fn create_jwt_with_roles(
+    key_pair: &RS256KeyPair,
+    sub: &str,
+    nonce: &str,
+    db_id: &str,
+    roles: &[&str],
+    valid_for: Duration,
+) -> (String, JWTClaims<CustomClaims>) {
+    sign_jwt(
+        key_pair,
+        AUTH0_ISSUER,
+        AUTH0_AUDIENCE,
+        sub,
+        nonce,
+        Some(HasuraJWTClaims {
+            x_hasura_default_role: roles[0].to_string(),
+            x_hasura_allowed_roles: roles.iter().map(|role| role.to_string()).collect(),
+            x_hasura_user_id: db_id.to_string(),
+        }),
+        valid_for,
+    )
+}
+
+fn sign_jwt(
+    key_pair: &RS256KeyPair,
+    issuer: &str,
+    audience: &str,
+    sub: &str,
+    nonce: &str,
+    hasura_claims: Option<HasuraJWTClaims>,
+    valid_for: Duration,
+) -> (String, JWTClaims<CustomClaims>) {
+    let custom_claims = CustomClaims { hasura_claims };
 
     let claims = Claims::with_custom_claims(custom_claims, valid_for)
-        .with_issuer(AUTH0_ISSUER)
-        .with_audience(AUTH0_AUDIENCE)
+        .with_issuer(issuer)
+        .with_audience(audience)
         .with_subject(sub)
         .with_nonce(nonce);
     let jwt = key_pair.sign(claims.clone()).unwrap();
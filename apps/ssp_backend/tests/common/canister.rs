@@ -1,40 +1,70 @@
-use candid::Principal;
-use pocket_ic::{query_candid_as, update_candid_as, CallError, ErrorCode, UserError};
+use candid::{decode_one, encode_args, encode_one, Nat, Principal};
+use pocket_ic::{
+    common::rest::RawMessageId, query_candid_as, update_candid_as, CallError, WasmResult,
+};
 use ssp_backend_types::{
-    Auth0JWKSet, Config, CreateCertificateRequest, CreateCertificateResponse,
-    GetCertificateResponse, GetDelegationResponse, GetUserCertificatesRequest,
-    GetUserCertificatesResponse, PrepareDelegationResponse, User,
+    Account, Achievement, ApiError, ApiResult, Auth0JWKSet, CollectionMetadata,
+    CommitUploadResponse, Config, CreateAchievementRequest, CreateAchievementResponse,
+    CreateCertificateRequest, CreateCertificateResponse, CreateCertificateShareTokenResponse,
+    CreateIssuerRequest, CreateIssuerResponse, CreateUploadRequest, CreateUploadResponse,
+    DelegationConfig, DelegationStats, GetAuditLogRequest, GetAuditLogResponse, GetBlocksArgs,
+    GetBlocksResult, GetCertificateAsVcResponse, GetCertificateResponse, GetDelegationResponse,
+    GetFileChunkRequest, GetFileChunkResponse, GetFileRequest, GetFileResponse,
+    GetManagedUserCertificatesRequest, GetMyCertificatesRequest, GetUserCertificatesRequest,
+    GetUserCertificatesResponse, HttpRequest, HttpResponse, Icrc3DataCertificate, Issuer,
+    IssuerMemberRequest, ManagedUser, ManagedUserGuardianRequest, OidcConfig,
+    PrepareDelegationResponse, RevokeCertificateRequest, SessionKey, SetCertificatePublicRequest,
+    SetIssuerVerifiedRequest, SupportedBlockType, SupportedStandard, TokenMetadata, TransferArg,
+    TransferResult, UpdateCertificateRequest, UpdateCertificateResponse, UploadChunkRequest, User,
+    VerifyCertificateRequest,
 };
 
-use super::test_env::TestEnv;
+use super::{auth_provider::AUTH0_ISSUER, test_env::TestEnv};
 
 pub fn initialize_canister(env: &TestEnv, jwks: Auth0JWKSet) {
-    set_jwks(env, env.controller(), jwks).unwrap();
+    set_jwks(env, env.controller(), AUTH0_ISSUER, jwks).unwrap();
 }
 
-pub fn extract_trap_message(res: CallError) -> String {
-    match res {
-        CallError::UserError(UserError {
-            code: ErrorCode::CanisterCalledTrap,
-            description,
-        }) => description,
-        _ => panic!("expected trap"),
-    }
+/// Unwraps the result of a canister call, which is not expected to trap.
+fn unwrap_api_result<T>(res: Result<(ApiResult<T>,), CallError>) -> Result<T, ApiError> {
+    res.map(|(res,)| res).unwrap().into()
 }
 
 pub fn prepare_delegation(
     env: &TestEnv,
     sender: Principal,
     jwt: String,
-) -> Result<PrepareDelegationResponse, CallError> {
-    update_candid_as(
+) -> Result<PrepareDelegationResponse, ApiError> {
+    unwrap_api_result(update_candid_as(
         env.pic(),
         env.canister_id(),
         sender,
         "prepare_delegation",
         (jwt,),
-    )
-    .map(|(res,)| res)
+    ))
+}
+
+/// Submits a `prepare_delegation` call without waiting for it to complete,
+/// e.g. to mock the HTTPS outcalls it makes. See [await_prepare_delegation].
+pub fn submit_prepare_delegation(env: &TestEnv, sender: Principal, jwt: String) -> RawMessageId {
+    env.pic()
+        .submit_call(
+            env.canister_id(),
+            sender,
+            "prepare_delegation",
+            encode_one(jwt).unwrap(),
+        )
+        .unwrap()
+}
+
+pub fn await_prepare_delegation(
+    env: &TestEnv,
+    message_id: RawMessageId,
+) -> Result<PrepareDelegationResponse, ApiError> {
+    match env.pic().await_call(message_id).unwrap() {
+        WasmResult::Reply(bytes) => decode_one::<ApiResult<_>>(&bytes).unwrap().into(),
+        WasmResult::Reject(message) => panic!("prepare_delegation rejected: {}", message),
+    }
 }
 
 pub fn get_delegation(
@@ -42,93 +72,764 @@ pub fn get_delegation(
     sender: Principal,
     jwt: String,
     expiration: u64,
-) -> Result<GetDelegationResponse, CallError> {
-    query_candid_as(
+) -> Result<GetDelegationResponse, ApiError> {
+    unwrap_api_result(query_candid_as(
         env.pic(),
         env.canister_id(),
         sender,
         "get_delegation",
         (jwt, expiration),
-    )
-    .map(|(res,)| res)
+    ))
+}
+
+pub fn revoke_session(
+    env: &TestEnv,
+    sender: Principal,
+    session_key: SessionKey,
+) -> Result<(), ApiError> {
+    unwrap_api_result(update_candid_as(
+        env.pic(),
+        env.canister_id(),
+        sender,
+        "revoke_session",
+        (session_key,),
+    ))
+}
+
+pub fn revoke_all_sessions(env: &TestEnv, sender: Principal) -> Result<(), ApiError> {
+    unwrap_api_result(update_candid_as(
+        env.pic(),
+        env.canister_id(),
+        sender,
+        "revoke_all_sessions",
+        (),
+    ))
 }
 
-pub fn sync_jwks(env: &TestEnv, sender: Principal) -> Result<(), CallError> {
-    update_candid_as(env.pic(), env.canister_id(), sender, "sync_jwks", ()).map(|(res,)| res)
+pub fn get_delegation_stats(env: &TestEnv, sender: Principal) -> Result<DelegationStats, ApiError> {
+    unwrap_api_result(query_candid_as(
+        env.pic(),
+        env.canister_id(),
+        sender,
+        "get_delegation_stats",
+        (),
+    ))
 }
 
-pub fn set_jwks(env: &TestEnv, sender: Principal, jwks: Auth0JWKSet) -> Result<(), CallError> {
-    update_candid_as(env.pic(), env.canister_id(), sender, "set_jwks", (jwks,)).map(|(res,)| res)
+pub fn sync_jwks(env: &TestEnv, sender: Principal) -> Result<(), ApiError> {
+    unwrap_api_result(update_candid_as(
+        env.pic(),
+        env.canister_id(),
+        sender,
+        "sync_jwks",
+        (),
+    ))
 }
 
-pub fn get_jwks(env: &TestEnv, sender: Principal) -> Result<Option<Auth0JWKSet>, CallError> {
-    query_candid_as(env.pic(), env.canister_id(), sender, "get_jwks", ()).map(|(res,)| res)
+/// Submits a `sync_jwks` call without waiting for it to complete,
+/// e.g. to mock the HTTPS outcalls it makes. See [await_sync_jwks].
+pub fn submit_sync_jwks(env: &TestEnv, sender: Principal) -> RawMessageId {
+    env.pic()
+        .submit_call(
+            env.canister_id(),
+            sender,
+            "sync_jwks",
+            encode_args(()).unwrap(),
+        )
+        .unwrap()
 }
 
-pub fn get_config(env: &TestEnv, sender: Principal) -> Result<Config, CallError> {
-    query_candid_as(env.pic(), env.canister_id(), sender, "get_config", ()).map(|(res,)| res)
+pub fn await_sync_jwks(env: &TestEnv, message_id: RawMessageId) -> Result<(), ApiError> {
+    match env.pic().await_call(message_id).unwrap() {
+        WasmResult::Reply(bytes) => decode_one::<ApiResult<_>>(&bytes).unwrap().into(),
+        WasmResult::Reject(message) => panic!("sync_jwks rejected: {}", message),
+    }
+}
+
+pub fn set_jwks(
+    env: &TestEnv,
+    sender: Principal,
+    issuer: &str,
+    jwks: Auth0JWKSet,
+) -> Result<(), ApiError> {
+    unwrap_api_result(update_candid_as(
+        env.pic(),
+        env.canister_id(),
+        sender,
+        "set_jwks",
+        (issuer, jwks),
+    ))
+}
+
+pub fn get_jwks(
+    env: &TestEnv,
+    sender: Principal,
+    issuer: &str,
+) -> Result<Option<Auth0JWKSet>, ApiError> {
+    unwrap_api_result(query_candid_as(
+        env.pic(),
+        env.canister_id(),
+        sender,
+        "get_jwks",
+        (issuer,),
+    ))
+}
+
+pub fn get_config(env: &TestEnv, sender: Principal) -> Result<Config, ApiError> {
+    unwrap_api_result(query_candid_as(
+        env.pic(),
+        env.canister_id(),
+        sender,
+        "get_config",
+        (),
+    ))
 }
 
 pub fn set_backend_principal(
     env: &TestEnv,
     sender: Principal,
     principal: Principal,
-) -> Result<(), CallError> {
-    update_candid_as(
+) -> Result<(), ApiError> {
+    unwrap_api_result(update_candid_as(
         env.pic(),
         env.canister_id(),
         sender,
         "set_backend_principal",
         (principal,),
+    ))
+}
+
+pub fn set_delegation_config(
+    env: &TestEnv,
+    sender: Principal,
+    delegation_config: DelegationConfig,
+) -> Result<(), ApiError> {
+    unwrap_api_result(update_candid_as(
+        env.pic(),
+        env.canister_id(),
+        sender,
+        "set_delegation_config",
+        (delegation_config,),
+    ))
+}
+
+pub fn set_oidc_provider(
+    env: &TestEnv,
+    sender: Principal,
+    oidc_config: OidcConfig,
+) -> Result<(), ApiError> {
+    unwrap_api_result(update_candid_as(
+        env.pic(),
+        env.canister_id(),
+        sender,
+        "set_oidc_provider",
+        (oidc_config,),
+    ))
+}
+
+pub fn remove_oidc_provider(
+    env: &TestEnv,
+    sender: Principal,
+    issuer: &str,
+) -> Result<(), ApiError> {
+    unwrap_api_result(update_candid_as(
+        env.pic(),
+        env.canister_id(),
+        sender,
+        "remove_oidc_provider",
+        (issuer,),
+    ))
+}
+
+pub fn get_oidc_providers(env: &TestEnv, sender: Principal) -> Result<Vec<OidcConfig>, ApiError> {
+    unwrap_api_result(query_candid_as(
+        env.pic(),
+        env.canister_id(),
+        sender,
+        "get_oidc_providers",
+        (),
+    ))
+}
+
+pub fn get_audit_log(
+    env: &TestEnv,
+    sender: Principal,
+    request: GetAuditLogRequest,
+) -> Result<GetAuditLogResponse, ApiError> {
+    unwrap_api_result(query_candid_as(
+        env.pic(),
+        env.canister_id(),
+        sender,
+        "get_audit_log",
+        (request,),
+    ))
+}
+
+pub fn icrc3_get_blocks(env: &TestEnv, sender: Principal, args: GetBlocksArgs) -> GetBlocksResult {
+    query_candid_as(
+        env.pic(),
+        env.canister_id(),
+        sender,
+        "icrc3_get_blocks",
+        (args,),
+    )
+    .map(|(res,)| res)
+    .unwrap()
+}
+
+pub fn icrc3_get_tip_certificate(env: &TestEnv, sender: Principal) -> Option<Icrc3DataCertificate> {
+    query_candid_as(
+        env.pic(),
+        env.canister_id(),
+        sender,
+        "icrc3_get_tip_certificate",
+        (),
+    )
+    .map(|(res,)| res)
+    .unwrap()
+}
+
+pub fn icrc3_supported_block_types(env: &TestEnv, sender: Principal) -> Vec<SupportedBlockType> {
+    query_candid_as(
+        env.pic(),
+        env.canister_id(),
+        sender,
+        "icrc3_supported_block_types",
+        (),
+    )
+    .map(|(res,)| res)
+    .unwrap()
+}
+
+pub fn icrc10_supported_standards(env: &TestEnv, sender: Principal) -> Vec<SupportedStandard> {
+    query_candid_as(
+        env.pic(),
+        env.canister_id(),
+        sender,
+        "icrc10_supported_standards",
+        (),
+    )
+    .map(|(res,)| res)
+    .unwrap()
+}
+
+pub fn icrc7_collection_metadata(env: &TestEnv, sender: Principal) -> CollectionMetadata {
+    query_candid_as(
+        env.pic(),
+        env.canister_id(),
+        sender,
+        "icrc7_collection_metadata",
+        (),
+    )
+    .map(|(res,)| res)
+    .unwrap()
+}
+
+pub fn icrc7_name(env: &TestEnv, sender: Principal) -> String {
+    query_candid_as(env.pic(), env.canister_id(), sender, "icrc7_name", ())
+        .map(|(res,)| res)
+        .unwrap()
+}
+
+pub fn icrc7_symbol(env: &TestEnv, sender: Principal) -> String {
+    query_candid_as(env.pic(), env.canister_id(), sender, "icrc7_symbol", ())
+        .map(|(res,)| res)
+        .unwrap()
+}
+
+pub fn icrc7_total_supply(env: &TestEnv, sender: Principal) -> Nat {
+    query_candid_as(
+        env.pic(),
+        env.canister_id(),
+        sender,
+        "icrc7_total_supply",
+        (),
+    )
+    .map(|(res,)| res)
+    .unwrap()
+}
+
+pub fn icrc7_balance_of(env: &TestEnv, sender: Principal, accounts: Vec<Account>) -> Vec<Nat> {
+    query_candid_as(
+        env.pic(),
+        env.canister_id(),
+        sender,
+        "icrc7_balance_of",
+        (accounts,),
+    )
+    .map(|(res,)| res)
+    .unwrap()
+}
+
+pub fn icrc7_tokens_of(
+    env: &TestEnv,
+    sender: Principal,
+    account: Account,
+    prev: Option<Nat>,
+    take: Option<Nat>,
+) -> Vec<Nat> {
+    query_candid_as(
+        env.pic(),
+        env.canister_id(),
+        sender,
+        "icrc7_tokens_of",
+        (account, prev, take),
+    )
+    .map(|(res,)| res)
+    .unwrap()
+}
+
+pub fn icrc7_owner_of(
+    env: &TestEnv,
+    sender: Principal,
+    token_ids: Vec<Nat>,
+) -> Vec<Option<Account>> {
+    query_candid_as(
+        env.pic(),
+        env.canister_id(),
+        sender,
+        "icrc7_owner_of",
+        (token_ids,),
+    )
+    .map(|(res,)| res)
+    .unwrap()
+}
+
+pub fn icrc7_token_metadata(
+    env: &TestEnv,
+    sender: Principal,
+    token_ids: Vec<Nat>,
+) -> Vec<Option<TokenMetadata>> {
+    query_candid_as(
+        env.pic(),
+        env.canister_id(),
+        sender,
+        "icrc7_token_metadata",
+        (token_ids,),
     )
     .map(|(res,)| res)
+    .unwrap()
+}
+
+pub fn icrc7_transfer(
+    env: &TestEnv,
+    sender: Principal,
+    args: Vec<TransferArg>,
+) -> Vec<Option<TransferResult>> {
+    update_candid_as(
+        env.pic(),
+        env.canister_id(),
+        sender,
+        "icrc7_transfer",
+        (args,),
+    )
+    .map(|(res,)| res)
+    .unwrap()
+}
+
+pub fn get_my_user(env: &TestEnv, sender: Principal) -> Result<User, ApiError> {
+    unwrap_api_result(query_candid_as(
+        env.pic(),
+        env.canister_id(),
+        sender,
+        "get_my_user",
+        (),
+    ))
+}
+
+pub fn get_my_managed_users(
+    env: &TestEnv,
+    sender: Principal,
+) -> Result<Vec<ManagedUser>, ApiError> {
+    unwrap_api_result(query_candid_as(
+        env.pic(),
+        env.canister_id(),
+        sender,
+        "get_my_managed_users",
+        (),
+    ))
+}
+
+pub fn add_managed_user_guardian(
+    env: &TestEnv,
+    sender: Principal,
+    request: ManagedUserGuardianRequest,
+) -> Result<(), ApiError> {
+    unwrap_api_result(update_candid_as(
+        env.pic(),
+        env.canister_id(),
+        sender,
+        "add_managed_user_guardian",
+        (request,),
+    ))
+}
+
+pub fn remove_managed_user_guardian(
+    env: &TestEnv,
+    sender: Principal,
+    request: ManagedUserGuardianRequest,
+) -> Result<(), ApiError> {
+    unwrap_api_result(update_candid_as(
+        env.pic(),
+        env.canister_id(),
+        sender,
+        "remove_managed_user_guardian",
+        (request,),
+    ))
+}
+
+pub fn create_issuer(
+    env: &TestEnv,
+    sender: Principal,
+    request: CreateIssuerRequest,
+) -> Result<CreateIssuerResponse, ApiError> {
+    unwrap_api_result(update_candid_as(
+        env.pic(),
+        env.canister_id(),
+        sender,
+        "create_issuer",
+        (request,),
+    ))
+}
+
+pub fn get_issuer(env: &TestEnv, sender: Principal, id: String) -> Result<Issuer, ApiError> {
+    unwrap_api_result(query_candid_as(
+        env.pic(),
+        env.canister_id(),
+        sender,
+        "get_issuer",
+        (id,),
+    ))
+}
+
+pub fn set_issuer_verified(
+    env: &TestEnv,
+    sender: Principal,
+    request: SetIssuerVerifiedRequest,
+) -> Result<(), ApiError> {
+    unwrap_api_result(update_candid_as(
+        env.pic(),
+        env.canister_id(),
+        sender,
+        "set_issuer_verified",
+        (request,),
+    ))
+}
+
+pub fn add_issuer_member(
+    env: &TestEnv,
+    sender: Principal,
+    request: IssuerMemberRequest,
+) -> Result<(), ApiError> {
+    unwrap_api_result(update_candid_as(
+        env.pic(),
+        env.canister_id(),
+        sender,
+        "add_issuer_member",
+        (request,),
+    ))
+}
+
+pub fn remove_issuer_member(
+    env: &TestEnv,
+    sender: Principal,
+    request: IssuerMemberRequest,
+) -> Result<(), ApiError> {
+    unwrap_api_result(update_candid_as(
+        env.pic(),
+        env.canister_id(),
+        sender,
+        "remove_issuer_member",
+        (request,),
+    ))
 }
 
-pub fn get_my_user(env: &TestEnv, sender: Principal) -> Result<User, CallError> {
-    query_candid_as(env.pic(), env.canister_id(), sender, "get_my_user", ()).map(|(res,)| res)
+pub fn create_achievement(
+    env: &TestEnv,
+    sender: Principal,
+    request: CreateAchievementRequest,
+) -> Result<CreateAchievementResponse, ApiError> {
+    unwrap_api_result(update_candid_as(
+        env.pic(),
+        env.canister_id(),
+        sender,
+        "create_achievement",
+        (request,),
+    ))
+}
+
+pub fn get_achievement(
+    env: &TestEnv,
+    sender: Principal,
+    id: String,
+) -> Result<Achievement, ApiError> {
+    unwrap_api_result(query_candid_as(
+        env.pic(),
+        env.canister_id(),
+        sender,
+        "get_achievement",
+        (id,),
+    ))
 }
 
 pub fn create_certificate(
     env: &TestEnv,
     sender: Principal,
     request: CreateCertificateRequest,
-) -> Result<CreateCertificateResponse, CallError> {
-    update_candid_as(
+) -> Result<CreateCertificateResponse, ApiError> {
+    unwrap_api_result(update_candid_as(
         env.pic(),
         env.canister_id(),
         sender,
         "create_certificate",
         (request,),
-    )
-    .map(|(res,)| res)
+    ))
 }
 
 pub fn get_user_certificates(
     env: &TestEnv,
     sender: Principal,
     request: GetUserCertificatesRequest,
-) -> Result<GetUserCertificatesResponse, CallError> {
-    query_candid_as(
+) -> Result<GetUserCertificatesResponse, ApiError> {
+    unwrap_api_result(query_candid_as(
         env.pic(),
         env.canister_id(),
         sender,
         "get_user_certificates",
         (request,),
-    )
-    .map(|(res,)| res)
+    ))
+}
+
+pub fn get_my_certificates(
+    env: &TestEnv,
+    sender: Principal,
+    request: GetMyCertificatesRequest,
+) -> Result<GetUserCertificatesResponse, ApiError> {
+    unwrap_api_result(query_candid_as(
+        env.pic(),
+        env.canister_id(),
+        sender,
+        "get_my_certificates",
+        (request,),
+    ))
+}
+
+pub fn get_managed_user_certificates(
+    env: &TestEnv,
+    sender: Principal,
+    request: GetManagedUserCertificatesRequest,
+) -> Result<GetUserCertificatesResponse, ApiError> {
+    unwrap_api_result(query_candid_as(
+        env.pic(),
+        env.canister_id(),
+        sender,
+        "get_managed_user_certificates",
+        (request,),
+    ))
 }
 
 pub fn get_certificate(
     env: &TestEnv,
     sender: Principal,
     id: String,
-) -> Result<GetCertificateResponse, CallError> {
-    query_candid_as(
+) -> Result<GetCertificateResponse, ApiError> {
+    get_certificate_version(env, sender, id, None)
+}
+
+pub fn get_certificate_as_vc(
+    env: &TestEnv,
+    sender: Principal,
+    id: String,
+) -> Result<GetCertificateAsVcResponse, ApiError> {
+    unwrap_api_result(query_candid_as(
+        env.pic(),
+        env.canister_id(),
+        sender,
+        "get_certificate_as_vc",
+        (id,),
+    ))
+}
+
+pub fn get_certificate_version(
+    env: &TestEnv,
+    sender: Principal,
+    id: String,
+    version: Option<u32>,
+) -> Result<GetCertificateResponse, ApiError> {
+    unwrap_api_result(query_candid_as(
         env.pic(),
         env.canister_id(),
         sender,
         "get_certificate",
+        (id, version),
+    ))
+}
+
+pub fn update_certificate(
+    env: &TestEnv,
+    sender: Principal,
+    request: UpdateCertificateRequest,
+) -> Result<UpdateCertificateResponse, ApiError> {
+    unwrap_api_result(update_candid_as(
+        env.pic(),
+        env.canister_id(),
+        sender,
+        "update_certificate",
+        (request,),
+    ))
+}
+
+pub fn revoke_certificate(
+    env: &TestEnv,
+    sender: Principal,
+    request: RevokeCertificateRequest,
+) -> Result<(), ApiError> {
+    unwrap_api_result(update_candid_as(
+        env.pic(),
+        env.canister_id(),
+        sender,
+        "revoke_certificate",
+        (request,),
+    ))
+}
+
+pub fn set_certificate_public(
+    env: &TestEnv,
+    sender: Principal,
+    request: SetCertificatePublicRequest,
+) -> Result<(), ApiError> {
+    unwrap_api_result(update_candid_as(
+        env.pic(),
+        env.canister_id(),
+        sender,
+        "set_certificate_public",
+        (request,),
+    ))
+}
+
+pub fn create_certificate_share_token(
+    env: &TestEnv,
+    sender: Principal,
+    id: String,
+) -> Result<CreateCertificateShareTokenResponse, ApiError> {
+    unwrap_api_result(update_candid_as(
+        env.pic(),
+        env.canister_id(),
+        sender,
+        "create_certificate_share_token",
         (id,),
+    ))
+}
+
+pub fn revoke_certificate_share_token(
+    env: &TestEnv,
+    sender: Principal,
+    id: String,
+) -> Result<(), ApiError> {
+    unwrap_api_result(update_candid_as(
+        env.pic(),
+        env.canister_id(),
+        sender,
+        "revoke_certificate_share_token",
+        (id,),
+    ))
+}
+
+pub fn verify_certificate(
+    env: &TestEnv,
+    sender: Principal,
+    request: VerifyCertificateRequest,
+) -> Result<GetCertificateResponse, ApiError> {
+    unwrap_api_result(query_candid_as(
+        env.pic(),
+        env.canister_id(),
+        sender,
+        "verify_certificate",
+        (request,),
+    ))
+}
+
+pub fn http_request(
+    env: &TestEnv,
+    sender: Principal,
+    request: HttpRequest,
+) -> Result<HttpResponse, CallError> {
+    query_candid_as(
+        env.pic(),
+        env.canister_id(),
+        sender,
+        "http_request",
+        (request,),
     )
     .map(|(res,)| res)
 }
+
+pub fn create_upload(
+    env: &TestEnv,
+    sender: Principal,
+    request: CreateUploadRequest,
+) -> Result<CreateUploadResponse, ApiError> {
+    unwrap_api_result(update_candid_as(
+        env.pic(),
+        env.canister_id(),
+        sender,
+        "create_upload",
+        (request,),
+    ))
+}
+
+pub fn upload_chunk(
+    env: &TestEnv,
+    sender: Principal,
+    request: UploadChunkRequest,
+) -> Result<(), ApiError> {
+    unwrap_api_result(update_candid_as(
+        env.pic(),
+        env.canister_id(),
+        sender,
+        "upload_chunk",
+        (request,),
+    ))
+}
+
+pub fn commit_upload(
+    env: &TestEnv,
+    sender: Principal,
+    upload_id: String,
+) -> Result<CommitUploadResponse, ApiError> {
+    unwrap_api_result(update_candid_as(
+        env.pic(),
+        env.canister_id(),
+        sender,
+        "commit_upload",
+        (upload_id,),
+    ))
+}
+
+pub fn get_file(
+    env: &TestEnv,
+    sender: Principal,
+    request: GetFileRequest,
+) -> Result<GetFileResponse, ApiError> {
+    unwrap_api_result(query_candid_as(
+        env.pic(),
+        env.canister_id(),
+        sender,
+        "get_file",
+        (request,),
+    ))
+}
+
+pub fn get_file_chunk(
+    env: &TestEnv,
+    sender: Principal,
+    request: GetFileChunkRequest,
+) -> Result<GetFileChunkResponse, ApiError> {
+    unwrap_api_result(query_candid_as(
+        env.pic(),
+        env.canister_id(),
+        sender,
+        "get_file_chunk",
+        (request,),
+    ))
+}
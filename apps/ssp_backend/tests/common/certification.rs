@@ -0,0 +1,166 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use candid::{Decode, Encode};
+use ic_certificate_verification::VerifyCertificate;
+use ic_certification::{Certificate as IcCertificate, HashTree, LookupResult};
+use ic_representation_independent_hash::{representation_independent_hash, Value};
+use ic_response_verification::verify_request_response_pair;
+use ring::digest::{digest, SHA256};
+use ssp_backend_types::{
+    GetCertificateAsVcResponse, HttpRequest, HttpResponse, SignedDelegation, UserKey,
+};
+
+use super::test_env::TestEnv;
+
+const MAX_IC_CERT_TIME_OFFSET_NS: u128 = 300_000_000_000; // 5 min
+const MIN_REQUESTED_VERIFICATION_VERSION: u8 = 2;
+
+pub fn sha256(bytes: &[u8]) -> Vec<u8> {
+    digest(&SHA256, bytes).as_ref().to_vec()
+}
+
+pub fn verify_delegation(
+    env: &TestEnv,
+    user_key: UserKey,
+    signed_delegation: &SignedDelegation,
+    root_key: &[u8],
+) {
+    const DOMAIN_SEPARATOR: &[u8] = b"ic-request-auth-delegation";
+
+    // The signed message is a signature domain separator
+    // followed by the representation independent hash of a map with entries
+    // pubkey, expiration and targets (if any), using the respective values from the delegation.
+    // See https://internetcomputer.org/docs/current/references/ic-interface-spec#authentication for details
+    let mut key_value_pairs = vec![
+        (
+            "pubkey".to_string(),
+            Value::Bytes(signed_delegation.delegation.pubkey.clone().into_vec()),
+        ),
+        (
+            "expiration".to_string(),
+            Value::Number(signed_delegation.delegation.expiration),
+        ),
+    ];
+    if let Some(targets) = &signed_delegation.delegation.targets {
+        key_value_pairs.push((
+            "targets".to_string(),
+            Value::Array(
+                targets
+                    .iter()
+                    .map(|target| Value::Bytes(target.as_slice().to_vec()))
+                    .collect(),
+            ),
+        ));
+    }
+    let mut msg: Vec<u8> = Vec::from([(DOMAIN_SEPARATOR.len() as u8)]);
+    msg.extend_from_slice(DOMAIN_SEPARATOR);
+    msg.extend_from_slice(&representation_independent_hash(&key_value_pairs));
+
+    env.pic()
+        .verify_canister_signature(
+            msg,
+            signed_delegation.signature.clone().into_vec(),
+            user_key.into_vec(),
+            root_key.to_vec(),
+        )
+        .expect("delegation signature invalid");
+}
+
+/// Verifies the canister signature of the credential, as a verifier of the credential would.
+/// Returns the parsed credential.
+pub fn verify_certificate_credential(
+    env: &TestEnv,
+    res: &GetCertificateAsVcResponse,
+    root_key: &[u8],
+) -> Result<serde_json::Value, String> {
+    const DOMAIN_SEPARATOR: &[u8] = b"ssp-certificate-credential";
+
+    let credential: serde_json::Value =
+        serde_json::from_str(&res.credential).map_err(|e| e.to_string())?;
+    // the issuer is the canister, whose id is part of the public key
+    if credential["issuer"] != format!("did:icp:{}", env.canister_id().to_text()) {
+        return Err("unexpected credential issuer".to_string());
+    }
+
+    let mut msg: Vec<u8> = Vec::from([(DOMAIN_SEPARATOR.len() as u8)]);
+    msg.extend_from_slice(DOMAIN_SEPARATOR);
+    msg.extend_from_slice(res.credential.as_bytes());
+
+    env.pic()
+        .verify_canister_signature(
+            msg,
+            res.signature.clone(),
+            res.public_key.clone(),
+            root_key.to_vec(),
+        )
+        .map_err(|e| format!("{:?}", e))?;
+
+    Ok(credential)
+}
+
+pub fn assert_ic_certification_is_valid(
+    test_env: &TestEnv,
+    ic_certificate: Vec<u8>,
+    ic_certificate_witness: Vec<u8>,
+) {
+    let cert: IcCertificate = serde_cbor::from_slice(&ic_certificate).unwrap();
+    let canister_id = test_env.canister_id();
+    let canister_id_bytes = canister_id.as_slice();
+    let current_time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Time went backwards")
+        .as_nanos();
+
+    cert.verify(
+        canister_id_bytes,
+        test_env.root_ic_key(),
+        &current_time,
+        &MAX_IC_CERT_TIME_OFFSET_NS,
+    )
+    .unwrap();
+
+    let tree: HashTree = serde_cbor::from_slice(&ic_certificate_witness).unwrap();
+    match cert
+        .tree
+        .lookup_path(vec![b"canister", canister_id_bytes, b"certified_data"])
+    {
+        LookupResult::Found(witness) => assert_eq!(witness, tree.digest()),
+        _ => panic!("expected LookupResult::Found"),
+    }
+}
+
+/// Verifies the response as the HTTP gateway would, using response verification v2.
+pub fn assert_http_response_is_valid(
+    test_env: &TestEnv,
+    request: &HttpRequest,
+    response: &HttpResponse,
+) {
+    // the candid types of the canister and of the verification library are equivalent
+    let request = Decode!(
+        &Encode!(request).unwrap(),
+        ic_http_certification::HttpRequest
+    )
+    .unwrap();
+    let response = Decode!(
+        &Encode!(response).unwrap(),
+        ic_http_certification::HttpResponse
+    )
+    .unwrap();
+    let current_time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Time went backwards")
+        .as_nanos();
+
+    let verification_info = verify_request_response_pair(
+        request,
+        response,
+        test_env.canister_id().as_slice(),
+        current_time,
+        MAX_IC_CERT_TIME_OFFSET_NS,
+        test_env.root_ic_key(),
+        MIN_REQUESTED_VERIFICATION_VERSION,
+    )
+    .unwrap();
+
+    assert_eq!(verification_info.verification_version, 2);
+}
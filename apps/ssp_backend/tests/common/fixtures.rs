@@ -0,0 +1,69 @@
+use candid::Principal;
+use ic_agent::Identity;
+use jwt_simple::prelude::*;
+use serde_bytes::ByteBuf;
+use ssp_backend_types::{
+    Account, CreateCertificateContentRequest, CreateCertificateRequest, Icrc3Value,
+    PrepareDelegationResponse,
+};
+
+use super::{
+    auth_provider::create_jwt,
+    canister::prepare_delegation,
+    identity::{generate_random_identity, pk_to_hex},
+    test_env::TestEnv,
+};
+
+/// Signs the user in with a new session, creating them on their first sign in.
+/// Returns the principal of the user.
+pub fn sign_up(env: &TestEnv, key_pair: &RS256KeyPair, sub: &str, db_id: &str) -> Principal {
+    let session_identity = generate_random_identity();
+    let (jwt, _) = create_jwt(
+        key_pair,
+        sub,
+        &pk_to_hex(&session_identity.public_key().unwrap()),
+        Some(db_id),
+        Duration::from_hours(10),
+    );
+    let PrepareDelegationResponse { user_key, .. } =
+        prepare_delegation(env, session_identity.sender().unwrap(), jwt).unwrap();
+
+    Principal::self_authenticating(&user_key)
+}
+
+pub fn certificate_request(
+    user_db_id: &str,
+    managed_user_db_id: Option<&str>,
+) -> CreateCertificateRequest {
+    CreateCertificateRequest {
+        user_db_id: user_db_id.to_string(),
+        content: CreateCertificateContentRequest {
+            name: "Test certificate".to_string(),
+            issued_at: 1704063600000000,
+            sport_category: "Swimming".to_string(),
+            notes: None,
+            file_uri: None,
+            file_hash: None,
+            external_id: None,
+            issuer_full_name: None,
+            issuer_club_name: None,
+        },
+        managed_user_db_id: managed_user_db_id.map(|id| id.to_string()),
+        issuer_id: None,
+        achievement_id: None,
+    }
+}
+
+pub fn account(owner: Principal) -> Account {
+    Account {
+        owner,
+        subaccount: None,
+    }
+}
+
+/// The account of the owner as it is encoded in the ICRC-3 blocks.
+pub fn account_value(owner: Principal) -> Icrc3Value {
+    Icrc3Value::Array(vec![Icrc3Value::Blob(ByteBuf::from(
+        owner.as_slice().to_vec(),
+    ))])
+}
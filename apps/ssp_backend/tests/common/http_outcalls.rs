@@ -0,0 +1,54 @@
+use pocket_ic::common::rest::{
+    CanisterHttpReject, CanisterHttpReply, CanisterHttpResponse, MockCanisterHttpResponse,
+};
+
+use super::test_env::TestEnv;
+
+/// The `SYS_TRANSIENT` reject code.
+const SYS_TRANSIENT_REJECT_CODE: u64 = 2;
+
+/// Replies to all the pending HTTPS outcalls with a `200 OK` response containing the given body.
+/// Returns the number of outcalls that have been replied to.
+pub fn reply_http_outcalls(env: &TestEnv, body: Vec<u8>) -> usize {
+    mock_http_outcalls(
+        env,
+        CanisterHttpResponse::CanisterHttpReply(CanisterHttpReply {
+            status: 200,
+            headers: vec![],
+            body,
+        }),
+    )
+}
+
+/// Rejects all the pending HTTPS outcalls, simulating an unreachable server.
+/// Returns the number of outcalls that have been rejected.
+pub fn reject_http_outcalls(env: &TestEnv) -> usize {
+    mock_http_outcalls(
+        env,
+        CanisterHttpResponse::CanisterHttpReject(CanisterHttpReject {
+            reject_code: SYS_TRANSIENT_REJECT_CODE,
+            message: "Connection refused".to_string(),
+        }),
+    )
+}
+
+fn mock_http_outcalls(env: &TestEnv, response: CanisterHttpResponse) -> usize {
+    // let the canister make the pending outcalls
+    env.ticks(10);
+
+    let requests = env.pic().get_canister_http();
+    for request in &requests {
+        env.pic()
+            .mock_canister_http_response(MockCanisterHttpResponse {
+                subnet_id: request.subnet_id,
+                request_id: request.request_id,
+                response: response.clone(),
+                additional_responses: vec![],
+            });
+    }
+
+    // let the canister process the responses
+    env.ticks(5);
+
+    requests.len()
+}
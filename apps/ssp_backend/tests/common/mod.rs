@@ -1,5 +1,8 @@
 pub mod auth_provider;
 pub mod canister;
+pub mod certification;
 pub mod date_time;
+pub mod fixtures;
+pub mod http_outcalls;
 pub mod identity;
 pub mod test_env;
@@ -6,8 +6,12 @@ use std::{fs::File, time::SystemTime};
 use candid::Principal;
 use ic_agent::Identity;
 use pocket_ic::{PocketIc, PocketIcBuilder};
+use ssp_backend_types::InitArgs;
 
-use super::identity::generate_random_identity;
+use super::{
+    auth_provider::{oidc_config, AUTH0_ISSUER},
+    identity::generate_random_identity,
+};
 
 pub struct TestEnv {
     pic: PocketIc,
@@ -38,7 +42,11 @@ impl TestEnv {
         pic.install_canister(
             canister_id,
             wasm_module,
-            candid::encode_args(()).unwrap(),
+            candid::encode_one(Some(InitArgs {
+                oidc_providers: Some(vec![oidc_config()]),
+                legacy_oidc_issuer: Some(AUTH0_ISSUER.to_string()),
+            }))
+            .unwrap(),
             Some(controller),
         );
 
@@ -71,6 +79,12 @@ impl TestEnv {
         self.ticks(10);
     }
 
+    /// Advances the canister time by the given duration, letting the expired timers run.
+    pub fn advance_canister_time(&self, duration: Duration) {
+        self.pic.advance_time(duration);
+        self.ticks(10);
+    }
+
     /// Produce and advance by some blocks
     pub fn ticks(&self, n: u64) {
         for _ in 0..n {
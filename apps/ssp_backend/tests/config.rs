@@ -2,12 +2,16 @@ pub mod common;
 
 use candid::Principal;
 use common::{
-    canister::{extract_trap_message, get_config, set_backend_principal},
+    auth_provider::{oidc_config, AUTH0_ISSUER},
+    canister::{
+        get_config, get_oidc_providers, remove_oidc_provider, set_backend_principal,
+        set_delegation_config, set_oidc_provider,
+    },
     identity::generate_random_identity,
-    test_env,
+    test_env::{self, upgrade_canister},
 };
 use ic_agent::Identity;
-use ssp_backend_types::Config;
+use ssp_backend_types::{ApiError, Config, DelegationConfig, OidcConfig, RoleSessionDuration};
 
 #[test]
 fn test_get_config_not_controller() {
@@ -17,7 +21,7 @@ fn test_get_config_not_controller() {
 
     let res = get_config(&env, sender).unwrap_err();
 
-    assert!(extract_trap_message(res).contains("Caller is not a controller"));
+    assert_eq!(res, ApiError::unauthorized("Caller is not a controller"));
 }
 
 #[test]
@@ -30,7 +34,9 @@ fn test_get_config() {
     assert_eq!(
         res,
         Config {
-            backend_principal: None
+            backend_principal: None,
+            delegation: DelegationConfig::default(),
+            legacy_oidc_issuer: Some(AUTH0_ISSUER.to_string()),
         }
     );
 }
@@ -44,7 +50,7 @@ fn test_set_backend_principal_not_controller() {
 
     let res = set_backend_principal(&env, sender, principal).unwrap_err();
 
-    assert!(extract_trap_message(res).contains("Caller is not a controller"));
+    assert_eq!(res, ApiError::unauthorized("Caller is not a controller"));
 }
 
 #[test]
@@ -56,7 +62,10 @@ fn test_set_backend_principal_anonymous() {
 
     let res = set_backend_principal(&env, sender, principal).unwrap_err();
 
-    assert!(extract_trap_message(res).contains("Backend principal cannot be anonymous"));
+    assert_eq!(
+        res,
+        ApiError::validation("backend_principal", "Backend principal cannot be anonymous")
+    );
 }
 
 #[test]
@@ -74,3 +83,169 @@ fn test_set_backend_principal() {
 
     assert_eq!(updated_config.backend_principal.unwrap(), principal);
 }
+
+#[test]
+fn test_set_delegation_config_not_controller() {
+    let env = test_env::create_test_env();
+
+    let sender = generate_random_identity().sender().unwrap();
+
+    let res = set_delegation_config(&env, sender, DelegationConfig::default()).unwrap_err();
+
+    assert_eq!(res, ApiError::unauthorized("Caller is not a controller"));
+}
+
+#[test]
+fn test_set_delegation_config_invalid() {
+    let env = test_env::create_test_env();
+
+    let sender = env.controller();
+    let delegation_config = DelegationConfig {
+        targets: Some(vec![]),
+        ..DelegationConfig::default()
+    };
+
+    let res = set_delegation_config(&env, sender, delegation_config).unwrap_err();
+
+    assert_eq!(
+        res,
+        ApiError::validation("targets", "At least one target is required.")
+    );
+}
+
+#[test]
+fn test_set_delegation_config() {
+    let env = test_env::create_test_env();
+
+    let sender = env.controller();
+    let delegation_config = DelegationConfig {
+        max_session_duration_ns: Some(3_600_000_000_000),
+        role_session_durations: vec![RoleSessionDuration {
+            role: "admin".to_string(),
+            max_session_duration_ns: 600_000_000_000,
+        }],
+        targets: Some(vec![env.canister_id()]),
+    };
+
+    set_delegation_config(&env, sender, delegation_config.clone()).unwrap();
+    let updated_config = get_config(&env, sender).unwrap();
+    assert_eq!(updated_config.delegation, delegation_config);
+
+    // the config is kept across upgrades
+    upgrade_canister(&env);
+    let upgraded_config = get_config(&env, sender).unwrap();
+    assert_eq!(upgraded_config.delegation, delegation_config);
+}
+
+#[test]
+fn test_get_oidc_providers_not_controller() {
+    let env = test_env::create_test_env();
+
+    let sender = generate_random_identity().sender().unwrap();
+
+    let res = get_oidc_providers(&env, sender).unwrap_err();
+
+    assert_eq!(res, ApiError::unauthorized("Caller is not a controller"));
+}
+
+#[test]
+fn test_get_oidc_providers() {
+    let env = test_env::create_test_env();
+
+    let res = get_oidc_providers(&env, env.controller()).unwrap();
+
+    // the providers the canister is installed with
+    assert_eq!(res, vec![oidc_config()]);
+}
+
+#[test]
+fn test_set_oidc_provider_not_controller() {
+    let env = test_env::create_test_env();
+
+    let sender = generate_random_identity().sender().unwrap();
+
+    let res = set_oidc_provider(&env, sender, oidc_config()).unwrap_err();
+
+    assert_eq!(res, ApiError::unauthorized("Caller is not a controller"));
+}
+
+#[test]
+fn test_set_oidc_provider_invalid() {
+    let env = test_env::create_test_env();
+
+    let sender = env.controller();
+    let oidc_config = OidcConfig {
+        audiences: vec![],
+        ..oidc_config()
+    };
+
+    let res = set_oidc_provider(&env, sender, oidc_config).unwrap_err();
+
+    assert_eq!(
+        res,
+        ApiError::validation("audiences", "At least one audience is required.")
+    );
+}
+
+#[test]
+fn test_set_oidc_provider() {
+    let env = test_env::create_test_env();
+
+    let sender = env.controller();
+    let google_config = OidcConfig {
+        issuer: "https://accounts.google.com".to_string(),
+        audiences: vec!["google-client-id".to_string()],
+        jwks_url: "https://www.googleapis.com/oauth2/v3/certs".to_string(),
+        claims_namespace: None,
+    };
+
+    // a provider with a new issuer is added, the providers are sorted by issuer
+    set_oidc_provider(&env, sender, google_config.clone()).unwrap();
+    let providers = get_oidc_providers(&env, sender).unwrap();
+    assert_eq!(providers, vec![oidc_config(), google_config.clone()]);
+
+    // a provider with an existing issuer is updated
+    let updated_auth0_config = OidcConfig {
+        audiences: vec!["staging-audience".to_string()],
+        ..oidc_config()
+    };
+    set_oidc_provider(&env, sender, updated_auth0_config.clone()).unwrap();
+    let providers = get_oidc_providers(&env, sender).unwrap();
+    assert_eq!(
+        providers,
+        vec![updated_auth0_config.clone(), google_config.clone()]
+    );
+
+    // the providers are kept across upgrades
+    upgrade_canister(&env);
+    let upgraded_providers = get_oidc_providers(&env, sender).unwrap();
+    assert_eq!(
+        upgraded_providers,
+        vec![updated_auth0_config, google_config]
+    );
+}
+
+#[test]
+fn test_remove_oidc_provider_not_controller() {
+    let env = test_env::create_test_env();
+
+    let sender = generate_random_identity().sender().unwrap();
+
+    let res = remove_oidc_provider(&env, sender, &oidc_config().issuer).unwrap_err();
+
+    assert_eq!(res, ApiError::unauthorized("Caller is not a controller"));
+}
+
+#[test]
+fn test_remove_oidc_provider() {
+    let env = test_env::create_test_env();
+
+    let sender = env.controller();
+
+    remove_oidc_provider(&env, sender, &oidc_config().issuer).unwrap();
+    let providers = get_oidc_providers(&env, sender).unwrap();
+    assert_eq!(providers, vec![]);
+
+    let res = remove_oidc_provider(&env, sender, &oidc_config().issuer).unwrap_err();
+    assert_eq!(res, ApiError::not_found("OIDC provider not found"));
+}
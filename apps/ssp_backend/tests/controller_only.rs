@@ -1,12 +1,13 @@
 pub mod common;
 
 use common::{
-    canister::{extract_trap_message, get_jwks, set_jwks, sync_jwks},
+    auth_provider::AUTH0_ISSUER,
+    canister::{get_delegation_stats, get_jwks, set_jwks, sync_jwks},
     identity::generate_random_identity,
     test_env,
 };
 use ic_agent::Identity;
-use ssp_backend_types::Auth0JWKSet;
+use ssp_backend_types::{ApiError, Auth0JWKSet};
 
 #[test]
 fn test_sync_jwks_controller_only() {
@@ -16,7 +17,18 @@ fn test_sync_jwks_controller_only() {
 
     let res = sync_jwks(&env, sender).unwrap_err();
 
-    assert!(extract_trap_message(res).contains("Caller is not a controller"));
+    assert_eq!(res, ApiError::unauthorized("Caller is not a controller"));
+}
+
+#[test]
+fn test_get_delegation_stats_controller_only() {
+    let env = test_env::create_test_env();
+
+    let sender = generate_random_identity().sender().unwrap();
+
+    let res = get_delegation_stats(&env, sender).unwrap_err();
+
+    assert_eq!(res, ApiError::unauthorized("Caller is not a controller"));
 }
 
 #[test]
@@ -25,9 +37,9 @@ fn test_set_jwks_controller_only() {
 
     let sender = generate_random_identity().sender().unwrap();
 
-    let res = set_jwks(&env, sender, Auth0JWKSet { keys: vec![] }).unwrap_err();
+    let res = set_jwks(&env, sender, AUTH0_ISSUER, Auth0JWKSet { keys: vec![] }).unwrap_err();
 
-    assert!(extract_trap_message(res).contains("Caller is not a controller"));
+    assert_eq!(res, ApiError::unauthorized("Caller is not a controller"));
 }
 
 #[test]
@@ -35,21 +47,42 @@ fn test_set_jwks_once() {
     let env = test_env::create_test_env();
 
     // initially, the canister doesn't have the jwks
-    let canister_jwks = get_jwks(&env, env.controller()).unwrap();
+    let canister_jwks = get_jwks(&env, env.controller(), AUTH0_ISSUER).unwrap();
     assert!(canister_jwks.is_none());
 
     // set dummy jwks
     let jwks = Auth0JWKSet { keys: vec![] };
-    set_jwks(&env, env.controller(), jwks.clone()).unwrap();
+    set_jwks(&env, env.controller(), AUTH0_ISSUER, jwks.clone()).unwrap();
 
     // now the canister has the jwks
-    let canister_jwks = get_jwks(&env, env.controller()).unwrap().unwrap();
+    let canister_jwks = get_jwks(&env, env.controller(), AUTH0_ISSUER)
+        .unwrap()
+        .unwrap();
     assert_eq!(canister_jwks, jwks);
 
     // try to set the jwks again
-    let res = set_jwks(&env, env.controller(), jwks).unwrap_err();
-    assert!(extract_trap_message(res)
-        .contains("JWKS already set. Call sync_jwks to fetch the JWKS from the auth provider"));
+    let res = set_jwks(&env, env.controller(), AUTH0_ISSUER, jwks).unwrap_err();
+    assert_eq!(
+        res,
+        ApiError::conflict(
+            "JWKS already set. Call sync_jwks to fetch the JWKS from the auth provider"
+        )
+    );
+}
+
+#[test]
+fn test_set_jwks_unknown_provider() {
+    let env = test_env::create_test_env();
+
+    let res = set_jwks(
+        &env,
+        env.controller(),
+        "https://unknown-issuer.local/",
+        Auth0JWKSet { keys: vec![] },
+    )
+    .unwrap_err();
+
+    assert_eq!(res, ApiError::not_found("OIDC provider not found"));
 }
 
 #[test]
@@ -58,7 +91,7 @@ fn test_get_jwks_controller_only() {
 
     let sender = generate_random_identity().sender().unwrap();
 
-    let res = get_jwks(&env, sender).unwrap_err();
+    let res = get_jwks(&env, sender, AUTH0_ISSUER).unwrap_err();
 
-    assert!(extract_trap_message(res).contains("Caller is not a controller"));
+    assert_eq!(res, ApiError::unauthorized("Caller is not a controller"));
 }
@@ -4,17 +4,27 @@ use std::time::SystemTime;
 
 use candid::Principal;
 use ic_agent::Identity;
-use ic_representation_independent_hash::{representation_independent_hash, Value};
 use jwt_simple::prelude::*;
 use ssp_backend_types::{
-    GetDelegationResponse, PrepareDelegationResponse, SignedDelegation, UserKey,
+    ApiError, DelegationConfig, DelegationStats, GetDelegationResponse, OidcConfig,
+    PrepareDelegationResponse, RoleSessionDuration,
 };
 
 use common::{
-    auth_provider::{create_jwt, initialize_auth_provider},
-    canister::{extract_trap_message, get_delegation, initialize_canister, prepare_delegation},
+    auth_provider::{
+        create_ed25519_jwk, create_es256_jwk, create_jwks, create_jwt, create_jwt_for_issuer,
+        initialize_auth_provider, oidc_config, AUTH0_AUDIENCE, AUTH0_ISSUER,
+    },
+    canister::{
+        await_prepare_delegation, get_delegation, get_delegation_stats, get_jwks,
+        initialize_canister, prepare_delegation, remove_oidc_provider, revoke_all_sessions,
+        revoke_session, set_delegation_config, set_jwks, set_oidc_provider,
+        submit_prepare_delegation,
+    },
+    certification::verify_delegation,
+    http_outcalls::{reject_http_outcalls, reply_http_outcalls},
     identity::{generate_random_identity, pk_to_hex},
-    test_env::{create_test_env, upgrade_canister, TestEnv},
+    test_env::{create_test_env, upgrade_canister},
 };
 
 const NANOS_IN_SECONDS: u64 = 1_000_000_000;
@@ -27,41 +37,11 @@ const JWT_VALID_FOR_HOURS: u64 = 10;
 const TEST_USER_SUB: &str = "test_sub";
 const TEST_USER_DB_ID: &str = "fafc11f5-c784-4cbe-9fbf-207889afd519";
 
-fn verify_delegation(
-    env: &TestEnv,
-    user_key: UserKey,
-    signed_delegation: &SignedDelegation,
-    root_key: &[u8],
-) {
-    const DOMAIN_SEPARATOR: &[u8] = b"ic-request-auth-delegation";
-
-    // The signed message is a signature domain separator
-    // followed by the representation independent hash of a map with entries
-    // pubkey, expiration and targets (if any), using the respective values from the delegation.
-    // See https://internetcomputer.org/docs/current/references/ic-interface-spec#authentication for details
-    let key_value_pairs = vec![
-        (
-            "pubkey".to_string(),
-            Value::Bytes(signed_delegation.delegation.pubkey.clone().into_vec()),
-        ),
-        (
-            "expiration".to_string(),
-            Value::Number(signed_delegation.delegation.expiration),
-        ),
-    ];
-    let mut msg: Vec<u8> = Vec::from([(DOMAIN_SEPARATOR.len() as u8)]);
-    msg.extend_from_slice(DOMAIN_SEPARATOR);
-    msg.extend_from_slice(&representation_independent_hash(&key_value_pairs));
-
-    env.pic()
-        .verify_canister_signature(
-            msg,
-            signed_delegation.signature.clone().into_vec(),
-            user_key.into_vec(),
-            root_key.to_vec(),
-        )
-        .expect("delegation signature invalid");
-}
+const GOOGLE_ISSUER: &str = "https://accounts.google.com";
+const GOOGLE_AUDIENCE: &str = "google-client-id";
+
+const OTHER_ISSUER: &str = "https://other-tenant.local/";
+const OTHER_USER_DB_ID: &str = "3f1c7b2e-5d7a-4b39-8c1e-6a2d9f0b4e71";
 
 #[test]
 fn test_prepare_delegation() {
@@ -107,7 +87,10 @@ fn test_prepare_delegation_wrong_identity() {
     let wrong_identity = generate_random_identity();
     let res = prepare_delegation(&env, wrong_identity.sender().unwrap(), jwt).unwrap_err();
 
-    assert!(extract_trap_message(res).contains("caller and token principal mismatch"));
+    assert_eq!(
+        res,
+        ApiError::unauthorized("caller and token principal mismatch")
+    );
 }
 
 #[test]
@@ -128,7 +111,10 @@ fn test_prepare_delegation_anonymous() {
 
     let res = prepare_delegation(&env, Principal::anonymous(), jwt).unwrap_err();
 
-    assert!(extract_trap_message(res).contains("caller and token principal mismatch"));
+    assert_eq!(
+        res,
+        ApiError::unauthorized("caller and token principal mismatch")
+    );
 }
 
 #[test]
@@ -155,7 +141,7 @@ fn test_prepare_delegation_wrong_claims() {
         let jwt = auth_provider_key_pair.sign(claims).unwrap();
         let res = prepare_delegation(&env, session_principal, jwt).unwrap_err();
 
-        assert!(extract_trap_message(res).contains("IssuerMismatch"));
+        assert_eq!(res, ApiError::unauthorized("IssuerMismatch"));
     }
 
     // wrong audience
@@ -165,7 +151,7 @@ fn test_prepare_delegation_wrong_claims() {
         let jwt = auth_provider_key_pair.sign(claims).unwrap();
         let res = prepare_delegation(&env, session_principal, jwt).unwrap_err();
 
-        assert!(extract_trap_message(res).contains("AudienceMismatch"));
+        assert_eq!(res, ApiError::unauthorized("AudienceMismatch"));
     }
 
     // iat too old
@@ -182,7 +168,7 @@ fn test_prepare_delegation_wrong_claims() {
         let jwt = auth_provider_key_pair.sign(claims).unwrap();
         let res = prepare_delegation(&env, session_principal, jwt).unwrap_err();
 
-        assert!(extract_trap_message(res).contains("IatTooOld"));
+        assert_eq!(res, ApiError::unauthorized("IatTooOld"));
     }
 
     // expired
@@ -199,7 +185,7 @@ fn test_prepare_delegation_wrong_claims() {
         let jwt = auth_provider_key_pair.sign(claims).unwrap();
         let res = prepare_delegation(&env, session_principal, jwt).unwrap_err();
 
-        assert!(extract_trap_message(res).contains("TokenExpired"));
+        assert_eq!(res, ApiError::unauthorized("TokenExpired"));
     }
 }
 
@@ -222,14 +208,316 @@ fn test_prepare_delegation_user_id_not_found() {
 
     let res = prepare_delegation(&env, session_principal, jwt).unwrap_err();
 
-    assert!(extract_trap_message(res).contains("User ID not found in hasura claims"));
+    assert_eq!(
+        res,
+        ApiError::unauthorized("User ID not found in hasura claims")
+    );
+}
+
+#[test]
+fn test_prepare_delegation_missing_key_id() {
+    let env = create_test_env();
+    let (_, jwks) = initialize_auth_provider();
+    initialize_canister(&env, jwks);
+
+    let session_identity = generate_random_identity();
+    let session_principal = session_identity.sender().unwrap();
+    let session_public_key = session_identity.public_key().unwrap();
+    // the key pair has no key id
+    let key_pair = RS256KeyPair::generate(2048).unwrap();
+    let (jwt, _) = create_jwt(
+        &key_pair,
+        TEST_USER_SUB,
+        &pk_to_hex(&session_public_key),
+        Some(TEST_USER_DB_ID),
+        Duration::from_hours(JWT_VALID_FOR_HOURS),
+    );
+
+    let res = prepare_delegation(&env, session_principal, jwt).unwrap_err();
+
+    assert_eq!(res, ApiError::unauthorized("MissingKeyId"));
+}
+
+#[test]
+fn test_prepare_delegation_es256_and_eddsa() {
+    let env = create_test_env();
+    let (auth_provider_key_pair, mut jwks) = initialize_auth_provider();
+    let es256_key_pair = ES256KeyPair::generate().with_key_id("es256_key_id");
+    let ed25519_key_pair = Ed25519KeyPair::generate().with_key_id("ed25519_key_id");
+    jwks.keys.push(create_es256_jwk(&es256_key_pair));
+    jwks.keys.push(create_ed25519_jwk(&ed25519_key_pair));
+    initialize_canister(&env, jwks);
+
+    let session_identity = generate_random_identity();
+    let session_principal = session_identity.sender().unwrap();
+    let session_public_key = session_identity.public_key().unwrap();
+    let (_, claims) = create_jwt(
+        &auth_provider_key_pair,
+        TEST_USER_SUB,
+        &pk_to_hex(&session_public_key),
+        Some(TEST_USER_DB_ID),
+        Duration::from_hours(JWT_VALID_FOR_HOURS),
+    );
+
+    let es256_jwt = es256_key_pair.sign(claims.clone()).unwrap();
+    let es256_res = prepare_delegation(&env, session_principal, es256_jwt.clone()).unwrap();
+    match get_delegation(&env, session_principal, es256_jwt, es256_res.expiration).unwrap() {
+        GetDelegationResponse::SignedDelegation(_) => {}
+        _ => panic!("expected GetDelegationResponse::SignedDelegation"),
+    }
+
+    let ed25519_jwt = ed25519_key_pair.sign(claims.clone()).unwrap();
+    let ed25519_res = prepare_delegation(&env, session_principal, ed25519_jwt).unwrap();
+
+    // the algorithm doesn't change the principal
+    assert_eq!(es256_res.user_key, ed25519_res.user_key);
+
+    // the key is only used with its algorithm
+    let es256_key_pair = ES256KeyPair::generate().with_key_id("ed25519_key_id");
+    let jwt = es256_key_pair.sign(claims).unwrap();
+    let res = prepare_delegation(&env, session_principal, jwt).unwrap_err();
+    assert_eq!(res, ApiError::unauthorized("InvalidAlgorithm"));
+}
+
+#[test]
+fn test_prepare_delegation_unknown_key_id_refreshes_jwks() {
+    let env = create_test_env();
+    let (_, jwks) = initialize_auth_provider();
+    assert_eq!(
+        reply_http_outcalls(&env, serde_json::to_vec(&jwks).unwrap()),
+        1
+    );
+
+    let session_identity = generate_random_identity();
+    let session_principal = session_identity.sender().unwrap();
+    let session_public_key = session_identity.public_key().unwrap();
+
+    // the auth provider rotates its keys
+    let rotated_key_pair = RS256KeyPair::generate(2048)
+        .unwrap()
+        .with_key_id("rotated_key_id");
+    let rotated_jwks = create_jwks(&rotated_key_pair);
+    let (jwt, _) = create_jwt(
+        &rotated_key_pair,
+        TEST_USER_SUB,
+        &pk_to_hex(&session_public_key),
+        Some(TEST_USER_DB_ID),
+        Duration::from_hours(JWT_VALID_FOR_HOURS),
+    );
+
+    let message_id = submit_prepare_delegation(&env, session_principal, jwt);
+    assert_eq!(
+        reply_http_outcalls(&env, serde_json::to_vec(&rotated_jwks).unwrap()),
+        1
+    );
+    await_prepare_delegation(&env, message_id).unwrap();
+    assert_eq!(
+        get_jwks(&env, env.controller(), AUTH0_ISSUER).unwrap(),
+        Some(rotated_jwks)
+    );
+
+    // the JWKS has just been refreshed, so another unknown key id doesn't trigger a refresh
+    let unknown_key_pair = RS256KeyPair::generate(2048)
+        .unwrap()
+        .with_key_id("unknown_key_id");
+    let (jwt, _) = create_jwt(
+        &unknown_key_pair,
+        TEST_USER_SUB,
+        &pk_to_hex(&session_public_key),
+        Some(TEST_USER_DB_ID),
+        Duration::from_hours(JWT_VALID_FOR_HOURS),
+    );
+    let res = prepare_delegation(&env, session_principal, jwt.clone()).unwrap_err();
+    assert_eq!(res, ApiError::unauthorized("KeyNotFound"));
+    assert_eq!(reject_http_outcalls(&env), 0);
+
+    // the JWKS is refreshed again after 5 minutes
+    env.advance_canister_time(std::time::Duration::from_secs(5 * 60));
+    let message_id = submit_prepare_delegation(&env, session_principal, jwt);
+    assert_eq!(reject_http_outcalls(&env), 1);
+    let res = await_prepare_delegation(&env, message_id).unwrap_err();
+    assert_eq!(res, ApiError::unauthorized("KeyNotFound"));
+}
+
+#[test]
+fn test_prepare_delegation_oidc_config() {
+    let env = create_test_env();
+    let (auth_provider_key_pair, jwks) = initialize_auth_provider();
+    initialize_canister(&env, jwks);
+
+    let session_identity = generate_random_identity();
+    let session_principal = session_identity.sender().unwrap();
+    let session_public_key = session_identity.public_key().unwrap();
+    let (jwt, _) = create_jwt(
+        &auth_provider_key_pair,
+        TEST_USER_SUB,
+        &pk_to_hex(&session_public_key),
+        Some(TEST_USER_DB_ID),
+        Duration::from_hours(JWT_VALID_FOR_HOURS),
+    );
+
+    // the token is issued for a different audience
+    set_oidc_provider(
+        &env,
+        env.controller(),
+        OidcConfig {
+            audiences: vec!["other-audience".to_string()],
+            ..oidc_config()
+        },
+    )
+    .unwrap();
+    let res = prepare_delegation(&env, session_principal, jwt.clone()).unwrap_err();
+    assert_eq!(res, ApiError::unauthorized("AudienceMismatch"));
+
+    // the custom claims are under a different namespace
+    set_oidc_provider(
+        &env,
+        env.controller(),
+        OidcConfig {
+            claims_namespace: Some("https://other-issuer.local/claims".to_string()),
+            ..oidc_config()
+        },
+    )
+    .unwrap();
+    let res = prepare_delegation(&env, session_principal, jwt.clone()).unwrap_err();
+    assert_eq!(
+        res,
+        ApiError::unauthorized("User ID not found in hasura claims")
+    );
+
+    // any of the configured audiences is accepted
+    set_oidc_provider(
+        &env,
+        env.controller(),
+        OidcConfig {
+            audiences: vec!["other-audience".to_string(), AUTH0_AUDIENCE.to_string()],
+            ..oidc_config()
+        },
+    )
+    .unwrap();
+    prepare_delegation(&env, session_principal, jwt.clone()).unwrap();
+
+    // the provider has been removed
+    remove_oidc_provider(&env, env.controller(), AUTH0_ISSUER).unwrap();
+    let res = prepare_delegation(&env, session_principal, jwt).unwrap_err();
+    assert_eq!(res, ApiError::unauthorized("IssuerMismatch"));
+}
+
+#[test]
+fn test_prepare_delegation_multiple_providers() {
+    let env = create_test_env();
+    let (auth0_key_pair, auth0_jwks) = initialize_auth_provider();
+    initialize_canister(&env, auth0_jwks);
+
+    // Google doesn't set the Hasura claims
+    let (google_key_pair, google_jwks) = initialize_auth_provider();
+    set_oidc_provider(
+        &env,
+        env.controller(),
+        OidcConfig {
+            issuer: GOOGLE_ISSUER.to_string(),
+            audiences: vec![GOOGLE_AUDIENCE.to_string()],
+            jwks_url: "https://www.googleapis.com/oauth2/v3/certs".to_string(),
+            claims_namespace: None,
+        },
+    )
+    .unwrap();
+    set_jwks(&env, env.controller(), GOOGLE_ISSUER, google_jwks).unwrap();
+
+    let session_identity = generate_random_identity();
+    let session_principal = session_identity.sender().unwrap();
+    let nonce = pk_to_hex(&session_identity.public_key().unwrap());
+    let (auth0_jwt, _) = create_jwt(
+        &auth0_key_pair,
+        TEST_USER_SUB,
+        &nonce,
+        Some(TEST_USER_DB_ID),
+        Duration::from_hours(JWT_VALID_FOR_HOURS),
+    );
+    let (google_jwt, google_claims) = create_jwt_for_issuer(
+        &google_key_pair,
+        GOOGLE_ISSUER,
+        GOOGLE_AUDIENCE,
+        TEST_USER_SUB,
+        &nonce,
+        None,
+        Duration::from_hours(JWT_VALID_FOR_HOURS),
+    );
+
+    let auth0_res = prepare_delegation(&env, session_principal, auth0_jwt).unwrap();
+    let google_res = prepare_delegation(&env, session_principal, google_jwt.clone()).unwrap();
+
+    // the same sub at two providers results in two different principals
+    assert_ne!(auth0_res.user_key, google_res.user_key);
+
+    match get_delegation(&env, session_principal, google_jwt, google_res.expiration).unwrap() {
+        GetDelegationResponse::SignedDelegation(_) => {}
+        _ => panic!("expected GetDelegationResponse::SignedDelegation"),
+    }
+
+    // the token is verified with the JWKS of its issuer, not of the other providers
+    let jwt = auth0_key_pair.sign(google_claims).unwrap();
+    let res = prepare_delegation(&env, session_principal, jwt).unwrap_err();
+    assert_eq!(res, ApiError::unauthorized("InvalidSignature"));
+}
+
+#[test]
+fn test_prepare_delegation_multiple_providers_with_hasura_claims() {
+    let env = create_test_env();
+    let (auth0_key_pair, auth0_jwks) = initialize_auth_provider();
+    initialize_canister(&env, auth0_jwks);
+
+    let (other_key_pair, other_jwks) = initialize_auth_provider();
+    set_oidc_provider(
+        &env,
+        env.controller(),
+        OidcConfig {
+            issuer: OTHER_ISSUER.to_string(),
+            jwks_url: format!("{OTHER_ISSUER}.well-known/jwks.json"),
+            ..oidc_config()
+        },
+    )
+    .unwrap();
+    set_jwks(&env, env.controller(), OTHER_ISSUER, other_jwks).unwrap();
+
+    let session_identity = generate_random_identity();
+    let session_principal = session_identity.sender().unwrap();
+    let nonce = pk_to_hex(&session_identity.public_key().unwrap());
+    let (auth0_jwt, _) = create_jwt(
+        &auth0_key_pair,
+        TEST_USER_SUB,
+        &nonce,
+        Some(TEST_USER_DB_ID),
+        Duration::from_hours(JWT_VALID_FOR_HOURS),
+    );
+    let (other_jwt, _) = create_jwt_for_issuer(
+        &other_key_pair,
+        OTHER_ISSUER,
+        AUTH0_AUDIENCE,
+        TEST_USER_SUB,
+        &nonce,
+        Some(OTHER_USER_DB_ID),
+        Duration::from_hours(JWT_VALID_FOR_HOURS),
+    );
+
+    // the same sub at two providers is linked to two different database users
+    let auth0_res = prepare_delegation(&env, session_principal, auth0_jwt.clone()).unwrap();
+    let other_res = prepare_delegation(&env, session_principal, other_jwt.clone()).unwrap();
+    assert_ne!(auth0_res.user_key, other_res.user_key);
+
+    // the existing users are found again, also after an upgrade
+    upgrade_canister(&env);
+    let auth0_res_after_upgrade = prepare_delegation(&env, session_principal, auth0_jwt).unwrap();
+    let other_res_after_upgrade = prepare_delegation(&env, session_principal, other_jwt).unwrap();
+    assert_eq!(auth0_res_after_upgrade.user_key, auth0_res.user_key);
+    assert_eq!(other_res_after_upgrade.user_key, other_res.user_key);
 }
 
 #[test]
 fn test_prepare_delegation_across_upgrades() {
     let env = create_test_env();
     let (auth_provider_key_pair, jwks) = initialize_auth_provider();
-    initialize_canister(&env, jwks.clone());
+    initialize_canister(&env, jwks);
 
     let session_identity = generate_random_identity();
     let session_principal = session_identity.sender().unwrap();
@@ -245,13 +533,92 @@ fn test_prepare_delegation_across_upgrades() {
     let res_before_upgrade = prepare_delegation(&env, session_principal, jwt.clone()).unwrap();
 
     upgrade_canister(&env);
-    initialize_canister(&env, jwks);
 
     let res_after_upgrade = prepare_delegation(&env, session_principal, jwt).unwrap();
 
     assert_eq!(res_before_upgrade.user_key, res_after_upgrade.user_key);
 }
 
+#[test]
+fn test_jwks_fetched_from_auth_provider() {
+    let env = create_test_env();
+    let (auth_provider_key_pair, jwks) = initialize_auth_provider();
+
+    assert_eq!(
+        reply_http_outcalls(&env, serde_json::to_vec(&jwks).unwrap()),
+        1
+    );
+    assert_eq!(
+        get_jwks(&env, env.controller(), AUTH0_ISSUER).unwrap(),
+        Some(jwks.clone())
+    );
+
+    let session_identity = generate_random_identity();
+    let session_principal = session_identity.sender().unwrap();
+    let (jwt, _) = create_jwt(
+        &auth_provider_key_pair,
+        TEST_USER_SUB,
+        &pk_to_hex(&session_identity.public_key().unwrap()),
+        Some(TEST_USER_DB_ID),
+        Duration::from_hours(JWT_VALID_FOR_HOURS),
+    );
+    prepare_delegation(&env, session_principal, jwt.clone()).unwrap();
+
+    // the JWKS has just been fetched, so it's not fetched again after the upgrade
+    upgrade_canister(&env);
+    assert_eq!(reject_http_outcalls(&env), 0);
+    prepare_delegation(&env, session_principal, jwt).unwrap();
+
+    // the JWKS is fetched again after 1 hour
+    env.advance_canister_time(std::time::Duration::from_secs(60 * 60));
+    assert_eq!(
+        reply_http_outcalls(&env, serde_json::to_vec(&jwks).unwrap()),
+        1
+    );
+}
+
+#[test]
+fn test_jwks_kept_when_fetch_fails_across_upgrades() {
+    let env = create_test_env();
+    let (auth_provider_key_pair, jwks) = initialize_auth_provider();
+    initialize_canister(&env, jwks.clone());
+
+    // the auth provider is unreachable
+    assert_eq!(reject_http_outcalls(&env), 1);
+    assert_eq!(
+        get_jwks(&env, env.controller(), AUTH0_ISSUER).unwrap(),
+        Some(jwks.clone())
+    );
+
+    // the fetch is retried after 1 minute, then after 2 minutes
+    env.advance_canister_time(std::time::Duration::from_secs(60));
+    assert_eq!(reject_http_outcalls(&env), 1);
+    env.advance_canister_time(std::time::Duration::from_secs(60));
+    assert_eq!(reject_http_outcalls(&env), 0);
+    env.advance_canister_time(std::time::Duration::from_secs(60));
+    assert_eq!(reject_http_outcalls(&env), 1);
+
+    // the fetch fails after the upgrade too
+    upgrade_canister(&env);
+    assert_eq!(reject_http_outcalls(&env), 1);
+    assert_eq!(
+        get_jwks(&env, env.controller(), AUTH0_ISSUER).unwrap(),
+        Some(jwks)
+    );
+
+    // the last known JWKS is still used to verify the JWTs
+    let session_identity = generate_random_identity();
+    let session_principal = session_identity.sender().unwrap();
+    let (jwt, _) = create_jwt(
+        &auth_provider_key_pair,
+        TEST_USER_SUB,
+        &pk_to_hex(&session_identity.public_key().unwrap()),
+        Some(TEST_USER_DB_ID),
+        Duration::from_hours(JWT_VALID_FOR_HOURS),
+    );
+    prepare_delegation(&env, session_principal, jwt).unwrap();
+}
+
 #[test]
 fn test_prepare_delegation_different_sessions() {
     let env = create_test_env();
@@ -323,6 +690,43 @@ fn test_get_delegation() {
     }
 }
 
+#[test]
+fn test_get_delegation_across_upgrades() {
+    let env = create_test_env();
+    let (auth_provider_key_pair, jwks) = initialize_auth_provider();
+    initialize_canister(&env, jwks);
+
+    let session_identity = generate_random_identity();
+    let session_principal = session_identity.sender().unwrap();
+    let session_public_key = session_identity.public_key().unwrap();
+    let (jwt, _) = create_jwt(
+        &auth_provider_key_pair,
+        TEST_USER_SUB,
+        &pk_to_hex(&session_public_key),
+        Some(TEST_USER_DB_ID),
+        Duration::from_hours(JWT_VALID_FOR_HOURS),
+    );
+
+    let PrepareDelegationResponse {
+        expiration,
+        user_key,
+    } = prepare_delegation(&env, session_principal, jwt.clone()).unwrap();
+
+    upgrade_canister(&env);
+
+    let res = get_delegation(&env, session_principal, jwt, expiration).unwrap();
+
+    match res {
+        GetDelegationResponse::SignedDelegation(signed_delegation) => {
+            assert_eq!(signed_delegation.delegation.pubkey, session_public_key);
+            assert_eq!(signed_delegation.delegation.expiration, expiration);
+
+            verify_delegation(&env, user_key, &signed_delegation, env.root_ic_key());
+        }
+        _ => panic!("Expected SignedDelegation"),
+    }
+}
+
 #[test]
 fn test_get_delegation_wrong_sub() {
     let env = create_test_env();
@@ -399,7 +803,10 @@ fn test_get_delegation_wrong_identity() {
     let wrong_identity = generate_random_identity();
     let res = get_delegation(&env, wrong_identity.sender().unwrap(), jwt, 0).unwrap_err();
 
-    assert!(extract_trap_message(res).contains("caller and token principal mismatch"));
+    assert_eq!(
+        res,
+        ApiError::unauthorized("caller and token principal mismatch")
+    );
 }
 
 #[test]
@@ -420,7 +827,10 @@ fn test_get_delegation_anonymous() {
 
     let res = get_delegation(&env, Principal::anonymous(), jwt, 0).unwrap_err();
 
-    assert!(extract_trap_message(res).contains("caller and token principal mismatch"));
+    assert_eq!(
+        res,
+        ApiError::unauthorized("caller and token principal mismatch")
+    );
 }
 
 #[test]
@@ -447,7 +857,7 @@ fn test_get_delegation_wrong_claims() {
         let jwt = auth_provider_key_pair.sign(claims).unwrap();
         let res = get_delegation(&env, session_principal, jwt, 0).unwrap_err();
 
-        assert!(extract_trap_message(res).contains("IssuerMismatch"));
+        assert_eq!(res, ApiError::unauthorized("IssuerMismatch"));
     }
 
     // wrong audience
@@ -457,7 +867,7 @@ fn test_get_delegation_wrong_claims() {
         let jwt = auth_provider_key_pair.sign(claims).unwrap();
         let res = get_delegation(&env, session_principal, jwt, 0).unwrap_err();
 
-        assert!(extract_trap_message(res).contains("AudienceMismatch"));
+        assert_eq!(res, ApiError::unauthorized("AudienceMismatch"));
     }
 
     // iat too old
@@ -471,7 +881,7 @@ fn test_get_delegation_wrong_claims() {
         let jwt = auth_provider_key_pair.sign(claims).unwrap();
         let res = get_delegation(&env, session_principal, jwt, 0).unwrap_err();
 
-        assert!(extract_trap_message(res).contains("IatTooOld"));
+        assert_eq!(res, ApiError::unauthorized("IatTooOld"));
     }
 
     // expired
@@ -485,6 +895,357 @@ fn test_get_delegation_wrong_claims() {
         let jwt = auth_provider_key_pair.sign(claims).unwrap();
         let res = get_delegation(&env, session_principal, jwt, 0).unwrap_err();
 
-        assert!(extract_trap_message(res).contains("TokenExpired"));
+        assert_eq!(res, ApiError::unauthorized("TokenExpired"));
+    }
+}
+
+#[test]
+fn test_revoke_session() {
+    let env = create_test_env();
+    let (auth_provider_key_pair, jwks) = initialize_auth_provider();
+    initialize_canister(&env, jwks);
+
+    let session1_identity = generate_random_identity();
+    let session1_principal = session1_identity.sender().unwrap();
+    let session1_public_key = session1_identity.public_key().unwrap();
+    let (jwt1, _) = create_jwt(
+        &auth_provider_key_pair,
+        TEST_USER_SUB,
+        &pk_to_hex(&session1_public_key),
+        Some(TEST_USER_DB_ID),
+        Duration::from_hours(JWT_VALID_FOR_HOURS),
+    );
+    let PrepareDelegationResponse {
+        expiration: expiration1,
+        user_key,
+    } = prepare_delegation(&env, session1_principal, jwt1.clone()).unwrap();
+
+    let session2_identity = generate_random_identity();
+    let session2_principal = session2_identity.sender().unwrap();
+    let session2_public_key = session2_identity.public_key().unwrap();
+    let (jwt2, _) = create_jwt(
+        &auth_provider_key_pair,
+        TEST_USER_SUB,
+        &pk_to_hex(&session2_public_key),
+        Some(TEST_USER_DB_ID),
+        Duration::from_hours(JWT_VALID_FOR_HOURS),
+    );
+    let PrepareDelegationResponse {
+        expiration: expiration2,
+        ..
+    } = prepare_delegation(&env, session2_principal, jwt2.clone()).unwrap();
+
+    let user_principal = Principal::self_authenticating(&user_key);
+    revoke_session(&env, user_principal, session1_public_key).unwrap();
+
+    let res = get_delegation(&env, session1_principal, jwt1.clone(), expiration1).unwrap_err();
+    assert_eq!(res, ApiError::unauthorized("Session revoked"));
+    // the ID token is still valid, but can't be used to sign the revoked session again
+    let res = prepare_delegation(&env, session1_principal, jwt1).unwrap_err();
+    assert_eq!(res, ApiError::unauthorized("Session revoked"));
+
+    // the other session is still valid, also with the updated certified data
+    let res = get_delegation(&env, session2_principal, jwt2, expiration2).unwrap();
+    match res {
+        GetDelegationResponse::SignedDelegation(signed_delegation) => {
+            assert_eq!(signed_delegation.delegation.pubkey, session2_public_key);
+
+            verify_delegation(&env, user_key, &signed_delegation, env.root_ic_key());
+        }
+        _ => panic!("Expected SignedDelegation"),
+    }
+}
+
+#[test]
+fn test_revoke_session_max_session_duration() {
+    let env = create_test_env();
+    let (auth_provider_key_pair, jwks) = initialize_auth_provider();
+    initialize_canister(&env, jwks);
+
+    // the delegation expiration depends on the time at which it is prepared
+    set_delegation_config(
+        &env,
+        env.controller(),
+        DelegationConfig {
+            max_session_duration_ns: Some(60 * NANOS_IN_SECONDS),
+            ..DelegationConfig::default()
+        },
+    )
+    .unwrap();
+
+    let session_identity = generate_random_identity();
+    let session_principal = session_identity.sender().unwrap();
+    let session_public_key = session_identity.public_key().unwrap();
+    let (jwt, _) = create_jwt(
+        &auth_provider_key_pair,
+        TEST_USER_SUB,
+        &pk_to_hex(&session_public_key),
+        Some(TEST_USER_DB_ID),
+        Duration::from_hours(JWT_VALID_FOR_HOURS),
+    );
+    let PrepareDelegationResponse { user_key, .. } =
+        prepare_delegation(&env, session_principal, jwt.clone()).unwrap();
+
+    let user_principal = Principal::self_authenticating(&user_key);
+    revoke_session(&env, user_principal, session_public_key).unwrap();
+
+    // the same ID token and session key can't sign the session again later
+    env.advance_canister_time(Duration::from_secs(5));
+    let res = prepare_delegation(&env, session_principal, jwt).unwrap_err();
+    assert_eq!(res, ApiError::unauthorized("Session revoked"));
+}
+
+#[test]
+fn test_prune_expired_delegations() {
+    let env = create_test_env();
+    let (auth_provider_key_pair, jwks) = initialize_auth_provider();
+    initialize_canister(&env, jwks);
+
+    set_delegation_config(
+        &env,
+        env.controller(),
+        DelegationConfig {
+            max_session_duration_ns: Some(60 * NANOS_IN_SECONDS),
+            ..DelegationConfig::default()
+        },
+    )
+    .unwrap();
+
+    let mut user_key = None;
+    let mut session_public_keys = vec![];
+    for _ in 0..2 {
+        let session_identity = generate_random_identity();
+        let session_public_key = session_identity.public_key().unwrap();
+        let (jwt, _) = create_jwt(
+            &auth_provider_key_pair,
+            TEST_USER_SUB,
+            &pk_to_hex(&session_public_key),
+            Some(TEST_USER_DB_ID),
+            Duration::from_hours(JWT_VALID_FOR_HOURS),
+        );
+        let res = prepare_delegation(&env, session_identity.sender().unwrap(), jwt).unwrap();
+        user_key = Some(res.user_key);
+        session_public_keys.push(session_public_key);
+    }
+
+    let user_principal = Principal::self_authenticating(user_key.unwrap());
+    revoke_session(&env, user_principal, session_public_keys[0].clone()).unwrap();
+
+    let stats = get_delegation_stats(&env, env.controller()).unwrap();
+    assert_eq!(
+        stats,
+        DelegationStats {
+            signatures_count: 1,
+            revoked_sessions_count: 1,
+            revoked_delegations_count: 0,
+        }
+    );
+
+    // the prune runs every hour, after the delegations and the revoked session have expired
+    env.advance_canister_time(Duration::from_secs(2 * 60 * 60));
+
+    let stats = get_delegation_stats(&env, env.controller()).unwrap();
+    assert_eq!(
+        stats,
+        DelegationStats {
+            signatures_count: 0,
+            revoked_sessions_count: 0,
+            revoked_delegations_count: 0,
+        }
+    );
+}
+
+#[test]
+fn test_revoke_session_not_found() {
+    let env = create_test_env();
+    let (auth_provider_key_pair, jwks) = initialize_auth_provider();
+    initialize_canister(&env, jwks);
+
+    let session_identity = generate_random_identity();
+    let session_principal = session_identity.sender().unwrap();
+    let session_public_key = session_identity.public_key().unwrap();
+    let (jwt, _) = create_jwt(
+        &auth_provider_key_pair,
+        TEST_USER_SUB,
+        &pk_to_hex(&session_public_key),
+        Some(TEST_USER_DB_ID),
+        Duration::from_hours(JWT_VALID_FOR_HOURS),
+    );
+
+    // the session principal is not a user principal
+    let res = revoke_session(&env, session_principal, session_public_key.clone()).unwrap_err();
+    assert_eq!(res, ApiError::not_found("No session found"));
+    let res = revoke_all_sessions(&env, session_principal).unwrap_err();
+    assert_eq!(res, ApiError::not_found("No session found"));
+
+    let PrepareDelegationResponse { user_key, .. } =
+        prepare_delegation(&env, session_principal, jwt).unwrap();
+    let user_principal = Principal::self_authenticating(&user_key);
+
+    let other_session_public_key = generate_random_identity().public_key().unwrap();
+    let res = revoke_session(&env, user_principal, other_session_public_key).unwrap_err();
+    assert_eq!(res, ApiError::not_found("Session not found"));
+
+    revoke_session(&env, user_principal, session_public_key.clone()).unwrap();
+    let res = revoke_session(&env, user_principal, session_public_key).unwrap_err();
+    assert_eq!(res, ApiError::not_found("Session not found"));
+}
+
+#[test]
+fn test_revoke_all_sessions_across_upgrades() {
+    let env = create_test_env();
+    let (auth_provider_key_pair, jwks) = initialize_auth_provider();
+    initialize_canister(&env, jwks);
+
+    let sessions: Vec<_> = (0..2)
+        .map(|_| {
+            let session_identity = generate_random_identity();
+            let session_principal = session_identity.sender().unwrap();
+            let (jwt, _) = create_jwt(
+                &auth_provider_key_pair,
+                TEST_USER_SUB,
+                &pk_to_hex(&session_identity.public_key().unwrap()),
+                Some(TEST_USER_DB_ID),
+                Duration::from_hours(JWT_VALID_FOR_HOURS),
+            );
+            let res = prepare_delegation(&env, session_principal, jwt.clone()).unwrap();
+            (session_principal, jwt, res)
+        })
+        .collect();
+
+    let user_principal = Principal::self_authenticating(&sessions[0].2.user_key);
+    revoke_all_sessions(&env, user_principal).unwrap();
+
+    upgrade_canister(&env);
+
+    for (session_principal, jwt, res) in sessions {
+        let err = get_delegation(&env, session_principal, jwt, res.expiration).unwrap_err();
+        assert_eq!(err, ApiError::unauthorized("Session revoked"));
+    }
+
+    // a new session can still be created
+    let session_identity = generate_random_identity();
+    let session_principal = session_identity.sender().unwrap();
+    let (jwt, _) = create_jwt(
+        &auth_provider_key_pair,
+        TEST_USER_SUB,
+        &pk_to_hex(&session_identity.public_key().unwrap()),
+        Some(TEST_USER_DB_ID),
+        Duration::from_hours(JWT_VALID_FOR_HOURS),
+    );
+    let PrepareDelegationResponse { expiration, .. } =
+        prepare_delegation(&env, session_principal, jwt.clone()).unwrap();
+    let res = get_delegation(&env, session_principal, jwt, expiration).unwrap();
+    assert!(matches!(res, GetDelegationResponse::SignedDelegation(_)));
+}
+
+#[test]
+fn test_prepare_delegation_max_session_duration() {
+    let env = create_test_env();
+    let (auth_provider_key_pair, jwks) = initialize_auth_provider();
+    initialize_canister(&env, jwks);
+
+    let one_hour_ns = 60 * 60 * NANOS_IN_SECONDS;
+    let ten_minutes_ns = 10 * 60 * NANOS_IN_SECONDS;
+    let prepare_session = |max_session_duration_ns: u64| {
+        let session_identity = generate_random_identity();
+        let (jwt, claims) = create_jwt(
+            &auth_provider_key_pair,
+            TEST_USER_SUB,
+            &pk_to_hex(&session_identity.public_key().unwrap()),
+            Some(TEST_USER_DB_ID),
+            Duration::from_hours(JWT_VALID_FOR_HOURS),
+        );
+        let now = env
+            .get_canister_time()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos() as u64;
+
+        let res = prepare_delegation(&env, session_identity.sender().unwrap(), jwt).unwrap();
+
+        assert!(res.expiration < claims.expires_at.unwrap().as_secs() * NANOS_IN_SECONDS);
+        assert!(res.expiration >= now + max_session_duration_ns);
+        assert!(res.expiration < now + max_session_duration_ns + NANOS_IN_SECONDS);
+    };
+
+    set_delegation_config(
+        &env,
+        env.controller(),
+        DelegationConfig {
+            max_session_duration_ns: Some(one_hour_ns),
+            role_session_durations: vec![RoleSessionDuration {
+                role: "admin".to_string(),
+                max_session_duration_ns: ten_minutes_ns,
+            }],
+            targets: None,
+        },
+    )
+    .unwrap();
+    prepare_session(one_hour_ns);
+
+    // the test users have the "user" role
+    set_delegation_config(
+        &env,
+        env.controller(),
+        DelegationConfig {
+            max_session_duration_ns: Some(one_hour_ns),
+            role_session_durations: vec![RoleSessionDuration {
+                role: "user".to_string(),
+                max_session_duration_ns: ten_minutes_ns,
+            }],
+            targets: None,
+        },
+    )
+    .unwrap();
+    prepare_session(ten_minutes_ns);
+}
+
+#[test]
+fn test_get_delegation_targets() {
+    let env = create_test_env();
+    let (auth_provider_key_pair, jwks) = initialize_auth_provider();
+    initialize_canister(&env, jwks);
+
+    let targets = vec![
+        env.canister_id(),
+        generate_random_identity().sender().unwrap(),
+    ];
+    set_delegation_config(
+        &env,
+        env.controller(),
+        DelegationConfig {
+            targets: Some(targets.clone()),
+            ..DelegationConfig::default()
+        },
+    )
+    .unwrap();
+
+    let session_identity = generate_random_identity();
+    let session_principal = session_identity.sender().unwrap();
+    let session_public_key = session_identity.public_key().unwrap();
+    let (jwt, _) = create_jwt(
+        &auth_provider_key_pair,
+        TEST_USER_SUB,
+        &pk_to_hex(&session_public_key),
+        Some(TEST_USER_DB_ID),
+        Duration::from_hours(JWT_VALID_FOR_HOURS),
+    );
+
+    let PrepareDelegationResponse {
+        expiration,
+        user_key,
+    } = prepare_delegation(&env, session_principal, jwt.clone()).unwrap();
+
+    let res = get_delegation(&env, session_principal, jwt, expiration).unwrap();
+
+    match res {
+        GetDelegationResponse::SignedDelegation(signed_delegation) => {
+            assert_eq!(signed_delegation.delegation.targets, Some(targets));
+
+            // the signature covers the targets
+            verify_delegation(&env, user_key, &signed_delegation, env.root_ic_key());
+        }
+        _ => panic!("Expected SignedDelegation"),
     }
 }
@@ -0,0 +1,296 @@
+pub mod common;
+
+use candid::{Nat, Principal};
+use common::{
+    auth_provider::initialize_auth_provider,
+    canister::{
+        create_certificate, icrc3_get_blocks, icrc3_get_tip_certificate,
+        icrc3_supported_block_types, initialize_canister, revoke_certificate,
+        set_backend_principal, update_certificate,
+    },
+    certification::{assert_ic_certification_is_valid, sha256},
+    fixtures::{account_value, certificate_request, sign_up},
+    identity::generate_random_identity,
+    test_env::{self, upgrade_canister, TestEnv},
+};
+use ic_agent::Identity;
+use ic_certification::{HashTree, LookupResult};
+use serde_bytes::ByteBuf;
+use ssp_backend_types::{
+    BlockWithId, CreateCertificateContentRequest, GetBlocksRequest, Icrc3Value,
+    RevokeCertificateRequest, UpdateCertificateRequest, MAX_ICRC3_BLOCKS_PER_RESPONSE,
+};
+
+const TEST_USER_SUB: &str = "test_sub";
+const TEST_USER_DB_ID: &str = "96b51c08-9846-40f2-8f37-a1e4421e2ba8";
+const MANAGED_USER_SUB: &str = "test_managed_user_sub";
+const MANAGED_USER_DB_ID: &str = "ccb31f93-1a16-4089-bc84-1822ae591da2";
+
+fn leb128(mut n: u64) -> Vec<u8> {
+    let mut bytes = vec![];
+    loop {
+        let byte = (n & 0x7f) as u8;
+        n >>= 7;
+        if n == 0 {
+            bytes.push(byte);
+            return bytes;
+        }
+        bytes.push(byte | 0x80);
+    }
+}
+
+/// The representation independent hash of an ICRC-3 value.
+fn icrc3_hash(value: &Icrc3Value) -> Vec<u8> {
+    match value {
+        Icrc3Value::Blob(bytes) => sha256(bytes),
+        Icrc3Value::Text(text) => sha256(text.as_bytes()),
+        Icrc3Value::Nat(nat) => sha256(&leb128(u64::try_from(&nat.0).unwrap())),
+        Icrc3Value::Int(_) => unimplemented!("no int values in certificate blocks"),
+        Icrc3Value::Array(values) => {
+            sha256(&values.iter().flat_map(icrc3_hash).collect::<Vec<_>>())
+        }
+        Icrc3Value::Map(entries) => {
+            let mut entry_hashes: Vec<Vec<u8>> = entries
+                .iter()
+                .map(|(key, value)| [sha256(key.as_bytes()), icrc3_hash(value)].concat())
+                .collect();
+            entry_hashes.sort();
+            sha256(&entry_hashes.concat())
+        }
+    }
+}
+
+fn field<'a>(value: &'a Icrc3Value, key: &str) -> Option<&'a Icrc3Value> {
+    match value {
+        Icrc3Value::Map(entries) => entries.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+        _ => panic!("expected Icrc3Value::Map"),
+    }
+}
+
+fn get_all_blocks(env: &TestEnv) -> Vec<BlockWithId> {
+    let res = icrc3_get_blocks(
+        env,
+        Principal::anonymous(),
+        vec![GetBlocksRequest {
+            start: Nat::from(0u64),
+            length: Nat::from(MAX_ICRC3_BLOCKS_PER_RESPONSE),
+        }],
+    );
+    assert_eq!(res.log_length, Nat::from(res.blocks.len()));
+    assert!(res.archived_blocks.is_empty());
+
+    res.blocks
+}
+
+/// Verifies that the blocks are hash-chained and that the last block is certified.
+fn assert_blocks_are_valid(env: &TestEnv, blocks: &[BlockWithId]) {
+    let mut parent_hash = None;
+    for (index, block) in blocks.iter().enumerate() {
+        assert_eq!(block.id, Nat::from(index));
+        assert_eq!(
+            field(&block.block, "phash"),
+            parent_hash
+                .map(|hash| Icrc3Value::Blob(ByteBuf::from(hash)))
+                .as_ref()
+        );
+        parent_hash = Some(icrc3_hash(&block.block));
+    }
+
+    let tip_certificate = icrc3_get_tip_certificate(env, Principal::anonymous()).unwrap();
+    assert_ic_certification_is_valid(
+        env,
+        tip_certificate.certificate.into_vec(),
+        tip_certificate.hash_tree.to_vec(),
+    );
+    let tree: HashTree = serde_cbor::from_slice(&tip_certificate.hash_tree).unwrap();
+    match tree.lookup_path(vec![b"last_block_index".as_slice()]) {
+        LookupResult::Found(index) => assert_eq!(index, leb128(blocks.len() as u64 - 1)),
+        _ => panic!("expected LookupResult::Found"),
+    }
+    match tree.lookup_path(vec![b"last_block_hash".as_slice()]) {
+        LookupResult::Found(hash) => assert_eq!(hash, parent_hash.unwrap()),
+        _ => panic!("expected LookupResult::Found"),
+    }
+}
+
+fn assert_block(
+    block: &BlockWithId,
+    btype: &str,
+    certificate_id: &str,
+    version: u32,
+    to: Principal,
+) -> Icrc3Value {
+    assert_eq!(
+        field(&block.block, "btype"),
+        Some(&Icrc3Value::Text(btype.to_string()))
+    );
+    let tx = field(&block.block, "tx").unwrap().clone();
+    assert_eq!(
+        field(&tx, "certificate_id"),
+        Some(&Icrc3Value::Text(certificate_id.to_string()))
+    );
+    assert_eq!(
+        field(&tx, "version"),
+        Some(&Icrc3Value::Nat(Nat::from(version)))
+    );
+    assert_eq!(field(&tx, "to"), Some(&account_value(to)));
+
+    tx
+}
+
+#[test]
+fn test_icrc3_supported_block_types() {
+    let env = test_env::create_test_env();
+
+    let res = icrc3_supported_block_types(&env, Principal::anonymous());
+
+    assert_eq!(
+        res.iter()
+            .map(|block_type| block_type.block_type.as_str())
+            .collect::<Vec<_>>(),
+        vec![
+            "ssp_certificate_issue",
+            "ssp_certificate_update",
+            "ssp_certificate_revoke",
+            "ssp_certificate_transfer"
+        ]
+    );
+}
+
+#[test]
+fn test_icrc3_blocks() {
+    let env = test_env::create_test_env();
+
+    // there's no tip to certify without blocks
+    assert!(get_all_blocks(&env).is_empty());
+    assert!(icrc3_get_tip_certificate(&env, Principal::anonymous()).is_none());
+
+    let backend_principal = generate_random_identity().sender().unwrap();
+    set_backend_principal(&env, env.controller(), backend_principal).unwrap();
+    let (auth_provider_key_pair, jwks) = initialize_auth_provider();
+    initialize_canister(&env, jwks);
+    let user_principal = sign_up(
+        &env,
+        &auth_provider_key_pair,
+        TEST_USER_SUB,
+        TEST_USER_DB_ID,
+    );
+
+    let certificate_id = create_certificate(
+        &env,
+        backend_principal,
+        certificate_request(TEST_USER_DB_ID, None),
+    )
+    .unwrap()
+    .id;
+    update_certificate(
+        &env,
+        backend_principal,
+        UpdateCertificateRequest {
+            id: certificate_id.clone(),
+            content: CreateCertificateContentRequest {
+                name: "Updated certificate".to_string(),
+                ..certificate_request(TEST_USER_DB_ID, None).content
+            },
+        },
+    )
+    .unwrap();
+    let reason = "Issued by mistake".to_string();
+    revoke_certificate(
+        &env,
+        backend_principal,
+        RevokeCertificateRequest {
+            id: certificate_id.clone(),
+            reason: reason.clone(),
+        },
+    )
+    .unwrap();
+    let managed_certificate_id = create_certificate(
+        &env,
+        backend_principal,
+        certificate_request(TEST_USER_DB_ID, Some(MANAGED_USER_DB_ID)),
+    )
+    .unwrap()
+    .id;
+    let managed_user_principal = sign_up(
+        &env,
+        &auth_provider_key_pair,
+        MANAGED_USER_SUB,
+        MANAGED_USER_DB_ID,
+    );
+
+    let test = || {
+        let blocks = get_all_blocks(&env);
+        assert_eq!(blocks.len(), 5);
+        assert_blocks_are_valid(&env, &blocks);
+
+        assert_block(
+            &blocks[0],
+            "ssp_certificate_issue",
+            &certificate_id,
+            1,
+            user_principal,
+        );
+        assert_block(
+            &blocks[1],
+            "ssp_certificate_update",
+            &certificate_id,
+            2,
+            user_principal,
+        );
+        // revocations add a new version, leaving the previous ones untouched
+        let tx = assert_block(
+            &blocks[2],
+            "ssp_certificate_revoke",
+            &certificate_id,
+            3,
+            user_principal,
+        );
+        assert_eq!(
+            field(&tx, "reason"),
+            Some(&Icrc3Value::Text(reason.clone()))
+        );
+        assert_block(
+            &blocks[3],
+            "ssp_certificate_issue",
+            &managed_certificate_id,
+            1,
+            user_principal,
+        );
+        let tx = assert_block(
+            &blocks[4],
+            "ssp_certificate_transfer",
+            &managed_certificate_id,
+            2,
+            managed_user_principal,
+        );
+        assert_eq!(field(&tx, "from"), Some(&account_value(user_principal)));
+
+        // the blocks can be requested in ranges
+        let res = icrc3_get_blocks(
+            &env,
+            Principal::anonymous(),
+            vec![
+                GetBlocksRequest {
+                    start: Nat::from(1u64),
+                    length: Nat::from(1u64),
+                },
+                GetBlocksRequest {
+                    start: Nat::from(3u64),
+                    length: Nat::from(10u64),
+                },
+            ],
+        );
+        assert_eq!(res.log_length, Nat::from(5u64));
+        assert_eq!(
+            res.blocks,
+            vec![blocks[1].clone(), blocks[3].clone(), blocks[4].clone()]
+        );
+
+        blocks
+    };
+
+    let blocks = test();
+    upgrade_canister(&env);
+    assert_eq!(test(), blocks);
+}
@@ -0,0 +1,461 @@
+pub mod common;
+
+use candid::{Nat, Principal};
+use common::{
+    auth_provider::initialize_auth_provider,
+    canister::{
+        create_certificate, icrc10_supported_standards, icrc7_balance_of,
+        icrc7_collection_metadata, icrc7_name, icrc7_owner_of, icrc7_symbol, icrc7_token_metadata,
+        icrc7_tokens_of, icrc7_total_supply, icrc7_transfer, initialize_canister,
+        revoke_certificate, set_backend_principal, set_certificate_public,
+    },
+    fixtures::{self, account, sign_up},
+    identity::generate_random_identity,
+    test_env::{self, upgrade_canister, TestEnv},
+};
+use ic_agent::Identity;
+use pocket_ic::query_candid_as;
+use serde_bytes::ByteBuf;
+use ssp_backend_types::{
+    Account, CreateCertificateContentRequest, CreateCertificateRequest, Icrc3Value,
+    RevokeCertificateRequest, SetCertificatePublicRequest, TransferArg, TransferError,
+    ICRC7_COLLECTION_NAME, ICRC7_COLLECTION_SYMBOL, MAX_ICRC7_QUERY_BATCH_SIZE,
+};
+use uuid::Uuid;
+
+const TEST_USER_SUB: &str = "test_sub";
+const TEST_USER_DB_ID: &str = "96b51c08-9846-40f2-8f37-a1e4421e2ba8";
+const ANOTHER_USER_SUB: &str = "another_test_sub";
+const ANOTHER_USER_DB_ID: &str = "ccb31f93-1a16-4089-bc84-1822ae591da2";
+
+fn certificate_request(name: &str) -> CreateCertificateRequest {
+    let request = fixtures::certificate_request(TEST_USER_DB_ID, None);
+    CreateCertificateRequest {
+        content: CreateCertificateContentRequest {
+            name: name.to_string(),
+            external_id: Some("external-id".to_string()),
+            ..request.content
+        },
+        ..request
+    }
+}
+
+fn token_id(certificate_id: &str) -> Nat {
+    Nat::from(Uuid::parse_str(certificate_id).unwrap().as_u128())
+}
+
+fn metadata_value<'a>(metadata: &'a [(String, Icrc3Value)], key: &str) -> Option<&'a Icrc3Value> {
+    metadata.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+}
+
+struct TestSetup {
+    user_principal: Principal,
+    another_user_principal: Principal,
+    backend_principal: Principal,
+    /// The token ids of the user's certificates, in ascending order.
+    token_ids: Vec<Nat>,
+    certificate_ids: Vec<String>,
+}
+
+fn setup(env: &TestEnv) -> TestSetup {
+    let backend_principal = generate_random_identity().sender().unwrap();
+    set_backend_principal(env, env.controller(), backend_principal).unwrap();
+    let (auth_provider_key_pair, jwks) = initialize_auth_provider();
+    initialize_canister(env, jwks);
+    let user_principal = sign_up(env, &auth_provider_key_pair, TEST_USER_SUB, TEST_USER_DB_ID);
+    let another_user_principal = sign_up(
+        env,
+        &auth_provider_key_pair,
+        ANOTHER_USER_SUB,
+        ANOTHER_USER_DB_ID,
+    );
+
+    let mut certificate_ids: Vec<String> = (0..3)
+        .map(|i| {
+            create_certificate(
+                env,
+                backend_principal,
+                certificate_request(&format!("Test certificate {}", i)),
+            )
+            .unwrap()
+            .id
+        })
+        .collect();
+    certificate_ids.sort_by_key(|id| token_id(id));
+
+    TestSetup {
+        user_principal,
+        another_user_principal,
+        backend_principal,
+        token_ids: certificate_ids.iter().map(|id| token_id(id)).collect(),
+        certificate_ids,
+    }
+}
+
+#[test]
+fn test_icrc7_tokens_of() {
+    let env = test_env::create_test_env();
+    let TestSetup {
+        user_principal,
+        another_user_principal,
+        token_ids,
+        certificate_ids,
+        ..
+    } = setup(&env);
+
+    let test = || {
+        let res = icrc7_tokens_of(&env, user_principal, account(user_principal), None, None);
+        assert_eq!(res, token_ids);
+
+        // the tokens can be paged through
+        let res = icrc7_tokens_of(
+            &env,
+            user_principal,
+            account(user_principal),
+            None,
+            Some(Nat::from(2u64)),
+        );
+        assert_eq!(res, token_ids[..2]);
+        let res = icrc7_tokens_of(
+            &env,
+            user_principal,
+            account(user_principal),
+            Some(token_ids[1].clone()),
+            Some(Nat::from(2u64)),
+        );
+        assert_eq!(res, token_ids[2..]);
+
+        // the all-zero subaccount is the default account
+        let res = icrc7_tokens_of(
+            &env,
+            user_principal,
+            Account {
+                owner: user_principal,
+                subaccount: Some(ByteBuf::from(vec![0; 32])),
+            },
+            None,
+            None,
+        );
+        assert_eq!(res, token_ids);
+
+        // certificates are never held by other subaccounts
+        let res = icrc7_tokens_of(
+            &env,
+            user_principal,
+            Account {
+                owner: user_principal,
+                subaccount: Some(ByteBuf::from(vec![1; 32])),
+            },
+            None,
+            None,
+        );
+        assert!(res.is_empty());
+
+        let res = icrc7_tokens_of(
+            &env,
+            user_principal,
+            account(another_user_principal),
+            None,
+            None,
+        );
+        assert!(res.is_empty());
+    };
+
+    test();
+    upgrade_canister(&env);
+    test();
+
+    // the private certificates are only listed to who can read them
+    let res = icrc7_tokens_of(
+        &env,
+        Principal::anonymous(),
+        account(user_principal),
+        None,
+        None,
+    );
+    assert!(res.is_empty());
+    let res = icrc7_tokens_of(
+        &env,
+        another_user_principal,
+        account(user_principal),
+        None,
+        None,
+    );
+    assert!(res.is_empty());
+
+    set_certificate_public(
+        &env,
+        user_principal,
+        SetCertificatePublicRequest {
+            id: certificate_ids[1].clone(),
+            is_public: true,
+        },
+    )
+    .unwrap();
+
+    let res = icrc7_tokens_of(
+        &env,
+        Principal::anonymous(),
+        account(user_principal),
+        None,
+        Some(Nat::from(1u64)),
+    );
+    assert_eq!(res, token_ids[1..2]);
+    let res = icrc7_tokens_of(
+        &env,
+        Principal::anonymous(),
+        account(user_principal),
+        Some(token_ids[1].clone()),
+        None,
+    );
+    assert!(res.is_empty());
+}
+
+#[test]
+fn test_icrc7_owner_of() {
+    let env = test_env::create_test_env();
+    let TestSetup {
+        user_principal,
+        token_ids,
+        certificate_ids,
+        ..
+    } = setup(&env);
+
+    let res = icrc7_owner_of(
+        &env,
+        user_principal,
+        vec![token_ids[0].clone(), token_id(&Uuid::new_v4().to_string())],
+    );
+    assert_eq!(res, vec![Some(account(user_principal)), None]);
+
+    // the private certificates are reported as non-existing to who can't read them
+    let res = icrc7_owner_of(&env, Principal::anonymous(), vec![token_ids[0].clone()]);
+    assert_eq!(res, vec![None]);
+
+    set_certificate_public(
+        &env,
+        user_principal,
+        SetCertificatePublicRequest {
+            id: certificate_ids[0].clone(),
+            is_public: true,
+        },
+    )
+    .unwrap();
+
+    let res = icrc7_owner_of(&env, Principal::anonymous(), vec![token_ids[0].clone()]);
+    assert_eq!(res, vec![Some(account(user_principal))]);
+
+    let res = query_candid_as::<_, (Vec<Option<Account>>,)>(
+        env.pic(),
+        env.canister_id(),
+        Principal::anonymous(),
+        "icrc7_owner_of",
+        (vec![token_ids[0].clone(); MAX_ICRC7_QUERY_BATCH_SIZE + 1],),
+    );
+    assert!(res.is_err());
+}
+
+#[test]
+fn test_icrc7_token_metadata() {
+    let env = test_env::create_test_env();
+    let TestSetup {
+        user_principal,
+        another_user_principal,
+        backend_principal,
+        token_ids,
+        certificate_ids,
+    } = setup(&env);
+
+    let res = icrc7_token_metadata(&env, user_principal, vec![token_ids[0].clone()]);
+    let metadata = res[0].as_ref().unwrap();
+    assert!(matches!(
+        metadata_value(metadata, "icrc7:name"),
+        Some(Icrc3Value::Text(name)) if name.starts_with("Test certificate")
+    ));
+    assert_eq!(
+        metadata_value(metadata, "icrc7:description"),
+        Some(&Icrc3Value::Text("Swimming certificate".to_string()))
+    );
+    assert_eq!(
+        metadata_value(metadata, "icrc7:url"),
+        Some(&Icrc3Value::Text(format!(
+            "https://{}.icp0.io/certificates/{}",
+            env.canister_id(),
+            certificate_ids[0]
+        )))
+    );
+    assert_eq!(metadata_value(metadata, "icrc7:image"), None);
+    assert_eq!(
+        metadata_value(metadata, "ssp:certificate_id"),
+        Some(&Icrc3Value::Text(certificate_ids[0].clone()))
+    );
+    assert!(matches!(
+        metadata_value(metadata, "ssp:name"),
+        Some(Icrc3Value::Text(name)) if name.starts_with("Test certificate")
+    ));
+    assert_eq!(
+        metadata_value(metadata, "ssp:issued_at"),
+        Some(&Icrc3Value::Text("2023-12-31T23:00:00+00:00".to_string()))
+    );
+    assert_eq!(
+        metadata_value(metadata, "ssp:sport_category"),
+        Some(&Icrc3Value::Text("Swimming".to_string()))
+    );
+    assert_eq!(
+        metadata_value(metadata, "ssp:external_id"),
+        Some(&Icrc3Value::Text("external-id".to_string()))
+    );
+    assert_eq!(metadata_value(metadata, "ssp:notes"), None);
+    assert_eq!(metadata_value(metadata, "ssp:revoked_at"), None);
+
+    // the metadata of private certificates is only visible to who can read them
+    let res = icrc7_token_metadata(&env, another_user_principal, vec![token_ids[0].clone()]);
+    assert_eq!(res, vec![None]);
+
+    set_certificate_public(
+        &env,
+        user_principal,
+        SetCertificatePublicRequest {
+            id: certificate_ids[0].clone(),
+            is_public: true,
+        },
+    )
+    .unwrap();
+    revoke_certificate(
+        &env,
+        backend_principal,
+        RevokeCertificateRequest {
+            id: certificate_ids[0].clone(),
+            reason: "Issued by mistake".to_string(),
+        },
+    )
+    .unwrap();
+
+    let res = icrc7_token_metadata(
+        &env,
+        Principal::anonymous(),
+        vec![token_ids[0].clone(), token_ids[1].clone()],
+    );
+    assert_eq!(res.len(), 2);
+    assert!(metadata_value(res[0].as_ref().unwrap(), "ssp:revoked_at").is_some());
+    assert_eq!(res[1], None);
+}
+
+#[test]
+fn test_icrc7_transfer() {
+    let env = test_env::create_test_env();
+    let TestSetup {
+        user_principal,
+        another_user_principal,
+        token_ids,
+        ..
+    } = setup(&env);
+
+    let res = icrc7_transfer(
+        &env,
+        user_principal,
+        vec![TransferArg {
+            from_subaccount: None,
+            to: account(another_user_principal),
+            token_id: token_ids[0].clone(),
+            memo: None,
+            created_at_time: None,
+        }],
+    );
+
+    assert_eq!(
+        res,
+        vec![Some(Err(TransferError::GenericError {
+            error_code: Nat::from(0u64),
+            message: "Certificates are soulbound and cannot be transferred".to_string(),
+        }))]
+    );
+    let res = icrc7_owner_of(&env, user_principal, vec![token_ids[0].clone()]);
+    assert_eq!(res, vec![Some(account(user_principal))]);
+}
+
+#[test]
+fn test_icrc7_collection() {
+    let env = test_env::create_test_env();
+    let TestSetup {
+        user_principal,
+        another_user_principal,
+        certificate_ids,
+        ..
+    } = setup(&env);
+
+    let res = icrc10_supported_standards(&env, Principal::anonymous());
+    let names: Vec<_> = res.iter().map(|standard| standard.name.as_str()).collect();
+    assert_eq!(names, vec!["ICRC-3", "ICRC-7", "ICRC-10"]);
+
+    assert_eq!(
+        icrc7_name(&env, Principal::anonymous()),
+        ICRC7_COLLECTION_NAME
+    );
+    assert_eq!(
+        icrc7_symbol(&env, Principal::anonymous()),
+        ICRC7_COLLECTION_SYMBOL
+    );
+    assert_eq!(
+        icrc7_total_supply(&env, Principal::anonymous()),
+        Nat::from(3u64)
+    );
+
+    let metadata = icrc7_collection_metadata(&env, Principal::anonymous());
+    assert_eq!(
+        metadata_value(&metadata, "icrc7:name"),
+        Some(&Icrc3Value::Text(ICRC7_COLLECTION_NAME.to_string()))
+    );
+    assert_eq!(
+        metadata_value(&metadata, "icrc7:symbol"),
+        Some(&Icrc3Value::Text(ICRC7_COLLECTION_SYMBOL.to_string()))
+    );
+    assert_eq!(
+        metadata_value(&metadata, "icrc7:total_supply"),
+        Some(&Icrc3Value::Nat(Nat::from(3u64)))
+    );
+    assert_eq!(
+        metadata_value(&metadata, "icrc7:max_query_batch_size"),
+        Some(&Icrc3Value::Nat(Nat::from(MAX_ICRC7_QUERY_BATCH_SIZE)))
+    );
+
+    let accounts = vec![
+        account(user_principal),
+        account(another_user_principal),
+        Account {
+            owner: user_principal,
+            subaccount: Some(ByteBuf::from(vec![1; 32])),
+        },
+    ];
+    let res = icrc7_balance_of(&env, user_principal, accounts.clone());
+    assert_eq!(res, vec![Nat::from(3u64), Nat::from(0u64), Nat::from(0u64)]);
+
+    // only the certificates that the caller can read are counted
+    let res = icrc7_balance_of(&env, Principal::anonymous(), accounts.clone());
+    assert_eq!(res, vec![Nat::from(0u64), Nat::from(0u64), Nat::from(0u64)]);
+
+    set_certificate_public(
+        &env,
+        user_principal,
+        SetCertificatePublicRequest {
+            id: certificate_ids[2].clone(),
+            is_public: true,
+        },
+    )
+    .unwrap();
+
+    let res = icrc7_balance_of(&env, Principal::anonymous(), accounts);
+    assert_eq!(res, vec![Nat::from(1u64), Nat::from(0u64), Nat::from(0u64)]);
+
+    let res = query_candid_as::<_, (Vec<Nat>,)>(
+        env.pic(),
+        env.canister_id(),
+        Principal::anonymous(),
+        "icrc7_balance_of",
+        (vec![
+            account(user_principal);
+            MAX_ICRC7_QUERY_BATCH_SIZE + 1
+        ],),
+    );
+    assert!(res.is_err());
+}
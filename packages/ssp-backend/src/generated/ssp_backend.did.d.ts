@@ -2,10 +2,73 @@ import type { Principal } from '@dfinity/principal';
 import type { ActorMethod } from '@dfinity/agent';
 import type { IDL } from '@dfinity/candid';
 
+export interface Account {
+  'owner' : Principal,
+  'subaccount' : [] | [Uint8Array | number[]],
+}
+export interface Achievement {
+  'id' : string,
+  'issuer_id' : [] | [string],
+  'name' : string,
+  'description' : string,
+  'created_at' : string,
+  'criteria' : string,
+  'sport_category' : [] | [string],
+}
+export type ApiError = { 'conflict' : { 'message' : string } } |
+  { 'internal' : { 'message' : string } } |
+  { 'not_found' : { 'message' : string } } |
+  { 'unauthorized' : { 'message' : string } } |
+  { 'validation' : { 'field' : string, 'message' : string } };
+export interface AuditLogEntry {
+  'previous_hash' : Uint8Array | number[],
+  'operation' : AuditLogOperation,
+  'timestamp' : Timestamp,
+  'caller' : Principal,
+  'payload_hash' : Uint8Array | number[],
+}
+export interface AuditLogEntryWithIndex {
+  'entry_cbor_hex' : string,
+  'index' : bigint,
+}
+export type AuditLogOperation = { 'create_issuer' : { 'id' : string } } |
+  { 'create_upload' : { 'upload_id' : string } } |
+  { 'remove_issuer_member' : { 'issuer_id' : string } } |
+  { 'upload_chunk' : { 'upload_id' : string } } |
+  { 'update_certificate' : { 'id' : string } } |
+  { 'sync_jwks' : null } |
+  { 'create_certificate_share_token' : { 'id' : string } } |
+  { 'remove_oidc_provider' : { 'issuer' : string } } |
+  { 'revoke_all_sessions' : null } |
+  { 'set_jwks' : { 'issuer' : string } } |
+  {
+    'claim_managed_user' : {
+      'user_principal' : Principal,
+      'managed_user_id' : string,
+    }
+  } |
+  { 'set_oidc_provider' : { 'issuer' : string } } |
+  { 'set_issuer_verified' : { 'id' : string } } |
+  { 'create_user' : { 'user_principal' : Principal } } |
+  { 'commit_upload' : { 'file_hash' : string, 'upload_id' : string } } |
+  { 'revoke_certificate' : { 'id' : string } } |
+  { 'remove_managed_user_guardian' : { 'managed_user_id' : string } } |
+  { 'create_certificate' : { 'id' : string } } |
+  { 'set_delegation_config' : null } |
+  { 'revoke_session' : null } |
+  { 'add_managed_user_guardian' : { 'managed_user_id' : string } } |
+  { 'set_certificate_public' : { 'id' : string } } |
+  { 'add_issuer_member' : { 'issuer_id' : string } } |
+  { 'set_backend_principal' : null } |
+  { 'create_achievement' : { 'id' : string } } |
+  { 'revoke_certificate_share_token' : { 'id' : string } };
 export interface Auth0JWK {
   'e' : string,
   'n' : string,
+  'x' : [] | [string],
+  'y' : [] | [string],
   'alg' : string,
+  'crv' : [] | [string],
   'kid' : string,
   'kty' : string,
   'use' : string,
@@ -15,13 +78,19 @@ export interface Auth0JWK {
 export interface Auth0JWKS { 'keys' : Array<Auth0JWK> }
 export interface Certificate {
   'user_principal' : Principal,
+  'updated_at' : [] | [string],
   'content' : CertificateContent,
   'created_at' : string,
+  'issuer' : [] | [CertificateIssuer],
+  'achievement_id' : [] | [string],
+  'issuer_principal' : [] | [Principal],
   'managed_user_id' : [] | [string],
+  'revocation' : [] | [CertificateRevocation],
 }
 export interface CertificateContent {
   'issued_at' : string,
   'name' : string,
+  'file_hash' : [] | [string],
   'file_uri' : [] | [string],
   'issuer_club_name' : [] | [string],
   'issuer_full_name' : [] | [string],
@@ -29,15 +98,62 @@ export interface CertificateContent {
   'external_id' : [] | [string],
   'sport_category' : string,
 }
-export interface CertificatePreviewWithId { 'id' : string, 'name' : string }
+export interface CertificateIssuer {
+  'id' : string,
+  'verified' : boolean,
+  'name' : string,
+}
+export interface CertificatePreviewWithId {
+  'id' : string,
+  'issued_at' : string,
+  'name' : string,
+  'issuer_club_name' : [] | [string],
+  'sport_category' : string,
+  'is_revoked' : boolean,
+}
+export interface CertificateRevocation {
+  'revoked_at' : string,
+  'reason' : string,
+}
 export interface CertificateWithId {
   'id' : string,
+  'version' : number,
   'certificate_cbor_hex' : string,
 }
-export interface Config { 'backend_principal' : [] | [Principal] }
+export interface CertificatesFilters {
+  'issued_at_from' : [] | [Timestamp],
+  'issuer_club_name' : [] | [string],
+  'issued_at_to' : [] | [Timestamp],
+  'sport_category' : [] | [string],
+}
+export interface CertificatesSort {
+  'field' : CertificatesSortField,
+  'order' : SortOrder,
+}
+export type CertificatesSortField = { 'issued_at' : null } |
+  { 'created_at' : null };
+export interface CommitUploadResponse { 'file_hash' : string }
+export type CommitUploadResult = { 'ok' : CommitUploadResponse } |
+  { 'err' : ApiError };
+export interface Config {
+  'backend_principal' : [] | [Principal],
+  'delegation' : DelegationConfig,
+  'legacy_oidc_issuer' : [] | [string],
+}
+export interface CreateAchievementRequest {
+  'issuer_id' : [] | [string],
+  'name' : string,
+  'description' : string,
+  'criteria' : string,
+  'sport_category' : [] | [string],
+}
+export interface CreateAchievementResponse { 'id' : string }
+export type CreateAchievementResult = { 'ok' : CreateAchievementResponse } |
+  { 'err' : ApiError };
 export interface CreateCertificateContentRequest {
   'issued_at' : Timestamp,
   'name' : string,
+  'file_hash' : [] | [string],
   'file_uri' : [] | [string],
   'issuer_club_name' : [] | [string],
   'issuer_full_name' : [] | [string],
@@ -47,64 +163,393 @@ export interface CreateCertificateContentRequest {
 }
 export interface CreateCertificateRequest {
   'content' : CreateCertificateContentRequest,
+  'issuer_id' : [] | [string],
   'managed_user_db_id' : [] | [string],
   'user_db_id' : string,
+  'achievement_id' : [] | [string],
 }
 export interface CreateCertificateResponse { 'id' : string }
+export type CreateCertificateResult = { 'ok' : CreateCertificateResponse } |
+  { 'err' : ApiError };
+export interface CreateCertificateShareTokenResponse { 'share_token' : string }
+export type CreateCertificateShareTokenResult = {
+    'ok' : CreateCertificateShareTokenResponse
+  } |
+  { 'err' : ApiError };
+export interface CreateIssuerRequest { 'name' : string }
+export interface CreateIssuerResponse { 'id' : string }
+export type CreateIssuerResult = { 'ok' : CreateIssuerResponse } |
+  { 'err' : ApiError };
+export interface CreateUploadRequest {
+  'size' : bigint,
+  'content_type' : string,
+}
+export interface CreateUploadResponse { 'upload_id' : string }
+export type CreateUploadResult = { 'ok' : CreateUploadResponse } |
+  { 'err' : ApiError };
 export interface Delegation {
   'pubkey' : PublicKey,
   'targets' : [] | [Array<Principal>],
   'expiration' : Timestamp,
 }
+export interface DelegationConfig {
+  'max_session_duration_ns' : [] | [bigint],
+  'role_session_durations' : Array<RoleSessionDuration>,
+  'targets' : [] | [Array<Principal>],
+}
+export interface DelegationStats {
+  'revoked_sessions_count' : bigint,
+  'revoked_delegations_count' : bigint,
+  'signatures_count' : bigint,
+}
+export type EmptyResult = { 'ok' : null } |
+  { 'err' : ApiError };
+export type GetAchievementResult = { 'ok' : Achievement } |
+  { 'err' : ApiError };
+export interface GetAuditLogRequest {
+  'limit' : [] | [number],
+  'start' : [] | [bigint],
+}
+export interface GetAuditLogResponse {
+  'ic_certificate' : Uint8Array | number[],
+  'entries' : Array<AuditLogEntryWithIndex>,
+  'ic_certificate_witness' : Uint8Array | number[],
+  'length' : bigint,
+  'tip_hash' : string,
+}
+export type GetAuditLogResult = { 'ok' : GetAuditLogResponse } |
+  { 'err' : ApiError };
+export type GetBlocksArgs = Array<{ 'start' : bigint, 'length' : bigint }>;
+export interface GetBlocksResult {
+  'log_length' : bigint,
+  'blocks' : Array<{ 'id' : bigint, 'block' : ICRC3Value }>,
+  'archived_blocks' : Array<
+    { 'args' : GetBlocksArgs, 'callback' : [Principal, string] }
+  >,
+}
+export interface GetCertificateAsVcResponse {
+  'signature' : Uint8Array | number[],
+  'credential' : string,
+  'public_key' : Uint8Array | number[],
+}
+export type GetCertificateAsVcResult = { 'ok' : GetCertificateAsVcResponse } |
+  { 'err' : ApiError };
 export interface GetCertificateResponse {
   'certificate' : CertificateWithId,
   'ic_certificate' : Uint8Array | number[],
   'ic_certificate_witness' : Uint8Array | number[],
+  'latest_version' : number,
 }
+export type GetCertificateResult = { 'ok' : GetCertificateResponse } |
+  { 'err' : ApiError };
+export type GetConfigResult = { 'ok' : Config } |
+  { 'err' : ApiError };
 export type GetDelegationResponse = { 'no_such_delegation' : null } |
   { 'signed_delegation' : SignedDelegation };
+export type GetDelegationResult = { 'ok' : GetDelegationResponse } |
+  { 'err' : ApiError };
+export type GetDelegationStatsResult = { 'ok' : DelegationStats } |
+  { 'err' : ApiError };
+export interface GetFileChunkRequest {
+  'file_hash' : string,
+  'index' : number,
+  'share_token' : [] | [string],
+}
+export interface GetFileChunkResponse { 'content' : Uint8Array | number[] }
+export type GetFileChunkResult = { 'ok' : GetFileChunkResponse } |
+  { 'err' : ApiError };
+export interface GetFileRequest {
+  'file_hash' : string,
+  'share_token' : [] | [string],
+}
+export interface GetFileResponse {
+  'chunks_count' : number,
+  'size' : bigint,
+  'content_type' : string,
+}
+export type GetFileResult = { 'ok' : GetFileResponse } |
+  { 'err' : ApiError };
+export type GetIssuerResult = { 'ok' : Issuer } |
+  { 'err' : ApiError };
+export type GetJwksResult = { 'ok' : [] | [Auth0JWKS] } |
+  { 'err' : ApiError };
+export interface GetManagedUserCertificatesRequest {
+  'filters' : [] | [CertificatesFilters],
+  'cursor' : [] | [string],
+  'sort' : [] | [CertificatesSort],
+  'managed_user_db_id' : string,
+  'limit' : [] | [number],
+}
+export interface GetMyCertificatesRequest {
+  'filters' : [] | [CertificatesFilters],
+  'cursor' : [] | [string],
+  'list' : MyCertificatesList,
+  'sort' : [] | [CertificatesSort],
+  'limit' : [] | [number],
+}
+export type GetMyManagedUsersResult = { 'ok' : Array<ManagedUser> } |
+  { 'err' : ApiError };
+export type GetMyUserResult = { 'ok' : User } |
+  { 'err' : ApiError };
+export type GetOidcProvidersResult = { 'ok' : Array<OidcConfig> } |
+  { 'err' : ApiError };
 export interface GetUserCertificatesRequest {
+  'filters' : [] | [CertificatesFilters],
   'user_principal' : [] | [Principal],
+  'cursor' : [] | [string],
+  'sort' : [] | [CertificatesSort],
+  'limit' : [] | [number],
   'user_db_id' : [] | [string],
 }
 export interface GetUserCertificatesResponse {
+  'next_cursor' : [] | [string],
   'certificates' : Array<CertificatePreviewWithId>,
 }
+export type GetUserCertificatesResult = { 'ok' : GetUserCertificatesResponse } |
+  { 'err' : ApiError };
+export type HeaderField = [string, string];
+export interface HttpRequest {
+  'url' : string,
+  'method' : string,
+  'body' : Uint8Array | number[],
+  'headers' : Array<HeaderField>,
+  'certificate_version' : [] | [number],
+}
+export interface HttpResponse {
+  'body' : Uint8Array | number[],
+  'headers' : Array<HeaderField>,
+  'upgrade' : [] | [boolean],
+  'status_code' : number,
+}
+export interface ICRC3DataCertificate {
+  'certificate' : Uint8Array | number[],
+  'hash_tree' : Uint8Array | number[],
+}
+export type ICRC3Value = { 'Int' : bigint } |
+  { 'Map' : Array<[string, ICRC3Value]> } |
+  { 'Nat' : bigint } |
+  { 'Blob' : Uint8Array | number[] } |
+  { 'Text' : string } |
+  { 'Array' : Array<ICRC3Value> };
+export interface InitArgs {
+  'oidc_providers' : [] | [Array<OidcConfig>],
+  'legacy_oidc_issuer' : [] | [string],
+}
+export interface Issuer {
+  'id' : string,
+  'name' : string,
+  'created_at' : string,
+  'coaches' : Array<Principal>,
+  'verified_at' : [] | [string],
+  'admins' : Array<Principal>,
+}
+export interface IssuerMemberRequest {
+  'issuer_id' : string,
+  'role' : IssuerMemberRole,
+  'user_db_id' : string,
+}
+export type IssuerMemberRole = { 'admin' : null } |
+  { 'coach' : null };
+export interface ManagedUser {
+  'claimed_at' : [] | [string],
+  'claimed_by' : [] | [Principal],
+  'guardians' : Array<Principal>,
+  'created_at' : string,
+  'db_id' : string,
+}
+export interface ManagedUserGuardianRequest {
+  'managed_user_db_id' : string,
+  'guardian_db_id' : string,
+}
+export type MyCertificatesList = { 'issued' : null } |
+  { 'received' : null };
+export interface OidcConfig {
+  'jwks_url' : string,
+  'claims_namespace' : [] | [string],
+  'issuer' : string,
+  'audiences' : Array<string>,
+}
 export interface PrepareDelegationResponse {
   'user_key' : UserKey,
   'expiration' : Timestamp,
 }
+export type PrepareDelegationResult = { 'ok' : PrepareDelegationResponse } |
+  { 'err' : ApiError };
 export type PublicKey = Uint8Array | number[];
+export interface RevokeCertificateRequest { 'id' : string, 'reason' : string }
+export interface RoleSessionDuration {
+  'role' : string,
+  'max_session_duration_ns' : bigint,
+}
+export type SessionKey = PublicKey;
+export interface SetCertificatePublicRequest {
+  'id' : string,
+  'is_public' : boolean,
+}
+export interface SetIssuerVerifiedRequest {
+  'id' : string,
+  'verified' : boolean,
+}
 export type Signature = Uint8Array | number[];
 export interface SignedDelegation {
   'signature' : Signature,
   'delegation' : Delegation,
 }
+export type SortOrder = { 'asc' : null } |
+  { 'desc' : null };
+export interface SupportedBlockType { 'url' : string, 'block_type' : string }
+export interface SupportedStandard { 'url' : string, 'name' : string }
 export type Timestamp = bigint;
+export interface TransferArg {
+  'to' : Account,
+  'token_id' : bigint,
+  'memo' : [] | [Uint8Array | number[]],
+  'from_subaccount' : [] | [Uint8Array | number[]],
+  'created_at_time' : [] | [bigint],
+}
+export type TransferError = {
+    'GenericError' : { 'message' : string, 'error_code' : bigint }
+  } |
+  { 'Duplicate' : { 'duplicate_of' : bigint } } |
+  { 'NonExistingTokenId' : null } |
+  { 'Unauthorized' : null } |
+  { 'CreatedInFuture' : { 'ledger_time' : bigint } } |
+  { 'InvalidRecipient' : null } |
+  { 'GenericBatchError' : { 'message' : string, 'error_code' : bigint } } |
+  { 'TooOld' : null };
+export type TransferResult = { 'Ok' : bigint } |
+  { 'Err' : TransferError };
+export interface UpdateCertificateRequest {
+  'id' : string,
+  'content' : CreateCertificateContentRequest,
+}
+export interface UpdateCertificateResponse { 'version' : number }
+export type UpdateCertificateResult = { 'ok' : UpdateCertificateResponse } |
+  { 'err' : ApiError };
+export interface UploadChunkRequest {
+  'content' : Uint8Array | number[],
+  'upload_id' : string,
+  'index' : number,
+}
 export interface User {
   'sub' : string,
   'created_at' : string,
   'db_id' : string,
+  'roles' : Array<UserRole>,
 }
 export type UserKey = PublicKey;
+export type UserRole = { 'admin' : null } |
+  { 'coach' : null } |
+  { 'athlete' : null } |
+  { 'club_admin' : null };
+export interface VerifyCertificateRequest {
+  'id' : string,
+  'version' : [] | [number],
+  'share_token' : [] | [string],
+}
 export interface _SERVICE {
+  'add_issuer_member' : ActorMethod<[IssuerMemberRequest], EmptyResult>,
+  'add_managed_user_guardian' : ActorMethod<
+    [ManagedUserGuardianRequest],
+    EmptyResult
+  >,
+  'commit_upload' : ActorMethod<[string], CommitUploadResult>,
+  'create_achievement' : ActorMethod<
+    [CreateAchievementRequest],
+    CreateAchievementResult
+  >,
   'create_certificate' : ActorMethod<
     [CreateCertificateRequest],
-    CreateCertificateResponse
+    CreateCertificateResult
+  >,
+  'create_certificate_share_token' : ActorMethod<
+    [string],
+    CreateCertificateShareTokenResult
+  >,
+  'create_issuer' : ActorMethod<[CreateIssuerRequest], CreateIssuerResult>,
+  'create_upload' : ActorMethod<[CreateUploadRequest], CreateUploadResult>,
+  'get_achievement' : ActorMethod<[string], GetAchievementResult>,
+  'get_audit_log' : ActorMethod<[GetAuditLogRequest], GetAuditLogResult>,
+  'get_certificate' : ActorMethod<
+    [string, [] | [number]],
+    GetCertificateResult
   >,
-  'get_certificate' : ActorMethod<[string], GetCertificateResponse>,
-  'get_config' : ActorMethod<[], Config>,
-  'get_delegation' : ActorMethod<[string, Timestamp], GetDelegationResponse>,
-  'get_jwks' : ActorMethod<[], [] | [Auth0JWKS]>,
-  'get_my_user' : ActorMethod<[], User>,
+  'get_certificate_as_vc' : ActorMethod<[string], GetCertificateAsVcResult>,
+  'get_config' : ActorMethod<[], GetConfigResult>,
+  'get_delegation' : ActorMethod<[string, Timestamp], GetDelegationResult>,
+  'get_delegation_stats' : ActorMethod<[], GetDelegationStatsResult>,
+  'get_file' : ActorMethod<[GetFileRequest], GetFileResult>,
+  'get_file_chunk' : ActorMethod<[GetFileChunkRequest], GetFileChunkResult>,
+  'get_issuer' : ActorMethod<[string], GetIssuerResult>,
+  'get_jwks' : ActorMethod<[string], GetJwksResult>,
+  'get_managed_user_certificates' : ActorMethod<
+    [GetManagedUserCertificatesRequest],
+    GetUserCertificatesResult
+  >,
+  'get_my_certificates' : ActorMethod<
+    [GetMyCertificatesRequest],
+    GetUserCertificatesResult
+  >,
+  'get_my_managed_users' : ActorMethod<[], GetMyManagedUsersResult>,
+  'get_my_user' : ActorMethod<[], GetMyUserResult>,
+  'get_oidc_providers' : ActorMethod<[], GetOidcProvidersResult>,
   'get_user_certificates' : ActorMethod<
     [GetUserCertificatesRequest],
-    GetUserCertificatesResponse
+    GetUserCertificatesResult
+  >,
+  'http_request' : ActorMethod<[HttpRequest], HttpResponse>,
+  'icrc10_supported_standards' : ActorMethod<[], Array<SupportedStandard>>,
+  'icrc3_get_blocks' : ActorMethod<[GetBlocksArgs], GetBlocksResult>,
+  'icrc3_get_tip_certificate' : ActorMethod<[], [] | [ICRC3DataCertificate]>,
+  'icrc3_supported_block_types' : ActorMethod<[], Array<SupportedBlockType>>,
+  'icrc7_balance_of' : ActorMethod<[Array<Account>], Array<bigint>>,
+  'icrc7_collection_metadata' : ActorMethod<[], Array<[string, ICRC3Value]>>,
+  'icrc7_name' : ActorMethod<[], string>,
+  'icrc7_owner_of' : ActorMethod<[Array<bigint>], Array<[] | [Account]>>,
+  'icrc7_symbol' : ActorMethod<[], string>,
+  'icrc7_token_metadata' : ActorMethod<
+    [Array<bigint>],
+    Array<[] | [Array<[string, ICRC3Value]>]>
+  >,
+  'icrc7_tokens_of' : ActorMethod<
+    [Account, [] | [bigint], [] | [bigint]],
+    Array<bigint>
+  >,
+  'icrc7_total_supply' : ActorMethod<[], bigint>,
+  'icrc7_transfer' : ActorMethod<
+    [Array<TransferArg>],
+    Array<[] | [TransferResult]>
+  >,
+  'prepare_delegation' : ActorMethod<[string], PrepareDelegationResult>,
+  'remove_issuer_member' : ActorMethod<[IssuerMemberRequest], EmptyResult>,
+  'remove_managed_user_guardian' : ActorMethod<
+    [ManagedUserGuardianRequest],
+    EmptyResult
+  >,
+  'remove_oidc_provider' : ActorMethod<[string], EmptyResult>,
+  'revoke_all_sessions' : ActorMethod<[], EmptyResult>,
+  'revoke_certificate' : ActorMethod<[RevokeCertificateRequest], EmptyResult>,
+  'revoke_certificate_share_token' : ActorMethod<[string], EmptyResult>,
+  'revoke_session' : ActorMethod<[SessionKey], EmptyResult>,
+  'set_backend_principal' : ActorMethod<[Principal], EmptyResult>,
+  'set_certificate_public' : ActorMethod<
+    [SetCertificatePublicRequest],
+    EmptyResult
+  >,
+  'set_delegation_config' : ActorMethod<[DelegationConfig], EmptyResult>,
+  'set_issuer_verified' : ActorMethod<[SetIssuerVerifiedRequest], EmptyResult>,
+  'set_jwks' : ActorMethod<[string, Auth0JWKS], EmptyResult>,
+  'set_oidc_provider' : ActorMethod<[OidcConfig], EmptyResult>,
+  'sync_jwks' : ActorMethod<[], EmptyResult>,
+  'update_certificate' : ActorMethod<
+    [UpdateCertificateRequest],
+    UpdateCertificateResult
+  >,
+  'upload_chunk' : ActorMethod<[UploadChunkRequest], EmptyResult>,
+  'verify_certificate' : ActorMethod<
+    [VerifyCertificateRequest],
+    GetCertificateResult
   >,
-  'prepare_delegation' : ActorMethod<[string], PrepareDelegationResponse>,
-  'set_backend_principal' : ActorMethod<[Principal], undefined>,
-  'set_jwks' : ActorMethod<[Auth0JWKS], undefined>,
-  'sync_jwks' : ActorMethod<[], undefined>,
 }
 export declare const idlFactory: IDL.InterfaceFactory;
 export declare const init: (args: { IDL: typeof IDL }) => IDL.Type[];
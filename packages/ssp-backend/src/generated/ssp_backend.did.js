@@ -1,8 +1,59 @@
 export const idlFactory = ({ IDL }) => {
+  const GetBlocksResult = IDL.Rec();
+  const ICRC3Value = IDL.Rec();
+  const OidcConfig = IDL.Record({
+    'jwks_url' : IDL.Text,
+    'claims_namespace' : IDL.Opt(IDL.Text),
+    'issuer' : IDL.Text,
+    'audiences' : IDL.Vec(IDL.Text),
+  });
+  const InitArgs = IDL.Record({
+    'oidc_providers' : IDL.Opt(IDL.Vec(OidcConfig)),
+    'legacy_oidc_issuer' : IDL.Opt(IDL.Text),
+  });
+  const IssuerMemberRole = IDL.Variant({
+    'admin' : IDL.Null,
+    'coach' : IDL.Null,
+  });
+  const IssuerMemberRequest = IDL.Record({
+    'issuer_id' : IDL.Text,
+    'role' : IssuerMemberRole,
+    'user_db_id' : IDL.Text,
+  });
+  const ApiError = IDL.Variant({
+    'conflict' : IDL.Record({ 'message' : IDL.Text }),
+    'internal' : IDL.Record({ 'message' : IDL.Text }),
+    'not_found' : IDL.Record({ 'message' : IDL.Text }),
+    'unauthorized' : IDL.Record({ 'message' : IDL.Text }),
+    'validation' : IDL.Record({ 'field' : IDL.Text, 'message' : IDL.Text }),
+  });
+  const EmptyResult = IDL.Variant({ 'ok' : IDL.Null, 'err' : ApiError });
+  const ManagedUserGuardianRequest = IDL.Record({
+    'managed_user_db_id' : IDL.Text,
+    'guardian_db_id' : IDL.Text,
+  });
+  const CommitUploadResponse = IDL.Record({ 'file_hash' : IDL.Text });
+  const CommitUploadResult = IDL.Variant({
+    'ok' : CommitUploadResponse,
+    'err' : ApiError,
+  });
+  const CreateAchievementRequest = IDL.Record({
+    'issuer_id' : IDL.Opt(IDL.Text),
+    'name' : IDL.Text,
+    'description' : IDL.Text,
+    'criteria' : IDL.Text,
+    'sport_category' : IDL.Opt(IDL.Text),
+  });
+  const CreateAchievementResponse = IDL.Record({ 'id' : IDL.Text });
+  const CreateAchievementResult = IDL.Variant({
+    'ok' : CreateAchievementResponse,
+    'err' : ApiError,
+  });
   const Timestamp = IDL.Nat64;
   const CreateCertificateContentRequest = IDL.Record({
     'issued_at' : Timestamp,
     'name' : IDL.Text,
+    'file_hash' : IDL.Opt(IDL.Text),
     'file_uri' : IDL.Opt(IDL.Text),
     'issuer_club_name' : IDL.Opt(IDL.Text),
     'issuer_full_name' : IDL.Opt(IDL.Text),
@@ -12,20 +63,109 @@ export const idlFactory = ({ IDL }) => {
   });
   const CreateCertificateRequest = IDL.Record({
     'content' : CreateCertificateContentRequest,
+    'issuer_id' : IDL.Opt(IDL.Text),
     'managed_user_db_id' : IDL.Opt(IDL.Text),
     'user_db_id' : IDL.Text,
+    'achievement_id' : IDL.Opt(IDL.Text),
   });
   const CreateCertificateResponse = IDL.Record({ 'id' : IDL.Text });
+  const CreateCertificateResult = IDL.Variant({
+    'ok' : CreateCertificateResponse,
+    'err' : ApiError,
+  });
+  const CreateCertificateShareTokenResponse = IDL.Record({
+    'share_token' : IDL.Text,
+  });
+  const CreateCertificateShareTokenResult = IDL.Variant({
+    'ok' : CreateCertificateShareTokenResponse,
+    'err' : ApiError,
+  });
+  const CreateIssuerRequest = IDL.Record({ 'name' : IDL.Text });
+  const CreateIssuerResponse = IDL.Record({ 'id' : IDL.Text });
+  const CreateIssuerResult = IDL.Variant({
+    'ok' : CreateIssuerResponse,
+    'err' : ApiError,
+  });
+  const CreateUploadRequest = IDL.Record({
+    'size' : IDL.Nat64,
+    'content_type' : IDL.Text,
+  });
+  const CreateUploadResponse = IDL.Record({ 'upload_id' : IDL.Text });
+  const CreateUploadResult = IDL.Variant({
+    'ok' : CreateUploadResponse,
+    'err' : ApiError,
+  });
+  const Achievement = IDL.Record({
+    'id' : IDL.Text,
+    'issuer_id' : IDL.Opt(IDL.Text),
+    'name' : IDL.Text,
+    'description' : IDL.Text,
+    'created_at' : IDL.Text,
+    'criteria' : IDL.Text,
+    'sport_category' : IDL.Opt(IDL.Text),
+  });
+  const GetAchievementResult = IDL.Variant({
+    'ok' : Achievement,
+    'err' : ApiError,
+  });
+  const GetAuditLogRequest = IDL.Record({
+    'limit' : IDL.Opt(IDL.Nat32),
+    'start' : IDL.Opt(IDL.Nat64),
+  });
+  const AuditLogEntryWithIndex = IDL.Record({
+    'entry_cbor_hex' : IDL.Text,
+    'index' : IDL.Nat64,
+  });
+  const GetAuditLogResponse = IDL.Record({
+    'ic_certificate' : IDL.Vec(IDL.Nat8),
+    'entries' : IDL.Vec(AuditLogEntryWithIndex),
+    'ic_certificate_witness' : IDL.Vec(IDL.Nat8),
+    'length' : IDL.Nat64,
+    'tip_hash' : IDL.Text,
+  });
+  const GetAuditLogResult = IDL.Variant({
+    'ok' : GetAuditLogResponse,
+    'err' : ApiError,
+  });
   const CertificateWithId = IDL.Record({
     'id' : IDL.Text,
+    'version' : IDL.Nat32,
     'certificate_cbor_hex' : IDL.Text,
   });
   const GetCertificateResponse = IDL.Record({
     'certificate' : CertificateWithId,
     'ic_certificate' : IDL.Vec(IDL.Nat8),
     'ic_certificate_witness' : IDL.Vec(IDL.Nat8),
+    'latest_version' : IDL.Nat32,
+  });
+  const GetCertificateResult = IDL.Variant({
+    'ok' : GetCertificateResponse,
+    'err' : ApiError,
+  });
+  const GetCertificateAsVcResponse = IDL.Record({
+    'signature' : IDL.Vec(IDL.Nat8),
+    'credential' : IDL.Text,
+    'public_key' : IDL.Vec(IDL.Nat8),
   });
-  const Config = IDL.Record({ 'backend_principal' : IDL.Opt(IDL.Principal) });
+  const GetCertificateAsVcResult = IDL.Variant({
+    'ok' : GetCertificateAsVcResponse,
+    'err' : ApiError,
+  });
+  const RoleSessionDuration = IDL.Record({
+    'role' : IDL.Text,
+    'max_session_duration_ns' : IDL.Nat64,
+  });
+  const DelegationConfig = IDL.Record({
+    'max_session_duration_ns' : IDL.Opt(IDL.Nat64),
+    'role_session_durations' : IDL.Vec(RoleSessionDuration),
+    'targets' : IDL.Opt(IDL.Vec(IDL.Principal)),
+  });
+  const Config = IDL.Record({
+    'backend_principal' : IDL.Opt(IDL.Principal),
+    'delegation' : DelegationConfig,
+    'legacy_oidc_issuer' : IDL.Opt(IDL.Text),
+  });
+  const GetConfigResult = IDL.Variant({ 'ok' : Config, 'err' : ApiError });
   const Signature = IDL.Vec(IDL.Nat8);
   const PublicKey = IDL.Vec(IDL.Nat8);
   const Delegation = IDL.Record({
@@ -41,10 +181,58 @@ export const idlFactory = ({ IDL }) => {
     'no_such_delegation' : IDL.Null,
     'signed_delegation' : SignedDelegation,
   });
+  const GetDelegationResult = IDL.Variant({
+    'ok' : GetDelegationResponse,
+    'err' : ApiError,
+  });
+  const DelegationStats = IDL.Record({
+    'revoked_sessions_count' : IDL.Nat64,
+    'revoked_delegations_count' : IDL.Nat64,
+    'signatures_count' : IDL.Nat64,
+  });
+  const GetDelegationStatsResult = IDL.Variant({
+    'ok' : DelegationStats,
+    'err' : ApiError,
+  });
+  const GetFileRequest = IDL.Record({
+    'file_hash' : IDL.Text,
+    'share_token' : IDL.Opt(IDL.Text),
+  });
+  const GetFileResponse = IDL.Record({
+    'chunks_count' : IDL.Nat32,
+    'size' : IDL.Nat64,
+    'content_type' : IDL.Text,
+  });
+  const GetFileResult = IDL.Variant({
+    'ok' : GetFileResponse,
+    'err' : ApiError,
+  });
+  const GetFileChunkRequest = IDL.Record({
+    'file_hash' : IDL.Text,
+    'index' : IDL.Nat32,
+    'share_token' : IDL.Opt(IDL.Text),
+  });
+  const GetFileChunkResponse = IDL.Record({ 'content' : IDL.Vec(IDL.Nat8) });
+  const GetFileChunkResult = IDL.Variant({
+    'ok' : GetFileChunkResponse,
+    'err' : ApiError,
+  });
+  const Issuer = IDL.Record({
+    'id' : IDL.Text,
+    'name' : IDL.Text,
+    'created_at' : IDL.Text,
+    'coaches' : IDL.Vec(IDL.Principal),
+    'verified_at' : IDL.Opt(IDL.Text),
+    'admins' : IDL.Vec(IDL.Principal),
+  });
+  const GetIssuerResult = IDL.Variant({ 'ok' : Issuer, 'err' : ApiError });
   const Auth0JWK = IDL.Record({
     'e' : IDL.Text,
     'n' : IDL.Text,
+    'x' : IDL.Opt(IDL.Text),
+    'y' : IDL.Opt(IDL.Text),
     'alg' : IDL.Text,
+    'crv' : IDL.Opt(IDL.Text),
     'kid' : IDL.Text,
     'kty' : IDL.Text,
     'use' : IDL.Text,
@@ -52,59 +240,398 @@ export const idlFactory = ({ IDL }) => {
     'x5t' : IDL.Text,
   });
   const Auth0JWKS = IDL.Record({ 'keys' : IDL.Vec(Auth0JWK) });
+  const GetJwksResult = IDL.Variant({
+    'ok' : IDL.Opt(Auth0JWKS),
+    'err' : ApiError,
+  });
+  const CertificatesFilters = IDL.Record({
+    'issued_at_from' : IDL.Opt(Timestamp),
+    'issuer_club_name' : IDL.Opt(IDL.Text),
+    'issued_at_to' : IDL.Opt(Timestamp),
+    'sport_category' : IDL.Opt(IDL.Text),
+  });
+  const CertificatesSortField = IDL.Variant({
+    'issued_at' : IDL.Null,
+    'created_at' : IDL.Null,
+  });
+  const SortOrder = IDL.Variant({ 'asc' : IDL.Null, 'desc' : IDL.Null });
+  const CertificatesSort = IDL.Record({
+    'field' : CertificatesSortField,
+    'order' : SortOrder,
+  });
+  const GetManagedUserCertificatesRequest = IDL.Record({
+    'filters' : IDL.Opt(CertificatesFilters),
+    'cursor' : IDL.Opt(IDL.Text),
+    'sort' : IDL.Opt(CertificatesSort),
+    'managed_user_db_id' : IDL.Text,
+    'limit' : IDL.Opt(IDL.Nat32),
+  });
+  const CertificatePreviewWithId = IDL.Record({
+    'id' : IDL.Text,
+    'issued_at' : IDL.Text,
+    'name' : IDL.Text,
+    'issuer_club_name' : IDL.Opt(IDL.Text),
+    'sport_category' : IDL.Text,
+    'is_revoked' : IDL.Bool,
+  });
+  const GetUserCertificatesResponse = IDL.Record({
+    'next_cursor' : IDL.Opt(IDL.Text),
+    'certificates' : IDL.Vec(CertificatePreviewWithId),
+  });
+  const GetUserCertificatesResult = IDL.Variant({
+    'ok' : GetUserCertificatesResponse,
+    'err' : ApiError,
+  });
+  const MyCertificatesList = IDL.Variant({
+    'issued' : IDL.Null,
+    'received' : IDL.Null,
+  });
+  const GetMyCertificatesRequest = IDL.Record({
+    'filters' : IDL.Opt(CertificatesFilters),
+    'cursor' : IDL.Opt(IDL.Text),
+    'list' : MyCertificatesList,
+    'sort' : IDL.Opt(CertificatesSort),
+    'limit' : IDL.Opt(IDL.Nat32),
+  });
+  const ManagedUser = IDL.Record({
+    'claimed_at' : IDL.Opt(IDL.Text),
+    'claimed_by' : IDL.Opt(IDL.Principal),
+    'guardians' : IDL.Vec(IDL.Principal),
+    'created_at' : IDL.Text,
+    'db_id' : IDL.Text,
+  });
+  const GetMyManagedUsersResult = IDL.Variant({
+    'ok' : IDL.Vec(ManagedUser),
+    'err' : ApiError,
+  });
+  const UserRole = IDL.Variant({
+    'admin' : IDL.Null,
+    'coach' : IDL.Null,
+    'athlete' : IDL.Null,
+    'club_admin' : IDL.Null,
+  });
   const User = IDL.Record({
     'sub' : IDL.Text,
     'created_at' : IDL.Text,
     'db_id' : IDL.Text,
+    'roles' : IDL.Vec(UserRole),
+  });
+  const GetMyUserResult = IDL.Variant({ 'ok' : User, 'err' : ApiError });
+  const GetOidcProvidersResult = IDL.Variant({
+    'ok' : IDL.Vec(OidcConfig),
+    'err' : ApiError,
   });
   const GetUserCertificatesRequest = IDL.Record({
+    'filters' : IDL.Opt(CertificatesFilters),
     'user_principal' : IDL.Opt(IDL.Principal),
+    'cursor' : IDL.Opt(IDL.Text),
+    'sort' : IDL.Opt(CertificatesSort),
+    'limit' : IDL.Opt(IDL.Nat32),
     'user_db_id' : IDL.Opt(IDL.Text),
   });
-  const CertificatePreviewWithId = IDL.Record({
-    'id' : IDL.Text,
-    'name' : IDL.Text,
+  const HeaderField = IDL.Tuple(IDL.Text, IDL.Text);
+  const HttpRequest = IDL.Record({
+    'url' : IDL.Text,
+    'method' : IDL.Text,
+    'body' : IDL.Vec(IDL.Nat8),
+    'headers' : IDL.Vec(HeaderField),
+    'certificate_version' : IDL.Opt(IDL.Nat16),
   });
-  const GetUserCertificatesResponse = IDL.Record({
-    'certificates' : IDL.Vec(CertificatePreviewWithId),
+  const HttpResponse = IDL.Record({
+    'body' : IDL.Vec(IDL.Nat8),
+    'headers' : IDL.Vec(HeaderField),
+    'upgrade' : IDL.Opt(IDL.Bool),
+    'status_code' : IDL.Nat16,
+  });
+  const SupportedStandard = IDL.Record({ 'url' : IDL.Text, 'name' : IDL.Text });
+  const GetBlocksArgs = IDL.Vec(
+    IDL.Record({ 'start' : IDL.Nat, 'length' : IDL.Nat })
+  );
+  ICRC3Value.fill(
+    IDL.Variant({
+      'Int' : IDL.Int,
+      'Map' : IDL.Vec(IDL.Tuple(IDL.Text, ICRC3Value)),
+      'Nat' : IDL.Nat,
+      'Blob' : IDL.Vec(IDL.Nat8),
+      'Text' : IDL.Text,
+      'Array' : IDL.Vec(ICRC3Value),
+    })
+  );
+  GetBlocksResult.fill(
+    IDL.Record({
+      'log_length' : IDL.Nat,
+      'blocks' : IDL.Vec(IDL.Record({ 'id' : IDL.Nat, 'block' : ICRC3Value })),
+      'archived_blocks' : IDL.Vec(
+        IDL.Record({
+          'args' : GetBlocksArgs,
+          'callback' : IDL.Func([GetBlocksArgs], [GetBlocksResult], ['query']),
+        })
+      ),
+    })
+  );
+  const ICRC3DataCertificate = IDL.Record({
+    'certificate' : IDL.Vec(IDL.Nat8),
+    'hash_tree' : IDL.Vec(IDL.Nat8),
+  });
+  const SupportedBlockType = IDL.Record({
+    'url' : IDL.Text,
+    'block_type' : IDL.Text,
+  });
+  const Account = IDL.Record({
+    'owner' : IDL.Principal,
+    'subaccount' : IDL.Opt(IDL.Vec(IDL.Nat8)),
+  });
+  const TransferArg = IDL.Record({
+    'to' : Account,
+    'token_id' : IDL.Nat,
+    'memo' : IDL.Opt(IDL.Vec(IDL.Nat8)),
+    'from_subaccount' : IDL.Opt(IDL.Vec(IDL.Nat8)),
+    'created_at_time' : IDL.Opt(IDL.Nat64),
+  });
+  const TransferError = IDL.Variant({
+    'GenericError' : IDL.Record({
+      'message' : IDL.Text,
+      'error_code' : IDL.Nat,
+    }),
+    'Duplicate' : IDL.Record({ 'duplicate_of' : IDL.Nat }),
+    'NonExistingTokenId' : IDL.Null,
+    'Unauthorized' : IDL.Null,
+    'CreatedInFuture' : IDL.Record({ 'ledger_time' : IDL.Nat64 }),
+    'InvalidRecipient' : IDL.Null,
+    'GenericBatchError' : IDL.Record({
+      'message' : IDL.Text,
+      'error_code' : IDL.Nat,
+    }),
+    'TooOld' : IDL.Null,
   });
+  const TransferResult = IDL.Variant({ 'Ok' : IDL.Nat, 'Err' : TransferError });
   const UserKey = PublicKey;
   const PrepareDelegationResponse = IDL.Record({
     'user_key' : UserKey,
     'expiration' : Timestamp,
   });
+  const PrepareDelegationResult = IDL.Variant({
+    'ok' : PrepareDelegationResponse,
+    'err' : ApiError,
+  });
+  const RevokeCertificateRequest = IDL.Record({
+    'id' : IDL.Text,
+    'reason' : IDL.Text,
+  });
+  const SessionKey = PublicKey;
+  const SetCertificatePublicRequest = IDL.Record({
+    'id' : IDL.Text,
+    'is_public' : IDL.Bool,
+  });
+  const SetIssuerVerifiedRequest = IDL.Record({
+    'id' : IDL.Text,
+    'verified' : IDL.Bool,
+  });
+  const UpdateCertificateRequest = IDL.Record({
+    'id' : IDL.Text,
+    'content' : CreateCertificateContentRequest,
+  });
+  const UpdateCertificateResponse = IDL.Record({ 'version' : IDL.Nat32 });
+  const UpdateCertificateResult = IDL.Variant({
+    'ok' : UpdateCertificateResponse,
+    'err' : ApiError,
+  });
+  const UploadChunkRequest = IDL.Record({
+    'content' : IDL.Vec(IDL.Nat8),
+    'upload_id' : IDL.Text,
+    'index' : IDL.Nat32,
+  });
+  const VerifyCertificateRequest = IDL.Record({
+    'id' : IDL.Text,
+    'version' : IDL.Opt(IDL.Nat32),
+    'share_token' : IDL.Opt(IDL.Text),
+  });
   return IDL.Service({
+    'add_issuer_member' : IDL.Func([IssuerMemberRequest], [EmptyResult], []),
+    'add_managed_user_guardian' : IDL.Func(
+        [ManagedUserGuardianRequest],
+        [EmptyResult],
+        [],
+      ),
+    'commit_upload' : IDL.Func([IDL.Text], [CommitUploadResult], []),
+    'create_achievement' : IDL.Func(
+        [CreateAchievementRequest],
+        [CreateAchievementResult],
+        [],
+      ),
     'create_certificate' : IDL.Func(
         [CreateCertificateRequest],
-        [CreateCertificateResponse],
+        [CreateCertificateResult],
+        [],
+      ),
+    'create_certificate_share_token' : IDL.Func(
+        [IDL.Text],
+        [CreateCertificateShareTokenResult],
         [],
       ),
+    'create_issuer' : IDL.Func([CreateIssuerRequest], [CreateIssuerResult], []),
+    'create_upload' : IDL.Func([CreateUploadRequest], [CreateUploadResult], []),
+    'get_achievement' : IDL.Func([IDL.Text], [GetAchievementResult], ['query']),
+    'get_audit_log' : IDL.Func(
+        [GetAuditLogRequest],
+        [GetAuditLogResult],
+        ['query'],
+      ),
     'get_certificate' : IDL.Func(
+        [IDL.Text, IDL.Opt(IDL.Nat32)],
+        [GetCertificateResult],
+        ['query'],
+      ),
+    'get_certificate_as_vc' : IDL.Func(
         [IDL.Text],
-        [GetCertificateResponse],
+        [GetCertificateAsVcResult],
         ['query'],
       ),
-    'get_config' : IDL.Func([], [Config], ['query']),
+    'get_config' : IDL.Func([], [GetConfigResult], ['query']),
     'get_delegation' : IDL.Func(
         [IDL.Text, Timestamp],
-        [GetDelegationResponse],
+        [GetDelegationResult],
+        ['query'],
+      ),
+    'get_delegation_stats' : IDL.Func(
+        [],
+        [GetDelegationStatsResult],
+        ['query'],
+      ),
+    'get_file' : IDL.Func([GetFileRequest], [GetFileResult], ['query']),
+    'get_file_chunk' : IDL.Func(
+        [GetFileChunkRequest],
+        [GetFileChunkResult],
+        ['query'],
+      ),
+    'get_issuer' : IDL.Func([IDL.Text], [GetIssuerResult], ['query']),
+    'get_jwks' : IDL.Func([IDL.Text], [GetJwksResult], ['query']),
+    'get_managed_user_certificates' : IDL.Func(
+        [GetManagedUserCertificatesRequest],
+        [GetUserCertificatesResult],
+        ['query'],
+      ),
+    'get_my_certificates' : IDL.Func(
+        [GetMyCertificatesRequest],
+        [GetUserCertificatesResult],
         ['query'],
       ),
-    'get_jwks' : IDL.Func([], [IDL.Opt(Auth0JWKS)], ['query']),
-    'get_my_user' : IDL.Func([], [User], ['query']),
+    'get_my_managed_users' : IDL.Func([], [GetMyManagedUsersResult], ['query']),
+    'get_my_user' : IDL.Func([], [GetMyUserResult], ['query']),
+    'get_oidc_providers' : IDL.Func([], [GetOidcProvidersResult], ['query']),
     'get_user_certificates' : IDL.Func(
         [GetUserCertificatesRequest],
-        [GetUserCertificatesResponse],
+        [GetUserCertificatesResult],
         ['query'],
       ),
-    'prepare_delegation' : IDL.Func(
-        [IDL.Text],
-        [PrepareDelegationResponse],
+    'http_request' : IDL.Func([HttpRequest], [HttpResponse], ['query']),
+    'icrc10_supported_standards' : IDL.Func(
+        [],
+        [IDL.Vec(SupportedStandard)],
+        ['query'],
+      ),
+    'icrc3_get_blocks' : IDL.Func(
+        [GetBlocksArgs],
+        [GetBlocksResult],
+        ['query'],
+      ),
+    'icrc3_get_tip_certificate' : IDL.Func(
+        [],
+        [IDL.Opt(ICRC3DataCertificate)],
+        ['query'],
+      ),
+    'icrc3_supported_block_types' : IDL.Func(
+        [],
+        [IDL.Vec(SupportedBlockType)],
+        ['query'],
+      ),
+    'icrc7_balance_of' : IDL.Func(
+        [IDL.Vec(Account)],
+        [IDL.Vec(IDL.Nat)],
+        ['query'],
+      ),
+    'icrc7_collection_metadata' : IDL.Func(
+        [],
+        [IDL.Vec(IDL.Tuple(IDL.Text, ICRC3Value))],
+        ['query'],
+      ),
+    'icrc7_name' : IDL.Func([], [IDL.Text], ['query']),
+    'icrc7_owner_of' : IDL.Func(
+        [IDL.Vec(IDL.Nat)],
+        [IDL.Vec(IDL.Opt(Account))],
+        ['query'],
+      ),
+    'icrc7_symbol' : IDL.Func([], [IDL.Text], ['query']),
+    'icrc7_token_metadata' : IDL.Func(
+        [IDL.Vec(IDL.Nat)],
+        [IDL.Vec(IDL.Opt(IDL.Vec(IDL.Tuple(IDL.Text, ICRC3Value))))],
+        ['query'],
+      ),
+    'icrc7_tokens_of' : IDL.Func(
+        [Account, IDL.Opt(IDL.Nat), IDL.Opt(IDL.Nat)],
+        [IDL.Vec(IDL.Nat)],
+        ['query'],
+      ),
+    'icrc7_total_supply' : IDL.Func([], [IDL.Nat], ['query']),
+    'icrc7_transfer' : IDL.Func(
+        [IDL.Vec(TransferArg)],
+        [IDL.Vec(IDL.Opt(TransferResult))],
+        [],
+      ),
+    'prepare_delegation' : IDL.Func([IDL.Text], [PrepareDelegationResult], []),
+    'remove_issuer_member' : IDL.Func([IssuerMemberRequest], [EmptyResult], []),
+    'remove_managed_user_guardian' : IDL.Func(
+        [ManagedUserGuardianRequest],
+        [EmptyResult],
+        [],
+      ),
+    'remove_oidc_provider' : IDL.Func([IDL.Text], [EmptyResult], []),
+    'revoke_all_sessions' : IDL.Func([], [EmptyResult], []),
+    'revoke_certificate' : IDL.Func(
+        [RevokeCertificateRequest],
+        [EmptyResult],
+        [],
+      ),
+    'revoke_certificate_share_token' : IDL.Func([IDL.Text], [EmptyResult], []),
+    'revoke_session' : IDL.Func([SessionKey], [EmptyResult], []),
+    'set_backend_principal' : IDL.Func([IDL.Principal], [EmptyResult], []),
+    'set_certificate_public' : IDL.Func(
+        [SetCertificatePublicRequest],
+        [EmptyResult],
         [],
       ),
-    'set_backend_principal' : IDL.Func([IDL.Principal], [], []),
-    'set_jwks' : IDL.Func([Auth0JWKS], [], []),
-    'sync_jwks' : IDL.Func([], [], []),
+    'set_delegation_config' : IDL.Func([DelegationConfig], [EmptyResult], []),
+    'set_issuer_verified' : IDL.Func(
+        [SetIssuerVerifiedRequest],
+        [EmptyResult],
+        [],
+      ),
+    'set_jwks' : IDL.Func([IDL.Text, Auth0JWKS], [EmptyResult], []),
+    'set_oidc_provider' : IDL.Func([OidcConfig], [EmptyResult], []),
+    'sync_jwks' : IDL.Func([], [EmptyResult], []),
+    'update_certificate' : IDL.Func(
+        [UpdateCertificateRequest],
+        [UpdateCertificateResult],
+        [],
+      ),
+    'upload_chunk' : IDL.Func([UploadChunkRequest], [EmptyResult], []),
+    'verify_certificate' : IDL.Func(
+        [VerifyCertificateRequest],
+        [GetCertificateResult],
+        ['query'],
+      ),
+  });
+};
+export const init = ({ IDL }) => {
+  const OidcConfig = IDL.Record({
+    'jwks_url' : IDL.Text,
+    'claims_namespace' : IDL.Opt(IDL.Text),
+    'issuer' : IDL.Text,
+    'audiences' : IDL.Vec(IDL.Text),
+  });
+  const InitArgs = IDL.Record({
+    'oidc_providers' : IDL.Opt(IDL.Vec(OidcConfig)),
+    'legacy_oidc_issuer' : IDL.Opt(IDL.Text),
   });
+  return [IDL.Opt(InitArgs)];
 };
-export const init = ({ IDL }) => { return []; };
@@ -10,9 +10,59 @@ import {
   fromHex,
   NodeType,
 } from "@dfinity/agent";
-import type { CertificateWithId } from "./generated/ssp_backend.did";
+import type {
+  ApiError,
+  CertificateWithId,
+} from "./generated/ssp_backend.did";
 import { Principal } from "@dfinity/principal";
 
+/**
+ * The result returned by all the endpoints of the canister,
+ * e.g. `GetFileResult` is `ApiResult<GetFileResponse>`.
+ */
+export type ApiResult<T> = { ok: T } | { err: ApiError };
+
+export type ApiErrorKind = ApiError extends infer E
+  ? E extends Record<infer K, unknown>
+    ? K
+    : never
+  : never;
+
+/**
+ * Thrown by {@link unwrapApiResult}, keeps the structured error returned by the canister.
+ */
+export class ApiResultError extends Error {
+  readonly kind: ApiErrorKind;
+  readonly field: string | null;
+
+  constructor(readonly error: ApiError) {
+    const [kind, value] = Object.entries(error)[0] as [
+      ApiErrorKind,
+      { message: string; field?: string },
+    ];
+    super(value.field ? `${value.field}: ${value.message}` : value.message);
+    this.name = "ApiResultError";
+    this.kind = kind;
+    this.field = value.field ?? null;
+  }
+}
+
+export const isApiResultOk = <T>(
+  result: ApiResult<T>,
+): result is { ok: T } => {
+  return "ok" in result;
+};
+
+/**
+ * Returns the value of the result, or throws an {@link ApiResultError} with the error.
+ */
+export const unwrapApiResult = <T>(result: ApiResult<T>): T => {
+  if (isApiResultOk(result)) {
+    return result.ok;
+  }
+  throw new ApiResultError(result.err);
+};
+
 export type DecodedCertificate = {
   content: {
     name: string;
@@ -20,12 +70,18 @@ export type DecodedCertificate = {
     sport_category: string;
     notes: string | null;
     file_uri: string | null;
+    file_hash?: string;
     external_id: string | null;
     issuer_full_name: string | null;
     issuer_club_name: string | null;
   };
   created_at: string;
   managed_user_id: string | null;
+  revocation?: {
+    reason: string;
+    revoked_at: string;
+  };
+  updated_at?: string;
   user_principal: Principal;
 };
 
@@ -90,6 +146,13 @@ const uuidToBytes = (uuid: string): Uint8Array => {
   return bytes;
 };
 
+const versionToBytes = (version: number): Uint8Array => {
+  // big endian u32, same as in the canister
+  const bytes = new Uint8Array(4);
+  new DataView(bytes.buffer).setUint32(0, version, false);
+  return bytes;
+};
+
 const areBuffersEqual = (buf1: ArrayBuffer, buf2: ArrayBuffer): boolean => {
   return compare(buf1, buf2) === 0;
 };
@@ -156,6 +219,7 @@ export const verifyCertificateIntegrity = async (
     SSP_CERTIFICATES_TREE_LABEL,
     certificateData.user_principal.toUint8Array(),
     uuidToBytes(data.id),
+    versionToBytes(data.version),
   ];
   let treeShaLookupResult = lookup_path(path, hashTree);
   const treeSha = lookupResultToBuffer(treeShaLookupResult);
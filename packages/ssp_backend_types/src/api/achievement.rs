@@ -0,0 +1,88 @@
+use candid::{CandidType, Deserialize};
+
+use super::{ApiError, ValidateRequest, MAX_SPORT_CATEGORY_CHARS_COUNT};
+
+pub const MAX_ACHIEVEMENT_NAME_CHARS_COUNT: usize = 100;
+pub const MAX_ACHIEVEMENT_DESCRIPTION_CHARS_COUNT: usize = 500;
+pub const MAX_ACHIEVEMENT_CRITERIA_CHARS_COUNT: usize = 1000;
+
+#[derive(Debug, Clone, CandidType, Deserialize, PartialEq, Eq)]
+pub struct Achievement {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub criteria: String,
+    pub sport_category: Option<String>,
+    pub issuer_id: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, CandidType, Deserialize, PartialEq, Eq)]
+pub struct CreateAchievementRequest {
+    pub name: String,
+    pub description: String,
+    /// The narrative of what is required to earn the achievement.
+    pub criteria: String,
+    /// Only certificates of this sport category can award the achievement, if set.
+    pub sport_category: Option<String>,
+    /// Only certificates issued on behalf of this issuer can award the achievement, if set.
+    /// Required for users, who must be one of its admins.
+    pub issuer_id: Option<String>,
+}
+
+impl ValidateRequest for CreateAchievementRequest {
+    fn validate(&self) -> Result<(), ApiError> {
+        validate_text("name", "Name", &self.name, MAX_ACHIEVEMENT_NAME_CHARS_COUNT)?;
+        validate_text(
+            "description",
+            "Description",
+            &self.description,
+            MAX_ACHIEVEMENT_DESCRIPTION_CHARS_COUNT,
+        )?;
+        validate_text(
+            "criteria",
+            "Criteria",
+            &self.criteria,
+            MAX_ACHIEVEMENT_CRITERIA_CHARS_COUNT,
+        )?;
+
+        match &self.sport_category {
+            Some(sport_category) => validate_text(
+                "sport_category",
+                "Sport category",
+                sport_category,
+                MAX_SPORT_CATEGORY_CHARS_COUNT,
+            )?,
+            None if self.issuer_id.is_none() => {
+                return Err(ApiError::validation(
+                    "sport_category",
+                    "Sport category is required for achievements without an issuer.",
+                ));
+            }
+            None => {}
+        }
+
+        Ok(())
+    }
+}
+
+fn validate_text(field: &str, label: &str, value: &str, max_chars: usize) -> Result<(), ApiError> {
+    if value.trim().is_empty() {
+        return Err(ApiError::validation(
+            field,
+            format!("{} cannot be empty.", label),
+        ));
+    } else if value.chars().count() > max_chars {
+        return Err(ApiError::validation(
+            field,
+            format!("{} cannot be longer than {} characters.", label, max_chars),
+        ));
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Clone, CandidType, Deserialize, PartialEq, Eq)]
+pub struct CreateAchievementResponse {
+    pub id: String,
+}
@@ -0,0 +1,125 @@
+use candid::{CandidType, Deserialize, Principal};
+use serde::Serialize;
+
+use super::{ApiError, ValidateRequest};
+use crate::Timestamp;
+
+pub const DEFAULT_AUDIT_LOG_PAGE_LIMIT: u32 = 50;
+pub const MAX_AUDIT_LOG_PAGE_LIMIT: u32 = 100;
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub enum AuditLogOperation {
+    #[serde(rename = "create_user")]
+    CreateUser { user_principal: Principal },
+    #[serde(rename = "create_certificate")]
+    CreateCertificate { id: String },
+    #[serde(rename = "set_backend_principal")]
+    SetBackendPrincipal,
+    #[serde(rename = "set_jwks")]
+    SetJwks { issuer: String },
+    #[serde(rename = "sync_jwks")]
+    SyncJwks,
+    #[serde(rename = "set_delegation_config")]
+    SetDelegationConfig,
+    #[serde(rename = "set_oidc_provider")]
+    SetOidcProvider { issuer: String },
+    #[serde(rename = "remove_oidc_provider")]
+    RemoveOidcProvider { issuer: String },
+    #[serde(rename = "revoke_session")]
+    RevokeSession,
+    #[serde(rename = "revoke_all_sessions")]
+    RevokeAllSessions,
+    #[serde(rename = "add_managed_user_guardian")]
+    AddManagedUserGuardian { managed_user_id: String },
+    #[serde(rename = "remove_managed_user_guardian")]
+    RemoveManagedUserGuardian { managed_user_id: String },
+    #[serde(rename = "claim_managed_user")]
+    ClaimManagedUser {
+        managed_user_id: String,
+        user_principal: Principal,
+    },
+    #[serde(rename = "create_issuer")]
+    CreateIssuer { id: String },
+    #[serde(rename = "set_issuer_verified")]
+    SetIssuerVerified { id: String },
+    #[serde(rename = "add_issuer_member")]
+    AddIssuerMember { issuer_id: String },
+    #[serde(rename = "remove_issuer_member")]
+    RemoveIssuerMember { issuer_id: String },
+    #[serde(rename = "create_achievement")]
+    CreateAchievement { id: String },
+    #[serde(rename = "update_certificate")]
+    UpdateCertificate { id: String },
+    #[serde(rename = "revoke_certificate")]
+    RevokeCertificate { id: String },
+    #[serde(rename = "set_certificate_public")]
+    SetCertificatePublic { id: String },
+    #[serde(rename = "create_certificate_share_token")]
+    CreateCertificateShareToken { id: String },
+    #[serde(rename = "revoke_certificate_share_token")]
+    RevokeCertificateShareToken { id: String },
+    #[serde(rename = "create_upload")]
+    CreateUpload { upload_id: String },
+    #[serde(rename = "upload_chunk")]
+    UploadChunk { upload_id: String },
+    #[serde(rename = "commit_upload")]
+    CommitUpload {
+        upload_id: String,
+        file_hash: String,
+    },
+}
+
+/// The audit log entry encoded in [AuditLogEntryWithIndex::entry_cbor_hex].
+///
+/// The hash of an entry is the SHA-256 hash of its CBOR and
+/// each entry contains the hash of the previous one (32 zero bytes for the first entry).
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct AuditLogEntry {
+    pub caller: Principal,
+    pub timestamp: Timestamp,
+    pub operation: AuditLogOperation,
+    /// The hash of the Candid encoded arguments of the call.
+    pub payload_hash: Vec<u8>,
+    pub previous_hash: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Default, CandidType, Deserialize, PartialEq, Eq)]
+pub struct GetAuditLogRequest {
+    /// The index of the first entry to return. Defaults to 0.
+    pub start: Option<u64>,
+    /// Defaults to [DEFAULT_AUDIT_LOG_PAGE_LIMIT].
+    pub limit: Option<u32>,
+}
+
+impl ValidateRequest for GetAuditLogRequest {
+    fn validate(&self) -> Result<(), ApiError> {
+        if let Some(limit) = self.limit {
+            if limit == 0 || limit > MAX_AUDIT_LOG_PAGE_LIMIT {
+                return Err(ApiError::validation(
+                    "limit",
+                    format!("Limit must be between 1 and {}.", MAX_AUDIT_LOG_PAGE_LIMIT),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, CandidType, Deserialize, PartialEq, Eq)]
+pub struct AuditLogEntryWithIndex {
+    pub index: u64,
+    pub entry_cbor_hex: String,
+}
+
+#[derive(Debug, Clone, CandidType, Deserialize, PartialEq, Eq)]
+pub struct GetAuditLogResponse {
+    pub entries: Vec<AuditLogEntryWithIndex>,
+    /// The total number of entries in the audit log.
+    pub length: u64,
+    /// The hex encoded hash of the last entry.
+    /// It is certified along with the length, see `ic_certificate_witness`.
+    pub tip_hash: String,
+    pub ic_certificate: Vec<u8>,
+    pub ic_certificate_witness: Vec<u8>,
+}
@@ -1,7 +1,7 @@
 use candid::{CandidType, Deserialize, Principal};
 use serde::Serialize;
 
-use super::ValidateRequest;
+use super::{ApiError, ValidateRequest};
 
 pub const MAX_NAME_CHARS_COUNT: usize = 100;
 pub const MAX_SPORT_CATEGORY_CHARS_COUNT: usize = 80;
@@ -9,9 +9,14 @@ pub const MAX_NOTES_CHARS_COUNT: usize = 500;
 const KB: usize = 1024;
 const MB: usize = KB * KB;
 pub const MAX_FILE_BYTES_SIZE: usize = MB + (500 * KB); // 1.5 MB
+/// Inline data URIs that are stored already can be longer, new files go to the file store.
+pub const MAX_FILE_URI_BYTES_SIZE: usize = 2 * KB;
 pub const MAX_EXTERNAL_ID_CHARS_COUNT: usize = 100;
 pub const MAX_ISSUER_FULL_NAME_CHARS_COUNT: usize = 100;
 pub const MAX_ISSUER_CLUB_NAME_CHARS_COUNT: usize = 100;
+pub const MAX_REVOCATION_REASON_CHARS_COUNT: usize = 500;
+pub const DEFAULT_CERTIFICATES_PAGE_LIMIT: u32 = 20;
+pub const MAX_CERTIFICATES_PAGE_LIMIT: u32 = 100;
 
 #[derive(Debug, CandidType, Serialize, Deserialize, Clone, PartialEq, Eq)]
 pub struct CertificateContent {
@@ -20,17 +25,50 @@ pub struct CertificateContent {
     pub sport_category: String,
     pub notes: Option<String>,
     pub file_uri: Option<String>,
+    /// The hash of the file in the file store.
+    #[serde(default)]
+    pub file_hash: Option<String>,
     pub external_id: Option<String>,
     pub issuer_full_name: Option<String>,
     pub issuer_club_name: Option<String>,
 }
 
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct CertificateRevocation {
+    pub reason: String,
+    pub revoked_at: String,
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct CertificateIssuer {
+    pub id: String,
+    pub name: String,
+    /// Whether the issuer was verified when the certificate was issued.
+    pub verified: bool,
+}
+
+/// The optional fields added after the first release are not present
+/// in the certificate CBOR when they are not set.
 #[derive(Debug, CandidType, Serialize, Deserialize, Clone, PartialEq, Eq)]
 pub struct Certificate {
     pub user_principal: Principal,
     pub created_at: String,
     pub content: CertificateContent,
+    /// The registered issuer on behalf of which the certificate was issued.
+    #[serde(default)]
+    pub issuer: Option<CertificateIssuer>,
+    /// The principal of the user who issued the certificate to `user_principal`.
+    #[serde(default)]
+    pub issuer_principal: Option<Principal>,
     pub managed_user_id: Option<String>,
+    #[serde(default)]
+    pub revocation: Option<CertificateRevocation>,
+    /// When this version of the certificate was created. Not set for the first version.
+    #[serde(default)]
+    pub updated_at: Option<String>,
+    /// The achievement that the certificate awards.
+    #[serde(default)]
+    pub achievement_id: Option<String>,
 }
 
 #[derive(Debug, CandidType, Serialize, Deserialize, Clone, PartialEq, Eq)]
@@ -39,7 +77,10 @@ pub struct CreateCertificateContentRequest {
     pub issued_at: u64,
     pub sport_category: String,
     pub notes: Option<String>,
+    /// Deprecated, upload the file with `create_upload` and reference it with `file_hash` instead.
     pub file_uri: Option<String>,
+    /// The hash of a file uploaded with `create_upload`, as returned by `commit_upload`.
+    pub file_hash: Option<String>,
     pub external_id: Option<String>,
     pub issuer_full_name: Option<String>,
     pub issuer_club_name: Option<String>,
@@ -50,71 +91,106 @@ pub struct CreateCertificateRequest {
     pub user_db_id: String,
     pub content: CreateCertificateContentRequest,
     pub managed_user_db_id: Option<String>,
+    /// The registered issuer on behalf of which the certificate is issued.
+    /// The caller must be one of its admins or coaches, unless it's the backend.
+    pub issuer_id: Option<String>,
+    /// The achievement awarded by the certificate, see `create_achievement`.
+    /// It must be for the sport category and the issuer of the certificate, if it has any.
+    pub achievement_id: Option<String>,
 }
 
-impl ValidateRequest for CreateCertificateRequest {
-    fn validate(&self) -> Result<(), String> {
-        let content = &self.content;
-
-        if content.name.is_empty() {
-            return Err("Title cannot be empty.".to_string());
-        } else if content.name.chars().count() > MAX_NAME_CHARS_COUNT {
-            return Err(format!(
-                "Title cannot be longer than {} characters.",
-                MAX_NAME_CHARS_COUNT
+impl ValidateRequest for CreateCertificateContentRequest {
+    fn validate(&self) -> Result<(), ApiError> {
+        if self.name.is_empty() {
+            return Err(ApiError::validation("name", "Title cannot be empty."));
+        } else if self.name.chars().count() > MAX_NAME_CHARS_COUNT {
+            return Err(ApiError::validation(
+                "name",
+                format!(
+                    "Title cannot be longer than {} characters.",
+                    MAX_NAME_CHARS_COUNT
+                ),
             ));
         }
 
-        if content.sport_category.is_empty() {
-            return Err("Sport category cannot be empty.".to_string());
-        } else if content.sport_category.chars().count() > MAX_SPORT_CATEGORY_CHARS_COUNT {
-            return Err(format!(
-                "Sport category cannot be longer than {} characters.",
-                MAX_SPORT_CATEGORY_CHARS_COUNT
+        if self.sport_category.is_empty() {
+            return Err(ApiError::validation(
+                "sport_category",
+                "Sport category cannot be empty.",
+            ));
+        } else if self.sport_category.chars().count() > MAX_SPORT_CATEGORY_CHARS_COUNT {
+            return Err(ApiError::validation(
+                "sport_category",
+                format!(
+                    "Sport category cannot be longer than {} characters.",
+                    MAX_SPORT_CATEGORY_CHARS_COUNT
+                ),
             ));
         }
 
-        if let Some(notes) = &content.notes {
+        if let Some(notes) = &self.notes {
             if notes.chars().count() > MAX_NOTES_CHARS_COUNT {
-                return Err(format!(
-                    "Notes cannot be longer than {} characters.",
-                    MAX_NOTES_CHARS_COUNT
+                return Err(ApiError::validation(
+                    "notes",
+                    format!(
+                        "Notes cannot be longer than {} characters.",
+                        MAX_NOTES_CHARS_COUNT
+                    ),
                 ));
             }
         }
 
-        if let Some(file_uri) = &content.file_uri {
-            if file_uri.len() > MAX_FILE_BYTES_SIZE {
-                return Err(format!(
-                    "File bytes cannot be longer than {} bytes.",
-                    MAX_FILE_BYTES_SIZE
+        if let Some(file_uri) = &self.file_uri {
+            if file_uri.len() > MAX_FILE_URI_BYTES_SIZE {
+                return Err(ApiError::validation(
+                    "file_uri",
+                    format!(
+                        "File URI cannot be longer than {} bytes, upload the file instead.",
+                        MAX_FILE_URI_BYTES_SIZE
+                    ),
                 ));
             }
         }
 
-        if let Some(external_id) = &content.external_id {
+        if self.file_uri.is_some() && self.file_hash.is_some() {
+            return Err(ApiError::validation(
+                "file_hash",
+                "File URI and file hash cannot be both set.",
+            ));
+        }
+
+        if let Some(external_id) = &self.external_id {
             if external_id.chars().count() > MAX_EXTERNAL_ID_CHARS_COUNT {
-                return Err(format!(
-                    "External ID cannot be longer than {} characters.",
-                    MAX_EXTERNAL_ID_CHARS_COUNT
+                return Err(ApiError::validation(
+                    "external_id",
+                    format!(
+                        "External ID cannot be longer than {} characters.",
+                        MAX_EXTERNAL_ID_CHARS_COUNT
+                    ),
                 ));
             }
         }
 
-        if let Some(issuer_full_name) = &content.issuer_full_name {
+        if let Some(issuer_full_name) = &self.issuer_full_name {
             if issuer_full_name.chars().count() > MAX_ISSUER_FULL_NAME_CHARS_COUNT {
-                return Err(format!(
-                    "Issuer full name cannot be longer than {} characters.",
-                    MAX_ISSUER_FULL_NAME_CHARS_COUNT
+                return Err(ApiError::validation(
+                    "issuer_full_name",
+                    format!(
+                        "Issuer full name cannot be longer than {} characters.",
+                        MAX_ISSUER_FULL_NAME_CHARS_COUNT
+                    ),
                 ));
             }
         }
 
-        if let Some(issuer_club_name) = &content.issuer_club_name {
+        if let Some(issuer_club_name) = &self.issuer_club_name {
             if issuer_club_name.chars().count() > MAX_ISSUER_CLUB_NAME_CHARS_COUNT {
-                return Err(format!(
-                    "Issuer club name cannot be longer than {} characters.",
-                    MAX_ISSUER_CLUB_NAME_CHARS_COUNT
+                return Err(ApiError::validation(
+                    "issuer_club_name",
+                    format!(
+                        "Issuer club name cannot be longer than {} characters.",
+                        MAX_ISSUER_CLUB_NAME_CHARS_COUNT
+                    ),
                 ));
             }
         }
@@ -123,51 +199,309 @@ impl ValidateRequest for CreateCertificateRequest {
     }
 }
 
+impl ValidateRequest for CreateCertificateRequest {
+    fn validate(&self) -> Result<(), ApiError> {
+        self.content.validate().map_err(|e| e.in_field("content"))
+    }
+}
+
 #[derive(Debug, Clone, CandidType, Deserialize, PartialEq, Eq)]
 pub struct CreateCertificateResponse {
     pub id: String,
 }
 
-#[derive(Debug, Clone, CandidType, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, CandidType, Deserialize, PartialEq, Eq)]
+pub enum CertificatesSortField {
+    #[serde(rename = "created_at")]
+    CreatedAt,
+    #[serde(rename = "issued_at")]
+    IssuedAt,
+}
+
+#[derive(Debug, Clone, Copy, CandidType, Deserialize, PartialEq, Eq)]
+pub enum SortOrder {
+    #[serde(rename = "asc")]
+    Asc,
+    #[serde(rename = "desc")]
+    Desc,
+}
+
+#[derive(Debug, Clone, Copy, CandidType, Deserialize, PartialEq, Eq)]
+pub struct CertificatesSort {
+    pub field: CertificatesSortField,
+    pub order: SortOrder,
+}
+
+#[derive(Debug, Clone, Default, CandidType, Deserialize, PartialEq, Eq)]
+pub struct CertificatesFilters {
+    /// Case insensitive.
+    pub sport_category: Option<String>,
+    /// Timestamp in microseconds (included).
+    pub issued_at_from: Option<u64>,
+    /// Timestamp in microseconds (included).
+    pub issued_at_to: Option<u64>,
+    /// Case insensitive.
+    pub issuer_club_name: Option<String>,
+}
+
+impl ValidateRequest for CertificatesFilters {
+    fn validate(&self) -> Result<(), ApiError> {
+        if let Some(sport_category) = &self.sport_category {
+            if sport_category.trim().is_empty() {
+                return Err(ApiError::validation(
+                    "sport_category",
+                    "Sport category filter cannot be empty.",
+                ));
+            }
+        }
+
+        if let (Some(issued_at_from), Some(issued_at_to)) = (self.issued_at_from, self.issued_at_to)
+        {
+            if issued_at_from > issued_at_to {
+                return Err(ApiError::validation(
+                    "issued_at_from",
+                    "Issued at from cannot be after issued at to.",
+                ));
+            }
+        }
+
+        if let Some(issuer_club_name) = &self.issuer_club_name {
+            if issuer_club_name.trim().is_empty() {
+                return Err(ApiError::validation(
+                    "issuer_club_name",
+                    "Issuer club name filter cannot be empty.",
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Default, CandidType, Deserialize, PartialEq, Eq)]
 pub struct GetUserCertificatesRequest {
     pub user_principal: Option<Principal>,
     pub user_db_id: Option<String>,
+    /// Defaults to [DEFAULT_CERTIFICATES_PAGE_LIMIT].
+    pub limit: Option<u32>,
+    /// The `next_cursor` of the previous page.
+    /// Must be used with the same sort options of the previous page.
+    pub cursor: Option<String>,
+    /// Defaults to newest certificates first.
+    pub sort: Option<CertificatesSort>,
+    pub filters: Option<CertificatesFilters>,
 }
 
 impl ValidateRequest for GetUserCertificatesRequest {
-    fn validate(&self) -> Result<(), String> {
+    fn validate(&self) -> Result<(), ApiError> {
         if self.user_principal.is_none() && self.user_db_id.is_none() {
-            return Err("Either user_principal or user_db_id must be provided.".to_string());
+            return Err(ApiError::validation(
+                "user_principal",
+                "Either user_principal or user_db_id must be provided.",
+            ));
         }
 
         if self.user_principal.is_some() && self.user_db_id.is_some() {
-            return Err("Only one of user_principal or user_db_id can be provided.".to_string());
+            return Err(ApiError::validation(
+                "user_principal",
+                "Only one of user_principal or user_db_id can be provided.",
+            ));
         }
 
-        Ok(())
+        validate_certificates_page(self.limit, self.filters.as_ref())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, CandidType, Deserialize, PartialEq, Eq)]
+pub enum MyCertificatesList {
+    /// The certificates issued to the caller.
+    #[default]
+    #[serde(rename = "received")]
+    Received,
+    /// The certificates that the caller issued to other users.
+    #[serde(rename = "issued")]
+    Issued,
+}
+
+#[derive(Debug, Clone, Default, CandidType, Deserialize, PartialEq, Eq)]
+pub struct GetMyCertificatesRequest {
+    pub list: MyCertificatesList,
+    /// Defaults to [DEFAULT_CERTIFICATES_PAGE_LIMIT].
+    pub limit: Option<u32>,
+    /// The `next_cursor` of the previous page.
+    /// Must be used with the same list and sort options of the previous page.
+    pub cursor: Option<String>,
+    /// Defaults to newest certificates first.
+    pub sort: Option<CertificatesSort>,
+    pub filters: Option<CertificatesFilters>,
+}
+
+impl ValidateRequest for GetMyCertificatesRequest {
+    fn validate(&self) -> Result<(), ApiError> {
+        validate_certificates_page(self.limit, self.filters.as_ref())
+    }
+}
+
+#[derive(Debug, Clone, Default, CandidType, Deserialize, PartialEq, Eq)]
+pub struct GetManagedUserCertificatesRequest {
+    pub managed_user_db_id: String,
+    /// Defaults to [DEFAULT_CERTIFICATES_PAGE_LIMIT].
+    pub limit: Option<u32>,
+    /// The `next_cursor` of the previous page.
+    /// Must be used with the same sort options of the previous page.
+    pub cursor: Option<String>,
+    /// Defaults to newest certificates first.
+    pub sort: Option<CertificatesSort>,
+    pub filters: Option<CertificatesFilters>,
+}
+
+impl ValidateRequest for GetManagedUserCertificatesRequest {
+    fn validate(&self) -> Result<(), ApiError> {
+        validate_certificates_page(self.limit, self.filters.as_ref())
     }
 }
 
+fn validate_certificates_page(
+    limit: Option<u32>,
+    filters: Option<&CertificatesFilters>,
+) -> Result<(), ApiError> {
+    if let Some(limit) = limit {
+        if limit == 0 || limit > MAX_CERTIFICATES_PAGE_LIMIT {
+            return Err(ApiError::validation(
+                "limit",
+                format!(
+                    "Limit must be between 1 and {}.",
+                    MAX_CERTIFICATES_PAGE_LIMIT
+                ),
+            ));
+        }
+    }
+
+    if let Some(filters) = filters {
+        filters.validate().map_err(|e| e.in_field("filters"))?;
+    }
+
+    Ok(())
+}
+
 #[derive(Debug, Clone, CandidType, Deserialize, PartialEq, Eq)]
 pub struct CertificatePreviewWithId {
     pub id: String,
     pub name: String,
+    pub sport_category: String,
+    pub issued_at: String,
+    pub issuer_club_name: Option<String>,
+    pub is_revoked: bool,
 }
 
 #[derive(Debug, Clone, CandidType, Deserialize, PartialEq, Eq)]
 pub struct GetUserCertificatesResponse {
     pub certificates: Vec<CertificatePreviewWithId>,
+    /// Present if there are more certificates to fetch. The page can have fewer certificates
+    /// than requested, even none, if many certificates don't match the filters.
+    pub next_cursor: Option<String>,
 }
 
 #[derive(Debug, Clone, CandidType, Deserialize, PartialEq, Eq)]
 pub struct CertificateWithId {
     pub id: String,
+    pub version: u32,
     pub certificate_cbor_hex: String,
 }
 
 #[derive(Debug, Clone, CandidType, Deserialize, PartialEq, Eq)]
 pub struct GetCertificateResponse {
     pub certificate: CertificateWithId,
+    pub latest_version: u32,
     pub ic_certificate: Vec<u8>,
     pub ic_certificate_witness: Vec<u8>,
 }
+
+/// The latest version of a certificate, as a W3C Verifiable Credential (VC Data Model 2.0).
+#[derive(Debug, Clone, CandidType, Deserialize, PartialEq, Eq)]
+pub struct GetCertificateAsVcResponse {
+    /// The JSON credential, issued by the canister to the user principal.
+    pub credential: String,
+    /// The DER-encoded canister signature public key of the issuer.
+    pub public_key: Vec<u8>,
+    /// The CBOR-encoded canister signature of the credential. The signed message is the length
+    /// of the `ssp-certificate-credential` domain separator, the separator and the credential.
+    pub signature: Vec<u8>,
+}
+
+#[derive(Debug, Clone, CandidType, Deserialize, PartialEq, Eq)]
+pub struct UpdateCertificateRequest {
+    pub id: String,
+    pub content: CreateCertificateContentRequest,
+}
+
+impl ValidateRequest for UpdateCertificateRequest {
+    fn validate(&self) -> Result<(), ApiError> {
+        self.content.validate().map_err(|e| e.in_field("content"))
+    }
+}
+
+#[derive(Debug, Clone, CandidType, Deserialize, PartialEq, Eq)]
+pub struct UpdateCertificateResponse {
+    pub version: u32,
+}
+
+#[derive(Debug, Clone, CandidType, Deserialize, PartialEq, Eq)]
+pub struct RevokeCertificateRequest {
+    pub id: String,
+    pub reason: String,
+}
+
+impl ValidateRequest for RevokeCertificateRequest {
+    fn validate(&self) -> Result<(), ApiError> {
+        if self.reason.is_empty() {
+            return Err(ApiError::validation(
+                "reason",
+                "Revocation reason cannot be empty.",
+            ));
+        } else if self.reason.chars().count() > MAX_REVOCATION_REASON_CHARS_COUNT {
+            return Err(ApiError::validation(
+                "reason",
+                format!(
+                    "Revocation reason cannot be longer than {} characters.",
+                    MAX_REVOCATION_REASON_CHARS_COUNT
+                ),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, CandidType, Deserialize, PartialEq, Eq)]
+pub struct SetCertificatePublicRequest {
+    pub id: String,
+    pub is_public: bool,
+}
+
+#[derive(Debug, Clone, CandidType, Deserialize, PartialEq, Eq)]
+pub struct CreateCertificateShareTokenResponse {
+    pub share_token: String,
+}
+
+#[derive(Debug, Clone, CandidType, Deserialize, PartialEq, Eq)]
+pub struct VerifyCertificateRequest {
+    pub id: String,
+    pub share_token: Option<String>,
+    pub version: Option<u32>,
+}
+
+impl ValidateRequest for VerifyCertificateRequest {
+    fn validate(&self) -> Result<(), ApiError> {
+        if let Some(share_token) = &self.share_token {
+            if share_token.is_empty() {
+                return Err(ApiError::validation(
+                    "share_token",
+                    "Share token cannot be empty.",
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
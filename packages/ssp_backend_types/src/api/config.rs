@@ -1,6 +1,304 @@
 use candid::{CandidType, Deserialize, Principal};
 
+use super::{ApiError, ValidateRequest};
+
 #[derive(Debug, Clone, CandidType, Deserialize, PartialEq, Eq)]
 pub struct Config {
     pub backend_principal: Option<Principal>,
+    pub delegation: DelegationConfig,
+    pub legacy_oidc_issuer: Option<String>,
+}
+
+/// The restrictions applied to the delegations issued by the canister.
+#[derive(Debug, Default, Clone, CandidType, Deserialize, PartialEq, Eq)]
+pub struct DelegationConfig {
+    /// The maximum lifetime of a delegation, in nanoseconds.
+    /// If not set, the delegation expires together with the ID token.
+    pub max_session_duration_ns: Option<u64>,
+    /// Overrides `max_session_duration_ns` for the users with the given Hasura default role.
+    pub role_session_durations: Vec<RoleSessionDuration>,
+    /// The canisters to which the delegations are restricted.
+    /// If not set, the delegations are valid for any canister.
+    pub targets: Option<Vec<Principal>>,
+}
+
+#[derive(Debug, Clone, CandidType, Deserialize, PartialEq, Eq)]
+pub struct RoleSessionDuration {
+    pub role: String,
+    pub max_session_duration_ns: u64,
+}
+
+impl ValidateRequest for DelegationConfig {
+    fn validate(&self) -> Result<(), ApiError> {
+        if self.max_session_duration_ns == Some(0) {
+            return Err(ApiError::validation(
+                "max_session_duration_ns",
+                "Must be greater than zero.",
+            ));
+        }
+
+        for (i, role_session_duration) in self.role_session_durations.iter().enumerate() {
+            if role_session_duration.role.is_empty() {
+                return Err(ApiError::validation(
+                    format!("role_session_durations[{}].role", i),
+                    "Role cannot be empty.",
+                ));
+            }
+            if self.role_session_durations[..i]
+                .iter()
+                .any(|other| other.role == role_session_duration.role)
+            {
+                return Err(ApiError::validation(
+                    format!("role_session_durations[{}].role", i),
+                    "Roles must be unique.",
+                ));
+            }
+            if role_session_duration.max_session_duration_ns == 0 {
+                return Err(ApiError::validation(
+                    format!("role_session_durations[{}].max_session_duration_ns", i),
+                    "Must be greater than zero.",
+                ));
+            }
+        }
+
+        if self
+            .targets
+            .as_ref()
+            .is_some_and(|targets| targets.is_empty())
+        {
+            return Err(ApiError::validation(
+                "targets",
+                "At least one target is required.",
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// An OpenID Connect provider that issues the JWTs accepted by the canister,
+/// identified by its issuer.
+#[derive(Debug, Clone, CandidType, Deserialize, PartialEq, Eq)]
+pub struct OidcConfig {
+    /// The expected `iss` claim, e.g. `https://<tenant>.auth0.com/` (with trailing slash).
+    pub issuer: String,
+    /// The accepted values of the `aud` claim.
+    pub audiences: Vec<String>,
+    /// The URL from which the JWKS is fetched,
+    /// e.g. `https://<tenant>.auth0.com/.well-known/jwks.json`.
+    pub jwks_url: String,
+    /// The claim under which the Hasura claims are set, e.g. `https://hasura.io/jwt/claims`.
+    /// Only the users of the providers that set them are linked to a database user.
+    pub claims_namespace: Option<String>,
+}
+
+impl ValidateRequest for OidcConfig {
+    fn validate(&self) -> Result<(), ApiError> {
+        validate_url("issuer", &self.issuer)?;
+
+        if self.audiences.is_empty() {
+            return Err(ApiError::validation(
+                "audiences",
+                "At least one audience is required.",
+            ));
+        }
+        if self.audiences.iter().any(|audience| audience.is_empty()) {
+            return Err(ApiError::validation(
+                "audiences",
+                "Audiences cannot be empty.",
+            ));
+        }
+
+        validate_url("jwks_url", &self.jwks_url)?;
+
+        if self
+            .claims_namespace
+            .as_ref()
+            .is_some_and(|claims_namespace| claims_namespace.is_empty())
+        {
+            return Err(ApiError::validation(
+                "claims_namespace",
+                "Claims namespace cannot be empty.",
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+fn validate_url(field: &str, url: &str) -> Result<(), ApiError> {
+    let host = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))
+        .unwrap_or_default();
+    if host.is_empty() {
+        return Err(ApiError::validation(field, "Must be an http or https URL."));
+    }
+
+    Ok(())
+}
+
+/// The arguments of the canister `init` and `post_upgrade` methods.
+#[derive(Debug, Clone, CandidType, Deserialize, PartialEq, Eq)]
+pub struct InitArgs {
+    /// If set, replaces the OIDC providers of the canister.
+    pub oidc_providers: Option<Vec<OidcConfig>>,
+    /// The issuer of the JWTs accepted before multiple OIDC providers were supported.
+    /// Its users keep the principals derived from their `sub` only,
+    /// while the principals of the other issuers' users are derived from the issuer too.
+    /// Cannot be changed once set.
+    pub legacy_oidc_issuer: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_oidc_config_validate() {
+        assert_eq!(oidc_config().validate(), Ok(()));
+        assert_eq!(
+            OidcConfig {
+                claims_namespace: None,
+                ..oidc_config()
+            }
+            .validate(),
+            Ok(())
+        );
+
+        let invalid_configs = [
+            (
+                OidcConfig {
+                    issuer: "".to_string(),
+                    ..oidc_config()
+                },
+                ApiError::validation("issuer", "Must be an http or https URL."),
+            ),
+            (
+                OidcConfig {
+                    issuer: "example.com/".to_string(),
+                    ..oidc_config()
+                },
+                ApiError::validation("issuer", "Must be an http or https URL."),
+            ),
+            (
+                OidcConfig {
+                    audiences: vec![],
+                    ..oidc_config()
+                },
+                ApiError::validation("audiences", "At least one audience is required."),
+            ),
+            (
+                OidcConfig {
+                    audiences: vec!["audience".to_string(), "".to_string()],
+                    ..oidc_config()
+                },
+                ApiError::validation("audiences", "Audiences cannot be empty."),
+            ),
+            (
+                OidcConfig {
+                    jwks_url: "https://".to_string(),
+                    ..oidc_config()
+                },
+                ApiError::validation("jwks_url", "Must be an http or https URL."),
+            ),
+            (
+                OidcConfig {
+                    claims_namespace: Some("".to_string()),
+                    ..oidc_config()
+                },
+                ApiError::validation("claims_namespace", "Claims namespace cannot be empty."),
+            ),
+        ];
+
+        for (config, expected_error) in invalid_configs {
+            assert_eq!(config.validate(), Err(expected_error));
+        }
+    }
+
+    #[test]
+    fn test_delegation_config_validate() {
+        assert_eq!(DelegationConfig::default().validate(), Ok(()));
+        assert_eq!(delegation_config().validate(), Ok(()));
+
+        let invalid_configs = [
+            (
+                DelegationConfig {
+                    max_session_duration_ns: Some(0),
+                    ..delegation_config()
+                },
+                ApiError::validation("max_session_duration_ns", "Must be greater than zero."),
+            ),
+            (
+                DelegationConfig {
+                    role_session_durations: vec![RoleSessionDuration {
+                        role: "".to_string(),
+                        max_session_duration_ns: 1,
+                    }],
+                    ..delegation_config()
+                },
+                ApiError::validation("role_session_durations[0].role", "Role cannot be empty."),
+            ),
+            (
+                DelegationConfig {
+                    role_session_durations: vec![
+                        RoleSessionDuration {
+                            role: "user".to_string(),
+                            max_session_duration_ns: 1,
+                        },
+                        RoleSessionDuration {
+                            role: "user".to_string(),
+                            max_session_duration_ns: 2,
+                        },
+                    ],
+                    ..delegation_config()
+                },
+                ApiError::validation("role_session_durations[1].role", "Roles must be unique."),
+            ),
+            (
+                DelegationConfig {
+                    role_session_durations: vec![RoleSessionDuration {
+                        role: "user".to_string(),
+                        max_session_duration_ns: 0,
+                    }],
+                    ..delegation_config()
+                },
+                ApiError::validation(
+                    "role_session_durations[0].max_session_duration_ns",
+                    "Must be greater than zero.",
+                ),
+            ),
+            (
+                DelegationConfig {
+                    targets: Some(vec![]),
+                    ..delegation_config()
+                },
+                ApiError::validation("targets", "At least one target is required."),
+            ),
+        ];
+
+        for (config, expected_error) in invalid_configs {
+            assert_eq!(config.validate(), Err(expected_error));
+        }
+    }
+
+    fn delegation_config() -> DelegationConfig {
+        DelegationConfig {
+            max_session_duration_ns: Some(3_600_000_000_000),
+            role_session_durations: vec![RoleSessionDuration {
+                role: "admin".to_string(),
+                max_session_duration_ns: 600_000_000_000,
+            }],
+            targets: Some(vec![Principal::management_canister()]),
+        }
+    }
+
+    fn oidc_config() -> OidcConfig {
+        OidcConfig {
+            issuer: "https://example.com/".to_string(),
+            audiences: vec!["audience".to_string()],
+            jwks_url: "https://example.com/.well-known/jwks.json".to_string(),
+            claims_namespace: Some("https://hasura.io/jwt/claims".to_string()),
+        }
+    }
 }
@@ -0,0 +1,140 @@
+use candid::{CandidType, Deserialize};
+
+/// The error returned by all the endpoints of the canister.
+#[derive(Debug, Clone, CandidType, Deserialize, PartialEq, Eq)]
+pub enum ApiError {
+    /// The caller is not allowed to perform the operation.
+    #[serde(rename = "unauthorized")]
+    Unauthorized { message: String },
+    /// The requested resource does not exist, or is not visible to the caller.
+    #[serde(rename = "not_found")]
+    NotFound { message: String },
+    /// A field of the request is invalid.
+    /// Nested fields are separated by a dot, e.g. `content.name`.
+    #[serde(rename = "validation")]
+    Validation { field: String, message: String },
+    /// The operation is not allowed in the current state of the resource.
+    #[serde(rename = "conflict")]
+    Conflict { message: String },
+    /// An unexpected error occurred in the canister.
+    #[serde(rename = "internal")]
+    Internal { message: String },
+}
+
+impl ApiError {
+    pub fn unauthorized(message: impl Into<String>) -> Self {
+        Self::Unauthorized {
+            message: message.into(),
+        }
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::NotFound {
+            message: message.into(),
+        }
+    }
+
+    pub fn validation(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::Validation {
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+
+    pub fn conflict(message: impl Into<String>) -> Self {
+        Self::Conflict {
+            message: message.into(),
+        }
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self::Internal {
+            message: message.into(),
+        }
+    }
+
+    /// Prefixes the field of a validation error with the field of the parent request.
+    /// Other errors are returned unchanged.
+    pub fn in_field(self, parent_field: &str) -> Self {
+        match self {
+            Self::Validation { field, message } => Self::Validation {
+                field: format!("{}.{}", parent_field, field),
+                message,
+            },
+            error => error,
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            Self::Unauthorized { message }
+            | Self::NotFound { message }
+            | Self::Validation { message, .. }
+            | Self::Conflict { message }
+            | Self::Internal { message } => message,
+        }
+    }
+}
+
+/// Errors coming from the lower layers of the canister are unexpected for the caller.
+impl From<String> for ApiError {
+    fn from(message: String) -> Self {
+        Self::internal(message)
+    }
+}
+
+/// The Candid `variant { ok : T; err : ApiError }` returned by the endpoints.
+#[derive(Debug, Clone, CandidType, Deserialize, PartialEq, Eq)]
+pub enum ApiResult<T> {
+    #[serde(rename = "ok")]
+    Ok(T),
+    #[serde(rename = "err")]
+    Err(ApiError),
+}
+
+impl<T> From<Result<T, ApiError>> for ApiResult<T> {
+    fn from(result: Result<T, ApiError>) -> Self {
+        match result {
+            Ok(value) => Self::Ok(value),
+            Err(error) => Self::Err(error),
+        }
+    }
+}
+
+impl<T> From<ApiResult<T>> for Result<T, ApiError> {
+    fn from(result: ApiResult<T>) -> Self {
+        match result {
+            ApiResult::Ok(value) => Ok(value),
+            ApiResult::Err(error) => Err(error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_api_error_in_field() {
+        let error = ApiError::validation("name", "Title cannot be empty.").in_field("content");
+
+        assert_eq!(
+            error,
+            ApiError::validation("content.name", "Title cannot be empty.")
+        );
+        assert_eq!(error.message(), "Title cannot be empty.");
+
+        let error = ApiError::not_found("Certificate not found").in_field("content");
+
+        assert_eq!(error, ApiError::not_found("Certificate not found"));
+    }
+
+    #[test]
+    fn test_api_result_conversion() {
+        let result: ApiResult<u32> = Ok(1).into();
+        assert_eq!(result, ApiResult::Ok(1));
+
+        let result: Result<u32, ApiError> = ApiResult::Err(ApiError::internal("error")).into();
+        assert_eq!(result, Err(ApiError::internal("error")));
+    }
+}
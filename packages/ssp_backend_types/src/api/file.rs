@@ -0,0 +1,111 @@
+use candid::{CandidType, Deserialize};
+use serde_bytes::ByteBuf;
+
+use super::{ApiError, ValidateRequest, MAX_FILE_BYTES_SIZE};
+
+/// The maximum size of an uploaded chunk, which keeps the `upload_chunk` calls
+/// well below the ingress message size limit.
+pub const MAX_FILE_CHUNK_BYTES_SIZE: usize = 512 * 1024; // 512 KB
+pub const ALLOWED_FILE_CONTENT_TYPES: [&str; 4] =
+    ["image/png", "image/jpeg", "image/webp", "application/pdf"];
+
+#[derive(Debug, Clone, CandidType, Deserialize, PartialEq, Eq)]
+pub struct CreateUploadRequest {
+    pub content_type: String,
+    /// The total size of the file, in bytes.
+    pub size: u64,
+}
+
+impl ValidateRequest for CreateUploadRequest {
+    fn validate(&self) -> Result<(), ApiError> {
+        if !ALLOWED_FILE_CONTENT_TYPES.contains(&self.content_type.as_str()) {
+            return Err(ApiError::validation(
+                "content_type",
+                format!(
+                    "Content type must be one of: {}.",
+                    ALLOWED_FILE_CONTENT_TYPES.join(", ")
+                ),
+            ));
+        }
+
+        if self.size == 0 {
+            return Err(ApiError::validation("size", "File cannot be empty."));
+        } else if self.size > MAX_FILE_BYTES_SIZE as u64 {
+            return Err(ApiError::validation(
+                "size",
+                format!(
+                    "File bytes cannot be longer than {} bytes.",
+                    MAX_FILE_BYTES_SIZE
+                ),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, CandidType, Deserialize, PartialEq, Eq)]
+pub struct CreateUploadResponse {
+    pub upload_id: String,
+}
+
+#[derive(Debug, Clone, CandidType, Deserialize, PartialEq, Eq)]
+pub struct UploadChunkRequest {
+    pub upload_id: String,
+    /// The index of the chunk, starting from 0.
+    pub index: u32,
+    pub content: ByteBuf,
+}
+
+impl ValidateRequest for UploadChunkRequest {
+    fn validate(&self) -> Result<(), ApiError> {
+        if self.content.is_empty() {
+            return Err(ApiError::validation("content", "Chunk cannot be empty."));
+        } else if self.content.len() > MAX_FILE_CHUNK_BYTES_SIZE {
+            return Err(ApiError::validation(
+                "content",
+                format!(
+                    "Chunk bytes cannot be longer than {} bytes.",
+                    MAX_FILE_CHUNK_BYTES_SIZE
+                ),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, CandidType, Deserialize, PartialEq, Eq)]
+pub struct CommitUploadResponse {
+    /// The hex encoded SHA-256 hash of the file content,
+    /// which can be used to reference the file in a certificate.
+    pub file_hash: String,
+}
+
+#[derive(Debug, Clone, CandidType, Deserialize, PartialEq, Eq)]
+pub struct GetFileRequest {
+    pub file_hash: String,
+    /// The share token of a certificate that references the file,
+    /// to read the file of a certificate that is shared but not public.
+    pub share_token: Option<String>,
+}
+
+#[derive(Debug, Clone, CandidType, Deserialize, PartialEq, Eq)]
+pub struct GetFileResponse {
+    pub content_type: String,
+    pub size: u64,
+    pub chunks_count: u32,
+}
+
+#[derive(Debug, Clone, CandidType, Deserialize, PartialEq, Eq)]
+pub struct GetFileChunkRequest {
+    pub file_hash: String,
+    pub index: u32,
+    /// See [GetFileRequest].
+    pub share_token: Option<String>,
+}
+
+#[derive(Debug, Clone, CandidType, Deserialize, PartialEq, Eq)]
+pub struct GetFileChunkResponse {
+    pub content: ByteBuf,
+}
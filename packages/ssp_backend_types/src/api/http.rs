@@ -0,0 +1,22 @@
+use candid::{CandidType, Deserialize};
+use serde_bytes::ByteBuf;
+
+pub type HeaderField = (String, String);
+
+/// The HTTP request, as sent by the HTTP gateway.
+#[derive(Debug, Clone, CandidType, Deserialize, PartialEq, Eq)]
+pub struct HttpRequest {
+    pub method: String,
+    pub url: String,
+    pub headers: Vec<HeaderField>,
+    pub body: ByteBuf,
+    pub certificate_version: Option<u16>,
+}
+
+#[derive(Debug, Clone, CandidType, Deserialize, PartialEq, Eq)]
+pub struct HttpResponse {
+    pub status_code: u16,
+    pub headers: Vec<HeaderField>,
+    pub body: ByteBuf,
+    pub upgrade: Option<bool>,
+}
@@ -0,0 +1,60 @@
+use candid::{CandidType, Deserialize, Int, Nat};
+use serde_bytes::ByteBuf;
+
+/// The maximum number of blocks returned by a single `icrc3_get_blocks` call.
+pub const MAX_ICRC3_BLOCKS_PER_RESPONSE: u64 = 100;
+
+/// The generic value of an ICRC-3 block,
+/// see https://github.com/dfinity/ICRC-1/tree/main/standards/ICRC-3.
+#[derive(Debug, Clone, CandidType, Deserialize, PartialEq, Eq)]
+pub enum Icrc3Value {
+    Blob(ByteBuf),
+    Text(String),
+    Nat(Nat),
+    Int(Int),
+    Array(Vec<Icrc3Value>),
+    Map(Vec<(String, Icrc3Value)>),
+}
+
+#[derive(Debug, Clone, CandidType, Deserialize, PartialEq, Eq)]
+pub struct GetBlocksRequest {
+    pub start: Nat,
+    pub length: Nat,
+}
+
+pub type GetBlocksArgs = Vec<GetBlocksRequest>;
+
+#[derive(Debug, Clone, CandidType, Deserialize, PartialEq, Eq)]
+pub struct BlockWithId {
+    pub id: Nat,
+    pub block: Icrc3Value,
+}
+
+candid::define_function!(pub GetBlocksCallback : (GetBlocksArgs) -> (GetBlocksResult) query);
+
+#[derive(Debug, Clone, CandidType, Deserialize, PartialEq, Eq)]
+pub struct ArchivedBlocks {
+    pub args: GetBlocksArgs,
+    pub callback: GetBlocksCallback,
+}
+
+#[derive(Debug, Clone, CandidType, Deserialize, PartialEq, Eq)]
+pub struct GetBlocksResult {
+    pub log_length: Nat,
+    pub blocks: Vec<BlockWithId>,
+    /// Always empty, as the blocks are never archived.
+    pub archived_blocks: Vec<ArchivedBlocks>,
+}
+
+#[derive(Debug, Clone, CandidType, Deserialize, PartialEq, Eq)]
+pub struct Icrc3DataCertificate {
+    pub certificate: ByteBuf,
+    /// The CBOR encoded hash tree, with the `last_block_index` and `last_block_hash` labels.
+    pub hash_tree: ByteBuf,
+}
+
+#[derive(Debug, Clone, CandidType, Deserialize, PartialEq, Eq)]
+pub struct SupportedBlockType {
+    pub block_type: String,
+    pub url: String,
+}
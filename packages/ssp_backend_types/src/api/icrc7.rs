@@ -0,0 +1,66 @@
+use candid::{CandidType, Deserialize, Nat, Principal};
+use serde_bytes::ByteBuf;
+
+use super::Icrc3Value;
+
+/// The name of the collection, returned by `icrc7_name`.
+pub const ICRC7_COLLECTION_NAME: &str = "SSP certificates";
+/// The symbol of the collection, returned by `icrc7_symbol`.
+pub const ICRC7_COLLECTION_SYMBOL: &str = "SSP";
+/// The default number of tokens returned by `icrc7_tokens_of`.
+pub const DEFAULT_ICRC7_TAKE_VALUE: u64 = 100;
+/// The maximum number of tokens returned by `icrc7_tokens_of`.
+pub const MAX_ICRC7_TAKE_VALUE: u64 = 100;
+/// The maximum number of token ids or accounts accepted by the batch query methods.
+pub const MAX_ICRC7_QUERY_BATCH_SIZE: usize = 100;
+
+/// An ICRC-1 account, see https://github.com/dfinity/ICRC-1/tree/main/standards/ICRC-1.
+#[derive(Debug, Clone, CandidType, Deserialize, PartialEq, Eq)]
+pub struct Account {
+    pub owner: Principal,
+    pub subaccount: Option<ByteBuf>,
+}
+
+impl Account {
+    /// Whether the account is the default account of the owner,
+    /// i.e. it has no subaccount or the all-zero subaccount.
+    pub fn is_default(&self) -> bool {
+        self.subaccount
+            .as_ref()
+            .map_or(true, |subaccount| subaccount.iter().all(|b| *b == 0))
+    }
+}
+
+pub type TokenMetadata = Vec<(String, Icrc3Value)>;
+
+pub type CollectionMetadata = Vec<(String, Icrc3Value)>;
+
+/// A standard implemented by the canister, see https://github.com/dfinity/ICRC/blob/main/ICRCs/ICRC-10/ICRC-10.md.
+#[derive(Debug, Clone, CandidType, Deserialize, PartialEq, Eq)]
+pub struct SupportedStandard {
+    pub name: String,
+    pub url: String,
+}
+
+#[derive(Debug, Clone, CandidType, Deserialize, PartialEq, Eq)]
+pub struct TransferArg {
+    pub from_subaccount: Option<ByteBuf>,
+    pub to: Account,
+    pub token_id: Nat,
+    pub memo: Option<ByteBuf>,
+    pub created_at_time: Option<u64>,
+}
+
+#[derive(Debug, Clone, CandidType, Deserialize, PartialEq, Eq)]
+pub enum TransferError {
+    NonExistingTokenId,
+    InvalidRecipient,
+    Unauthorized,
+    TooOld,
+    CreatedInFuture { ledger_time: u64 },
+    Duplicate { duplicate_of: Nat },
+    GenericError { error_code: Nat, message: String },
+    GenericBatchError { error_code: Nat, message: String },
+}
+
+pub type TransferResult = Result<Nat, TransferError>;
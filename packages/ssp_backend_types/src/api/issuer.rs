@@ -0,0 +1,65 @@
+use candid::{CandidType, Deserialize, Principal};
+
+use super::{ApiError, ValidateRequest};
+
+pub const MAX_ISSUER_NAME_CHARS_COUNT: usize = 100;
+
+#[derive(Debug, Clone, CandidType, Deserialize, PartialEq, Eq)]
+pub struct Issuer {
+    pub id: String,
+    pub name: String,
+    pub admins: Vec<Principal>,
+    pub coaches: Vec<Principal>,
+    /// Present if the issuer has been verified by the controllers.
+    pub verified_at: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, CandidType, Deserialize, PartialEq, Eq)]
+pub struct CreateIssuerRequest {
+    pub name: String,
+}
+
+impl ValidateRequest for CreateIssuerRequest {
+    fn validate(&self) -> Result<(), ApiError> {
+        if self.name.trim().is_empty() {
+            return Err(ApiError::validation("name", "Name cannot be empty."));
+        } else if self.name.chars().count() > MAX_ISSUER_NAME_CHARS_COUNT {
+            return Err(ApiError::validation(
+                "name",
+                format!(
+                    "Name cannot be longer than {} characters.",
+                    MAX_ISSUER_NAME_CHARS_COUNT
+                ),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, CandidType, Deserialize, PartialEq, Eq)]
+pub struct CreateIssuerResponse {
+    pub id: String,
+}
+
+#[derive(Debug, Clone, CandidType, Deserialize, PartialEq, Eq)]
+pub struct SetIssuerVerifiedRequest {
+    pub id: String,
+    pub verified: bool,
+}
+
+#[derive(Debug, Clone, Copy, CandidType, Deserialize, PartialEq, Eq)]
+pub enum IssuerMemberRole {
+    #[serde(rename = "admin")]
+    Admin,
+    #[serde(rename = "coach")]
+    Coach,
+}
+
+#[derive(Debug, Clone, CandidType, Deserialize, PartialEq, Eq)]
+pub struct IssuerMemberRequest {
+    pub issuer_id: String,
+    pub user_db_id: String,
+    pub role: IssuerMemberRole,
+}
@@ -1,17 +1,35 @@
+mod achievement;
+mod audit_log;
 mod certificate;
 mod config;
+mod error;
+mod file;
+mod http;
+mod icrc3;
+mod icrc7;
+mod issuer;
 mod user;
 
+pub use achievement::*;
+pub use audit_log::*;
 pub use certificate::*;
 pub use config::*;
+pub use error::*;
+pub use file::*;
+pub use http::*;
+pub use icrc3::*;
+pub use icrc7::*;
+pub use issuer::*;
 pub use user::*;
 
 /// Implement this trait to validate the request.
 ///
+/// Errors must be [ApiError::Validation], with the name of the invalid field.
+///
 /// # Example:
 ///
 /// ```
-/// use ssp_backend_types::ValidateRequest;
+/// use ssp_backend_types::{ApiError, ValidateRequest};
 ///
 /// struct CreateCertificateRequest {
 ///     title: String,
@@ -19,20 +37,20 @@ pub use user::*;
 /// }
 ///
 /// impl ValidateRequest for CreateCertificateRequest {
-///     fn validate(&self) -> Result<(), String> {
+///     fn validate(&self) -> Result<(), ApiError> {
 ///         if self.title.is_empty() {
-///             return Err("Title cannot be empty.".to_string());
+///             return Err(ApiError::validation("title", "Title cannot be empty."));
 ///         }
 ///
 ///         if self.title.chars().count() > 100 {
-///             return Err(format!(
-///                 "Title cannot be longer than {} characters.",
-///                 100
+///             return Err(ApiError::validation(
+///                 "title",
+///                 format!("Title cannot be longer than {} characters.", 100),
 ///             ));
 ///         }
 ///
 ///         if self.content.is_empty() {
-///             return Err("Content cannot be empty.".to_string());
+///             return Err(ApiError::validation("content", "Content cannot be empty."));
 ///         }
 ///
 ///         Ok(())
@@ -41,5 +59,5 @@ pub use user::*;
 /// ```
 pub trait ValidateRequest {
     /// Validate the request, based on its fields.
-    fn validate(&self) -> Result<(), String>;
+    fn validate(&self) -> Result<(), ApiError>;
 }
@@ -1,8 +1,38 @@
-use candid::{CandidType, Deserialize};
+use candid::{CandidType, Deserialize, Principal};
 
 #[derive(Debug, Clone, CandidType, Deserialize, PartialEq, Eq)]
 pub struct User {
     pub sub: String,
     pub db_id: String,
     pub created_at: String,
+    pub roles: Vec<UserRole>,
+}
+
+#[derive(Debug, Clone, Copy, CandidType, Deserialize, PartialEq, Eq)]
+pub enum UserRole {
+    #[serde(rename = "athlete")]
+    Athlete,
+    #[serde(rename = "coach")]
+    Coach,
+    #[serde(rename = "club_admin")]
+    ClubAdmin,
+    #[serde(rename = "admin")]
+    Admin,
+}
+
+#[derive(Debug, Clone, CandidType, Deserialize, PartialEq, Eq)]
+pub struct ManagedUser {
+    /// The database id that the managed user will have once they sign up.
+    pub db_id: String,
+    pub guardians: Vec<Principal>,
+    pub created_at: String,
+    /// The principal of the user who claimed the certificates, once they signed up.
+    pub claimed_by: Option<Principal>,
+    pub claimed_at: Option<String>,
+}
+
+#[derive(Debug, Clone, CandidType, Deserialize, PartialEq, Eq)]
+pub struct ManagedUserGuardianRequest {
+    pub managed_user_db_id: String,
+    pub guardian_db_id: String,
 }
@@ -35,16 +35,42 @@ pub enum GetDelegationResponse {
     NoSuchDelegation,
 }
 
+/// The number of delegation entries stored by the canister,
+/// which are removed periodically once expired.
+#[derive(Clone, Debug, CandidType, Deserialize, PartialEq, Eq)]
+pub struct DelegationStats {
+    pub signatures_count: u64,
+    pub revoked_sessions_count: u64,
+    pub revoked_delegations_count: u64,
+}
+
 #[derive(CandidType, Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct Auth0JWK {
+    /// `RSA`, `EC` or `OKP`.
     pub kty: String,
+    #[serde(default)]
     pub r#use: String,
+    /// The modulus of `RSA` keys.
+    #[serde(default)]
     pub n: String,
+    /// The exponent of `RSA` keys.
+    #[serde(default)]
     pub e: String,
     pub kid: String,
+    /// Not set by all the providers, e.g. Google and Apple.
+    #[serde(default)]
     pub x5t: String,
+    #[serde(default)]
     pub x5c: Vec<String>,
+    /// `RS256`, `ES256` or `EdDSA`. If empty, the algorithm of the JWT header is used.
+    #[serde(default)]
     pub alg: String,
+    /// The curve of `EC` (`P-256`) and `OKP` (`Ed25519`) keys.
+    pub crv: Option<String>,
+    /// The x coordinate of `EC` keys, or the public key of `OKP` keys.
+    pub x: Option<String>,
+    /// The y coordinate of `EC` keys.
+    pub y: Option<String>,
 }
 
 #[derive(CandidType, Debug, PartialEq, Clone, Serialize, Deserialize)]